@@ -0,0 +1,33 @@
+use std::env;
+use std::process::Command;
+
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    Command::new(program)
+        .args(args)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn main() {
+    let git_sha = command_output("git", &["rev-parse", "--short=8", "HEAD"]).unwrap_or_else(|| "00000000".to_string());
+    let rustc = env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    let rustc_version = command_output(&rustc, &["--version"]).unwrap_or_else(|| "unknown".to_string());
+
+    println!("cargo:rustc-env=GIT_SHA={}", git_sha);
+    println!("cargo:rustc-env=RUSTC_VERSION={}", rustc_version);
+    // HEAD는 브랜치를 가리키는 ref라서 커밋해도 바뀌지 않음, 가리키는 브랜치 파일과 packed-refs도 지켜봄
+    for path in ["HEAD", "packed-refs"] {
+        rerun_if_git_path_changed(path);
+    }
+    if let Some(head_ref) = command_output("git", &["symbolic-ref", "-q", "HEAD"]) {
+        rerun_if_git_path_changed(&head_ref);
+    }
+}
+
+fn rerun_if_git_path_changed(path: &str) {
+    if let Some(path) = command_output("git", &["rev-parse", "--git-path", path]) {
+        println!("cargo:rerun-if-changed={}", path);
+    }
+}
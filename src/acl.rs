@@ -0,0 +1,467 @@
+use crate::command::{Command, CommandCategory};
+use crate::command_registry;
+use crate::errors::RedisError;
+use crate::eviction::parse_memory;
+use crate::namespace;
+use crate::protocol_constants::DEFAULT_USER;
+use crate::resp::RespValue;
+use crate::util::{glob_match, time_independent_eq};
+use std::collections::{BTreeMap, HashSet};
+
+const ALL_CATEGORIES: &str = "@all";
+
+// ACL 사용자, 비밀번호는 Redis처럼 SHA-256(소문자 hex)으로만 보관함
+#[derive(Debug, Clone)]
+pub struct AclUser {
+    name: String,
+    enabled: bool,
+    nopass: bool,
+    passwords: Vec<String>,
+    allowed_commands: HashSet<&'static str>,
+    // GETUSER/LIST에 보여 줄 명령 규칙, +@all/-@all이 나오면 그 앞의 규칙은 지움
+    command_rules: Vec<String>,
+    key_patterns: Vec<String>,
+    channel_patterns: Vec<String>,
+    // namespace:<prefix>로 정한 키 접두사와 그 접두사 아래의 할당량, 접두사가 없으면 할당량도 없음
+    namespace: Option<Namespace>,
+}
+
+// 사용자의 키 이름 앞에 몰래 붙는 접두사, 사용자는 접두사를 뺀 이름만 보고 씀
+// 할당량은 접두사 아래의 키 수와 메모리(Keyspace가 세는 값)로, 넘으면 메모리를 늘리는 쓰기만 막음
+#[derive(Debug, Clone, PartialEq)]
+pub struct Namespace {
+    pub prefix: String,
+    pub max_keys: Option<usize>,
+    pub max_memory: Option<usize>,
+}
+
+impl AclUser {
+    // SETUSER로 처음 만든 사용자는 꺼져 있고 아무 권한도 없음
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            enabled: false,
+            nopass: false,
+            passwords: Vec::new(),
+            allowed_commands: HashSet::new(),
+            command_rules: Vec::new(),
+            key_patterns: Vec::new(),
+            channel_patterns: Vec::new(),
+            namespace: None,
+        }
+    }
+
+    // 기본 사용자: 모든 명령, 키, 채널을 비밀번호 없이 쓸 수 있음
+    fn default_user() -> Self {
+        let mut user = Self::new(DEFAULT_USER);
+        for rule in ["on", "nopass", "allkeys", "allchannels", "allcommands"] {
+            user.apply_rule(rule).unwrap_or_else(|e| unreachable!("invalid default ACL rule {}: {}", rule, e));
+        }
+        user
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn namespace(&self) -> Option<&Namespace> {
+        self.namespace.as_ref()
+    }
+
+    // 켜져 있고 비밀번호가 없으면 AUTH 없이 이 사용자로 인증됨
+    pub fn is_nopass(&self) -> bool {
+        self.enabled && self.nopass
+    }
+
+    fn check_password(&self, password: &str) -> bool {
+        if self.nopass {
+            return true;
+        }
+        let hash = sha256_hex(password.as_bytes());
+        self.passwords.iter().any(|stored| time_independent_eq(stored.as_bytes(), hash.as_bytes()))
+    }
+
+    fn apply_rule(&mut self, rule: &str) -> Result<(), String> {
+        match rule.to_lowercase().as_str() {
+            "on" => self.enabled = true,
+            "off" => self.enabled = false,
+            "nopass" => {
+                self.nopass = true;
+                self.passwords.clear();
+            }
+            "resetpass" => {
+                self.nopass = false;
+                self.passwords.clear();
+            }
+            "allkeys" => self.key_patterns = vec!["*".to_string()],
+            "resetkeys" => self.key_patterns.clear(),
+            "allchannels" => self.channel_patterns = vec!["*".to_string()],
+            "resetchannels" => self.channel_patterns.clear(),
+            "resetnamespace" => self.namespace = None,
+            "allcommands" => self.apply_command_rule(true, ALL_CATEGORIES)?,
+            "nocommands" => self.apply_command_rule(false, ALL_CATEGORIES)?,
+            "reset" => {
+                for rule in ["resetpass", "resetkeys", "resetchannels", "resetnamespace", "off", "nocommands"] {
+                    self.apply_rule(rule)?;
+                }
+            }
+            _ => return self.apply_pattern_rule(rule),
+        }
+        Ok(())
+    }
+
+    // namespace:<prefix>, maxkeys:<n>, maxmemory:<bytes>, Redis ACL에는 없는 이 서버의 규칙
+    fn apply_namespace_rule(&mut self, name: &str, value: &str) -> Result<(), String> {
+        if name == "namespace" {
+            if value.is_empty() {
+                return Err("The namespace prefix can't be empty".into());
+            }
+            let namespace = self.namespace.get_or_insert_with(|| Namespace { prefix: String::new(), max_keys: None, max_memory: None });
+            namespace.prefix = value.to_string();
+            return Ok(());
+        }
+        let Some(namespace) = self.namespace.as_mut() else {
+            return Err(format!("Set a namespace before its {} quota", name));
+        };
+        match name {
+            "maxkeys" => namespace.max_keys = Some(value.parse().map_err(|_| format!("Invalid key count '{}'", value))?),
+            _ => namespace.max_memory = Some(parse_memory(value)? as usize),
+        }
+        Ok(())
+    }
+
+    fn apply_pattern_rule(&mut self, rule: &str) -> Result<(), String> {
+        if let Some((name, value)) = rule.split_once(':') {
+            let name = name.to_lowercase();
+            if matches!(name.as_str(), "namespace" | "maxkeys" | "maxmemory") {
+                return self.apply_namespace_rule(&name, value);
+            }
+        }
+        let mut chars = rule.chars();
+        let (Some(prefix), rest) = (chars.next(), chars.as_str()) else {
+            return Err("Syntax error".into());
+        };
+        match prefix {
+            '>' => {
+                let hash = sha256_hex(rest.as_bytes());
+                if !self.passwords.contains(&hash) {
+                    self.passwords.push(hash);
+                }
+                self.nopass = false;
+            }
+            '#' => {
+                if rest.len() != 64 || !rest.bytes().all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b)) {
+                    return Err("The password hash must be exactly 64 characters and contain only lowercase hexadecimal characters".into());
+                }
+                if !self.passwords.iter().any(|hash| hash == rest) {
+                    self.passwords.push(rest.to_string());
+                }
+                self.nopass = false;
+            }
+            '<' | '!' => {
+                let hash = if prefix == '<' { sha256_hex(rest.as_bytes()) } else { rest.to_string() };
+                let before = self.passwords.len();
+                self.passwords.retain(|stored| *stored != hash);
+                if self.passwords.len() == before {
+                    return Err("The password you are trying to remove from the user does not exist".into());
+                }
+            }
+            '~' => {
+                if !self.key_patterns.iter().any(|pattern| pattern == rest) {
+                    self.key_patterns.push(rest.to_string());
+                }
+            }
+            '&' => {
+                if !self.channel_patterns.iter().any(|pattern| pattern == rest) {
+                    self.channel_patterns.push(rest.to_string());
+                }
+            }
+            '+' => self.apply_command_rule(true, rest)?,
+            '-' => self.apply_command_rule(false, rest)?,
+            _ => return Err("Syntax error".into()),
+        }
+        Ok(())
+    }
+
+    // +get, -flushall, +@read, -@admin처럼 명령 하나 또는 카테고리 전체를 허용/금지함
+    fn apply_command_rule(&mut self, allow: bool, target: &str) -> Result<(), String> {
+        let target = target.to_lowercase();
+        let commands: Vec<&'static str> = match target.strip_prefix('@') {
+            Some("all") => command_registry::registry().specs().map(|spec| spec.name).collect(),
+            Some(category) => {
+                let category = CommandCategory::from_name(category).ok_or("Unknown command or category name in ACL")?;
+                command_registry::registry()
+                    .specs()
+                    .filter(|spec| spec.category() == category)
+                    .map(|spec| spec.name)
+                    .collect()
+            }
+            None => {
+                let handler = command_registry::lookup(&target.to_uppercase()).ok_or("Unknown command or category name in ACL")?;
+                vec![handler.spec().name]
+            }
+        };
+        for command in commands {
+            if allow {
+                self.allowed_commands.insert(command);
+            } else {
+                self.allowed_commands.remove(command);
+            }
+        }
+        if target == ALL_CATEGORIES {
+            self.command_rules.clear();
+        }
+        self.command_rules.push(format!("{}{}", if allow { '+' } else { '-' }, target));
+        Ok(())
+    }
+
+    fn flags(&self) -> Vec<&'static str> {
+        let mut flags = vec![if self.enabled { "on" } else { "off" }];
+        if self.nopass {
+            flags.push("nopass");
+        }
+        flags
+    }
+
+    fn commands_description(&self) -> String {
+        if self.command_rules.is_empty() {
+            format!("-{}", ALL_CATEGORIES)
+        } else {
+            self.command_rules.join(" ")
+        }
+    }
+
+    fn keys_description(&self) -> String {
+        self.key_patterns.iter().map(|pattern| format!("~{}", pattern)).collect::<Vec<_>>().join(" ")
+    }
+
+    fn channels_description(&self) -> String {
+        self.channel_patterns.iter().map(|pattern| format!("&{}", pattern)).collect::<Vec<_>>().join(" ")
+    }
+
+    // 예: "namespace:team-a: maxkeys:1000 maxmemory:1048576", 접두사가 없으면 빈 문자열
+    fn namespace_description(&self) -> String {
+        let Some(namespace) = &self.namespace else {
+            return String::new();
+        };
+        let mut parts = vec![format!("namespace:{}", namespace.prefix)];
+        parts.extend(namespace.max_keys.map(|max_keys| format!("maxkeys:{}", max_keys)));
+        parts.extend(namespace.max_memory.map(|max_memory| format!("maxmemory:{}", max_memory)));
+        parts.join(" ")
+    }
+
+    // ACL LIST의 한 줄, 예: "user default on nopass ~* &* +@all"
+    pub fn describe(&self) -> String {
+        let mut parts = vec!["user".to_string(), self.name.clone()];
+        parts.extend(self.flags().into_iter().map(String::from));
+        parts.extend(self.passwords.iter().map(|hash| format!("#{}", hash)));
+        if !self.key_patterns.is_empty() {
+            parts.push(self.keys_description());
+        }
+        if self.channel_patterns.is_empty() {
+            parts.push("resetchannels".to_string());
+        } else {
+            parts.push(self.channels_description());
+        }
+        parts.push(self.commands_description());
+        if self.namespace.is_some() {
+            parts.push(self.namespace_description());
+        }
+        parts.join(" ")
+    }
+
+    pub fn to_resp(&self) -> RespValue {
+        RespValue::field_map(vec![
+            ("flags", RespValue::Array(self.flags().into_iter().map(RespValue::bulk).collect())),
+            ("passwords", RespValue::Array(self.passwords.iter().map(|hash| RespValue::bulk(hash.as_str())).collect())),
+            ("commands", RespValue::bulk(self.commands_description())),
+            ("keys", RespValue::bulk(self.keys_description())),
+            ("channels", RespValue::bulk(self.channels_description())),
+            ("selectors", RespValue::Array(Vec::new())),
+            ("namespace", RespValue::bulk(self.namespace_description())),
+        ])
+    }
+
+    // 명령 이름, 접근하는 키, 발행/구독하는 채널을 모두 허용해야 실행할 수 있음
+    pub fn check(&self, command: &Command) -> Result<(), RedisError> {
+        if !self.allowed_commands.contains(command.name()) {
+            return Err(RedisError::NoPerm(format!(
+                "User {} has no permissions to run the '{}' command",
+                self.name,
+                command.name().to_lowercase()
+            )));
+        }
+        let key_allowed = |key: &Vec<u8>| self.key_patterns.iter().any(|pattern| glob_match(pattern.as_bytes(), key));
+        if !command.keys().into_iter().all(key_allowed) {
+            return Err(RedisError::NoPerm("No permissions to access a key".into()));
+        }
+        // PSUBSCRIBE의 패턴은 허용된 패턴과 글자 그대로 같아야 함
+        let all_channels = self.channel_patterns.iter().any(|pattern| pattern == "*");
+        let channels_allowed = match command {
+            Command::PSUBSCRIBE(patterns) => {
+                all_channels || patterns.iter().all(|pattern| self.channel_patterns.contains(pattern))
+            }
+            _ => command
+                .channels()
+                .into_iter()
+                .all(|channel| self.channel_patterns.iter().any(|pattern| glob_match(pattern.as_bytes(), channel.as_bytes()))),
+        };
+        if !channels_allowed {
+            return Err(RedisError::NoPerm("No permissions to access a channel".into()));
+        }
+        Ok(())
+    }
+
+    // 네임스페이스가 있으면 명령의 키를 그 접두사 아래로 옮김, 키 패턴은 옮기기 전의 이름으로 check에서 검사함
+    pub fn confine(&self, command: &mut Command) -> Result<(), RedisError> {
+        match &self.namespace {
+            Some(namespace) => namespace::confine(command, namespace.prefix.as_bytes()),
+            None => Ok(()),
+        }
+    }
+}
+
+// 사용자 이름 -> 사용자, default 사용자는 항상 있고 지울 수 없음
+#[derive(Debug)]
+pub struct Acl {
+    users: BTreeMap<String, AclUser>,
+    // 마지막으로 default 사용자에 반영한 requirepass, 바뀌었을 때만 다시 반영함
+    applied_requirepass: String,
+}
+
+impl Acl {
+    pub fn new() -> Self {
+        let mut users = BTreeMap::new();
+        users.insert(DEFAULT_USER.to_string(), AclUser::default_user());
+        Self {
+            users,
+            applied_requirepass: String::new(),
+        }
+    }
+
+    pub fn user(&self, name: &str) -> Option<&AclUser> {
+        self.users.get(name)
+    }
+
+    pub fn default_user(&self) -> &AclUser {
+        self.users.get(DEFAULT_USER).unwrap_or_else(|| unreachable!("the default user always exists"))
+    }
+
+    // Redis처럼 requirepass는 default 사용자의 비밀번호를 통째로 바꾸고, 빈 값이면 nopass로 되돌림
+    pub fn apply_requirepass(&mut self, requirepass: &str) {
+        if requirepass == self.applied_requirepass {
+            return;
+        }
+        self.applied_requirepass = requirepass.to_string();
+        let rules: &[&str] = if requirepass.is_empty() {
+            &["nopass"]
+        } else {
+            &["resetpass"]
+        };
+        if let Some(user) = self.users.get_mut(DEFAULT_USER) {
+            for rule in rules {
+                let _ = user.apply_rule(rule);
+            }
+            if !requirepass.is_empty() {
+                let _ = user.apply_rule(&format!(">{}", requirepass));
+            }
+        }
+    }
+
+    pub fn authenticate(&self, username: &str, password: &str) -> bool {
+        self.users
+            .get(username)
+            .is_some_and(|user| user.enabled && user.check_password(password))
+    }
+
+    // 규칙을 모두 적용해 본 뒤에 바꾸므로 하나라도 틀리면 사용자는 그대로임
+    pub fn set_user(&mut self, name: &str, rules: &[String]) -> Result<(), String> {
+        if name.chars().any(|c| c.is_whitespace() || c == '\0') {
+            return Err("Usernames can't contain spaces or null characters".into());
+        }
+        let mut user = self.users.get(name).cloned().unwrap_or_else(|| AclUser::new(name));
+        for rule in rules {
+            user.apply_rule(rule)
+                .map_err(|e| format!("Error in ACL SETUSER modifier '{}': {}", rule, e))?;
+        }
+        self.users.insert(name.to_string(), user);
+        Ok(())
+    }
+
+    pub fn delete_user(&mut self, name: &str) -> bool {
+        self.users.remove(name).is_some()
+    }
+
+    pub fn users(&self) -> impl Iterator<Item = &AclUser> {
+        self.users.values()
+    }
+
+    // 키스페이스가 키 수와 메모리를 따로 세야 하는 접두사
+    pub fn namespace_prefixes(&self) -> Vec<Vec<u8>> {
+        self.users
+            .values()
+            .filter_map(AclUser::namespace)
+            .map(|namespace| namespace.prefix.as_bytes().to_vec())
+            .collect()
+    }
+}
+
+const SHA256_K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+pub fn sha256_hex(data: &[u8]) -> String {
+    let mut state: [u32; 8] = [
+        0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+    ];
+
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&((data.len() as u64) * 8).to_be_bytes());
+
+    for block in message.chunks(64) {
+        let mut w = [0u32; 64];
+        for (i, word) in block.chunks(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..64 {
+            let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+            let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+            w[i] = w[i - 16].wrapping_add(s0).wrapping_add(w[i - 7]).wrapping_add(s1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = state;
+        for (k, word) in SHA256_K.iter().zip(w.iter()) {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let ch = (e & f) ^ (!e & g);
+            let temp1 = h.wrapping_add(s1).wrapping_add(ch).wrapping_add(*k).wrapping_add(*word);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let temp2 = s0.wrapping_add(maj);
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(temp1);
+            d = c;
+            c = b;
+            b = a;
+            a = temp1.wrapping_add(temp2);
+        }
+
+        for (value, add) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+            *value = value.wrapping_add(add);
+        }
+    }
+
+    state.iter().map(|value| format!("{:08x}", value)).collect()
+}
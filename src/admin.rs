@@ -0,0 +1,81 @@
+use crate::event_publisher::EventPublisher;
+use crate::logging::{self, log_warning};
+use crate::util::json_string;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+pub const ADMIN_PATH_CONFIG: &str = "/config";
+pub const ADMIN_PATH_INFO: &str = "/info";
+pub const ADMIN_PATH_CLIENTS: &str = "/clients";
+pub const ADMIN_PATH_REPLICAS: &str = "/replicas";
+pub const ADMIN_PATH_SLOTS: &str = "/slots";
+pub const ADMIN_PATH_METRICS: &str = "/metrics";
+
+pub struct AdminResponse {
+    pub status: u16,
+    pub body: String,
+}
+
+impl AdminResponse {
+    pub fn ok(body: String) -> Self {
+        Self { status: 200, body }
+    }
+
+    pub fn error(status: u16, message: &str) -> Self {
+        Self {
+            status,
+            body: format!("{{\"error\":{}}}", json_string(message)),
+        }
+    }
+
+    fn reason(&self) -> &'static str {
+        match self.status {
+            200 => "OK",
+            400 => "Bad Request",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            _ => "Internal Server Error",
+        }
+    }
+}
+
+// 관리용 HTTP 리스너 (읽기 전용 JSON 뷰)
+pub async fn serve_admin(listener: TcpListener, publisher: EventPublisher) {
+    while let Ok((stream, addr)) = listener.accept().await {
+        let publisher = publisher.clone();
+        logging::spawn(async move {
+            if let Err(e) = handle_admin_connection(stream, publisher).await {
+                log_warning!("Admin request from {} failed: {}", addr, e);
+            }
+        });
+    }
+}
+
+async fn handle_admin_connection(mut stream: TcpStream, publisher: EventPublisher) -> Result<(), String> {
+    let mut buffer = [0u8; 1024];
+    let n = stream.read(&mut buffer).await.map_err(|e| format!("Failed to read request: {}", e))?;
+    let request = String::from_utf8_lossy(&buffer[..n]);
+    let request_line = request.lines().next().unwrap_or_default();
+    let mut parts = request_line.split_whitespace();
+
+    let response = match (parts.next(), parts.next()) {
+        (Some("GET"), Some(target)) => {
+            let path = target.split('?').next().unwrap_or(target).trim_end_matches('/');
+            publisher.publish_admin_request(path.to_string()).await?
+        }
+        (Some(_), Some(_)) => AdminResponse::error(405, "only GET is supported"),
+        _ => AdminResponse::error(400, "malformed request line"),
+    };
+
+    let http_response = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        response.status,
+        response.reason(),
+        response.body.len(),
+        response.body
+    );
+    stream
+        .write_all(http_response.as_bytes())
+        .await
+        .map_err(|e| format!("Failed to write response: {}", e))
+}
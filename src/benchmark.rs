@@ -0,0 +1,235 @@
+use crate::client::Client;
+use crate::random::Random;
+use crate::resp::RespValue;
+use crate::util::{construct_redis_command, format_host_port};
+use std::io;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::time::{Duration, Instant};
+
+// redis-benchmark와 같은 기본값
+const DEFAULT_CLIENTS: usize = 50;
+const DEFAULT_REQUESTS: u64 = 100_000;
+const DEFAULT_DATA_SIZE: usize = 3;
+const TESTS: [&str; 3] = ["set", "get", "incr"];
+const LATENCY_PERCENTILES: [f64; 3] = [50.0, 95.0, 99.0];
+
+pub const USAGE: &str = "Usage: redis-benchmark [-h <host>] [-p <port>] [-c <clients>] [-n <requests>] [-d <size>] [-P <numreq>] [-r <keyspacelen>] [-t <tests>] [-q]
+
+ -h <hostname>      Server hostname (default 127.0.0.1)
+ -p <port>          Server port (default 6379)
+ -c <clients>       Number of parallel connections (default 50)
+ -n <requests>      Total number of requests per test (default 100000)
+ -d <size>          Data size of SET values in bytes (default 3)
+ -P <numreq>        Pipeline <numreq> requests (default 1, no pipeline)
+ -r <keyspacelen>   Use random keys in the range 0..keyspacelen instead of a single key
+ -t <tests>         Comma separated list of tests to run: set,get,incr (default all)
+ -q                 Quiet. Just show the throughput and p50 latency
+";
+
+#[derive(Debug, Clone)]
+pub struct BenchmarkOptions {
+    pub host: String,
+    pub port: u16,
+    pub clients: usize,
+    pub requests: u64,
+    pub data_size: usize,
+    pub pipeline: usize,
+    // 0이면 모든 요청이 같은 키를 씀
+    pub keyspace_len: usize,
+    pub tests: Vec<String>,
+    pub quiet: bool,
+}
+
+impl Default for BenchmarkOptions {
+    fn default() -> Self {
+        Self {
+            host: "127.0.0.1".into(),
+            port: 6379,
+            clients: DEFAULT_CLIENTS,
+            requests: DEFAULT_REQUESTS,
+            data_size: DEFAULT_DATA_SIZE,
+            pipeline: 1,
+            keyspace_len: 0,
+            tests: TESTS.iter().map(|test| test.to_string()).collect(),
+            quiet: false,
+        }
+    }
+}
+
+impl BenchmarkOptions {
+    // 프로그램 이름을 뺀 인자, 옵션 이름은 redis-benchmark를 따름
+    pub fn parse(args: &[String]) -> Result<Self, String> {
+        let mut options = Self::default();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            if arg == "-q" {
+                options.quiet = true;
+                continue;
+            }
+            let mut value = || args.next().ok_or_else(|| format!("{} option requires an argument", arg));
+            let number = |value: &String| value.parse::<u64>().map_err(|_| format!("Invalid value for {}: {}", arg, value));
+            match arg.as_str() {
+                "-h" => options.host = value()?.clone(),
+                "-p" => options.port = value()?.parse().map_err(|_| format!("Invalid port for {}", arg))?,
+                "-c" => options.clients = number(value()?)? as usize,
+                "-n" => options.requests = number(value()?)?,
+                "-d" => options.data_size = number(value()?)? as usize,
+                "-P" => options.pipeline = number(value()?)? as usize,
+                "-r" => options.keyspace_len = number(value()?)? as usize,
+                "-t" => {
+                    let tests: Vec<String> = value()?.to_lowercase().split(',').map(|test| test.trim().to_string()).collect();
+                    if let Some(unknown) = tests.iter().find(|test| !TESTS.contains(&test.as_str())) {
+                        return Err(format!("Unknown test '{}', supported tests are {}", unknown, TESTS.join(",")));
+                    }
+                    options.tests = tests;
+                }
+                _ => return Err(format!("Unrecognized option {}", arg)),
+            }
+        }
+        if options.clients == 0 || options.pipeline == 0 {
+            return Err("-c and -P must be at least 1".into());
+        }
+        Ok(options)
+    }
+}
+
+pub struct BenchmarkReport {
+    pub test: String,
+    pub requests: u64,
+    pub errors: u64,
+    pub elapsed: Duration,
+    // 요청마다 파이프라인 묶음을 보낸 때부터 그 응답을 받은 때까지, 오름차순
+    latencies_us: Vec<u64>,
+}
+
+impl BenchmarkReport {
+    pub fn requests_per_sec(&self) -> f64 {
+        self.requests as f64 / self.elapsed.as_secs_f64().max(f64::EPSILON)
+    }
+
+    pub fn percentile_ms(&self, percentile: f64) -> f64 {
+        if self.latencies_us.is_empty() {
+            return 0.0;
+        }
+        let rank = ((percentile / 100.0) * self.latencies_us.len() as f64).ceil() as usize;
+        self.latencies_us[rank.clamp(1, self.latencies_us.len()) - 1] as f64 / 1000.0
+    }
+
+    pub fn average_ms(&self) -> f64 {
+        let total: u64 = self.latencies_us.iter().sum();
+        total as f64 / self.latencies_us.len().max(1) as f64 / 1000.0
+    }
+
+    pub fn format(&self, options: &BenchmarkOptions) -> String {
+        let name = self.test.to_uppercase();
+        if options.quiet {
+            let errors = if self.errors > 0 { format!(", {} error replies", self.errors) } else { String::new() };
+            return format!("{}: {:.2} requests per second, p50={:.3} msec{}", name, self.requests_per_sec(), self.percentile_ms(50.0), errors);
+        }
+        let mut out = format!("====== {} ======\n", name);
+        out.push_str(&format!("  {} requests completed in {:.2} seconds\n", self.requests, self.elapsed.as_secs_f64()));
+        out.push_str(&format!("  {} parallel clients\n", options.clients));
+        out.push_str(&format!("  {} bytes payload\n", options.data_size));
+        out.push_str(&format!("  pipeline {}\n", options.pipeline));
+        if self.errors > 0 {
+            out.push_str(&format!("  {} error replies\n", self.errors));
+        }
+        out.push_str(&format!("\nthroughput summary: {:.2} requests per second\n", self.requests_per_sec()));
+        out.push_str("latency summary (msec):\n");
+        out.push_str(&format!("{:>10}{:>10}", "avg", "min"));
+        for percentile in LATENCY_PERCENTILES {
+            out.push_str(&format!("{:>10}", format!("p{}", percentile)));
+        }
+        out.push_str(&format!("{:>10}\n", "max"));
+        out.push_str(&format!("{:>10.3}{:>10.3}", self.average_ms(), self.percentile_ms(0.0)));
+        for percentile in LATENCY_PERCENTILES {
+            out.push_str(&format!("{:>10.3}", self.percentile_ms(percentile)));
+        }
+        out.push_str(&format!("{:>10.3}\n", self.percentile_ms(100.0)));
+        out
+    }
+}
+
+// 테스트를 차례로 돌림, 테스트마다 연결을 새로 맺어 앞 테스트의 밀린 응답이 섞이지 않게 함
+pub async fn run(options: &BenchmarkOptions) -> io::Result<Vec<BenchmarkReport>> {
+    let mut reports = Vec::new();
+    for test in &options.tests {
+        reports.push(run_test(options, test).await?);
+    }
+    Ok(reports)
+}
+
+async fn run_test(options: &BenchmarkOptions, test: &str) -> io::Result<BenchmarkReport> {
+    let addr = format_host_port(&options.host, options.port);
+    let mut clients = Vec::with_capacity(options.clients);
+    for _ in 0..options.clients {
+        clients.push(Client::connect(addr.as_str()).await?);
+    }
+    // 연결마다 남은 요청에서 파이프라인 하나만큼씩 가져가므로 느린 연결이 있어도 전체 요청 수는 정확함
+    let remaining = Arc::new(AtomicU64::new(options.requests));
+    let value = "x".repeat(options.data_size);
+    let started_at = Instant::now();
+    let tasks: Vec<_> = clients
+        .into_iter()
+        .map(|client| tokio::spawn(drive_client(client, test.to_string(), value.clone(), options.clone(), remaining.clone())))
+        .collect();
+    let mut latencies_us = Vec::with_capacity(options.requests as usize);
+    let mut errors = 0;
+    for task in tasks {
+        let (client_latencies, client_errors) = task.await.map_err(io::Error::other)??;
+        latencies_us.extend(client_latencies);
+        errors += client_errors;
+    }
+    let elapsed = started_at.elapsed();
+    latencies_us.sort_unstable();
+    Ok(BenchmarkReport {
+        test: test.to_string(),
+        requests: latencies_us.len() as u64,
+        errors,
+        elapsed,
+        latencies_us,
+    })
+}
+
+async fn drive_client(
+    mut client: Client,
+    test: String,
+    value: String,
+    options: BenchmarkOptions,
+    remaining: Arc<AtomicU64>,
+) -> io::Result<(Vec<u64>, u64)> {
+    let pipeline = options.pipeline as u64;
+    let mut latencies_us = Vec::new();
+    let mut errors = 0;
+    let random = Random::new();
+    loop {
+        let batch = remaining
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |left| (left > 0).then(|| left - left.min(pipeline)))
+            .map_or(0, |left| left.min(pipeline));
+        if batch == 0 {
+            break;
+        }
+        let mut request = Vec::new();
+        for _ in 0..batch {
+            let key = match options.keyspace_len {
+                0 => format!("{}:__rand_int__", if test == "incr" { "counter" } else { "key" }),
+                len => format!("{}:{:012}", if test == "incr" { "counter" } else { "key" }, random.below(len)),
+            };
+            request.extend(match test.as_str() {
+                "set" => construct_redis_command(&["SET", key.as_str(), value.as_str()]),
+                "get" => construct_redis_command(&["GET", key.as_str()]),
+                _ => construct_redis_command(&["INCR", key.as_str()]),
+            });
+        }
+        let sent_at = Instant::now();
+        client.send_raw(&request).await?;
+        for _ in 0..batch {
+            if matches!(client.read_reply().await?, RespValue::Error(_)) {
+                errors += 1;
+            }
+            latencies_us.push(sent_at.elapsed().as_micros() as u64);
+        }
+    }
+    Ok((latencies_us, errors))
+}
@@ -0,0 +1,28 @@
+use redis_starter_rust::benchmark::{self, BenchmarkOptions, USAGE};
+
+#[tokio::main]
+async fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.iter().any(|arg| arg == "--help") {
+        print!("{}", USAGE);
+        return;
+    }
+    let options = match BenchmarkOptions::parse(&args) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            std::process::exit(1);
+        }
+    };
+    match benchmark::run(&options).await {
+        Ok(reports) => {
+            for report in reports {
+                println!("{}", report.format(&options));
+            }
+        }
+        Err(e) => {
+            eprintln!("Could not connect to {}:{}: {}", options.host, options.port, e);
+            std::process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,18 @@
+use redis_starter_rust::cli::{self, CliOptions, USAGE};
+
+#[tokio::main]
+async fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().is_some_and(|arg| arg == "--help") {
+        print!("{}", USAGE);
+        return;
+    }
+    let options = match CliOptions::parse(&args) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            std::process::exit(1);
+        }
+    };
+    std::process::exit(cli::run(&options).await);
+}
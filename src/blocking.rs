@@ -0,0 +1,90 @@
+use crate::command::Command;
+use crate::trace::TraceContext;
+use std::collections::{HashMap, VecDeque};
+
+// 데이터가 없어서 대기 중인 블로킹 명령, 키가 준비되면 같은 명령을 다시 실행해서 응답함
+pub struct BlockedClient {
+    pub command: Command,
+    pub deadline_ms: Option<u64>,
+    pub trace: Option<TraceContext>,
+}
+
+pub struct BlockingRegistry {
+    clients: HashMap<u64, BlockedClient>,
+    // 키 -> 대기 순서대로의 클라이언트, 먼저 막힌 클라이언트가 먼저 깨어남
+    waiters: HashMap<Vec<u8>, VecDeque<u64>>,
+}
+
+impl BlockingRegistry {
+    pub fn new() -> Self {
+        Self {
+            clients: HashMap::new(),
+            waiters: HashMap::new(),
+        }
+    }
+
+    pub fn block(&mut self, client_id: u64, blocked: BlockedClient) {
+        for key in blocked.command.blocking_keys() {
+            let waiters = self.waiters.entry(key.clone()).or_default();
+            if !waiters.contains(&client_id) {
+                waiters.push_back(client_id);
+            }
+        }
+        self.clients.insert(client_id, blocked);
+    }
+
+    pub fn unblock(&mut self, client_id: u64) -> Option<BlockedClient> {
+        let blocked = self.clients.remove(&client_id)?;
+        for key in blocked.command.blocking_keys() {
+            if let Some(waiters) = self.waiters.get_mut(key) {
+                waiters.retain(|waiter| *waiter != client_id);
+                if waiters.is_empty() {
+                    self.waiters.remove(key);
+                }
+            }
+        }
+        Some(blocked)
+    }
+
+    // 키에서 기다리는 클라이언트 중 조건에 맞는 가장 먼저 막힌 클라이언트
+    pub fn first_waiter(&self, key: &[u8], accepts: impl Fn(&BlockedClient) -> bool) -> Option<u64> {
+        self.waiters
+            .get(key)?
+            .iter()
+            .copied()
+            .find(|waiter| self.clients.get(waiter).is_some_and(&accepts))
+    }
+
+    pub fn is_watched(&self, key: &[u8]) -> bool {
+        self.waiters.contains_key(key)
+    }
+
+    pub fn watched_keys(&self) -> Vec<Vec<u8>> {
+        self.waiters.keys().cloned().collect()
+    }
+
+    pub fn client_ids(&self) -> Vec<u64> {
+        self.clients.keys().copied().collect()
+    }
+
+    pub fn timed_out(&self, now_ms: u64) -> Vec<u64> {
+        self.clients
+            .iter()
+            .filter(|(_, blocked)| blocked.deadline_ms.is_some_and(|deadline| deadline <= now_ms))
+            .map(|(client_id, _)| *client_id)
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.clients.len()
+    }
+}
+
+// WAIT으로 대기 중인 클라이언트, 레플리카마다 WAIT 시점까지 보낸 offset을 ACK가 넘어야 함
+pub struct ReplicaWait {
+    pub client_id: u64,
+    pub numreplicas: usize,
+    // (레플리카 번호, 넘어야 할 offset)
+    pub targets: Vec<(u64, i64)>,
+    pub deadline_ms: Option<u64>,
+}
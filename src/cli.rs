@@ -0,0 +1,264 @@
+use crate::client::Client;
+use crate::command_parser::CommandParser;
+use crate::resp::RespValue;
+use crate::util::format_host_port;
+use std::io::{IsTerminal, Write};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, BufReader};
+
+// 응답을 보낸 뒤에도 연결에서 메시지가 계속 오는 명령, 연결이 끊길 때까지 읽어서 보여 줌
+const STREAMING_COMMANDS: [&str; 4] = ["subscribe", "psubscribe", "ssubscribe", "monitor"];
+
+pub const USAGE: &str = "Usage: redis-cli [OPTIONS] [cmd [arg [arg ...]]]
+
+ -h <hostname>      Server hostname (default 127.0.0.1)
+ -p <port>          Server port (default 6379)
+ -s <socket>        Server socket (overrides hostname and port)
+ -3                 Start the session in RESP3 protocol mode (sends HELLO 3)
+ --pipe             Transfer raw Redis protocol from stdin to server
+ --help             Output this help and exit
+
+Without a command, starts an interactive session. Type quit or exit to leave.
+";
+
+#[derive(Debug, Clone)]
+pub struct CliOptions {
+    pub host: String,
+    pub port: u16,
+    pub socket: Option<String>,
+    pub resp3: bool,
+    pub pipe: bool,
+    // 비어 있으면 대화형으로 실행함
+    pub command: Vec<String>,
+}
+
+impl Default for CliOptions {
+    fn default() -> Self {
+        Self {
+            host: "127.0.0.1".into(),
+            port: 6379,
+            socket: None,
+            resp3: false,
+            pipe: false,
+            command: Vec::new(),
+        }
+    }
+}
+
+impl CliOptions {
+    // 프로그램 이름을 뺀 인자, 옵션이 아닌 첫 인자부터는 보낼 명령
+    pub fn parse(args: &[String]) -> Result<Self, String> {
+        let mut options = Self::default();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or_else(|| format!("{} option requires an argument", arg));
+            match arg.as_str() {
+                "-h" => options.host = value()?.clone(),
+                "-p" => options.port = value()?.parse().map_err(|_| format!("Invalid port for {}", arg))?,
+                "-s" => options.socket = Some(value()?.clone()),
+                "-3" => options.resp3 = true,
+                "--pipe" => options.pipe = true,
+                _ if arg.starts_with('-') => return Err(format!("Unrecognized option {}", arg)),
+                _ => {
+                    options.command = std::iter::once(arg).chain(args).cloned().collect();
+                    break;
+                }
+            }
+        }
+        Ok(options)
+    }
+
+    fn target(&self) -> String {
+        match &self.socket {
+            Some(socket) => socket.clone(),
+            None => format_host_port(&self.host, self.port),
+        }
+    }
+
+    async fn connect(&self) -> Result<Client, String> {
+        let connected = match &self.socket {
+            Some(socket) => Client::connect_unix(socket).await,
+            None => Client::connect(self.target()).await,
+        };
+        let mut client = connected.map_err(|e| format!("Could not connect to Redis at {}: {}", self.target(), e))?;
+        if self.resp3 {
+            if let RespValue::Error(message) = client.command(&["HELLO", "3"]).await.map_err(|e| e.to_string())? {
+                return Err(format!("Failed to switch to RESP3: {}", message));
+            }
+        }
+        Ok(client)
+    }
+}
+
+// 종료 코드를 돌려줌, 연결하지 못했거나 --pipe에서 에러 응답이 있었으면 1
+pub async fn run(options: &CliOptions) -> i32 {
+    let result = if options.pipe {
+        run_pipe(options).await
+    } else if !options.command.is_empty() {
+        run_command(options).await
+    } else {
+        run_repl(options).await
+    };
+    match result {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("{}", e);
+            1
+        }
+    }
+}
+
+async fn run_command(options: &CliOptions) -> Result<i32, String> {
+    let mut client = options.connect().await?;
+    execute(&mut client, &options.command).await.map_err(|e| e.to_string())?;
+    Ok(0)
+}
+
+async fn run_pipe(options: &CliOptions) -> Result<i32, String> {
+    let mut data = Vec::new();
+    tokio::io::stdin().read_to_end(&mut data).await.map_err(|e| format!("Failed to read stdin: {}", e))?;
+    let mut client = options.connect().await?;
+    let summary = client.pipe(&data).await.map_err(|e| format!("Error writing to the server: {}", e))?;
+    println!("errors: {}, replies: {}", summary.errors, summary.replies);
+    Ok(if summary.errors > 0 { 1 } else { 0 })
+}
+
+// 입력은 터미널의 줄 편집을 그대로 씀, 터미널이 아니면 프롬프트 없이 한 줄에 명령 하나씩 실행함
+// 연결이 끊기면 다음 명령 때 다시 연결함
+async fn run_repl(options: &CliOptions) -> Result<i32, String> {
+    let interactive = std::io::stdin().is_terminal();
+    let mut client = match options.connect().await {
+        Ok(client) => Some(client),
+        Err(e) => {
+            eprintln!("{}", e);
+            None
+        }
+    };
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    loop {
+        if interactive {
+            let prompt = if client.is_some() { options.target() } else { "not connected".into() };
+            print!("{}> ", prompt);
+            let _ = std::io::stdout().flush();
+        }
+        let Ok(Some(line)) = lines.next_line().await else {
+            break;
+        };
+        let args = match CommandParser::frame_args(line.trim().as_bytes()) {
+            Ok(args) => args.into_iter().map(|arg| String::from_utf8_lossy(&arg).into_owned()).collect::<Vec<_>>(),
+            Err(_) => {
+                println!("Invalid argument(s)");
+                continue;
+            }
+        };
+        let Some(name) = args.first() else {
+            continue;
+        };
+        if name.eq_ignore_ascii_case("quit") || name.eq_ignore_ascii_case("exit") {
+            break;
+        }
+        if client.is_none() {
+            match options.connect().await {
+                Ok(connected) => client = Some(connected),
+                Err(e) => {
+                    println!("{}", e);
+                    continue;
+                }
+            }
+        }
+        if let Some(connected) = client.as_mut() {
+            if let Err(e) = execute(connected, &args).await {
+                println!("Error: {}", e);
+                client = None;
+            }
+        }
+    }
+    Ok(0)
+}
+
+// RESP3 연결에서는 응답 앞에 무효화 같은 push 메시지가 먼저 올 수 있으므로 보여 주고 응답을 마저 기다림
+async fn execute(client: &mut Client, args: &[String]) -> std::io::Result<()> {
+    client.send_command(args).await?;
+    loop {
+        let reply = client.read_reply().await?;
+        print!("{}", format_reply(&reply));
+        if !matches!(reply, RespValue::Push(_)) {
+            break;
+        }
+    }
+    if STREAMING_COMMANDS.contains(&args[0].to_lowercase().as_str()) {
+        println!("Reading messages... (press Ctrl-C to quit)");
+        loop {
+            print!("{}", format_reply(&client.read_reply().await?));
+        }
+    }
+    Ok(())
+}
+
+// redis-cli가 터미널에 보여 주는 형식, 중첩된 배열은 번호 너비만큼 들여씀
+pub fn format_reply(reply: &RespValue) -> String {
+    let mut out = String::new();
+    format_into(reply, "", &mut out);
+    out
+}
+
+fn format_into(reply: &RespValue, indent: &str, out: &mut String) {
+    let (items, separator, empty): (Vec<&RespValue>, char, &str) = match reply {
+        RespValue::SimpleString(value) => return out.push_str(&format!("{}\n", value)),
+        RespValue::Error(message) => return out.push_str(&format!("(error) {}\n", message)),
+        RespValue::Integer(value) => return out.push_str(&format!("(integer) {}\n", value)),
+        RespValue::BulkString(value) => return out.push_str(&format!("{}\n", quote(value))),
+        RespValue::NullBulk | RespValue::NullArray => return out.push_str("(nil)\n"),
+        RespValue::Double(value) => return out.push_str(&format!("(double) {}\n", value)),
+        RespValue::Boolean(value) => return out.push_str(&format!("({})\n", value)),
+        RespValue::BigNumber(value) => return out.push_str(&format!("(big number) {}\n", value)),
+        RespValue::Array(items) => (items.iter().collect(), ')', "(empty array)"),
+        RespValue::Push(items) => (items.iter().collect(), ')', "(empty push)"),
+        RespValue::Set(items) => (items.iter().collect(), '~', "(empty set)"),
+        RespValue::Map(entries) => (entries.iter().map(|(key, _)| key).collect(), '#', "(empty hash)"),
+    };
+    if items.is_empty() {
+        out.push_str(&format!("{}\n", empty));
+        return;
+    }
+    let width = items.len().to_string().len();
+    for (index, item) in items.iter().enumerate() {
+        let prefix = format!("{:>width$}{} ", index + 1, separator, width = width);
+        if index > 0 {
+            out.push_str(indent);
+        }
+        out.push_str(&prefix);
+        let item_indent = format!("{}{}", indent, " ".repeat(prefix.len()));
+        let RespValue::Map(entries) = reply else {
+            format_into(item, &item_indent, out);
+            continue;
+        };
+        // 맵은 "1# key => value", 값이 여러 줄이면 "=> " 뒤에 맞춰 들여씀
+        let mut key = String::new();
+        format_into(item, &item_indent, &mut key);
+        let key = key.trim_end_matches('\n');
+        out.push_str(key);
+        out.push_str(" => ");
+        let value_indent = format!("{}{}", item_indent, " ".repeat(key.len() + 4));
+        format_into(&entries[index].1, &value_indent, out);
+    }
+}
+
+// redis-cli처럼 출력할 수 없는 바이트는 이스케이프해서 따옴표로 감쌈
+fn quote(value: &[u8]) -> String {
+    let mut quoted = String::from("\"");
+    for &byte in value {
+        match byte {
+            b'\\' => quoted.push_str("\\\\"),
+            b'"' => quoted.push_str("\\\""),
+            b'\n' => quoted.push_str("\\n"),
+            b'\r' => quoted.push_str("\\r"),
+            b'\t' => quoted.push_str("\\t"),
+            0x07 => quoted.push_str("\\a"),
+            0x08 => quoted.push_str("\\b"),
+            byte if byte.is_ascii_graphic() || byte == b' ' => quoted.push(byte as char),
+            byte => quoted.push_str(&format!("\\x{:02x}", byte)),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
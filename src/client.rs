@@ -0,0 +1,109 @@
+use crate::random::Random;
+use crate::resp::RespValue;
+use crate::util::construct_redis_command;
+use std::io;
+use std::path::Path;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::{TcpStream, ToSocketAddrs, UnixStream};
+
+const READ_CHUNK_SIZE: usize = 16 * 1024;
+// redis-cli --pipe와 같은 길이의 표시
+const PIPE_MARKER_LEN: usize = 40;
+
+// 서버에 붙는 최소한의 RESP 클라이언트, 보내기와 읽기를 나눠 두어 파이프라이닝도 할 수 있음
+// 응답은 서버와 같은 RespValue로 읽으므로 HELLO 3 뒤의 RESP3 타입도 그대로 받음
+pub struct Client {
+    reader: Box<dyn AsyncRead + Send + Unpin>,
+    writer: Box<dyn AsyncWrite + Send + Unpin>,
+    // 읽었지만 아직 응답 하나로 끝나지 않은 바이트
+    buffer: Vec<u8>,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct PipeSummary {
+    pub replies: u64,
+    pub errors: u64,
+}
+
+impl Client {
+    pub async fn connect(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        let stream = TcpStream::connect(addr).await?;
+        stream.set_nodelay(true)?;
+        let (reader, writer) = stream.into_split();
+        Ok(Self::new(Box::new(reader), Box::new(writer)))
+    }
+
+    pub async fn connect_unix(path: impl AsRef<Path>) -> io::Result<Self> {
+        let (reader, writer) = UnixStream::connect(path).await?.into_split();
+        Ok(Self::new(Box::new(reader), Box::new(writer)))
+    }
+
+    fn new(reader: Box<dyn AsyncRead + Send + Unpin>, writer: Box<dyn AsyncWrite + Send + Unpin>) -> Self {
+        Self { reader, writer, buffer: Vec::new() }
+    }
+
+    pub async fn send_command<T: AsRef<[u8]>>(&mut self, args: &[T]) -> io::Result<()> {
+        self.writer.write_all(&construct_redis_command(args)).await
+    }
+
+    // 이미 인코딩한 요청을 그대로 보냄, 파이프라인 여러 개를 한 번에 쓸 때 씀
+    pub async fn send_raw(&mut self, data: &[u8]) -> io::Result<()> {
+        self.writer.write_all(data).await
+    }
+
+    // 에러 응답도 RespValue::Error로 돌려주고, 연결이 끊기거나 응답을 해석하지 못할 때만 Err
+    pub async fn read_reply(&mut self) -> io::Result<RespValue> {
+        read_reply(&mut self.reader, &mut self.buffer).await
+    }
+
+    pub async fn command<T: AsRef<[u8]>>(&mut self, args: &[T]) -> io::Result<RespValue> {
+        self.send_command(args).await?;
+        self.read_reply().await
+    }
+
+    // 인코딩된 요청을 통째로 보내고 응답 수와 에러 수를 셈, 끝에 보낸 ECHO 표시가 돌아오면 모든 응답을 받은 것
+    // 보내는 동안에도 응답을 읽어야 서버가 밀린 응답 때문에 읽기를 멈췄을 때 서로 기다리지 않음
+    pub async fn pipe(&mut self, data: &[u8]) -> io::Result<PipeSummary> {
+        let marker = Random::new().hex(PIPE_MARKER_LEN);
+        let echo = construct_redis_command(&["ECHO", marker.as_str()]);
+        let marker = RespValue::bulk(marker);
+        let writer = &mut self.writer;
+        let send = async {
+            writer.write_all(data).await?;
+            writer.write_all(&echo).await?;
+            writer.flush().await
+        };
+        let (reader, buffer) = (&mut self.reader, &mut self.buffer);
+        let receive = async {
+            let mut summary = PipeSummary::default();
+            loop {
+                let reply = read_reply(reader, buffer).await?;
+                if reply == marker {
+                    return Ok(summary);
+                }
+                summary.replies += 1;
+                if matches!(reply, RespValue::Error(_)) {
+                    summary.errors += 1;
+                }
+            }
+        };
+        let ((), summary) = tokio::try_join!(send, receive)?;
+        Ok(summary)
+    }
+}
+
+async fn read_reply(reader: &mut (dyn AsyncRead + Send + Unpin), buffer: &mut Vec<u8>) -> io::Result<RespValue> {
+    let mut chunk = vec![0u8; READ_CHUNK_SIZE];
+    loop {
+        let parsed = RespValue::parse(buffer).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        if let Some((reply, len)) = parsed {
+            buffer.drain(..len);
+            return Ok(reply);
+        }
+        let n = reader.read(&mut chunk).await?;
+        if n == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        buffer.extend_from_slice(&chunk[..n]);
+    }
+}
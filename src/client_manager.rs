@@ -1,5 +1,8 @@
+use crate::protocol_constants::RESP2_PROTOCOL;
 use crate::redis_client::Client;
-use std::collections::HashMap;
+use crate::tracking::TrackingOptions;
+use crate::util::glob_match;
+use std::collections::{HashMap, HashSet};
 
 
 pub struct ClientManager {
@@ -16,8 +19,8 @@ impl ClientManager {
         self.clients.insert(client_id, client);
     }
 
-    pub fn remove_client(&mut self, client_id: u64) {
-        self.clients.remove(&client_id);
+    pub fn remove_client(&mut self, client_id: u64) -> Option<Client> {
+        self.clients.remove(&client_id)
     }
 
     pub fn get_client(&self, client_id: u64) -> Option<&Client> {
@@ -27,4 +30,71 @@ impl ClientManager {
     pub fn get_client_mut(&mut self, client_id: &u64) -> Option<&mut Client> {
         self.clients.get_mut(client_id)
     }
+
+    // 없는 클라이언트(마스터 링크 등)는 RESP2로 봄
+    pub fn protocol(&self, client_id: u64) -> u8 {
+        self.clients.get(&client_id).map_or(RESP2_PROTOCOL, |client| client.protocol)
+    }
+
+    pub fn list_clients(&self) -> Vec<&Client> {
+        self.clients.values().collect()
+    }
+
+    pub fn subscribers(&self, channel: &str) -> Vec<u64> {
+        self.clients
+            .values()
+            .filter(|client| client.subscriptions.contains(channel))
+            .map(|client| client.id)
+            .collect()
+    }
+
+    // 구독자가 한 명 이상 있는 채널
+    pub fn active_channels(&self) -> HashSet<&String> {
+        self.clients.values().flat_map(|client| client.subscriptions.iter()).collect()
+    }
+
+    // Redis의 NUMPAT처럼 클라이언트와 관계없이 서로 다른 패턴의 수
+    pub fn pattern_count(&self) -> usize {
+        self.clients
+            .values()
+            .flat_map(|client| client.pattern_subscriptions.iter())
+            .collect::<HashSet<_>>()
+            .len()
+    }
+
+    // (구독자, 매칭된 패턴) - 한 클라이언트가 여러 패턴으로 매칭되면 패턴마다 한 번씩 받음
+    pub fn pattern_subscribers(&self, channel: &str) -> Vec<(u64, String)> {
+        self.clients
+            .values()
+            .flat_map(|client| {
+                client
+                    .pattern_subscriptions
+                    .iter()
+                    .filter(|pattern| glob_match(pattern.as_bytes(), channel.as_bytes()))
+                    .map(|pattern| (client.id, pattern.clone()))
+            })
+            .collect()
+    }
+
+    // CLIENT TRACKING이 켜진 클라이언트와 그 옵션
+    pub fn tracking_clients(&self) -> Vec<(u64, TrackingOptions)> {
+        self.clients
+            .values()
+            .filter_map(|client| client.tracking.clone().map(|options| (client.id, options)))
+            .collect()
+    }
+
+    // INFO clients의 connected_clients, Redis처럼 레플리카 연결은 빼고 셈
+    pub fn normal_client_count(&self) -> usize {
+        self.clients.values().filter(|client| !client.is_replica).count()
+    }
+
+    // 출력 버퍼가 가장 큰 클라이언트의 대기 중인 바이트 수
+    pub fn max_output_buffer_bytes(&self) -> usize {
+        self.clients.values().map(|client| client.output_buffer_bytes()).max().unwrap_or(0)
+    }
+
+    pub fn clients_with_pending_output(&self) -> Vec<u64> {
+        self.clients.values().filter(|client| client.has_pending_output()).map(|client| client.id).collect()
+    }
 }
\ No newline at end of file
@@ -0,0 +1,172 @@
+use crate::logging::{self, log_verbose};
+use std::io;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::io::AsyncWriteExt;
+use tokio::net::tcp::OwnedWriteHalf;
+use tokio::sync::{mpsc, Notify};
+use tokio::task::JoinHandle;
+
+// 소켓에 쓰지 못한 응답이 이만큼 쌓이면 그 연결의 요청을 더 읽지 않음
+const OUTPUT_PAUSE_BYTES: usize = 1024 * 1024;
+// 한 연결이 이벤트 큐에 동시에 올려 둘 수 있는 명령 수, 파이프라인을 많이 보내는 클라이언트 하나가 큐를 차지하지 않게 함
+const MAX_INFLIGHT_COMMANDS: usize = 16;
+
+// 연결의 읽기 태스크, 이벤트 루프, 전송 태스크가 함께 보는 흐름 제어 상태
+// 읽기 태스크만 기다리므로 진행이 있을 때마다 notify_one으로 깨움
+#[derive(Debug, Default)]
+pub struct Backpressure {
+    pending_output: AtomicUsize,
+    inflight: AtomicUsize,
+    paused: AtomicBool,
+    progress: Notify,
+}
+
+impl Backpressure {
+    pub fn pending_output(&self) -> usize {
+        self.pending_output.load(Ordering::Relaxed)
+    }
+
+    pub fn inflight(&self) -> usize {
+        self.inflight.load(Ordering::Relaxed)
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+
+    // 읽기 전에 부름, 클라이언트가 응답을 읽어 갈 때까지 요청을 더 받지 않음
+    pub async fn wait_for_output(&self) {
+        self.wait_until(|backpressure| backpressure.pending_output() < OUTPUT_PAUSE_BYTES).await;
+    }
+
+    // 명령을 이벤트 큐에 올리기 전에 부름
+    pub async fn acquire_command_slot(&self) {
+        self.wait_until(|backpressure| backpressure.inflight() < MAX_INFLIGHT_COMMANDS).await;
+        self.inflight.fetch_add(1, Ordering::Relaxed);
+    }
+
+    // 이벤트 루프가 명령을 꺼냈을 때 부름
+    pub fn command_dequeued(&self) {
+        let _ = self.inflight.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |inflight| inflight.checked_sub(1));
+        self.progress.notify_one();
+    }
+
+    async fn wait_until(&self, ready: impl Fn(&Self) -> bool) {
+        if ready(self) {
+            return;
+        }
+        self.paused.store(true, Ordering::Relaxed);
+        while !ready(self) {
+            self.progress.notified().await;
+        }
+        self.paused.store(false, Ordering::Relaxed);
+    }
+
+    fn output_written(&self, len: usize) {
+        self.pending_output.fetch_sub(len, Ordering::Relaxed);
+        self.progress.notify_one();
+    }
+}
+
+// 일반 클라이언트의 응답 경로: 이벤트 루프는 buffer에 쓰고, flush하면 전송 태스크로 넘겨 소켓 쓰기를 기다리지 않음
+// 레플리카로 바뀌면 finish로 쓰기 절반을 돌려받아 ReplicaOutput에 넘김
+#[derive(Debug)]
+pub struct ClientOutput {
+    buffer: Vec<u8>,
+    sender: mpsc::UnboundedSender<Vec<u8>>,
+    backpressure: Arc<Backpressure>,
+    task: JoinHandle<Option<OwnedWriteHalf>>,
+}
+
+impl ClientOutput {
+    pub fn spawn(client_id: u64, mut writer: OwnedWriteHalf, backpressure: Arc<Backpressure>) -> Self {
+        let (sender, mut receiver) = mpsc::unbounded_channel::<Vec<u8>>();
+        let task_backpressure = backpressure.clone();
+        let task = logging::spawn(async move {
+            while let Some(data) = receiver.recv().await {
+                if let Err(e) = writer.write_all(&data).await {
+                    log_verbose!("Failed to write to client {}: {}", client_id, e);
+                    // 더 보낼 수 없으므로 기다리는 읽기 태스크를 풀어 줌, 다음 flush가 실패하면서 연결이 닫힘
+                    task_backpressure.pending_output.store(0, Ordering::Relaxed);
+                    task_backpressure.progress.notify_one();
+                    return None;
+                }
+                task_backpressure.output_written(data.len());
+            }
+            Some(writer)
+        });
+        Self {
+            buffer: Vec::new(),
+            sender,
+            backpressure,
+            task,
+        }
+    }
+
+    pub fn backpressure(&self) -> Arc<Backpressure> {
+        self.backpressure.clone()
+    }
+
+    pub fn direct_replies(&self) -> DirectReplies {
+        DirectReplies {
+            sender: self.sender.downgrade(),
+            backpressure: self.backpressure.clone(),
+        }
+    }
+
+    pub fn buffer_mut(&mut self) -> &mut Vec<u8> {
+        &mut self.buffer
+    }
+
+    pub fn has_pending(&self) -> bool {
+        !self.buffer.is_empty()
+    }
+
+    // 아직 flush하지 않은 응답과 전송 태스크가 쓰지 못한 응답
+    pub fn pending_bytes(&self) -> usize {
+        self.buffer.len() + self.backpressure.pending_output()
+    }
+
+    // 전송 태스크가 끝났으면(소켓 쓰기 실패) 에러
+    pub fn flush(&mut self) -> io::Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        let data = std::mem::take(&mut self.buffer);
+        let len = data.len();
+        // 전송 태스크가 먼저 빼지 않도록 보내기 전에 더함
+        self.backpressure.pending_output.fetch_add(len, Ordering::Relaxed);
+        self.sender.send(data).map_err(|_| {
+            self.backpressure.pending_output.fetch_sub(len, Ordering::Relaxed);
+            io::Error::new(io::ErrorKind::BrokenPipe, "client output task stopped")
+        })
+    }
+
+    // 남은 응답을 모두 보낸 뒤 쓰기 절반을 돌려줌, 쓰기에 실패했으면 None
+    pub async fn finish(mut self) -> Option<OwnedWriteHalf> {
+        let _ = self.flush();
+        drop(self.sender);
+        self.task.await.ok().flatten()
+    }
+}
+
+// 이벤트 루프를 거치지 않고 연결 태스크가 바로 보내는 응답, 스크립트가 이벤트 루프를 잡고 있는 동안 BUSY를 보낼 때 씀
+// 약한 참조라서 레플리카로 바뀔 때 finish가 전송 태스크를 끝내는 것을 막지 않음
+pub struct DirectReplies {
+    sender: mpsc::WeakUnboundedSender<Vec<u8>>,
+    backpressure: Arc<Backpressure>,
+}
+
+impl DirectReplies {
+    pub fn send(&self, data: Vec<u8>) {
+        let Some(sender) = self.sender.upgrade() else {
+            return;
+        };
+        let len = data.len();
+        self.backpressure.pending_output.fetch_add(len, Ordering::Relaxed);
+        if sender.send(data).is_err() {
+            self.backpressure.pending_output.fetch_sub(len, Ordering::Relaxed);
+        }
+    }
+}
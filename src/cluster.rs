@@ -0,0 +1,716 @@
+use crate::cluster_bus::{BusMessage, BusMessageKind, GossipEntry};
+use crate::errors::RedisError;
+use crate::logging::{log_notice, log_warning};
+use crate::protocol_constants::*;
+use crate::random::Random;
+use crate::resp::RespValue;
+use crate::util::{format_host_port, json_string, key_hash_slot, CLUSTER_SLOTS};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::net::IpAddr;
+use std::sync::Arc;
+
+// Redis처럼 클러스터 버스 포트는 클라이언트 포트 + 10000
+pub const CLUSTER_BUS_PORT_OFFSET: u16 = 10000;
+const CLUSTER_NODE_ID_LEN: usize = 40;
+// Redis 기본 cluster-node-timeout
+pub const DEFAULT_CLUSTER_NODE_TIMEOUT_MS: u64 = 15000;
+// 노드마다 이 간격으로 PING을 보내고, 핸드셰이크 중이면 MEET을 다시 보냄
+const CLUSTER_PING_INTERVAL_MS: u64 = 1000;
+// 핸드셰이크는 node timeout과 1초 중 긴 쪽이 지나면 포기함
+const CLUSTER_MIN_HANDSHAKE_TIMEOUT_MS: u64 = 1000;
+// 장애 보고는 node timeout의 두 배 동안만 유효함
+const CLUSTER_FAIL_REPORT_VALIDITY_MULT: u64 = 2;
+
+// 노드 id는 Redis처럼 40자리 16진수
+fn generate_node_id(random: &Random) -> String {
+    random.hex(CLUSTER_NODE_ID_LEN)
+}
+
+// 0 이상 16384 미만이어야 슬롯 번호
+pub fn parse_slot(value: &str) -> Option<u16> {
+    value.parse::<u16>().ok().filter(|slot| *slot < CLUSTER_SLOTS)
+}
+
+// CLUSTER NODES 플래그 이름을 그대로 씀
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeHealth {
+    OK,
+    // 이 노드만 응답을 받지 못한 상태
+    PFAIL,
+    // 과반의 마스터가 장애를 보고해서 클러스터 전체에 알린 상태
+    FAIL,
+}
+
+impl NodeHealth {
+    fn as_str(&self) -> &'static str {
+        match self {
+            NodeHealth::OK => "ok",
+            NodeHealth::PFAIL => "pfail",
+            NodeHealth::FAIL => "fail",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ClusterNode {
+    pub id: String,
+    pub ip: String,
+    pub port: u16,
+    pub bus_port: u16,
+    pub config_epoch: u64,
+    // 레플리카면 복제하는 마스터의 id, 다른 노드의 값은 그 노드가 보낸 버스 메시지로 알게 됨
+    master: Option<String>,
+    // CLUSTER MEET 후 첫 PONG을 받기 전이면 true, id는 임시로 만든 값
+    handshake: bool,
+    health: NodeHealth,
+    // 응답을 기다리는 가장 오래된 PING을 보낸 시각
+    ping_sent: Option<u64>,
+    last_ping: u64,
+    pong_received: u64,
+    created_at: u64,
+    // 이 노드가 PFAIL/FAIL이라고 알려 온 노드 id와 그 시각
+    fail_reports: HashMap<String, u64>,
+}
+
+impl ClusterNode {
+    fn new(id: String, ip: &str, port: u16, bus_port: u16, now: u64) -> Self {
+        Self {
+            id,
+            ip: ip.to_string(),
+            port,
+            bus_port,
+            config_epoch: 0,
+            master: None,
+            handshake: false,
+            health: NodeHealth::OK,
+            ping_sent: None,
+            last_ping: 0,
+            pong_received: now,
+            created_at: now,
+            fail_reports: HashMap::new(),
+        }
+    }
+
+    fn address(&self) -> String {
+        format!("{}:{}@{}", self.ip, self.port, self.bus_port)
+    }
+
+    fn bus_address(&self) -> String {
+        format_host_port(&self.ip, self.bus_port)
+    }
+
+    // CLUSTER SLOTS의 노드 항목: [ip, port, id]
+    fn slot_endpoint(&self) -> RespValue {
+        RespValue::Array(vec![RespValue::bulk(self.ip.as_str()), RespValue::Integer(self.port as i64), RespValue::bulk(self.id.as_str())])
+    }
+}
+
+// 이 서버가 아는 클러스터 구성: 노드 목록과 슬롯마다 담당 노드
+pub struct ClusterState {
+    myself: String,
+    nodes: BTreeMap<String, ClusterNode>,
+    // 슬롯 번호 위치에 담당 노드 id, 아무도 맡지 않았으면 None
+    slots: Vec<Option<String>>,
+    // 리샤딩 중인 슬롯: 내보내는 슬롯은 받을 노드 id, 받아 오는 슬롯은 보내는 노드 id
+    migrating: HashMap<u16, String>,
+    importing: HashMap<u16, String>,
+    current_epoch: u64,
+    node_timeout: u64,
+    random: Arc<Random>,
+}
+
+// CLUSTER SETSLOT 하위 명령 이름을 그대로 씀
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug)]
+pub enum SlotState {
+    IMPORTING(String),
+    MIGRATING(String),
+    STABLE,
+    NODE(String),
+}
+
+impl ClusterState {
+    pub fn new(ip: &str, port: u16, node_timeout: u64, random: Arc<Random>) -> Self {
+        let myself = ClusterNode::new(generate_node_id(&random), ip, port, port.wrapping_add(CLUSTER_BUS_PORT_OFFSET), 0);
+        Self {
+            myself: myself.id.clone(),
+            nodes: BTreeMap::from([(myself.id.clone(), myself)]),
+            slots: vec![None; CLUSTER_SLOTS as usize],
+            migrating: HashMap::new(),
+            importing: HashMap::new(),
+            current_epoch: 0,
+            node_timeout,
+            random,
+        }
+    }
+
+    pub fn myself(&self) -> &ClusterNode {
+        &self.nodes[&self.myself]
+    }
+
+    pub fn slot_owner(&self, slot: u16) -> Option<&ClusterNode> {
+        self.slots[slot as usize].as_ref().and_then(|id| self.nodes.get(id))
+    }
+
+    fn owns(&self, slot: u16) -> bool {
+        self.slots[slot as usize].as_ref() == Some(&self.myself)
+    }
+
+    // 키 명령을 이 노드가 처리할 수 있으면 Ok, 아니면 클라이언트에게 돌려줄 리다이렉트/에러
+    // missing_keys는 이 노드에 없는 키 수로, 슬롯을 옮기는 중에 키가 어느 쪽에 있는지 판단할 때 씀
+    // replica_read는 READONLY 연결의 읽기 명령이라는 뜻으로, 레플리카가 자기 마스터의 슬롯이면 직접 처리함
+    pub fn route(&self, keys: &[&Vec<u8>], asking: bool, missing_keys: usize, replica_read: bool) -> Result<(), RedisError> {
+        let Some(first) = keys.first() else {
+            return Ok(());
+        };
+        let slot = key_hash_slot(first);
+        if keys.iter().any(|key| key_hash_slot(key) != slot) {
+            return Err(RedisError::CrossSlot);
+        }
+        if !self.is_ok() {
+            return Err(RedisError::ClusterDown);
+        }
+        let Some(owner) = self.slot_owner(slot) else {
+            return Err(RedisError::ClusterDown);
+        };
+        // 여러 키 중 일부만 옮겨진 상태면 어느 노드도 한 번에 처리할 수 없으므로 잠시 뒤 다시 시도하게 함
+        if owner.id != self.myself {
+            if replica_read && self.myself().master.as_ref() == Some(&owner.id) {
+                return Ok(());
+            }
+            if asking && self.importing.contains_key(&slot) {
+                return if keys.len() > 1 && missing_keys > 0 { Err(RedisError::TryAgain) } else { Ok(()) };
+            }
+            return Err(RedisError::Moved { slot, addr: format_host_port(&owner.ip, owner.port) });
+        }
+        match self.migrating.get(&slot).and_then(|target| self.nodes.get(target)) {
+            Some(_) if keys.len() > 1 && missing_keys > 0 && missing_keys < keys.len() => Err(RedisError::TryAgain),
+            Some(target) if missing_keys > 0 => Err(RedisError::Ask { slot, addr: format_host_port(&target.ip, target.port) }),
+            _ => Ok(()),
+        }
+    }
+
+    // CLUSTER SETSLOT, keys_in_slot은 이 노드에 남아 있는 그 슬롯의 키 수
+    pub fn set_slot(&mut self, slot: u16, state: &SlotState, keys_in_slot: usize) -> Result<(), String> {
+        match state {
+            SlotState::IMPORTING(source) => {
+                if self.owns(slot) {
+                    return Err(format!("I'm already the owner of hash slot {}", slot));
+                }
+                self.check_known(source)?;
+                self.importing.insert(slot, source.clone());
+            }
+            SlotState::MIGRATING(target) => {
+                if !self.owns(slot) {
+                    return Err(format!("I'm not the owner of hash slot {}", slot));
+                }
+                self.check_known(target)?;
+                self.migrating.insert(slot, target.clone());
+            }
+            SlotState::STABLE => {
+                self.importing.remove(&slot);
+                self.migrating.remove(&slot);
+            }
+            SlotState::NODE(node_id) => {
+                self.check_known(node_id)?;
+                if self.owns(slot) && *node_id != self.myself && keys_in_slot > 0 {
+                    return Err(format!(
+                        "Can't assign hashslot {} to a different node while I still hold keys for this hash slot.",
+                        slot
+                    ));
+                }
+                if *node_id != self.myself {
+                    self.migrating.remove(&slot);
+                }
+                // 옮겨 받기를 마친 노드는 다른 노드의 오래된 설정보다 우선하도록 새 epoch를 가짐
+                if *node_id == self.myself && self.importing.remove(&slot).is_some() {
+                    self.bump_config_epoch();
+                }
+                self.slots[slot as usize] = Some(node_id.clone());
+            }
+        }
+        Ok(())
+    }
+
+    fn check_known(&self, node_id: &str) -> Result<(), String> {
+        if self.nodes.contains_key(node_id) {
+            Ok(())
+        } else {
+            Err(format!("I don't know about node {}", node_id))
+        }
+    }
+
+    fn bump_config_epoch(&mut self) {
+        self.current_epoch += 1;
+        let epoch = self.current_epoch;
+        if let Some(myself) = self.nodes.get_mut(&self.myself) {
+            myself.config_epoch = epoch;
+        }
+    }
+
+    // CLUSTER MEET, 실제 MEET 메시지는 다음 cron에서 보내고 PONG을 받으면 진짜 id로 바뀜
+    pub fn meet(&mut self, ip: &str, port: u16, bus_port: u16, now: u64) -> Result<(), String> {
+        let Ok(ip) = ip.parse::<IpAddr>() else {
+            return Err(format!("Invalid node address specified: {}:{}", ip, port));
+        };
+        let ip = ip.to_string();
+        if !self.nodes.values().any(|node| node.ip == ip && node.port == port) {
+            let mut node = ClusterNode::new(generate_node_id(&self.random), &ip, port, bus_port, now);
+            node.handshake = true;
+            self.nodes.insert(node.id.clone(), node);
+        }
+        Ok(())
+    }
+
+    // CLUSTER REPLICATE, 성공하면 복제를 시작할 마스터 주소를 돌려주고 다음 버스 메시지부터 다른 노드에 알림
+    pub fn replicate(&mut self, node_id: &str) -> Result<(String, u16), String> {
+        let Some(master) = self.nodes.get(node_id).filter(|node| !node.handshake) else {
+            return Err(format!("Unknown node {}", node_id));
+        };
+        if master.id == self.myself {
+            return Err("Can't replicate myself".to_string());
+        }
+        if master.master.is_some() {
+            return Err("I can only replicate a master, not a replica.".to_string());
+        }
+        if self.slots.iter().any(|owner| owner.as_ref() == Some(&self.myself)) {
+            return Err("To set a master the node must be empty and without assigned slots.".to_string());
+        }
+        let address = (master.ip.clone(), master.port);
+        let master_id = master.id.clone();
+        if let Some(myself) = self.nodes.get_mut(&self.myself) {
+            myself.master = Some(master_id);
+        }
+        Ok(address)
+    }
+
+    // CLUSTER REPLICAS <node-id>: 그 마스터를 복제한다고 알려 온 노드들의 CLUSTER NODES 줄
+    pub fn replicas_reply(&self, node_id: &str) -> Result<RespValue, String> {
+        let Some(master) = self.nodes.get(node_id) else {
+            return Err(format!("Unknown node {}", node_id));
+        };
+        if master.master.is_some() {
+            return Err("The specified node is not a master".to_string());
+        }
+        let shards = self.shards();
+        let lines: Vec<String> = self.replicas_of(node_id).map(|replica| self.node_line(replica, &shards)).collect();
+        Ok(RespValue::bulk_array(&lines))
+    }
+
+    fn replicas_of<'a>(&'a self, master_id: &'a str) -> impl Iterator<Item = &'a ClusterNode> {
+        self.nodes.values().filter(move |node| node.master.as_deref() == Some(master_id))
+    }
+
+    // 보내는 노드의 정보와 슬롯, 그리고 핸드셰이크를 마친 다른 노드들의 상태를 담은 메시지
+    fn message(&self, kind: BusMessageKind) -> BusMessage {
+        let myself = self.myself();
+        let gossip = self
+            .nodes
+            .values()
+            .filter(|node| node.id != self.myself && !node.handshake)
+            .map(|node| GossipEntry {
+                id: node.id.clone(),
+                ip: node.ip.clone(),
+                port: node.port,
+                bus_port: node.bus_port,
+                health: node.health.as_str().to_string(),
+            })
+            .collect();
+        BusMessage {
+            kind,
+            sender_id: myself.id.clone(),
+            ip: myself.ip.clone(),
+            port: myself.port,
+            bus_port: myself.bus_port,
+            current_epoch: self.current_epoch,
+            config_epoch: myself.config_epoch,
+            slots: self.shards().remove(self.myself.as_str()).unwrap_or_default(),
+            failed: None,
+            master: myself.master.clone(),
+            gossip,
+        }
+    }
+
+    // 다른 노드에서 받은 메시지를 반영하고 보낼 응답을 (버스 주소, 메시지)로 돌려줌
+    pub fn handle_message(&mut self, message: BusMessage, now: u64) -> Vec<(String, BusMessage)> {
+        if message.sender_id == self.myself {
+            return Vec::new();
+        }
+        if !self.nodes.contains_key(&message.sender_id) {
+            match message.kind {
+                // 처음 보는 노드의 MEET은 그대로 받아들이고 PONG으로 핸드셰이크를 끝냄
+                BusMessageKind::MEET => {
+                    let node = ClusterNode::new(message.sender_id.clone(), &message.ip, message.port, message.bus_port, now);
+                    self.nodes.insert(node.id.clone(), node);
+                }
+                // MEET한 주소와 노드가 알리는 주소(cluster-announce-ip)가 다를 수 있으므로 포트가 같은 핸드셰이크 노드도 찾음
+                BusMessageKind::PONG => {
+                    let handshakes = || self.nodes.values().filter(|node| node.handshake && node.port == message.port);
+                    let Some(handshake_id) = handshakes()
+                        .find(|node| node.ip == message.ip)
+                        .or_else(|| handshakes().next())
+                        .map(|node| node.id.clone())
+                    else {
+                        return Vec::new();
+                    };
+                    // 이후 CLUSTER NODES/SLOTS와 MOVED에는 노드가 알린 주소를 씀
+                    if let Some(mut node) = self.nodes.remove(&handshake_id) {
+                        node.id = message.sender_id.clone();
+                        node.handshake = false;
+                        node.ip = message.ip.clone();
+                        node.bus_port = message.bus_port;
+                        self.nodes.insert(node.id.clone(), node);
+                    }
+                }
+                // 모르는 노드의 PING/FAIL은 무시함
+                _ => return Vec::new(),
+            }
+        }
+
+        self.current_epoch = self.current_epoch.max(message.current_epoch);
+        if let Some(sender) = self.nodes.get_mut(&message.sender_id) {
+            sender.config_epoch = message.config_epoch;
+            if sender.master != message.master {
+                if let Some(master) = &message.master {
+                    log_notice!("Cluster node {} is now a replica of {}", sender.id, master);
+                }
+                sender.master = message.master.clone();
+            }
+            // 페일오버가 없으므로 다시 응답하는 노드는 바로 정상으로 되돌림
+            if message.kind == BusMessageKind::PONG {
+                sender.pong_received = now;
+                sender.ping_sent = None;
+                sender.health = NodeHealth::OK;
+            }
+        }
+        self.update_slots(&message);
+        self.handle_epoch_collision(&message);
+        self.process_gossip(&message, now);
+
+        if let (BusMessageKind::FAIL, Some(failed)) = (message.kind, &message.failed) {
+            if let Some(node) = self.nodes.get_mut(failed).filter(|node| node.id != self.myself) {
+                if node.health != NodeHealth::FAIL {
+                    log_notice!("Cluster node {} marked as failing by {}", failed, message.sender_id);
+                }
+                node.health = NodeHealth::FAIL;
+            }
+        }
+
+        match message.kind {
+            BusMessageKind::MEET | BusMessageKind::PING => {
+                let addr = format_host_port(&message.ip, message.bus_port);
+                vec![(addr, self.message(BusMessageKind::PONG))]
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    // 보내는 노드가 더 높은 config epoch로 주장하는 슬롯은 그 노드에게 넘김, 받아 오는 중인 슬롯은 건드리지 않음
+    fn update_slots(&mut self, message: &BusMessage) {
+        for (start, end) in &message.slots {
+            for slot in *start..=(*end).min(CLUSTER_SLOTS - 1) {
+                if self.importing.contains_key(&slot) {
+                    continue;
+                }
+                let claim = match self.slot_owner(slot) {
+                    None => true,
+                    Some(owner) => owner.id != message.sender_id && owner.config_epoch < message.config_epoch,
+                };
+                if claim {
+                    if self.owns(slot) {
+                        self.migrating.remove(&slot);
+                    }
+                    self.slots[slot as usize] = Some(message.sender_id.clone());
+                }
+            }
+        }
+    }
+
+    // 두 마스터의 config epoch가 같으면 id가 작은 쪽이 새 epoch를 받아서 슬롯 충돌을 풀 수 있게 함
+    fn handle_epoch_collision(&mut self, message: &BusMessage) {
+        if message.config_epoch == self.myself().config_epoch && self.myself < message.sender_id {
+            self.bump_config_epoch();
+        }
+    }
+
+    // 가십으로 알게 된 노드는 핸드셰이크를 시작하고, 다른 노드들의 상태는 장애 보고로 모음
+    // Redis처럼 장애 보고는 마스터가 보낸 것만 셈
+    fn process_gossip(&mut self, message: &BusMessage, now: u64) {
+        for entry in &message.gossip {
+            if entry.id == self.myself {
+                continue;
+            }
+            match self.nodes.get_mut(&entry.id) {
+                Some(_) if message.master.is_some() => {}
+                Some(node) => {
+                    if entry.health == NodeHealth::OK.as_str() {
+                        node.fail_reports.remove(&message.sender_id);
+                    } else {
+                        node.fail_reports.insert(message.sender_id.clone(), now);
+                    }
+                }
+                None => {
+                    if self.meet(&entry.ip, entry.port, entry.bus_port, now).is_err() {
+                        log_warning!("Ignoring gossip about node {} with invalid address {}", entry.id, entry.ip);
+                    }
+                }
+            }
+        }
+    }
+
+    // ActiveExpireCycle마다 호출, PING/MEET을 보내고 응답이 없는 노드를 PFAIL, 과반이 보고하면 FAIL로 표시함
+    pub fn cron(&mut self, now: u64) -> Vec<(String, BusMessage)> {
+        let handshake_timeout = self.node_timeout.max(CLUSTER_MIN_HANDSHAKE_TIMEOUT_MS);
+        self.nodes
+            .retain(|_, node| !node.handshake || now.saturating_sub(node.created_at) <= handshake_timeout);
+
+        let mut outgoing = Vec::new();
+        let mut pinged = Vec::new();
+        for node in self.nodes.values_mut() {
+            if node.id == self.myself || now.saturating_sub(node.last_ping) < CLUSTER_PING_INTERVAL_MS {
+                continue;
+            }
+            node.last_ping = now;
+            node.ping_sent.get_or_insert(now);
+            pinged.push((node.bus_address(), node.handshake));
+        }
+        for (addr, handshake) in pinged {
+            let kind = if handshake { BusMessageKind::MEET } else { BusMessageKind::PING };
+            outgoing.push((addr, self.message(kind)));
+        }
+
+        let node_timeout = self.node_timeout;
+        for node in self.nodes.values_mut() {
+            if node.handshake || node.health != NodeHealth::OK {
+                continue;
+            }
+            if node.ping_sent.is_some_and(|sent| now.saturating_sub(sent) > node_timeout) {
+                log_notice!("Cluster node {} is not reachable, marking as possibly failing", node.id);
+                node.health = NodeHealth::PFAIL;
+            }
+        }
+
+        // 나를 포함해서 슬롯을 맡은 마스터의 과반이 보고해야 FAIL, 레플리카인 나는 표를 갖지 않음
+        let quorum = self.shards().len() / 2 + 1;
+        let own_vote = usize::from(self.myself().master.is_none());
+        let report_validity = node_timeout * CLUSTER_FAIL_REPORT_VALIDITY_MULT;
+        let mut failed = Vec::new();
+        for node in self.nodes.values_mut() {
+            node.fail_reports.retain(|_, reported_at| now.saturating_sub(*reported_at) <= report_validity);
+            if node.health == NodeHealth::PFAIL && node.fail_reports.len() + own_vote >= quorum {
+                log_notice!("Marking cluster node {} as failing (quorum reached)", node.id);
+                node.health = NodeHealth::FAIL;
+                failed.push(node.id.clone());
+            }
+        }
+        for failed in failed {
+            let mut message = self.message(BusMessageKind::FAIL);
+            message.failed = Some(failed);
+            for node in self.nodes.values().filter(|node| node.id != self.myself && !node.handshake) {
+                outgoing.push((node.bus_address(), message.clone()));
+            }
+        }
+        outgoing
+    }
+
+    // 하나라도 이미 담당 노드가 있으면 아무 슬롯도 바꾸지 않음
+    pub fn add_slots(&mut self, slots: &[u16]) -> Result<(), String> {
+        Self::check_duplicates(slots)?;
+        if let Some(slot) = slots.iter().find(|slot| self.slots[**slot as usize].is_some()) {
+            return Err(format!("Slot {} is already busy", slot));
+        }
+        for slot in slots {
+            self.slots[*slot as usize] = Some(self.myself.clone());
+        }
+        Ok(())
+    }
+
+    pub fn del_slots(&mut self, slots: &[u16]) -> Result<(), String> {
+        Self::check_duplicates(slots)?;
+        if let Some(slot) = slots.iter().find(|slot| self.slots[**slot as usize].is_none()) {
+            return Err(format!("Slot {} is already unassigned", slot));
+        }
+        for slot in slots {
+            self.slots[*slot as usize] = None;
+            self.importing.remove(slot);
+            self.migrating.remove(slot);
+        }
+        Ok(())
+    }
+
+    fn check_duplicates(slots: &[u16]) -> Result<(), String> {
+        let mut seen = HashSet::new();
+        match slots.iter().find(|slot| !seen.insert(**slot)) {
+            Some(slot) => Err(format!("Slot {} specified multiple times", slot)),
+            None => Ok(()),
+        }
+    }
+
+    fn assigned_slots(&self) -> usize {
+        self.slots.iter().filter(|owner| owner.is_some()).count()
+    }
+
+    // 같은 노드가 연달아 맡은 슬롯을 (시작, 끝, 노드 id) 구간으로 묶음
+    fn slot_ranges(&self) -> Vec<(u16, u16, &str)> {
+        let mut ranges: Vec<(u16, u16, &str)> = Vec::new();
+        for (slot, owner) in self.slots.iter().enumerate() {
+            let Some(owner) = owner.as_deref() else {
+                continue;
+            };
+            match ranges.last_mut() {
+                Some((_, end, id)) if *id == owner && *end as usize + 1 == slot => *end = slot as u16,
+                _ => ranges.push((slot as u16, slot as u16, owner)),
+            }
+        }
+        ranges
+    }
+
+    // 슬롯을 하나 이상 맡은 노드만 샤드로 셈
+    fn shards(&self) -> BTreeMap<&str, Vec<(u16, u16)>> {
+        let mut shards: BTreeMap<&str, Vec<(u16, u16)>> = BTreeMap::new();
+        for (start, end, id) in self.slot_ranges() {
+            shards.entry(id).or_default().push((start, end));
+        }
+        shards
+    }
+
+    fn slots_with_health(&self, health: NodeHealth) -> usize {
+        (0..CLUSTER_SLOTS).filter(|slot| self.slot_owner(*slot).is_some_and(|owner| owner.health == health)).count()
+    }
+
+    // cluster-require-full-coverage 기본값처럼 모든 슬롯에 담당 노드가 있고 FAIL인 담당 노드가 없어야 ok
+    pub fn is_ok(&self) -> bool {
+        self.assigned_slots() == CLUSTER_SLOTS as usize && self.slots_with_health(NodeHealth::FAIL) == 0
+    }
+
+    pub fn info(&self) -> String {
+        let assigned = self.assigned_slots();
+        let pfail = self.slots_with_health(NodeHealth::PFAIL);
+        let fail = self.slots_with_health(NodeHealth::FAIL);
+        let mut info = String::new();
+        info.push_str(&format!("cluster_state:{}{}", if self.is_ok() { "ok" } else { "fail" }, CRLF));
+        info.push_str(&format!("cluster_slots_assigned:{}{}", assigned, CRLF));
+        info.push_str(&format!("cluster_slots_ok:{}{}", assigned - pfail - fail, CRLF));
+        info.push_str(&format!("cluster_slots_pfail:{}{}", pfail, CRLF));
+        info.push_str(&format!("cluster_slots_fail:{}{}", fail, CRLF));
+        info.push_str(&format!("cluster_known_nodes:{}{}", self.nodes.len(), CRLF));
+        info.push_str(&format!("cluster_size:{}{}", self.shards().len(), CRLF));
+        info.push_str(&format!("cluster_current_epoch:{}{}", self.current_epoch, CRLF));
+        info.push_str(&format!("cluster_my_epoch:{}{}", self.myself().config_epoch, CRLF));
+        info
+    }
+
+    // CLUSTER NODES 형식: <id> <ip:port@cport> <flags> <master> <ping-sent> <pong-recv> <config-epoch> <link-state> <slot>...
+    pub fn nodes_description(&self) -> String {
+        let shards = self.shards();
+        let mut description = String::new();
+        for node in self.nodes.values() {
+            description.push_str(&self.node_line(node, &shards));
+            description.push('\n');
+        }
+        description
+    }
+
+    fn node_line(&self, node: &ClusterNode, shards: &BTreeMap<&str, Vec<(u16, u16)>>) -> String {
+        let role = if node.master.is_some() { "slave" } else { "master" };
+        let mut flags = if node.id == self.myself { format!("myself,{}", role) } else { role.to_string() };
+        match node.health {
+            NodeHealth::PFAIL => flags.push_str(",fail?"),
+            NodeHealth::FAIL => flags.push_str(",fail"),
+            NodeHealth::OK => {}
+        }
+        if node.handshake {
+            flags = "handshake".to_string();
+        }
+        let link_state = if node.health == NodeHealth::OK { "connected" } else { "disconnected" };
+        let mut line = format!(
+            "{} {} {} {} {} {} {} {}",
+            node.id,
+            node.address(),
+            flags,
+            node.master.as_deref().unwrap_or("-"),
+            node.ping_sent.unwrap_or(0),
+            if node.id == self.myself { 0 } else { node.pong_received },
+            node.config_epoch,
+            link_state
+        );
+        for (start, end) in shards.get(node.id.as_str()).into_iter().flatten() {
+            if start == end {
+                line.push_str(&format!(" {}", start));
+            } else {
+                line.push_str(&format!(" {}-{}", start, end));
+            }
+        }
+        if node.id == self.myself {
+            for (slot, target) in &self.migrating {
+                line.push_str(&format!(" [{}->-{}]", slot, target));
+            }
+            for (slot, source) in &self.importing {
+                line.push_str(&format!(" [{}-<-{}]", slot, source));
+            }
+        }
+        line
+    }
+
+    // CLUSTER SLOTS: 구간마다 [시작, 끝, 마스터 [ip, port, id], 레플리카 [ip, port, id]...], FAIL인 레플리카는 뺌
+    pub fn slots_reply(&self) -> RespValue {
+        let ranges = self.slot_ranges();
+        let mut reply = Vec::new();
+        for (start, end, id) in ranges {
+            let mut range = vec![RespValue::Integer(start as i64), RespValue::Integer(end as i64), self.nodes[id].slot_endpoint()];
+            range.extend(self.replicas_of(id).filter(|replica| replica.health != NodeHealth::FAIL).map(ClusterNode::slot_endpoint));
+            reply.push(RespValue::Array(range));
+        }
+        RespValue::Array(reply)
+    }
+
+    // CLUSTER SHARDS: 샤드마다 "slots"와 마스터, 레플리카 순서의 "nodes"를 담은 맵
+    pub fn shards_reply(&self, replication_offset: u64) -> RespValue {
+        let shards = self.shards();
+        let mut reply = Vec::new();
+        for (id, ranges) in shards {
+            let slots = ranges
+                .into_iter()
+                .flat_map(|(start, end)| [RespValue::Integer(start as i64), RespValue::Integer(end as i64)])
+                .collect();
+            let nodes = std::iter::once(&self.nodes[id])
+                .chain(self.replicas_of(id))
+                .map(|node| {
+                    let offset = if node.id == self.myself { replication_offset } else { 0 };
+                    let health = if node.health == NodeHealth::FAIL { "failed" } else { "online" };
+                    RespValue::field_map(vec![
+                        ("id", RespValue::bulk(node.id.as_str())),
+                        ("port", RespValue::Integer(node.port as i64)),
+                        ("ip", RespValue::bulk(node.ip.as_str())),
+                        ("endpoint", RespValue::bulk(node.ip.as_str())),
+                        ("role", RespValue::bulk(if node.master.is_some() { "replica" } else { "master" })),
+                        ("replication-offset", RespValue::Integer(offset as i64)),
+                        ("health", RespValue::bulk(health)),
+                    ])
+                })
+                .collect();
+            reply.push(RespValue::field_map(vec![("slots", RespValue::Array(slots)), ("nodes", RespValue::Array(nodes))]));
+        }
+        RespValue::Array(reply)
+    }
+
+    // 관리 API용 슬롯 맵
+    pub fn slots_json(&self) -> String {
+        let entries: Vec<String> = self
+            .slot_ranges()
+            .into_iter()
+            .map(|(start, end, id)| {
+                let node = &self.nodes[id];
+                format!(
+                    "{{\"start\":{},\"end\":{},\"node\":{},\"addr\":{}}}",
+                    start,
+                    end,
+                    json_string(&node.id),
+                    json_string(&format_host_port(&node.ip, node.port))
+                )
+            })
+            .collect();
+        format!("[{}]", entries.join(","))
+    }
+}
@@ -0,0 +1,316 @@
+use crate::command_parser::{CommandParser, FrameDecoder};
+use crate::event_publisher::EventPublisher;
+use crate::logging::{self, log_warning};
+use crate::util::construct_redis_command;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+use tokio::time::Duration;
+
+const BUS_CONNECT_TIMEOUT: Duration = Duration::from_secs(1);
+// 헤더 필드 수: 종류, id, ip, port, 버스 port, current epoch, config epoch, 슬롯, FAIL 대상, 복제하는 마스터
+const HEADER_FIELDS: usize = 10;
+const GOSSIP_FIELDS: usize = 5;
+// 비어 있는 필드 자리
+const EMPTY_FIELD: &str = "-";
+
+// 버스 메시지 종류는 Redis 클러스터 버스의 메시지 이름을 그대로 씀
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BusMessageKind {
+    MEET,
+    PING,
+    PONG,
+    FAIL,
+}
+
+impl BusMessageKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            BusMessageKind::MEET => "MEET",
+            BusMessageKind::PING => "PING",
+            BusMessageKind::PONG => "PONG",
+            BusMessageKind::FAIL => "FAIL",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "MEET" => Some(BusMessageKind::MEET),
+            "PING" => Some(BusMessageKind::PING),
+            "PONG" => Some(BusMessageKind::PONG),
+            "FAIL" => Some(BusMessageKind::FAIL),
+            _ => None,
+        }
+    }
+}
+
+// 보내는 노드가 아는 다른 노드 하나, health는 "ok", "pfail", "fail" 중 하나
+#[derive(Debug, Clone)]
+pub struct GossipEntry {
+    pub id: String,
+    pub ip: String,
+    pub port: u16,
+    pub bus_port: u16,
+    pub health: String,
+}
+
+// 노드 사이에 주고받는 메시지, 보내는 노드의 정보와 슬롯을 항상 담음
+#[derive(Debug, Clone)]
+pub struct BusMessage {
+    pub kind: BusMessageKind,
+    pub sender_id: String,
+    pub ip: String,
+    pub port: u16,
+    pub bus_port: u16,
+    pub current_epoch: u64,
+    pub config_epoch: u64,
+    pub slots: Vec<(u16, u16)>,
+    // FAIL 메시지가 알리는 노드
+    pub failed: Option<String>,
+    // 보내는 노드가 레플리카면 복제하는 마스터의 id, 다른 노드는 이걸로 샤드마다 레플리카를 알게 됨
+    pub master: Option<String>,
+    pub gossip: Vec<GossipEntry>,
+}
+
+impl BusMessage {
+    // 클라이언트 요청과 같은 RESP 배열로 보냄
+    pub fn encode(&self) -> Vec<u8> {
+        let slots = if self.slots.is_empty() {
+            EMPTY_FIELD.to_string()
+        } else {
+            self.slots.iter().map(|(start, end)| format!("{}-{}", start, end)).collect::<Vec<_>>().join(",")
+        };
+        let mut fields = vec![
+            self.kind.as_str().to_string(),
+            self.sender_id.clone(),
+            self.ip.clone(),
+            self.port.to_string(),
+            self.bus_port.to_string(),
+            self.current_epoch.to_string(),
+            self.config_epoch.to_string(),
+            slots,
+            self.failed.clone().unwrap_or_else(|| EMPTY_FIELD.to_string()),
+            self.master.clone().unwrap_or_else(|| EMPTY_FIELD.to_string()),
+        ];
+        for entry in &self.gossip {
+            fields.extend([
+                entry.id.clone(),
+                entry.ip.clone(),
+                entry.port.to_string(),
+                entry.bus_port.to_string(),
+                entry.health.clone(),
+            ]);
+        }
+        let fields: Vec<&str> = fields.iter().map(|field| field.as_str()).collect();
+        construct_redis_command(&fields)
+    }
+
+    pub fn decode(args: &[String]) -> Option<Self> {
+        if args.len() < HEADER_FIELDS || (args.len() - HEADER_FIELDS) % GOSSIP_FIELDS != 0 {
+            return None;
+        }
+        let slots = match args[7].as_str() {
+            EMPTY_FIELD => Vec::new(),
+            ranges => ranges
+                .split(',')
+                .map(|range| {
+                    let (start, end) = range.split_once('-')?;
+                    Some((start.parse().ok()?, end.parse().ok()?))
+                })
+                .collect::<Option<Vec<(u16, u16)>>>()?,
+        };
+        let gossip = args[HEADER_FIELDS..]
+            .chunks(GOSSIP_FIELDS)
+            .map(|entry| {
+                Some(GossipEntry {
+                    id: entry[0].clone(),
+                    ip: entry[1].clone(),
+                    port: entry[2].parse().ok()?,
+                    bus_port: entry[3].parse().ok()?,
+                    health: entry[4].clone(),
+                })
+            })
+            .collect::<Option<Vec<GossipEntry>>>()?;
+        Some(Self {
+            kind: BusMessageKind::parse(&args[0])?,
+            sender_id: args[1].clone(),
+            ip: args[2].clone(),
+            port: args[3].parse().ok()?,
+            bus_port: args[4].parse().ok()?,
+            current_epoch: args[5].parse().ok()?,
+            config_epoch: args[6].parse().ok()?,
+            slots,
+            failed: (args[8] != EMPTY_FIELD).then(|| args[8].clone()),
+            master: (args[9] != EMPTY_FIELD).then(|| args[9].clone()),
+            gossip,
+        })
+    }
+}
+
+// 다른 노드가 연결해 오는 버스 포트, 받은 메시지는 이벤트 핸들러가 처리하고 응답은 이쪽에서 연 링크로 보냄
+// inbound_links는 ClusterBus::inbound_links로 받은 카운터, 연결이 열려 있는 동안 1씩 더해 둠
+pub async fn serve_bus(listener: TcpListener, publisher: EventPublisher, inbound_links: Arc<AtomicUsize>) {
+    while let Ok((stream, addr)) = listener.accept().await {
+        let publisher = publisher.clone();
+        let inbound_links = inbound_links.clone();
+        logging::spawn(async move {
+            inbound_links.fetch_add(1, Ordering::Relaxed);
+            if let Err(e) = read_bus_messages(stream, &publisher).await {
+                log_warning!("Cluster bus connection from {} closed: {}", addr, e);
+            }
+            inbound_links.fetch_sub(1, Ordering::Relaxed);
+        });
+    }
+}
+
+async fn read_bus_messages(mut stream: TcpStream, publisher: &EventPublisher) -> Result<(), String> {
+    let mut decoder = FrameDecoder::new();
+    let mut temp_buffer = [0u8; 4096];
+    loop {
+        while let Some(frame) = decoder.next_frame().map_err(|e| e.to_string())? {
+            let args: Vec<String> = CommandParser::frame_args(&frame)
+                .map_err(|e| e.to_string())?
+                .iter()
+                .map(|arg| String::from_utf8_lossy(arg).into_owned())
+                .collect();
+            let message = BusMessage::decode(&args).ok_or_else(|| "invalid cluster bus message".to_string())?;
+            publisher.publish_cluster_message(message).await?;
+        }
+        match stream.read(&mut temp_buffer).await {
+            Ok(n) if n > 0 => decoder.feed(&temp_buffer[..n]),
+            Ok(_) => return Ok(()),
+            Err(e) => return Err(e.to_string()),
+        }
+    }
+}
+
+// 노드마다 보내기 전용 링크 하나, 연결이 안 되면 메시지를 버리고 다음 메시지 때 다시 연결함
+// 응답이 오지 않으면 PING 타임아웃으로 장애가 감지되므로 링크 상태를 따로 알리지 않음
+#[derive(Default)]
+pub struct ClusterBus {
+    links: HashMap<String, mpsc::UnboundedSender<Vec<u8>>>,
+    // 다른 노드가 연결해 온 버스 연결 수, 받는 쪽은 연결마다 태스크만 있어서 serve_bus와 카운터를 나눠 가짐
+    inbound_links: Arc<AtomicUsize>,
+}
+
+impl ClusterBus {
+    pub fn send(&mut self, addr: &str, message: &BusMessage) {
+        let data = message.encode();
+        let link = self.links.entry(addr.to_string()).or_insert_with(|| Self::spawn_link(addr.to_string()));
+        if let Err(mpsc::error::SendError(data)) = link.send(data) {
+            let link = Self::spawn_link(addr.to_string());
+            let _ = link.send(data);
+            self.links.insert(addr.to_string(), link);
+        }
+    }
+
+    pub fn inbound_links(&self) -> Arc<AtomicUsize> {
+        self.inbound_links.clone()
+    }
+
+    // INFO clients의 cluster_connections, 이쪽에서 연 링크와 다른 노드가 연결해 온 링크를 함께 셈
+    pub fn connection_count(&self) -> usize {
+        self.links.values().filter(|link| !link.is_closed()).count() + self.inbound_links.load(Ordering::Relaxed)
+    }
+
+    fn spawn_link(addr: String) -> mpsc::UnboundedSender<Vec<u8>> {
+        let (sender, mut receiver) = mpsc::unbounded_channel::<Vec<u8>>();
+        logging::spawn(async move {
+            let mut stream: Option<TcpStream> = None;
+            while let Some(data) = receiver.recv().await {
+                if stream.is_none() {
+                    stream = match tokio::time::timeout(BUS_CONNECT_TIMEOUT, TcpStream::connect(&addr)).await {
+                        Ok(Ok(stream)) => Some(stream),
+                        _ => continue,
+                    };
+                }
+                if let Some(connection) = stream.as_mut() {
+                    if connection.write_all(&data).await.is_err() {
+                        stream = None;
+                    }
+                }
+            }
+        });
+        sender
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message() -> BusMessage {
+        BusMessage {
+            kind: BusMessageKind::FAIL,
+            sender_id: "a".repeat(40),
+            ip: "127.0.0.1".to_string(),
+            port: 7000,
+            bus_port: 17000,
+            current_epoch: 5,
+            config_epoch: 3,
+            slots: vec![(0, 5460), (10923, 10923)],
+            failed: Some("c".repeat(40)),
+            master: Some("d".repeat(40)),
+            gossip: vec![GossipEntry {
+                id: "b".repeat(40),
+                ip: "::1".to_string(),
+                port: 7001,
+                bus_port: 17001,
+                health: "pfail".to_string(),
+            }],
+        }
+    }
+
+    // 버스 연결에서 읽는 것처럼 프레임을 풀어서 인자 목록으로 바꿈
+    fn read_args(data: &[u8]) -> Vec<String> {
+        let mut decoder = FrameDecoder::new();
+        decoder.feed(data);
+        let frame = decoder.next_frame().unwrap().expect("a complete frame");
+        CommandParser::frame_args(&frame).unwrap().iter().map(|arg| String::from_utf8_lossy(arg).into_owned()).collect()
+    }
+
+    #[test]
+    fn messages_survive_an_encode_decode_round_trip() {
+        let sent = message();
+        let received = BusMessage::decode(&read_args(&sent.encode())).expect("a valid message");
+        assert_eq!(received.kind, BusMessageKind::FAIL);
+        assert_eq!(received.sender_id, sent.sender_id);
+        assert_eq!((received.ip.as_str(), received.port, received.bus_port), ("127.0.0.1", 7000, 17000));
+        assert_eq!((received.current_epoch, received.config_epoch), (5, 3));
+        assert_eq!(received.slots, sent.slots);
+        assert_eq!(received.failed, sent.failed);
+        assert_eq!(received.master, sent.master);
+        assert_eq!(received.gossip.len(), 1);
+        assert_eq!((received.gossip[0].ip.as_str(), received.gossip[0].port, received.gossip[0].health.as_str()), ("::1", 7001, "pfail"));
+        assert_eq!(received.encode(), sent.encode());
+
+        // 슬롯도 FAIL 대상도 마스터도 없으면 빈 필드로 보냄
+        let empty = BusMessage { kind: BusMessageKind::PING, slots: Vec::new(), failed: None, master: None, gossip: Vec::new(), ..message() };
+        let args = read_args(&empty.encode());
+        assert_eq!(args.len(), HEADER_FIELDS);
+        assert_eq!((args[7].as_str(), args[8].as_str(), args[9].as_str()), (EMPTY_FIELD, EMPTY_FIELD, EMPTY_FIELD));
+        let received = BusMessage::decode(&args).expect("a valid message");
+        assert!(received.slots.is_empty() && received.failed.is_none() && received.master.is_none() && received.gossip.is_empty());
+    }
+
+    #[test]
+    fn malformed_messages_are_rejected() {
+        let args = read_args(&message().encode());
+        assert!(BusMessage::decode(&args[..HEADER_FIELDS - 1]).is_none());
+        // 가십 항목이 잘려 있음
+        assert!(BusMessage::decode(&args[..args.len() - 1]).is_none());
+        let replace = |index: usize, value: &str| {
+            let mut args = args.clone();
+            args[index] = value.to_string();
+            BusMessage::decode(&args)
+        };
+        assert!(replace(0, "UPDATE").is_none());
+        assert!(replace(3, "70000").is_none());
+        assert!(replace(7, "0-5460,10923").is_none());
+        assert!(replace(HEADER_FIELDS + 2, "port").is_none());
+    }
+}
@@ -1,255 +1,2719 @@
+// 변형 이름은 Redis 명령/옵션 이름을 그대로 대문자로 씀
+#![allow(clippy::upper_case_acronyms)]
+
+use crate::client::Client;
+use crate::cluster::SlotState;
+use crate::command_registry::{self, CommandSpec, ExecutionContext, Executor, CMD_DENYOOM, CMD_WRITE};
+use crate::config_handler::{self, ConfigHandler, Db, RuntimeSettings};
+use crate::errors::RedisError;
 use crate::event_publisher::EventPublisher;
+use crate::eviction;
+use crate::lazyfree;
+use crate::lcs;
+use crate::logging::{self, log_notice, log_warning};
+use crate::notify;
+use crate::persistence;
 use crate::protocol_constants::*;
+use crate::random::Random;
+use crate::resp::RespValue;
+use crate::rdb_codec::{self, dump_payload, restore_payload};
+use crate::rdb_encoding;
 use crate::replication_config::ReplicationConfig;
-use crate::ValueEntry;
+use crate::scripting::FunctionRegistry;
+use crate::trace::TraceContext;
+use crate::tracking::TrackingOptions;
+use crate::util::{construct_redis_command, current_time_ms, glob_match};
+use crate::value_encoding::{HashValue, ListValue, StringValue, ZSetValue};
+use crate::value_entry::{RedisValue, ValueEntry};
 use std::collections::HashMap;
-use std::net::SocketAddr;
 use std::sync::Arc;
-use tokio::io::AsyncWriteExt;
-use tokio::net::tcp::OwnedWriteHalf;
+use std::time::Duration;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
 use tokio::sync::RwLock;
 
+// 만료된 키만 연달아 뽑히는 경우를 대비한 재시도 횟수
+const RANDOMKEY_MAX_ATTEMPTS: usize = 16;
+// SCAN 페이지 버퍼를 미리 잡을 때의 상한
+const SCAN_MAX_PREALLOCATED: usize = 1024;
+
+// BLPOP/BRPOP이 꺼낸 (키, 값)
+type PoppedElement = (Vec<u8>, Vec<u8>);
+
 pub enum Command {
-    PING,
-    ECHO(String),
-    GET(String),
-    SET { key: String, value: String, px: Option<u64>, ex: Option<u64> },
+    // 메시지가 있으면 PONG 대신 그대로 돌려줌
+    PING(Option<Vec<u8>>),
+    ECHO(Vec<u8>),
+    GET(Vec<u8>),
+    SET { key: Vec<u8>, value: Vec<u8>, expiration: Option<Expiration> },
+    GETSET { key: Vec<u8>, value: Vec<u8> },
+    // expiration이 있으면 TTL을 바꾸고, persist면 TTL을 지움, 둘 다 없으면 GET과 같음
+    GETEX { key: Vec<u8>, expiration: Option<Expiration>, persist: bool },
+    INCR(Vec<u8>),
+    // LEN과 IDX는 함께 쓸 수 없고, MINMATCHLEN과 WITHMATCHLEN은 IDX일 때만 의미가 있음
+    LCS { key1: Vec<u8>, key2: Vec<u8>, len: bool, idx: bool, min_match_len: usize, with_match_len: bool },
+    TYPE(Vec<u8>),
+    EXPIRE { key: Vec<u8>, seconds: i64, conditions: Vec<ExpireCondition> },
+    PEXPIRE { key: Vec<u8>, milliseconds: i64, conditions: Vec<ExpireCondition> },
+    EXPIREAT { key: Vec<u8>, timestamp: i64, conditions: Vec<ExpireCondition> },
+    PEXPIREAT { key: Vec<u8>, timestamp_ms: i64, conditions: Vec<ExpireCondition> },
+    TTL(Vec<u8>),
+    PTTL(Vec<u8>),
+    EXPIRETIME(Vec<u8>),
+    PEXPIRETIME(Vec<u8>),
+    PERSIST(Vec<u8>),
+    DEL(Vec<Vec<u8>>),
+    UNLINK(Vec<Vec<u8>>),
+    EXISTS(Vec<Vec<u8>>),
+    TOUCH(Vec<Vec<u8>>),
+    OBJECT(ObjectCommand),
+    DUMP(Vec<u8>),
+    DEBUG(DebugCommand),
+    LATENCY(LatencyCommand),
+    MEMORY(MemoryCommand),
+    SUBSCRIBE(Vec<String>),
+    UNSUBSCRIBE(Vec<String>),
+    PUBLISH { channel: String, message: Vec<u8> },
+    PSUBSCRIBE(Vec<String>),
+    PUNSUBSCRIBE(Vec<String>),
+    PUBSUB(PubSubCommand),
+    SSUBSCRIBE(Vec<String>),
+    SUNSUBSCRIBE(Vec<String>),
+    SPUBLISH { channel: String, message: Vec<u8> },
+    CLIENT(ClientCommand),
+    ACL(AclCommand),
+    CLUSTER(ClusterCommand),
+    ASKING,
+    // 클러스터 레플리카에서 마스터 슬롯의 읽기를 받을지 정함
+    READONLY,
+    READWRITE,
+    SENTINEL(SentinelCommand),
+    // protover가 없으면 프로토콜을 바꾸지 않고 현재 연결 정보만 돌려줌
+    HELLO { protover: Option<i64>, auth: Option<(String, String)>, setname: Option<String> },
+    // 사용자 이름이 없으면 default 사용자로 인증함
+    AUTH { username: Option<String>, password: String },
+    QUIT,
+    // 연결을 막 연결한 상태로 되돌림, MULTI 중에도 큐에 쌓지 않음
+    RESET,
+    MULTI,
+    EXEC,
+    DISCARD,
+    // EXEC 전에 이 키들이 바뀌면 트랜잭션을 실행하지 않고 nil을 돌려줌
+    WATCH(Vec<Vec<u8>>),
+    UNWATCH,
+    SCRIPT(ScriptCommand),
+    FUNCTION(FunctionCommand),
+    EVAL { script: String, keys: Vec<Vec<u8>>, args: Vec<Vec<u8>> },
+    // sha는 소문자로 바꿔 둠
+    EVALSHA { sha: String, keys: Vec<Vec<u8>>, args: Vec<Vec<u8>> },
+    FCALL { function: String, keys: Vec<Vec<u8>>, args: Vec<Vec<u8>> },
+    FCALLRO { function: String, keys: Vec<Vec<u8>>, args: Vec<Vec<u8>> },
+    HSET { key: Vec<u8>, fields: Vec<(Vec<u8>, Vec<u8>)> },
+    HGET { key: Vec<u8>, field: Vec<u8> },
+    HGETALL(Vec<u8>),
+    HRANDFIELD { key: Vec<u8>, count: Option<i64>, withvalues: bool },
+    HDEL { key: Vec<u8>, fields: Vec<Vec<u8>> },
+    HEXPIRE { key: Vec<u8>, seconds: i64, conditions: Vec<ExpireCondition>, fields: Vec<Vec<u8>> },
+    HPEXPIRE { key: Vec<u8>, milliseconds: i64, conditions: Vec<ExpireCondition>, fields: Vec<Vec<u8>> },
+    HTTL { key: Vec<u8>, fields: Vec<Vec<u8>> },
+    HPERSIST { key: Vec<u8>, fields: Vec<Vec<u8>> },
+    LPUSH { key: Vec<u8>, values: Vec<Vec<u8>> },
+    RPUSH { key: Vec<u8>, values: Vec<Vec<u8>> },
+    LPOP { key: Vec<u8>, count: Option<usize> },
+    RPOP { key: Vec<u8>, count: Option<usize> },
+    BLPOP { keys: Vec<Vec<u8>>, timeout_ms: u64 },
+    BRPOP { keys: Vec<Vec<u8>>, timeout_ms: u64 },
+    LMOVE { source: Vec<u8>, destination: Vec<u8>, from: ListDirection, to: ListDirection },
+    BLMOVE { source: Vec<u8>, destination: Vec<u8>, from: ListDirection, to: ListDirection, timeout_ms: u64 },
+    BRPOPLPUSH { source: Vec<u8>, destination: Vec<u8>, timeout_ms: u64 },
+    LMPOP { keys: Vec<Vec<u8>>, direction: ListDirection, count: usize },
+    BLMPOP { keys: Vec<Vec<u8>>, direction: ListDirection, count: usize, timeout_ms: u64 },
+    ZADD { key: Vec<u8>, members: Vec<(f64, Vec<u8>)>, incr: bool },
+    ZINCRBY { key: Vec<u8>, increment: f64, member: Vec<u8> },
+    ZMPOP { keys: Vec<Vec<u8>>, direction: ScoreDirection, count: usize },
+    BZMPOP { keys: Vec<Vec<u8>>, direction: ScoreDirection, count: usize, timeout_ms: u64 },
+    ZRANDMEMBER { key: Vec<u8>, count: Option<i64>, withscores: bool },
+    ZSCORE { key: Vec<u8>, member: Vec<u8> },
+    RESTORE {
+        key: Vec<u8>,
+        ttl_ms: i64,
+        payload: Vec<u8>,
+        replace: bool,
+        absttl: bool,
+        idle_seconds: Option<u64>,
+        frequency: Option<u8>,
+        // RESTORE-ASKING: 받아 오는 중인 슬롯의 키를 ASKING 없이 받음
+        asking: bool,
+    },
+    // 대상 DB는 하나뿐이라 0만 받음, auth는 (사용자, 비밀번호)
+    MIGRATE {
+        host: String,
+        port: u16,
+        keys: Vec<Vec<u8>>,
+        timeout_ms: u64,
+        copy: bool,
+        replace: bool,
+        auth: Option<(Option<String>, String)>,
+    },
     CONFIG(ConfigCommand),
-    KEYS(String),
-    INFO(String),
+    // namespace는 파서가 아니라 namespace::confine이 채움, 그 접두사 아래의 키만 접두사를 떼고 보여 줌
+    KEYS { pattern: Vec<u8>, namespace: Option<Vec<u8>> },
+    RANDOMKEY,
+    DBSIZE(Option<Vec<u8>>),
+    FLUSHDB(FlushMode),
+    FLUSHALL(FlushMode),
+    SCAN { cursor: u64, pattern: Option<Vec<u8>>, count: usize, type_filter: Option<String>, namespace: Option<Vec<u8>> },
+    INFO(Option<String>),
     REPLCONF(Vec<String>),
     PSYNC(Vec<String>),
+    REPLICAOF(Option<(String, u16)>),
+    SLAVEOF(Option<(String, u16)>),
+    WAIT { numreplicas: usize, timeout_ms: u64 },
+    BGSAVE,
+    LASTSAVE,
+    // SAVE면 Some(true), NOSAVE면 Some(false), 지정하지 않으면 저장 지점 설정을 따름
+    SHUTDOWN(Option<bool>),
 }
 
 pub enum ConfigCommand {
-    GET(String),
+    // CONFIG GET pattern [pattern ...]
+    GET(Vec<String>),
+    // CONFIG SET name value [name value ...]
+    SET(Vec<(String, String)>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlushMode {
+    SYNC,
+    ASYNC,
+}
+
+impl FlushMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FlushMode::SYNC => SYNC_OPTION,
+            FlushMode::ASYNC => ASYNC_OPTION,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ClientCommand {
+    ID,
+    TRACKING(Option<TrackingOptions>),
+    GETREDIR,
+    // TYPE으로 거르지 않으면 None
+    LIST(Option<ClientType>),
+    // 빈 이름이면 이름을 지움
+    SETNAME(String),
+    GETNAME,
+    // yes는 OPTIN, no는 OPTOUT 모드에서 바로 다음 명령의 추적 여부를 바꿈
+    CACHING(bool),
+    // 예전 형식(CLIENT KILL ip:port)은 legacy, 찾으면 OK 못 찾으면 에러이고 새 형식은 닫은 수를 돌려줌
+    KILL { filter: ClientKillFilter, legacy: bool },
+}
+
+#[derive(Debug)]
+pub enum AclCommand {
+    // 규칙은 순서대로 적용하며, 없는 사용자면 꺼져 있고 권한이 없는 상태에서 시작함
+    SETUSER { username: String, rules: Vec<String> },
+    GETUSER(String),
+    DELUSER(Vec<String>),
+    LIST,
+    USERS,
+    WHOAMI,
+}
+
+// 주어진 조건을 모두 만족하는 연결을 닫음, skip_me면 명령을 보낸 연결은 빼고
+#[derive(Debug)]
+pub struct ClientKillFilter {
+    pub id: Option<u64>,
+    pub addr: Option<String>,
+    pub laddr: Option<String>,
+    pub client_type: Option<ClientType>,
+    pub user: Option<String>,
+    // MAXAGE 0은 Redis처럼 나이로 거르지 않음
+    pub max_age_secs: Option<u64>,
+    pub skip_me: bool,
+}
+
+#[derive(Debug)]
+pub enum ClusterCommand {
+    INFO,
+    MYID,
+    NODES,
+    SLOTS,
+    SHARDS,
+    KEYSLOT(Vec<u8>),
+    // ADDSLOTSRANGE/DELSLOTSRANGE도 구간을 풀어서 슬롯 목록으로 받음
+    ADDSLOTS(Vec<u16>),
+    DELSLOTS(Vec<u16>),
+    SETSLOT { slot: u16, state: SlotState },
+    COUNTKEYSINSLOT(u16),
+    GETKEYSINSLOT { slot: u16, count: usize },
+    MEET { ip: String, port: u16, bus_port: u16 },
+    REPLICATE(String),
+    // SLAVES도 REPLICAS로 받음
+    REPLICAS(String),
+}
+
+#[derive(Debug)]
+pub enum SentinelCommand {
+    MASTERS,
+    MASTER(String),
+    // SLAVES도 REPLICAS로 받음
+    REPLICAS(String),
+    SENTINELS(String),
+    GETMASTERADDRBYNAME(String),
+    ISMASTERDOWNBYADDR { ip: String, port: u16, epoch: u64, runid: String },
+    CKQUORUM(String),
+    FAILOVER(String),
+    MYID,
+    MONITOR { name: String, ip: String, port: u16, quorum: usize },
+    REMOVE(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClientType {
+    NORMAL,
+    MASTER,
+    REPLICA,
+    PUBSUB,
+}
+
+#[derive(Debug)]
+pub enum PubSubCommand {
+    CHANNELS(Option<String>),
+    NUMSUB(Vec<String>),
+    NUMPAT,
+    SHARDCHANNELS(Option<String>),
+    SHARDNUMSUB(Vec<String>),
+}
+
+#[derive(Debug)]
+pub enum ScriptCommand {
+    LOAD(String),
+    EXISTS(Vec<String>),
+    FLUSH(FlushMode),
+    // 스크립트가 도는 동안에는 연결 태스크가 처리하므로 이벤트 루프에 오면 항상 NOTBUSY
+    KILL,
+}
+
+#[derive(Debug)]
+pub enum FunctionCommand {
+    LOAD { code: String, replace: bool },
+    LIST { pattern: Option<String>, with_code: bool },
+    DELETE(String),
+    FLUSH(FlushMode),
+    KILL,
+}
+
+#[derive(Debug)]
+pub enum DebugCommand {
+    REPORT,
+    // 클라이언트 라이브러리 시험용으로 요청한 RESP 타입의 응답을 돌려줌
+    PROTOCOL(String),
+    // 일부러 이벤트 루프를 멈춤, 밀리초
+    SLEEP(u64),
+    SETACTIVEEXPIRE(bool),
+    OBJECT(Vec<u8>),
+    CHANGEREPLID,
+    // RDB 파일로 저장한 뒤 키스페이스와 함수를 비우고 다시 읽음
+    RELOAD,
+}
+
+#[derive(Debug)]
+pub enum LatencyCommand {
+    LATEST,
+    HISTORY(String),
+    // 이벤트를 주지 않으면 모든 이벤트를 지움
+    RESET(Vec<String>),
+    DOCTOR,
+}
+
+#[derive(Debug)]
+pub enum MemoryCommand {
+    // samples가 0이면 컬렉션의 모든 원소를 셈
+    USAGE { key: Vec<u8>, samples: usize },
+    STATS,
+    DOCTOR,
+}
+
+#[derive(Debug)]
+pub enum ObjectCommand {
+    ENCODING(Vec<u8>),
+    IDLETIME(Vec<u8>),
+    FREQ(Vec<u8>),
+    REFCOUNT(Vec<u8>),
+    HELP,
+}
+
+// SET과 GETEX의 만료 옵션, EX/PX는 지금부터의 시간이고 EXAT/PXAT은 유닉스 시각
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Expiration {
+    EX(u64),
+    PX(u64),
+    EXAT(u64),
+    PXAT(u64),
+}
+
+impl Expiration {
+    // 만료 시각(ms)으로 바꿈, Redis처럼 0이나 시각으로 바꾸다 넘치는 값은 거부함
+    fn deadline_ms(self, command: &str) -> Result<u64, RedisError> {
+        let now_ms = current_time_ms();
+        let (amount, deadline_ms) = match self {
+            Expiration::EX(seconds) => (seconds, seconds.checked_mul(1000).and_then(|ms| ms.checked_add(now_ms))),
+            Expiration::PX(ms) => (ms, ms.checked_add(now_ms)),
+            Expiration::EXAT(seconds) => (seconds, seconds.checked_mul(1000)),
+            Expiration::PXAT(ms) => (ms, Some(ms)),
+        };
+        match deadline_ms.filter(|deadline_ms| amount > 0 && i64::try_from(*deadline_ms).is_ok()) {
+            Some(deadline_ms) => Ok(deadline_ms),
+            None => Err(format!("invalid expire time in '{}' command", command.to_lowercase()).into()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExpireCondition {
+    NX,
+    XX,
+    GT,
+    LT,
+}
+
+impl ExpireCondition {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExpireCondition::NX => NX_OPTION,
+            ExpireCondition::XX => XX_OPTION,
+            ExpireCondition::GT => GT_OPTION,
+            ExpireCondition::LT => LT_OPTION,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListDirection {
+    LEFT,
+    RIGHT,
+}
+
+impl ListDirection {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ListDirection::LEFT => LEFT_OPTION,
+            ListDirection::RIGHT => RIGHT_OPTION,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScoreDirection {
+    MIN,
+    MAX,
+}
+
+impl ScoreDirection {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScoreDirection::MIN => MIN_OPTION,
+            ScoreDirection::MAX => MAX_OPTION,
+        }
+    }
+}
+
+// LMPOP/ZMPOP 계열이 한 키에서 꺼낸 결과, 직접 실행과 블로킹 처리에서 같이 사용함
+pub struct MultiPopOutcome {
+    pub key: Vec<u8>,
+    pub response: RespValue,
+    pub replication: Vec<u8>,
+    pub class: u32,
+    pub event: &'static str,
+    pub key_removed: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandCategory {
+    Connection,
+    Read,
+    Write,
+    Admin,
+    PubSub,
+    Scripting,
+}
+
+impl CommandCategory {
+    pub fn from_name(name: &str) -> Option<CommandCategory> {
+        match name.to_lowercase().as_str() {
+            "connection" => Some(CommandCategory::Connection),
+            "read" => Some(CommandCategory::Read),
+            "write" => Some(CommandCategory::Write),
+            "admin" => Some(CommandCategory::Admin),
+            "pubsub" => Some(CommandCategory::PubSub),
+            "scripting" => Some(CommandCategory::Scripting),
+            _ => None,
+        }
+    }
 }
 
 pub enum CommandResponse {
-    Simple(String),
-    Bulk(Vec<u8>),
-    EndStream,
+    Value(RespValue),
+    // FULLRESYNC 뒤의 RDB, bulk string과 달리 끝에 CRLF가 없음
+    Rdb(Vec<u8>),
 }
 
 impl Command {
-    pub async fn handle_command(
+    pub fn name(&self) -> &'static str {
+        match self {
+            Command::PING(_) => PING_COMMAND,
+            Command::ECHO(_) => ECHO_COMMAND,
+            Command::GET(_) => GET_COMMAND,
+            Command::SET { .. } => SET_COMMAND,
+            Command::GETSET { .. } => GETSET_COMMAND,
+            Command::GETEX { .. } => GETEX_COMMAND,
+            Command::INCR(_) => INCR_COMMAND,
+            Command::LCS { .. } => LCS_COMMAND,
+            Command::TYPE(_) => TYPE_COMMAND,
+            Command::EXPIRE { .. } => EXPIRE_COMMAND,
+            Command::PEXPIRE { .. } => PEXPIRE_COMMAND,
+            Command::EXPIREAT { .. } => EXPIREAT_COMMAND,
+            Command::PEXPIREAT { .. } => PEXPIREAT_COMMAND,
+            Command::TTL(_) => TTL_COMMAND,
+            Command::PTTL(_) => PTTL_COMMAND,
+            Command::EXPIRETIME(_) => EXPIRETIME_COMMAND,
+            Command::PEXPIRETIME(_) => PEXPIRETIME_COMMAND,
+            Command::PERSIST(_) => PERSIST_COMMAND,
+            Command::DEL(_) => DEL_COMMAND,
+            Command::UNLINK(_) => UNLINK_COMMAND,
+            Command::EXISTS(_) => EXISTS_COMMAND,
+            Command::TOUCH(_) => TOUCH_COMMAND,
+            Command::HSET { .. } => HSET_COMMAND,
+            Command::HGET { .. } => HGET_COMMAND,
+            Command::HGETALL(_) => HGETALL_COMMAND,
+            Command::HRANDFIELD { .. } => HRANDFIELD_COMMAND,
+            Command::HDEL { .. } => HDEL_COMMAND,
+            Command::HEXPIRE { .. } => HEXPIRE_COMMAND,
+            Command::HPEXPIRE { .. } => HPEXPIRE_COMMAND,
+            Command::HTTL { .. } => HTTL_COMMAND,
+            Command::HPERSIST { .. } => HPERSIST_COMMAND,
+            Command::LPUSH { .. } => LPUSH_COMMAND,
+            Command::RPUSH { .. } => RPUSH_COMMAND,
+            Command::LPOP { .. } => LPOP_COMMAND,
+            Command::RPOP { .. } => RPOP_COMMAND,
+            Command::BLPOP { .. } => BLPOP_COMMAND,
+            Command::BRPOP { .. } => BRPOP_COMMAND,
+            Command::LMOVE { .. } => LMOVE_COMMAND,
+            Command::BLMOVE { .. } => BLMOVE_COMMAND,
+            Command::BRPOPLPUSH { .. } => BRPOPLPUSH_COMMAND,
+            Command::LMPOP { .. } => LMPOP_COMMAND,
+            Command::BLMPOP { .. } => BLMPOP_COMMAND,
+            Command::ZADD { .. } => ZADD_COMMAND,
+            Command::ZMPOP { .. } => ZMPOP_COMMAND,
+            Command::BZMPOP { .. } => BZMPOP_COMMAND,
+            Command::ZRANDMEMBER { .. } => ZRANDMEMBER_COMMAND,
+            Command::ZSCORE { .. } => ZSCORE_COMMAND,
+            Command::ZINCRBY { .. } => ZINCRBY_COMMAND,
+            Command::OBJECT(_) => OBJECT_COMMAND,
+            Command::DUMP(_) => DUMP_COMMAND,
+            Command::DEBUG(_) => DEBUG_COMMAND,
+            Command::LATENCY(_) => LATENCY_COMMAND,
+            Command::MEMORY(_) => MEMORY_COMMAND,
+            Command::SUBSCRIBE(_) => SUBSCRIBE_COMMAND,
+            Command::UNSUBSCRIBE(_) => UNSUBSCRIBE_COMMAND,
+            Command::PUBLISH { .. } => PUBLISH_COMMAND,
+            Command::PSUBSCRIBE(_) => PSUBSCRIBE_COMMAND,
+            Command::PUNSUBSCRIBE(_) => PUNSUBSCRIBE_COMMAND,
+            Command::PUBSUB(_) => PUBSUB_COMMAND,
+            Command::SSUBSCRIBE(_) => SSUBSCRIBE_COMMAND,
+            Command::SUNSUBSCRIBE(_) => SUNSUBSCRIBE_COMMAND,
+            Command::SPUBLISH { .. } => SPUBLISH_COMMAND,
+            Command::CLIENT(_) => CLIENT_COMMAND,
+            Command::ACL(_) => ACL_COMMAND,
+            Command::CLUSTER(_) => CLUSTER_COMMAND,
+            Command::SENTINEL(_) => SENTINEL_COMMAND,
+            Command::HELLO { .. } => HELLO_COMMAND,
+            Command::AUTH { .. } => AUTH_COMMAND,
+            Command::QUIT => QUIT_COMMAND,
+            Command::RESET => RESET_COMMAND,
+            Command::ASKING => ASKING_COMMAND,
+            Command::READONLY => READONLY_COMMAND,
+            Command::READWRITE => READWRITE_COMMAND,
+            Command::MULTI => MULTI_COMMAND,
+            Command::EXEC => EXEC_COMMAND,
+            Command::DISCARD => DISCARD_COMMAND,
+            Command::WATCH(_) => WATCH_COMMAND,
+            Command::UNWATCH => UNWATCH_COMMAND,
+            Command::SCRIPT(_) => SCRIPT_COMMAND,
+            Command::FUNCTION(_) => FUNCTION_COMMAND,
+            Command::EVAL { .. } => EVAL_COMMAND,
+            Command::EVALSHA { .. } => EVALSHA_COMMAND,
+            Command::FCALL { .. } => FCALL_COMMAND,
+            Command::FCALLRO { .. } => FCALL_RO_COMMAND,
+            Command::RESTORE { asking: false, .. } => RESTORE_COMMAND,
+            Command::RESTORE { asking: true, .. } => RESTORE_ASKING_COMMAND,
+            Command::MIGRATE { .. } => MIGRATE_COMMAND,
+            Command::CONFIG(_) => CONFIG_COMMAND,
+            Command::KEYS { .. } => KEYS_COMMAND,
+            Command::SCAN { .. } => SCAN_COMMAND,
+            Command::RANDOMKEY => RANDOMKEY_COMMAND,
+            Command::DBSIZE(_) => DBSIZE_COMMAND,
+            Command::FLUSHDB(_) => FLUSHDB_COMMAND,
+            Command::FLUSHALL(_) => FLUSHALL_COMMAND,
+            Command::INFO(_) => INFO_COMMAND,
+            Command::REPLCONF(_) => REPLCONF_COMMAND,
+            Command::PSYNC(_) => PSYNC_COMMAND,
+            Command::REPLICAOF(_) => REPLICAOF_COMMAND,
+            Command::SLAVEOF(_) => SLAVEOF_COMMAND,
+            Command::WAIT { .. } => WAIT_COMMAND,
+            Command::BGSAVE => BGSAVE_COMMAND,
+            Command::LASTSAVE => LASTSAVE_COMMAND,
+            Command::SHUTDOWN(_) => SHUTDOWN_COMMAND,
+        }
+    }
+
+    // 플래그와 arity는 레지스트리의 명령 테이블에 있음
+    pub fn spec(&self) -> &'static CommandSpec {
+        command_registry::handler(self.name()).spec()
+    }
+
+    pub fn executor(&self) -> Executor {
+        command_registry::handler(self.name()).executor()
+    }
+
+    pub fn category(&self) -> CommandCategory {
+        self.spec().category()
+    }
+
+    // 명령 테이블의 플래그에 하위 명령의 플래그를 더해 봄, FUNCTION은 LOAD/DELETE/FLUSH만 함수 목록을 바꿈
+    pub fn has_flag(&self, flag: u32) -> bool {
+        let subcommand_flags = match self {
+            Command::FUNCTION(FunctionCommand::LOAD { .. }) => CMD_WRITE | CMD_DENYOOM,
+            Command::FUNCTION(FunctionCommand::DELETE(_) | FunctionCommand::FLUSH(_)) => CMD_WRITE,
+            _ => 0,
+        };
+        (self.spec().flags | subcommand_flags) & flag != 0
+    }
+
+    // 키를 다루는 명령의 대상 키, 클라이언트 추적과 무효화에 사용함
+    pub fn keys(&self) -> Vec<&Vec<u8>> {
+        match self {
+            Command::GET(key)
+            | Command::SET { key, .. }
+            | Command::GETSET { key, .. }
+            | Command::GETEX { key, .. }
+            | Command::INCR(key)
+            | Command::TYPE(key)
+            | Command::EXPIRE { key, .. }
+            | Command::PEXPIRE { key, .. }
+            | Command::EXPIREAT { key, .. }
+            | Command::PEXPIREAT { key, .. }
+            | Command::TTL(key)
+            | Command::PTTL(key)
+            | Command::EXPIRETIME(key)
+            | Command::PEXPIRETIME(key)
+            | Command::PERSIST(key)
+            | Command::DUMP(key)
+            | Command::RESTORE { key, .. }
+            | Command::HSET { key, .. }
+            | Command::HGET { key, .. }
+            | Command::HGETALL(key)
+            | Command::HRANDFIELD { key, .. }
+            | Command::HDEL { key, .. }
+            | Command::HEXPIRE { key, .. }
+            | Command::HPEXPIRE { key, .. }
+            | Command::HTTL { key, .. }
+            | Command::HPERSIST { key, .. }
+            | Command::LPUSH { key, .. }
+            | Command::RPUSH { key, .. }
+            | Command::LPOP { key, .. }
+            | Command::RPOP { key, .. }
+            | Command::ZADD { key, .. }
+            | Command::ZRANDMEMBER { key, .. }
+            | Command::ZSCORE { key, .. }
+            | Command::ZINCRBY { key, .. } => vec![key],
+            Command::OBJECT(
+                ObjectCommand::ENCODING(key)
+                | ObjectCommand::IDLETIME(key)
+                | ObjectCommand::FREQ(key)
+                | ObjectCommand::REFCOUNT(key),
+            ) => vec![key],
+            Command::MEMORY(MemoryCommand::USAGE { key, .. }) => vec![key],
+            Command::DEBUG(DebugCommand::OBJECT(key)) => vec![key],
+            Command::DEL(keys)
+            | Command::UNLINK(keys)
+            | Command::EXISTS(keys)
+            | Command::TOUCH(keys)
+            | Command::WATCH(keys)
+            | Command::MIGRATE { keys, .. } => keys.iter().collect(),
+            Command::LCS { key1, key2, .. } => vec![key1, key2],
+            Command::BLPOP { keys, .. }
+            | Command::BRPOP { keys, .. }
+            | Command::LMPOP { keys, .. }
+            | Command::BLMPOP { keys, .. }
+            | Command::ZMPOP { keys, .. }
+            | Command::BZMPOP { keys, .. } => keys.iter().collect(),
+            Command::LMOVE { source, destination, .. }
+            | Command::BLMOVE { source, destination, .. }
+            | Command::BRPOPLPUSH { source, destination, .. } => vec![source, destination],
+            Command::EVAL { keys, .. }
+            | Command::EVALSHA { keys, .. }
+            | Command::FCALL { keys, .. }
+            | Command::FCALLRO { keys, .. } => keys.iter().collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    // keys()와 같은 키를 고칠 수 있게 돌려줌, 네임스페이스 접두사를 붙일 때 씀
+    pub fn keys_mut(&mut self) -> Vec<&mut Vec<u8>> {
+        match self {
+            Command::GET(key)
+            | Command::SET { key, .. }
+            | Command::GETSET { key, .. }
+            | Command::GETEX { key, .. }
+            | Command::INCR(key)
+            | Command::TYPE(key)
+            | Command::EXPIRE { key, .. }
+            | Command::PEXPIRE { key, .. }
+            | Command::EXPIREAT { key, .. }
+            | Command::PEXPIREAT { key, .. }
+            | Command::TTL(key)
+            | Command::PTTL(key)
+            | Command::EXPIRETIME(key)
+            | Command::PEXPIRETIME(key)
+            | Command::PERSIST(key)
+            | Command::DUMP(key)
+            | Command::RESTORE { key, .. }
+            | Command::HSET { key, .. }
+            | Command::HGET { key, .. }
+            | Command::HGETALL(key)
+            | Command::HRANDFIELD { key, .. }
+            | Command::HDEL { key, .. }
+            | Command::HEXPIRE { key, .. }
+            | Command::HPEXPIRE { key, .. }
+            | Command::HTTL { key, .. }
+            | Command::HPERSIST { key, .. }
+            | Command::LPUSH { key, .. }
+            | Command::RPUSH { key, .. }
+            | Command::LPOP { key, .. }
+            | Command::RPOP { key, .. }
+            | Command::ZADD { key, .. }
+            | Command::ZRANDMEMBER { key, .. }
+            | Command::ZSCORE { key, .. }
+            | Command::ZINCRBY { key, .. } => vec![key],
+            Command::OBJECT(
+                ObjectCommand::ENCODING(key)
+                | ObjectCommand::IDLETIME(key)
+                | ObjectCommand::FREQ(key)
+                | ObjectCommand::REFCOUNT(key),
+            ) => vec![key],
+            Command::MEMORY(MemoryCommand::USAGE { key, .. }) => vec![key],
+            Command::DEBUG(DebugCommand::OBJECT(key)) => vec![key],
+            Command::DEL(keys)
+            | Command::UNLINK(keys)
+            | Command::EXISTS(keys)
+            | Command::TOUCH(keys)
+            | Command::WATCH(keys)
+            | Command::MIGRATE { keys, .. } => keys.iter_mut().collect(),
+            Command::LCS { key1, key2, .. } => vec![key1, key2],
+            Command::BLPOP { keys, .. }
+            | Command::BRPOP { keys, .. }
+            | Command::LMPOP { keys, .. }
+            | Command::BLMPOP { keys, .. }
+            | Command::ZMPOP { keys, .. }
+            | Command::BZMPOP { keys, .. } => keys.iter_mut().collect(),
+            Command::LMOVE { source, destination, .. }
+            | Command::BLMOVE { source, destination, .. }
+            | Command::BRPOPLPUSH { source, destination, .. } => vec![source, destination],
+            Command::EVAL { keys, .. }
+            | Command::EVALSHA { keys, .. }
+            | Command::FCALL { keys, .. }
+            | Command::FCALLRO { keys, .. } => keys.iter_mut().collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    // 발행하거나 구독하는 채널, ACL의 채널 패턴 검사에 사용함. PSUBSCRIBE의 패턴은 따로 검사함
+    pub fn channels(&self) -> Vec<&String> {
+        match self {
+            Command::SUBSCRIBE(channels) | Command::SSUBSCRIBE(channels) => channels.iter().collect(),
+            Command::PUBLISH { channel, .. } | Command::SPUBLISH { channel, .. } => vec![channel],
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn deprecation(&self) -> Option<&'static str> {
+        match self {
+            Command::GETSET { .. } => Some("SET key value GET"),
+            Command::SLAVEOF(_) => Some("REPLICAOF host port"),
+            Command::BRPOPLPUSH { .. } => Some("BLMOVE source destination RIGHT LEFT timeout"),
+            Command::ZADD { incr: true, .. } => Some("ZINCRBY key increment member"),
+            _ => None,
+        }
+    }
+
+    // 데이터가 없으면 이벤트 핸들러에서 클라이언트를 대기시키는 명령, 0이면 무한히 기다림
+    pub fn blocking_timeout_ms(&self) -> Option<u64> {
+        match self {
+            Command::BLPOP { timeout_ms, .. }
+            | Command::BRPOP { timeout_ms, .. }
+            | Command::BLMOVE { timeout_ms, .. }
+            | Command::BRPOPLPUSH { timeout_ms, .. }
+            | Command::BLMPOP { timeout_ms, .. }
+            | Command::BZMPOP { timeout_ms, .. } => Some(*timeout_ms),
+            _ => None,
+        }
+    }
+
+    // 블로킹 명령이 기다리는 값의 타입, 다른 타입의 키가 써져도 깨우지 않음
+    pub fn blocking_value_type(&self) -> &'static str {
+        match self {
+            Command::BZMPOP { .. } => "zset",
+            _ => "list",
+        }
+    }
+
+    // 블로킹 명령이 기다리는 키, BLMOVE는 destination이 아니라 source에 데이터가 들어와야 깨어남
+    pub fn blocking_keys(&self) -> Vec<&Vec<u8>> {
+        match self {
+            Command::BLMOVE { source, .. } | Command::BRPOPLPUSH { source, .. } => vec![source],
+            command => command.keys(),
+        }
+    }
+
+    // 타임아웃이나 MULTI 안에서 꺼낼 것이 없을 때의 응답
+    pub fn blocking_nil_response(&self) -> RespValue {
+        match self {
+            Command::BLPOP { .. } | Command::BRPOP { .. } | Command::BLMPOP { .. } | Command::BZMPOP { .. } => RespValue::NullArray,
+            _ => RespValue::NullBulk,
+        }
+    }
+
+    // LMOVE 계열의 (source, destination, from, to), BRPOPLPUSH는 RIGHT LEFT로 동작함
+    pub fn move_args(&self) -> (&Vec<u8>, &Vec<u8>, ListDirection, ListDirection) {
+        match self {
+            Command::LMOVE { source, destination, from, to } | Command::BLMOVE { source, destination, from, to, .. } => {
+                (source, destination, *from, *to)
+            }
+            Command::BRPOPLPUSH { source, destination, .. } => (source, destination, ListDirection::RIGHT, ListDirection::LEFT),
+            _ => unreachable!("not a list move command"),
+        }
+    }
+
+    // (쓴 바이트 수, 에러로 응답했는지)를 돌려줌
+    pub async fn handle_command<W: AsyncWrite + Unpin + ?Sized>(
         &self,
-        writer: &mut OwnedWriteHalf,
-        db: &Arc<RwLock<HashMap<String, ValueEntry>>>,
-        config: &Arc<RwLock<HashMap<String, String>>>,
-        replication_config: &Arc<RwLock<ReplicationConfig>>,
-        peer_addr: SocketAddr,
-        publisher: &EventPublisher,
-    ) -> std::io::Result<()> {
-        match self.execute(db, config, replication_config, peer_addr, publisher).await {
+        writer: &mut W,
+        context: ExecutionContext<'_>,
+        protocol: u8,
+    ) -> std::io::Result<(usize, bool)> {
+        let mut written = 0;
+        let mut failed = false;
+        match self.execute(context).await {
             Ok(responses) => {
                 for response in responses {
                     match response {
-                        CommandResponse::Simple(response) => {
-                            writer.write_all(response.as_bytes()).await?;
+                        CommandResponse::Value(value) => {
+                            failed |= matches!(value, RespValue::Error(_));
+                            let response = value.encode(protocol);
+                            writer.write_all(&response).await?;
+                            written += response.len();
                         }
-                        CommandResponse::Bulk(data) => {
-                            let header = format!("${}{}", data.len(), CRLF);
-                            writer.write_all(header.as_bytes()).await?;
-                            writer.write_all(&data).await?;
-                        }
-                        CommandResponse::EndStream => break,
+                        CommandResponse::Rdb(data) => written += Self::write_payload(writer, &data).await?,
                     }
                 }
             }
             Err(e) => {
-                let err_response = format!("-ERR {}\r\n", e);
-                writer.write_all(err_response.as_bytes()).await?;
+                let response = RespValue::from(e).encode(protocol);
+                writer.write_all(&response).await?;
+                written += response.len();
+                failed = true;
             }
         }
-        Ok(())
+        Ok((written, failed))
     }
 
-    pub async fn execute(
-        &self,
-        db: &Arc<RwLock<HashMap<String, ValueEntry>>>,
-        config: &Arc<RwLock<HashMap<String, String>>>,
-        replication_config: &Arc<RwLock<ReplicationConfig>>,
-        peer_addr: SocketAddr,
-        publisher: &EventPublisher,
-    ) -> Result<Vec<CommandResponse>, String> {
-        match self {
-            Command::PING => Ok(vec![CommandResponse::Simple(format!(
-                "{}PONG{}",
-                SIMPLE_STRING_PREFIX, CRLF
-            ))]),
-            Command::ECHO(echo_message) => Ok(vec![CommandResponse::Simple(format!(
-                "{}{}{}{}{}",
-                BULK_STRING_PREFIX,
-                echo_message.len(),
-                CRLF,
-                echo_message,
-                CRLF
-            ))]),
-            Command::GET(key) => {
-                let db = db.read().await;
-                Ok(vec![CommandResponse::Simple(
-                    Self::execute_get(key, &db).await,
-                )])
-            }
-            Command::SET { key, value, ex, px } => {
-                let role = replication_config.read().await.get_role().await;
-                let mut db = db.write().await;
+    // 길이 헤더와 페이로드만 씀, RDB 전송은 bulk string과 달리 끝에 CRLF가 없음
+    async fn write_payload<W: AsyncWrite + Unpin + ?Sized>(writer: &mut W, data: &[u8]) -> std::io::Result<usize> {
+        let header = format!("{}{}{}", BULK_STRING_PREFIX, data.len(), CRLF);
+        writer.write_all(header.as_bytes()).await?;
+        // FULLRESYNC의 RDB처럼 큰 페이로드는 나눠서 써서 한 번에 소켓 버퍼를 채우지 않게 함
+        for chunk in data.chunks(BULK_WRITE_CHUNK_SIZE) {
+            writer.write_all(chunk).await?;
+        }
+        Ok(header.len() + data.len())
+    }
 
-                if role == "slave" {
-                    let response = Self::execute_set(key, value, *ex, *px, &mut db).await;
-                    return Ok(vec![CommandResponse::Simple(response)]);
-                }
+    pub(crate) async fn run_ping(&self, _context: ExecutionContext<'_>) -> Result<Vec<CommandResponse>, RedisError> {
+        let Command::PING(message) = self else {
+            unreachable!("not a PING command");
+        };
+        match message {
+            None => Ok(vec![CommandResponse::Value(RespValue::SimpleString("PONG".into()))]),
+            Some(message) => Ok(vec![CommandResponse::Value(RespValue::bulk(message.as_slice()))]),
+        }
+    }
 
-                let response = Self::execute_set(key, value, *ex, *px, &mut db).await;
+    pub(crate) async fn run_echo(&self, _context: ExecutionContext<'_>) -> Result<Vec<CommandResponse>, RedisError> {
+        let Command::ECHO(echo_message) = self else {
+            unreachable!("not an ECHO command");
+        };
+        Ok(vec![CommandResponse::Value(RespValue::bulk(echo_message.as_slice()))])
+    }
 
-                let replicated_command = format!(
-                    "*3\r\n$3\r\nSET\r\n${}\r\n{}\r\n${}\r\n{}\r\n",
-                    key.len(),
-                    key,
-                    value.len(),
-                    value
-                );
+    pub(crate) async fn run_get(&self, context: ExecutionContext<'_>) -> Result<Vec<CommandResponse>, RedisError> {
+        let ExecutionContext { db, publisher, trace, .. } = context;
+        let Command::GET(key) = self else {
+            unreachable!("not a GET command");
+        };
+        Self::expire_on_access(&[key], db, publisher, trace).await?;
+        let db = db.read().await;
+        Ok(vec![CommandResponse::Value(Self::execute_get(key, &db).await?)])
+    }
 
-                publisher.publish_propagate_slave(replicated_command).await
-                    .map_err(|e| format!("Failed to propagate command to slaves: {}", e))?;
+    pub(crate) async fn run_lcs(&self, context: ExecutionContext<'_>) -> Result<Vec<CommandResponse>, RedisError> {
+        let ExecutionContext { db, config, publisher, trace, .. } = context;
+        let Command::LCS { key1, key2, .. } = self else {
+            unreachable!("not an LCS command");
+        };
+        Self::expire_on_access(&[key1, key2], db, publisher, trace).await?;
+        let max_table_size = config
+            .read()
+            .await
+            .get("proto_max_bulk_len")
+            .and_then(|len| eviction::parse_memory(len).ok())
+            .unwrap_or(DEFAULT_PROTO_MAX_BULK_LEN as u64);
+        let db = db.read().await;
+        Ok(vec![CommandResponse::Value(self.execute_lcs(&db, max_table_size)?)])
+    }
 
-                Ok(vec![CommandResponse::Simple(response)])
-            }
-            Command::CONFIG(command) => Ok(vec![CommandResponse::Simple(
-                Self::execute_config(command, config).await,
-            )]),
-            Command::KEYS(_pattern) => Ok(vec![CommandResponse::Simple(Self::execute_keys(db).await)]),
-            Command::INFO(section) => Ok(vec![CommandResponse::Simple(
-                Self::execute_info(section, replication_config).await,
-            )]),
-            Command::REPLCONF(args) => Ok(vec![CommandResponse::Simple(
-                Self::execute_replconf(args, peer_addr, publisher).await,
-            )]),
-            Command::PSYNC(args) => Ok(Self::execute_psync(args, replication_config).await),
-        }
+    pub(crate) async fn run_type(&self, context: ExecutionContext<'_>) -> Result<Vec<CommandResponse>, RedisError> {
+        let ExecutionContext { db, publisher, trace, .. } = context;
+        let Command::TYPE(key) = self else {
+            unreachable!("not a TYPE command");
+        };
+        Self::expire_on_access(&[key], db, publisher, trace).await?;
+        let db = db.read().await;
+        let type_name = match db.get(key) {
+            Some(entry) if !entry.is_expired() => entry.value.type_name(),
+            _ => "none",
+        };
+        Ok(vec![CommandResponse::Value(RespValue::SimpleString(type_name.into()))])
     }
 
-    async fn execute_get(key: &String, db: &HashMap<String, ValueEntry>) -> String {
-        match db.get(key) {
-            Some(value_entry) => {
-                if value_entry.is_expired() {
-                    format!("{}-1{}", BULK_STRING_PREFIX, CRLF)
-                } else {
-                    format!("{}{}{}{}{}", BULK_STRING_PREFIX, value_entry.value.len(), CRLF, value_entry.value, CRLF)
-                }
-            }
-            None => format!("{}-1{}", BULK_STRING_PREFIX, CRLF),
+    pub(crate) async fn run_set(&self, context: ExecutionContext<'_>) -> Result<Vec<CommandResponse>, RedisError> {
+        let ExecutionContext { db, config, replication_config, publisher, trace, .. } = context;
+        let Command::SET { key, value, expiration } = self else {
+            unreachable!("not a SET command");
+        };
+        let role = replication_config.read().await.get_role().await;
+        let mut db = db.write().await;
+
+        if role == "slave" {
+            let response = Self::execute_set(key, value, *expiration, &mut db).await?;
+            return Ok(vec![CommandResponse::Value(response)]);
         }
+
+        let response = Self::execute_set(key, value, *expiration, &mut db).await?;
+        let expiration_ms = db.get(key).and_then(|entry| entry.expiration_ms());
+
+        Self::notify_keyspace_event(config, publisher, notify::NOTIFY_STRING, SET_EVENT, key).await?;
+        if expiration.is_some() {
+            Self::notify_keyspace_event(config, publisher, notify::NOTIFY_GENERIC, EXPIRE_EVENT, key).await?;
+        }
+
+        Self::propagate_set(key, value, expiration_ms, publisher, trace).await?;
+        Ok(vec![CommandResponse::Value(response)])
     }
 
-    async fn execute_set(key: &String, value: &String, ex: Option<u64>, px: Option<u64>, db: &mut HashMap<String, ValueEntry>) -> String {
-        let expiration_ms = match (px, ex) {
-            (Some(ms), _) => Some(ms),
-            (None, Some(s)) => Some(s * 1000),
-            _ => None,
+    // Redis처럼 SET으로 바꿔 SET과 같은 경로로 전파함
+    pub(crate) async fn run_getset(&self, context: ExecutionContext<'_>) -> Result<Vec<CommandResponse>, RedisError> {
+        let ExecutionContext { db, config, replication_config, publisher, trace, .. } = context;
+        let Command::GETSET { key, value } = self else {
+            unreachable!("not a GETSET command");
+        };
+        let role = replication_config.read().await.get_role().await;
+        let (response, expiration_ms) = {
+            let mut db = db.write().await;
+            let response = Self::execute_get(key, &db).await?;
+            Self::execute_set(key, value, None, &mut db).await?;
+            (response, db.get(key).and_then(|entry| entry.expiration_ms()))
         };
 
-        db.insert(key.clone(), ValueEntry::new_relative(value.clone(), expiration_ms));
-        format!("{}OK{}", SIMPLE_STRING_PREFIX, CRLF)
+        if role != "slave" {
+            Self::notify_keyspace_event(config, publisher, notify::NOTIFY_STRING, SET_EVENT, key).await?;
+            Self::propagate_set(key, value, expiration_ms, publisher, trace).await?;
+        }
+
+        Ok(vec![CommandResponse::Value(response)])
     }
 
-    async fn execute_config(command: &ConfigCommand, config: &Arc<RwLock<HashMap<String, String>>>) -> String {
-        match command {
-            ConfigCommand::GET(key) => {
-                let config = config.read().await;
-                match config.get(key.as_str()) {
-                    Some(value) => {
-                        format!("{}2{}{}{}{}{}{}{}{}{}{}{}", ARRAY_PREFIX, CRLF, BULK_STRING_PREFIX, key.len(), CRLF, key, CRLF, BULK_STRING_PREFIX, value.len(), CRLF, value, CRLF)
+    // 레플리카에는 GETEX 대신 바뀐 결과만 PEXPIREAT/PERSIST로, 이미 지난 시각이면 DEL로 보냄
+    pub(crate) async fn run_getex(&self, context: ExecutionContext<'_>) -> Result<Vec<CommandResponse>, RedisError> {
+        let ExecutionContext { db, config, replication_config, publisher, trace, .. } = context;
+        let Command::GETEX { key, expiration, persist } = self else {
+            unreachable!("not a GETEX command");
+        };
+        Self::expire_on_access(&[key], db, publisher, trace).await?;
+        let role = replication_config.read().await.get_role().await;
+        let deadline_ms = expiration.map(|expiration| expiration.deadline_ms(GETEX_COMMAND)).transpose()?;
+        let (response, change) = {
+            let mut db = db.write().await;
+            let response = Self::execute_get(key, &db).await?;
+            let change = match deadline_ms {
+                _ if response == RespValue::NullBulk => None,
+                Some(deadline_ms) if deadline_ms <= current_time_ms() => {
+                    db.remove(key);
+                    Some((construct_redis_command(&[DEL_COMMAND.as_bytes(), key]), DEL_EVENT))
+                }
+                Some(deadline_ms) => {
+                    if let Some(mut entry) = db.get_mut(key) {
+                        entry.set_expiration_ms(Some(deadline_ms));
                     }
-                    None => format!("{}-1{}", BULK_STRING_PREFIX, CRLF),
+                    let deadline = deadline_ms.to_string();
+                    Some((construct_redis_command(&[PEXPIREAT_COMMAND.as_bytes(), key, deadline.as_bytes()]), EXPIRE_EVENT))
                 }
-            }
+                None if *persist && Self::execute_persist(key, &mut db) => {
+                    Some((construct_redis_command(&[PERSIST_COMMAND.as_bytes(), key]), PERSIST_EVENT))
+                }
+                None => None,
+            };
+            (response, change)
+        };
+
+        if let Some((message, event)) = change.filter(|_| role != "slave") {
+            publisher.publish_propagate_slave(message, trace).await
+                .map_err(|e| format!("Failed to propagate command to slaves: {}", e))?;
+            Self::notify_keyspace_event(config, publisher, notify::NOTIFY_GENERIC, event, key).await?;
+        }
+
+        Ok(vec![CommandResponse::Value(response)])
+    }
+
+    // 결과가 아니라 INCR 자체를 전파해도 레플리카에서 같은 값이 나옴
+    pub(crate) async fn run_incr(&self, context: ExecutionContext<'_>) -> Result<Vec<CommandResponse>, RedisError> {
+        let ExecutionContext { db, config, replication_config, publisher, trace, .. } = context;
+        let Command::INCR(key) = self else {
+            unreachable!("not an INCR command");
+        };
+        let role = replication_config.read().await.get_role().await;
+        let value = Self::execute_incr(key, &mut *db.write().await)?;
+
+        if role != "slave" {
+            Self::notify_keyspace_event(config, publisher, notify::NOTIFY_STRING, INCRBY_EVENT, key).await?;
+            publisher.publish_propagate_slave(construct_redis_command(&[INCR_COMMAND.as_bytes(), key]), trace).await
+                .map_err(|e| format!("Failed to propagate command to slaves: {}", e))?;
+        }
+
+        Ok(vec![CommandResponse::Value(RespValue::Integer(value))])
+    }
+
+    pub(crate) async fn run_expire(&self, context: ExecutionContext<'_>) -> Result<Vec<CommandResponse>, RedisError> {
+        let ExecutionContext { db, config, replication_config, publisher, trace, .. } = context;
+        let (
+            Command::EXPIRE { key, conditions, .. }
+            | Command::PEXPIRE { key, conditions, .. }
+            | Command::EXPIREAT { key, conditions, .. }
+            | Command::PEXPIREAT { key, conditions, .. }
+        ) = self else {
+            unreachable!("not an EXPIRE command");
+        };
+        let role = replication_config.read().await.get_role().await;
+        let deadline_ms = self.expire_deadline_ms()?;
+        let (updated, deleted) = {
+            let mut db = db.write().await;
+            let updated = Self::execute_expire(key, deadline_ms, conditions, &mut db);
+            (updated, updated && !db.contains_key(key))
+        };
+
+        if updated && role != "slave" {
+            // 조건은 마스터에서 이미 확인했으므로 레플리카에는 결과만 절대 시각이나 DEL로 보냄
+            let message = if deleted {
+                construct_redis_command(&[DEL_COMMAND.as_bytes(), key])
+            } else {
+                construct_redis_command(&[PEXPIREAT_COMMAND.as_bytes(), key, deadline_ms.to_string().as_bytes()])
+            };
+            publisher.publish_propagate_slave(message, trace).await
+                .map_err(|e| format!("Failed to propagate command to slaves: {}", e))?;
+            // 이미 지난 시각이면 키가 바로 지워지므로 Redis처럼 del로 알림
+            let event = if deleted { DEL_EVENT } else { EXPIRE_EVENT };
+            Self::notify_keyspace_event(config, publisher, notify::NOTIFY_GENERIC, event, key).await?;
         }
+
+        Ok(vec![CommandResponse::Value(RespValue::Integer(updated as i64))])
     }
 
-    async fn execute_keys(db: &Arc<RwLock<HashMap<String, ValueEntry>>>) -> String {
+    pub(crate) async fn run_ttl(&self, context: ExecutionContext<'_>) -> Result<Vec<CommandResponse>, RedisError> {
+        let ExecutionContext { db, .. } = context;
+        let (Command::TTL(key) | Command::PTTL(key) | Command::EXPIRETIME(key) | Command::PEXPIRETIME(key)) = self else {
+            unreachable!("not a TTL command");
+        };
         let db = db.read().await;
-        let keys: Vec<String> = db.keys().cloned().collect();
-        let mut response = format!("*{}\r\n", keys.len());
-        for key in keys {
-            response.push_str(&format!("${}\r\n{}\r\n", key.len(), key));
+        Ok(vec![CommandResponse::Value(RespValue::Integer(self.execute_ttl(key, &db)))])
+    }
+
+    pub(crate) async fn run_persist(&self, context: ExecutionContext<'_>) -> Result<Vec<CommandResponse>, RedisError> {
+        let ExecutionContext { db, config, replication_config, publisher, trace, .. } = context;
+        let Command::PERSIST(key) = self else {
+            unreachable!("not a PERSIST command");
+        };
+        let role = replication_config.read().await.get_role().await;
+        let persisted = {
+            let mut db = db.write().await;
+            Self::execute_persist(key, &mut db)
+        };
+
+        if persisted && role != "slave" {
+            publisher.publish_propagate_slave(construct_redis_command(&[PERSIST_COMMAND.as_bytes(), key]), trace).await
+                .map_err(|e| format!("Failed to propagate command to slaves: {}", e))?;
+            Self::notify_keyspace_event(config, publisher, notify::NOTIFY_GENERIC, PERSIST_EVENT, key).await?;
         }
-        response
+
+        Ok(vec![CommandResponse::Value(RespValue::Integer(persisted as i64))])
     }
 
-    async fn execute_info(section: &String, replication_config: &Arc<RwLock<ReplicationConfig>>) -> String {
-        if section.to_lowercase() == "replication" {
-            let replication_config = replication_config.read().await;
-            let replication_info = replication_config.get_replication_info().await;
-            format!("${}\r\n{}\r\n", replication_info.len(), replication_info)
-        } else {
-            format!("{}-1{}", BULK_STRING_PREFIX, CRLF)
+    pub(crate) async fn run_del(&self, context: ExecutionContext<'_>) -> Result<Vec<CommandResponse>, RedisError> {
+        let ExecutionContext { db, config, replication_config, publisher, trace, .. } = context;
+        let (Command::DEL(keys) | Command::UNLINK(keys)) = self else {
+            unreachable!("not a DEL command");
+        };
+        let role = replication_config.read().await.get_role().await;
+        let deleted = {
+            let mut db = db.write().await;
+            Self::execute_del(keys, matches!(self, Command::UNLINK(_)), &mut db)
+        };
+
+        if !deleted.is_empty() && role != "slave" {
+            let mut args = vec![self.name().as_bytes()];
+            args.extend(keys.iter().map(|key| key.as_slice()));
+            publisher.publish_propagate_slave(construct_redis_command(&args), trace).await
+                .map_err(|e| format!("Failed to propagate command to slaves: {}", e))?;
+            for key in &deleted {
+                Self::notify_keyspace_event(config, publisher, notify::NOTIFY_GENERIC, DEL_EVENT, key).await?;
+            }
         }
+
+        Ok(vec![CommandResponse::Value(RespValue::Integer(deleted.len() as i64))])
     }
-    pub async fn execute_replconf(
-        args: &Vec<String>,
-        peer_addr: SocketAddr,
-        publisher: &EventPublisher,
-    ) -> String {
-        // TODO: 요구사항에는, --listening-port로 전파하는 것처럼 되어있지만 실제로는 그렇지 않아 리팩토링 필요
-        if args[0] == "listening-port" {
-            if let Err(e) = publisher.publish_slave_connected(peer_addr).await {
-                return format!("-ERR Failed to register slave: {}{}", e, CRLF);
+
+    pub(crate) async fn run_exists(&self, context: ExecutionContext<'_>) -> Result<Vec<CommandResponse>, RedisError> {
+        let ExecutionContext { db, publisher, trace, .. } = context;
+        let Command::EXISTS(keys) = self else {
+            unreachable!("not an EXISTS command");
+        };
+        let key_refs: Vec<&Vec<u8>> = keys.iter().collect();
+        Self::expire_on_access(&key_refs, db, publisher, trace).await?;
+        let db = db.read().await;
+        let count = keys
+            .iter()
+            .filter(|key| db.get(key).is_some_and(|entry| !entry.is_expired()))
+            .count();
+        Ok(vec![CommandResponse::Value(RespValue::Integer(count as i64))])
+    }
+
+    pub(crate) async fn run_touch(&self, context: ExecutionContext<'_>) -> Result<Vec<CommandResponse>, RedisError> {
+        let ExecutionContext { db, .. } = context;
+        let Command::TOUCH(keys) = self else {
+            unreachable!("not a TOUCH command");
+        };
+        let db = db.read().await;
+        let count = keys
+            .iter()
+            .filter_map(|key| db.get(key))
+            .filter(|entry| !entry.is_expired())
+            .inspect(|entry| db.touch(entry))
+            .count();
+        Ok(vec![CommandResponse::Value(RespValue::Integer(count as i64))])
+    }
+
+    pub(crate) async fn run_hash_write(&self, context: ExecutionContext<'_>) -> Result<Vec<CommandResponse>, RedisError> {
+        let ExecutionContext { db, config, replication_config, publisher, trace, .. } = context;
+        let role = replication_config.read().await.get_role().await;
+        let (response, changed, key_removed) = {
+            let mut db = db.write().await;
+            let (response, changed) = self.execute_hash_write(&mut db)?;
+            (response, changed, !db.contains_key(self.hash_key()))
+        };
+
+        if changed && role != "slave" {
+            publisher.publish_propagate_slave(self.hash_replication_command(), trace).await
+                .map_err(|e| format!("Failed to propagate command to slaves: {}", e))?;
+            Self::notify_keyspace_event(config, publisher, notify::NOTIFY_HASH, self.hash_event(), self.hash_key()).await?;
+            if key_removed {
+                Self::notify_keyspace_event(config, publisher, notify::NOTIFY_GENERIC, DEL_EVENT, self.hash_key()).await?;
+            }
+        }
+
+        Ok(vec![CommandResponse::Value(response)])
+    }
+
+    pub(crate) async fn run_list_write(&self, context: ExecutionContext<'_>) -> Result<Vec<CommandResponse>, RedisError> {
+        let ExecutionContext { db, config, replication_config, publisher, trace, .. } = context;
+        let (
+            Command::LPUSH { key, .. }
+            | Command::RPUSH { key, .. }
+            | Command::LPOP { key, .. }
+            | Command::RPOP { key, .. }
+        ) = self else {
+            unreachable!("not a LPUSH command");
+        };
+        let role = replication_config.read().await.get_role().await;
+        let (response, changed, key_removed) = {
+            let mut db = db.write().await;
+            let (response, changed) = self.execute_list_write(&mut db)?;
+            (response, changed, !db.contains_key(key))
+        };
+
+        if changed && role != "slave" {
+            publisher.publish_propagate_slave(self.list_replication_command(), trace).await
+                .map_err(|e| format!("Failed to propagate command to slaves: {}", e))?;
+            Self::notify_keyspace_event(config, publisher, notify::NOTIFY_LIST, self.list_event(), key).await?;
+            if key_removed {
+                Self::notify_keyspace_event(config, publisher, notify::NOTIFY_GENERIC, DEL_EVENT, key).await?;
             }
-            return format!("{}OK{}", SIMPLE_STRING_PREFIX, CRLF);
-        } else if args[0] == "capa" {
-            return format!("{}OK{}", SIMPLE_STRING_PREFIX, CRLF);
         }
-        format!("-ERR Invalid REPLCONF arguments{}", CRLF)
+
+        Ok(vec![CommandResponse::Value(response)])
     }
 
-    async fn execute_psync(
-        args: &Vec<String>,
-        replication_config: &Arc<RwLock<ReplicationConfig>>,
-    ) -> Vec<CommandResponse> {
-        let master_repl_id = replication_config.read().await.get_repl_id().await;
-        let requested_offset: i64 = args
-            .get(1)
-            .and_then(|offset| offset.parse::<i64>().ok())
-            .unwrap_or(-1);
-
-        let master_offset = 0;
-
-        if requested_offset == -1 || requested_offset < master_offset {
-            let full_resync_response = format!(
-                "{}FULLRESYNC {} {}{}",
-                SIMPLE_STRING_PREFIX, master_repl_id, master_offset, CRLF
-            );
-
-            // TODO : give real rdb file if needed
-            const EMPTY_RDB_FILE: &[u8] = &[
-                0x52, 0x45, 0x44, 0x49, 0x53, 0x30, 0x30, 0x30, 0x39,
-                0xFF,
-            ];
-
-            vec![
-                CommandResponse::Simple(full_resync_response),
-                CommandResponse::Bulk(EMPTY_RDB_FILE.to_vec()),
-            ]
-        } else {
-            vec![CommandResponse::Simple(format!(
-                "{}CONTINUE{}",
-                SIMPLE_STRING_PREFIX, CRLF
-            ))]
+    pub(crate) async fn run_lmove(&self, context: ExecutionContext<'_>) -> Result<Vec<CommandResponse>, RedisError> {
+        let ExecutionContext { db, config, replication_config, publisher, trace, .. } = context;
+        let role = replication_config.read().await.get_role().await;
+        let (source, destination, from, to) = self.move_args();
+        let (moved, source_removed) = {
+            let mut db = db.write().await;
+            let moved = self.execute_move(&mut db)?;
+            (moved, !db.contains_key(source))
+        };
+
+        if moved.is_some() && role != "slave" {
+            publisher.publish_propagate_slave(self.move_replication_command(), trace).await
+                .map_err(|e| format!("Failed to propagate command to slaves: {}", e))?;
+            let (pop_event, push_event) = Self::move_events(from, to);
+            Self::notify_keyspace_event(config, publisher, notify::NOTIFY_LIST, pop_event, source).await?;
+            Self::notify_keyspace_event(config, publisher, notify::NOTIFY_LIST, push_event, destination).await?;
+            if source_removed {
+                Self::notify_keyspace_event(config, publisher, notify::NOTIFY_GENERIC, DEL_EVENT, source).await?;
+            }
         }
+
+        Ok(vec![CommandResponse::Value(moved.map_or(RespValue::NullBulk, RespValue::bulk))])
     }
 
-    pub async fn execute_without_response(
-        &self,
-        db: &mut HashMap<String, ValueEntry>,
-    ) -> Result<(), String> {
-        match self {
-            Command::SET { key, value, ex, px } => {
-                Self::execute_set(key, value, *ex, *px, db).await;
-                Ok(())
+    pub(crate) async fn run_multi_pop(&self, context: ExecutionContext<'_>) -> Result<Vec<CommandResponse>, RedisError> {
+        let ExecutionContext { db, config, replication_config, publisher, trace, .. } = context;
+        let role = replication_config.read().await.get_role().await;
+        let outcome = {
+            let mut db = db.write().await;
+            self.execute_multi_pop(&mut db)?
+        };
+        let Some(outcome) = outcome else {
+            return Ok(vec![CommandResponse::Value(RespValue::NullArray)]);
+        };
+
+        if role != "slave" {
+            publisher.publish_propagate_slave(outcome.replication, trace).await
+                .map_err(|e| format!("Failed to propagate command to slaves: {}", e))?;
+            Self::notify_keyspace_event(config, publisher, outcome.class, outcome.event, &outcome.key).await?;
+            if outcome.key_removed {
+                Self::notify_keyspace_event(config, publisher, notify::NOTIFY_GENERIC, DEL_EVENT, &outcome.key).await?;
             }
-            _ => Ok(()),
         }
+
+        Ok(vec![CommandResponse::Value(outcome.response)])
     }
-}
\ No newline at end of file
+
+    pub(crate) async fn run_zadd(&self, context: ExecutionContext<'_>) -> Result<Vec<CommandResponse>, RedisError> {
+        let ExecutionContext { db, config, replication_config, publisher, trace, .. } = context;
+        let Command::ZADD { key, members, incr } = self else {
+            unreachable!("not a ZADD command");
+        };
+        if *incr {
+            return self.run_zincrby(context).await;
+        }
+        let role = replication_config.read().await.get_role().await;
+        let added = {
+            let mut db = db.write().await;
+            Self::execute_zadd(key, members, &mut db)?
+        };
+
+        if role != "slave" {
+            publisher.publish_propagate_slave(Self::zadd_replication_command(key, members), trace).await
+                .map_err(|e| format!("Failed to propagate command to slaves: {}", e))?;
+            Self::notify_keyspace_event(config, publisher, notify::NOTIFY_ZSET, ZADD_EVENT, key).await?;
+        }
+
+        Ok(vec![CommandResponse::Value(RespValue::Integer(added as i64))])
+    }
+
+    // ZINCRBY와 옛 형식인 ZADD key INCR, 레플리카에는 더한 결과 점수로 ZADD를 보냄
+    pub(crate) async fn run_zincrby(&self, context: ExecutionContext<'_>) -> Result<Vec<CommandResponse>, RedisError> {
+        let ExecutionContext { db, config, replication_config, publisher, trace, .. } = context;
+        let (key, increment, member) = match self {
+            Command::ZINCRBY { key, increment, member } => (key, *increment, member),
+            Command::ZADD { key, members, incr: true } => (key, members[0].0, &members[0].1),
+            _ => unreachable!("not a ZINCRBY command"),
+        };
+        let role = replication_config.read().await.get_role().await;
+        let score = {
+            let mut db = db.write().await;
+            Self::execute_zincrby(key, increment, member, &mut db)?
+        };
+
+        if role != "slave" {
+            let members = [(score, member.clone())];
+            publisher.publish_propagate_slave(Self::zadd_replication_command(key, &members), trace).await
+                .map_err(|e| format!("Failed to propagate command to slaves: {}", e))?;
+            Self::notify_keyspace_event(config, publisher, notify::NOTIFY_ZSET, ZINCR_EVENT, key).await?;
+        }
+
+        Ok(vec![CommandResponse::Value(RespValue::Double(score))])
+    }
+
+    pub(crate) async fn run_zrandmember(&self, context: ExecutionContext<'_>) -> Result<Vec<CommandResponse>, RedisError> {
+        let ExecutionContext { db, publisher, trace, .. } = context;
+        let Command::ZRANDMEMBER { key, count, withscores } = self else {
+            unreachable!("not a ZRANDMEMBER command");
+        };
+        Self::expire_on_access(&[key], db, publisher, trace).await?;
+        let db = db.read().await;
+        Ok(vec![CommandResponse::Value(Self::execute_zrandmember(key, *count, *withscores, &db)?)])
+    }
+
+    // RESP3 클라이언트는 점수를 double로, RESP2 클라이언트는 bulk string으로 받음
+    pub(crate) async fn run_zscore(&self, context: ExecutionContext<'_>) -> Result<Vec<CommandResponse>, RedisError> {
+        let ExecutionContext { db, publisher, trace, .. } = context;
+        let Command::ZSCORE { key, member } = self else {
+            unreachable!("not a ZSCORE command");
+        };
+        Self::expire_on_access(&[key], db, publisher, trace).await?;
+        let db = db.read().await;
+        let score = match db.get(key) {
+            Some(entry) if !entry.is_expired() => {
+                let score = entry.expect_zset()?.score(member);
+                db.touch(entry);
+                score
+            }
+            _ => None,
+        };
+        Ok(vec![CommandResponse::Value(score.map_or(RespValue::NullBulk, RespValue::Double))])
+    }
+
+    pub(crate) async fn run_hget(&self, context: ExecutionContext<'_>) -> Result<Vec<CommandResponse>, RedisError> {
+        let ExecutionContext { db, publisher, trace, .. } = context;
+        let Command::HGET { key, field } = self else {
+            unreachable!("not a HGET command");
+        };
+        Self::expire_on_access(&[key], db, publisher, trace).await?;
+        let db = db.read().await;
+        let value = match db.get(key) {
+            Some(entry) if !entry.is_expired() => {
+                entry.expect_hash()?;
+                db.touch(entry);
+                entry.hash_field(field)
+            }
+            _ => None,
+        };
+        Ok(vec![CommandResponse::Value(value.map_or(RespValue::NullBulk, RespValue::bulk))])
+    }
+
+    pub(crate) async fn run_hgetall(&self, context: ExecutionContext<'_>) -> Result<Vec<CommandResponse>, RedisError> {
+        let ExecutionContext { db, publisher, trace, .. } = context;
+        let Command::HGETALL(key) = self else {
+            unreachable!("not a HGETALL command");
+        };
+        Self::expire_on_access(&[key], db, publisher, trace).await?;
+        let db = db.read().await;
+        let mut pairs = Vec::new();
+        if let Some(entry) = db.get(key).filter(|entry| !entry.is_expired()) {
+            for (field, value) in entry.expect_hash()?.iter() {
+                if !entry.is_field_expired(field) {
+                    pairs.push((field, value));
+                }
+            }
+            db.touch(entry);
+        }
+        let response = pairs
+            .into_iter()
+            .map(|(field, value)| (RespValue::bulk(field), RespValue::bulk(value)))
+            .collect();
+        Ok(vec![CommandResponse::Value(RespValue::Map(response))])
+    }
+
+    pub(crate) async fn run_hrandfield(&self, context: ExecutionContext<'_>) -> Result<Vec<CommandResponse>, RedisError> {
+        let ExecutionContext { db, publisher, trace, .. } = context;
+        let Command::HRANDFIELD { key, count, withvalues } = self else {
+            unreachable!("not a HRANDFIELD command");
+        };
+        Self::expire_on_access(&[key], db, publisher, trace).await?;
+        let db = db.read().await;
+        Ok(vec![CommandResponse::Value(Self::execute_hrandfield(key, *count, *withvalues, &db)?)])
+    }
+
+    pub(crate) async fn run_httl(&self, context: ExecutionContext<'_>) -> Result<Vec<CommandResponse>, RedisError> {
+        let ExecutionContext { db, .. } = context;
+        let Command::HTTL { key, fields } = self else {
+            unreachable!("not a HTTL command");
+        };
+        let db = db.read().await;
+        let ttls: Vec<i64> = match db.get(key) {
+            Some(entry) if !entry.is_expired() => {
+                entry.expect_hash()?;
+                fields
+                    .iter()
+                    .map(|field| match entry.hash_field(field) {
+                        None => -2,
+                        Some(_) => entry.field_expiration_ms(field).map_or(-1, |ms| {
+                            ((ms.saturating_sub(current_time_ms()) + 500) / 1000) as i64
+                        }),
+                    })
+                    .collect()
+            }
+            _ => vec![-2; fields.len()],
+        };
+        Ok(vec![CommandResponse::Value(RespValue::integer_array(&ttls))])
+    }
+
+    pub(crate) async fn run_object(&self, context: ExecutionContext<'_>) -> Result<Vec<CommandResponse>, RedisError> {
+        let ExecutionContext { db, config, .. } = context;
+        let Command::OBJECT(command) = self else {
+            unreachable!("not an OBJECT command");
+        };
+        let lfu_enabled = config
+            .read()
+            .await
+            .get("maxmemory_policy")
+            .is_some_and(|policy| policy.contains("lfu"));
+        let db = db.read().await;
+        Ok(vec![CommandResponse::Value(Self::execute_object(command, lfu_enabled, &db)?)])
+    }
+
+    pub(crate) async fn run_dump(&self, context: ExecutionContext<'_>) -> Result<Vec<CommandResponse>, RedisError> {
+        let ExecutionContext { db, .. } = context;
+        let Command::DUMP(key) = self else {
+            unreachable!("not a DUMP command");
+        };
+        let db = db.read().await;
+        match db.get(key) {
+            Some(entry) if !entry.is_expired() => Ok(vec![CommandResponse::Value(RespValue::bulk(dump_payload(&entry.value, entry.field_expirations())))]),
+            _ => Ok(vec![CommandResponse::Value(RespValue::NullBulk)]),
+        }
+    }
+
+    pub(crate) async fn run_restore(&self, context: ExecutionContext<'_>) -> Result<Vec<CommandResponse>, RedisError> {
+        let ExecutionContext { db, config, replication_config, publisher, trace, .. } = context;
+        let Command::RESTORE { key, .. } = self else {
+            unreachable!("not a RESTORE command");
+        };
+        let role = replication_config.read().await.get_role().await;
+        {
+            let mut db = db.write().await;
+            self.execute_restore(&mut db)?;
+        }
+
+        if role != "slave" {
+            publisher.publish_propagate_slave(self.restore_replication_command(), trace).await
+                .map_err(|e| format!("Failed to propagate command to slaves: {}", e))?;
+            Self::notify_keyspace_event(config, publisher, notify::NOTIFY_GENERIC, RESTORE_EVENT, key).await?;
+        }
+
+        Ok(vec![CommandResponse::Value(RespValue::ok())])
+    }
+
+    pub(crate) async fn run_migrate(&self, context: ExecutionContext<'_>) -> Result<Vec<CommandResponse>, RedisError> {
+        let ExecutionContext { db, config, replication_config, publisher, trace, .. } = context;
+        let Command::MIGRATE { keys, copy, .. } = self else {
+            unreachable!("not a MIGRATE command");
+        };
+        let role = replication_config.read().await.get_role().await;
+        let cluster_enabled = config.read().await.get("cluster_enabled").is_some_and(|enabled| enabled == "yes");
+        let dumped = {
+            let db = db.read().await;
+            Self::dump_for_migration(keys, &db)
+        };
+        if dumped.is_empty() {
+            return Ok(vec![CommandResponse::Value(RespValue::SimpleString(MIGRATE_NOKEY_REPLY.into()))]);
+        }
+        let restore_command = if cluster_enabled { RESTORE_ASKING_COMMAND } else { RESTORE_COMMAND };
+        let (migrated, error) = self.send_to_target(restore_command, &dumped).await?;
+
+        // COPY가 아니면 대상이 받은 키만 지우고, 레플리카에는 DEL로 전파함, 거절된 키가 있으면 그 에러로 답함
+        if !copy && !migrated.is_empty() {
+            {
+                let mut db = db.write().await;
+                for key in &migrated {
+                    db.remove(key);
+                }
+            }
+            if role != "slave" {
+                let mut args = vec![DEL_COMMAND.as_bytes()];
+                args.extend(migrated.iter().map(|key| key.as_slice()));
+                publisher.publish_propagate_slave(construct_redis_command(&args), trace).await
+                    .map_err(|e| format!("Failed to propagate command to slaves: {}", e))?;
+                for key in &migrated {
+                    Self::notify_keyspace_event(config, publisher, notify::NOTIFY_GENERIC, DEL_EVENT, key).await?;
+                }
+            }
+        }
+        match error {
+            Some(error) => Err(error),
+            None => Ok(vec![CommandResponse::Value(RespValue::ok())]),
+        }
+    }
+
+    pub(crate) async fn run_config(&self, context: ExecutionContext<'_>) -> Result<Vec<CommandResponse>, RedisError> {
+        let ExecutionContext { db, config, runtime, .. } = context;
+        let Command::CONFIG(command) = self else {
+            unreachable!("not a CONFIG command");
+        };
+        Ok(vec![CommandResponse::Value(Self::execute_config(command, config, db, runtime).await)])
+    }
+
+    pub(crate) async fn run_keys(&self, context: ExecutionContext<'_>) -> Result<Vec<CommandResponse>, RedisError> {
+        let ExecutionContext { db, publisher, trace, .. } = context;
+        let Command::KEYS { pattern, namespace } = self else {
+            unreachable!("not a KEYS command");
+        };
+        let (keys, expired) = Self::execute_keys(pattern, namespace, &*db.read().await);
+        Self::expire_found(&expired, publisher, trace).await?;
+        Ok(vec![CommandResponse::Value(RespValue::bulk_array(&keys))])
+    }
+
+    pub(crate) async fn run_dbsize(&self, context: ExecutionContext<'_>) -> Result<Vec<CommandResponse>, RedisError> {
+        let ExecutionContext { db, .. } = context;
+        let Command::DBSIZE(namespace) = self else {
+            unreachable!("not a DBSIZE command");
+        };
+        let db = db.read().await;
+        let prefix = namespace.as_deref().unwrap_or_default();
+        let size = db.alive().filter(|(key, _)| key.starts_with(prefix)).count();
+        Ok(vec![CommandResponse::Value(RespValue::Integer(size as i64))])
+    }
+
+    pub(crate) async fn run_flush(&self, context: ExecutionContext<'_>) -> Result<Vec<CommandResponse>, RedisError> {
+        let ExecutionContext { db, replication_config, publisher, trace, .. } = context;
+        let (Command::FLUSHDB(mode) | Command::FLUSHALL(mode)) = self else {
+            unreachable!("not a FLUSHDB command");
+        };
+        let role = replication_config.read().await.get_role().await;
+        {
+            let mut db = db.write().await;
+            Self::execute_flush(*mode, &mut db);
+        }
+
+        if role != "slave" {
+            publisher.publish_propagate_slave(construct_redis_command(&[self.name(), mode.as_str()]), trace).await
+                .map_err(|e| format!("Failed to propagate command to slaves: {}", e))?;
+        }
+
+        Ok(vec![CommandResponse::Value(RespValue::ok())])
+    }
+
+    pub(crate) async fn run_randomkey(&self, context: ExecutionContext<'_>) -> Result<Vec<CommandResponse>, RedisError> {
+        let ExecutionContext { db, publisher, trace, .. } = context;
+        let (key, expired) = Self::execute_randomkey(&*db.read().await);
+        Self::expire_found(&expired, publisher, trace).await?;
+        Ok(vec![CommandResponse::Value(key.map_or(RespValue::NullBulk, RespValue::bulk))])
+    }
+
+    pub(crate) async fn run_scan(&self, context: ExecutionContext<'_>) -> Result<Vec<CommandResponse>, RedisError> {
+        let ExecutionContext { db, publisher, trace, .. } = context;
+        let Command::SCAN { cursor, pattern, count, type_filter, namespace } = self else {
+            unreachable!("not a SCAN command");
+        };
+        let (next_cursor, keys, expired) = Self::execute_scan(*cursor, pattern, *count, type_filter, namespace, &*db.read().await);
+        Self::expire_found(&expired, publisher, trace).await?;
+
+        let response = RespValue::Array(vec![RespValue::bulk(next_cursor.to_string()), RespValue::bulk_array(&keys)]);
+        Ok(vec![CommandResponse::Value(response)])
+    }
+
+    pub(crate) async fn run_replconf(&self, context: ExecutionContext<'_>) -> Result<Vec<CommandResponse>, RedisError> {
+        let ExecutionContext { client_id, publisher, .. } = context;
+        let Command::REPLCONF(args) = self else {
+            unreachable!("not a REPLCONF command");
+        };
+        Ok(Self::execute_replconf(args, client_id, publisher)
+            .await
+            .map(CommandResponse::Value)
+            .into_iter()
+            .collect())
+    }
+
+    pub(crate) async fn run_psync(&self, context: ExecutionContext<'_>) -> Result<Vec<CommandResponse>, RedisError> {
+        let ExecutionContext { db, config, replication_config, functions, .. } = context;
+        let Command::PSYNC(args) = self else {
+            unreachable!("not a PSYNC command");
+        };
+        Self::execute_psync(args, db, config, replication_config, functions).await
+    }
+
+    // ExecutionContext로 실행하는 명령의 응답, 이벤트 핸들러가 실행하는 명령은 이벤트 핸들러가 이 길로 보내지 않음
+    pub async fn execute(&self, context: ExecutionContext<'_>) -> Result<Vec<CommandResponse>, RedisError> {
+        match self.executor() {
+            Executor::Context(execute) => execute(self, context).await,
+            Executor::EventHandler(_) => Err(format!("{} must be handled by the event handler", self.name()).into()),
+        }
+    }
+
+    // 접근한 키가 만료되었으면 이벤트 루프에 지우기를 맡김, 그동안은 읽는 쪽이 만료된 키를 없는 키처럼 다룸
+    // 읽기 명령은 이벤트 루프 밖에서도 돌기 때문에 여기서는 쓰기 잠금을 잡지 않음
+    // 레플리카는 마스터의 DEL을 기다리므로 이벤트 루프가 지우지 않고 넘김
+    async fn expire_on_access(
+        keys: &[&Vec<u8>],
+        db: &Arc<RwLock<Db>>,
+        publisher: &EventPublisher,
+        trace: Option<TraceContext>,
+    ) -> Result<(), RedisError> {
+        let expired: Vec<Vec<u8>> = {
+            let db = db.read().await;
+            keys.iter().filter(|key| db.get(key).is_some_and(|entry| entry.is_expired())).map(|key| (*key).clone()).collect()
+        };
+        if expired.is_empty() {
+            return Ok(());
+        }
+        Ok(publisher.publish_expired_keys_found(expired, trace).await?)
+    }
+
+    // 키를 나열하다 만난 만료된 키를 GET처럼 지움
+    async fn expire_found(
+        expired: &[Vec<u8>],
+        publisher: &EventPublisher,
+        trace: Option<TraceContext>,
+    ) -> Result<(), RedisError> {
+        if expired.is_empty() {
+            return Ok(());
+        }
+        Ok(publisher.publish_expired_keys_found(expired.to_vec(), trace).await?)
+    }
+
+    async fn execute_get(key: &[u8], db: &Db) -> Result<RespValue, RedisError> {
+        match db.get(key) {
+            Some(value_entry) => {
+                if value_entry.is_expired() {
+                    Ok(RespValue::NullBulk)
+                } else {
+                    let value = value_entry.expect_string()?;
+                    db.touch(value_entry);
+                    Ok(RespValue::bulk(value))
+                }
+            }
+            None => Ok(RespValue::NullBulk),
+        }
+    }
+
+    // 없는 키는 빈 문자열로 봄
+    // IDX는 {matches: [[[a 시작, a 끝], [b 시작, b 끝], (길이)], ...], len: LCS 길이}, 구간은 문자열 뒤쪽부터 나옴
+    fn execute_lcs(&self, db: &Db, max_table_size: u64) -> Result<RespValue, RedisError> {
+        let Command::LCS { key1, key2, len, idx, min_match_len, with_match_len } = self else {
+            unreachable!("not an LCS command");
+        };
+        let value = |key: &[u8]| -> Result<Vec<u8>, RedisError> {
+            let Some(entry) = db.get(key).filter(|entry| !entry.is_expired()) else {
+                return Ok(Vec::new());
+            };
+            let value = entry.expect_string().map_err(|_| RedisError::from(LCS_NOT_STRING_ERROR))?.into_owned();
+            db.touch(entry);
+            Ok(value)
+        };
+        let (a, b) = (value(key1)?, value(key2)?);
+        if lcs::table_size(&a, &b) > max_table_size {
+            return Err(LCS_MEMORY_ERROR.into());
+        }
+
+        let lcs = lcs::longest_common_subsequence(&a, &b);
+        if *len {
+            return Ok(RespValue::Integer(lcs.sequence.len() as i64));
+        }
+        if !*idx {
+            return Ok(RespValue::bulk(lcs.sequence));
+        }
+        let matches = lcs
+            .matches
+            .iter()
+            .filter(|range| range.len() >= *min_match_len)
+            .map(|range| {
+                let mut item = vec![
+                    RespValue::integer_array(&[range.a.0 as i64, range.a.1 as i64]),
+                    RespValue::integer_array(&[range.b.0 as i64, range.b.1 as i64]),
+                ];
+                if *with_match_len {
+                    item.push(RespValue::Integer(range.len() as i64));
+                }
+                RespValue::Array(item)
+            })
+            .collect();
+        Ok(RespValue::field_map(vec![
+            ("matches", RespValue::Array(matches)),
+            ("len", RespValue::Integer(lcs.sequence.len() as i64)),
+        ]))
+    }
+
+    async fn execute_set(key: &[u8], value: &[u8], expiration: Option<Expiration>, db: &mut Db) -> Result<RespValue, RedisError> {
+        let deadline_ms = expiration.map(|expiration| expiration.deadline_ms(SET_COMMAND)).transpose()?;
+        db.insert(key.to_vec(), ValueEntry::new_absolute(RedisValue::String(StringValue::new(value.to_vec())), deadline_ms));
+        Ok(RespValue::ok())
+    }
+
+    // 없는 키는 0으로 보고, Redis처럼 TTL은 그대로 둠
+    fn execute_incr(key: &[u8], db: &mut Db) -> Result<i64, RedisError> {
+        let (current, expiration_ms) = match db.get(key).filter(|entry| !entry.is_expired()) {
+            Some(entry) => {
+                let current = rdb_encoding::canonical_integer(&entry.expect_string()?)
+                    .ok_or_else(|| RedisError::from(NOT_AN_INTEGER_ERROR))?;
+                (current, entry.expiration_ms())
+            }
+            None => (0, None),
+        };
+        let value = current.checked_add(1).ok_or_else(|| RedisError::from(INCR_OVERFLOW_ERROR))?;
+        let entry = ValueEntry::new_absolute(RedisValue::String(StringValue::new(value.to_string().into_bytes())), expiration_ms);
+        db.insert(key.to_vec(), entry);
+        Ok(value)
+    }
+
+    fn expire_deadline_ms(&self) -> Result<i64, RedisError> {
+        let deadline_ms = match self {
+            Command::EXPIRE { seconds, .. } => seconds
+                .checked_mul(1000)
+                .and_then(|ms| ms.checked_add(current_time_ms() as i64)),
+            Command::PEXPIRE { milliseconds, .. } => milliseconds.checked_add(current_time_ms() as i64),
+            Command::EXPIREAT { timestamp, .. } => timestamp.checked_mul(1000),
+            Command::PEXPIREAT { timestamp_ms, .. } => Some(*timestamp_ms),
+            _ => None,
+        };
+        deadline_ms.ok_or_else(|| format!("invalid expire time in '{}' command", self.name().to_lowercase()).into())
+    }
+
+    fn expire_conditions_met(conditions: &[ExpireCondition], current_expiration: Option<i64>, deadline_ms: i64) -> bool {
+        conditions.iter().all(|condition| match condition {
+            ExpireCondition::NX => current_expiration.is_none(),
+            ExpireCondition::XX => current_expiration.is_some(),
+            ExpireCondition::GT => current_expiration.is_some_and(|current| deadline_ms > current),
+            ExpireCondition::LT => current_expiration.map_or(true, |current| deadline_ms < current),
+        })
+    }
+
+    fn hash_key(&self) -> &[u8] {
+        match self {
+            Command::HSET { key, .. }
+            | Command::HDEL { key, .. }
+            | Command::HEXPIRE { key, .. }
+            | Command::HPEXPIRE { key, .. }
+            | Command::HPERSIST { key, .. } => key,
+            _ => b"",
+        }
+    }
+
+    fn hash_event(&self) -> &'static str {
+        match self {
+            Command::HSET { .. } => HSET_EVENT,
+            Command::HDEL { .. } => HDEL_EVENT,
+            Command::HEXPIRE { .. } | Command::HPEXPIRE { .. } => HEXPIRE_EVENT,
+            _ => HPERSIST_EVENT,
+        }
+    }
+
+    fn list_event(&self) -> &'static str {
+        match self {
+            Command::LPUSH { .. } => LPUSH_EVENT,
+            Command::RPUSH { .. } => RPUSH_EVENT,
+            Command::LPOP { .. } => LPOP_EVENT,
+            _ => RPOP_EVENT,
+        }
+    }
+
+    fn list_replication_command(&self) -> Vec<u8> {
+        let mut args = vec![self.name().as_bytes()];
+        let count;
+        match self {
+            Command::LPUSH { key, values } | Command::RPUSH { key, values } => {
+                args.push(key);
+                args.extend(values.iter().map(|value| value.as_slice()));
+            }
+            Command::LPOP { key, count: pop_count } | Command::RPOP { key, count: pop_count } => {
+                args.push(key);
+                if let Some(pop_count) = pop_count {
+                    count = pop_count.to_string();
+                    args.push(count.as_bytes());
+                }
+            }
+            _ => unreachable!("not a list write command"),
+        }
+        construct_redis_command(&args)
+    }
+
+    // 리스트 쓰기 명령의 공통 처리, (응답, 데이터가 바뀌었는지)를 돌려줌
+    // 마지막 원소가 빠지면 Redis처럼 키도 지움
+    fn execute_list_write(&self, db: &mut Db) -> Result<(RespValue, bool), RedisError> {
+        let key = match self {
+            Command::LPUSH { key, .. } | Command::RPUSH { key, .. } | Command::LPOP { key, .. } | Command::RPOP { key, .. } => key,
+            _ => unreachable!("not a list write command"),
+        };
+        if db.get(key).is_some_and(|entry| entry.is_expired()) {
+            db.remove(key);
+        }
+
+        let result = match self {
+            Command::LPUSH { values, .. } | Command::RPUSH { values, .. } => {
+                let limits = db.listpack_limits();
+                let mut entry = db.get_or_insert_with(key, || ValueEntry::new_relative(RedisValue::List(ListValue::default()), None));
+                let list = entry.expect_list_mut()?;
+                for value in values {
+                    if matches!(self, Command::LPUSH { .. }) {
+                        list.push_front(value.clone(), limits);
+                    } else {
+                        list.push_back(value.clone(), limits);
+                    }
+                }
+                let len = list.len();
+                entry.touch();
+                (RespValue::Integer(len as i64), true)
+            }
+            Command::LPOP { count, .. } | Command::RPOP { count, .. } => {
+                let Some(mut entry) = db.get_mut(key) else {
+                    let nil = if count.is_some() { RespValue::NullArray } else { RespValue::NullBulk };
+                    return Ok((nil, false));
+                };
+                let list = entry.expect_list_mut()?;
+                let popped: Vec<Vec<u8>> = (0..count.unwrap_or(1).min(list.len()))
+                    .filter_map(|_| if matches!(self, Command::LPOP { .. }) { list.pop_front() } else { list.pop_back() })
+                    .collect();
+                entry.touch();
+                let response = match count {
+                    Some(_) => RespValue::bulk_array(&popped),
+                    None => RespValue::bulk(popped[0].as_slice()),
+                };
+                (response, !popped.is_empty())
+            }
+            _ => unreachable!("not a list write command"),
+        };
+
+        if db.get(key).is_some_and(|entry| entry.expect_list().is_ok_and(|list| list.is_empty())) {
+            db.remove(key);
+        }
+        Ok(result)
+    }
+
+    // BLPOP/BRPOP: 앞의 키부터 보고 비어 있지 않은 첫 리스트에서 꺼냄, 모두 비어 있으면 None
+    pub fn execute_blocking_pop(&self, db: &mut Db) -> Result<Option<PoppedElement>, RedisError> {
+        let (keys, left) = match self {
+            Command::BLPOP { keys, .. } => (keys, true),
+            Command::BRPOP { keys, .. } => (keys, false),
+            _ => unreachable!("not a blocking pop command"),
+        };
+        for key in keys {
+            if db.get(key).is_some_and(|entry| entry.is_expired()) {
+                db.remove(key);
+            }
+            let Some(mut entry) = db.get_mut(key) else {
+                continue;
+            };
+            let list = entry.expect_list_mut()?;
+            let Some(value) = (if left { list.pop_front() } else { list.pop_back() }) else {
+                continue;
+            };
+            if list.is_empty() {
+                drop(entry);
+                db.remove(key);
+            } else {
+                entry.touch();
+            }
+            return Ok(Some((key.clone(), value)));
+        }
+        Ok(None)
+    }
+
+    // LMOVE/BLMOVE/BRPOPLPUSH: destination 타입을 먼저 확인해서 WRONGTYPE이면 source를 건드리지 않음
+    // source와 destination이 같으면 같은 리스트 안에서 회전함
+    pub fn execute_move(&self, db: &mut Db) -> Result<Option<Vec<u8>>, RedisError> {
+        let (source, destination, from, to) = self.move_args();
+        for key in [source, destination] {
+            if db.get(key).is_some_and(|entry| entry.is_expired()) {
+                db.remove(key);
+            }
+        }
+        if db.get(source).is_none() {
+            return Ok(None);
+        }
+        if let Some(entry) = db.get(destination) {
+            entry.expect_list()?;
+        }
+
+        let Some(mut entry) = db.get_mut(source) else {
+            return Ok(None);
+        };
+        let list = entry.expect_list_mut()?;
+        let Some(value) = (if from == ListDirection::LEFT { list.pop_front() } else { list.pop_back() }) else {
+            return Ok(None);
+        };
+        let source_empty = list.is_empty();
+        drop(entry);
+        if source != destination && source_empty {
+            db.remove(source);
+        }
+
+        let limits = db.listpack_limits();
+        let mut entry = db.get_or_insert_with(destination, || ValueEntry::new_relative(RedisValue::List(ListValue::default()), None));
+        let list = entry.expect_list_mut()?;
+        if to == ListDirection::LEFT {
+            list.push_front(value.clone(), limits);
+        } else {
+            list.push_back(value.clone(), limits);
+        }
+        entry.touch();
+        Ok(Some(value))
+    }
+
+    fn execute_zadd(key: &[u8], members: &[(f64, Vec<u8>)], db: &mut Db) -> Result<usize, RedisError> {
+        if db.get(key).is_some_and(|entry| entry.is_expired()) {
+            db.remove(key);
+        }
+        let limits = db.listpack_limits();
+        let mut entry = db.get_or_insert_with(key, || ValueEntry::new_relative(RedisValue::ZSet(ZSetValue::default()), None));
+        let zset = entry.expect_zset_mut()?;
+        let added = members
+            .iter()
+            .filter(|(score, member)| zset.insert(member.clone(), *score, limits))
+            .count();
+        entry.touch();
+        Ok(added)
+    }
+
+    // 없는 멤버는 0점에서 시작함, +inf와 -inf를 더해 NaN이 되면 값을 바꾸지 않고 에러로 답함
+    fn execute_zincrby(key: &[u8], increment: f64, member: &[u8], db: &mut Db) -> Result<f64, RedisError> {
+        if db.get(key).is_some_and(|entry| entry.is_expired()) {
+            db.remove(key);
+        }
+        let limits = db.listpack_limits();
+        let mut entry = db.get_or_insert_with(key, || ValueEntry::new_relative(RedisValue::ZSet(ZSetValue::default()), None));
+        let zset = entry.expect_zset_mut()?;
+        let score = zset.score(member).unwrap_or(0.0) + increment;
+        if score.is_nan() {
+            return Err(RedisError::Err(SCORE_NAN_ERROR.into()));
+        }
+        zset.insert(member.to_vec(), score, limits);
+        entry.touch();
+        Ok(score)
+    }
+
+    // count가 없으면 멤버 하나, 있으면 Random::sample_indices의 규칙으로 고른 배열
+    // WITHSCORES는 RESP2와 RESP3 모두 멤버와 점수를 번갈아 담은 배열로 답함
+    fn execute_zrandmember(key: &[u8], count: Option<i64>, withscores: bool, db: &Db) -> Result<RespValue, RedisError> {
+        let mut members = Vec::new();
+        if let Some(entry) = db.get(key).filter(|entry| !entry.is_expired()) {
+            members.extend(entry.expect_zset()?.iter().map(|(member, score)| (member, RespValue::Double(score))));
+            db.touch(entry);
+        }
+        Ok(Self::random_elements(&members, count, withscores, db.random()))
+    }
+
+    fn execute_hrandfield(key: &[u8], count: Option<i64>, withvalues: bool, db: &Db) -> Result<RespValue, RedisError> {
+        let mut fields = Vec::new();
+        if let Some(entry) = db.get(key).filter(|entry| !entry.is_expired()) {
+            for (field, value) in entry.expect_hash()?.iter() {
+                if !entry.is_field_expired(field) {
+                    fields.push((field, RespValue::bulk(value)));
+                }
+            }
+            db.touch(entry);
+        }
+        // 해시 테이블의 순회 순서는 실행마다 달라서, 시드를 준 경우에는 정렬해야 같은 결과가 나옴
+        if db.random().is_seeded() {
+            fields.sort_by(|a, b| a.0.cmp(b.0));
+        }
+        Ok(Self::random_elements(&fields, count, withvalues, db.random()))
+    }
+
+    // ZRANDMEMBER와 HRANDFIELD가 함께 씀, count가 없으면 하나를 bulk로, 있으면 sample_indices로 고른 배열로 답함
+    // with_values면 각 요소 뒤에 점수나 값을 붙임
+    fn random_elements(elements: &[(&[u8], RespValue)], count: Option<i64>, with_values: bool, random: &Random) -> RespValue {
+        let Some(count) = count else {
+            if elements.is_empty() {
+                return RespValue::NullBulk;
+            }
+            return RespValue::bulk(elements[random.below(elements.len())].0);
+        };
+        let mut reply = Vec::new();
+        for index in random.sample_indices(elements.len(), count) {
+            let (element, value) = &elements[index];
+            reply.push(RespValue::bulk(*element));
+            if with_values {
+                reply.push(value.clone());
+            }
+        }
+        RespValue::Array(reply)
+    }
+
+    fn zadd_replication_command(key: &[u8], members: &[(f64, Vec<u8>)]) -> Vec<u8> {
+        let scores: Vec<String> = members.iter().map(|(score, _)| format_score(*score)).collect();
+        let mut args = vec![ZADD_COMMAND.as_bytes(), key];
+        for ((_, member), score) in members.iter().zip(scores.iter()) {
+            args.push(score.as_bytes());
+            args.push(member);
+        }
+        construct_redis_command(&args)
+    }
+
+    // LMPOP/BLMPOP/ZMPOP/BZMPOP: 앞의 키부터 보고 비어 있지 않은 첫 키에서 최대 count개를 꺼냄
+    // 레플리카에는 실제로 꺼낸 키 하나에 대한 LPOP/RPOP 또는 ZMPOP으로 전파함
+    pub fn execute_multi_pop(&self, db: &mut Db) -> Result<Option<MultiPopOutcome>, RedisError> {
+        let (keys, count) = match self {
+            Command::LMPOP { keys, count, .. }
+            | Command::BLMPOP { keys, count, .. }
+            | Command::ZMPOP { keys, count, .. }
+            | Command::BZMPOP { keys, count, .. } => (keys, *count),
+            _ => unreachable!("not a multi pop command"),
+        };
+        for key in keys {
+            if db.get(key).is_some_and(|entry| entry.is_expired()) {
+                db.remove(key);
+            }
+            let Some(mut entry) = db.get_mut(key) else {
+                continue;
+            };
+            let count_arg = count.to_string();
+            let outcome = match self {
+                Command::LMPOP { direction, .. } | Command::BLMPOP { direction, .. } => {
+                    let list = entry.expect_list_mut()?;
+                    let popped: Vec<Vec<u8>> = (0..count.min(list.len()))
+                        .filter_map(|_| if *direction == ListDirection::LEFT { list.pop_front() } else { list.pop_back() })
+                        .collect();
+                    if popped.is_empty() {
+                        continue;
+                    }
+                    let (pop_command, event) = match direction {
+                        ListDirection::LEFT => (LPOP_COMMAND, LPOP_EVENT),
+                        ListDirection::RIGHT => (RPOP_COMMAND, RPOP_EVENT),
+                    };
+                    MultiPopOutcome {
+                        key: key.clone(),
+                        response: RespValue::Array(vec![RespValue::bulk(key.as_slice()), RespValue::bulk_array(&popped)]),
+                        replication: construct_redis_command(&[pop_command.as_bytes(), key, count_arg.as_bytes()]),
+                        class: notify::NOTIFY_LIST,
+                        event,
+                        key_removed: list.is_empty(),
+                    }
+                }
+                _ => {
+                    let direction = match self {
+                        Command::ZMPOP { direction, .. } | Command::BZMPOP { direction, .. } => *direction,
+                        _ => unreachable!("not a multi pop command"),
+                    };
+                    let zset = entry.expect_zset_mut()?;
+                    let mut members: Vec<(Vec<u8>, f64)> = zset.iter().map(|(member, score)| (member.to_vec(), score)).collect();
+                    // 점수가 같으면 멤버 이름 순서, MAX는 그 반대 순서
+                    members.sort_by(|a, b| a.1.total_cmp(&b.1).then_with(|| a.0.cmp(&b.0)));
+                    if direction == ScoreDirection::MAX {
+                        members.reverse();
+                    }
+                    members.truncate(count);
+                    if members.is_empty() {
+                        continue;
+                    }
+                    let mut popped = Vec::new();
+                    for (member, score) in members {
+                        zset.remove(&member);
+                        popped.push(RespValue::Array(vec![RespValue::bulk(member), RespValue::Double(score)]));
+                    }
+                    let response = RespValue::Array(vec![RespValue::bulk(key.as_slice()), RespValue::Array(popped)]);
+                    let event = if direction == ScoreDirection::MIN { ZPOPMIN_EVENT } else { ZPOPMAX_EVENT };
+                    MultiPopOutcome {
+                        key: key.clone(),
+                        response,
+                        replication: construct_redis_command(&[
+                            ZMPOP_COMMAND.as_bytes(),
+                            b"1",
+                            key,
+                            direction.as_str().as_bytes(),
+                            COUNT_OPTION.as_bytes(),
+                            count_arg.as_bytes(),
+                        ]),
+                        class: notify::NOTIFY_ZSET,
+                        event,
+                        key_removed: zset.is_empty(),
+                    }
+                }
+            };
+            if outcome.key_removed {
+                drop(entry);
+                db.remove(key);
+            } else {
+                entry.touch();
+            }
+            return Ok(Some(outcome));
+        }
+        Ok(None)
+    }
+
+    // 블로킹 이동도 레플리카에는 실제로 일어난 LMOVE로 전파함
+    pub fn move_replication_command(&self) -> Vec<u8> {
+        let (source, destination, from, to) = self.move_args();
+        construct_redis_command(&[LMOVE_COMMAND.as_bytes(), source, destination, from.as_str().as_bytes(), to.as_str().as_bytes()])
+    }
+
+    // RESTORE는 받은 payload 그대로 전파하되, 상대 TTL은 마스터가 정한 절대 시각으로 바꿔서 보냄
+    fn restore_replication_command(&self) -> Vec<u8> {
+        let Command::RESTORE { key, ttl_ms, payload, absttl, .. } = self else {
+            unreachable!("not a RESTORE command");
+        };
+        let ttl_ms = match (*ttl_ms, *absttl) {
+            (0, _) | (_, true) => *ttl_ms,
+            (ttl_ms, false) => current_time_ms() as i64 + ttl_ms,
+        };
+        let ttl = ttl_ms.to_string();
+        let mut args = vec![RESTORE_COMMAND.as_bytes(), key, ttl.as_bytes(), payload, REPLACE_OPTION.as_bytes()];
+        if ttl_ms != 0 {
+            args.push(ABSTTL_OPTION.as_bytes());
+        }
+        construct_redis_command(&args)
+    }
+
+    pub fn move_events(from: ListDirection, to: ListDirection) -> (&'static str, &'static str) {
+        let pop_event = if from == ListDirection::LEFT { LPOP_EVENT } else { RPOP_EVENT };
+        let push_event = if to == ListDirection::LEFT { LPUSH_EVENT } else { RPUSH_EVENT };
+        (pop_event, push_event)
+    }
+
+    // 설정된 클래스일 때만 이벤트 핸들러로 넘겨서, 꺼져 있으면 큐에 아무것도 쌓이지 않게 함
+    // SET과 GETSET이 함께 씀
+    // 상대 TTL을 그대로 보내면 레플리카가 받은 시점부터 다시 세므로 마스터가 정한 절대 시각을 PXAT으로 붙여 한 명령으로 보냄
+    async fn propagate_set(
+        key: &[u8],
+        value: &[u8],
+        expiration_ms: Option<u64>,
+        publisher: &EventPublisher,
+        trace: Option<TraceContext>,
+    ) -> Result<(), RedisError> {
+        let expiration_ms = expiration_ms.map(|expiration_ms| expiration_ms.to_string());
+        let mut args = vec![SET_COMMAND.as_bytes(), key, value];
+        if let Some(expiration_ms) = &expiration_ms {
+            args.extend([PXAT_OPTION.as_bytes(), expiration_ms.as_bytes()]);
+        }
+        let replicated_command = construct_redis_command(&args);
+        publisher.publish_propagate_slave(replicated_command, trace).await
+            .map_err(|e| format!("Failed to propagate command to slaves: {}", e))?;
+        Ok(())
+    }
+
+    async fn notify_keyspace_event(
+        config: &Arc<RwLock<HashMap<String, String>>>,
+        publisher: &EventPublisher,
+        class: u32,
+        event: &'static str,
+        key: &[u8],
+    ) -> Result<(), RedisError> {
+        if !notify::is_enabled(&*config.read().await, class) {
+            return Ok(());
+        }
+        Ok(publisher.publish_keyspace_notification(class, event, key).await?)
+    }
+
+    fn hash_replication_command(&self) -> Vec<u8> {
+        let mut args = vec![self.name().as_bytes()];
+        match self {
+            Command::HSET { key, fields } => {
+                args.push(key);
+                for (field, value) in fields {
+                    args.push(field);
+                    args.push(value);
+                }
+                construct_redis_command(&args)
+            }
+            Command::HDEL { key, fields } => {
+                args.push(key);
+                args.extend(fields.iter().map(|field| field.as_slice()));
+                construct_redis_command(&args)
+            }
+            Command::HEXPIRE { key, seconds: amount, conditions, fields }
+            | Command::HPEXPIRE { key, milliseconds: amount, conditions, fields } => {
+                let amount = amount.to_string();
+                let numfields = fields.len().to_string();
+                args.push(key);
+                args.push(amount.as_bytes());
+                args.extend(conditions.iter().map(|condition| condition.as_str().as_bytes()));
+                args.push(FIELDS_OPTION.as_bytes());
+                args.push(numfields.as_bytes());
+                args.extend(fields.iter().map(|field| field.as_slice()));
+                construct_redis_command(&args)
+            }
+            Command::HPERSIST { key, fields } => {
+                let numfields = fields.len().to_string();
+                args.push(key);
+                args.push(FIELDS_OPTION.as_bytes());
+                args.push(numfields.as_bytes());
+                args.extend(fields.iter().map(|field| field.as_slice()));
+                construct_redis_command(&args)
+            }
+            _ => unreachable!("not a hash write command"),
+        }
+    }
+
+    // 해시 쓰기 명령의 공통 처리, (응답, 데이터가 바뀌었는지)를 돌려줌
+    // 마지막 필드가 사라지면 Redis처럼 키도 지움
+    fn execute_hash_write(&self, db: &mut Db) -> Result<(RespValue, bool), RedisError> {
+        let key = match self {
+            Command::HSET { key, .. }
+            | Command::HDEL { key, .. }
+            | Command::HEXPIRE { key, .. }
+            | Command::HPEXPIRE { key, .. }
+            | Command::HPERSIST { key, .. } => key,
+            _ => unreachable!("not a hash write command"),
+        };
+        if db.get(key).is_some_and(|entry| entry.is_expired()) {
+            db.remove(key);
+        }
+        if let Some(mut entry) = db.get_mut(key) {
+            entry.expect_hash()?;
+            entry.remove_expired_fields();
+        }
+
+        let result = match self {
+            Command::HSET { fields, .. } => {
+                let limits = db.listpack_limits();
+                let mut entry = db.get_or_insert_with(key, || ValueEntry::new_relative(RedisValue::Hash(HashValue::default()), None));
+                let mut added = 0;
+                for (field, value) in fields {
+                    entry.set_field_expiration_ms(field, None);
+                    if entry.expect_hash_mut()?.insert(field.clone(), value.clone(), limits) {
+                        added += 1;
+                    }
+                }
+                entry.touch();
+                (RespValue::Integer(added), true)
+            }
+            Command::HDEL { fields, .. } => {
+                let removed = match db.get_mut(key) {
+                    Some(mut entry) => fields.iter().filter(|field| entry.remove_hash_field(field)).count(),
+                    None => 0,
+                };
+                (RespValue::Integer(removed as i64), removed > 0)
+            }
+            Command::HEXPIRE { seconds: amount, conditions, fields, .. }
+            | Command::HPEXPIRE { milliseconds: amount, conditions, fields, .. } => {
+                let amount_ms = if matches!(self, Command::HEXPIRE { .. }) { amount.checked_mul(1000) } else { Some(*amount) };
+                let deadline_ms = amount_ms
+                    .filter(|ms| *ms >= 0)
+                    .and_then(|ms| ms.checked_add(current_time_ms() as i64))
+                    .ok_or_else(|| INVALID_FIELD_EXPIRE_ERROR.to_string())?;
+                let Some(mut entry) = db.get_mut(key) else {
+                    return Ok((RespValue::integer_array(&vec![-2; fields.len()]), false));
+                };
+
+                let mut results = Vec::new();
+                for field in fields {
+                    let current = entry.field_expiration_ms(field).map(|ms| ms as i64);
+                    let result = if entry.hash_field(field).is_none() {
+                        -2
+                    } else if !Self::expire_conditions_met(conditions, current, deadline_ms) {
+                        0
+                    } else if deadline_ms <= current_time_ms() as i64 {
+                        entry.remove_hash_field(field);
+                        2
+                    } else {
+                        entry.set_field_expiration_ms(field, Some(deadline_ms as u64));
+                        1
+                    };
+                    results.push(result);
+                }
+                let changed = results.iter().any(|result| *result > 0);
+                (RespValue::integer_array(&results), changed)
+            }
+            Command::HPERSIST { fields, .. } => {
+                let Some(mut entry) = db.get_mut(key) else {
+                    return Ok((RespValue::integer_array(&vec![-2; fields.len()]), false));
+                };
+                let results: Vec<i64> = fields
+                    .iter()
+                    .map(|field| match (entry.hash_field(field), entry.field_expiration_ms(field)) {
+                        (None, _) => -2,
+                        (Some(_), None) => -1,
+                        (Some(_), Some(_)) => {
+                            entry.set_field_expiration_ms(field, None);
+                            1
+                        }
+                    })
+                    .collect();
+                let changed = results.contains(&1);
+                (RespValue::integer_array(&results), changed)
+            }
+            _ => unreachable!("not a hash write command"),
+        };
+
+        if db.get(key).is_some_and(|entry| entry.expect_hash().is_ok_and(|hash| hash.is_empty())) {
+            db.remove(key);
+        }
+        Ok(result)
+    }
+
+    fn execute_expire(
+        key: &[u8],
+        deadline_ms: i64,
+        conditions: &[ExpireCondition],
+        db: &mut Db,
+    ) -> bool {
+        let current_expiration = match db.get(key) {
+            Some(entry) if !entry.is_expired() => entry.expiration_ms().map(|ms| ms as i64),
+            _ => return false,
+        };
+
+        if !Self::expire_conditions_met(conditions, current_expiration, deadline_ms) {
+            return false;
+        }
+
+        if deadline_ms <= current_time_ms() as i64 {
+            db.remove(key);
+        } else if let Some(mut entry) = db.get_mut(key) {
+            entry.set_expiration_ms(Some(deadline_ms as u64));
+            entry.touch();
+        }
+        true
+    }
+
+    fn execute_ttl(&self, key: &[u8], db: &Db) -> i64 {
+        let entry = match db.get(key) {
+            Some(entry) if !entry.is_expired() => entry,
+            _ => return -2,
+        };
+
+        match self {
+            Command::TTL(_) => entry.remaining_ms().map_or(-1, |ms| ((ms + 500) / 1000) as i64),
+            Command::PTTL(_) => entry.remaining_ms().map_or(-1, |ms| ms as i64),
+            Command::EXPIRETIME(_) => entry.expiration_ms().map_or(-1, |ms| (ms / 1000) as i64),
+            _ => entry.expiration_ms().map_or(-1, |ms| ms as i64),
+        }
+    }
+
+    fn execute_persist(key: &[u8], db: &mut Db) -> bool {
+        match db.get_mut(key) {
+            Some(mut entry) if !entry.is_expired() && entry.expiration_ms().is_some() => {
+                entry.set_expiration_ms(None);
+                entry.touch();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    // TODO: UNLINK는 지금은 DEL과 동일하게 동기적으로 해제됨
+    // UNLINK은 키만 바로 지우고, 큰 값의 해제는 lazyfree 스레드에 맡김
+    fn execute_del<'a>(keys: &'a [Vec<u8>], lazy: bool, db: &mut Db) -> Vec<&'a Vec<u8>> {
+        let mut deleted = Vec::new();
+        for key in keys {
+            let Some(entry) = db.remove(key) else {
+                continue;
+            };
+            if !entry.is_expired() {
+                deleted.push(key);
+            }
+            if lazy {
+                lazyfree::free_entry(entry);
+            }
+        }
+        deleted
+    }
+
+    async fn execute_config(command: &ConfigCommand, config: &Arc<RwLock<HashMap<String, String>>>, db: &Arc<RwLock<Db>>, runtime: &RuntimeSettings) -> RespValue {
+        match command {
+            // 여러 패턴에 걸린 설정도 한 번만 돌려줌
+            ConfigCommand::GET(patterns) => {
+                let config = config.read().await;
+                let mut matched: Vec<&config_handler::ConfigParameter> = Vec::new();
+                for pattern in patterns {
+                    for parameter in config_handler::matching_config_parameters(pattern) {
+                        if !matched.iter().any(|seen| seen.name == parameter.name) {
+                            matched.push(parameter);
+                        }
+                    }
+                }
+                RespValue::Map(
+                    matched
+                        .into_iter()
+                        .map(|parameter| (RespValue::bulk(parameter.name), RespValue::bulk(parameter.value(&config))))
+                        .collect(),
+                )
+            }
+            // Redis처럼 모든 값을 먼저 검사하고, 하나라도 틀리면 아무것도 바꾸지 않음
+            ConfigCommand::SET(pairs) => {
+                let mut updates: Vec<(&str, String)> = Vec::new();
+                for (name, value) in pairs {
+                    let Some(parameter) = config_handler::config_parameter(name) else {
+                        return RespValue::error(&format!("{} - '{}'", UNKNOWN_CONFIG_SET_OPTION_ERROR, name));
+                    };
+                    let checked = if updates.iter().any(|(key, _)| *key == parameter.key) {
+                        Err(DUPLICATE_CONFIG_PARAMETER_ERROR.to_string())
+                    } else {
+                        parameter.validate(value)
+                    };
+                    match checked {
+                        Ok(value) => updates.push((parameter.key, value)),
+                        Err(e) => return RespValue::error(&format!("{} (possibly related to argument '{}') - {}", CONFIG_SET_FAILED_ERROR, name, e)),
+                    }
+                }
+                let mut config = config.write().await;
+                for (key, value) in updates {
+                    config.insert(key.to_string(), value);
+                }
+                ConfigHandler::apply_runtime_config(&config, runtime);
+                let mut db = db.write().await;
+                db.set_lfu(ConfigHandler::lfu_config(&config));
+                db.set_listpack_limits(ConfigHandler::listpack_limits(&config));
+                RespValue::ok()
+            }
+        }
+    }
+
+    // MIGRATE로 보낼 (키, 남은 TTL, DUMP payload), 없거나 만료된 키는 뺌
+    fn dump_for_migration(keys: &[Vec<u8>], db: &Db) -> Vec<(Vec<u8>, u64, Vec<u8>)> {
+        keys.iter()
+            .filter_map(|key| {
+                let entry = db.get(key).filter(|entry| !entry.is_expired())?;
+                Some((key.clone(), entry.remaining_ms().unwrap_or(0), dump_payload(&entry.value, entry.field_expirations())))
+            })
+            .collect()
+    }
+
+    // 대상 인스턴스에 RESTORE를 파이프라인으로 보내고 받아들인 키와 첫 에러 응답을 돌려줌
+    // Redis처럼 연결과 응답 읽기가 각각 timeout 안에 끝나야 함
+    async fn send_to_target(
+        &self,
+        restore_command: &str,
+        dumped: &[(Vec<u8>, u64, Vec<u8>)],
+    ) -> Result<(Vec<Vec<u8>>, Option<RedisError>), RedisError> {
+        let Command::MIGRATE { host, port, timeout_ms, replace, auth, .. } = self else {
+            unreachable!("not a MIGRATE command");
+        };
+        let timeout = Duration::from_millis(*timeout_ms);
+        let mut target = tokio::time::timeout(timeout, Client::connect((host.as_str(), *port)))
+            .await
+            .ok()
+            .and_then(Result::ok)
+            .ok_or_else(|| RedisError::IoErr(MIGRATE_CONNECT_ERROR.into()))?;
+
+        let mut requests = Vec::new();
+        match auth {
+            Some((Some(username), password)) => requests.push(construct_redis_command(&[AUTH_COMMAND, username, password])),
+            Some((None, password)) => requests.push(construct_redis_command(&[AUTH_COMMAND, password])),
+            None => {}
+        }
+        for (key, ttl_ms, payload) in dumped {
+            let ttl = ttl_ms.to_string();
+            let mut args = vec![restore_command.as_bytes(), key, ttl.as_bytes(), payload];
+            if *replace {
+                args.push(REPLACE_OPTION.as_bytes());
+            }
+            requests.push(construct_redis_command(&args));
+        }
+
+        let exchange = async {
+            target.send_raw(&requests.concat()).await?;
+            let mut replies = Vec::with_capacity(requests.len());
+            for _ in 0..requests.len() {
+                replies.push(target.read_reply().await?);
+            }
+            Ok::<_, std::io::Error>(replies)
+        };
+        let replies = tokio::time::timeout(timeout, exchange)
+            .await
+            .ok()
+            .and_then(Result::ok)
+            .ok_or_else(|| RedisError::IoErr(MIGRATE_IO_ERROR.into()))?;
+
+        let mut replies = replies.into_iter();
+        if auth.is_some() {
+            if let Some(RespValue::Error(message)) = replies.next() {
+                return Err(format!("Target instance replied with error: {}", message).into());
+            }
+        }
+        let mut migrated = Vec::new();
+        let mut error = None;
+        for ((key, _, _), reply) in dumped.iter().zip(replies) {
+            match reply {
+                RespValue::Error(message) => {
+                    error.get_or_insert_with(|| RedisError::from(format!("Target instance replied with error: {}", message)));
+                }
+                _ => migrated.push(key.clone()),
+            }
+        }
+        Ok((migrated, error))
+    }
+
+    fn execute_restore(&self, db: &mut Db) -> Result<(), RedisError> {
+        let Command::RESTORE { key, ttl_ms, payload, replace, absttl, idle_seconds, frequency, .. } = self else {
+            unreachable!("not a RESTORE command");
+        };
+
+        if !replace && db.get(key).is_some_and(|entry| !entry.is_expired()) {
+            return Err(RedisError::BusyKey);
+        }
+        let (value, field_expirations) = restore_payload(payload, db.listpack_limits())?;
+
+        let expiration_ms = match (*ttl_ms, *absttl) {
+            (0, _) => None,
+            (ttl_ms, true) => Some(ttl_ms as u64),
+            (ttl_ms, false) => Some(current_time_ms() + ttl_ms as u64),
+        };
+        if expiration_ms.is_some_and(|expiration_ms| expiration_ms <= current_time_ms()) {
+            db.remove(key);
+            return Ok(());
+        }
+
+        let mut entry = ValueEntry::new_absolute(value, expiration_ms);
+        entry.set_field_expirations(field_expirations);
+        if let Some(idle_seconds) = idle_seconds {
+            entry.set_idle_ms(idle_seconds * 1000);
+        }
+        if let Some(frequency) = frequency {
+            entry.set_lfu_frequency(*frequency);
+        }
+        db.insert(key.clone(), entry);
+        Ok(())
+    }
+
+    // OBJECT는 키를 조회해도 접근 시간을 갱신하지 않음
+    fn execute_object(command: &ObjectCommand, lfu_enabled: bool, db: &Db) -> Result<RespValue, RedisError> {
+        let key = match command {
+            ObjectCommand::ENCODING(key)
+            | ObjectCommand::IDLETIME(key)
+            | ObjectCommand::FREQ(key)
+            | ObjectCommand::REFCOUNT(key) => key,
+            ObjectCommand::HELP => {
+                return Ok(RespValue::Array(OBJECT_HELP_LINES.iter().map(|line| RespValue::SimpleString(line.to_string())).collect()));
+            }
+        };
+        let entry = match db.get(key) {
+            Some(entry) if !entry.is_expired() => entry,
+            _ => return Ok(RespValue::NullBulk),
+        };
+
+        match command {
+            ObjectCommand::ENCODING(_) => Ok(RespValue::bulk(entry.value.encoding())),
+            ObjectCommand::IDLETIME(_) if lfu_enabled => Err(LFU_SELECTED_ERROR.into()),
+            ObjectCommand::IDLETIME(_) => Ok(RespValue::Integer((entry.idle_ms() / 1000) as i64)),
+            ObjectCommand::FREQ(_) if !lfu_enabled => Err(LFU_NOT_SELECTED_ERROR.into()),
+            ObjectCommand::FREQ(_) => Ok(RespValue::Integer(db.lfu_frequency(entry) as i64)),
+            // TODO: 값 공유(shared integers)가 없어서 항상 1
+            ObjectCommand::REFCOUNT(_) => Ok(RespValue::Integer(1)),
+            ObjectCommand::HELP => unreachable!("OBJECT HELP has no key"),
+        }
+    }
+
+    // TODO: DB가 하나뿐이라 FLUSHDB와 FLUSHALL이 같은 동작을 함
+    fn execute_flush(mode: FlushMode, db: &mut Db) {
+        let old_db = db.take();
+        match mode {
+            FlushMode::SYNC => drop(old_db),
+            FlushMode::ASYNC => {
+                // 빈 맵으로 먼저 교체하고, 기존 값의 해제는 백그라운드에서 진행
+                lazyfree::free_db(old_db);
+            }
+        }
+    }
+
+    // KeySet에서 바로 뽑으므로 키 수와 상관없이 한 번에 고름, KeySet의 순서는 명령 순서로만 정해져서 시드가 고정되면 결과도 재현됨
+    // 뽑았다가 만료되어 버린 키도 함께 돌려줘서 지우게 함
+    fn execute_randomkey(db: &Db) -> (Option<Vec<u8>>, Vec<Vec<u8>>) {
+        let mut expired: Vec<Vec<u8>> = Vec::new();
+        for _ in 0..RANDOMKEY_MAX_ATTEMPTS {
+            let Some(key) = db.key_set().random(db.random()) else {
+                return (None, expired);
+            };
+            if db.is_alive(key) {
+                return (Some(key.to_vec()), expired);
+            }
+            if !expired.iter().any(|expired| **expired == **key) {
+                expired.push(key.to_vec());
+            }
+        }
+        (db.alive().next().map(|(key, _)| key.to_vec()), expired)
+    }
+
+    // 키를 고정된 해시 순서로 순회하기 때문에, 순회 내내 존재한 키는 정확히 한 번 반환됨
+    // 순서가 HashMap의 버킷 배치와 상관없으므로 재해시로 테이블이 커지거나 줄어도 커서의 위치는 그대로임(Redis가 역비트 커서로 얻는 성질)
+    // 커서는 다음에 볼 해시 값이며, 정렬된 색인에서 커서 위치부터 COUNT개만 꺼내므로 한 번의 호출이 키스페이스 전체를 훑지 않음
+    fn execute_scan(
+        cursor: u64,
+        pattern: &Option<Vec<u8>>,
+        count: usize,
+        type_filter: &Option<String>,
+        namespace: &Option<Vec<u8>>,
+        db: &Db,
+    ) -> (u64, Vec<Vec<u8>>, Vec<Vec<u8>>) {
+        // COUNT는 클라이언트가 정하므로 미리 잡는 크기는 제한함
+        let mut page: Vec<(u64, &Arc<[u8]>)> = Vec::with_capacity(count.min(SCAN_MAX_PREALLOCATED));
+        let mut ordered = db.scan_from(cursor).peekable();
+        page.extend(ordered.by_ref().take(count));
+
+        let next_cursor = match page.last() {
+            Some(&(last_hash, _)) => {
+                // 해시 충돌로 같은 해시를 가진 키가 페이지 경계에서 잘리지 않도록 함께 반환
+                while let Some(colliding) = ordered.next_if(|&(hash, _)| hash == last_hash) {
+                    page.push(colliding);
+                }
+                // 마지막 해시가 u64::MAX면 그 뒤에 올 키가 없으므로 순회를 끝냄
+                match last_hash.checked_add(1) {
+                    Some(next) if ordered.peek().is_some() => next,
+                    _ => 0,
+                }
+            }
+            None => 0,
+        };
+
+        // 페이지에서 만료된 키는 패턴이나 타입과 상관없이 지우도록 따로 모음
+        let (keys, expired): (Vec<_>, Vec<_>) = page.into_iter().map(|(_, key)| key).partition(|key| db.is_alive(key));
+        let prefix = namespace.as_deref().unwrap_or_default();
+        let keys = keys
+            .into_iter()
+            .filter_map(|key| {
+                let name = key.strip_prefix(prefix)?;
+                let matched = pattern.as_ref().map_or(true, |pattern| glob_match(pattern, name))
+                    && type_filter.as_ref().map_or(true, |type_name| db[&**key].value.type_name().eq_ignore_ascii_case(type_name));
+                matched.then(|| name.to_vec())
+            })
+            .collect();
+        (next_cursor, keys, expired.into_iter().map(|key| key.to_vec()).collect())
+    }
+
+    // 패턴에 맞는 키 중 살아 있는 키와 만료된 키를 나눠 돌려줌, 살아 있는 키는 네임스페이스 접두사를 떼고, 만료된 키는 지울 수 있게 그대로 둠
+    fn execute_keys(pattern: &[u8], namespace: &Option<Vec<u8>>, db: &Db) -> (Vec<Vec<u8>>, Vec<Vec<u8>>) {
+        let prefix = namespace.as_deref().unwrap_or_default();
+        let (keys, expired): (Vec<_>, Vec<_>) = db
+            .iter()
+            .filter(|(key, _)| key.strip_prefix(prefix).is_some_and(|name| glob_match(pattern, name)))
+            .partition(|(_, entry)| !entry.is_expired());
+        let keys = keys.into_iter().map(|(key, _)| key[prefix.len()..].to_vec()).collect();
+        (keys, expired.into_iter().map(|(key, _)| key.to_vec()).collect())
+    }
+
+    pub async fn execute_replconf(
+        args: &[String],
+        client_id: u64,
+        publisher: &EventPublisher,
+    ) -> Option<RespValue> {
+        // TODO: 요구사항에는, --listening-port로 전파하는 것처럼 되어있지만 실제로는 그렇지 않아 리팩토링 필요
+        let subcommand = args[0].to_lowercase();
+        if subcommand == REPLCONF_LISTENING_PORT {
+            if let Err(e) = publisher.publish_slave_listening_port(client_id, args[1].parse::<u16>().ok()).await {
+                return Some(RespValue::error(&format!("Failed to register slave: {}", e)));
+            }
+            return Some(RespValue::ok());
+        } else if subcommand == REPLCONF_IP_ADDRESS {
+            if let Err(e) = publisher.publish_slave_announced(client_id, args[1].clone()).await {
+                return Some(RespValue::error(&format!("Failed to register slave: {}", e)));
+            }
+            return Some(RespValue::ok());
+        } else if subcommand == REPLCONF_CAPA {
+            return Some(RespValue::ok());
+        } else if args[0].eq_ignore_ascii_case(REPLCONF_ACK) {
+            // ACK에는 응답하지 않음
+            if let Ok(offset) = args[1].parse::<i64>() {
+                if let Err(e) = publisher.publish_slave_acked(client_id, offset).await {
+                    log_warning!("Failed to record replica ack: {}", e);
+                }
+            }
+            return None;
+        }
+        Some(RespValue::error("Invalid REPLCONF arguments"))
+    }
+
+    // 복제 백로그가 없어 빠진 구간을 다시 보낼 수 없으므로 PSYNC는 항상 전체 동기화로 응답함
+    async fn execute_psync(
+        args: &Vec<String>,
+        db: &Arc<RwLock<Db>>,
+        config: &Arc<RwLock<HashMap<String, String>>>,
+        replication_config: &Arc<RwLock<ReplicationConfig>>,
+        functions: &Arc<RwLock<FunctionRegistry>>,
+    ) -> Result<Vec<CommandResponse>, RedisError> {
+        let repl_guard = replication_config.read().await;
+        let master_repl_id = repl_guard.get_repl_id().await;
+        let master_offset = repl_guard.get_repl_offset().await;
+        if let [replid, offset] = args.as_slice() {
+            if replid != "?" {
+                // 이어받으면 보낼 것이 없으므로 응답만 하고, 복제 ID가 바뀌었으면 새 ID를 알려 줌
+                if let Ok(psync_offset) = offset.parse::<u64>() {
+                    if repl_guard.can_continue(replid, psync_offset).await {
+                        log_notice!("Partial resynchronization request accepted for {} at offset {}", replid, offset);
+                        return Ok(vec![CommandResponse::Value(RespValue::SimpleString(format!("{} {}", CONTINUE, master_repl_id)))]);
+                    }
+                }
+                log_notice!(
+                    "Partial resynchronization not accepted for {} at offset {}: no replication backlog past offset {}",
+                    replid,
+                    offset,
+                    master_offset
+                );
+            }
+        }
+        drop(repl_guard);
+        let full_resync_response = RespValue::SimpleString(format!("{} {} {}", FULLRESYNC, master_repl_id, master_offset));
+
+        // 스냅샷 이후의 쓰기는 이벤트 루프가 이 응답 뒤에 전파하므로 레플리카에서 순서가 맞음
+        let entries = persistence::snapshot(&*db.read().await);
+        let functions = functions.read().await.codes();
+        let compress = persistence::rdb_compression(&*config.read().await);
+        let rdb = logging::spawn_blocking(move || rdb_codec::encode_rdb(&[&entries], &[], &functions, compress))
+            .await
+            .map_err(|e| format!("Failed to build RDB payload: {}", e))?;
+
+        Ok(vec![
+            CommandResponse::Value(full_resync_response),
+            CommandResponse::Rdb(rdb),
+        ])
+    }
+
+    pub async fn execute_without_response(
+        &self,
+        db: &mut Db,
+    ) -> Result<(), RedisError> {
+        match self {
+            Command::SET { key, value, expiration } => Self::execute_set(key, value, *expiration, db).await.map(|_| ()),
+            // 레플리카는 마스터가 보낸 시각을 그대로 적용하고, 이미 지난 시각이어도 직접 지우지 않고 마스터의 DEL을 기다림
+            Command::EXPIRE { key, .. }
+            | Command::PEXPIRE { key, .. }
+            | Command::EXPIREAT { key, .. }
+            | Command::PEXPIREAT { key, .. } => {
+                let deadline_ms = self.expire_deadline_ms()?;
+                if let Some(mut entry) = db.get_mut(key) {
+                    entry.set_expiration_ms(Some(deadline_ms.max(0) as u64));
+                }
+                Ok(())
+            }
+            Command::PERSIST(key) => {
+                Self::execute_persist(key, db);
+                Ok(())
+            }
+            Command::HSET { .. }
+            | Command::HDEL { .. }
+            | Command::HEXPIRE { .. }
+            | Command::HPEXPIRE { .. }
+            | Command::HPERSIST { .. } => self.execute_hash_write(db).map(|_| ()),
+            Command::LPUSH { .. } | Command::RPUSH { .. } | Command::LPOP { .. } | Command::RPOP { .. } => {
+                self.execute_list_write(db).map(|_| ())
+            }
+            Command::LMOVE { .. } => self.execute_move(db).map(|_| ()),
+            Command::LMPOP { .. } | Command::ZMPOP { .. } => self.execute_multi_pop(db).map(|_| ()),
+            Command::ZADD { key, members, incr: false } => Self::execute_zadd(key, members, db).map(|_| ()),
+            Command::ZADD { key, members, incr: true } => Self::execute_zincrby(key, members[0].0, &members[0].1, db).map(|_| ()),
+            Command::ZINCRBY { key, increment, member } => Self::execute_zincrby(key, *increment, member, db).map(|_| ()),
+            Command::DEL(keys) | Command::UNLINK(keys) => {
+                Self::execute_del(keys, matches!(self, Command::UNLINK(_)), db);
+                Ok(())
+            }
+            Command::GETSET { key, value } => Self::execute_set(key, value, None, db).await.map(|_| ()),
+            Command::INCR(key) => Self::execute_incr(key, db).map(|_| ()),
+            Command::RESTORE { .. } => self.execute_restore(db),
+            Command::FLUSHDB(mode) | Command::FLUSHALL(mode) => {
+                Self::execute_flush(*mode, db);
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+// Redis처럼 정수 점수는 소수점 없이, 무한대는 inf/-inf로 표시함
+pub fn format_score(score: f64) -> String {
+    if score.is_infinite() {
+        return if score > 0.0 { "inf".into() } else { "-inf".into() };
+    }
+    score.to_string()
+}
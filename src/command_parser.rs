@@ -1,146 +1,2194 @@
-use crate::command::{Command, ConfigCommand};
+use crate::cluster::{self, SlotState};
+use crate::command::{AclCommand, ClientCommand, ClientKillFilter, ClientType, ClusterCommand, Command, ConfigCommand, DebugCommand, Expiration, ExpireCondition, FlushMode, FunctionCommand, LatencyCommand, ListDirection, MemoryCommand, ScoreDirection, ObjectCommand, PubSubCommand, ScriptCommand, SentinelCommand};
+use crate::command_registry::{self, ClientNames};
 use crate::errors::ArgumentError;
+use crate::memory::DEFAULT_USAGE_SAMPLES;
 use crate::protocol_constants::*;
+use crate::tracking::TrackingOptions;
+use crate::util::parse_bytes;
+
+// ZRANDMEMBER와 HRANDFIELD의 음수 count로 만들 수 있는 응답 요소 수
+const RANDOM_COUNT_LIMIT: i64 = 1 << 24;
 
 pub struct CommandParser;
 
+// 연결마다 하나씩 두고 읽은 바이트를 쌓아 둠, TCP 세그먼트로 나뉘어 온 요청과 한 번에 온 여러 요청을 순서대로 하나씩 꺼냄
+// 버퍼는 요청 크기만큼 늘어나고, bulk string 하나가 max_bulk_len(proto-max-bulk-len)을 넘으면 프로토콜 에러
+pub struct FrameDecoder {
+    buffer: Vec<u8>,
+    max_bulk_len: usize,
+}
+
+impl Default for FrameDecoder {
+    fn default() -> Self {
+        Self::with_max_bulk_len(DEFAULT_PROTO_MAX_BULK_LEN)
+    }
+}
+
+impl FrameDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_max_bulk_len(max_bulk_len: usize) -> Self {
+        Self {
+            buffer: Vec::new(),
+            max_bulk_len,
+        }
+    }
+
+    pub fn feed(&mut self, data: &[u8]) {
+        self.buffer.extend_from_slice(data);
+    }
+
+    // 완성된 요청 하나를 버퍼에서 떼어 냄, 아직 덜 왔으면 None
+    pub fn next_frame(&mut self) -> Result<Option<Vec<u8>>, ArgumentError> {
+        let Some(frame_len) = CommandParser::frame_len(&self.buffer, self.max_bulk_len)? else {
+            return Ok(None);
+        };
+        Ok(Some(self.buffer.drain(..frame_len).collect()))
+    }
+}
+
 impl CommandParser {
-    pub fn parse_message(message: &str) -> Result<Command, ArgumentError> {
-        let mut lines = message.lines();
-        let first_line = lines.next().ok_or(ArgumentError::General(EMPTY_MESSAGE_ERROR.into()))?;
+    // 버퍼 앞에 완성된 요청이 있으면 그 길이, 아직 덜 왔으면 None
+    // "*<n>"으로 시작하면 bulk string 배열, 아니면 개행까지가 인라인 명령(빈 줄 포함)
+    // 개행 없이 계속 쌓이는 인라인 요청이나 너무 긴 bulk string은 다 받기 전에 거절함
+    // Redis처럼 "*0"이나 "*-1"은 인자 없는 요청으로 보고 헤더 줄만 잘라 냄
+    // 여기서 나는 에러는 요청 경계를 잃은 프로토콜 에러라 연결을 닫아야 함
+    pub fn frame_len(buffer: &[u8], max_bulk_len: usize) -> Result<Option<usize>, ArgumentError> {
+        let Some(line_end) = buffer.iter().position(|&b| b == b'\n') else {
+            if buffer.len() > PROTO_INLINE_MAX_SIZE {
+                return Err(ArgumentError::General(INLINE_REQUEST_TOO_BIG_ERROR.into()));
+            }
+            return Ok(None);
+        };
+        if !buffer.starts_with(ARRAY_PREFIX.as_bytes()) {
+            return Ok(Some(line_end + 1));
+        }
+        let num_args = Self::frame_number(&buffer[1..line_end]).ok_or(ArgumentError::General(INVALID_MULTIBULK_LENGTH_ERROR.into()))?;
+        let mut pos = line_end + 1;
+        for _ in 0..num_args.max(0) {
+            let Some(len_end) = buffer[pos..].iter().position(|&b| b == b'\n').map(|end| pos + end) else {
+                return Ok(None);
+            };
+            if !buffer[pos..].starts_with(BULK_STRING_PREFIX.as_bytes()) {
+                return Err(ArgumentError::General(format!("{}, got '{}'", EXPECTED_BULK_PREFIX_ERROR, buffer[pos] as char)));
+            }
+            let bulk_len = Self::frame_number(&buffer[pos + 1..len_end])
+                .and_then(|len| usize::try_from(len).ok())
+                .filter(|&len| len <= max_bulk_len)
+                .ok_or(ArgumentError::General(INVALID_BULK_LENGTH_ERROR.into()))?;
+            pos = len_end + 1 + bulk_len + CRLF.len();
+            if pos > buffer.len() {
+                return Ok(None);
+            }
+            if &buffer[pos - CRLF.len()..pos] != CRLF.as_bytes() {
+                return Err(ArgumentError::General(BULK_STRING_LENGTH_MISMATCH_ERROR.into()));
+            }
+        }
+        Ok(Some(pos))
+    }
 
-        if first_line.starts_with(ARRAY_PREFIX) {
-            let num_args: usize = first_line[1..].parse().map_err(|_| ArgumentError::General(INVALID_ARRAY_SIZE_ERROR.into()))?;
-            let mut args = Vec::new();
+    fn frame_number(line: &[u8]) -> Option<i64> {
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        std::str::from_utf8(line).ok()?.parse().ok()
+    }
 
-            for _ in 0..num_args {
-                let bulk_len_line = lines.next().ok_or(ArgumentError::General(MISSING_BULK_LENGTH_ERROR.into()))?;
-                if !bulk_len_line.starts_with(BULK_STRING_PREFIX) {
-                    return Err(ArgumentError::General(INVALID_BULK_STRING_FORMAT_ERROR.into()));
-                }
-                let bulk_len: usize = bulk_len_line[1..].parse().map_err(|_| ArgumentError::General(INVALID_BULK_LENGTH_ERROR.into()))?;
-                let bulk_string = lines.next().ok_or(ArgumentError::General(MISSING_BULK_STRING_ERROR.into()))?;
+    // frame_len으로 잘라 낸 요청 하나의 인자들, 빈 줄이면 빈 목록
+    pub fn frame_args(frame: &[u8]) -> Result<Vec<Vec<u8>>, ArgumentError> {
+        if frame.starts_with(ARRAY_PREFIX.as_bytes()) {
+            Self::read_multibulk(frame)
+        } else {
+            Self::split_inline(frame)
+        }
+    }
 
-                if bulk_string.len() != bulk_len {
-                    return Err(ArgumentError::General(BULK_STRING_LENGTH_MISMATCH_ERROR.into()));
-                }
-                args.push(bulk_string.to_string());
+    // redis-cli/telnet의 인라인 명령을 Redis(sdssplitargs)처럼 나눔
+    // "..." 안에서는 \n, \r, \t, \b, \a, \xHH 이스케이프를, '...' 안에서는 \'만 풀어 줌
+    // 닫는 따옴표 바로 뒤에 공백이 아닌 글자가 오거나 따옴표가 닫히지 않으면 에러
+    fn split_inline(line: &[u8]) -> Result<Vec<Vec<u8>>, ArgumentError> {
+        let unbalanced = || ArgumentError::General(UNBALANCED_QUOTES_ERROR.into());
+        let mut args = Vec::new();
+        let mut pos = 0;
+        loop {
+            while pos < line.len() && line[pos].is_ascii_whitespace() {
+                pos += 1;
+            }
+            if pos >= line.len() {
+                return Ok(args);
             }
 
-            if let Some(command_name) = args.get(0).map(|s| s.as_str()) {
-                match command_name {
-                    PING_COMMAND => Self::parse_ping(&args),
-                    ECHO_COMMAND => Self::parse_echo(&args),
-                    GET_COMMAND => Self::parse_get(&args),
-                    SET_COMMAND => Self::parse_set(&args),
-                    CONFIG_COMMAND => Self::parse_config(&args),
-                    KEYS_COMMAND => Self::parse_keys(&args),
-                    INFO_COMMAND => Self::parse_info(&args),
-                    REPLCONF_COMMAND => Self::parse_replconf(&args),
-                    PSYNC_COMMAND => Self::parse_psync(&args),
-                    _ => Err(ArgumentError::General(format!("{}: {}", UNKNOWN_COMMAND_ERROR, command_name))),
+            let mut arg = Vec::new();
+            let quote = match line[pos] {
+                b'"' | b'\'' => Some(line[pos]),
+                _ => None,
+            };
+            if quote.is_some() {
+                pos += 1;
+            }
+            loop {
+                let Some(&c) = line.get(pos) else {
+                    if quote.is_some() {
+                        return Err(unbalanced());
+                    }
+                    break;
+                };
+                match quote {
+                    None if c.is_ascii_whitespace() => break,
+                    None => arg.push(c),
+                    Some(b'"') if c == b'\\' => {
+                        if let Some(byte) = Self::hex_escape(&line[pos + 1..]) {
+                            arg.push(byte);
+                            pos += 3;
+                        } else if let Some(&escaped) = line.get(pos + 1) {
+                            pos += 1;
+                            arg.push(match escaped {
+                                b'n' => b'\n',
+                                b'r' => b'\r',
+                                b't' => b'\t',
+                                b'b' => 0x08,
+                                b'a' => 0x07,
+                                other => other,
+                            });
+                        } else {
+                            arg.push(c);
+                        }
+                    }
+                    Some(b'\'') if c == b'\\' && line.get(pos + 1) == Some(&b'\'') => {
+                        pos += 1;
+                        arg.push(b'\'');
+                    }
+                    Some(q) if c == q => {
+                        if line.get(pos + 1).is_some_and(|next| !next.is_ascii_whitespace()) {
+                            return Err(unbalanced());
+                        }
+                        pos += 1;
+                        break;
+                    }
+                    Some(_) => arg.push(c),
                 }
-            } else {
-                Err(ArgumentError::General(EMPTY_COMMAND_ERROR.into()))
+                pos += 1;
             }
-        } else {
-            Err(ArgumentError::General(UNSUPPORTED_PROTOCOL_ERROR.into()))
+            args.push(arg);
+        }
+    }
+
+    // "\x" 뒤의 16진수 두 자리
+    fn hex_escape(rest: &[u8]) -> Option<u8> {
+        match rest {
+            [b'x', high, low, ..] if high.is_ascii_hexdigit() && low.is_ascii_hexdigit() => {
+                u8::from_str_radix(std::str::from_utf8(&[*high, *low]).ok()?, 16).ok()
+            }
+            _ => None,
+        }
+    }
+
+    fn read_multibulk(message: &[u8]) -> Result<Vec<Vec<u8>>, ArgumentError> {
+        let mut rest = message;
+        let first_line = Self::take_line(&mut rest).ok_or(ArgumentError::General(EMPTY_MESSAGE_ERROR.into()))?;
+        if !first_line.starts_with(ARRAY_PREFIX.as_bytes()) {
+            return Err(ArgumentError::General(UNSUPPORTED_PROTOCOL_ERROR.into()));
+        }
+
+        let num_args: i64 = parse_bytes(&first_line[1..]).ok_or(ArgumentError::General(INVALID_MULTIBULK_LENGTH_ERROR.into()))?;
+        let mut args = Vec::new();
+
+        for _ in 0..num_args.max(0) {
+            let bulk_len_line = Self::take_line(&mut rest).ok_or(ArgumentError::General(MISSING_BULK_LENGTH_ERROR.into()))?;
+            if !bulk_len_line.starts_with(BULK_STRING_PREFIX.as_bytes()) {
+                return Err(ArgumentError::General(EXPECTED_BULK_PREFIX_ERROR.into()));
+            }
+            let bulk_len: usize = parse_bytes(&bulk_len_line[1..]).ok_or(ArgumentError::General(INVALID_BULK_LENGTH_ERROR.into()))?;
+            if rest.is_empty() {
+                return Err(ArgumentError::General(MISSING_BULK_STRING_ERROR.into()));
+            }
+            // 길이만큼 그대로 읽어야 값 안의 개행(FUNCTION LOAD 코드 등)이 보존됨
+            let bulk_string = rest
+                .get(..bulk_len)
+                .ok_or(ArgumentError::General(BULK_STRING_LENGTH_MISMATCH_ERROR.into()))?;
+            rest = rest[bulk_len..]
+                .strip_prefix(CRLF.as_bytes())
+                .or_else(|| rest[bulk_len..].strip_prefix(b"\n"))
+                .ok_or(ArgumentError::General(BULK_STRING_LENGTH_MISMATCH_ERROR.into()))?;
+            args.push(bulk_string.to_vec());
+        }
+        Ok(args)
+    }
+
+    // 키와 값은 바이트 그대로 넘기고, 명령 이름/옵션/숫자처럼 문자열로 비교할 인자만 text/upper로 읽음
+    // 명령 이름으로 레지스트리에서 핸들러를 찾아 arity를 먼저 확인한 뒤 핸들러의 파서에 넘김
+    pub fn parse_args(args: &[Vec<u8>]) -> Result<Command, ArgumentError> {
+        let Some(command_name) = args.first().map(|name| Self::text(name)) else {
+            return Err(ArgumentError::General(EMPTY_COMMAND_ERROR.into()));
+        };
+        let Some(handler) = command_registry::lookup(&command_name) else {
+            return Err(Self::unknown_command(args));
+        };
+        if !handler.spec().accepts_arity(args.len()) {
+            return Err(Self::wrong_arity(&command_name));
+        }
+        // 파서는 args[0]을 대문자 상수와 비교하므로 소문자로 온 이름은 테이블의 이름으로 바꿔서 넘김
+        if args[0] != handler.spec().name.as_bytes() {
+            let mut args = args.to_vec();
+            args[0] = handler.spec().name.as_bytes().to_vec();
+            return handler.parse(&args);
+        }
+        handler.parse(args)
+    }
+
+    // 클라이언트 요청은 rename-command로 바꾼 이름을 원래 이름으로 돌려서 파싱하고, 끈 명령은 없는 명령처럼 다룸
+    pub fn parse_client_args(args: &[Vec<u8>], names: &ClientNames) -> Result<Command, ArgumentError> {
+        let Some(command_name) = args.first().map(|name| Self::text(name)) else {
+            return Self::parse_args(args);
+        };
+        match names.command_name(&command_name) {
+            Some(name) if name == command_name => Self::parse_args(args),
+            Some(name) => {
+                let mut args = args.to_vec();
+                args[0] = name.as_bytes().to_vec();
+                Self::parse_args(&args)
+            }
+            None => Err(Self::unknown_command(args)),
+        }
+    }
+
+    // Redis와 같은 형식: ERR unknown command 'name', with args beginning with: 'a' 'b'
+    // 인자는 앞에서부터 붙이다 128자를 넘으면 멈추고, 인자마다 뒤에 공백을 하나 둠
+    fn unknown_command(args: &[Vec<u8>]) -> ArgumentError {
+        let name: String = Self::text(&args[0]).chars().take(UNKNOWN_COMMAND_ECHO_LIMIT).collect();
+        let mut echoed = String::new();
+        for arg in &args[1..] {
+            let room = UNKNOWN_COMMAND_ECHO_LIMIT.saturating_sub(echoed.len());
+            if room == 0 {
+                break;
+            }
+            let arg: String = Self::text(arg).chars().take(room).collect();
+            echoed.push_str(&format!("'{}' ", arg));
+        }
+        ArgumentError::General(format!("{} '{}', with args beginning with: {}", UNKNOWN_COMMAND_ERROR, name, echoed))
+    }
+
+    fn take_line<'a>(rest: &mut &'a [u8]) -> Option<&'a [u8]> {
+        if rest.is_empty() {
+            return None;
         }
+        let (line, remaining) = match rest.iter().position(|&b| b == b'\n') {
+            Some(end) => (&rest[..end], &rest[end + 1..]),
+            None => (*rest, &[][..]),
+        };
+        *rest = remaining;
+        Some(line.strip_suffix(b"\r").unwrap_or(line))
     }
 
-    fn check_args_len(args: &[String], expected_len: usize, command_name: &str) -> Result<(), ArgumentError> {
+    fn text(arg: &[u8]) -> String {
+        String::from_utf8_lossy(arg).into_owned()
+    }
+
+    fn upper(arg: &[u8]) -> String {
+        Self::text(arg).to_uppercase()
+    }
+
+    fn texts(args: &[Vec<u8>]) -> Vec<String> {
+        args.iter().map(|arg| Self::text(arg)).collect()
+    }
+
+    // command_name은 명령 이름이나 subcommand_name의 "object|help" 꼴
+    fn check_args_len(args: &[Vec<u8>], expected_len: usize, command_name: &str) -> Result<(), ArgumentError> {
         if args.len() != expected_len {
-            Err(ArgumentError::General(format!("{}: {} {}", ARGUMENT_ERROR, command_name, expected_len - 1)))
+            Err(Self::wrong_arity(command_name))
         } else {
             Ok(())
         }
     }
 
-    fn parse_ping(args: &[String]) -> Result<Command, ArgumentError> {
-        Self::check_args_len(args, 1, PING_COMMAND)?;
-        Ok(Command::PING)
+    fn wrong_arity(command_name: &str) -> ArgumentError {
+        ArgumentError::General(format!("{} '{}' command", WRONG_ARITY_ERROR, command_name.to_lowercase()))
+    }
+
+    // Redis가 하위 명령의 arity 에러에 쓰는 이름, 예: object|help
+    fn subcommand_name(args: &[Vec<u8>]) -> String {
+        format!("{}|{}", Self::text(&args[0]), Self::text(&args[1])).to_lowercase()
+    }
+
+    // Redis와 같은 형식: ERR unknown subcommand 'x'. Try CLIENT HELP.
+    fn unknown_subcommand(args: &[Vec<u8>]) -> ArgumentError {
+        let subcommand: String = Self::text(&args[1]).chars().take(UNKNOWN_COMMAND_ECHO_LIMIT).collect();
+        ArgumentError::General(format!("{} '{}'. Try {} HELP.", UNKNOWN_SUBCOMMAND_ERROR, subcommand, Self::upper(&args[0])))
+    }
+
+    pub(crate) fn parse_ping(args: &[Vec<u8>]) -> Result<Command, ArgumentError> {
+        if args.len() > 2 {
+            return Err(Self::wrong_arity(PING_COMMAND));
+        }
+        Ok(Command::PING(args.get(1).cloned()))
+    }
+
+    // 인자가 없는 명령
+    pub(crate) fn parse_no_args(args: &[Vec<u8>]) -> Result<Command, ArgumentError> {
+        let command_name = Self::text(&args[0]);
+        Self::check_args_len(args, 1, &command_name)?;
+        match command_name.as_str() {
+            ASKING_COMMAND => Ok(Command::ASKING),
+            READONLY_COMMAND => Ok(Command::READONLY),
+            READWRITE_COMMAND => Ok(Command::READWRITE),
+            QUIT_COMMAND => Ok(Command::QUIT),
+            RESET_COMMAND => Ok(Command::RESET),
+            MULTI_COMMAND => Ok(Command::MULTI),
+            EXEC_COMMAND => Ok(Command::EXEC),
+            DISCARD_COMMAND => Ok(Command::DISCARD),
+            UNWATCH_COMMAND => Ok(Command::UNWATCH),
+            RANDOMKEY_COMMAND => Ok(Command::RANDOMKEY),
+            DBSIZE_COMMAND => Ok(Command::DBSIZE(None)),
+            BGSAVE_COMMAND => Ok(Command::BGSAVE),
+            LASTSAVE_COMMAND => Ok(Command::LASTSAVE),
+            _ => Err(Self::unknown_command(args)),
+        }
     }
 
-    fn parse_echo(args: &[String]) -> Result<Command, ArgumentError> {
+    pub(crate) fn parse_echo(args: &[Vec<u8>]) -> Result<Command, ArgumentError> {
         Self::check_args_len(args, 2, ECHO_COMMAND)?;
         Ok(Command::ECHO(args[1].clone()))
     }
 
-    fn parse_get(args: &[String]) -> Result<Command, ArgumentError> {
+    pub(crate) fn parse_get(args: &[Vec<u8>]) -> Result<Command, ArgumentError> {
         Self::check_args_len(args, 2, GET_COMMAND)?;
         Ok(Command::GET(args[1].clone()))
     }
 
-    fn parse_set(args: &[String]) -> Result<Command, ArgumentError> {
+    pub(crate) fn parse_set(args: &[Vec<u8>]) -> Result<Command, ArgumentError> {
         if args.len() < 3 {
-            return Err(ArgumentError::General(SET_ARGUMENTS_ERROR.into()));
+            return Err(Self::wrong_arity(SET_COMMAND));
         }
 
         let key = args[1].clone();
         let value = args[2].clone();
-        let mut ex = None;
-        let mut px = None;
+        let mut expiration = None;
 
+        // 만료 옵션은 하나만 받음
         let mut arg_index = 3;
         while arg_index < args.len() {
-            match args[arg_index].to_uppercase().as_str() {
-                PX_OPTION => {
-                    px = Some(Self::parse_option_value(&args, arg_index, PX_OPTION)?);
+            match Self::parse_expiration(args, arg_index)? {
+                Some(_) if expiration.is_some() => return Err(ArgumentError::General(SYNTAX_ERROR.into())),
+                Some(parsed) => {
+                    expiration = Some(parsed);
                     arg_index += 2;
                 }
-                EX_OPTION => {
-                    ex = Some(Self::parse_option_value(&args, arg_index, EX_OPTION)?);
-                    arg_index += 2;
+                None => return Err(ArgumentError::General(format!("{}: '{}'", UNKNOWN_OPTION_ERROR, Self::text(&args[arg_index])))),
+            }
+        }
+
+        Ok(Command::SET { key, value, expiration })
+    }
+
+    // GETEX key [EX seconds | PX milliseconds | EXAT unix-time-seconds | PXAT unix-time-milliseconds | PERSIST]
+    pub(crate) fn parse_getex(args: &[Vec<u8>]) -> Result<Command, ArgumentError> {
+        let key = args[1].clone();
+        let (expiration, persist) = match args.len() {
+            2 => (None, false),
+            3 if Self::upper(&args[2]) == PERSIST_OPTION => (None, true),
+            4 => match Self::parse_expiration(args, 2)? {
+                Some(expiration) => (Some(expiration), false),
+                None => return Err(ArgumentError::General(SYNTAX_ERROR.into())),
+            },
+            _ => return Err(ArgumentError::General(SYNTAX_ERROR.into())),
+        };
+        Ok(Command::GETEX { key, expiration, persist })
+    }
+
+    // EX/PX/EXAT/PXAT <값>, 만료 옵션이 아니면 None
+    // Redis처럼 정수가 아니면 정수 에러, 음수면 invalid expire time, 0과 넘치는 값은 실행할 때 같은 에러로 거부함
+    fn parse_expiration(args: &[Vec<u8>], index: usize) -> Result<Option<Expiration>, ArgumentError> {
+        let option = Self::upper(&args[index]);
+        let expiration: fn(u64) -> Expiration = match option.as_str() {
+            EX_OPTION => Expiration::EX,
+            PX_OPTION => Expiration::PX,
+            EXAT_OPTION => Expiration::EXAT,
+            PXAT_OPTION => Expiration::PXAT,
+            _ => return Ok(None),
+        };
+        let Some(value) = args.get(index + 1) else {
+            return Err(ArgumentError::General(format!("{}: {}", OPTION_ARGUMENT_MISSING_ERROR, option)));
+        };
+        let amount = Self::text(value).parse::<i64>().map_err(|_| ArgumentError::General(NOT_AN_INTEGER_ERROR.into()))?;
+        let amount = u64::try_from(amount).map_err(|_| {
+            ArgumentError::General(format!("invalid expire time in '{}' command", Self::text(&args[0]).to_lowercase()))
+        })?;
+        Ok(Some(expiration(amount)))
+    }
+
+    pub(crate) fn parse_getset(args: &[Vec<u8>]) -> Result<Command, ArgumentError> {
+        Self::check_args_len(args, 3, GETSET_COMMAND)?;
+        Ok(Command::GETSET { key: args[1].clone(), value: args[2].clone() })
+    }
+
+    pub(crate) fn parse_incr(args: &[Vec<u8>]) -> Result<Command, ArgumentError> {
+        Self::check_args_len(args, 2, INCR_COMMAND)?;
+        Ok(Command::INCR(args[1].clone()))
+    }
+
+    // LCS key1 key2 [LEN] [IDX] [MINMATCHLEN len] [WITHMATCHLEN], 음수 MINMATCHLEN은 0으로 봄
+    pub(crate) fn parse_lcs(args: &[Vec<u8>]) -> Result<Command, ArgumentError> {
+        if args.len() < 3 {
+            return Err(Self::wrong_arity(LCS_COMMAND));
+        }
+        let (mut len, mut idx, mut min_match_len, mut with_match_len) = (false, false, 0, false);
+        let mut options = args[3..].iter();
+        while let Some(option) = options.next() {
+            match Self::upper(option).as_str() {
+                LEN_OPTION => len = true,
+                IDX_OPTION => idx = true,
+                WITHMATCHLEN_OPTION => with_match_len = true,
+                MINMATCHLEN_OPTION => {
+                    let value = options.next().ok_or_else(|| ArgumentError::General(SYNTAX_ERROR.into()))?;
+                    let value = Self::text(value)
+                        .parse::<i64>()
+                        .map_err(|_| ArgumentError::General(NOT_AN_INTEGER_ERROR.into()))?;
+                    min_match_len = value.max(0) as usize;
                 }
-                _ => return Err(ArgumentError::General(format!("{}: '{}'", UNKNOWN_OPTION_ERROR, args[arg_index]))),
+                _ => return Err(ArgumentError::General(SYNTAX_ERROR.into())),
+            }
+        }
+        if len && idx {
+            return Err(ArgumentError::General(LCS_LEN_AND_IDX_ERROR.into()));
+        }
+        Ok(Command::LCS { key1: args[1].clone(), key2: args[2].clone(), len, idx, min_match_len, with_match_len })
+    }
+
+    pub(crate) fn parse_type(args: &[Vec<u8>]) -> Result<Command, ArgumentError> {
+        Self::check_args_len(args, 2, TYPE_COMMAND)?;
+        Ok(Command::TYPE(args[1].clone()))
+    }
+
+    pub(crate) fn parse_expire(args: &[Vec<u8>]) -> Result<Command, ArgumentError> {
+        if args.len() < 3 {
+            return Err(Self::wrong_arity(&Self::text(&args[0])));
+        }
+
+        // Redis처럼 옵션을 먼저 확인하므로 시간이 정수가 아니어도 옵션 에러가 먼저 나감
+        let key = args[1].clone();
+        let conditions = Self::parse_expire_conditions(&args[3..])?;
+        let amount = Self::text(&args[2]).parse::<i64>().map_err(|_| ArgumentError::General(NOT_AN_INTEGER_ERROR.into()))?;
+
+        match Self::text(&args[0]).as_str() {
+            EXPIRE_COMMAND => Ok(Command::EXPIRE { key, seconds: amount, conditions }),
+            PEXPIRE_COMMAND => Ok(Command::PEXPIRE { key, milliseconds: amount, conditions }),
+            EXPIREAT_COMMAND => Ok(Command::EXPIREAT { key, timestamp: amount, conditions }),
+            _ => Ok(Command::PEXPIREAT { key, timestamp_ms: amount, conditions }),
+        }
+    }
+
+    fn parse_expire_conditions(options: &[Vec<u8>]) -> Result<Vec<ExpireCondition>, ArgumentError> {
+        let mut conditions = Vec::new();
+        for option in options {
+            let condition = match Self::upper(option).as_str() {
+                NX_OPTION => ExpireCondition::NX,
+                XX_OPTION => ExpireCondition::XX,
+                GT_OPTION => ExpireCondition::GT,
+                LT_OPTION => ExpireCondition::LT,
+                _ => return Err(ArgumentError::General(format!("{} {}", UNSUPPORTED_OPTION_ERROR, Self::text(option)))),
+            };
+            if !conditions.contains(&condition) {
+                conditions.push(condition);
+            }
+        }
+        let has = |condition| conditions.contains(&condition);
+        if has(ExpireCondition::NX) && conditions.len() > 1 {
+            return Err(ArgumentError::General(NX_INCOMPATIBLE_ERROR.into()));
+        }
+        if has(ExpireCondition::GT) && has(ExpireCondition::LT) {
+            return Err(ArgumentError::General(GT_LT_INCOMPATIBLE_ERROR.into()));
+        }
+        Ok(conditions)
+    }
+
+    pub(crate) fn parse_hset(args: &[Vec<u8>]) -> Result<Command, ArgumentError> {
+        if args.len() < 4 || args.len() % 2 != 0 {
+            return Err(Self::wrong_arity(HSET_COMMAND));
+        }
+        let fields = args[2..]
+            .chunks(2)
+            .map(|pair| (pair[0].clone(), pair[1].clone()))
+            .collect();
+        Ok(Command::HSET { key: args[1].clone(), fields })
+    }
+
+    pub(crate) fn parse_push(args: &[Vec<u8>]) -> Result<Command, ArgumentError> {
+        if args.len() < 3 {
+            return Err(Self::wrong_arity(&Self::text(&args[0])));
+        }
+        let (key, values) = (args[1].clone(), args[2..].to_vec());
+        match Self::text(&args[0]).as_str() {
+            LPUSH_COMMAND => Ok(Command::LPUSH { key, values }),
+            _ => Ok(Command::RPUSH { key, values }),
+        }
+    }
+
+    pub(crate) fn parse_pop(args: &[Vec<u8>]) -> Result<Command, ArgumentError> {
+        if args.len() != 2 && args.len() != 3 {
+            return Err(Self::wrong_arity(&Self::text(&args[0])));
+        }
+        let count = match args.get(2) {
+            Some(count) => {
+                let count = Self::text(count)
+                    .parse::<i64>()
+                    .map_err(|_| ArgumentError::General(NOT_AN_INTEGER_ERROR.into()))?;
+                Some(usize::try_from(count).map_err(|_| ArgumentError::General(VALUE_NOT_POSITIVE_ERROR.into()))?)
             }
+            None => None,
+        };
+        let key = args[1].clone();
+        match Self::text(&args[0]).as_str() {
+            LPOP_COMMAND => Ok(Command::LPOP { key, count }),
+            _ => Ok(Command::RPOP { key, count }),
         }
+    }
 
-        Ok(Command::SET { key, value, ex, px })
+    // BLPOP key [key ...] timeout: timeout은 초 단위 실수, 0이면 무한히 기다림
+    pub(crate) fn parse_blocking_pop(args: &[Vec<u8>]) -> Result<Command, ArgumentError> {
+        if args.len() < 3 {
+            return Err(Self::wrong_arity(&Self::text(&args[0])));
+        }
+        let keys = args[1..args.len() - 1].to_vec();
+        let timeout_ms = Self::parse_timeout(&args[args.len() - 1])?;
+        match Self::text(&args[0]).as_str() {
+            BLPOP_COMMAND => Ok(Command::BLPOP { keys, timeout_ms }),
+            _ => Ok(Command::BRPOP { keys, timeout_ms }),
+        }
     }
 
-    fn parse_option_value(args: &[String], index: usize, option: &str) -> Result<u64, ArgumentError> {
-        if index + 1 < args.len() {
-            args[index + 1].parse::<u64>().map_err(|_| ArgumentError::General(format!("{}: {}", INVALID_OPTION_VALUE_ERROR, option)))
+    // LMOVE source destination LEFT|RIGHT LEFT|RIGHT, BLMOVE는 끝에 timeout이 붙음
+    pub(crate) fn parse_lmove(args: &[Vec<u8>]) -> Result<Command, ArgumentError> {
+        let blocking = args[0] == BLMOVE_COMMAND.as_bytes();
+        Self::check_args_len(args, if blocking { 6 } else { 5 }, &Self::text(&args[0]))?;
+        let direction = |value: &[u8]| match Self::upper(value).as_str() {
+            LEFT_OPTION => Ok(ListDirection::LEFT),
+            RIGHT_OPTION => Ok(ListDirection::RIGHT),
+            _ => Err(ArgumentError::General(SYNTAX_ERROR.into())),
+        };
+        let (source, destination) = (args[1].clone(), args[2].clone());
+        let (from, to) = (direction(&args[3])?, direction(&args[4])?);
+        if blocking {
+            let timeout_ms = Self::parse_timeout(&args[5])?;
+            Ok(Command::BLMOVE { source, destination, from, to, timeout_ms })
         } else {
-            Err(ArgumentError::General(format!("{}: {}", OPTION_ARGUMENT_MISSING_ERROR, option)))
+            Ok(Command::LMOVE { source, destination, from, to })
+        }
+    }
+
+    // [B]LMPOP/[B]ZMPOP [timeout] numkeys key [key ...] <LEFT|RIGHT|MIN|MAX> [COUNT count]
+    pub(crate) fn parse_brpoplpush(args: &[Vec<u8>]) -> Result<Command, ArgumentError> {
+        Self::check_args_len(args, 4, BRPOPLPUSH_COMMAND)?;
+        Ok(Command::BRPOPLPUSH {
+            source: args[1].clone(),
+            destination: args[2].clone(),
+            timeout_ms: Self::parse_timeout(&args[3])?,
+        })
+    }
+
+    pub(crate) fn parse_multi_pop(args: &[Vec<u8>]) -> Result<Command, ArgumentError> {
+        let command_name = Self::text(&args[0]);
+        let command_name = command_name.as_str();
+        let blocking = matches!(command_name, BLMPOP_COMMAND | BZMPOP_COMMAND);
+        let numkeys_index = if blocking { 2 } else { 1 };
+        if args.len() < numkeys_index + 3 {
+            return Err(Self::wrong_arity(command_name));
+        }
+        let timeout_ms = if blocking { Self::parse_timeout(&args[1])? } else { 0 };
+        let numkeys = Self::text(&args[numkeys_index]).parse::<i64>()
+            .map_err(|_| ArgumentError::General(NOT_AN_INTEGER_ERROR.into()))?;
+        if numkeys <= 0 {
+            return Err(ArgumentError::General(NUMKEYS_NOT_POSITIVE_ERROR.into()));
+        }
+        let keys_end = numkeys_index + 1 + numkeys as usize;
+        if keys_end >= args.len() {
+            return Err(ArgumentError::General(SYNTAX_ERROR.into()));
+        }
+        let keys = args[numkeys_index + 1..keys_end].to_vec();
+        let direction = Self::upper(&args[keys_end]);
+
+        let count = match &args[keys_end + 1..] {
+            [] => 1,
+            [option, count] if option.eq_ignore_ascii_case(COUNT_OPTION.as_bytes()) => {
+                let count = Self::text(count)
+                    .parse::<i64>()
+                    .map_err(|_| ArgumentError::General(NOT_AN_INTEGER_ERROR.into()))?;
+                if count <= 0 {
+                    return Err(ArgumentError::General(COUNT_NOT_POSITIVE_ERROR.into()));
+                }
+                count as usize
+            }
+            _ => return Err(ArgumentError::General(SYNTAX_ERROR.into())),
+        };
+
+        match (command_name, direction.as_str()) {
+            (LMPOP_COMMAND | BLMPOP_COMMAND, LEFT_OPTION | RIGHT_OPTION) => {
+                let direction = if direction == LEFT_OPTION { ListDirection::LEFT } else { ListDirection::RIGHT };
+                if blocking {
+                    Ok(Command::BLMPOP { keys, direction, count, timeout_ms })
+                } else {
+                    Ok(Command::LMPOP { keys, direction, count })
+                }
+            }
+            (ZMPOP_COMMAND | BZMPOP_COMMAND, MIN_OPTION | MAX_OPTION) => {
+                let direction = if direction == MIN_OPTION { ScoreDirection::MIN } else { ScoreDirection::MAX };
+                if blocking {
+                    Ok(Command::BZMPOP { keys, direction, count, timeout_ms })
+                } else {
+                    Ok(Command::ZMPOP { keys, direction, count })
+                }
+            }
+            _ => Err(ArgumentError::General(SYNTAX_ERROR.into())),
+        }
+    }
+
+    // ZADD key [INCR] score member [score member ...], INCR는 ZINCRBY로 대체된 옛 형식이라 쌍을 하나만 받음
+    pub(crate) fn parse_zadd(args: &[Vec<u8>]) -> Result<Command, ArgumentError> {
+        let incr = args.len() > 3 && args[2].eq_ignore_ascii_case(INCR_OPTION.as_bytes());
+        let pairs = if incr { &args[3..] } else { &args[2..] };
+        if pairs.len() < 2 || pairs.len() % 2 != 0 {
+            return Err(Self::wrong_arity(ZADD_COMMAND));
+        }
+        if incr && pairs.len() > 2 {
+            return Err(ArgumentError::General(ZADD_INCR_PAIR_ERROR.into()));
+        }
+        let members = pairs
+            .chunks(2)
+            .map(|pair| Ok((Self::parse_score(&pair[0])?, pair[1].clone())))
+            .collect::<Result<Vec<_>, ArgumentError>>()?;
+        Ok(Command::ZADD { key: args[1].clone(), members, incr })
+    }
+
+    pub(crate) fn parse_zincrby(args: &[Vec<u8>]) -> Result<Command, ArgumentError> {
+        Self::check_args_len(args, 4, ZINCRBY_COMMAND)?;
+        Ok(Command::ZINCRBY { key: args[1].clone(), increment: Self::parse_score(&args[2])?, member: args[3].clone() })
+    }
+
+    fn parse_score(arg: &[u8]) -> Result<f64, ArgumentError> {
+        Self::text(arg)
+            .parse::<f64>()
+            .ok()
+            .filter(|score| !score.is_nan())
+            .ok_or_else(|| ArgumentError::General(NOT_A_FLOAT_ERROR.into()))
+    }
+
+    // ZRANDMEMBER key [count [WITHSCORES]]
+    pub(crate) fn parse_zrandmember(args: &[Vec<u8>]) -> Result<Command, ArgumentError> {
+        let (count, withscores) = Self::parse_random_count(args, WITHSCORES_OPTION)?;
+        Ok(Command::ZRANDMEMBER { key: args[1].clone(), count, withscores })
+    }
+
+    // HRANDFIELD key [count [WITHVALUES]]
+    pub(crate) fn parse_hrandfield(args: &[Vec<u8>]) -> Result<Command, ArgumentError> {
+        let (count, withvalues) = Self::parse_random_count(args, WITHVALUES_OPTION)?;
+        Ok(Command::HRANDFIELD { key: args[1].clone(), count, withvalues })
+    }
+
+    // ZRANDMEMBER와 HRANDFIELD의 [count [with_option]] 부분
+    // 음수 count는 키 크기와 상관없이 그만큼 응답을 만듦, Redis처럼 흘려 보내지 못하고 응답을 통째로 만들기 때문에
+    // RANDOM_COUNT_LIMIT보다 많이 달라고 하면 메모리를 잡기 전에 거절함
+    fn parse_random_count(args: &[Vec<u8>], with_option: &str) -> Result<(Option<i64>, bool), ArgumentError> {
+        if args.len() > 4 {
+            return Err(ArgumentError::General(SYNTAX_ERROR.into()));
+        }
+        let count = match args.get(2) {
+            Some(count) => Some(Self::text(count).parse::<i64>().map_err(|_| ArgumentError::General(NOT_AN_INTEGER_ERROR.into()))?),
+            None => None,
+        };
+        let with_values = match args.get(3) {
+            Some(option) if option.eq_ignore_ascii_case(with_option.as_bytes()) => true,
+            Some(_) => return Err(ArgumentError::General(SYNTAX_ERROR.into())),
+            None => false,
+        };
+        // with_option이면 요소마다 값이 하나 더 붙으므로 Redis처럼 절반만 받음
+        let limit = if with_values { RANDOM_COUNT_LIMIT / 2 } else { RANDOM_COUNT_LIMIT };
+        if count.is_some_and(|count| count < -limit) {
+            return Err(ArgumentError::General(VALUE_OUT_OF_RANGE_ERROR.into()));
+        }
+        Ok((count, with_values))
+    }
+
+    pub(crate) fn parse_zscore(args: &[Vec<u8>]) -> Result<Command, ArgumentError> {
+        Self::check_args_len(args, 3, ZSCORE_COMMAND)?;
+        Ok(Command::ZSCORE { key: args[1].clone(), member: args[2].clone() })
+    }
+
+    fn parse_timeout(value: &[u8]) -> Result<u64, ArgumentError> {
+        let seconds = Self::text(value)
+            .parse::<f64>()
+            .ok()
+            .filter(|seconds| seconds.is_finite())
+            .ok_or_else(|| ArgumentError::General(TIMEOUT_NOT_FLOAT_ERROR.into()))?;
+        if seconds < 0.0 {
+            return Err(ArgumentError::General(TIMEOUT_NEGATIVE_ERROR.into()));
+        }
+        // Redis처럼 밀리초가 long long을 넘으면 거절함, 그대로 변환하면 u64로 포화되어 마감 시각 계산이 넘침
+        let millis = (seconds * 1000.0).ceil();
+        if millis >= i64::MAX as f64 {
+            return Err(ArgumentError::General(TIMEOUT_OUT_OF_RANGE_ERROR.into()));
         }
+        Ok(millis as u64)
+    }
+
+    pub(crate) fn parse_hget(args: &[Vec<u8>]) -> Result<Command, ArgumentError> {
+        Self::check_args_len(args, 3, HGET_COMMAND)?;
+        Ok(Command::HGET { key: args[1].clone(), field: args[2].clone() })
     }
 
-    fn parse_config(args: &[String]) -> Result<Command, ArgumentError> {
+    pub(crate) fn parse_hgetall(args: &[Vec<u8>]) -> Result<Command, ArgumentError> {
+        Self::check_args_len(args, 2, HGETALL_COMMAND)?;
+        Ok(Command::HGETALL(args[1].clone()))
+    }
+
+    pub(crate) fn parse_hdel(args: &[Vec<u8>]) -> Result<Command, ArgumentError> {
         if args.len() < 3 {
-            return Err(ArgumentError::General(CONFIG_ARGUMENTS_ERROR.into()));
+            return Err(Self::wrong_arity(HDEL_COMMAND));
+        }
+        Ok(Command::HDEL { key: args[1].clone(), fields: args[2..].to_vec() })
+    }
+
+    // FIELDS numfields field [field ...] 부분을 읽음
+    fn parse_fields(args: &[Vec<u8>], index: usize) -> Result<Vec<Vec<u8>>, ArgumentError> {
+        if !args.get(index).is_some_and(|arg| arg.eq_ignore_ascii_case(FIELDS_OPTION.as_bytes())) {
+            return Err(ArgumentError::General(FIELDS_MISSING_ERROR.into()));
+        }
+        let numfields = args
+            .get(index + 1)
+            .map(|arg| Self::text(arg))
+            .ok_or_else(|| ArgumentError::General(FIELDS_MISSING_ERROR.into()))?
+            .parse::<usize>()
+            .map_err(|_| ArgumentError::General(NOT_AN_INTEGER_ERROR.into()))?;
+        if numfields == 0 {
+            return Err(ArgumentError::General(NUMFIELDS_ZERO_ERROR.into()));
+        }
+        let fields = &args[index + 2..];
+        if fields.len() != numfields {
+            return Err(ArgumentError::General(NUMFIELDS_MISMATCH_ERROR.into()));
+        }
+        Ok(fields.to_vec())
+    }
+
+    pub(crate) fn parse_hexpire(args: &[Vec<u8>]) -> Result<Command, ArgumentError> {
+        if args.len() < 6 {
+            return Err(Self::wrong_arity(&Self::text(&args[0])));
+        }
+        let key = args[1].clone();
+        let amount = Self::text(&args[2]).parse::<i64>().map_err(|_| ArgumentError::General(NOT_AN_INTEGER_ERROR.into()))?;
+        let fields_index = args
+            .iter()
+            .skip(3)
+            .position(|arg| arg.eq_ignore_ascii_case(FIELDS_OPTION.as_bytes()))
+            .map_or(3, |position| position + 3);
+        let conditions = Self::parse_expire_conditions(&args[3..fields_index])?;
+        let fields = Self::parse_fields(args, fields_index)?;
+
+        match Self::text(&args[0]).as_str() {
+            HEXPIRE_COMMAND => Ok(Command::HEXPIRE { key, seconds: amount, conditions, fields }),
+            _ => Ok(Command::HPEXPIRE { key, milliseconds: amount, conditions, fields }),
+        }
+    }
+
+    pub(crate) fn parse_hash_fields_command(args: &[Vec<u8>]) -> Result<Command, ArgumentError> {
+        if args.len() < 5 {
+            return Err(Self::wrong_arity(&Self::text(&args[0])));
+        }
+        let key = args[1].clone();
+        let fields = Self::parse_fields(args, 2)?;
+        match Self::text(&args[0]).as_str() {
+            HTTL_COMMAND => Ok(Command::HTTL { key, fields }),
+            _ => Ok(Command::HPERSIST { key, fields }),
+        }
+    }
+
+    pub(crate) fn parse_ttl(args: &[Vec<u8>]) -> Result<Command, ArgumentError> {
+        Self::check_args_len(args, 2, &Self::text(&args[0]))?;
+        let key = args[1].clone();
+        match Self::text(&args[0]).as_str() {
+            TTL_COMMAND => Ok(Command::TTL(key)),
+            PTTL_COMMAND => Ok(Command::PTTL(key)),
+            EXPIRETIME_COMMAND => Ok(Command::EXPIRETIME(key)),
+            PEXPIRETIME_COMMAND => Ok(Command::PEXPIRETIME(key)),
+            _ => Ok(Command::PERSIST(key)),
+        }
+    }
+
+    pub(crate) fn parse_multi_key(args: &[Vec<u8>]) -> Result<Command, ArgumentError> {
+        if args.len() < 2 {
+            return Err(Self::wrong_arity(&Self::text(&args[0])));
+        }
+        let keys = args[1..].to_vec();
+        match Self::text(&args[0]).as_str() {
+            DEL_COMMAND => Ok(Command::DEL(keys)),
+            UNLINK_COMMAND => Ok(Command::UNLINK(keys)),
+            TOUCH_COMMAND => Ok(Command::TOUCH(keys)),
+            WATCH_COMMAND => Ok(Command::WATCH(keys)),
+            _ => Ok(Command::EXISTS(keys)),
+        }
+    }
+
+    pub(crate) fn parse_config(args: &[Vec<u8>]) -> Result<Command, ArgumentError> {
+        match Self::upper(&args[1]).as_str() {
+            CONFIG_GET_OPTION | CONFIG_SET_OPTION if args.len() < 3 => Err(Self::wrong_arity(&Self::subcommand_name(args))),
+            CONFIG_GET_OPTION => Ok(Command::CONFIG(ConfigCommand::GET(Self::texts(&args[2..])))),
+            CONFIG_SET_OPTION => {
+                if args.len() % 2 != 0 {
+                    return Err(ArgumentError::General(format!("{} - '{}'", UNKNOWN_CONFIG_SET_OPTION_ERROR, Self::text(&args[2]))));
+                }
+                let pairs = args[2..].chunks(2).map(|pair| (Self::text(&pair[0]), Self::text(&pair[1]))).collect();
+                Ok(Command::CONFIG(ConfigCommand::SET(pairs)))
+            }
+            _ => Err(Self::unknown_subcommand(args)),
+        }
+    }
+
+    pub(crate) fn parse_object(args: &[Vec<u8>]) -> Result<Command, ArgumentError> {
+        let subcommand: fn(Vec<u8>) -> ObjectCommand = match Self::upper(&args[1]).as_str() {
+            OBJECT_HELP_OPTION => {
+                Self::check_args_len(args, 2, &Self::subcommand_name(args))?;
+                return Ok(Command::OBJECT(ObjectCommand::HELP));
+            }
+            OBJECT_ENCODING_OPTION => ObjectCommand::ENCODING,
+            OBJECT_IDLETIME_OPTION => ObjectCommand::IDLETIME,
+            OBJECT_FREQ_OPTION => ObjectCommand::FREQ,
+            OBJECT_REFCOUNT_OPTION => ObjectCommand::REFCOUNT,
+            _ => return Err(Self::unknown_subcommand(args)),
+        };
+        Self::check_args_len(args, 3, &Self::subcommand_name(args))?;
+        Ok(Command::OBJECT(subcommand(args[2].clone())))
+    }
+
+    pub(crate) fn parse_subscribe(args: &[Vec<u8>]) -> Result<Command, ArgumentError> {
+        if args.len() < 2 {
+            return Err(Self::wrong_arity(&Self::text(&args[0])));
+        }
+        match Self::text(&args[0]).as_str() {
+            PSUBSCRIBE_COMMAND => Ok(Command::PSUBSCRIBE(Self::texts(&args[1..]))),
+            SSUBSCRIBE_COMMAND => Ok(Command::SSUBSCRIBE(Self::texts(&args[1..]))),
+            _ => Ok(Command::SUBSCRIBE(Self::texts(&args[1..]))),
+        }
+    }
+
+    // 채널을 주지 않으면 구독 중인 채널 전부에서 나감
+    pub(crate) fn parse_unsubscribe(args: &[Vec<u8>]) -> Result<Command, ArgumentError> {
+        let channels = Self::texts(&args[1..]);
+        match Self::text(&args[0]).as_str() {
+            PUNSUBSCRIBE_COMMAND => Ok(Command::PUNSUBSCRIBE(channels)),
+            SUNSUBSCRIBE_COMMAND => Ok(Command::SUNSUBSCRIBE(channels)),
+            _ => Ok(Command::UNSUBSCRIBE(channels)),
+        }
+    }
+
+    pub(crate) fn parse_publish(args: &[Vec<u8>]) -> Result<Command, ArgumentError> {
+        Self::check_args_len(args, 3, &Self::text(&args[0]))?;
+        if args[0] == SPUBLISH_COMMAND.as_bytes() {
+            return Ok(Command::SPUBLISH { channel: Self::text(&args[1]), message: args[2].clone() });
+        }
+        Ok(Command::PUBLISH { channel: Self::text(&args[1]), message: args[2].clone() })
+    }
+
+    pub(crate) fn parse_script(args: &[Vec<u8>]) -> Result<Command, ArgumentError> {
+        if args.len() < 2 {
+            return Err(Self::wrong_arity(SCRIPT_COMMAND));
+        }
+        match Self::upper(&args[1]).as_str() {
+            SCRIPT_LOAD_OPTION => {
+                Self::check_args_len(args, 3, &Self::subcommand_name(args))?;
+                Ok(Command::SCRIPT(ScriptCommand::LOAD(Self::text(&args[2]))))
+            }
+            SCRIPT_EXISTS_OPTION if args.len() > 2 => Ok(Command::SCRIPT(ScriptCommand::EXISTS(Self::texts(&args[2..])))),
+            SCRIPT_EXISTS_OPTION => Err(Self::wrong_arity(&Self::subcommand_name(args))),
+            SCRIPT_FLUSH_OPTION => {
+                let mode = Self::parse_flush_mode(args, 2)?;
+                Ok(Command::SCRIPT(ScriptCommand::FLUSH(mode)))
+            }
+            SCRIPT_KILL_OPTION => {
+                Self::check_args_len(args, 2, &Self::subcommand_name(args))?;
+                Ok(Command::SCRIPT(ScriptCommand::KILL))
+            }
+            _ => Err(Self::unknown_subcommand(args)),
+        }
+    }
+
+    // EVAL script numkeys [key ...] [arg ...], EVALSHA는 script 자리에 SHA1이, FCALL/FCALL_RO는 함수 이름이 옴
+    pub(crate) fn parse_eval(args: &[Vec<u8>]) -> Result<Command, ArgumentError> {
+        let numkeys = Self::text(&args[2])
+            .parse::<i64>()
+            .map_err(|_| ArgumentError::General(NOT_AN_INTEGER_ERROR.into()))?;
+        if numkeys < 0 {
+            return Err(ArgumentError::General(SCRIPT_NEGATIVE_KEYS_ERROR.into()));
+        }
+        if numkeys as usize > args.len() - 3 {
+            return Err(ArgumentError::General(SCRIPT_TOO_MANY_KEYS_ERROR.into()));
+        }
+        let keys_end = 3 + numkeys as usize;
+        let keys = args[3..keys_end].to_vec();
+        let script_args = args[keys_end..].to_vec();
+        match Self::text(&args[0]).as_str() {
+            EVAL_COMMAND => Ok(Command::EVAL { script: Self::text(&args[1]), keys, args: script_args }),
+            EVALSHA_COMMAND => Ok(Command::EVALSHA { sha: Self::text(&args[1]).to_lowercase(), keys, args: script_args }),
+            FCALL_COMMAND => Ok(Command::FCALL { function: Self::text(&args[1]), keys, args: script_args }),
+            _ => Ok(Command::FCALLRO { function: Self::text(&args[1]), keys, args: script_args }),
+        }
+    }
+
+    pub(crate) fn parse_function(args: &[Vec<u8>]) -> Result<Command, ArgumentError> {
+        if args.len() < 2 {
+            return Err(Self::wrong_arity(FUNCTION_COMMAND));
+        }
+        match Self::upper(&args[1]).as_str() {
+            FUNCTION_LOAD_OPTION => match args.len() {
+                3 => Ok(Command::FUNCTION(FunctionCommand::LOAD { code: Self::text(&args[2]), replace: false })),
+                4 if args[2].eq_ignore_ascii_case(REPLACE_OPTION.as_bytes()) => {
+                    Ok(Command::FUNCTION(FunctionCommand::LOAD { code: Self::text(&args[3]), replace: true }))
+                }
+                _ => Err(ArgumentError::General(SYNTAX_ERROR.into())),
+            },
+            FUNCTION_LIST_OPTION => {
+                let mut pattern = None;
+                let mut with_code = false;
+                let mut index = 2;
+                while index < args.len() {
+                    match Self::upper(&args[index]).as_str() {
+                        WITHCODE_OPTION => with_code = true,
+                        LIBRARYNAME_OPTION if index + 1 < args.len() => {
+                            index += 1;
+                            pattern = Some(Self::text(&args[index]));
+                        }
+                        _ => return Err(ArgumentError::General(SYNTAX_ERROR.into())),
+                    }
+                    index += 1;
+                }
+                Ok(Command::FUNCTION(FunctionCommand::LIST { pattern, with_code }))
+            }
+            FUNCTION_DELETE_OPTION => {
+                Self::check_args_len(args, 3, &Self::subcommand_name(args))?;
+                Ok(Command::FUNCTION(FunctionCommand::DELETE(Self::text(&args[2]))))
+            }
+            FUNCTION_FLUSH_OPTION => {
+                let mode = Self::parse_flush_mode(args, 2)?;
+                Ok(Command::FUNCTION(FunctionCommand::FLUSH(mode)))
+            }
+            FUNCTION_KILL_OPTION => {
+                Self::check_args_len(args, 2, &Self::subcommand_name(args))?;
+                Ok(Command::FUNCTION(FunctionCommand::KILL))
+            }
+            _ => Err(Self::unknown_subcommand(args)),
+        }
+    }
+
+    pub(crate) fn parse_client(args: &[Vec<u8>]) -> Result<Command, ArgumentError> {
+        if args.len() < 2 {
+            return Err(Self::wrong_arity(CLIENT_COMMAND));
+        }
+        match Self::upper(&args[1]).as_str() {
+            CLIENT_ID_OPTION => Self::check_args_len(args, 2, &Self::subcommand_name(args)).map(|_| Command::CLIENT(ClientCommand::ID)),
+            CLIENT_GETREDIR_OPTION => Self::check_args_len(args, 2, &Self::subcommand_name(args)).map(|_| Command::CLIENT(ClientCommand::GETREDIR)),
+            CLIENT_TRACKING_OPTION => Self::parse_client_tracking(args),
+            CLIENT_LIST_OPTION => Self::parse_client_list(args),
+            CLIENT_SETNAME_OPTION => Self::check_args_len(args, 3, &Self::subcommand_name(args)).map(|_| Command::CLIENT(ClientCommand::SETNAME(Self::text(&args[2])))),
+            CLIENT_GETNAME_OPTION => Self::check_args_len(args, 2, &Self::subcommand_name(args)).map(|_| Command::CLIENT(ClientCommand::GETNAME)),
+            CLIENT_KILL_OPTION => Self::parse_client_kill(args),
+            CLIENT_CACHING_OPTION => {
+                Self::check_args_len(args, 3, &Self::subcommand_name(args))?;
+                match Self::upper(&args[2]).as_str() {
+                    YES_OPTION => Ok(Command::CLIENT(ClientCommand::CACHING(true))),
+                    NO_OPTION => Ok(Command::CLIENT(ClientCommand::CACHING(false))),
+                    _ => Err(ArgumentError::General(SYNTAX_ERROR.into())),
+                }
+            }
+            _ => Err(Self::unknown_subcommand(args)),
+        }
+    }
+
+    pub(crate) fn parse_acl(args: &[Vec<u8>]) -> Result<Command, ArgumentError> {
+        if args.len() < 2 {
+            return Err(Self::wrong_arity(ACL_COMMAND));
+        }
+        let subcommand = match Self::upper(&args[1]).as_str() {
+            ACL_SETUSER_OPTION if args.len() >= 3 => AclCommand::SETUSER {
+                username: Self::text(&args[2]),
+                rules: args[3..].iter().map(|rule| Self::text(rule)).collect(),
+            },
+            ACL_GETUSER_OPTION if args.len() == 3 => AclCommand::GETUSER(Self::text(&args[2])),
+            ACL_DELUSER_OPTION if args.len() >= 3 => AclCommand::DELUSER(args[2..].iter().map(|name| Self::text(name)).collect()),
+            ACL_LIST_OPTION if args.len() == 2 => AclCommand::LIST,
+            ACL_USERS_OPTION if args.len() == 2 => AclCommand::USERS,
+            ACL_WHOAMI_OPTION if args.len() == 2 => AclCommand::WHOAMI,
+            ACL_SETUSER_OPTION | ACL_GETUSER_OPTION | ACL_DELUSER_OPTION | ACL_LIST_OPTION | ACL_USERS_OPTION | ACL_WHOAMI_OPTION => {
+                return Err(Self::wrong_arity(&Self::subcommand_name(args)));
+            }
+            _ => return Err(Self::unknown_subcommand(args)),
+        };
+        Ok(Command::ACL(subcommand))
+    }
+
+    // HELLO [protover [AUTH username password] [SETNAME clientname]]
+    pub(crate) fn parse_hello(args: &[Vec<u8>]) -> Result<Command, ArgumentError> {
+        let Some(protover) = args.get(1) else {
+            return Ok(Command::HELLO { protover: None, auth: None, setname: None });
+        };
+        let protover = Self::text(protover)
+            .parse::<i64>()
+            .map_err(|_| ArgumentError::General(PROTOCOL_VERSION_ERROR.into()))?;
+        let mut auth = None;
+        let mut setname = None;
+        let mut i = 2;
+        while i < args.len() {
+            let remaining = args.len() - i - 1;
+            match Self::upper(&args[i]).as_str() {
+                HELLO_AUTH_OPTION if remaining >= 2 => {
+                    auth = Some((Self::text(&args[i + 1]), Self::text(&args[i + 2])));
+                    i += 3;
+                }
+                HELLO_SETNAME_OPTION if remaining >= 1 => {
+                    setname = Some(Self::text(&args[i + 1]));
+                    i += 2;
+                }
+                _ => return Err(ArgumentError::General(format!("Syntax error in HELLO option '{}'", Self::text(&args[i])))),
+            }
+        }
+        Ok(Command::HELLO { protover: Some(protover), auth, setname })
+    }
+
+    pub(crate) fn parse_auth(args: &[Vec<u8>]) -> Result<Command, ArgumentError> {
+        match args.len() {
+            2 => Ok(Command::AUTH { username: None, password: Self::text(&args[1]) }),
+            3 => Ok(Command::AUTH { username: Some(Self::text(&args[1])), password: Self::text(&args[2]) }),
+            _ => Err(ArgumentError::General(SYNTAX_ERROR.into())),
+        }
+    }
+
+    pub(crate) fn parse_cluster(args: &[Vec<u8>]) -> Result<Command, ArgumentError> {
+        if args.len() < 2 {
+            return Err(Self::wrong_arity(CLUSTER_COMMAND));
+        }
+        let subcommand = match Self::upper(&args[1]).as_str() {
+            CLUSTER_INFO_OPTION if args.len() == 2 => ClusterCommand::INFO,
+            CLUSTER_MYID_OPTION if args.len() == 2 => ClusterCommand::MYID,
+            CLUSTER_NODES_OPTION if args.len() == 2 => ClusterCommand::NODES,
+            CLUSTER_SLOTS_OPTION if args.len() == 2 => ClusterCommand::SLOTS,
+            CLUSTER_SHARDS_OPTION if args.len() == 2 => ClusterCommand::SHARDS,
+            CLUSTER_KEYSLOT_OPTION if args.len() == 3 => ClusterCommand::KEYSLOT(args[2].clone()),
+            CLUSTER_ADDSLOTS_OPTION if args.len() > 2 => ClusterCommand::ADDSLOTS(Self::parse_slots(&args[2..])?),
+            CLUSTER_DELSLOTS_OPTION if args.len() > 2 => ClusterCommand::DELSLOTS(Self::parse_slots(&args[2..])?),
+            CLUSTER_SETSLOT_OPTION if args.len() >= 4 => Self::parse_setslot(args)?,
+            CLUSTER_COUNTKEYSINSLOT_OPTION if args.len() == 3 => ClusterCommand::COUNTKEYSINSLOT(Self::parse_slots(&args[2..3])?[0]),
+            CLUSTER_GETKEYSINSLOT_OPTION if args.len() == 4 => ClusterCommand::GETKEYSINSLOT {
+                slot: Self::parse_slots(&args[2..3])?[0],
+                count: Self::text(&args[3]).parse().map_err(|_| ArgumentError::General(INVALID_KEY_COUNT_ERROR.into()))?,
+            },
+            CLUSTER_MEET_OPTION if args.len() == 4 || args.len() == 5 => Self::parse_meet(args)?,
+            CLUSTER_REPLICATE_OPTION if args.len() == 3 => ClusterCommand::REPLICATE(Self::text(&args[2])),
+            CLUSTER_REPLICAS_OPTION | CLUSTER_SLAVES_OPTION if args.len() == 3 => ClusterCommand::REPLICAS(Self::text(&args[2])),
+            CLUSTER_ADDSLOTSRANGE_OPTION if args.len() > 2 && args.len() % 2 == 0 => {
+                ClusterCommand::ADDSLOTS(Self::parse_slot_ranges(&args[2..])?)
+            }
+            CLUSTER_DELSLOTSRANGE_OPTION if args.len() > 2 && args.len() % 2 == 0 => {
+                ClusterCommand::DELSLOTS(Self::parse_slot_ranges(&args[2..])?)
+            }
+            CLUSTER_INFO_OPTION
+            | CLUSTER_MYID_OPTION
+            | CLUSTER_NODES_OPTION
+            | CLUSTER_SLOTS_OPTION
+            | CLUSTER_SHARDS_OPTION
+            | CLUSTER_KEYSLOT_OPTION
+            | CLUSTER_ADDSLOTS_OPTION
+            | CLUSTER_DELSLOTS_OPTION
+            | CLUSTER_ADDSLOTSRANGE_OPTION
+            | CLUSTER_DELSLOTSRANGE_OPTION
+            | CLUSTER_SETSLOT_OPTION
+            | CLUSTER_COUNTKEYSINSLOT_OPTION
+            | CLUSTER_GETKEYSINSLOT_OPTION
+            | CLUSTER_MEET_OPTION
+            | CLUSTER_REPLICATE_OPTION
+            | CLUSTER_REPLICAS_OPTION
+            | CLUSTER_SLAVES_OPTION => {
+                return Err(Self::wrong_arity(&Self::subcommand_name(args)))
+            }
+            _ => return Err(Self::unknown_subcommand(args)),
+        };
+        Ok(Command::CLUSTER(subcommand))
+    }
+
+    pub(crate) fn parse_sentinel(args: &[Vec<u8>]) -> Result<Command, ArgumentError> {
+        if args.len() < 2 {
+            return Err(Self::wrong_arity(SENTINEL_COMMAND));
+        }
+        let name = || Self::text(&args[2]);
+        let subcommand = match Self::upper(&args[1]).as_str() {
+            SENTINEL_MASTERS_OPTION if args.len() == 2 => SentinelCommand::MASTERS,
+            SENTINEL_MYID_OPTION if args.len() == 2 => SentinelCommand::MYID,
+            SENTINEL_MASTER_OPTION if args.len() == 3 => SentinelCommand::MASTER(name()),
+            SENTINEL_REPLICAS_OPTION | SENTINEL_SLAVES_OPTION if args.len() == 3 => SentinelCommand::REPLICAS(name()),
+            SENTINEL_SENTINELS_OPTION if args.len() == 3 => SentinelCommand::SENTINELS(name()),
+            SENTINEL_GET_MASTER_ADDR_BY_NAME_OPTION if args.len() == 3 => SentinelCommand::GETMASTERADDRBYNAME(name()),
+            SENTINEL_CKQUORUM_OPTION if args.len() == 3 => SentinelCommand::CKQUORUM(name()),
+            SENTINEL_FAILOVER_OPTION if args.len() == 3 => SentinelCommand::FAILOVER(name()),
+            SENTINEL_REMOVE_OPTION if args.len() == 3 => SentinelCommand::REMOVE(name()),
+            SENTINEL_IS_MASTER_DOWN_BY_ADDR_OPTION if args.len() == 6 => SentinelCommand::ISMASTERDOWNBYADDR {
+                ip: Self::text(&args[2]),
+                port: Self::text(&args[3]).parse().map_err(|_| ArgumentError::General(NOT_AN_INTEGER_ERROR.into()))?,
+                epoch: Self::text(&args[4]).parse().map_err(|_| ArgumentError::General(NOT_AN_INTEGER_ERROR.into()))?,
+                runid: Self::text(&args[5]),
+            },
+            SENTINEL_MONITOR_OPTION if args.len() == 6 => SentinelCommand::MONITOR {
+                name: name(),
+                ip: Self::text(&args[3]),
+                port: Self::text(&args[4]).parse().map_err(|_| ArgumentError::General(NOT_AN_INTEGER_ERROR.into()))?,
+                quorum: Self::text(&args[5]).parse().map_err(|_| ArgumentError::General(NOT_AN_INTEGER_ERROR.into()))?,
+            },
+            SENTINEL_MASTERS_OPTION
+            | SENTINEL_MYID_OPTION
+            | SENTINEL_MASTER_OPTION
+            | SENTINEL_REPLICAS_OPTION
+            | SENTINEL_SLAVES_OPTION
+            | SENTINEL_SENTINELS_OPTION
+            | SENTINEL_GET_MASTER_ADDR_BY_NAME_OPTION
+            | SENTINEL_CKQUORUM_OPTION
+            | SENTINEL_FAILOVER_OPTION
+            | SENTINEL_REMOVE_OPTION
+            | SENTINEL_IS_MASTER_DOWN_BY_ADDR_OPTION
+            | SENTINEL_MONITOR_OPTION => {
+                return Err(Self::wrong_arity(&Self::subcommand_name(args)))
+            }
+            _ => return Err(Self::unknown_subcommand(args)),
+        };
+        Ok(Command::SENTINEL(subcommand))
+    }
+
+    // CLUSTER SETSLOT <slot> IMPORTING|MIGRATING|NODE <node-id> 또는 STABLE
+    fn parse_setslot(args: &[Vec<u8>]) -> Result<ClusterCommand, ArgumentError> {
+        let slot = Self::parse_slots(&args[2..3])?[0];
+        let state = match (Self::upper(&args[3]).as_str(), args.get(4)) {
+            (SETSLOT_IMPORTING_OPTION, Some(node_id)) if args.len() == 5 => SlotState::IMPORTING(Self::text(node_id)),
+            (SETSLOT_MIGRATING_OPTION, Some(node_id)) if args.len() == 5 => SlotState::MIGRATING(Self::text(node_id)),
+            (SETSLOT_NODE_OPTION, Some(node_id)) if args.len() == 5 => SlotState::NODE(Self::text(node_id)),
+            (SETSLOT_STABLE_OPTION, None) => SlotState::STABLE,
+            _ => return Err(ArgumentError::General(SYNTAX_ERROR.into())),
+        };
+        Ok(ClusterCommand::SETSLOT { slot, state })
+    }
+
+    // CLUSTER MEET <ip> <port> [<cluster bus port>], 버스 포트를 생략하면 port + 10000
+    fn parse_meet(args: &[Vec<u8>]) -> Result<ClusterCommand, ArgumentError> {
+        let port: u16 = Self::text(&args[3]).parse()
+            .map_err(|_| ArgumentError::General(format!("Invalid base port specified: {}", Self::text(&args[3]))))?;
+        let bus_port = match args.get(4) {
+            Some(bus_port) => Self::text(bus_port)
+                .parse()
+                .map_err(|_| ArgumentError::General(format!("Invalid bus port specified: {}", Self::text(bus_port))))?,
+            None => port.checked_add(cluster::CLUSTER_BUS_PORT_OFFSET).ok_or(ArgumentError::General(format!(
+                "Invalid base port specified: {}",
+                Self::text(&args[3])
+            )))?,
+        };
+        Ok(ClusterCommand::MEET {
+            ip: Self::text(&args[2]),
+            port,
+            bus_port,
+        })
+    }
+
+    fn parse_slots(args: &[Vec<u8>]) -> Result<Vec<u16>, ArgumentError> {
+        args.iter()
+            .map(|slot| cluster::parse_slot(&Self::text(slot)).ok_or(ArgumentError::General(INVALID_SLOT_ERROR.into())))
+            .collect()
+    }
+
+    // "<시작> <끝>" 쌍을 슬롯 목록으로 풂
+    fn parse_slot_ranges(args: &[Vec<u8>]) -> Result<Vec<u16>, ArgumentError> {
+        let mut slots = Vec::new();
+        for pair in args.chunks(2) {
+            let bounds = Self::parse_slots(pair)?;
+            if bounds[0] > bounds[1] {
+                return Err(ArgumentError::General(format!(
+                    "start slot number {} is greater than end slot number {}",
+                    bounds[0], bounds[1]
+                )));
+            }
+            slots.extend(bounds[0]..=bounds[1]);
+        }
+        Ok(slots)
+    }
+
+    pub(crate) fn parse_client_list(args: &[Vec<u8>]) -> Result<Command, ArgumentError> {
+        match args.len() {
+            2 => return Ok(Command::CLIENT(ClientCommand::LIST(None))),
+            4 if args[2].eq_ignore_ascii_case(TYPE_OPTION.as_bytes()) => {}
+            _ => return Err(ArgumentError::General(SYNTAX_ERROR.into())),
+        }
+        Ok(Command::CLIENT(ClientCommand::LIST(Some(Self::parse_client_type(&args[3])?))))
+    }
+
+    fn parse_client_type(value: &[u8]) -> Result<ClientType, ArgumentError> {
+        match Self::upper(value).as_str() {
+            CLIENT_TYPE_NORMAL => Ok(ClientType::NORMAL),
+            CLIENT_TYPE_MASTER => Ok(ClientType::MASTER),
+            CLIENT_TYPE_REPLICA | CLIENT_TYPE_SLAVE => Ok(ClientType::REPLICA),
+            CLIENT_TYPE_PUBSUB => Ok(ClientType::PUBSUB),
+            _ => Err(ArgumentError::General(format!("{} '{}'", UNKNOWN_CLIENT_TYPE_ERROR, Self::text(value)))),
+        }
+    }
+
+    // CLIENT KILL ip:port 또는 CLIENT KILL <filter> <value> [<filter> <value> ...]
+    pub(crate) fn parse_client_kill(args: &[Vec<u8>]) -> Result<Command, ArgumentError> {
+        let mut filter = ClientKillFilter { id: None, addr: None, laddr: None, client_type: None, user: None, max_age_secs: None, skip_me: true };
+        if args.len() == 3 {
+            filter.addr = Some(Self::text(&args[2]));
+            filter.skip_me = false;
+            return Ok(Command::CLIENT(ClientCommand::KILL { filter, legacy: true }));
+        }
+        if args.len() < 3 || args.len() % 2 != 0 {
+            return Err(ArgumentError::General(SYNTAX_ERROR.into()));
+        }
+        for pair in args[2..].chunks(2) {
+            let value = &pair[1];
+            match Self::upper(&pair[0]).as_str() {
+                CLIENT_KILL_ID_FILTER => {
+                    let id = Self::text(value).parse::<u64>().ok().filter(|id| *id > 0);
+                    filter.id = Some(id.ok_or(ArgumentError::General(CLIENT_ID_ERROR.into()))?);
+                }
+                CLIENT_KILL_ADDR_FILTER => filter.addr = Some(Self::text(value)),
+                CLIENT_KILL_LADDR_FILTER => filter.laddr = Some(Self::text(value)),
+                TYPE_OPTION => filter.client_type = Some(Self::parse_client_type(value)?),
+                CLIENT_KILL_USER_FILTER => filter.user = Some(Self::text(value)),
+                CLIENT_KILL_MAXAGE_FILTER => {
+                    let max_age = Self::text(value).parse::<u64>().map_err(|_| ArgumentError::General(NOT_AN_INTEGER_ERROR.into()))?;
+                    filter.max_age_secs = Some(max_age).filter(|max_age| *max_age > 0);
+                }
+                CLIENT_KILL_SKIPME_FILTER => {
+                    filter.skip_me = match Self::upper(value).as_str() {
+                        "YES" => true,
+                        "NO" => false,
+                        _ => return Err(ArgumentError::General(SYNTAX_ERROR.into())),
+                    }
+                }
+                _ => return Err(ArgumentError::General(SYNTAX_ERROR.into())),
+            }
+        }
+        Ok(Command::CLIENT(ClientCommand::KILL { filter, legacy: false }))
+    }
+
+    pub(crate) fn parse_client_tracking(args: &[Vec<u8>]) -> Result<Command, ArgumentError> {
+        let enabled = match args.get(2).map(|value| Self::upper(value)) {
+            Some(value) if value == ON_OPTION => true,
+            Some(value) if value == OFF_OPTION => false,
+            _ => return Err(ArgumentError::General(SYNTAX_ERROR.into())),
+        };
+        let mut options = TrackingOptions::default();
+        let mut index = 3;
+        while index < args.len() {
+            match Self::upper(&args[index]).as_str() {
+                REDIRECT_OPTION if index + 1 < args.len() => {
+                    index += 1;
+                    let redirect = Self::text(&args[index]).parse::<u64>()
+                        .map_err(|_| ArgumentError::General(NOT_AN_INTEGER_ERROR.into()))?;
+                    options.redirect = Some(redirect);
+                }
+                PREFIX_OPTION if index + 1 < args.len() => {
+                    index += 1;
+                    options.prefixes.push(Self::text(&args[index]));
+                }
+                BCAST_OPTION => options.bcast = true,
+                NOLOOP_OPTION => options.noloop = true,
+                OPTIN_OPTION => options.optin = true,
+                OPTOUT_OPTION => options.optout = true,
+                _ => return Err(ArgumentError::General(SYNTAX_ERROR.into())),
+            }
+            index += 1;
+        }
+        if !options.prefixes.is_empty() && !options.bcast {
+            return Err(ArgumentError::General(PREFIX_REQUIRES_BCAST_ERROR.into()));
+        }
+        if options.optin && options.optout {
+            return Err(ArgumentError::General(OPTIN_AND_OPTOUT_ERROR.into()));
+        }
+        if options.bcast && (options.optin || options.optout) {
+            return Err(ArgumentError::General(OPTIN_OPTOUT_WITH_BCAST_ERROR.into()));
+        }
+        Ok(Command::CLIENT(ClientCommand::TRACKING(enabled.then_some(options))))
+    }
+
+    pub(crate) fn parse_pubsub(args: &[Vec<u8>]) -> Result<Command, ArgumentError> {
+        if args.len() < 2 {
+            return Err(Self::wrong_arity(PUBSUB_COMMAND));
+        }
+        let subcommand = match Self::upper(&args[1]).as_str() {
+            PUBSUB_CHANNELS_OPTION if args.len() <= 3 => PubSubCommand::CHANNELS(args.get(2).map(|pattern| Self::text(pattern))),
+            PUBSUB_SHARDCHANNELS_OPTION if args.len() <= 3 => PubSubCommand::SHARDCHANNELS(args.get(2).map(|pattern| Self::text(pattern))),
+            PUBSUB_NUMSUB_OPTION => PubSubCommand::NUMSUB(Self::texts(&args[2..])),
+            PUBSUB_SHARDNUMSUB_OPTION => PubSubCommand::SHARDNUMSUB(Self::texts(&args[2..])),
+            PUBSUB_NUMPAT_OPTION if args.len() == 2 => PubSubCommand::NUMPAT,
+            PUBSUB_CHANNELS_OPTION | PUBSUB_SHARDCHANNELS_OPTION | PUBSUB_NUMPAT_OPTION => {
+                return Err(Self::wrong_arity(&Self::subcommand_name(args)))
+            }
+            _ => return Err(Self::unknown_subcommand(args)),
+        };
+        Ok(Command::PUBSUB(subcommand))
+    }
+
+    pub(crate) fn parse_flush(args: &[Vec<u8>]) -> Result<Command, ArgumentError> {
+        let mode = Self::parse_flush_mode(args, 1)?;
+        match Self::text(&args[0]).as_str() {
+            FLUSHDB_COMMAND => Ok(Command::FLUSHDB(mode)),
+            _ => Ok(Command::FLUSHALL(mode)),
+        }
+    }
+
+    // 선택적인 마지막 인자 SYNC | ASYNC
+    fn parse_flush_mode(args: &[Vec<u8>], mode_index: usize) -> Result<FlushMode, ArgumentError> {
+        match args.get(mode_index).map(|mode| Self::upper(mode)) {
+            None => Ok(FlushMode::SYNC),
+            Some(mode) if args.len() == mode_index + 1 && mode == SYNC_OPTION => Ok(FlushMode::SYNC),
+            Some(mode) if args.len() == mode_index + 1 && mode == ASYNC_OPTION => Ok(FlushMode::ASYNC),
+            _ => Err(ArgumentError::General(SYNTAX_ERROR.into())),
+        }
+    }
+
+    pub(crate) fn parse_debug(args: &[Vec<u8>]) -> Result<Command, ArgumentError> {
+        if args.len() < 2 {
+            return Err(Self::wrong_arity(DEBUG_COMMAND));
+        }
+        match Self::upper(&args[1]).as_str() {
+            DEBUG_REPORT_OPTION => Self::check_args_len(args, 2, &Self::subcommand_name(args)).map(|_| Command::DEBUG(DebugCommand::REPORT)),
+            DEBUG_PROTOCOL_OPTION => {
+                Self::check_args_len(args, 3, &Self::subcommand_name(args))?;
+                Ok(Command::DEBUG(DebugCommand::PROTOCOL(Self::text(&args[2]).to_lowercase())))
+            }
+            DEBUG_SLEEP_OPTION => {
+                Self::check_args_len(args, 3, &Self::subcommand_name(args))?;
+                let seconds = Self::text(&args[2])
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|seconds| seconds.is_finite() && *seconds >= 0.0)
+                    .ok_or_else(|| ArgumentError::General(NOT_A_FLOAT_ERROR.into()))?;
+                Ok(Command::DEBUG(DebugCommand::SLEEP((seconds * 1000.0) as u64)))
+            }
+            DEBUG_SET_ACTIVE_EXPIRE_OPTION => {
+                Self::check_args_len(args, 3, &Self::subcommand_name(args))?;
+                let enabled = Self::text(&args[2])
+                    .parse::<i64>()
+                    .map_err(|_| ArgumentError::General(NOT_AN_INTEGER_ERROR.into()))?;
+                Ok(Command::DEBUG(DebugCommand::SETACTIVEEXPIRE(enabled != 0)))
+            }
+            DEBUG_OBJECT_OPTION => Self::check_args_len(args, 3, &Self::subcommand_name(args)).map(|_| Command::DEBUG(DebugCommand::OBJECT(args[2].clone()))),
+            DEBUG_CHANGE_REPL_ID_OPTION => Self::check_args_len(args, 2, &Self::subcommand_name(args)).map(|_| Command::DEBUG(DebugCommand::CHANGEREPLID)),
+            DEBUG_RELOAD_OPTION => Self::check_args_len(args, 2, &Self::subcommand_name(args)).map(|_| Command::DEBUG(DebugCommand::RELOAD)),
+            _ => Err(Self::unknown_subcommand(args)),
+        }
+    }
+
+    pub(crate) fn parse_latency(args: &[Vec<u8>]) -> Result<Command, ArgumentError> {
+        if args.len() < 2 {
+            return Err(Self::wrong_arity(LATENCY_COMMAND));
+        }
+        let subcommand = match Self::upper(&args[1]).as_str() {
+            LATENCY_LATEST_OPTION => Self::check_args_len(args, 2, &Self::subcommand_name(args)).map(|_| LatencyCommand::LATEST)?,
+            LATENCY_HISTORY_OPTION => {
+                Self::check_args_len(args, 3, &Self::subcommand_name(args))?;
+                LatencyCommand::HISTORY(Self::text(&args[2]).to_lowercase())
+            }
+            LATENCY_RESET_OPTION => LatencyCommand::RESET(args[2..].iter().map(|event| Self::text(event).to_lowercase()).collect()),
+            LATENCY_DOCTOR_OPTION => Self::check_args_len(args, 2, &Self::subcommand_name(args)).map(|_| LatencyCommand::DOCTOR)?,
+            _ => return Err(Self::unknown_subcommand(args)),
+        };
+        Ok(Command::LATENCY(subcommand))
+    }
+
+    pub(crate) fn parse_memory(args: &[Vec<u8>]) -> Result<Command, ArgumentError> {
+        if args.len() < 2 {
+            return Err(Self::wrong_arity(MEMORY_COMMAND));
+        }
+        let subcommand = match Self::upper(&args[1]).as_str() {
+            MEMORY_USAGE_OPTION => {
+                if args.len() < 3 {
+                    return Err(Self::wrong_arity(&Self::subcommand_name(args)));
+                }
+                let samples = match &args[3..] {
+                    [] => DEFAULT_USAGE_SAMPLES,
+                    [option, count] if option.eq_ignore_ascii_case(SAMPLES_OPTION.as_bytes()) => Self::text(count)
+                        .parse::<usize>()
+                        .map_err(|_| ArgumentError::General(NOT_AN_INTEGER_ERROR.into()))?,
+                    _ => return Err(ArgumentError::General(SYNTAX_ERROR.into())),
+                };
+                MemoryCommand::USAGE { key: args[2].clone(), samples }
+            }
+            MEMORY_STATS_OPTION => Self::check_args_len(args, 2, &Self::subcommand_name(args)).map(|_| MemoryCommand::STATS)?,
+            MEMORY_DOCTOR_OPTION => Self::check_args_len(args, 2, &Self::subcommand_name(args)).map(|_| MemoryCommand::DOCTOR)?,
+            _ => return Err(Self::unknown_subcommand(args)),
+        };
+        Ok(Command::MEMORY(subcommand))
+    }
+
+    pub(crate) fn parse_dump(args: &[Vec<u8>]) -> Result<Command, ArgumentError> {
+        Self::check_args_len(args, 2, DUMP_COMMAND)?;
+        Ok(Command::DUMP(args[1].clone()))
+    }
+
+    pub(crate) fn parse_restore(args: &[Vec<u8>]) -> Result<Command, ArgumentError> {
+        let asking = args[0].eq_ignore_ascii_case(RESTORE_ASKING_COMMAND.as_bytes());
+        if args.len() < 4 {
+            let name = if asking { RESTORE_ASKING_COMMAND } else { RESTORE_COMMAND };
+            return Err(Self::wrong_arity(name));
+        }
+
+        let ttl_ms = Self::text(&args[2]).parse::<i64>().map_err(|_| ArgumentError::General(NOT_AN_INTEGER_ERROR.into()))?;
+        if ttl_ms < 0 {
+            return Err(ArgumentError::General(INVALID_TTL_ERROR.into()));
+        }
+        let payload = args[3].clone();
+
+        let mut replace = false;
+        let mut absttl = false;
+        let mut idle_seconds = None;
+        let mut frequency = None;
+        let mut arg_index = 4;
+        while arg_index < args.len() {
+            match Self::upper(&args[arg_index]).as_str() {
+                REPLACE_OPTION => replace = true,
+                ABSTTL_OPTION => absttl = true,
+                OBJECT_IDLETIME_OPTION if frequency.is_none() => {
+                    arg_index += 1;
+                    let value = args.get(arg_index).ok_or(ArgumentError::General(SYNTAX_ERROR.into()))?;
+                    idle_seconds = Some(Self::text(value).parse::<u64>().map_err(|_| ArgumentError::General(NOT_AN_INTEGER_ERROR.into()))?);
+                }
+                OBJECT_FREQ_OPTION if idle_seconds.is_none() => {
+                    arg_index += 1;
+                    let value = args.get(arg_index).ok_or(ArgumentError::General(SYNTAX_ERROR.into()))?;
+                    frequency = Some(Self::text(value).parse::<u8>().map_err(|_| ArgumentError::General(NOT_AN_INTEGER_ERROR.into()))?);
+                }
+                _ => return Err(ArgumentError::General(SYNTAX_ERROR.into())),
+            }
+            arg_index += 1;
+        }
+
+        Ok(Command::RESTORE { key: args[1].clone(), ttl_ms, payload, replace, absttl, idle_seconds, frequency, asking })
+    }
+
+    // MIGRATE host port key|"" destination-db timeout [COPY] [REPLACE] [AUTH password | AUTH2 username password] [KEYS key [key ...]]
+    pub(crate) fn parse_migrate(args: &[Vec<u8>]) -> Result<Command, ArgumentError> {
+        let port = Self::text(&args[2]).parse::<u16>().map_err(|_| ArgumentError::General(NOT_AN_INTEGER_ERROR.into()))?;
+        let db = Self::text(&args[4]).parse::<i64>().map_err(|_| ArgumentError::General(NOT_AN_INTEGER_ERROR.into()))?;
+        if db != 0 {
+            return Err(ArgumentError::General(DB_INDEX_OUT_OF_RANGE_ERROR.into()));
         }
+        // Redis처럼 0 이하의 timeout은 1초로 봄
+        let timeout_ms = Self::text(&args[5]).parse::<i64>().map_err(|_| ArgumentError::General(NOT_AN_INTEGER_ERROR.into()))?;
+        let timeout_ms = if timeout_ms <= 0 { 1000 } else { timeout_ms as u64 };
 
-        match args[1].to_uppercase().as_str() {
-            CONFIG_GET_OPTION => Ok(Command::CONFIG(ConfigCommand::GET(args[2].clone()))),
-            _ => Err(ArgumentError::General(UNSUPPORTED_CONFIG_SUBCOMMAND_ERROR.into())),
+        let mut copy = false;
+        let mut replace = false;
+        let mut auth = None;
+        let mut keys = None;
+        let mut arg_index = 6;
+        while arg_index < args.len() {
+            match Self::upper(&args[arg_index]).as_str() {
+                MIGRATE_COPY_OPTION => copy = true,
+                REPLACE_OPTION => replace = true,
+                MIGRATE_AUTH_OPTION if arg_index + 1 < args.len() => {
+                    auth = Some((None, Self::text(&args[arg_index + 1])));
+                    arg_index += 1;
+                }
+                MIGRATE_AUTH2_OPTION if arg_index + 2 < args.len() => {
+                    auth = Some((Some(Self::text(&args[arg_index + 1])), Self::text(&args[arg_index + 2])));
+                    arg_index += 2;
+                }
+                MIGRATE_KEYS_OPTION => {
+                    if !args[3].is_empty() {
+                        return Err(ArgumentError::General(MIGRATE_KEYS_WITH_KEY_ERROR.into()));
+                    }
+                    keys = Some(args[arg_index + 1..].to_vec());
+                    break;
+                }
+                _ => return Err(ArgumentError::General(SYNTAX_ERROR.into())),
+            }
+            arg_index += 1;
         }
+
+        Ok(Command::MIGRATE {
+            host: Self::text(&args[1]),
+            port,
+            keys: keys.unwrap_or_else(|| vec![args[3].clone()]),
+            timeout_ms,
+            copy,
+            replace,
+            auth,
+        })
     }
 
-    fn parse_keys(args: &[String]) -> Result<Command, ArgumentError> {
+    pub(crate) fn parse_keys(args: &[Vec<u8>]) -> Result<Command, ArgumentError> {
         Self::check_args_len(args, 2, KEYS_COMMAND)?;
-        if args[1] != "*" {
-            return Err(ArgumentError::General(UNSUPPORTED_PATTERN_ERROR.into()));
+        Ok(Command::KEYS { pattern: args[1].clone(), namespace: None })
+    }
+
+    pub(crate) fn parse_scan(args: &[Vec<u8>]) -> Result<Command, ArgumentError> {
+        if args.len() < 2 {
+            return Err(Self::wrong_arity(SCAN_COMMAND));
+        }
+
+        let cursor = Self::text(&args[1]).parse::<u64>().map_err(|_| ArgumentError::General(INVALID_CURSOR_ERROR.into()))?;
+        let mut pattern = None;
+        let mut count = 10;
+        let mut type_filter = None;
+
+        let mut arg_index = 2;
+        while arg_index < args.len() {
+            let value = args.get(arg_index + 1).ok_or(ArgumentError::General(SYNTAX_ERROR.into()))?;
+            match Self::upper(&args[arg_index]).as_str() {
+                MATCH_OPTION => pattern = Some(value.clone()),
+                COUNT_OPTION => {
+                    count = Self::text(value)
+                        .parse::<usize>()
+                        .map_err(|_| ArgumentError::General(NOT_AN_INTEGER_ERROR.into()))?;
+                    if count == 0 {
+                        return Err(ArgumentError::General(SYNTAX_ERROR.into()));
+                    }
+                }
+                TYPE_OPTION => type_filter = Some(Self::text(value)),
+                _ => return Err(ArgumentError::General(SYNTAX_ERROR.into())),
+            }
+            arg_index += 2;
         }
-        Ok(Command::KEYS(args[1].clone()))
+
+        Ok(Command::SCAN { cursor, pattern, count, type_filter, namespace: None })
     }
 
-    fn parse_info(args: &[String]) -> Result<Command, ArgumentError> {
-        Self::check_args_len(args, 2, INFO_COMMAND)?;
-        Ok(Command::INFO(args[1].clone()))
+    pub(crate) fn parse_info(args: &[Vec<u8>]) -> Result<Command, ArgumentError> {
+        if args.len() > 2 {
+            return Err(Self::wrong_arity(INFO_COMMAND));
+        }
+        Ok(Command::INFO(args.get(1).map(|section| Self::text(section))))
+    }
+    pub(crate) fn parse_shutdown(args: &[Vec<u8>]) -> Result<Command, ArgumentError> {
+        if args.len() > 2 {
+            return Err(ArgumentError::General(SYNTAX_ERROR.into()));
+        }
+        match args.get(1).map(|option| Self::upper(option)).as_deref() {
+            None => Ok(Command::SHUTDOWN(None)),
+            Some(SAVE_OPTION) => Ok(Command::SHUTDOWN(Some(true))),
+            Some(NOSAVE_OPTION) => Ok(Command::SHUTDOWN(Some(false))),
+            Some(_) => Err(ArgumentError::General(SYNTAX_ERROR.into())),
+        }
     }
-    fn parse_replconf(args: &[String]) -> Result<Command, ArgumentError> {
+
+    pub(crate) fn parse_replconf(args: &[Vec<u8>]) -> Result<Command, ArgumentError> {
         if args.len() < 3 {
-            return Err(ArgumentError::General(CONFIG_ARGUMENTS_ERROR.into()));
+            return Err(Self::wrong_arity(REPLCONF_COMMAND));
         }
-        Ok(Command::REPLCONF(args[1..].to_vec()))
+        Ok(Command::REPLCONF(Self::texts(&args[1..])))
     }
 
-    fn parse_psync(args: &[String]) -> Result<Command, ArgumentError> {
+    pub(crate) fn parse_psync(args: &[Vec<u8>]) -> Result<Command, ArgumentError> {
         if args.len() < 3 {
-            return Err(ArgumentError::General(CONFIG_ARGUMENTS_ERROR.into()));
+            return Err(Self::wrong_arity(PSYNC_COMMAND));
+        }
+        Ok(Command::PSYNC(Self::texts(&args[1..])))
+    }
+
+    // WAIT numreplicas timeout: timeout은 밀리초, 0이면 무한히 기다림
+    pub(crate) fn parse_wait(args: &[Vec<u8>]) -> Result<Command, ArgumentError> {
+        Self::check_args_len(args, 3, WAIT_COMMAND)?;
+        let numreplicas = Self::text(&args[1]).parse::<i64>()
+            .map_err(|_| ArgumentError::General(NOT_AN_INTEGER_ERROR.into()))?;
+        let timeout_ms = Self::text(&args[2]).parse::<i64>()
+            .map_err(|_| ArgumentError::General(NOT_AN_INTEGER_ERROR.into()))?;
+        if timeout_ms < 0 {
+            return Err(ArgumentError::General(TIMEOUT_NEGATIVE_ERROR.into()));
+        }
+        Ok(Command::WAIT { numreplicas: numreplicas.max(0) as usize, timeout_ms: timeout_ms as u64 })
+    }
+
+    pub(crate) fn parse_replicaof(args: &[Vec<u8>]) -> Result<Command, ArgumentError> {
+        Self::check_args_len(args, 3, &Self::text(&args[0]))?;
+
+        let target = if args[1].eq_ignore_ascii_case(b"NO") && args[2].eq_ignore_ascii_case(b"ONE") {
+            None
+        } else {
+            let port = Self::text(&args[2]).parse::<u16>().map_err(|_| ArgumentError::General(REPLICAOF_ARGUMENTS_ERROR.into()))?;
+            Some((Self::text(&args[1]), port))
+        };
+
+        match Self::text(&args[0]).as_str() {
+            REPLICAOF_COMMAND => Ok(Command::REPLICAOF(target)),
+            _ => Ok(Command::SLAVEOF(target)),
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Command, ArgumentError> {
+        let args: Vec<Vec<u8>> = args.iter().map(|arg| arg.as_bytes().to_vec()).collect();
+        CommandParser::parse_args(&args)
+    }
+
+    fn parse_ok(args: &[&str]) -> Command {
+        parse(args).unwrap_or_else(|e| panic!("{:?} failed to parse: {}", args, e))
+    }
+
+    fn parse_err(args: &[&str]) -> String {
+        match parse(args) {
+            Ok(command) => panic!("{:?} parsed as {}", args, command.name()),
+            Err(ArgumentError::General(message)) => message,
+        }
+    }
+
+    type Expected<T> = fn(&T) -> bool;
+
+    fn arity(name: &str) -> String {
+        format!("wrong number of arguments for '{}' command", name)
+    }
+
+    #[test]
+    fn parses_connection_commands() {
+        assert!(matches!(parse_ok(&["PING"]), Command::PING(None)));
+        assert!(matches!(parse_ok(&["ping", "hi"]), Command::PING(Some(message)) if message == b"hi"));
+        assert_eq!(parse_err(&["PING", "a", "b"]), arity("ping"));
+        assert!(matches!(parse_ok(&["ECHO", "hi"]), Command::ECHO(message) if message == b"hi"));
+        assert_eq!(parse_err(&["ECHO"]), arity("echo"));
+
+        assert!(matches!(parse_ok(&["HELLO"]), Command::HELLO { protover: None, auth: None, setname: None }));
+        let Command::HELLO { protover, auth, setname } = parse_ok(&["HELLO", "3", "AUTH", "alice", "secret", "SETNAME", "app"]) else {
+            panic!("not HELLO");
+        };
+        assert_eq!(protover, Some(3));
+        assert_eq!(auth, Some(("alice".to_string(), "secret".to_string())));
+        assert_eq!(setname.as_deref(), Some("app"));
+        assert_eq!(parse_err(&["HELLO", "three"]), PROTOCOL_VERSION_ERROR);
+        assert_eq!(parse_err(&["HELLO", "3", "AUTH", "alice"]), "Syntax error in HELLO option 'AUTH'");
+
+        assert!(matches!(parse_ok(&["AUTH", "pw"]), Command::AUTH { username: None, password } if password == "pw"));
+        assert!(matches!(parse_ok(&["AUTH", "alice", "pw"]), Command::AUTH { username: Some(user), .. } if user == "alice"));
+        assert_eq!(parse_err(&["AUTH", "a", "b", "c"]), SYNTAX_ERROR);
+        assert_eq!(parse_err(&["AUTH"]), arity("auth"));
+    }
+
+    #[test]
+    fn parses_commands_without_arguments() {
+        let cases: &[(&str, Expected<Command>)] = &[
+            ("QUIT", |command| matches!(command, Command::QUIT)),
+            ("RESET", |command| matches!(command, Command::RESET)),
+            ("ASKING", |command| matches!(command, Command::ASKING)),
+            ("READONLY", |command| matches!(command, Command::READONLY)),
+            ("READWRITE", |command| matches!(command, Command::READWRITE)),
+            ("MULTI", |command| matches!(command, Command::MULTI)),
+            ("EXEC", |command| matches!(command, Command::EXEC)),
+            ("DISCARD", |command| matches!(command, Command::DISCARD)),
+            ("UNWATCH", |command| matches!(command, Command::UNWATCH)),
+            ("RANDOMKEY", |command| matches!(command, Command::RANDOMKEY)),
+            ("DBSIZE", |command| matches!(command, Command::DBSIZE(None))),
+            ("BGSAVE", |command| matches!(command, Command::BGSAVE)),
+            ("LASTSAVE", |command| matches!(command, Command::LASTSAVE)),
+        ];
+        for (name, expected) in cases {
+            // 소문자로 온 이름도 같은 명령으로 읽음
+            assert!(expected(&parse_ok(&[&name.to_lowercase()])), "{}", name);
+            assert_eq!(parse_err(&[name, "extra"]), arity(&name.to_lowercase()));
         }
-        Ok(Command::PSYNC(args[1..].to_vec()))
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn rejects_empty_and_unknown_commands() {
+        assert_eq!(parse_err(&[]), EMPTY_COMMAND_ERROR);
+        assert_eq!(parse_err(&["NOSUCH", "a", "b"]), "unknown command 'NOSUCH', with args beginning with: 'a' 'b' ");
+    }
+
+    #[test]
+    fn parses_string_commands() {
+        assert!(matches!(parse_ok(&["GET", "k"]), Command::GET(key) if key == b"k"));
+        assert_eq!(parse_err(&["GET"]), arity("get"));
+
+        assert!(matches!(parse_ok(&["SET", "k", "v"]), Command::SET { expiration: None, .. }));
+        assert!(matches!(parse_ok(&["SET", "k", "v", "ex", "10"]), Command::SET { expiration: Some(Expiration::EX(10)), .. }));
+        assert!(matches!(parse_ok(&["SET", "k", "v", "PX", "10"]), Command::SET { expiration: Some(Expiration::PX(10)), .. }));
+        assert!(matches!(parse_ok(&["SET", "k", "v", "EXAT", "10"]), Command::SET { expiration: Some(Expiration::EXAT(10)), .. }));
+        assert!(matches!(parse_ok(&["SET", "k", "v", "PXAT", "10"]), Command::SET { expiration: Some(Expiration::PXAT(10)), .. }));
+        assert_eq!(parse_err(&["SET", "k"]), arity("set"));
+        assert_eq!(parse_err(&["SET", "k", "v", "EX", "1", "PX", "1"]), SYNTAX_ERROR);
+        assert_eq!(parse_err(&["SET", "k", "v", "KEEP"]), "Unknown option: 'KEEP'");
+        assert_eq!(parse_err(&["SET", "k", "v", "EX"]), format!("{}: EX", OPTION_ARGUMENT_MISSING_ERROR));
+        assert_eq!(parse_err(&["SET", "k", "v", "EX", "soon"]), NOT_AN_INTEGER_ERROR);
+        assert_eq!(parse_err(&["SET", "k", "v", "EX", "-1"]), "invalid expire time in 'set' command");
+
+        assert!(matches!(parse_ok(&["GETSET", "k", "v"]), Command::GETSET { .. }));
+        assert_eq!(parse_err(&["GETSET", "k"]), arity("getset"));
+        assert!(matches!(parse_ok(&["GETEX", "k"]), Command::GETEX { expiration: None, persist: false, .. }));
+        assert!(matches!(parse_ok(&["GETEX", "k", "persist"]), Command::GETEX { expiration: None, persist: true, .. }));
+        assert!(matches!(parse_ok(&["GETEX", "k", "EX", "5"]), Command::GETEX { expiration: Some(Expiration::EX(5)), .. }));
+        assert_eq!(parse_err(&["GETEX", "k", "KEEPTTL"]), SYNTAX_ERROR);
+        assert_eq!(parse_err(&["GETEX", "k", "EX", "5", "PERSIST"]), SYNTAX_ERROR);
+        assert!(matches!(parse_ok(&["INCR", "k"]), Command::INCR(_)));
+        assert_eq!(parse_err(&["INCR"]), arity("incr"));
+        assert!(matches!(parse_ok(&["TYPE", "k"]), Command::TYPE(_)));
+        assert_eq!(parse_err(&["TYPE", "k", "x"]), arity("type"));
+
+        assert!(matches!(
+            parse_ok(&["LCS", "a", "b", "IDX", "MINMATCHLEN", "-3", "WITHMATCHLEN"]),
+            Command::LCS { len: false, idx: true, min_match_len: 0, with_match_len: true, .. }
+        ));
+        assert!(matches!(parse_ok(&["LCS", "a", "b", "LEN"]), Command::LCS { len: true, idx: false, .. }));
+        assert_eq!(parse_err(&["LCS", "a", "b", "LEN", "IDX"]), LCS_LEN_AND_IDX_ERROR);
+        assert_eq!(parse_err(&["LCS", "a", "b", "MINMATCHLEN"]), SYNTAX_ERROR);
+        assert_eq!(parse_err(&["LCS", "a", "b", "FAST"]), SYNTAX_ERROR);
+        assert_eq!(parse_err(&["LCS", "a"]), arity("lcs"));
+    }
+
+    #[test]
+    fn parses_expire_and_key_commands() {
+        let Command::EXPIRE { seconds, conditions, .. } = parse_ok(&["EXPIRE", "k", "10", "nx"]) else {
+            panic!("not EXPIRE");
+        };
+        assert_eq!((seconds, conditions), (10, vec![ExpireCondition::NX]));
+        assert!(matches!(parse_ok(&["PEXPIRE", "k", "10"]), Command::PEXPIRE { milliseconds: 10, .. }));
+        assert!(matches!(parse_ok(&["EXPIREAT", "k", "10", "GT"]), Command::EXPIREAT { timestamp: 10, .. }));
+        assert!(matches!(parse_ok(&["PEXPIREAT", "k", "10", "XX", "LT"]), Command::PEXPIREAT { timestamp_ms: 10, .. }));
+        assert_eq!(parse_err(&["EXPIRE", "k"]), arity("expire"));
+        assert_eq!(parse_err(&["EXPIRE", "k", "10", "NX", "XX"]), NX_INCOMPATIBLE_ERROR);
+        assert_eq!(parse_err(&["EXPIRE", "k", "10", "GT", "LT"]), GT_LT_INCOMPATIBLE_ERROR);
+        assert_eq!(parse_err(&["EXPIRE", "k", "10", "KEEP"]), "Unsupported option KEEP");
+        // 옵션 에러가 정수 에러보다 먼저 나감
+        assert_eq!(parse_err(&["EXPIRE", "k", "soon", "KEEP"]), "Unsupported option KEEP");
+        assert_eq!(parse_err(&["EXPIRE", "k", "soon"]), NOT_AN_INTEGER_ERROR);
+
+        assert!(matches!(parse_ok(&["TTL", "k"]), Command::TTL(_)));
+        assert!(matches!(parse_ok(&["PTTL", "k"]), Command::PTTL(_)));
+        assert!(matches!(parse_ok(&["EXPIRETIME", "k"]), Command::EXPIRETIME(_)));
+        assert!(matches!(parse_ok(&["PEXPIRETIME", "k"]), Command::PEXPIRETIME(_)));
+        assert!(matches!(parse_ok(&["PERSIST", "k"]), Command::PERSIST(_)));
+        assert_eq!(parse_err(&["TTL", "a", "b"]), arity("ttl"));
+
+        assert!(matches!(parse_ok(&["DEL", "a", "b"]), Command::DEL(keys) if keys.len() == 2));
+        assert!(matches!(parse_ok(&["UNLINK", "a"]), Command::UNLINK(_)));
+        assert!(matches!(parse_ok(&["EXISTS", "a"]), Command::EXISTS(_)));
+        assert!(matches!(parse_ok(&["TOUCH", "a"]), Command::TOUCH(_)));
+        assert!(matches!(parse_ok(&["WATCH", "a"]), Command::WATCH(_)));
+        assert_eq!(parse_err(&["DEL"]), arity("del"));
+        assert_eq!(parse_err(&["WATCH"]), arity("watch"));
+
+        assert!(matches!(parse_ok(&["KEYS", "*"]), Command::KEYS { pattern, namespace: None } if pattern == b"*"));
+        assert_eq!(parse_err(&["KEYS"]), arity("keys"));
+        assert!(matches!(parse_ok(&["SCAN", "0"]), Command::SCAN { cursor: 0, pattern: None, count: 10, type_filter: None, .. }));
+        let Command::SCAN { cursor, pattern, count, type_filter, .. } = parse_ok(&["SCAN", "7", "MATCH", "a*", "COUNT", "3", "TYPE", "hash"]) else {
+            panic!("not SCAN");
+        };
+        assert_eq!((cursor, pattern.as_deref(), count, type_filter.as_deref()), (7, Some(&b"a*"[..]), 3, Some("hash")));
+        assert_eq!(parse_err(&["SCAN", "x"]), INVALID_CURSOR_ERROR);
+        assert_eq!(parse_err(&["SCAN", "0", "COUNT", "0"]), SYNTAX_ERROR);
+        assert_eq!(parse_err(&["SCAN", "0", "MATCH"]), SYNTAX_ERROR);
+        assert_eq!(parse_err(&["SCAN", "0", "LIMIT", "1"]), SYNTAX_ERROR);
+
+        assert!(matches!(parse_ok(&["FLUSHDB"]), Command::FLUSHDB(FlushMode::SYNC)));
+        assert!(matches!(parse_ok(&["FLUSHALL", "async"]), Command::FLUSHALL(FlushMode::ASYNC)));
+        assert_eq!(parse_err(&["FLUSHALL", "LATER"]), SYNTAX_ERROR);
+        assert_eq!(parse_err(&["FLUSHDB", "SYNC", "ASYNC"]), SYNTAX_ERROR);
+    }
+
+    #[test]
+    fn parses_hash_commands() {
+        assert!(matches!(parse_ok(&["HSET", "h", "f1", "v1", "f2", "v2"]), Command::HSET { fields, .. } if fields.len() == 2));
+        assert_eq!(parse_err(&["HSET", "h", "f1"]), arity("hset"));
+        assert_eq!(parse_err(&["HSET", "h", "f1", "v1", "f2"]), arity("hset"));
+        assert!(matches!(parse_ok(&["HGET", "h", "f"]), Command::HGET { .. }));
+        assert_eq!(parse_err(&["HGET", "h"]), arity("hget"));
+        assert!(matches!(parse_ok(&["HGETALL", "h"]), Command::HGETALL(_)));
+        assert!(matches!(parse_ok(&["HDEL", "h", "a", "b"]), Command::HDEL { fields, .. } if fields.len() == 2));
+        assert_eq!(parse_err(&["HDEL", "h"]), arity("hdel"));
+
+        assert!(matches!(parse_ok(&["HRANDFIELD", "h"]), Command::HRANDFIELD { count: None, withvalues: false, .. }));
+        assert!(matches!(parse_ok(&["HRANDFIELD", "h", "-2", "WITHVALUES"]), Command::HRANDFIELD { count: Some(-2), withvalues: true, .. }));
+        assert_eq!(parse_err(&["HRANDFIELD", "h", "2", "WITHSCORES"]), SYNTAX_ERROR);
+        assert_eq!(parse_err(&["HRANDFIELD", "h", "many"]), NOT_AN_INTEGER_ERROR);
+        assert_eq!(parse_err(&["HRANDFIELD", "h", &(-RANDOM_COUNT_LIMIT - 1).to_string()]), VALUE_OUT_OF_RANGE_ERROR);
+
+        let Command::HEXPIRE { seconds, conditions, fields, .. } = parse_ok(&["HEXPIRE", "h", "10", "NX", "FIELDS", "2", "a", "b"]) else {
+            panic!("not HEXPIRE");
+        };
+        assert_eq!((seconds, conditions, fields.len()), (10, vec![ExpireCondition::NX], 2));
+        assert!(matches!(parse_ok(&["HPEXPIRE", "h", "10", "FIELDS", "1", "a"]), Command::HPEXPIRE { milliseconds: 10, .. }));
+        assert_eq!(parse_err(&["HEXPIRE", "h", "10", "FIELDS", "2"]), arity("hexpire"));
+        assert_eq!(parse_err(&["HEXPIRE", "h", "10", "FIELDS", "2", "a"]), NUMFIELDS_MISMATCH_ERROR);
+        assert_eq!(parse_err(&["HEXPIRE", "h", "10", "NX", "1", "a"]), FIELDS_MISSING_ERROR);
+        assert_eq!(parse_err(&["HEXPIRE", "h", "10", "KEEP", "FIELDS", "1", "a"]), "Unsupported option KEEP");
+        assert_eq!(parse_err(&["HEXPIRE", "h", "10", "FIELDS", "0", "a"]), NUMFIELDS_ZERO_ERROR);
+
+        assert!(matches!(parse_ok(&["HTTL", "h", "FIELDS", "1", "a"]), Command::HTTL { .. }));
+        assert!(matches!(parse_ok(&["HPERSIST", "h", "FIELDS", "1", "a"]), Command::HPERSIST { .. }));
+        assert_eq!(parse_err(&["HTTL", "h", "FIELD", "1", "a"]), FIELDS_MISSING_ERROR);
+        assert_eq!(parse_err(&["HPERSIST", "h", "FIELDS", "1"]), arity("hpersist"));
+    }
+
+    #[test]
+    fn parses_list_commands() {
+        assert!(matches!(parse_ok(&["LPUSH", "l", "a", "b"]), Command::LPUSH { values, .. } if values.len() == 2));
+        assert!(matches!(parse_ok(&["RPUSH", "l", "a"]), Command::RPUSH { .. }));
+        assert_eq!(parse_err(&["RPUSH", "l"]), arity("rpush"));
+        assert!(matches!(parse_ok(&["LPOP", "l"]), Command::LPOP { count: None, .. }));
+        assert!(matches!(parse_ok(&["RPOP", "l", "3"]), Command::RPOP { count: Some(3), .. }));
+        assert_eq!(parse_err(&["LPOP", "l", "-1"]), VALUE_NOT_POSITIVE_ERROR);
+        assert_eq!(parse_err(&["LPOP", "l", "x"]), NOT_AN_INTEGER_ERROR);
+        assert_eq!(parse_err(&["LPOP", "l", "1", "2"]), arity("lpop"));
+
+        assert!(matches!(parse_ok(&["BLPOP", "a", "b", "0.5"]), Command::BLPOP { keys, timeout_ms: 500 } if keys.len() == 2));
+        assert!(matches!(parse_ok(&["BRPOP", "a", "0"]), Command::BRPOP { timeout_ms: 0, .. }));
+        assert_eq!(parse_err(&["BLPOP", "a"]), arity("blpop"));
+        assert_eq!(parse_err(&["BLPOP", "a", "-1"]), TIMEOUT_NEGATIVE_ERROR);
+        assert_eq!(parse_err(&["BLPOP", "a", "inf"]), TIMEOUT_NOT_FLOAT_ERROR);
+        assert_eq!(parse_err(&["BLPOP", "a", "1e300"]), TIMEOUT_OUT_OF_RANGE_ERROR);
+
+        assert!(matches!(
+            parse_ok(&["LMOVE", "s", "d", "left", "RIGHT"]),
+            Command::LMOVE { from: ListDirection::LEFT, to: ListDirection::RIGHT, .. }
+        ));
+        assert!(matches!(parse_ok(&["BLMOVE", "s", "d", "RIGHT", "LEFT", "1"]), Command::BLMOVE { timeout_ms: 1000, .. }));
+        assert_eq!(parse_err(&["LMOVE", "s", "d", "UP", "LEFT"]), SYNTAX_ERROR);
+        assert_eq!(parse_err(&["BLMOVE", "s", "d", "LEFT", "LEFT"]), arity("blmove"));
+        assert!(matches!(parse_ok(&["BRPOPLPUSH", "s", "d", "2"]), Command::BRPOPLPUSH { timeout_ms: 2000, .. }));
+        assert_eq!(parse_err(&["BRPOPLPUSH", "s", "d"]), arity("brpoplpush"));
+
+        assert!(matches!(
+            parse_ok(&["LMPOP", "2", "a", "b", "LEFT", "COUNT", "3"]),
+            Command::LMPOP { keys, direction: ListDirection::LEFT, count: 3 } if keys.len() == 2
+        ));
+        assert!(matches!(parse_ok(&["BLMPOP", "1", "1", "a", "RIGHT"]), Command::BLMPOP { count: 1, timeout_ms: 1000, .. }));
+        assert_eq!(parse_err(&["LMPOP", "1", "a"]), arity("lmpop"));
+        assert_eq!(parse_err(&["LMPOP", "0", "a", "LEFT"]), NUMKEYS_NOT_POSITIVE_ERROR);
+        assert_eq!(parse_err(&["LMPOP", "2", "a", "LEFT"]), SYNTAX_ERROR);
+        assert_eq!(parse_err(&["LMPOP", "1", "a", "LEFT", "COUNT", "0"]), COUNT_NOT_POSITIVE_ERROR);
+        assert_eq!(parse_err(&["LMPOP", "1", "a", "MIN"]), SYNTAX_ERROR);
+        assert_eq!(parse_err(&["BLMPOP", "-1", "1", "a", "LEFT"]), TIMEOUT_NEGATIVE_ERROR);
+    }
+
+    #[test]
+    fn parses_sorted_set_commands() {
+        assert!(matches!(parse_ok(&["ZADD", "z", "1", "a", "2.5", "b"]), Command::ZADD { members, incr: false, .. } if members.len() == 2));
+        assert!(matches!(parse_ok(&["ZADD", "z", "INCR", "1", "a"]), Command::ZADD { incr: true, .. }));
+        assert_eq!(parse_err(&["ZADD", "z", "1"]), arity("zadd"));
+        assert_eq!(parse_err(&["ZADD", "z", "INCR", "1", "a", "2", "b"]), ZADD_INCR_PAIR_ERROR);
+        assert_eq!(parse_err(&["ZADD", "z", "nan", "a"]), NOT_A_FLOAT_ERROR);
+        assert!(matches!(parse_ok(&["ZINCRBY", "z", "-1.5", "a"]), Command::ZINCRBY { increment, .. } if increment == -1.5));
+        assert_eq!(parse_err(&["ZINCRBY", "z", "one", "a"]), NOT_A_FLOAT_ERROR);
+        assert_eq!(parse_err(&["ZINCRBY", "z", "1"]), arity("zincrby"));
+
+        assert!(matches!(
+            parse_ok(&["ZMPOP", "1", "z", "MAX", "COUNT", "2"]),
+            Command::ZMPOP { direction: ScoreDirection::MAX, count: 2, .. }
+        ));
+        assert!(matches!(parse_ok(&["BZMPOP", "0", "1", "z", "min"]), Command::BZMPOP { direction: ScoreDirection::MIN, timeout_ms: 0, .. }));
+        assert_eq!(parse_err(&["ZMPOP", "1", "z", "LEFT"]), SYNTAX_ERROR);
+
+        assert!(matches!(parse_ok(&["ZRANDMEMBER", "z", "2", "withscores"]), Command::ZRANDMEMBER { count: Some(2), withscores: true, .. }));
+        assert_eq!(parse_err(&["ZRANDMEMBER", "z", "2", "WITHSCORES", "x"]), SYNTAX_ERROR);
+        assert!(matches!(parse_ok(&["ZSCORE", "z", "a"]), Command::ZSCORE { .. }));
+        assert_eq!(parse_err(&["ZSCORE", "z"]), arity("zscore"));
+    }
+
+    #[test]
+    fn parses_object_dump_restore_and_migrate() {
+        assert!(matches!(parse_ok(&["OBJECT", "encoding", "k"]), Command::OBJECT(ObjectCommand::ENCODING(_))));
+        assert!(matches!(parse_ok(&["OBJECT", "IDLETIME", "k"]), Command::OBJECT(ObjectCommand::IDLETIME(_))));
+        assert!(matches!(parse_ok(&["OBJECT", "FREQ", "k"]), Command::OBJECT(ObjectCommand::FREQ(_))));
+        assert!(matches!(parse_ok(&["OBJECT", "REFCOUNT", "k"]), Command::OBJECT(ObjectCommand::REFCOUNT(_))));
+        assert!(matches!(parse_ok(&["OBJECT", "help"]), Command::OBJECT(ObjectCommand::HELP)));
+        assert_eq!(parse_err(&["OBJECT", "HELP", "k"]), arity("object|help"));
+        assert_eq!(parse_err(&["OBJECT", "ENCODING"]), arity("object|encoding"));
+        assert_eq!(parse_err(&["OBJECT", "nosuch", "k"]), "unknown subcommand 'nosuch'. Try OBJECT HELP.");
+
+        assert!(matches!(parse_ok(&["DUMP", "k"]), Command::DUMP(_)));
+        assert_eq!(parse_err(&["DUMP"]), arity("dump"));
+
+        let Command::RESTORE { ttl_ms, replace, absttl, idle_seconds, frequency, asking, .. } =
+            parse_ok(&["RESTORE", "k", "100", "payload", "REPLACE", "ABSTTL", "IDLETIME", "5"])
+        else {
+            panic!("not RESTORE");
+        };
+        assert_eq!((ttl_ms, replace, absttl, idle_seconds, frequency, asking), (100, true, true, Some(5), None, false));
+        assert!(matches!(parse_ok(&["RESTORE-ASKING", "k", "0", "payload", "FREQ", "3"]), Command::RESTORE { asking: true, frequency: Some(3), .. }));
+        assert_eq!(parse_err(&["RESTORE", "k", "0"]), arity("restore"));
+        assert_eq!(parse_err(&["RESTORE", "k", "-1", "payload"]), INVALID_TTL_ERROR);
+        assert_eq!(parse_err(&["RESTORE", "k", "0", "payload", "IDLETIME", "1", "FREQ", "1"]), SYNTAX_ERROR);
+        assert_eq!(parse_err(&["RESTORE", "k", "0", "payload", "FREQ", "300"]), NOT_AN_INTEGER_ERROR);
+
+        let Command::MIGRATE { host, port, keys, timeout_ms, copy, replace, auth } =
+            parse_ok(&["MIGRATE", "127.0.0.1", "7000", "", "0", "0", "COPY", "AUTH2", "alice", "pw", "KEYS", "a", "b"])
+        else {
+            panic!("not MIGRATE");
+        };
+        assert_eq!((host.as_str(), port, keys.len(), timeout_ms, copy, replace), ("127.0.0.1", 7000, 2, 1000, true, false));
+        assert_eq!(auth, Some((Some("alice".to_string()), "pw".to_string())));
+        assert!(matches!(
+            parse_ok(&["MIGRATE", "h", "7000", "k", "0", "50", "REPLACE", "AUTH", "pw"]),
+            Command::MIGRATE { keys, timeout_ms: 50, replace: true, auth: Some((None, _)), .. } if keys == vec![b"k".to_vec()]
+        ));
+        assert_eq!(parse_err(&["MIGRATE", "h", "7000", "k", "1", "50"]), DB_INDEX_OUT_OF_RANGE_ERROR);
+        assert_eq!(parse_err(&["MIGRATE", "h", "7000", "k", "0", "50", "KEYS", "a"]), MIGRATE_KEYS_WITH_KEY_ERROR);
+        assert_eq!(parse_err(&["MIGRATE", "h", "port", "k", "0", "50"]), NOT_AN_INTEGER_ERROR);
+        assert_eq!(parse_err(&["MIGRATE", "h", "7000", "k", "0"]), arity("migrate"));
+    }
+
+    #[test]
+    fn parses_config_and_info() {
+        assert!(matches!(parse_ok(&["CONFIG", "get", "maxmemory", "save"]), Command::CONFIG(ConfigCommand::GET(names)) if names.len() == 2));
+        let Command::CONFIG(ConfigCommand::SET(pairs)) = parse_ok(&["CONFIG", "SET", "maxmemory", "10mb"]) else {
+            panic!("not CONFIG SET");
+        };
+        assert_eq!(pairs, vec![("maxmemory".to_string(), "10mb".to_string())]);
+        assert_eq!(parse_err(&["CONFIG", "GET"]), arity("config|get"));
+        assert_eq!(parse_err(&["CONFIG", "SET"]), arity("config|set"));
+        assert_eq!(parse_err(&["CONFIG", "SET", "maxmemory"]), format!("{} - 'maxmemory'", UNKNOWN_CONFIG_SET_OPTION_ERROR));
+        assert_eq!(parse_err(&["CONFIG", "SET", "maxmemory", "1", "save"]), format!("{} - 'maxmemory'", UNKNOWN_CONFIG_SET_OPTION_ERROR));
+        assert_eq!(parse_err(&["CONFIG", "REWRITE", "x"]), "unknown subcommand 'REWRITE'. Try CONFIG HELP.");
+
+        assert!(matches!(parse_ok(&["INFO"]), Command::INFO(None)));
+        assert!(matches!(parse_ok(&["INFO", "memory"]), Command::INFO(Some(section)) if section == "memory"));
+        assert_eq!(parse_err(&["INFO", "a", "b"]), arity("info"));
+    }
+
+    #[test]
+    fn parses_pubsub_commands() {
+        assert!(matches!(parse_ok(&["SUBSCRIBE", "a", "b"]), Command::SUBSCRIBE(channels) if channels.len() == 2));
+        assert!(matches!(parse_ok(&["PSUBSCRIBE", "a*"]), Command::PSUBSCRIBE(_)));
+        assert!(matches!(parse_ok(&["SSUBSCRIBE", "a"]), Command::SSUBSCRIBE(_)));
+        assert_eq!(parse_err(&["SUBSCRIBE"]), arity("subscribe"));
+        assert_eq!(parse_err(&["SSUBSCRIBE"]), arity("ssubscribe"));
+        assert!(matches!(parse_ok(&["UNSUBSCRIBE"]), Command::UNSUBSCRIBE(channels) if channels.is_empty()));
+        assert!(matches!(parse_ok(&["PUNSUBSCRIBE", "a*"]), Command::PUNSUBSCRIBE(_)));
+        assert!(matches!(parse_ok(&["SUNSUBSCRIBE", "a"]), Command::SUNSUBSCRIBE(_)));
+
+        assert!(matches!(parse_ok(&["PUBLISH", "c", "m"]), Command::PUBLISH { channel, .. } if channel == "c"));
+        assert!(matches!(parse_ok(&["SPUBLISH", "c", "m"]), Command::SPUBLISH { .. }));
+        assert_eq!(parse_err(&["PUBLISH", "c"]), arity("publish"));
+        assert_eq!(parse_err(&["SPUBLISH", "c", "m", "x"]), arity("spublish"));
+
+        assert!(matches!(parse_ok(&["PUBSUB", "CHANNELS"]), Command::PUBSUB(PubSubCommand::CHANNELS(None))));
+        assert!(matches!(parse_ok(&["PUBSUB", "channels", "a*"]), Command::PUBSUB(PubSubCommand::CHANNELS(Some(_)))));
+        assert!(matches!(parse_ok(&["PUBSUB", "SHARDCHANNELS"]), Command::PUBSUB(PubSubCommand::SHARDCHANNELS(None))));
+        assert!(matches!(parse_ok(&["PUBSUB", "NUMSUB", "a", "b"]), Command::PUBSUB(PubSubCommand::NUMSUB(channels)) if channels.len() == 2));
+        assert!(matches!(parse_ok(&["PUBSUB", "SHARDNUMSUB"]), Command::PUBSUB(PubSubCommand::SHARDNUMSUB(_))));
+        assert!(matches!(parse_ok(&["PUBSUB", "NUMPAT"]), Command::PUBSUB(PubSubCommand::NUMPAT)));
+        assert_eq!(parse_err(&["PUBSUB", "CHANNELS", "a", "b"]), arity("pubsub|channels"));
+        assert_eq!(parse_err(&["PUBSUB", "NUMPAT", "x"]), arity("pubsub|numpat"));
+        assert_eq!(parse_err(&["PUBSUB", "nosuch"]), "unknown subcommand 'nosuch'. Try PUBSUB HELP.");
+    }
+
+    #[test]
+    fn parses_scripting_commands() {
+        assert!(matches!(parse_ok(&["SCRIPT", "LOAD", "return 1"]), Command::SCRIPT(ScriptCommand::LOAD(_))));
+        assert!(matches!(parse_ok(&["SCRIPT", "EXISTS", "a", "b"]), Command::SCRIPT(ScriptCommand::EXISTS(shas)) if shas.len() == 2));
+        assert!(matches!(parse_ok(&["SCRIPT", "FLUSH", "ASYNC"]), Command::SCRIPT(ScriptCommand::FLUSH(FlushMode::ASYNC))));
+        assert!(matches!(parse_ok(&["SCRIPT", "KILL"]), Command::SCRIPT(ScriptCommand::KILL)));
+        assert_eq!(parse_err(&["SCRIPT", "LOAD"]), arity("script|load"));
+        assert_eq!(parse_err(&["SCRIPT", "EXISTS"]), arity("script|exists"));
+        assert_eq!(parse_err(&["SCRIPT", "FLUSH", "NOW"]), SYNTAX_ERROR);
+        assert_eq!(parse_err(&["SCRIPT", "DEBUG", "YES"]), "unknown subcommand 'DEBUG'. Try SCRIPT HELP.");
+
+        let Command::EVAL { script, keys, args } = parse_ok(&["EVAL", "return 1", "1", "k", "a", "b"]) else {
+            panic!("not EVAL");
+        };
+        assert_eq!((script.as_str(), keys.len(), args.len()), ("return 1", 1, 2));
+        assert!(matches!(parse_ok(&["EVALSHA", "ABCDEF", "0"]), Command::EVALSHA { sha, .. } if sha == "abcdef"));
+        assert!(matches!(parse_ok(&["FCALL", "f", "0", "a"]), Command::FCALL { function, keys, args } if function == "f" && keys.is_empty() && args.len() == 1));
+        assert!(matches!(parse_ok(&["FCALL_RO", "f", "1", "k"]), Command::FCALLRO { keys, .. } if keys.len() == 1));
+        assert_eq!(parse_err(&["EVAL", "return 1"]), arity("eval"));
+        assert_eq!(parse_err(&["EVAL", "return 1", "-1"]), SCRIPT_NEGATIVE_KEYS_ERROR);
+        assert_eq!(parse_err(&["EVAL", "return 1", "2", "k"]), SCRIPT_TOO_MANY_KEYS_ERROR);
+        assert_eq!(parse_err(&["FCALL", "f", "one"]), NOT_AN_INTEGER_ERROR);
+
+        assert!(matches!(parse_ok(&["FUNCTION", "LOAD", "code"]), Command::FUNCTION(FunctionCommand::LOAD { replace: false, .. })));
+        assert!(matches!(parse_ok(&["FUNCTION", "LOAD", "replace", "code"]), Command::FUNCTION(FunctionCommand::LOAD { replace: true, .. })));
+        assert!(matches!(
+            parse_ok(&["FUNCTION", "LIST", "WITHCODE", "LIBRARYNAME", "lib*"]),
+            Command::FUNCTION(FunctionCommand::LIST { pattern: Some(_), with_code: true })
+        ));
+        assert!(matches!(parse_ok(&["FUNCTION", "DELETE", "lib"]), Command::FUNCTION(FunctionCommand::DELETE(_))));
+        assert!(matches!(parse_ok(&["FUNCTION", "FLUSH"]), Command::FUNCTION(FunctionCommand::FLUSH(FlushMode::SYNC))));
+        assert!(matches!(parse_ok(&["FUNCTION", "KILL"]), Command::FUNCTION(FunctionCommand::KILL)));
+        assert_eq!(parse_err(&["FUNCTION", "LOAD", "KEEP", "code"]), SYNTAX_ERROR);
+        assert_eq!(parse_err(&["FUNCTION", "LIST", "LIBRARYNAME"]), SYNTAX_ERROR);
+        assert_eq!(parse_err(&["FUNCTION", "DELETE"]), arity("function|delete"));
+        assert_eq!(parse_err(&["FUNCTION", "DUMP"]), "unknown subcommand 'DUMP'. Try FUNCTION HELP.");
+    }
+
+    #[test]
+    fn parses_client_and_acl_commands() {
+        assert!(matches!(parse_ok(&["CLIENT", "ID"]), Command::CLIENT(ClientCommand::ID)));
+        assert!(matches!(parse_ok(&["CLIENT", "GETREDIR"]), Command::CLIENT(ClientCommand::GETREDIR)));
+        assert!(matches!(parse_ok(&["CLIENT", "GETNAME"]), Command::CLIENT(ClientCommand::GETNAME)));
+        assert!(matches!(parse_ok(&["CLIENT", "SETNAME", "app"]), Command::CLIENT(ClientCommand::SETNAME(name)) if name == "app"));
+        assert!(matches!(parse_ok(&["CLIENT", "LIST"]), Command::CLIENT(ClientCommand::LIST(None))));
+        assert!(matches!(parse_ok(&["CLIENT", "LIST", "TYPE", "slave"]), Command::CLIENT(ClientCommand::LIST(Some(ClientType::REPLICA)))));
+        assert!(matches!(parse_ok(&["CLIENT", "CACHING", "yes"]), Command::CLIENT(ClientCommand::CACHING(true))));
+        assert_eq!(parse_err(&["CLIENT", "ID", "x"]), arity("client|id"));
+        assert_eq!(parse_err(&["CLIENT", "SETNAME"]), arity("client|setname"));
+        assert_eq!(parse_err(&["CLIENT", "LIST", "TYPE", "robot"]), "Unknown client type 'robot'");
+        assert_eq!(parse_err(&["CLIENT", "LIST", "ID"]), SYNTAX_ERROR);
+        assert_eq!(parse_err(&["CLIENT", "CACHING", "maybe"]), SYNTAX_ERROR);
+        assert_eq!(parse_err(&["CLIENT", "PAUSE", "10"]), "unknown subcommand 'PAUSE'. Try CLIENT HELP.");
+
+        let Command::CLIENT(ClientCommand::KILL { filter, legacy }) = parse_ok(&["CLIENT", "KILL", "127.0.0.1:5000"]) else {
+            panic!("not CLIENT KILL");
+        };
+        assert!(legacy && !filter.skip_me);
+        assert_eq!(filter.addr.as_deref(), Some("127.0.0.1:5000"));
+        let Command::CLIENT(ClientCommand::KILL { filter, legacy }) =
+            parse_ok(&["CLIENT", "KILL", "ID", "3", "TYPE", "pubsub", "USER", "alice", "MAXAGE", "0", "SKIPME", "no"])
+        else {
+            panic!("not CLIENT KILL");
+        };
+        assert!(!legacy && !filter.skip_me);
+        assert_eq!((filter.id, filter.client_type, filter.user.as_deref(), filter.max_age_secs), (Some(3), Some(ClientType::PUBSUB), Some("alice"), None));
+        assert_eq!(parse_err(&["CLIENT", "KILL", "ID", "0"]), CLIENT_ID_ERROR);
+        assert_eq!(parse_err(&["CLIENT", "KILL", "ID", "1", "SKIPME"]), SYNTAX_ERROR);
+        assert_eq!(parse_err(&["CLIENT", "KILL", "NAME", "x"]), SYNTAX_ERROR);
+
+        let Command::CLIENT(ClientCommand::TRACKING(Some(options))) =
+            parse_ok(&["CLIENT", "TRACKING", "on", "REDIRECT", "5", "BCAST", "PREFIX", "a:", "NOLOOP"])
+        else {
+            panic!("not CLIENT TRACKING ON");
+        };
+        assert_eq!((options.redirect, options.bcast, options.prefixes, options.noloop), (Some(5), true, vec!["a:".to_string()], true));
+        assert!(matches!(parse_ok(&["CLIENT", "TRACKING", "OFF"]), Command::CLIENT(ClientCommand::TRACKING(None))));
+        assert_eq!(parse_err(&["CLIENT", "TRACKING"]), SYNTAX_ERROR);
+        assert_eq!(parse_err(&["CLIENT", "TRACKING", "ON", "PREFIX", "a:"]), PREFIX_REQUIRES_BCAST_ERROR);
+        assert_eq!(parse_err(&["CLIENT", "TRACKING", "ON", "OPTIN", "OPTOUT"]), OPTIN_AND_OPTOUT_ERROR);
+        assert_eq!(parse_err(&["CLIENT", "TRACKING", "ON", "BCAST", "OPTIN"]), OPTIN_OPTOUT_WITH_BCAST_ERROR);
+
+        assert!(matches!(
+            parse_ok(&["ACL", "SETUSER", "alice", "on", ">pw"]),
+            Command::ACL(AclCommand::SETUSER { username, rules }) if username == "alice" && rules.len() == 2
+        ));
+        assert!(matches!(parse_ok(&["ACL", "GETUSER", "alice"]), Command::ACL(AclCommand::GETUSER(_))));
+        assert!(matches!(parse_ok(&["ACL", "DELUSER", "a", "b"]), Command::ACL(AclCommand::DELUSER(names)) if names.len() == 2));
+        assert!(matches!(parse_ok(&["ACL", "LIST"]), Command::ACL(AclCommand::LIST)));
+        assert!(matches!(parse_ok(&["ACL", "USERS"]), Command::ACL(AclCommand::USERS)));
+        assert!(matches!(parse_ok(&["ACL", "WHOAMI"]), Command::ACL(AclCommand::WHOAMI)));
+        assert_eq!(parse_err(&["ACL", "GETUSER"]), arity("acl|getuser"));
+        assert_eq!(parse_err(&["ACL", "WHOAMI", "x"]), arity("acl|whoami"));
+        assert_eq!(parse_err(&["ACL", "LOG"]), "unknown subcommand 'LOG'. Try ACL HELP.");
+    }
+
+    #[test]
+    fn parses_cluster_and_sentinel_commands() {
+        let simple: &[(&str, Expected<ClusterCommand>)] = &[
+            ("INFO", |command| matches!(command, ClusterCommand::INFO)),
+            ("MYID", |command| matches!(command, ClusterCommand::MYID)),
+            ("NODES", |command| matches!(command, ClusterCommand::NODES)),
+            ("SLOTS", |command| matches!(command, ClusterCommand::SLOTS)),
+            ("SHARDS", |command| matches!(command, ClusterCommand::SHARDS)),
+        ];
+        for (subcommand, expected) in simple {
+            let Command::CLUSTER(command) = parse_ok(&["CLUSTER", subcommand]) else {
+                panic!("not CLUSTER");
+            };
+            assert!(expected(&command), "{}", subcommand);
+            assert_eq!(parse_err(&["CLUSTER", subcommand, "x"]), arity(&format!("cluster|{}", subcommand.to_lowercase())));
+        }
+        assert!(matches!(parse_ok(&["CLUSTER", "KEYSLOT", "k"]), Command::CLUSTER(ClusterCommand::KEYSLOT(_))));
+        assert!(matches!(parse_ok(&["CLUSTER", "ADDSLOTS", "1", "2"]), Command::CLUSTER(ClusterCommand::ADDSLOTS(slots)) if slots == vec![1, 2]));
+        assert!(matches!(parse_ok(&["CLUSTER", "DELSLOTS", "3"]), Command::CLUSTER(ClusterCommand::DELSLOTS(slots)) if slots == vec![3]));
+        assert!(matches!(parse_ok(&["CLUSTER", "ADDSLOTSRANGE", "1", "3"]), Command::CLUSTER(ClusterCommand::ADDSLOTS(slots)) if slots == vec![1, 2, 3]));
+        assert!(matches!(parse_ok(&["CLUSTER", "DELSLOTSRANGE", "5", "5"]), Command::CLUSTER(ClusterCommand::DELSLOTS(slots)) if slots == vec![5]));
+        assert!(matches!(parse_ok(&["CLUSTER", "COUNTKEYSINSLOT", "7"]), Command::CLUSTER(ClusterCommand::COUNTKEYSINSLOT(7))));
+        assert!(matches!(parse_ok(&["CLUSTER", "GETKEYSINSLOT", "7", "10"]), Command::CLUSTER(ClusterCommand::GETKEYSINSLOT { slot: 7, count: 10 })));
+        assert!(matches!(
+            parse_ok(&["CLUSTER", "SETSLOT", "7", "IMPORTING", "node"]),
+            Command::CLUSTER(ClusterCommand::SETSLOT { slot: 7, state: SlotState::IMPORTING(_) })
+        ));
+        assert!(matches!(parse_ok(&["CLUSTER", "SETSLOT", "7", "STABLE"]), Command::CLUSTER(ClusterCommand::SETSLOT { state: SlotState::STABLE, .. })));
+        assert!(matches!(
+            parse_ok(&["CLUSTER", "MEET", "127.0.0.1", "7000"]),
+            Command::CLUSTER(ClusterCommand::MEET { port: 7000, bus_port: 17000, .. })
+        ));
+        assert!(matches!(parse_ok(&["CLUSTER", "MEET", "127.0.0.1", "7000", "8000"]), Command::CLUSTER(ClusterCommand::MEET { bus_port: 8000, .. })));
+        assert!(matches!(parse_ok(&["CLUSTER", "REPLICATE", "node"]), Command::CLUSTER(ClusterCommand::REPLICATE(_))));
+        assert!(matches!(parse_ok(&["CLUSTER", "SLAVES", "node"]), Command::CLUSTER(ClusterCommand::REPLICAS(_))));
+        assert_eq!(parse_err(&["CLUSTER", "ADDSLOTS", "16384"]), INVALID_SLOT_ERROR);
+        assert_eq!(parse_err(&["CLUSTER", "ADDSLOTSRANGE", "3", "1"]), "start slot number 3 is greater than end slot number 1");
+        assert_eq!(parse_err(&["CLUSTER", "ADDSLOTSRANGE", "1"]), arity("cluster|addslotsrange"));
+        assert_eq!(parse_err(&["CLUSTER", "SETSLOT", "7", "STABLE", "node"]), SYNTAX_ERROR);
+        assert_eq!(parse_err(&["CLUSTER", "GETKEYSINSLOT", "7", "many"]), INVALID_KEY_COUNT_ERROR);
+        assert_eq!(parse_err(&["CLUSTER", "MEET", "127.0.0.1", "port"]), "Invalid base port specified: port");
+        assert_eq!(parse_err(&["CLUSTER", "MEET", "127.0.0.1", "65535"]), "Invalid base port specified: 65535");
+        assert_eq!(parse_err(&["CLUSTER", "FAILOVER"]), "unknown subcommand 'FAILOVER'. Try CLUSTER HELP.");
+
+        assert!(matches!(parse_ok(&["SENTINEL", "MASTERS"]), Command::SENTINEL(SentinelCommand::MASTERS)));
+        assert!(matches!(parse_ok(&["SENTINEL", "MYID"]), Command::SENTINEL(SentinelCommand::MYID)));
+        assert!(matches!(parse_ok(&["SENTINEL", "MASTER", "m"]), Command::SENTINEL(SentinelCommand::MASTER(_))));
+        assert!(matches!(parse_ok(&["SENTINEL", "SLAVES", "m"]), Command::SENTINEL(SentinelCommand::REPLICAS(_))));
+        assert!(matches!(parse_ok(&["SENTINEL", "SENTINELS", "m"]), Command::SENTINEL(SentinelCommand::SENTINELS(_))));
+        assert!(matches!(parse_ok(&["SENTINEL", "GET-MASTER-ADDR-BY-NAME", "m"]), Command::SENTINEL(SentinelCommand::GETMASTERADDRBYNAME(_))));
+        assert!(matches!(parse_ok(&["SENTINEL", "CKQUORUM", "m"]), Command::SENTINEL(SentinelCommand::CKQUORUM(_))));
+        assert!(matches!(parse_ok(&["SENTINEL", "FAILOVER", "m"]), Command::SENTINEL(SentinelCommand::FAILOVER(_))));
+        assert!(matches!(parse_ok(&["SENTINEL", "REMOVE", "m"]), Command::SENTINEL(SentinelCommand::REMOVE(_))));
+        assert!(matches!(
+            parse_ok(&["SENTINEL", "IS-MASTER-DOWN-BY-ADDR", "127.0.0.1", "6379", "2", "*"]),
+            Command::SENTINEL(SentinelCommand::ISMASTERDOWNBYADDR { port: 6379, epoch: 2, .. })
+        ));
+        assert!(matches!(
+            parse_ok(&["SENTINEL", "MONITOR", "m", "127.0.0.1", "6379", "2"]),
+            Command::SENTINEL(SentinelCommand::MONITOR { port: 6379, quorum: 2, .. })
+        ));
+        assert_eq!(parse_err(&["SENTINEL", "MASTER"]), arity("sentinel|master"));
+        assert_eq!(parse_err(&["SENTINEL", "MONITOR", "m", "127.0.0.1", "port", "2"]), NOT_AN_INTEGER_ERROR);
+        assert_eq!(parse_err(&["SENTINEL", "RESET", "m"]), "unknown subcommand 'RESET'. Try SENTINEL HELP.");
+    }
+
+    #[test]
+    fn parses_debug_latency_and_memory_commands() {
+        assert!(matches!(parse_ok(&["DEBUG", "REPORT"]), Command::DEBUG(DebugCommand::REPORT)));
+        assert!(matches!(parse_ok(&["DEBUG", "PROTOCOL", "MAP"]), Command::DEBUG(DebugCommand::PROTOCOL(kind)) if kind == "map"));
+        assert!(matches!(parse_ok(&["DEBUG", "SLEEP", "0.25"]), Command::DEBUG(DebugCommand::SLEEP(250))));
+        assert!(matches!(parse_ok(&["DEBUG", "SET-ACTIVE-EXPIRE", "0"]), Command::DEBUG(DebugCommand::SETACTIVEEXPIRE(false))));
+        assert!(matches!(parse_ok(&["DEBUG", "OBJECT", "k"]), Command::DEBUG(DebugCommand::OBJECT(_))));
+        assert!(matches!(parse_ok(&["DEBUG", "CHANGE-REPL-ID"]), Command::DEBUG(DebugCommand::CHANGEREPLID)));
+        assert!(matches!(parse_ok(&["DEBUG", "RELOAD"]), Command::DEBUG(DebugCommand::RELOAD)));
+        assert_eq!(parse_err(&["DEBUG", "SLEEP"]), arity("debug|sleep"));
+        assert_eq!(parse_err(&["DEBUG", "SLEEP", "-1"]), NOT_A_FLOAT_ERROR);
+        assert_eq!(parse_err(&["DEBUG", "SET-ACTIVE-EXPIRE", "yes"]), NOT_AN_INTEGER_ERROR);
+        assert_eq!(parse_err(&["DEBUG", "SEGFAULT"]), "unknown subcommand 'SEGFAULT'. Try DEBUG HELP.");
+
+        assert!(matches!(parse_ok(&["LATENCY", "LATEST"]), Command::LATENCY(LatencyCommand::LATEST)));
+        assert!(matches!(parse_ok(&["LATENCY", "HISTORY", "COMMAND"]), Command::LATENCY(LatencyCommand::HISTORY(event)) if event == "command"));
+        assert!(matches!(parse_ok(&["LATENCY", "RESET"]), Command::LATENCY(LatencyCommand::RESET(events)) if events.is_empty()));
+        assert!(matches!(parse_ok(&["LATENCY", "DOCTOR"]), Command::LATENCY(LatencyCommand::DOCTOR)));
+        assert_eq!(parse_err(&["LATENCY", "HISTORY"]), arity("latency|history"));
+        assert_eq!(parse_err(&["LATENCY", "GRAPH", "command"]), "unknown subcommand 'GRAPH'. Try LATENCY HELP.");
+
+        assert!(matches!(
+            parse_ok(&["MEMORY", "USAGE", "k"]),
+            Command::MEMORY(MemoryCommand::USAGE { samples: DEFAULT_USAGE_SAMPLES, .. })
+        ));
+        assert!(matches!(parse_ok(&["MEMORY", "USAGE", "k", "SAMPLES", "0"]), Command::MEMORY(MemoryCommand::USAGE { samples: 0, .. })));
+        assert!(matches!(parse_ok(&["MEMORY", "STATS"]), Command::MEMORY(MemoryCommand::STATS)));
+        assert!(matches!(parse_ok(&["MEMORY", "DOCTOR"]), Command::MEMORY(MemoryCommand::DOCTOR)));
+        assert_eq!(parse_err(&["MEMORY", "USAGE"]), arity("memory|usage"));
+        assert_eq!(parse_err(&["MEMORY", "USAGE", "k", "SAMPLES"]), SYNTAX_ERROR);
+        assert_eq!(parse_err(&["MEMORY", "USAGE", "k", "SAMPLES", "x"]), NOT_AN_INTEGER_ERROR);
+        assert_eq!(parse_err(&["MEMORY", "PURGE"]), "unknown subcommand 'PURGE'. Try MEMORY HELP.");
+    }
+
+    #[test]
+    fn parses_replication_and_server_commands() {
+        assert!(matches!(parse_ok(&["REPLCONF", "listening-port", "6380"]), Command::REPLCONF(args) if args == vec!["listening-port", "6380"]));
+        assert!(matches!(parse_ok(&["PSYNC", "?", "-1"]), Command::PSYNC(args) if args == vec!["?", "-1"]));
+        assert_eq!(parse_err(&["REPLCONF", "GETACK"]), arity("replconf"));
+        assert_eq!(parse_err(&["PSYNC", "?"]), arity("psync"));
+
+        assert!(matches!(parse_ok(&["REPLICAOF", "no", "one"]), Command::REPLICAOF(None)));
+        assert!(matches!(parse_ok(&["REPLICAOF", "localhost", "6379"]), Command::REPLICAOF(Some((host, 6379))) if host == "localhost"));
+        assert!(matches!(parse_ok(&["SLAVEOF", "NO", "ONE"]), Command::SLAVEOF(None)));
+        assert_eq!(parse_err(&["REPLICAOF", "localhost", "port"]), REPLICAOF_ARGUMENTS_ERROR);
+        assert_eq!(parse_err(&["SLAVEOF", "localhost"]), arity("slaveof"));
+
+        assert!(matches!(parse_ok(&["WAIT", "-1", "100"]), Command::WAIT { numreplicas: 0, timeout_ms: 100 }));
+        assert_eq!(parse_err(&["WAIT", "1", "-1"]), TIMEOUT_NEGATIVE_ERROR);
+        assert_eq!(parse_err(&["WAIT", "one", "0"]), NOT_AN_INTEGER_ERROR);
+        assert_eq!(parse_err(&["WAIT", "1"]), arity("wait"));
+
+        assert!(matches!(parse_ok(&["SHUTDOWN"]), Command::SHUTDOWN(None)));
+        assert!(matches!(parse_ok(&["SHUTDOWN", "save"]), Command::SHUTDOWN(Some(true))));
+        assert!(matches!(parse_ok(&["SHUTDOWN", "NOSAVE"]), Command::SHUTDOWN(Some(false))));
+        assert_eq!(parse_err(&["SHUTDOWN", "NOW"]), SYNTAX_ERROR);
+        assert_eq!(parse_err(&["SHUTDOWN", "SAVE", "NOW"]), SYNTAX_ERROR);
+    }
+}
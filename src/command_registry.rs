@@ -0,0 +1,361 @@
+use crate::command::{Command, CommandCategory, CommandResponse};
+use crate::command_parser::CommandParser;
+use crate::config_handler::{Config, Db, RuntimeSettings};
+use crate::errors::{ArgumentError, RedisError};
+use crate::event_handler::EventHandler;
+use crate::event_publisher::EventPublisher;
+use crate::protocol_constants::*;
+use crate::replication_config::ReplicationConfig;
+use crate::scripting::FunctionRegistry;
+use crate::trace::TraceContext;
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, OnceLock};
+use tokio::sync::RwLock;
+
+// Redis 명령 테이블의 flags와 같은 의미, category는 이 플래그로 정해짐
+pub const CMD_WRITE: u32 = 1 << 0;
+pub const CMD_READONLY: u32 = 1 << 1;
+pub const CMD_ADMIN: u32 = 1 << 2;
+pub const CMD_PUBSUB: u32 = 1 << 3;
+pub const CMD_SCRIPTING: u32 = 1 << 4;
+// maxmemory를 넘었을 때 OOM으로 거절하는 명령
+pub const CMD_DENYOOM: u32 = 1 << 5;
+// RESP2로 구독 중인 연결에서도 실행할 수 있는 명령
+pub const CMD_SUBSCRIBED: u32 = 1 << 6;
+// sentinel 모드에서도 받는 명령
+pub const CMD_SENTINEL: u32 = 1 << 7;
+// requirepass가 설정되어 있어도 인증 전에 실행할 수 있는 명령
+pub const CMD_NO_AUTH: u32 = 1 << 8;
+// 스크립트의 redis.call로는 부를 수 없는 명령, 대부분 이벤트 핸들러가 연결 상태를 보고 직접 처리하는 명령
+pub const CMD_NOSCRIPT: u32 = 1 << 9;
+
+pub type ParseFn = fn(&[Vec<u8>]) -> Result<Command, ArgumentError>;
+pub type ExecuteFuture<'a> = Pin<Box<dyn Future<Output = Result<Vec<CommandResponse>, RedisError>> + Send + 'a>>;
+pub type ExecuteFn = for<'a> fn(&'a Command, ExecutionContext<'a>) -> ExecuteFuture<'a>;
+pub type EventHandlerFuture<'a> = Pin<Box<dyn Future<Output = ()> + Send + 'a>>;
+pub type EventHandlerFn = for<'a> fn(Command, EventHandlerContext<'a>) -> EventHandlerFuture<'a>;
+
+pub struct CommandSpec {
+    pub name: &'static str,
+    // Redis처럼 명령 이름을 포함한 인자 수, 음수면 그 절댓값 이상
+    pub arity: i32,
+    pub flags: u32,
+}
+
+impl CommandSpec {
+    pub fn has_flag(&self, flag: u32) -> bool {
+        self.flags & flag != 0
+    }
+
+    pub fn accepts_arity(&self, argc: usize) -> bool {
+        let argc = argc as i32;
+        if self.arity < 0 {
+            argc >= -self.arity
+        } else {
+            argc == self.arity
+        }
+    }
+
+    pub fn category(&self) -> CommandCategory {
+        if self.has_flag(CMD_ADMIN) {
+            CommandCategory::Admin
+        } else if self.has_flag(CMD_PUBSUB) {
+            CommandCategory::PubSub
+        } else if self.has_flag(CMD_SCRIPTING) {
+            CommandCategory::Scripting
+        } else if self.has_flag(CMD_WRITE) {
+            CommandCategory::Write
+        } else if self.has_flag(CMD_READONLY) {
+            CommandCategory::Read
+        } else {
+            CommandCategory::Connection
+        }
+    }
+}
+
+// 명령 하나를 실행할 때 필요한 서버 상태
+pub struct ExecutionContext<'a> {
+    pub db: &'a Arc<RwLock<Db>>,
+    pub config: &'a Arc<RwLock<Config>>,
+    pub replication_config: &'a Arc<RwLock<ReplicationConfig>>,
+    pub functions: &'a Arc<RwLock<FunctionRegistry>>,
+    pub client_id: u64,
+    pub publisher: &'a EventPublisher,
+    pub runtime: &'a Arc<RuntimeSettings>,
+    pub trace: Option<TraceContext>,
+}
+
+// 연결 상태, 구독, 블로킹 대기, 스크립트처럼 이벤트 핸들러만 가진 상태가 필요한 명령의 실행 문맥
+// 이런 명령은 응답도 이벤트 핸들러를 통해 직접 씀
+pub struct EventHandlerContext<'a> {
+    pub handler: &'a mut EventHandler,
+    pub client_id: u64,
+    pub trace: Option<TraceContext>,
+}
+
+// Context는 ExecutionContext로 실행해서 응답을 돌려받고, EventHandler는 이벤트 루프 안에서 실행하며 응답을 직접 씀
+#[derive(Clone, Copy)]
+pub enum Executor {
+    Context(ExecuteFn),
+    EventHandler(EventHandlerFn),
+}
+
+// 파서는 parse로 인자를 Command로 바꾸고, 디스패처는 spec의 플래그를 보고 executor로 실행함
+pub trait CommandHandler: Send + Sync {
+    fn spec(&self) -> &CommandSpec;
+
+    fn parse(&self, args: &[Vec<u8>]) -> Result<Command, ArgumentError>;
+
+    fn executor(&self) -> Executor;
+}
+
+// 기본 명령들: 파싱은 CommandParser의 함수를, 실행은 명령마다 하나씩 있는 Command::run_* 또는 EventHandler::run_* 함수를 씀
+struct BuiltinCommand {
+    spec: CommandSpec,
+    parse: ParseFn,
+    executor: Executor,
+}
+
+impl CommandHandler for BuiltinCommand {
+    fn spec(&self) -> &CommandSpec {
+        &self.spec
+    }
+
+    fn parse(&self, args: &[Vec<u8>]) -> Result<Command, ArgumentError> {
+        (self.parse)(args)
+    }
+
+    fn executor(&self) -> Executor {
+        self.executor
+    }
+}
+
+const fn builtin(name: &'static str, arity: i32, flags: u32, parse: ParseFn, executor: Executor) -> BuiltinCommand {
+    BuiltinCommand {
+        spec: CommandSpec { name, arity, flags },
+        parse,
+        executor,
+    }
+}
+
+// async fn인 Command::run_*을 테이블에 넣을 수 있는 함수 포인터로 감쌈
+macro_rules! run {
+    ($run:ident) => {{
+        fn execute<'a>(command: &'a Command, context: ExecutionContext<'a>) -> ExecuteFuture<'a> {
+            Box::pin(command.$run(context))
+        }
+        Executor::Context(execute)
+    }};
+}
+
+// EventHandler::run_*을 같은 방식으로 감쌈
+macro_rules! in_event_handler {
+    ($run:ident) => {{
+        fn execute<'a>(command: Command, context: EventHandlerContext<'a>) -> EventHandlerFuture<'a> {
+            Box::pin(EventHandler::$run(command, context))
+        }
+        Executor::EventHandler(execute)
+    }};
+}
+
+static BUILTIN_COMMANDS: &[BuiltinCommand] = &[
+    builtin(PING_COMMAND, -1, CMD_SUBSCRIBED | CMD_SENTINEL, CommandParser::parse_ping, run!(run_ping)),
+    builtin(ECHO_COMMAND, 2, 0, CommandParser::parse_echo, run!(run_echo)),
+    builtin(HELLO_COMMAND, -1, CMD_SENTINEL | CMD_NO_AUTH | CMD_NOSCRIPT, CommandParser::parse_hello, in_event_handler!(run_hello)),
+    builtin(AUTH_COMMAND, -2, CMD_SENTINEL | CMD_NO_AUTH | CMD_NOSCRIPT, CommandParser::parse_auth, in_event_handler!(run_auth)),
+    builtin(QUIT_COMMAND, 1, CMD_SUBSCRIBED | CMD_SENTINEL | CMD_NO_AUTH | CMD_NOSCRIPT, CommandParser::parse_no_args, in_event_handler!(run_quit)),
+    builtin(RESET_COMMAND, 1, CMD_SUBSCRIBED | CMD_NO_AUTH | CMD_NOSCRIPT, CommandParser::parse_no_args, in_event_handler!(run_reset)),
+    builtin(CLIENT_COMMAND, -2, CMD_SENTINEL | CMD_NOSCRIPT, CommandParser::parse_client, in_event_handler!(run_client)),
+    builtin(ACL_COMMAND, -2, CMD_ADMIN | CMD_SENTINEL | CMD_NOSCRIPT, CommandParser::parse_acl, in_event_handler!(run_acl)),
+    builtin(ASKING_COMMAND, 1, CMD_NOSCRIPT, CommandParser::parse_no_args, in_event_handler!(run_asking_readonly)),
+    builtin(READONLY_COMMAND, 1, CMD_NOSCRIPT, CommandParser::parse_no_args, in_event_handler!(run_asking_readonly)),
+    builtin(READWRITE_COMMAND, 1, CMD_NOSCRIPT, CommandParser::parse_no_args, in_event_handler!(run_asking_readonly)),
+    builtin(MULTI_COMMAND, 1, CMD_NOSCRIPT, CommandParser::parse_no_args, in_event_handler!(run_transaction_control)),
+    builtin(EXEC_COMMAND, 1, CMD_NOSCRIPT, CommandParser::parse_no_args, in_event_handler!(run_transaction_control)),
+    builtin(DISCARD_COMMAND, 1, CMD_NOSCRIPT, CommandParser::parse_no_args, in_event_handler!(run_transaction_control)),
+    builtin(WATCH_COMMAND, -2, CMD_NOSCRIPT, CommandParser::parse_multi_key, in_event_handler!(run_transaction_control)),
+    builtin(UNWATCH_COMMAND, 1, CMD_NOSCRIPT, CommandParser::parse_no_args, in_event_handler!(run_unwatch)),
+    builtin(WAIT_COMMAND, 3, CMD_NOSCRIPT, CommandParser::parse_wait, in_event_handler!(run_wait)),
+    builtin(GET_COMMAND, 2, CMD_READONLY, CommandParser::parse_get, run!(run_get)),
+    builtin(SET_COMMAND, -3, CMD_WRITE | CMD_DENYOOM, CommandParser::parse_set, run!(run_set)),
+    builtin(GETSET_COMMAND, 3, CMD_WRITE | CMD_DENYOOM, CommandParser::parse_getset, run!(run_getset)),
+    builtin(GETEX_COMMAND, -2, CMD_WRITE, CommandParser::parse_getex, run!(run_getex)),
+    builtin(INCR_COMMAND, 2, CMD_WRITE | CMD_DENYOOM, CommandParser::parse_incr, run!(run_incr)),
+    builtin(LCS_COMMAND, -3, CMD_READONLY, CommandParser::parse_lcs, run!(run_lcs)),
+    builtin(TYPE_COMMAND, 2, CMD_READONLY, CommandParser::parse_type, run!(run_type)),
+    builtin(EXPIRE_COMMAND, -3, CMD_WRITE, CommandParser::parse_expire, run!(run_expire)),
+    builtin(PEXPIRE_COMMAND, -3, CMD_WRITE, CommandParser::parse_expire, run!(run_expire)),
+    builtin(EXPIREAT_COMMAND, -3, CMD_WRITE, CommandParser::parse_expire, run!(run_expire)),
+    builtin(PEXPIREAT_COMMAND, -3, CMD_WRITE, CommandParser::parse_expire, run!(run_expire)),
+    builtin(TTL_COMMAND, 2, CMD_READONLY, CommandParser::parse_ttl, run!(run_ttl)),
+    builtin(PTTL_COMMAND, 2, CMD_READONLY, CommandParser::parse_ttl, run!(run_ttl)),
+    builtin(EXPIRETIME_COMMAND, 2, CMD_READONLY, CommandParser::parse_ttl, run!(run_ttl)),
+    builtin(PEXPIRETIME_COMMAND, 2, CMD_READONLY, CommandParser::parse_ttl, run!(run_ttl)),
+    builtin(PERSIST_COMMAND, 2, CMD_WRITE, CommandParser::parse_ttl, run!(run_persist)),
+    builtin(DEL_COMMAND, -2, CMD_WRITE, CommandParser::parse_multi_key, run!(run_del)),
+    builtin(UNLINK_COMMAND, -2, CMD_WRITE, CommandParser::parse_multi_key, run!(run_del)),
+    builtin(EXISTS_COMMAND, -2, CMD_READONLY, CommandParser::parse_multi_key, run!(run_exists)),
+    builtin(TOUCH_COMMAND, -2, CMD_READONLY, CommandParser::parse_multi_key, run!(run_touch)),
+    builtin(KEYS_COMMAND, 2, CMD_READONLY, CommandParser::parse_keys, run!(run_keys)),
+    builtin(SCAN_COMMAND, -2, CMD_READONLY, CommandParser::parse_scan, run!(run_scan)),
+    builtin(RANDOMKEY_COMMAND, 1, CMD_READONLY, CommandParser::parse_no_args, run!(run_randomkey)),
+    builtin(DBSIZE_COMMAND, 1, CMD_READONLY, CommandParser::parse_no_args, run!(run_dbsize)),
+    builtin(OBJECT_COMMAND, -2, CMD_READONLY, CommandParser::parse_object, run!(run_object)),
+    builtin(DUMP_COMMAND, 2, CMD_READONLY, CommandParser::parse_dump, run!(run_dump)),
+    builtin(RESTORE_COMMAND, -4, CMD_WRITE | CMD_DENYOOM, CommandParser::parse_restore, run!(run_restore)),
+    builtin(RESTORE_ASKING_COMMAND, -4, CMD_WRITE | CMD_DENYOOM, CommandParser::parse_restore, run!(run_restore)),
+    builtin(MIGRATE_COMMAND, -6, CMD_WRITE, CommandParser::parse_migrate, run!(run_migrate)),
+    builtin(FLUSHDB_COMMAND, -1, CMD_WRITE, CommandParser::parse_flush, run!(run_flush)),
+    builtin(FLUSHALL_COMMAND, -1, CMD_WRITE, CommandParser::parse_flush, run!(run_flush)),
+    builtin(HSET_COMMAND, -4, CMD_WRITE | CMD_DENYOOM, CommandParser::parse_hset, run!(run_hash_write)),
+    builtin(HGET_COMMAND, 3, CMD_READONLY, CommandParser::parse_hget, run!(run_hget)),
+    builtin(HGETALL_COMMAND, 2, CMD_READONLY, CommandParser::parse_hgetall, run!(run_hgetall)),
+    builtin(HRANDFIELD_COMMAND, -2, CMD_READONLY, CommandParser::parse_hrandfield, run!(run_hrandfield)),
+    builtin(HDEL_COMMAND, -3, CMD_WRITE, CommandParser::parse_hdel, run!(run_hash_write)),
+    builtin(HEXPIRE_COMMAND, -6, CMD_WRITE, CommandParser::parse_hexpire, run!(run_hash_write)),
+    builtin(HPEXPIRE_COMMAND, -6, CMD_WRITE, CommandParser::parse_hexpire, run!(run_hash_write)),
+    builtin(HTTL_COMMAND, -5, CMD_READONLY, CommandParser::parse_hash_fields_command, run!(run_httl)),
+    builtin(HPERSIST_COMMAND, -5, CMD_WRITE, CommandParser::parse_hash_fields_command, run!(run_hash_write)),
+    builtin(LPUSH_COMMAND, -3, CMD_WRITE | CMD_DENYOOM, CommandParser::parse_push, run!(run_list_write)),
+    builtin(RPUSH_COMMAND, -3, CMD_WRITE | CMD_DENYOOM, CommandParser::parse_push, run!(run_list_write)),
+    builtin(LPOP_COMMAND, -2, CMD_WRITE, CommandParser::parse_pop, run!(run_list_write)),
+    builtin(RPOP_COMMAND, -2, CMD_WRITE, CommandParser::parse_pop, run!(run_list_write)),
+    builtin(BLPOP_COMMAND, -3, CMD_WRITE, CommandParser::parse_blocking_pop, in_event_handler!(run_blocking)),
+    builtin(BRPOP_COMMAND, -3, CMD_WRITE, CommandParser::parse_blocking_pop, in_event_handler!(run_blocking)),
+    builtin(LMOVE_COMMAND, 5, CMD_WRITE, CommandParser::parse_lmove, run!(run_lmove)),
+    builtin(BLMOVE_COMMAND, 6, CMD_WRITE, CommandParser::parse_lmove, in_event_handler!(run_blocking)),
+    builtin(BRPOPLPUSH_COMMAND, 4, CMD_WRITE, CommandParser::parse_brpoplpush, in_event_handler!(run_blocking)),
+    builtin(LMPOP_COMMAND, -4, CMD_WRITE, CommandParser::parse_multi_pop, run!(run_multi_pop)),
+    builtin(BLMPOP_COMMAND, -5, CMD_WRITE, CommandParser::parse_multi_pop, in_event_handler!(run_blocking)),
+    builtin(ZADD_COMMAND, -4, CMD_WRITE | CMD_DENYOOM, CommandParser::parse_zadd, run!(run_zadd)),
+    builtin(ZMPOP_COMMAND, -4, CMD_WRITE, CommandParser::parse_multi_pop, run!(run_multi_pop)),
+    builtin(BZMPOP_COMMAND, -5, CMD_WRITE, CommandParser::parse_multi_pop, in_event_handler!(run_blocking)),
+    builtin(ZRANDMEMBER_COMMAND, -2, CMD_READONLY, CommandParser::parse_zrandmember, run!(run_zrandmember)),
+    builtin(ZSCORE_COMMAND, 3, CMD_READONLY, CommandParser::parse_zscore, run!(run_zscore)),
+    builtin(ZINCRBY_COMMAND, 4, CMD_WRITE | CMD_DENYOOM, CommandParser::parse_zincrby, run!(run_zincrby)),
+    builtin(SUBSCRIBE_COMMAND, -2, CMD_PUBSUB | CMD_SUBSCRIBED | CMD_SENTINEL | CMD_NOSCRIPT, CommandParser::parse_subscribe, in_event_handler!(run_subscribe)),
+    builtin(UNSUBSCRIBE_COMMAND, -1, CMD_PUBSUB | CMD_SUBSCRIBED | CMD_SENTINEL | CMD_NOSCRIPT, CommandParser::parse_unsubscribe, in_event_handler!(run_unsubscribe)),
+    builtin(PSUBSCRIBE_COMMAND, -2, CMD_PUBSUB | CMD_SUBSCRIBED | CMD_SENTINEL | CMD_NOSCRIPT, CommandParser::parse_subscribe, in_event_handler!(run_subscribe)),
+    builtin(PUNSUBSCRIBE_COMMAND, -1, CMD_PUBSUB | CMD_SUBSCRIBED | CMD_SENTINEL | CMD_NOSCRIPT, CommandParser::parse_unsubscribe, in_event_handler!(run_unsubscribe)),
+    builtin(SSUBSCRIBE_COMMAND, -2, CMD_PUBSUB | CMD_SUBSCRIBED | CMD_NOSCRIPT, CommandParser::parse_subscribe, in_event_handler!(run_ssubscribe)),
+    builtin(SUNSUBSCRIBE_COMMAND, -1, CMD_PUBSUB | CMD_SUBSCRIBED | CMD_NOSCRIPT, CommandParser::parse_unsubscribe, in_event_handler!(run_sunsubscribe)),
+    builtin(PUBLISH_COMMAND, 3, CMD_PUBSUB | CMD_SENTINEL, CommandParser::parse_publish, in_event_handler!(run_publish)),
+    builtin(SPUBLISH_COMMAND, 3, CMD_PUBSUB, CommandParser::parse_publish, in_event_handler!(run_publish)),
+    builtin(PUBSUB_COMMAND, -2, CMD_PUBSUB | CMD_NOSCRIPT, CommandParser::parse_pubsub, in_event_handler!(run_pubsub)),
+    builtin(SCRIPT_COMMAND, -2, CMD_SCRIPTING | CMD_NOSCRIPT, CommandParser::parse_script, in_event_handler!(run_script)),
+    builtin(FUNCTION_COMMAND, -2, CMD_SCRIPTING | CMD_NOSCRIPT, CommandParser::parse_function, in_event_handler!(run_function)),
+    builtin(EVAL_COMMAND, -3, CMD_SCRIPTING | CMD_NOSCRIPT, CommandParser::parse_eval, in_event_handler!(run_script_call)),
+    builtin(EVALSHA_COMMAND, -3, CMD_SCRIPTING | CMD_NOSCRIPT, CommandParser::parse_eval, in_event_handler!(run_script_call)),
+    builtin(FCALL_COMMAND, -3, CMD_SCRIPTING | CMD_NOSCRIPT, CommandParser::parse_eval, in_event_handler!(run_script_call)),
+    builtin(FCALL_RO_COMMAND, -3, CMD_SCRIPTING | CMD_NOSCRIPT, CommandParser::parse_eval, in_event_handler!(run_script_call)),
+    builtin(CONFIG_COMMAND, -2, CMD_ADMIN | CMD_NOSCRIPT, CommandParser::parse_config, run!(run_config)),
+    builtin(INFO_COMMAND, -1, CMD_ADMIN | CMD_SENTINEL | CMD_NOSCRIPT, CommandParser::parse_info, in_event_handler!(run_info)),
+    builtin(DEBUG_COMMAND, -2, CMD_ADMIN | CMD_NOSCRIPT, CommandParser::parse_debug, in_event_handler!(run_debug)),
+    builtin(LATENCY_COMMAND, -2, CMD_ADMIN | CMD_NOSCRIPT, CommandParser::parse_latency, in_event_handler!(run_latency)),
+    builtin(MEMORY_COMMAND, -2, CMD_READONLY | CMD_NOSCRIPT, CommandParser::parse_memory, in_event_handler!(run_memory)),
+    builtin(CLUSTER_COMMAND, -2, CMD_ADMIN | CMD_NOSCRIPT, CommandParser::parse_cluster, in_event_handler!(run_cluster)),
+    builtin(SENTINEL_COMMAND, -2, CMD_ADMIN | CMD_SENTINEL | CMD_NOSCRIPT, CommandParser::parse_sentinel, in_event_handler!(run_sentinel)),
+    builtin(REPLCONF_COMMAND, -3, CMD_ADMIN | CMD_NOSCRIPT, CommandParser::parse_replconf, run!(run_replconf)),
+    builtin(PSYNC_COMMAND, -3, CMD_ADMIN | CMD_NOSCRIPT, CommandParser::parse_psync, run!(run_psync)),
+    builtin(REPLICAOF_COMMAND, 3, CMD_ADMIN | CMD_NOSCRIPT, CommandParser::parse_replicaof, in_event_handler!(run_replicaof)),
+    builtin(SLAVEOF_COMMAND, 3, CMD_ADMIN | CMD_NOSCRIPT, CommandParser::parse_replicaof, in_event_handler!(run_replicaof)),
+    builtin(BGSAVE_COMMAND, -1, CMD_ADMIN | CMD_NOSCRIPT, CommandParser::parse_no_args, in_event_handler!(run_bgsave)),
+    builtin(LASTSAVE_COMMAND, 1, CMD_ADMIN | CMD_NOSCRIPT, CommandParser::parse_no_args, in_event_handler!(run_lastsave)),
+    builtin(SHUTDOWN_COMMAND, -1, CMD_ADMIN | CMD_SENTINEL | CMD_NOSCRIPT, CommandParser::parse_shutdown, in_event_handler!(run_shutdown)),
+];
+
+// 명령 이름 -> 핸들러, 이름은 Command::name()과 같은 대문자
+pub struct CommandRegistry {
+    handlers: HashMap<&'static str, &'static dyn CommandHandler>,
+}
+
+impl CommandRegistry {
+    fn new() -> Self {
+        let mut registry = Self {
+            handlers: HashMap::new(),
+        };
+        for command in BUILTIN_COMMANDS {
+            registry.register(command);
+        }
+        registry
+    }
+
+    pub fn register(&mut self, handler: &'static dyn CommandHandler) {
+        self.handlers.insert(handler.spec().name, handler);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&'static dyn CommandHandler> {
+        self.handlers.get(name).copied()
+    }
+
+    pub fn specs(&self) -> impl Iterator<Item = &'static CommandSpec> + '_ {
+        self.handlers.values().map(|handler| handler.spec())
+    }
+}
+
+pub fn registry() -> &'static CommandRegistry {
+    static REGISTRY: OnceLock<CommandRegistry> = OnceLock::new();
+    REGISTRY.get_or_init(CommandRegistry::new)
+}
+
+// 명령 이름은 대소문자를 가리지 않으므로 테이블의 대문자 이름으로 맞춰서 찾음
+pub fn lookup(name: &str) -> Option<&'static dyn CommandHandler> {
+    registry().get(&name.to_ascii_uppercase())
+}
+
+// rename-command와 disabled-commands로 바꾼 클라이언트용 이름, 레지스트리와 내부 실행은 원래 이름을 그대로 씀
+// 마스터의 복제 스트림은 원래 이름으로 오므로 클라이언트 요청에만 적용함
+// 서버마다 시작할 때 설정에서 만들어 연결 태스크에 넘기므로 한 프로세스의 서버끼리 섞이지 않음
+#[derive(Debug, Default)]
+pub struct ClientNames {
+    renamed: HashMap<String, &'static str>,
+    hidden: HashSet<&'static str>,
+}
+
+impl ClientNames {
+    // renames는 "원래이름 새이름"을 ';'로 이은 값, 새 이름이 없으면 그 명령을 끔
+    // disabled는 공백으로 구분한 명령 이름
+    pub fn parse(renames: &str, disabled: &str) -> Result<Self, String> {
+        let mut names = Self::default();
+        let mut hide = |name: &str| -> Result<&'static str, String> {
+            let handler = registry().get(&name.to_uppercase()).ok_or_else(|| format!("Unknown command '{}'", name))?;
+            names.hidden.insert(handler.spec().name);
+            Ok(handler.spec().name)
+        };
+        let mut renamed = Vec::new();
+        for rename in renames.split(';').map(str::trim).filter(|rename| !rename.is_empty()) {
+            let (name, new_name) = rename.split_once(' ').map_or((rename, ""), |(name, new_name)| (name, new_name.trim()));
+            let command = hide(name)?;
+            if !new_name.is_empty() {
+                renamed.push((new_name.to_uppercase(), command));
+            }
+        }
+        for name in disabled.split_whitespace() {
+            hide(name)?;
+        }
+        for (new_name, command) in renamed {
+            if registry().get(&new_name).is_some() && !names.hidden.contains(new_name.as_str()) {
+                return Err(format!("Cannot rename {} to existing command '{}'", command, new_name));
+            }
+            names.renamed.insert(new_name, command);
+        }
+        Ok(names)
+    }
+
+    // 클라이언트가 보낸 이름을 레지스트리의 이름으로 바꿈, 바꾸거나 끈 명령의 원래 이름이면 None
+    pub fn command_name<'a>(&self, name: &'a str) -> Option<&'a str> {
+        let upper = name.to_ascii_uppercase();
+        match self.renamed.get(&upper) {
+            Some(command) => Some(command),
+            None if self.hidden.contains(upper.as_str()) => None,
+            None => Some(name),
+        }
+    }
+}
+
+// 파싱된 명령은 항상 테이블에 있는 이름을 가짐
+pub fn handler(name: &str) -> &'static dyn CommandHandler {
+    lookup(name).unwrap_or_else(|| unreachable!("command {} is not registered", name))
+}
@@ -0,0 +1,110 @@
+use crate::command::Command;
+use crate::command_registry::ExecutionContext;
+use crate::config_handler::{Config, Db, RuntimeSettings};
+use crate::event_publisher::EventPublisher;
+use crate::logging;
+use crate::replication_config::ReplicationConfig;
+use crate::scripting::FunctionRegistry;
+use crate::trace::TraceContext;
+use std::collections::VecDeque;
+use std::io;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tokio::task::{JoinError, JoinHandle};
+use tokio::time::{Duration, Instant};
+
+// 읽기 전용 명령을 이벤트 루프 밖의 태스크에서 실행함
+// 키스페이스는 RwLock이므로 읽기끼리는 여러 코어에서 함께 돌고, 쓰기는 db.write()에서 먼저 시작한 읽기가 끝나기를 기다림
+// 응답은 시작한 순서대로 거둬야 같은 연결의 응답 순서가 유지되므로 큐로 관리함
+pub struct ConcurrentReads {
+    db: Arc<RwLock<Db>>,
+    config: Arc<RwLock<Config>>,
+    replication_config: Arc<RwLock<ReplicationConfig>>,
+    functions: Arc<RwLock<FunctionRegistry>>,
+    publisher: EventPublisher,
+    runtime: Arc<RuntimeSettings>,
+    pending: VecDeque<(u64, JoinHandle<CompletedRead>)>,
+    total: u64,
+}
+
+// 태스크가 돌려주는 실행 결과, 응답은 클라이언트의 버퍼에 바로 쓰지 못하므로 따로 모아 옴
+pub struct CompletedRead {
+    pub command: Command,
+    pub addr: SocketAddr,
+    pub reply: Vec<u8>,
+    pub result: io::Result<(usize, bool)>,
+    pub duration: Duration,
+    pub trace: Option<TraceContext>,
+}
+
+impl ConcurrentReads {
+    pub fn new(
+        db: Arc<RwLock<Db>>,
+        config: Arc<RwLock<Config>>,
+        replication_config: Arc<RwLock<ReplicationConfig>>,
+        functions: Arc<RwLock<FunctionRegistry>>,
+        publisher: EventPublisher,
+        runtime: Arc<RuntimeSettings>,
+    ) -> Self {
+        Self {
+            db,
+            config,
+            replication_config,
+            functions,
+            publisher,
+            runtime,
+            pending: VecDeque::new(),
+            total: 0,
+        }
+    }
+
+    pub fn spawn(&mut self, client_id: u64, command: Command, addr: SocketAddr, protocol: u8, trace: Option<TraceContext>) {
+        let db = self.db.clone();
+        let config = self.config.clone();
+        let replication_config = self.replication_config.clone();
+        let functions = self.functions.clone();
+        let publisher = self.publisher.clone();
+        let runtime = self.runtime.clone();
+        let task = logging::spawn(async move {
+            let started_at = Instant::now();
+            let mut reply = Vec::new();
+            let context = ExecutionContext {
+                db: &db,
+                config: &config,
+                replication_config: &replication_config,
+                functions: &functions,
+                client_id,
+                publisher: &publisher,
+                runtime: &runtime,
+                trace,
+            };
+            let result = command.handle_command(&mut reply, context, protocol).await;
+            CompletedRead {
+                command,
+                addr,
+                reply,
+                result,
+                duration: started_at.elapsed(),
+                trace,
+            }
+        });
+        self.pending.push_back((client_id, task));
+        self.total += 1;
+    }
+
+    pub fn has_pending_for(&self, client_id: u64) -> bool {
+        self.pending.iter().any(|(id, _)| *id == client_id)
+    }
+
+    // 지금까지 이벤트 루프 밖에서 실행한 명령 수
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    // 가장 먼저 시작한 명령이 끝나기를 기다림, 태스크가 패닉했으면 Err
+    pub async fn next(&mut self) -> Option<(u64, Result<CompletedRead, JoinError>)> {
+        let (client_id, task) = self.pending.pop_front()?;
+        Some((client_id, task.await))
+    }
+}
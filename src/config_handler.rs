@@ -1,55 +1,382 @@
-use crate::command::Command;
-use crate::command_parser::CommandParser;
+use crate::command_parser::{CommandParser, FrameDecoder};
+use crate::command_registry::ClientNames;
+use crate::errors::ArgumentError;
 use crate::event_publisher::EventPublisher;
+use crate::eviction::{self, EvictionPolicy};
+use crate::keyspace::Keyspace;
+use crate::logging::{self, log_notice, log_verbose, log_warning, LogLevel, Logger};
+use crate::notify;
+use crate::persistence;
 use crate::protocol_constants::*;
+use crate::rate_limit::RateLimits;
 use crate::rdb_parser::RdbParser;
+use crate::replica_output::OutputBufferLimits;
 use crate::replication_config::ReplicationConfig;
-use crate::util::construct_redis_command;
-use crate::value_entry::ValueEntry;
+use crate::scripting::FunctionRegistry;
+use crate::trace::{self, Tracer};
+use crate::util::{connect_tcp, construct_redis_command, format_host_port, glob_match, parse_bytes};
+use crate::value_encoding::{self, ListpackLimits};
+use crate::value_entry::{LfuConfig, DEFAULT_LFU_DECAY_MINUTES, DEFAULT_LFU_LOG_FACTOR};
 use std::collections::HashMap;
-use std::env;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::path::Path;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
-use tokio::net::{TcpStream, tcp::{OwnedReadHalf, OwnedWriteHalf}};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
 use tokio::sync::RwLock;
+use tokio::time::Duration;
 use std::sync::Arc;
 
-pub type Db = HashMap<String, ValueEntry>;
+pub type Db = Keyspace;
 pub type Config = HashMap<String, String>;
 
+// include가 자기 자신을 다시 읽어도 끝나도록 중첩 깊이를 제한함
+const MAX_CONFIG_INCLUDE_DEPTH: usize = 16;
+// Redis처럼 루프백에서만 받고, '-'가 붙은 주소는 바인드하지 못해도 넘어감
+pub const DEFAULT_BIND: &str = "127.0.0.1 -::1";
+const MASTER_RECONNECT_MIN_DELAY: Duration = Duration::from_secs(1);
+const MASTER_RECONNECT_MAX_DELAY: Duration = Duration::from_secs(30);
+// Redis repl-timeout 기본값, 이 시간 동안 마스터에게서 아무것도 오지 않으면 연결이 끊긴 것으로 봄
+const REPL_TIMEOUT: Duration = Duration::from_secs(60);
+
+pub const CONFIG_TYPE_STRING: &str = "string";
+pub const CONFIG_TYPE_INTEGER: &str = "integer";
+pub const CONFIG_TYPE_BOOL: &str = "bool";
+
+pub fn config_value_type(key: &str) -> &'static str {
+    match key {
+        "port"
+        | "replica_of_port"
+        | "admin_port"
+        | "slowlog_log_slower_than"
+        | "latency_monitor_threshold"
+        | "debug_random_seed"
+        | "event_queue_capacity"
+        | "lfu_log_factor"
+        | "maxmemory_samples"
+        | "lfu_decay_time"
+        | "hash_max_listpack_entries"
+        | "hash_max_listpack_value"
+        | "list_max_listpack_size"
+        | "zset_max_listpack_entries"
+        | "zset_max_listpack_value"
+        | "client_max_commands_per_sec"
+        | "client_max_input_bytes_per_sec"
+        | "busy_reply_threshold" => CONFIG_TYPE_INTEGER,
+        "trace" => CONFIG_TYPE_BOOL,
+        _ => CONFIG_TYPE_STRING,
+    }
+}
+
+// CONFIG GET/SET이 보여 주는 설정: Redis 이름, 설정 맵의 키, 설정하지 않았을 때 보여 줄 기본값
+// validate가 없으면 시작할 때만 정할 수 있는 설정이고, 있으면 CONFIG SET으로 저장할 값을 만들어 줌
+// 값을 쓰는 쪽이 매번 설정 맵에서 읽으므로 맵에 넣으면 바로 반영됨
+type ValidateFn = fn(&str) -> Result<String, String>;
+
+pub struct ConfigParameter {
+    pub name: &'static str,
+    pub key: &'static str,
+    pub default: &'static str,
+    validate: Option<ValidateFn>,
+}
+
+impl ConfigParameter {
+    pub fn validate(&self, value: &str) -> Result<String, String> {
+        match self.validate {
+            Some(validate) => validate(value),
+            None => Err("can't set immutable config".into()),
+        }
+    }
+
+    // 검증 함수가 있는 설정만 CONFIG SET으로 바꿀 수 있음
+    pub fn is_mutable(&self) -> bool {
+        self.validate.is_some()
+    }
+
+    pub fn value(&self, config: &Config) -> String {
+        config.get(self.key).map_or(self.default, |value| value.as_str()).to_string()
+    }
+}
+
+const fn parameter(name: &'static str, key: &'static str, default: &'static str, validate: Option<ValidateFn>) -> ConfigParameter {
+    ConfigParameter { name, key, default, validate }
+}
+
+const CONFIG_PARAMETERS: [ConfigParameter; 47] = [
+    parameter("port", "port", "6379", None),
+    parameter("bind", "bind", DEFAULT_BIND, None),
+    parameter("protected-mode", "protected_mode", "yes", Some(validate_yes_no)),
+    parameter("tcp-backlog", "tcp_backlog", "511", None),
+    parameter("tcp-keepalive", "tcp_keepalive", "300", None),
+    parameter("tcp-nodelay", "tcp_nodelay", "yes", None),
+    parameter("repl-disable-tcp-nodelay", "repl_disable_tcp_nodelay", "no", Some(validate_yes_no)),
+    parameter("admin-port", "admin_port", "", None),
+    parameter("firewall", "firewall", "", None),
+    parameter("requirepass", "requirepass", "", Some(validate_string)),
+    parameter("masterauth", "masterauth", "", Some(validate_string)),
+    parameter("dir", "dir", ".", Some(validate_dir)),
+    parameter("dbfilename", "file_name", "dump.rdb", Some(validate_dbfilename)),
+    parameter("save", "save", "3600 1 300 100 60 10000", Some(validate_save)),
+    parameter("rdbcompression", "rdbcompression", "yes", Some(validate_yes_no)),
+    parameter("maxmemory", "maxmemory", "0", Some(validate_memory)),
+    parameter("maxmemory-policy", "maxmemory_policy", "noeviction", Some(validate_maxmemory_policy)),
+    parameter("maxmemory-samples", "maxmemory_samples", "5", Some(validate_positive_integer)),
+    parameter("lfu-log-factor", "lfu_log_factor", "10", Some(validate_integer)),
+    parameter("lfu-decay-time", "lfu_decay_time", "1", Some(validate_integer)),
+    parameter("hash-max-listpack-entries", "hash_max_listpack_entries", "128", Some(validate_integer)),
+    parameter("hash-max-listpack-value", "hash_max_listpack_value", "64", Some(validate_integer)),
+    parameter("list-max-listpack-size", "list_max_listpack_size", "-2", Some(validate_list_max_listpack_size)),
+    parameter("zset-max-listpack-entries", "zset_max_listpack_entries", "128", Some(validate_integer)),
+    parameter("zset-max-listpack-value", "zset_max_listpack_value", "64", Some(validate_integer)),
+    parameter("disabled-commands", "disabled_commands", "", None),
+    parameter("client-max-commands-per-sec", "client_max_commands_per_sec", "0", Some(validate_integer)),
+    parameter("client-max-input-bytes-per-sec", "client_max_input_bytes_per_sec", "0", Some(validate_memory)),
+    parameter("lazyfree-lazy-expire", "lazyfree_lazy_expire", "no", Some(validate_yes_no)),
+    parameter("lazyfree-lazy-eviction", "lazyfree_lazy_eviction", "no", Some(validate_yes_no)),
+    parameter("notify-keyspace-events", "notify_keyspace_events", "", Some(validate_notify_flags)),
+    parameter("slowlog-log-slower-than", "slowlog_log_slower_than", "10000", Some(validate_integer)),
+    parameter("latency-monitor-threshold", "latency_monitor_threshold", "0", Some(validate_integer)),
+    parameter("proto-max-bulk-len", "proto_max_bulk_len", "536870912", None),
+    parameter("repl-ping-replica-period", "repl_ping_replica_period", "10", Some(validate_positive_integer)),
+    parameter("shutdown-timeout", "shutdown_timeout", "10", Some(validate_integer)),
+    parameter("busy-reply-threshold", "busy_reply_threshold", "5000", Some(validate_integer)),
+    parameter("client-output-buffer-limit-replica", "client_output_buffer_limit_replica", "256mb 64mb 60", Some(validate_output_buffer_limit)),
+    parameter("cluster-enabled", "cluster_enabled", "no", None),
+    parameter("cluster-node-timeout", "cluster_node_timeout", "15000", None),
+    parameter("cluster-announce-ip", "cluster_announce_ip", "", None),
+    parameter("event-queue-capacity", "event_queue_capacity", "32", None),
+    parameter("overload-policy", "overload_policy", "block", None),
+    parameter("trace", "trace", "no", Some(validate_yes_no)),
+    parameter("loglevel", "loglevel", "notice", Some(validate_loglevel)),
+    parameter("logfile", "logfile", "", None),
+    parameter("debug-random-seed", "debug_random_seed", "", None),
+];
+
+pub fn config_parameter(name: &str) -> Option<&'static ConfigParameter> {
+    CONFIG_PARAMETERS.iter().find(|parameter| parameter.name.eq_ignore_ascii_case(name))
+}
+
+// 설정 맵의 키(cluster_enabled)로 찾음, 표에 없는 키면 None
+pub fn config_parameter_by_key(key: &str) -> Option<&'static ConfigParameter> {
+    CONFIG_PARAMETERS.iter().find(|parameter| parameter.key == key)
+}
+
+// Redis처럼 대소문자를 구분하지 않는 glob으로 이름을 찾음, 표의 순서대로 돌려줌
+pub fn matching_config_parameters(pattern: &str) -> Vec<&'static ConfigParameter> {
+    let pattern = pattern.to_lowercase();
+    CONFIG_PARAMETERS
+        .iter()
+        .filter(|parameter| glob_match(pattern.as_bytes(), parameter.name.as_bytes()))
+        .collect()
+}
+
+// "127.0.0.1 -::1"처럼 공백으로 나눈 주소 목록 -> (주소, 실패해도 되는지)
+// '*'는 모든 IPv4 주소, '::*'는 모든 IPv6 주소
+pub fn parse_bind_addresses(spec: &str, port: u16) -> Result<Vec<(SocketAddr, bool)>, String> {
+    let mut addresses = Vec::new();
+    for address in spec.split_whitespace() {
+        let (address, optional) = match address.strip_prefix('-') {
+            Some(address) => (address, true),
+            None => (address, false),
+        };
+        let ip = match address {
+            "*" => IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+            "::*" => IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+            _ => address.parse().map_err(|_| format!("Invalid bind address '{}'", address))?,
+        };
+        addresses.push((SocketAddr::new(ip, port), optional));
+    }
+    if addresses.is_empty() {
+        return Err("bind needs at least one address".into());
+    }
+    Ok(addresses)
+}
+
+fn validate_memory(value: &str) -> Result<String, String> {
+    eviction::parse_memory(value).map(|bytes| bytes.to_string())
+}
+
+fn validate_maxmemory_policy(value: &str) -> Result<String, String> {
+    EvictionPolicy::parse(value).map(|_| value.to_lowercase())
+}
+
+fn validate_loglevel(value: &str) -> Result<String, String> {
+    LogLevel::parse(value).map(|_| value.to_lowercase())
+}
+
+fn validate_notify_flags(value: &str) -> Result<String, String> {
+    notify::parse_flags(value).map(|_| value.to_string())
+}
+
+fn validate_save(value: &str) -> Result<String, String> {
+    persistence::parse_save_points(value).map(|_| value.to_string())
+}
+
+fn validate_string(value: &str) -> Result<String, String> {
+    Ok(value.to_string())
+}
+
+fn validate_yes_no(value: &str) -> Result<String, String> {
+    match value.to_lowercase().as_str() {
+        "yes" => Ok("yes".into()),
+        "no" => Ok("no".into()),
+        _ => Err("argument must be 'yes' or 'no'".into()),
+    }
+}
+
+fn validate_integer(value: &str) -> Result<String, String> {
+    value
+        .parse::<u64>()
+        .map(|number| number.to_string())
+        .map_err(|_| "argument couldn't be parsed into an integer".into())
+}
+
+fn validate_positive_integer(value: &str) -> Result<String, String> {
+    match value.parse::<u64>() {
+        Ok(number) if number > 0 => Ok(number.to_string()),
+        _ => Err("argument must be a positive integer".into()),
+    }
+}
+
+// 양수는 원소 수, -1 ~ -5는 4KB ~ 64KB 크기 한도
+fn validate_list_max_listpack_size(value: &str) -> Result<String, String> {
+    match value.parse::<i64>() {
+        Ok(size) if size > 0 || (-5..=-1).contains(&size) => Ok(size.to_string()),
+        _ => Err("argument must be a positive integer or between -5 and -1".into()),
+    }
+}
+
+fn validate_dir(value: &str) -> Result<String, String> {
+    if Path::new(value).is_dir() {
+        Ok(value.to_string())
+    } else {
+        Err(format!("No such directory '{}'", value))
+    }
+}
+
+fn validate_dbfilename(value: &str) -> Result<String, String> {
+    if value.is_empty() || value.contains('/') {
+        return Err("dbfilename can't be a path, just a filename".into());
+    }
+    Ok(value.to_string())
+}
+
+fn validate_output_buffer_limit(value: &str) -> Result<String, String> {
+    OutputBufferLimits::parse(value).map(|_| value.to_string())
+}
+
+// 마스터의 PSYNC 응답, CONTINUE에 복제 ID가 붙어 있으면 마스터가 그 ID로 기록을 이어 가고 있음
+enum PsyncReply {
+    FullResync { replid: String, offset: u64 },
+    Continue { replid: Option<String> },
+}
+
+// 설정 맵을 보지 않고 잠금 없이 읽는 설정, 서버마다 하나씩 두고 시작할 때와 CONFIG SET 뒤에 apply_runtime_config로 고침
+#[derive(Debug, Default)]
+pub struct RuntimeSettings {
+    pub tracer: Tracer,
+    pub rate_limits: Arc<RateLimits>,
+    pub logger: Arc<Logger>,
+}
+
+#[derive(Clone)]
 pub struct ConfigHandler {
-    db: Arc<RwLock<HashMap<String, ValueEntry>>>,
+    db: Arc<RwLock<Db>>,
     config: Arc<RwLock<HashMap<String, String>>>,
     replication_config: Arc<RwLock<ReplicationConfig>>,
+    functions: Arc<RwLock<FunctionRegistry>>,
     publisher: EventPublisher,
+    runtime: Arc<RuntimeSettings>,
 }
 
 impl ConfigHandler {
     pub fn new(
-        db: Arc<RwLock<HashMap<String, ValueEntry>>>,
+        db: Arc<RwLock<Db>>,
         config: Arc<RwLock<HashMap<String, String>>>,
         replication_config: Arc<RwLock<ReplicationConfig>>,
+        functions: Arc<RwLock<FunctionRegistry>>,
         publisher: EventPublisher,
+        runtime: Arc<RuntimeSettings>,
     ) -> Self {
         Self { 
             db, 
             config, 
             replication_config,
+            functions,
             publisher,
+            runtime,
+        }
+    }
+
+    // 설정 맵 대신 RuntimeSettings로 읽는 설정(trace, 로그 수준 등)을 반영함, 시작할 때와 CONFIG SET 뒤에 호출됨
+    // LFU 설정과 listpack 한도는 configure_db와 CONFIG SET에서 그 서버의 키스페이스에 넣음
+    pub fn apply_runtime_config(config: &Config, runtime: &RuntimeSettings) {
+        runtime.tracer.set_enabled(config.get("trace").is_some_and(|value| value == "yes"));
+        runtime.logger.set_level(config.get("loglevel").and_then(|level| LogLevel::parse(level).ok()).unwrap_or(LogLevel::Notice));
+        let integer = |key: &str, default: u64| config.get(key).and_then(|value| value.parse::<u64>().ok()).unwrap_or(default);
+        runtime.rate_limits.set(
+            integer("client_max_commands_per_sec", 0),
+            config
+                .get("client_max_input_bytes_per_sec")
+                .and_then(|value| eviction::parse_memory(value).ok())
+                .unwrap_or(0),
+        );
+    }
+
+    // 설정 에러는 preflight에서 알리므로 여기서는 경고만 남기고 이름을 바꾸지 않음
+    pub fn client_names(config: &Config) -> ClientNames {
+        let renames = config.get("rename_command").map_or("", |renames| renames.as_str());
+        let disabled = config.get("disabled_commands").map_or("", |disabled| disabled.as_str());
+        ClientNames::parse(renames, disabled).unwrap_or_else(|e| {
+            log_warning!("Ignoring rename-command and disabled-commands: {}", e);
+            ClientNames::default()
+        })
+    }
+
+    pub fn listpack_limits(config: &Config) -> ListpackLimits {
+        let limit = |key: &str, default: usize| config.get(key).and_then(|value| value.parse::<usize>().ok()).unwrap_or(default);
+        ListpackLimits {
+            hash_entries: limit("hash_max_listpack_entries", value_encoding::DEFAULT_HASH_MAX_LISTPACK_ENTRIES),
+            hash_value: limit("hash_max_listpack_value", value_encoding::DEFAULT_HASH_MAX_LISTPACK_VALUE),
+            list_size: config
+                .get("list_max_listpack_size")
+                .and_then(|value| value.parse::<i64>().ok())
+                .unwrap_or(value_encoding::DEFAULT_LIST_MAX_LISTPACK_SIZE),
+            zset_entries: limit("zset_max_listpack_entries", value_encoding::DEFAULT_ZSET_MAX_LISTPACK_ENTRIES),
+            zset_value: limit("zset_max_listpack_value", value_encoding::DEFAULT_ZSET_MAX_LISTPACK_VALUE),
         }
     }
 
-    pub async fn load_config(&self) {
-        let args: Vec<String> = env::args().collect();
-        match ConfigHandler::parse_env(args) {
+    pub fn lfu_config(config: &Config) -> LfuConfig {
+        let integer = |key: &str, default: u64| config.get(key).and_then(|value| value.parse::<u64>().ok()).unwrap_or(default);
+        LfuConfig {
+            log_factor: integer("lfu_log_factor", DEFAULT_LFU_LOG_FACTOR),
+            decay_minutes: integer("lfu_decay_time", DEFAULT_LFU_DECAY_MINUTES),
+        }
+    }
+
+    // 이벤트 채널 크기가 설정에 따라 정해지므로 publisher를 만들기 전에 호출됨
+    // args는 명령줄과 같은 형식, 첫 원소는 프로그램 이름
+    pub async fn load_config(config: &Arc<RwLock<HashMap<String, String>>>, runtime: &RuntimeSettings, args: &[String]) -> Result<(), String> {
+        match ConfigHandler::read_config_entries(args.to_vec()) {
             Ok(result) => {
-                let mut config = self.config.write().await;
+                let mut config = config.write().await;
                 for (key, value) in result {
                     config.insert(key, value);
                 }
-                println!("Configuration loaded.");
+                // 이후의 로그가 모두 파일로 가도록 가장 먼저 엶, 열지 못하면 표준 출력에 씀
+                if let Err(e) = runtime.logger.set_file(config.get("logfile").map_or("", |path| path.as_str())) {
+                    log_warning!("{}", e);
+                }
+                if let Some(Err(e)) = config.get("loglevel").map(|level| LogLevel::parse(level)) {
+                    log_warning!("{}, using notice", e);
+                }
+                ConfigHandler::apply_runtime_config(&config, runtime);
+                log_notice!("Configuration loaded.");
+                Ok(())
             }
             Err(e) => {
-                eprintln!("Failed to parse configuration: {}", e);
+                log_warning!("Failed to parse configuration: {}", e);
+                Err(e)
             }
         }
     }
@@ -57,13 +384,26 @@ impl ConfigHandler {
     pub async fn configure_db(&mut self) {
         let dir = self.config.read().await.get("dir").cloned().unwrap_or_default();
         let db_file_name = self.config.read().await.get("file_name").cloned().unwrap_or_default();
+        {
+            let config = self.config.read().await;
+            let mut db = self.db.write().await;
+            db.set_lfu(ConfigHandler::lfu_config(&config));
+            db.set_listpack_limits(ConfigHandler::listpack_limits(&config));
+        }
 
         if !dir.is_empty() && !db_file_name.is_empty() {
             let rdb_file_path = format!("{}/{}", dir, db_file_name);
             let mut db_guard = self.db.write().await;
-            if let Ok(mut parser) = RdbParser::new(&mut *db_guard, &rdb_file_path) {
-                if let Err(e) = parser.parse().await {
-                    eprintln!("Error during RDB parsing: {}", e);
+            if let Ok(mut parser) = RdbParser::new(&mut db_guard, &rdb_file_path) {
+                match parser.parse().await {
+                    Ok(()) => {
+                        if let Some((replid, offset)) = parser.replication() {
+                            log_notice!("Restored replication ID {} and offset {} from RDB", replid, offset);
+                            self.replication_config.read().await.restore_replication(replid, offset).await;
+                        }
+                        self.functions.write().await.restore(parser.functions());
+                    }
+                    Err(e) => log_warning!("Error during RDB parsing: {}", e),
                 }
             }
         }
@@ -74,10 +414,8 @@ impl ConfigHandler {
         let replica_of_port = self.config.read().await.get("replica_of_port").cloned().unwrap_or_default();
 
         if !replica_of_host.is_empty() && !replica_of_port.is_empty() {
-            self.replication_config.write().await.set_replica_of(replica_of_host.clone(), replica_of_port.parse::<u16>().expect("none")).await;
-            if let Err(e) = self.handshake_with_master(replica_of_host.clone(), replica_of_port.clone()).await {
-                eprintln!("configure failure with : {}", e);
-                return;
+            if let Err(e) = self.handshake_with_master(replica_of_host, replica_of_port).await {
+                log_warning!("configure failure with : {}", e);
             }
         }
     }
@@ -88,6 +426,91 @@ impl ConfigHandler {
             .unwrap_or(6379)
     }
 
+    // 첫 인자가 옵션이 아니면 redis.conf 경로로 읽고, 뒤에 오는 명령줄 옵션이 파일의 값을 덮어씀
+    // INFO server의 config_file, 설정 파일 없이 명령줄 옵션만 주었으면 None
+    pub fn config_file_path(args: &[String]) -> Option<String> {
+        let config_file = args.get(1).filter(|arg| !arg.starts_with("--"))?;
+        let path = std::fs::canonicalize(config_file).unwrap_or_else(|_| Path::new(config_file).to_path_buf());
+        Some(path.display().to_string())
+    }
+
+    fn read_config_entries(args: Vec<String>) -> Result<Vec<(String, String)>, String> {
+        let Some(config_file) = args.get(1).filter(|arg| !arg.starts_with("--")) else {
+            return ConfigHandler::parse_env(args);
+        };
+        let mut entries = ConfigHandler::parse_config_file(Path::new(config_file), 0)?;
+        let overrides: Vec<String> = args[..1].iter().chain(&args[2..]).cloned().collect();
+        if overrides.len() > 1 {
+            for (key, value) in ConfigHandler::parse_env(overrides)? {
+                // rename-command는 덮어쓰지 않고 파일과 명령줄의 값을 모두 적용함
+                match entries.iter_mut().find(|(existing, _)| key == "rename_command" && *existing == key) {
+                    Some((_, renames)) => {
+                        renames.push(';');
+                        renames.push_str(&value);
+                    }
+                    None => entries.push((key, value)),
+                }
+            }
+        }
+        Ok(entries)
+    }
+
+    // redis.conf 형식: 한 줄에 지시어 하나, '#'으로 시작하는 줄은 주석, 인자는 인라인 명령처럼 따옴표로 묶을 수 있음
+    // 지시어는 같은 이름의 명령줄 옵션으로 바꿔 parse_env로 검사하고, 인자가 여럿이면 공백으로 이어 한 값으로 만듦
+    // include는 그 자리에 다른 파일을 읽어 넣고, save와 sentinel monitor는 Redis처럼 줄마다 값을 더함(save ""는 끔)
+    fn parse_config_file(path: &Path, depth: usize) -> Result<Vec<(String, String)>, String> {
+        if depth > MAX_CONFIG_INCLUDE_DEPTH {
+            return Err(format!("Too many nested includes while reading {}", path.display()));
+        }
+        let contents = std::fs::read_to_string(path).map_err(|e| format!("Failed to read config file {}: {}", path.display(), e))?;
+
+        let mut entries: Vec<(String, String)> = Vec::new();
+        for (index, line) in contents.lines().enumerate() {
+            let fail = |message: String| format!("{}:{}: {}", path.display(), index + 1, message);
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut args = CommandParser::frame_args(line.as_bytes())
+                .map_err(|ArgumentError::General(message)| fail(message))?
+                .into_iter()
+                .map(|arg| String::from_utf8_lossy(&arg).into_owned());
+            let mut directive = args.next().unwrap_or_default().to_lowercase();
+            let mut values: Vec<String> = args.collect();
+            if directive == "include" {
+                let [included] = &values[..] else {
+                    return Err(fail("include requires exactly one file path".into()));
+                };
+                entries.extend(ConfigHandler::parse_config_file(Path::new(included), depth + 1)?);
+                continue;
+            }
+            // "sentinel monitor ..."처럼 하위 지시어가 있으면 --sentinel-monitor로 읽음
+            if directive == "sentinel" && !values.is_empty() {
+                directive = format!("sentinel-{}", values.remove(0).to_lowercase());
+            }
+
+            let mut directive_args = vec![String::new(), format!("--{}", directive)];
+            if !values.is_empty() {
+                directive_args.push(values.join(" "));
+            }
+            for (key, value) in ConfigHandler::parse_env(directive_args).map_err(fail)? {
+                let previous = entries.iter_mut().rev().find(|(existing, _)| *existing == key);
+                match (key.as_str(), previous) {
+                    ("save", Some((_, save_points))) if !save_points.is_empty() && !value.is_empty() => {
+                        save_points.push(' ');
+                        save_points.push_str(&value);
+                    }
+                    ("sentinel_monitor" | "rename_command", Some((_, values))) => {
+                        values.push(';');
+                        values.push_str(&value);
+                    }
+                    _ => entries.push((key, value)),
+                }
+            }
+        }
+        Ok(entries)
+    }
+
     fn parse_env(args: Vec<String>) -> Result<Vec<(String, String)>, String> {
         if args.len() <= 1 {
             return Err("No configuration arguments provided to parse".into());
@@ -122,22 +545,449 @@ impl ConfigHandler {
                         return Err("Argument Error: --port option requires an argument".into());
                     }
                 }
-                "--replicaof" => {
+                "--firewall" => {
                     if arg_index + 1 < args.len() {
-                        let replica_location = args[arg_index + 1].clone();
-                        let replica_location_split: Vec<&str> = replica_location.split_whitespace().collect();
-
-                        if replica_location_split.len() == 2 {
-                            result.push(("replica_of_host".into(), replica_location_split[0].into()));
-                            result.push(("replica_of_port".into(), replica_location_split[1].into()));
-                            arg_index += 2;
-                        } else {
-                            return Err("Argument Error: --replicaof requires a host and port (e.g., 'localhost 6379')".into());
+                        result.push(("firewall".into(), args[arg_index + 1].clone()));
+                        arg_index += 2;
+                    } else {
+                        return Err("Argument Error: --firewall option requires an argument".into());
+                    }
+                }
+                "--bind" => {
+                    if arg_index + 1 < args.len() {
+                        result.push(("bind".into(), args[arg_index + 1].clone()));
+                        arg_index += 2;
+                    } else {
+                        return Err("Argument Error: --bind option requires an argument".into());
+                    }
+                }
+                "--protected-mode" => {
+                    if arg_index + 1 < args.len() {
+                        result.push(("protected_mode".into(), args[arg_index + 1].clone()));
+                        arg_index += 2;
+                    } else {
+                        return Err("Argument Error: --protected-mode option requires an argument".into());
+                    }
+                }
+                "--tcp-backlog" => {
+                    if arg_index + 1 < args.len() {
+                        result.push(("tcp_backlog".into(), args[arg_index + 1].clone()));
+                        arg_index += 2;
+                    } else {
+                        return Err("Argument Error: --tcp-backlog option requires an argument".into());
+                    }
+                }
+                "--tcp-keepalive" => {
+                    if arg_index + 1 < args.len() {
+                        result.push(("tcp_keepalive".into(), args[arg_index + 1].clone()));
+                        arg_index += 2;
+                    } else {
+                        return Err("Argument Error: --tcp-keepalive option requires an argument".into());
+                    }
+                }
+                "--tcp-nodelay" => {
+                    if arg_index + 1 < args.len() {
+                        result.push(("tcp_nodelay".into(), args[arg_index + 1].clone()));
+                        arg_index += 2;
+                    } else {
+                        return Err("Argument Error: --tcp-nodelay option requires an argument".into());
+                    }
+                }
+                "--shutdown-timeout" => {
+                    if arg_index + 1 < args.len() {
+                        result.push(("shutdown_timeout".into(), args[arg_index + 1].clone()));
+                        arg_index += 2;
+                    } else {
+                        return Err("Argument Error: --shutdown-timeout option requires an argument".into());
+                    }
+                }
+                // lua-time-limit는 Redis 7 이전 이름
+                "--busy-reply-threshold" | "--lua-time-limit" => {
+                    if arg_index + 1 < args.len() {
+                        result.push(("busy_reply_threshold".into(), args[arg_index + 1].clone()));
+                        arg_index += 2;
+                    } else {
+                        return Err(format!("Argument Error: {} option requires an argument", args[arg_index]));
+                    }
+                }
+                "--loglevel" => {
+                    if arg_index + 1 < args.len() {
+                        result.push(("loglevel".into(), args[arg_index + 1].clone()));
+                        arg_index += 2;
+                    } else {
+                        return Err("Argument Error: --loglevel option requires an argument".into());
+                    }
+                }
+                "--logfile" => {
+                    if arg_index + 1 < args.len() {
+                        result.push(("logfile".into(), args[arg_index + 1].clone()));
+                        arg_index += 2;
+                    } else {
+                        return Err("Argument Error: --logfile option requires an argument".into());
+                    }
+                }
+                "--repl-disable-tcp-nodelay" => {
+                    if arg_index + 1 < args.len() {
+                        result.push(("repl_disable_tcp_nodelay".into(), args[arg_index + 1].clone()));
+                        arg_index += 2;
+                    } else {
+                        return Err("Argument Error: --repl-disable-tcp-nodelay option requires an argument".into());
+                    }
+                }
+                "--requirepass" => {
+                    if arg_index + 1 < args.len() {
+                        result.push(("requirepass".into(), args[arg_index + 1].clone()));
+                        arg_index += 2;
+                    } else {
+                        return Err("Argument Error: --requirepass option requires an argument".into());
+                    }
+                }
+                "--masterauth" => {
+                    if arg_index + 1 < args.len() {
+                        result.push(("masterauth".into(), args[arg_index + 1].clone()));
+                        arg_index += 2;
+                    } else {
+                        return Err("Argument Error: --masterauth option requires an argument".into());
+                    }
+                }
+                "--admin-port" => {
+                    if arg_index + 1 < args.len() {
+                        result.push(("admin_port".into(), args[arg_index + 1].clone()));
+                        arg_index += 2;
+                    } else {
+                        return Err("Argument Error: --admin-port option requires an argument".into());
+                    }
+                }
+                "--maxmemory" => {
+                    if arg_index + 1 < args.len() {
+                        result.push(("maxmemory".into(), args[arg_index + 1].clone()));
+                        arg_index += 2;
+                    } else {
+                        return Err("Argument Error: --maxmemory option requires an argument".into());
+                    }
+                }
+                "--lfu-log-factor" => {
+                    if arg_index + 1 < args.len() {
+                        result.push(("lfu_log_factor".into(), args[arg_index + 1].clone()));
+                        arg_index += 2;
+                    } else {
+                        return Err("Argument Error: --lfu-log-factor option requires an argument".into());
+                    }
+                }
+                "--lfu-decay-time" => {
+                    if arg_index + 1 < args.len() {
+                        result.push(("lfu_decay_time".into(), args[arg_index + 1].clone()));
+                        arg_index += 2;
+                    } else {
+                        return Err("Argument Error: --lfu-decay-time option requires an argument".into());
+                    }
+                }
+                "--hash-max-listpack-entries" => {
+                    if arg_index + 1 < args.len() {
+                        result.push(("hash_max_listpack_entries".into(), args[arg_index + 1].clone()));
+                        arg_index += 2;
+                    } else {
+                        return Err("Argument Error: --hash-max-listpack-entries option requires an argument".into());
+                    }
+                }
+                "--hash-max-listpack-value" => {
+                    if arg_index + 1 < args.len() {
+                        result.push(("hash_max_listpack_value".into(), args[arg_index + 1].clone()));
+                        arg_index += 2;
+                    } else {
+                        return Err("Argument Error: --hash-max-listpack-value option requires an argument".into());
+                    }
+                }
+                "--list-max-listpack-size" => {
+                    if arg_index + 1 < args.len() {
+                        result.push(("list_max_listpack_size".into(), args[arg_index + 1].clone()));
+                        arg_index += 2;
+                    } else {
+                        return Err("Argument Error: --list-max-listpack-size option requires an argument".into());
+                    }
+                }
+                "--zset-max-listpack-entries" => {
+                    if arg_index + 1 < args.len() {
+                        result.push(("zset_max_listpack_entries".into(), args[arg_index + 1].clone()));
+                        arg_index += 2;
+                    } else {
+                        return Err("Argument Error: --zset-max-listpack-entries option requires an argument".into());
+                    }
+                }
+                "--zset-max-listpack-value" => {
+                    if arg_index + 1 < args.len() {
+                        result.push(("zset_max_listpack_value".into(), args[arg_index + 1].clone()));
+                        arg_index += 2;
+                    } else {
+                        return Err("Argument Error: --zset-max-listpack-value option requires an argument".into());
+                    }
+                }
+                "--client-max-commands-per-sec" => {
+                    if arg_index + 1 < args.len() {
+                        result.push(("client_max_commands_per_sec".into(), args[arg_index + 1].clone()));
+                        arg_index += 2;
+                    } else {
+                        return Err("Argument Error: --client-max-commands-per-sec option requires an argument".into());
+                    }
+                }
+                "--client-max-input-bytes-per-sec" => {
+                    if arg_index + 1 < args.len() {
+                        result.push(("client_max_input_bytes_per_sec".into(), args[arg_index + 1].clone()));
+                        arg_index += 2;
+                    } else {
+                        return Err("Argument Error: --client-max-input-bytes-per-sec option requires an argument".into());
+                    }
+                }
+                "--notify-keyspace-events" => {
+                    if arg_index + 1 < args.len() {
+                        result.push(("notify_keyspace_events".into(), args[arg_index + 1].clone()));
+                        arg_index += 2;
+                    } else {
+                        return Err("Argument Error: --notify-keyspace-events option requires an argument".into());
+                    }
+                }
+                "--maxmemory-policy" => {
+                    if arg_index + 1 < args.len() {
+                        result.push(("maxmemory_policy".into(), args[arg_index + 1].clone()));
+                        arg_index += 2;
+                    } else {
+                        return Err("Argument Error: --maxmemory-policy option requires an argument".into());
+                    }
+                }
+                "--maxmemory-samples" => {
+                    if arg_index + 1 < args.len() {
+                        result.push(("maxmemory_samples".into(), args[arg_index + 1].clone()));
+                        arg_index += 2;
+                    } else {
+                        return Err("Argument Error: --maxmemory-samples option requires an argument".into());
+                    }
+                }
+                "--rdbcompression" => {
+                    if arg_index + 1 < args.len() {
+                        result.push(("rdbcompression".into(), args[arg_index + 1].clone()));
+                        arg_index += 2;
+                    } else {
+                        return Err("Argument Error: --rdbcompression option requires an argument".into());
+                    }
+                }
+                "--save" => {
+                    if arg_index + 1 < args.len() {
+                        result.push(("save".into(), args[arg_index + 1].clone()));
+                        arg_index += 2;
+                    } else {
+                        return Err("Argument Error: --save option requires an argument".into());
+                    }
+                }
+                "--preflight" => {
+                    result.push(("preflight".into(), "yes".into()));
+                    arg_index += 1;
+                }
+                "--repl-ping-replica-period" => {
+                    if arg_index + 1 < args.len() {
+                        result.push(("repl_ping_replica_period".into(), args[arg_index + 1].clone()));
+                        arg_index += 2;
+                    } else {
+                        return Err("Argument Error: --repl-ping-replica-period option requires an argument".into());
+                    }
+                }
+                "--proto-max-bulk-len" => {
+                    if arg_index + 1 < args.len() {
+                        result.push(("proto_max_bulk_len".into(), args[arg_index + 1].clone()));
+                        arg_index += 2;
+                    } else {
+                        return Err("Argument Error: --proto-max-bulk-len option requires an argument".into());
+                    }
+                }
+                "--client-output-buffer-limit-replica" => {
+                    if arg_index + 1 < args.len() {
+                        result.push(("client_output_buffer_limit_replica".into(), args[arg_index + 1].clone()));
+                        arg_index += 2;
+                    } else {
+                        return Err("Argument Error: --client-output-buffer-limit-replica option requires an argument".into());
+                    }
+                }
+                "--cluster-enabled" => {
+                    if arg_index + 1 < args.len() {
+                        result.push(("cluster_enabled".into(), args[arg_index + 1].clone()));
+                        arg_index += 2;
+                    } else {
+                        return Err("Argument Error: --cluster-enabled option requires an argument".into());
+                    }
+                }
+                "--sentinel" => {
+                    result.push(("sentinel".into(), "yes".into()));
+                    arg_index += 1;
+                }
+                // 여러 마스터를 감시할 수 있도록 반복해서 줄 수 있고, ';'로 이어서 저장함
+                "--sentinel-monitor" => {
+                    if arg_index + 1 < args.len() {
+                        let monitor = args[arg_index + 1].clone();
+                        match result.iter_mut().find(|(key, _)| key == "sentinel_monitor") {
+                            Some((_, monitors)) => {
+                                monitors.push(';');
+                                monitors.push_str(&monitor);
+                            }
+                            None => result.push(("sentinel_monitor".into(), monitor)),
                         }
+                        arg_index += 2;
+                    } else {
+                        return Err("Argument Error: --sentinel-monitor option requires an argument".into());
+                    }
+                }
+                // "원래이름 새이름", 새 이름이 없으면 끔, 여러 번 주면 모두 적용함
+                "--rename-command" => {
+                    if arg_index + 1 < args.len() {
+                        let rename = args[arg_index + 1].clone();
+                        match result.iter_mut().find(|(key, _)| key == "rename_command") {
+                            Some((_, renames)) => {
+                                renames.push(';');
+                                renames.push_str(&rename);
+                            }
+                            None => result.push(("rename_command".into(), rename)),
+                        }
+                        arg_index += 2;
+                    } else {
+                        return Err("Argument Error: --rename-command option requires an argument".into());
+                    }
+                }
+                "--disabled-commands" => {
+                    if arg_index + 1 < args.len() {
+                        result.push(("disabled_commands".into(), args[arg_index + 1].clone()));
+                        arg_index += 2;
+                    } else {
+                        return Err("Argument Error: --disabled-commands option requires an argument".into());
+                    }
+                }
+                "--sentinel-down-after-milliseconds" => {
+                    if arg_index + 1 < args.len() {
+                        result.push(("sentinel_down_after_milliseconds".into(), args[arg_index + 1].clone()));
+                        arg_index += 2;
+                    } else {
+                        return Err("Argument Error: --sentinel-down-after-milliseconds option requires an argument".into());
+                    }
+                }
+                "--sentinel-failover-timeout" => {
+                    if arg_index + 1 < args.len() {
+                        result.push(("sentinel_failover_timeout".into(), args[arg_index + 1].clone()));
+                        arg_index += 2;
+                    } else {
+                        return Err("Argument Error: --sentinel-failover-timeout option requires an argument".into());
+                    }
+                }
+                "--sentinel-announce-ip" => {
+                    if arg_index + 1 < args.len() {
+                        result.push(("sentinel_announce_ip".into(), args[arg_index + 1].clone()));
+                        arg_index += 2;
+                    } else {
+                        return Err("Argument Error: --sentinel-announce-ip option requires an argument".into());
+                    }
+                }
+                "--cluster-node-timeout" => {
+                    if arg_index + 1 < args.len() {
+                        result.push(("cluster_node_timeout".into(), args[arg_index + 1].clone()));
+                        arg_index += 2;
+                    } else {
+                        return Err("Argument Error: --cluster-node-timeout option requires an argument".into());
+                    }
+                }
+                "--cluster-announce-ip" => {
+                    if arg_index + 1 < args.len() {
+                        result.push(("cluster_announce_ip".into(), args[arg_index + 1].clone()));
+                        arg_index += 2;
+                    } else {
+                        return Err("Argument Error: --cluster-announce-ip option requires an argument".into());
+                    }
+                }
+                "--latency-monitor-threshold" => {
+                    if arg_index + 1 < args.len() {
+                        result.push(("latency_monitor_threshold".into(), args[arg_index + 1].clone()));
+                        arg_index += 2;
+                    } else {
+                        return Err("Argument Error: --latency-monitor-threshold option requires an argument".into());
+                    }
+                }
+                "--slowlog-log-slower-than" => {
+                    if arg_index + 1 < args.len() {
+                        result.push(("slowlog_log_slower_than".into(), args[arg_index + 1].clone()));
+                        arg_index += 2;
                     } else {
-                        return Err("Argument Error: --replicaof requires a host and port (e.g., 'localhost 6379')".into());
+                        return Err("Argument Error: --slowlog-log-slower-than option requires an argument".into());
                     }
                 }
+                "--debug-random-seed" => {
+                    if arg_index + 1 < args.len() {
+                        result.push(("debug_random_seed".into(), args[arg_index + 1].clone()));
+                        arg_index += 2;
+                    } else {
+                        return Err("Argument Error: --debug-random-seed option requires an argument".into());
+                    }
+                }
+                "--event-queue-capacity" => {
+                    if arg_index + 1 < args.len() {
+                        result.push(("event_queue_capacity".into(), args[arg_index + 1].clone()));
+                        arg_index += 2;
+                    } else {
+                        return Err("Argument Error: --event-queue-capacity option requires an argument".into());
+                    }
+                }
+                "--overload-policy" => {
+                    if arg_index + 1 < args.len() {
+                        result.push(("overload_policy".into(), args[arg_index + 1].clone()));
+                        arg_index += 2;
+                    } else {
+                        return Err("Argument Error: --overload-policy option requires an argument".into());
+                    }
+                }
+                "--lazyfree-lazy-expire" => {
+                    if arg_index + 1 < args.len() {
+                        result.push(("lazyfree_lazy_expire".into(), args[arg_index + 1].clone()));
+                        arg_index += 2;
+                    } else {
+                        return Err("Argument Error: --lazyfree-lazy-expire option requires an argument".into());
+                    }
+                }
+                "--lazyfree-lazy-eviction" => {
+                    if arg_index + 1 < args.len() {
+                        result.push(("lazyfree_lazy_eviction".into(), args[arg_index + 1].clone()));
+                        arg_index += 2;
+                    } else {
+                        return Err("Argument Error: --lazyfree-lazy-eviction option requires an argument".into());
+                    }
+                }
+                "--trace" => {
+                    if arg_index + 1 < args.len() {
+                        result.push(("trace".into(), args[arg_index + 1].clone()));
+                        arg_index += 2;
+                    } else {
+                        return Err("Argument Error: --trace option requires an argument".into());
+                    }
+                }
+                // "host port" 한 인자로 주거나 redis-server처럼 host와 port를 따로 줄 수 있음
+                // host는 이름이어도 되고 마스터에 연결할 때마다 다시 찾음, IPv6 주소는 [::1]처럼 괄호로 감싸도 됨
+                "--replicaof" => {
+                    let usage = "Argument Error: --replicaof requires a host and port (e.g., 'localhost 6379' or localhost 6379)";
+                    let location: Vec<&str> = args.get(arg_index + 1).map_or(Vec::new(), |location| location.split_whitespace().collect());
+                    let (host, port) = match location[..] {
+                        [host, port] => {
+                            arg_index += 2;
+                            (host, port)
+                        }
+                        [host] => match args.get(arg_index + 2).filter(|port| !port.starts_with("--")) {
+                            Some(port) => {
+                                arg_index += 3;
+                                (host, port.as_str())
+                            }
+                            None => return Err(usage.into()),
+                        },
+                        _ => return Err(usage.into()),
+                    };
+                    if port.parse::<u16>().is_err() {
+                        return Err(format!("Argument Error: --replicaof has an invalid port '{}'", port));
+                    }
+                    let host = host.strip_prefix('[').and_then(|host| host.strip_suffix(']')).unwrap_or(host);
+                    result.push(("replica_of_host".into(), host.into()));
+                    result.push(("replica_of_port".into(), port.into()));
+                }
                 _ => return Err(format!("Argument Error: '{}' is an unknown option", args[arg_index])),
             }
         }
@@ -145,186 +995,284 @@ impl ConfigHandler {
         Ok(result)
     }
 
+    // 마스터 링크는 연결이 끊기면 다시 연결하는 태스크로 돌며, REPLICAOF NO ONE이나 다른 마스터로 바꿀 때 abort됨
     pub async fn handshake_with_master(&self, master_host: String, master_port: String) -> Result<(), String> {
-        let master_address = format!("{}:{}", master_host, master_port);
+        let master_port = master_port.parse::<u16>().map_err(|e| format!("Invalid master port: {}", e))?;
+        let replication_config = self.replication_config.read().await.clone();
+        replication_config.set_replica_of(master_host.clone(), master_port).await;
+
+        let handler = self.clone();
+        let master_link = logging::spawn(async move { handler.run_master_link(master_host, master_port).await });
+        replication_config.set_master_link(master_link).await;
+        Ok(())
+    }
+
+    // 핸드셰이크가 실패하거나 연결이 끊기면 1초부터 두 배씩 늘려 최대 30초까지 기다렸다가 다시 연결함
+    async fn run_master_link(self, master_host: String, master_port: u16) {
+        let replication_config = self.replication_config.read().await.clone();
+        // 마지막 FULLRESYNC의 replid, 다시 연결할 때 PSYNC로 이어받기를 요청함
+        // 처음에는 RDB에서 읽은 복제 기록이 있으면 그것으로 요청함
+        let mut master_replid = replication_config.take_cached_master().await;
+        let mut delay = MASTER_RECONNECT_MIN_DELAY;
+        loop {
+            match self.sync_with_master(&master_host, master_port, &mut master_replid).await {
+                Ok((read_stream, write_stream, pending)) => {
+                    delay = MASTER_RECONNECT_MIN_DELAY;
+                    replication_config.set_master_link_up(true).await;
+                    let reason = self.stream_from_master(read_stream, write_stream, pending).await;
+                    log_warning!("Lost connection to master: {}", reason);
+                }
+                Err(e) => log_warning!("Failed to sync with master: {}", e),
+            }
+            replication_config.set_master_link_up(false).await;
+            log_notice!("Reconnecting to master in {} seconds", delay.as_secs());
+            tokio::time::sleep(delay).await;
+            delay = (delay * 2).min(MASTER_RECONNECT_MAX_DELAY);
+        }
+    }
+
+    // 핸드셰이크와 PSYNC를 마친 연결, 그리고 그 뒤에 이미 읽어 둔 복제 스트림 바이트
+    async fn sync_with_master(
+        &self,
+        master_host: &str,
+        master_port: u16,
+        master_replid: &mut Option<String>,
+    ) -> Result<(OwnedReadHalf, OwnedWriteHalf, Vec<u8>), String> {
+        let master_address = format_host_port(master_host, master_port);
         let port = self.get_port().await;
 
-        let stream = TcpStream::connect(&master_address).await.map_err(|e| format!("Failed to connect to master: {}", e))?;
+        let (keepalive, nodelay) = {
+            let config = self.config.read().await;
+            let keepalive = config.get("tcp_keepalive").and_then(|seconds| seconds.parse::<u64>().ok()).unwrap_or(300) > 0;
+            let nodelay = config.get("repl_disable_tcp_nodelay").map_or(true, |disabled| disabled != "yes");
+            (keepalive, nodelay)
+        };
+        let stream = tokio::time::timeout(REPL_TIMEOUT, connect_tcp(&master_address, keepalive))
+            .await
+            .map_err(|_| "Timed out connecting to master".to_string())?
+            .map_err(|e| format!("Failed to connect to master: {}", e))?;
+        stream.set_nodelay(nodelay).map_err(|e| format!("Failed to set TCP_NODELAY on master link: {}", e))?;
         let (mut read_stream, mut write_stream) = stream.into_split();
 
-        self.send_command_with_writer(&mut write_stream, &[PING_COMMAND]).await?;
-        self.expect_pong_response(&mut read_stream).await?;
+        // 마스터가 응답과 RDB, 이후 명령을 한 번에 보낼 수 있으므로 읽고 남은 바이트는 다음 단계로 넘김
+        let mut pending = Vec::new();
 
-        self.send_command_with_writer(&mut write_stream, &[REPLCONF_COMMAND, "listening-port", &port.to_string()]).await?;
-        self.expect_ok_response(&mut read_stream).await?;
+        self.send_command_with_writer(&mut write_stream, &[PING_COMMAND]).await?;
+        self.expect_pong_response(&mut read_stream, &mut pending).await?;
 
-        self.send_command_with_writer(&mut write_stream, &[REPLCONF_COMMAND, "capa", "psync2"]).await?;
-        self.expect_ok_response(&mut read_stream).await?;
+        let masterauth = self.config.read().await.get("masterauth").filter(|password| !password.is_empty()).cloned();
+        if let Some(masterauth) = masterauth {
+            self.send_command_with_writer(&mut write_stream, &[AUTH_COMMAND, &masterauth]).await?;
+            self.expect_ok_response(&mut read_stream, &mut pending).await?;
+        }
 
-        self.send_command_with_writer(&mut write_stream, &[PSYNC_COMMAND, "?", "-1"]).await?;
-        self.expect_fullresync_response(&mut read_stream).await?;
+        self.send_command_with_writer(&mut write_stream, &[REPLCONF_COMMAND, REPLCONF_LISTENING_PORT, &port.to_string()]).await?;
+        self.expect_ok_response(&mut read_stream, &mut pending).await?;
 
-        let mut buffer = [0u8; 1024];
-        let mut total_bytes = 0;
-        let mut rdb_size = 0;
-        let mut reading_size = true;
-        let mut size_str = String::new();
+        self.send_command_with_writer(&mut write_stream, &[REPLCONF_COMMAND, REPLCONF_CAPA, "psync2"]).await?;
+        self.expect_ok_response(&mut read_stream, &mut pending).await?;
 
-        loop {
-            let bytes_read = read_stream.read(&mut buffer).await
-                .map_err(|e| format!("Failed to read RDB data: {}", e))?;
-            
-            if bytes_read == 0 {
-                return Err("Unexpected EOF while reading RDB data".to_string());
+        // 전에 동기화한 적이 있으면 다음 offset부터 이어받기를 요청하고, 마스터가 거절하면 FULLRESYNC로 받음
+        let replication_config = self.replication_config.read().await.clone();
+        let (psync_replid, psync_offset) = match master_replid.as_ref() {
+            Some(replid) => (replid.clone(), (replication_config.get_repl_offset().await + 1).to_string()),
+            None => ("?".to_string(), "-1".to_string()),
+        };
+        self.send_command_with_writer(&mut write_stream, &[PSYNC_COMMAND, &psync_replid, &psync_offset]).await?;
+        match self.expect_psync_response(&mut read_stream, &mut pending).await? {
+            PsyncReply::FullResync { replid, offset } => {
+                self.load_rdb_from_master(&mut read_stream, &mut pending).await?;
+                // 복제 offset은 FULLRESYNC 응답의 offset부터 마스터에게서 받아 처리한 바이트 수만큼 늘어나며 GETACK에 대한 응답으로 보고함
+                replication_config.set_repl_offset(offset).await;
+                replication_config.set_replid(replid.clone()).await;
+                *master_replid = Some(replid);
+                // 하위 레플리카가 가진 데이터는 이전 스냅샷 기준이므로 끊어서 다시 동기화하게 함
+                self.publisher.publish_master_resynced().await?;
             }
-
-            if reading_size {
-                for i in 0..bytes_read {
-                    let byte = buffer[i];
-                    if byte == b'\r' {
-                        if i + 1 < bytes_read && buffer[i + 1] == b'\n' {
-                            rdb_size = size_str.parse()
-                                .map_err(|e| format!("Failed to parse RDB size: {}", e))?;
-                            total_bytes = bytes_read - (i + 2);
-                            if total_bytes > 0 {
-                                // 남은 데이터가 있다면 처리
-                                println!("Reading RDB file of size: {}", rdb_size);
-                                if total_bytes >= rdb_size {
-                                    println!("Read {} bytes of RDB data", rdb_size);
-                                    break;
-                                }
-                            }
-                            reading_size = false;
-                            break;
-                        }
-                    } else if byte != b'$' {
-                        size_str.push(byte as char);
-                    }
-                }
-                if reading_size {
-                    continue;
-                }
-            } else {
-                total_bytes += bytes_read;
-                if total_bytes >= rdb_size {
-                    println!("Read {} bytes of RDB data", rdb_size);
-                    break;
+            PsyncReply::Continue { replid } => {
+                log_notice!("Resuming replication at offset {}", psync_offset);
+                // 승격된 레플리카로 옮겨 온 경우, 지금 ID는 두 번째 복제 ID로 남겨 하위 레플리카도 이어받을 수 있게 함
+                if let Some(replid) = replid.filter(|replid| master_replid.as_ref() != Some(replid)) {
+                    log_notice!("Master replication ID changed to {}", replid);
+                    replication_config.shift_replid(replid.clone()).await;
+                    *master_replid = Some(replid);
                 }
             }
         }
+        replication_config.record_master_io().await;
+        Ok((read_stream, write_stream, pending))
+    }
+
+    // RDB 뒤에 이어 온 복제 스트림 바이트는 pending에 남겨 두고, 로드를 마친 뒤 stream_from_master가 먼저 처리함
+    async fn load_rdb_from_master(&self, read_stream: &mut OwnedReadHalf, pending: &mut Vec<u8>) -> Result<(), String> {
+        // 마스터는 RDB를 만드는 동안 연결을 유지하려고 빈 줄을 보낼 수 있음
+        let mut size_line = String::new();
+        while size_line.is_empty() {
+            size_line = Self::read_line(read_stream, pending).await?.trim().to_string();
+        }
+        let rdb_size: usize = size_line
+            .strip_prefix(BULK_STRING_PREFIX)
+            .unwrap_or(&size_line)
+            .parse()
+            .map_err(|e| format!("Failed to parse RDB size: {}", e))?;
+        log_verbose!("Reading RDB file of size: {}", rdb_size);
+        while pending.len() < rdb_size {
+            Self::fill_buffer(read_stream, pending).await?;
+        }
+        let rdb: Vec<u8> = pending.drain(..rdb_size).collect();
+        log_verbose!("Read {} bytes of RDB data", rdb_size);
+
+        // 전체 동기화이므로 기존 데이터를 버리고 마스터의 스냅샷으로 바꿈
+        let mut db_guard = self.db.write().await;
+        db_guard.clear();
+        let mut parser = RdbParser::from_bytes(&mut db_guard, rdb);
+        parser.parse().await.map_err(|e| format!("Failed to load RDB from master: {}", e))?;
+        // 함수 라이브러리도 마스터의 것으로 바꿈
+        self.functions.write().await.restore(parser.functions());
+        Ok(())
+    }
 
-        self.replication_config.write().await.set_replica_of(master_host.clone(), master_port.parse::<u16>().expect("none")).await;
+    // 연결이 끊길 때까지 마스터가 보내는 명령을 실행하고, 끊긴 이유를 돌려줌
+    async fn stream_from_master(&self, mut read_stream: OwnedReadHalf, mut write_stream: OwnedWriteHalf, pending: Vec<u8>) -> String {
+        let replication_config = self.replication_config.read().await.clone();
+        let mut decoder = FrameDecoder::new();
+        decoder.feed(&pending);
+        let mut temp_buffer = [0u8; 1024];
+        // 마스터가 SELECT로 고른 DB, 서버에는 DB 0만 있으므로 다른 DB를 향한 명령은 적용하지 않음
+        let mut selected_db = 0;
 
-        let publisher = self.publisher.clone();
-        tokio::spawn(async move {
-            let mut buffer = Vec::new();
-            let mut temp_buffer = [0u8; 1024];
-            
+        loop {
             loop {
-                match read_stream.read(&mut temp_buffer).await {
-                    Ok(n) if n > 0 => {
-                        buffer.extend_from_slice(&temp_buffer[..n]);
-                        
-                        let mut pos = 0;
-                        while pos < buffer.len() {
-                            if buffer[pos] == b'*' {
-                                let mut array_end = pos;
-                                let mut elements = 0;
-                                let mut expected_elements = 0;
-                                let mut is_complete = false;
-
-                                if let Some(size_end) = buffer[pos + 1..].iter().position(|&b| b == b'\r') {
-                                    if let Ok(size) = String::from_utf8_lossy(&buffer[pos + 1..pos + 1 + size_end]).parse::<usize>() {
-                                        expected_elements = size;
-                                        array_end = pos + 1 + size_end + 2;
-
-                                        while elements < expected_elements && array_end < buffer.len() {
-                                            if buffer[array_end] != b'$' {
-                                                break;
-                                            }
-
-                                            if let Some(len_end) = buffer[array_end + 1..].iter().position(|&b| b == b'\r') {
-                                                if let Ok(len) = String::from_utf8_lossy(&buffer[array_end + 1..array_end + 1 + len_end]).parse::<usize>() {
-                                                    array_end = array_end + 1 + len_end + 2 + len + 2;
-                                                    elements += 1;
-
-                                                    if elements == expected_elements && array_end <= buffer.len() {
-                                                        is_complete = true;
-                                                        break;
-                                                    }
-                                                }
-                                            }
-                                        }
-                                    }
-                                }
-
-                                if is_complete {
-                                    let command_data = buffer[pos..array_end].to_vec();
-                                    if let Ok(command) = String::from_utf8(command_data) {
-                                        if let Ok(parsed_command) = CommandParser::parse_message(&command) {
-                                            if let Err(e) = publisher.publish_command(0, parsed_command).await {
-                                                eprintln!("Failed to publish command from master: {}", e);
-                                            }
-                                        }
-                                    }
-                                    pos = array_end;
-                                } else {
-                                    break;
-                                }
-                            } else {
-                                pos += 1;
-                            }
-                        }
+                let raw = match decoder.next_frame() {
+                    Ok(Some(raw)) => raw,
+                    Ok(None) => break,
+                    Err(e) => return format!("protocol error in replication stream: {}", e),
+                };
+                self.handle_master_frame(raw, &mut selected_db, &mut write_stream).await;
+            }
 
-                        if pos > 0 {
-                            buffer = buffer[pos..].to_vec();
-                        }
-                    }
-                    Ok(0) | Err(_) => break,
-                    _ => continue,
+            match tokio::time::timeout(REPL_TIMEOUT, read_stream.read(&mut temp_buffer)).await {
+                Ok(Ok(n)) if n > 0 => {
+                    decoder.feed(&temp_buffer[..n]);
+                    replication_config.record_master_io().await;
                 }
+                Ok(Ok(_)) => return "connection closed".to_string(),
+                Ok(Err(e)) => return e.to_string(),
+                Err(_) => return format!("no data for {} seconds", REPL_TIMEOUT.as_secs()),
             }
-        });
+        }
+    }
 
+    // 빈 줄, PING, SELECT까지 모든 바이트가 복제 스트림의 일부이므로 offset에 세고 하위 레플리카에게도 그대로 보냄
+    async fn handle_master_frame(&self, raw: Vec<u8>, selected_db: &mut u64, write_stream: &mut OwnedWriteHalf) {
+        let replication_config = self.replication_config.read().await.clone();
+        let args = CommandParser::frame_args(&raw);
+        let mut command = None;
+        let mut trace = None;
+        match args {
+            Ok(args) if args.is_empty() => {}
+            Ok(args) if args[0].eq_ignore_ascii_case(SELECT_COMMAND.as_bytes()) => match args.get(1).and_then(|db| parse_bytes(db)) {
+                Some(db) => *selected_db = db,
+                None => log_warning!("Invalid SELECT from master: {:?}", String::from_utf8_lossy(&raw)),
+            },
+            Ok(args)
+                if args[0].eq_ignore_ascii_case(REPLCONF_COMMAND.as_bytes())
+                    && args.get(1).is_some_and(|arg| arg.eq_ignore_ascii_case(REPLCONF_GETACK.as_bytes())) =>
+            {
+                // GETACK 자신은 응답한 뒤에 offset에 더함
+                let offset = replication_config.get_repl_offset().await.to_string();
+                let ack = construct_redis_command(&[REPLCONF_COMMAND, REPLCONF_ACK, &offset]);
+                if let Err(e) = write_stream.write_all(&ack).await {
+                    log_warning!("Failed to send ACK to master: {}", e);
+                }
+            }
+            // 발행은 DB와 상관없으므로 다른 DB를 고른 뒤에 온 것도 전달함
+            Ok(args)
+                if *selected_db != 0
+                    && ![PUBLISH_COMMAND, SPUBLISH_COMMAND].iter().any(|name| args[0].eq_ignore_ascii_case(name.as_bytes())) => {}
+            Ok(args) => match CommandParser::parse_args(&args) {
+                Ok(parsed_command) => {
+                    trace = self.runtime.tracer.start();
+                    trace::record(trace, "parse", &format!("client=master command={}", parsed_command.name()));
+                    command = Some(parsed_command);
+                }
+                Err(e) => log_warning!("Failed to parse command from master: {}", e),
+            },
+            Err(e) => log_warning!("Failed to parse command from master: {}", e),
+        }
+        replication_config.advance_repl_offset(raw.len()).await;
+        if let Err(e) = self.publisher.publish_master_stream(command, raw, trace).await {
+            log_warning!("Failed to publish command from master: {}", e);
+        }
+    }
+
+    async fn fill_buffer(stream: &mut OwnedReadHalf, pending: &mut Vec<u8>) -> Result<(), String> {
+        let mut buffer = [0u8; 1024];
+        let bytes_read = tokio::time::timeout(REPL_TIMEOUT, stream.read(&mut buffer))
+            .await
+            .map_err(|_| "Timed out waiting for the master".to_string())?
+            .map_err(|e| format!("Failed to read from master: {}", e))?;
+        if bytes_read == 0 {
+            return Err("Unexpected EOF from master".to_string());
+        }
+        pending.extend_from_slice(&buffer[..bytes_read]);
         Ok(())
     }
 
+    async fn read_line(stream: &mut OwnedReadHalf, pending: &mut Vec<u8>) -> Result<String, String> {
+        loop {
+            if let Some(end) = pending.windows(2).position(|window| window == CRLF.as_bytes()) {
+                let line = String::from_utf8_lossy(&pending[..end]).to_string();
+                pending.drain(..end + 2);
+                return Ok(line);
+            }
+            Self::fill_buffer(stream, pending).await?;
+        }
+    }
+
     async fn send_command_with_writer(&self, stream: &mut OwnedWriteHalf, args: &[&str]) -> Result<(), String> {
         let command = construct_redis_command(args);
-        stream.write_all(command.as_bytes()).await.map_err(|e| format!("Failed to send command to master: {}", e))
+        stream.write_all(&command).await.map_err(|e| format!("Failed to send command to master: {}", e))
     }
 
-    async fn expect_pong_response(&self, stream: &mut OwnedReadHalf) -> Result<(), String> {
-        let mut buffer = [0u8; 512];
-        let bytes_read = stream.read(&mut buffer).await.map_err(|e| format!("Failed to read PONG response from master: {}", e))?;
-        let response = String::from_utf8_lossy(&buffer[..bytes_read]);
+    async fn expect_pong_response(&self, stream: &mut OwnedReadHalf, pending: &mut Vec<u8>) -> Result<(), String> {
+        let response = Self::read_line(stream, pending).await.map_err(|e| format!("Failed to read PONG response from master: {}", e))?;
         if response.contains(SIMPLE_STRING_PREFIX) && response.contains("PONG") {
-            println!("Master responded with PONG");
+            log_notice!("Master responded with PONG");
+            Ok(())
+        } else if response.starts_with(ERROR_PREFIX) && response.contains("NOAUTH") {
+            // 마스터에 requirepass가 있으면 PING도 거절되지만 연결은 살아 있으므로 이어서 AUTH를 보냄
+            log_notice!("Master requires authentication");
             Ok(())
         } else {
             Err(format!("Unexpected response from master: {}", response))
         }
     }
 
-    async fn expect_ok_response(&self, stream: &mut OwnedReadHalf) -> Result<(), String> {
-        let mut buffer = [0u8; 512];
-        let bytes_read = stream.read(&mut buffer).await.map_err(|e| format!("Failed to read OK response from master: {}", e))?;
-        let response = String::from_utf8_lossy(&buffer[..bytes_read]);
+    async fn expect_ok_response(&self, stream: &mut OwnedReadHalf, pending: &mut Vec<u8>) -> Result<(), String> {
+        let response = Self::read_line(stream, pending).await.map_err(|e| format!("Failed to read OK response from master: {}", e))?;
         if response.contains(SIMPLE_STRING_PREFIX) && response.contains("OK") {
-            println!("Master acknowledged command with OK");
+            log_notice!("Master acknowledged command with OK");
             Ok(())
         } else {
             Err(format!("Unexpected response from master: {}", response))
         }
     }
 
-    async fn expect_fullresync_response(&self, stream: &mut OwnedReadHalf) -> Result<(), String> {
-        let mut buffer = [0u8; 512];
-        let bytes_read = stream.read(&mut buffer).await.map_err(|e| format!("Failed to read FULLRESYNC response from master: {}", e))?;
-        let response = String::from_utf8_lossy(&buffer[..bytes_read]);
-        if response.contains(SIMPLE_STRING_PREFIX) && response.contains("FULLRESYNC") {
-            println!("Master responded with FULLRESYNC");
-            Ok(())
+    // "+FULLRESYNC <replid> <offset>"이면 (replid, offset), "+CONTINUE"이면 None
+    async fn expect_psync_response(&self, stream: &mut OwnedReadHalf, pending: &mut Vec<u8>) -> Result<PsyncReply, String> {
+        let response = Self::read_line(stream, pending).await.map_err(|e| format!("Failed to read PSYNC response from master: {}", e))?;
+        if response.contains(SIMPLE_STRING_PREFIX) && response.contains(FULLRESYNC) {
+            log_notice!("Master responded with FULLRESYNC");
+            let mut parts = response.split_whitespace().skip(1);
+            match (parts.next(), parts.next().and_then(|offset| offset.parse::<u64>().ok())) {
+                (Some(replid), Some(offset)) => Ok(PsyncReply::FullResync { replid: replid.to_string(), offset }),
+                _ => Err(format!("Invalid FULLRESYNC response from master: {}", response)),
+            }
+        } else if response.contains(SIMPLE_STRING_PREFIX) && response.contains(CONTINUE) {
+            log_notice!("Master responded with CONTINUE");
+            let replid = response.split_whitespace().nth(1).map(str::to_string);
+            Ok(PsyncReply::Continue { replid })
         } else {
             Err(format!("Unexpected response from master: {}", response))
         }
@@ -4,4 +4,81 @@ use thiserror::Error;
 pub enum ArgumentError {
     #[error("Argument Error: {0}")]
     General(String),
-}
\ No newline at end of file
+}
+
+// 명령 실행 에러, Display가 클라이언트에게 보내는 에러 문자열(코드 + 메시지) 그대로임
+// 클라이언트가 첫 단어(ERR, WRONGTYPE, MOVED 등)로 에러 종류를 구분함
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum RedisError {
+    #[error("ERR {0}")]
+    Err(String),
+    #[error("WRONGTYPE Operation against a key holding the wrong kind of value")]
+    WrongType,
+    #[error("READONLY You can't write against a read only replica.")]
+    ReadOnly,
+    #[error("UNBLOCKED force unblock from blocking operation, instance state changed (master -> replica?)")]
+    Unblocked,
+    #[error("OOM command not allowed when used memory > 'maxmemory'.")]
+    Oom,
+    // 네임스페이스의 키 수나 메모리가 ACL 사용자의 maxkeys/maxmemory에 닿음
+    #[error("OOM command not allowed when the key namespace is over its '{0}' quota.")]
+    NamespaceQuota(&'static str),
+    #[error("EXECABORT Transaction discarded because of previous errors.")]
+    ExecAbort,
+    // 이벤트 큐가 차서 shed 정책으로 거절한 명령, 클라이언트가 스크립트의 BUSY로 알고 SCRIPT KILL을 보내지 않도록 코드를 따로 씀
+    #[error("OVERLOADED Server is overloaded, command shed. Retry later.")]
+    Shed,
+    #[error("BUSYKEY Target key name already exists.")]
+    BusyKey,
+    #[error("NOPROTO unsupported protocol version")]
+    NoProto,
+    #[error("NOAUTH Authentication required.")]
+    NoAuth,
+    #[error("WRONGPASS invalid username-password pair or user is disabled.")]
+    WrongPass,
+    #[error("NOPERM {0}")]
+    NoPerm(String),
+    #[error("DENIED Redis is running in protected mode because protected mode is enabled and no password is set for the default user. In this mode connections are only accepted from the loopback interface. If you want to connect from external computers to Redis you may adopt one of the following solutions: 1) Just disable protected mode sending the command 'CONFIG SET protected-mode no' from the loopback interface by connecting to Redis from the same host the server is running, however MAKE SURE Redis is not publicly accessible from internet if you do so. 2) Alternatively you can just disable the protected mode by editing the Redis configuration file, and setting the protected mode option to 'no', and then restarting the server. 3) If you started the server manually just for testing, restart it with the '--protected-mode no' option. 4) Set up an authentication password for the default user. NOTE: You only need to do one of the above things in order for the server to start accepting connections from the outside.")]
+    Denied,
+    #[error("MOVED {slot} {addr}")]
+    Moved { slot: u16, addr: String },
+    #[error("ASK {slot} {addr}")]
+    Ask { slot: u16, addr: String },
+    #[error("CROSSSLOT Keys in request don't hash to the same slot")]
+    CrossSlot,
+    #[error("CLUSTERDOWN The cluster is down")]
+    ClusterDown,
+    #[error("TRYAGAIN Multiple keys request during rehashing of slot")]
+    TryAgain,
+    #[error("IOERR {0}")]
+    IoErr(String),
+    #[error("INPROG Failover already in progress")]
+    InProgress,
+    #[error("NOGOODSLAVE No suitable replica to promote")]
+    NoGoodSlave,
+    #[error("NOQUORUM {0}")]
+    NoQuorum(String),
+    #[error("NOSCRIPT No matching script. Please use EVAL.")]
+    NoScript,
+    #[error("BUSY Redis is busy running a script. You can only call SCRIPT KILL or SHUTDOWN NOSAVE.")]
+    ScriptBusy,
+    #[error("BUSY Redis is busy running a script. You can only call FUNCTION KILL or SHUTDOWN NOSAVE.")]
+    FunctionBusy,
+    #[error("NOTBUSY No scripts in execution right now.")]
+    NotBusy,
+    #[error("UNKILLABLE Sorry the script already executed write commands against the dataset. You can either wait the script termination or kill the server in a hard way using the SHUTDOWN NOSAVE command.")]
+    Unkillable,
+}
+
+// 코드가 따로 없는 에러 메시지는 ERR로 보냄
+impl From<String> for RedisError {
+    fn from(message: String) -> Self {
+        RedisError::Err(message)
+    }
+}
+
+impl From<&str> for RedisError {
+    fn from(message: &str) -> Self {
+        RedisError::Err(message.to_string())
+    }
+}
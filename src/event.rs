@@ -1,12 +1,20 @@
+use crate::admin::AdminResponse;
+use crate::client_output::ClientOutput;
+use crate::cluster_bus::BusMessage;
 use crate::command::Command;
+use crate::sentinel::SentinelRequest;
+use crate::resp::RespValue;
+use crate::trace::TraceContext;
 use std::net::SocketAddr;
-use tokio::net::tcp::OwnedWriteHalf;
+use tokio::sync::oneshot;
 
 pub enum RedisEvent {
     ClientConnected {
         client_id: u64,
-        writer: OwnedWriteHalf,
+        output: ClientOutput,
         addr: SocketAddr,
+        local_addr: SocketAddr,
+        kill_switch: oneshot::Sender<()>,
     },
     ClientDisconnected {
         client_id: u64,
@@ -15,15 +23,80 @@ pub enum RedisEvent {
     CommandReceived {
         client_id: u64,
         command: Command,
+        trace: Option<TraceContext>,
+    },
+    CommandError {
+        client_id: u64,
+        message: String,
     },
 
-    SlaveConnected {
-        addr: SocketAddr,
+    SlaveListeningPort {
+        client_id: u64,
+        listening_port: Option<u16>,
+    },
+    SlaveAnnounced {
+        client_id: u64,
+        ip: String,
+    },
+    SlaveAcked {
+        client_id: u64,
+        offset: i64,
     },
+    ReplicaAckProbe,
+    ActiveExpireCycle,
+    BackgroundSaveFinished {
+        result: Result<(), String>,
+    },
+    ShutdownRequested,
+    DebugDumpRequested,
     SlaveDisconnected {
-        addr: SocketAddr,
+        client_id: u64,
+        replica_id: u64,
+    },
+    PromotionDrained {
+        client_id: u64,
+    },
+    // 읽기 경로에서 만난 만료된 키, 지우기와 DEL 전파는 이벤트 루프가 다시 확인하고 함
+    ExpiredKeysFound {
+        keys: Vec<Vec<u8>>,
+        trace: Option<TraceContext>,
+    },
+    KeyspaceNotification {
+        class: u32,
+        event: &'static str,
+        key: Vec<u8>,
     },
     PropagateSlave {
+        message: Vec<u8>,
+        trace: Option<TraceContext>,
+    },
+    // EXEC 동안 전파되는 명령을 모았다가(exec: false) 끝나면 MULTI/EXEC로 감싸서 보냄(exec: true)
+    PropagateTransaction {
+        exec: bool,
+    },
+    // 마스터에게서 받은 복제 스트림 조각, 적용할 명령이 없어도 하위 레플리카에게는 그대로 전달함
+    MasterStream {
+        command: Option<Command>,
+        raw: Vec<u8>,
+        trace: Option<TraceContext>,
+    },
+    MasterResynced,
+    // 클러스터 버스로 다른 노드에게서 받은 메시지
+    ClusterMessage {
+        message: BusMessage,
+    },
+    // sentinel이 감시 대상이나 다른 sentinel에게 보낸 요청의 응답
+    SentinelReply {
+        request: SentinelRequest,
+        reply: Result<RespValue, String>,
+    },
+    // 마스터의 hello 채널에서 받은 다른 sentinel의 알림
+    SentinelHello {
         message: String,
     },
+
+    AdminRequest {
+        path: String,
+        reply: oneshot::Sender<AdminResponse>,
+    },
 } 
\ No newline at end of file
@@ -1,100 +1,3227 @@
+use crate::acl::{Acl, AclUser};
+use crate::admin::{AdminResponse, ADMIN_PATH_CLIENTS, ADMIN_PATH_CONFIG, ADMIN_PATH_INFO, ADMIN_PATH_METRICS, ADMIN_PATH_REPLICAS, ADMIN_PATH_SLOTS};
+use crate::blocking::{BlockedClient, BlockingRegistry, ReplicaWait};
+use crate::cluster::ClusterState;
+use crate::cluster_bus::ClusterBus;
+use crate::logging::{self, log_notice, log_verbose, log_warning};
+use crate::sentinel::SentinelState;
+use crate::sentinel_link::SentinelLinks;
 use crate::client_manager::ClientManager;
+use crate::command::{AclCommand, ClientCommand, ClientType, ClusterCommand, Command, CommandCategory, CommandResponse, DebugCommand, FlushMode, FunctionCommand, LatencyCommand, MemoryCommand, PubSubCommand, ScriptCommand, SentinelCommand};
+use crate::command_parser::CommandParser;
+use crate::concurrent_reads::ConcurrentReads;
+use crate::command_registry::{self, EventHandlerContext, ExecutionContext, Executor, CMD_DENYOOM, CMD_NOSCRIPT, CMD_NO_AUTH, CMD_SENTINEL, CMD_SUBSCRIBED, CMD_WRITE};
+use crate::config_handler::{config_parameter_by_key, config_value_type, ConfigHandler, ConfigParameter, Db, RuntimeSettings, CONFIG_TYPE_BOOL, CONFIG_TYPE_INTEGER};
+use crate::errors::{ArgumentError, RedisError};
 use crate::event::RedisEvent;
 use crate::event_publisher::EventPublisher;
-use crate::redis_client::Client;
-use crate::replication_config::ReplicationConfig;
+use crate::eviction::{self, EvictionPool, EvictionPolicy};
+use crate::firewall::Firewall;
+use crate::hooks::{CallResult, CommandCall, HookRegistry};
+use crate::latency::{LatencyMonitor, LATENCY_EVENT_COMMAND, LATENCY_EVENT_EXPIRE_CYCLE, LATENCY_EVENT_FORK};
+use crate::lazyfree;
+use crate::memory::{MemoryReport, DEFAULT_USAGE_SAMPLES};
+use crate::notify;
+use crate::persistence::{self, Persistence};
+use crate::redis_client::{Client, Transaction};
+use crate::replica_output::OutputBufferLimits;
+use crate::protocol_constants::*;
+use crate::pubsub::{self, ShardChannels};
+use crate::random::Random;
+use crate::rdb_codec;
+use crate::rdb_parser::RdbParser;
+use crate::resp::{self, RespValue};
+use crate::script_cache::ScriptCache;
+use crate::scripting::{self, FunctionRegistry, ScriptMonitor, ScriptRun, ScriptStep};
+use crate::replication_config::{ReplicationConfig, SlaveInfo};
+use crate::server_info::{ServerInfo, RUN_ID_LEN, SERVER_VERSION};
+use crate::stats::Stats;
+use crate::state_manager::StateManager;
+use crate::trace::{self, TraceContext};
+use crate::tracking::TrackingTable;
+use crate::watch::WatchTable;
+use crate::util::{construct_redis_command, current_time_ms, format_host_port, glob_match, json_string, key_hash_slot};
 use crate::value_entry::ValueEntry;
-use std::collections::HashMap;
+use bytes::Bytes;
+use std::collections::{HashMap, HashSet};
+use std::net::SocketAddr;
 use std::sync::Arc;
-use tokio::io::AsyncWriteExt;
+use tokio::io::AsyncWrite;
 use tokio::sync::RwLock;
+use tokio::time::{Duration, Instant};
+
+const DEFAULT_SLOWLOG_THRESHOLD_US: u64 = 10_000;
+// Redis repl-ping-replica-period 기본값(초)
+const DEFAULT_REPL_PING_REPLICA_PERIOD_SECS: u64 = 10;
+// Redis shutdown-timeout 기본값(초)
+const DEFAULT_SHUTDOWN_TIMEOUT_SECS: u64 = 10;
+// Redis busy-reply-threshold 기본값(밀리초)
+const DEFAULT_BUSY_REPLY_THRESHOLD_MS: u64 = 5000;
+const DEBUG_REPORT_SLOWLOG_ENTRIES: usize = 10;
+// Redis LRU 시계는 초 단위 24비트
+const LRU_CLOCK_MAX: u64 = (1 << 24) - 1;
+// 큐가 계속 차 있어도 이만큼 이벤트를 처리하면 응답을 보냄
+const MAX_CORKED_EVENTS: usize = 64;
+// SIGUSR1 덤프에 남기는 키 수, 키스페이스가 커도 로그가 넘치지 않게 함
+const DEBUG_DUMP_KEY_SAMPLES: usize = 20;
+const SECRET_CONFIG_MARKERS: [&str; 4] = ["pass", "secret", "token", "auth"];
+// Redis active expire 기본값: 한 번에 20개를 샘플링하고, 25% 넘게 만료되었으면 반복
+const ACTIVE_EXPIRE_SAMPLE_SIZE: usize = 20;
+const ACTIVE_EXPIRE_REPEAT_PERCENT: usize = 25;
+const ACTIVE_EXPIRE_MAX_ROUNDS: usize = 16;
 
 pub struct EventHandler {
-    db: Arc<RwLock<HashMap<String, ValueEntry>>>,
+    db: Arc<RwLock<Db>>,
     config: Arc<RwLock<HashMap<String, String>>>,
     replication_config: Arc<RwLock<ReplicationConfig>>,
+    stats: Arc<RwLock<Stats>>,
+    server_info: Arc<RwLock<ServerInfo>>,
     client_manager: ClientManager,
     publisher: EventPublisher,
+    firewall: Firewall,
+    acl: Acl,
+    // cluster-enabled가 아니면 None
+    cluster: Option<ClusterState>,
+    cluster_bus: ClusterBus,
+    // --sentinel로 시작했을 때만 Some, 이때는 데이터 명령을 받지 않음
+    sentinel: Option<SentinelState>,
+    sentinel_links: SentinelLinks,
+    master_transaction: Option<Vec<Command>>,
+    scripts: ScriptCache,
+    script_monitor: Arc<ScriptMonitor>,
+    functions: Arc<RwLock<FunctionRegistry>>,
+    shard_channels: ShardChannels,
+    tracking_table: TrackingTable,
+    watch_table: WatchTable,
+    blocking: BlockingRegistry,
+    // 대기 중인 클라이언트가 있는 키에 쓰기가 일어나면 모아 두었다가 명령(또는 EXEC)이 끝난 뒤 깨움
+    ready_keys: Vec<Vec<u8>>,
+    executing_transaction: bool,
+    // EXEC 중에 전파된 명령, 하나도 없으면 MULTI/EXEC도 보내지 않음
+    transaction_propagation: Option<Vec<u8>>,
+    replica_waits: Vec<ReplicaWait>,
+    persistence: Persistence,
+    latency: LatencyMonitor,
+    // ReplicaAckProbe는 1초마다 오므로 마지막 PING 이후의 틱 수가 곧 경과 초
+    ticks_since_replica_ping: u64,
+    // 실행 중인 명령의 클라이언트와 그 클라이언트에게 에러로 응답했는지, commandstats의 failed_calls에 씀
+    current_call: Option<(u64, bool)>,
+    // SHUTDOWN을 보낸 클라이언트(시그널이면 None)와 SAVE 여부, 이벤트 루프가 큐를 비운 뒤 finish_shutdown으로 마무리함
+    shutdown_request: Option<(Option<u64>, Option<bool>)>,
+    // 마지막 flush 이후 처리한 이벤트 수
+    corked_events: usize,
+    // DEBUG SET-ACTIVE-EXPIRE 0이면 만료된 키는 접근할 때만 지움
+    active_expire_enabled: bool,
+    // 이벤트 루프 밖에서 실행 중인 읽기 전용 명령, 다른 이벤트를 처리하기 전에 응답을 거둠
+    concurrent_reads: ConcurrentReads,
+    hooks: HookRegistry,
+    eviction_pool: EvictionPool,
+    random: Arc<Random>,
+    runtime: Arc<RuntimeSettings>,
 }
 
 impl EventHandler {
     pub fn new(
-        db: Arc<RwLock<HashMap<String, ValueEntry>>>,
-        config: Arc<RwLock<HashMap<String, String>>>,
-        replication_config: Arc<RwLock<ReplicationConfig>>,
+        state: &StateManager,
         publisher: EventPublisher,
+        firewall: Firewall,
+        cluster: Option<ClusterState>,
+        cluster_bus: ClusterBus,
+        sentinel: Option<SentinelState>,
     ) -> Self {
+        let (db, config, replication_config, functions) = (state.get_db(), state.get_config(), state.get_replication_config(), state.get_functions());
+        let sentinel_links = SentinelLinks::new(publisher.clone());
+        let runtime = state.get_runtime();
+        let concurrent_reads = ConcurrentReads::new(db.clone(), config.clone(), replication_config.clone(), functions.clone(), publisher.clone(), runtime.clone());
         Self {
             db,
             config,
             replication_config,
+            stats: state.get_stats(),
+            server_info: state.get_server_info(),
             client_manager: ClientManager::new(),
             publisher,
+            firewall,
+            acl: Acl::new(),
+            cluster,
+            cluster_bus,
+            sentinel,
+            sentinel_links,
+            master_transaction: None,
+            scripts: ScriptCache::new(),
+            script_monitor: state.get_script_monitor(),
+            functions,
+            shard_channels: ShardChannels::new(),
+            tracking_table: TrackingTable::new(),
+            watch_table: WatchTable::new(),
+            blocking: BlockingRegistry::new(),
+            ready_keys: Vec::new(),
+            executing_transaction: false,
+            transaction_propagation: None,
+            replica_waits: Vec::new(),
+            persistence: Persistence::new(),
+            latency: LatencyMonitor::new(),
+            ticks_since_replica_ping: 0,
+            current_call: None,
+            shutdown_request: None,
+            corked_events: 0,
+            active_expire_enabled: true,
+            concurrent_reads,
+            hooks: HookRegistry::new(),
+            eviction_pool: EvictionPool::default(),
+            random: state.get_random(),
+            runtime,
         }
     }
 
     pub async fn handle_event(&mut self, event: RedisEvent) {
+        // 읽기 전용 명령이 아니면 먼저 시작한 읽기를 마치고 처리함, 연달아 들어온 읽기만 함께 실행됨
+        if !matches!(&event, RedisEvent::CommandReceived { command, .. } if command.category() == CommandCategory::Read) {
+            self.complete_concurrent_reads().await;
+        }
         match event {
-            RedisEvent::ClientConnected { client_id, writer, addr } => {
-                println!("New client connected: {}", client_id);
-                let client = Client::new(client_id, writer, addr);
+            RedisEvent::ClientConnected { client_id, output, addr, local_addr, kill_switch } => {
+                log_verbose!("New client connected: id={} addr={}", client_id, addr);
+                self.stats.write().await.record_connection();
+                let mut client = Client::new(client_id, output, addr, local_addr, kill_switch);
+                // Redis처럼 나중에 requirepass를 설정해도 이미 연결된 클라이언트는 인증된 상태로 둠
+                self.sync_requirepass().await;
+                client.authenticated = self.acl.default_user().is_nopass();
+                // 등록하지 않고 버리면 kill switch도 함께 닫혀 읽기 태스크가 끝남
+                if self.protected_mode_denies(addr).await {
+                    log_notice!("Denied connection from {} in protected mode", addr);
+                    let response = RespValue::from(RedisError::Denied);
+                    let _ = client.write_all(&response.encode(client.protocol)).await;
+                    let _ = client.flush();
+                    return;
+                }
                 self.client_manager.add_client(client_id, client);
             }
 
             RedisEvent::ClientDisconnected { client_id } => {
-                println!("Client disconnected: {}", client_id);
-                self.client_manager.remove_client(client_id);
+                // CLIENT KILL이나 쓰기 실패로 이미 정리한 연결이면 읽기 태스크가 늦게 보낸 것이므로 무시함
+                let Some(mut client) = self.release_client(client_id) else {
+                    return;
+                };
+                // QUIT의 OK나 프로토콜 에러 응답처럼 끊기 직전에 쓴 응답을 마저 보냄
+                let _ = client.flush();
+                log_verbose!("Client disconnected: {}", client.label());
+                let replica_id = self.replication_config.read().await.unregister_slave(client_id).await;
+                if let Some(replica_id) = replica_id {
+                    log_notice!("Slave disconnected: replica {}", replica_id);
+                    self.publish_server_event(&format!("replica-disconnected id={}", replica_id)).await;
+                }
             }
 
-            RedisEvent::CommandReceived { client_id, command } => {
-                if client_id == 0 {
-                    let mut db = self.db.write().await;
-                    if let Err(e) = command.execute_without_response(&mut db).await {
-                        eprintln!("Failed to execute command from master: {}", e);
+            RedisEvent::CommandReceived { client_id, mut command, trace } => {
+                trace::record(trace, "execute", &format!("client={} command={}", client_id, command.name()));
+                if let Some(client) = self.client_manager.get_client(client_id) {
+                    client.backpressure.command_dequeued();
+                }
+                if let Some(client) = self.client_manager.get_client_mut(&client_id) {
+                    client.increment_request_count();
+                    if !client.authenticated && !command.spec().has_flag(CMD_NO_AUTH) {
+                        client.flag_transaction_error();
+                        let response = RespValue::from(RedisError::NoAuth);
+                        self.reject_command(client_id, command.name(), &response).await;
+                        return;
+                    }
+                    if !command.spec().has_flag(CMD_NO_AUTH) {
+                        let permitted = match self.acl.user(&client.user) {
+                            Some(user) => user.check(&command).and_then(|()| user.confine(&mut command)),
+                            None => Err(RedisError::NoPerm(format!("User {} no longer exists", client.user))),
+                        };
+                        if let Err(e) = permitted {
+                            log_notice!("[acl] denied {} for user {} (client {})", command.name(), client.user, client.label());
+                            client.flag_transaction_error();
+                            self.reject_command(client_id, command.name(), &RespValue::from(e)).await;
+                            return;
+                        }
+                    }
+                    if !self.firewall.is_allowed(client.addr.ip(), command.category()) {
+                        log_notice!("[firewall] denied {} from {} (client {})", command.name(), client.addr, client.label());
+                        client.flag_transaction_error();
+                        let response = RespValue::error(&format!("command '{}' is not allowed from {}", command.name(), client.addr.ip()));
+                        self.reject_command(client_id, command.name(), &response).await;
+                        return;
+                    }
+                    if self.publisher.should_shed() {
+                        self.publisher.record_shed();
+                        let response = RespValue::from(RedisError::Shed);
+                        self.reject_command(client_id, command.name(), &response).await;
+                        return;
+                    }
+                    // RESP2에서는 메시지와 응답을 구분할 수 없어서 구독 중에는 구독 관련 명령만 받음, RESP3는 메시지가 push 타입이라 제한하지 않음
+                    if client.protocol == RESP2_PROTOCOL
+                        && (client.is_subscribed() || self.shard_channels.count_for(client_id) > 0)
+                        && !command.spec().has_flag(CMD_SUBSCRIBED)
+                    {
+                        let response = RespValue::error(&format!(
+                            "Can't execute '{}': only (P|S)SUBSCRIBE / (P|S)UNSUBSCRIBE / PING / QUIT / RESET are allowed in this context",
+                            command.name().to_lowercase()
+                        ));
+                        self.reject_command(client_id, command.name(), &response).await;
+                        return;
+                    }
+                    if let Some(replacement) = command.deprecation() {
+                        log_notice!(
+                            "[deprecated] client={} addr={} command={} replacement='{}'",
+                            client.label(), client.addr, command.name(), replacement
+                        );
+                        self.stats.write().await.record_deprecated_call(command.name());
+                    }
+                    if self.sentinel.is_some() && !command.spec().has_flag(CMD_SENTINEL) {
+                        let response = RespValue::error(SENTINEL_MODE_COMMAND_ERROR);
+                        self.reject_command(client_id, command.name(), &response).await;
+                        return;
+                    }
+                    if matches!(command, Command::ASKING | Command::READONLY | Command::READWRITE) {
+                        self.execute_command(client_id, command, trace).await;
+                        return;
+                    }
+                    if let Err(redirect) = self.route_in_cluster(client_id, &command).await {
+                        if let Some(client) = self.client_manager.get_client_mut(&client_id) {
+                            client.flag_transaction_error();
+                        }
+                        self.reject_command(client_id, command.name(), &RespValue::from(redirect)).await;
+                        return;
                     }
-                } else if let Some(client) = self.client_manager.get_client_mut(&client_id) {
-                    if let Err(e) = command.handle_command(
-                        &mut client.writer,
-                        &self.db,
-                        &self.config,
-                        &self.replication_config,
-                        client.addr,
-                        &self.publisher,
-                    ).await {
-                        eprintln!("Failed to handle command: {}", e);
+                    // 레플리카의 쓰기는 마스터 링크(client 0)로만 들어옴
+                    if command.has_flag(CMD_WRITE) && self.replication_config.read().await.get_role().await == "slave" {
+                        if let Some(client) = self.client_manager.get_client_mut(&client_id) {
+                            client.flag_transaction_error();
+                        }
+                        let response = RespValue::from(RedisError::ReadOnly);
+                        self.reject_command(client_id, command.name(), &response).await;
+                        return;
+                    }
+                    if command.has_flag(CMD_DENYOOM) && !self.free_memory_for_write().await {
+                        if let Some(client) = self.client_manager.get_client_mut(&client_id) {
+                            client.flag_transaction_error();
+                        }
+                        let response = RespValue::from(RedisError::Oom);
+                        self.reject_command(client_id, command.name(), &response).await;
+                        return;
+                    }
+                    self.handle_transaction_command(client_id, command, trace).await;
+                }
+            }
+
+            RedisEvent::CommandError { client_id, message } => {
+                if let Some(client) = self.client_manager.get_client_mut(&client_id) {
+                    client.flag_transaction_error();
+                }
+                self.write_reply(client_id, "unknown", &RespValue::error(&message)).await;
+            }
+
+            RedisEvent::SlaveListeningPort { client_id, listening_port } => {
+                if let Some(client) = self.client_manager.get_client_mut(&client_id) {
+                    client.replica_listening_port = listening_port;
+                }
+            }
+
+            RedisEvent::SlaveAnnounced { client_id, ip } => {
+                if let Some(client) = self.client_manager.get_client_mut(&client_id) {
+                    client.replica_announced_ip = Some(ip.clone());
+                    self.replication_config.read().await.set_slave_announced_ip(client_id, ip).await;
+                }
+            }
+
+            RedisEvent::SlaveAcked { client_id, offset } => {
+                self.replication_config.read().await.record_slave_ack(client_id, offset).await;
+                self.resolve_replica_waits(false).await;
+            }
+
+            RedisEvent::ReplicaAckProbe => {
+                if self.replication_config.read().await.get_role().await == "master" {
+                    self.ping_replicas().await;
+                }
+                self.probe_replica_acks().await;
+            }
+
+            RedisEvent::ActiveExpireCycle => {
+                self.stats.write().await.sample_ops(current_time_ms());
+                if self.active_expire_enabled {
+                    let started_at = Instant::now();
+                    self.active_expire_cycle().await;
+                    self.active_expire_hash_fields().await;
+                    self.record_latency(LATENCY_EVENT_EXPIRE_CYCLE, started_at.elapsed()).await;
+                }
+                self.expire_blocked_clients().await;
+                self.resolve_replica_waits(true).await;
+                self.check_save_points().await;
+                self.cluster_cron();
+                self.sentinel_cron();
+            }
+
+            RedisEvent::SentinelReply { request, reply } => {
+                if let Some(sentinel) = self.sentinel.as_mut() {
+                    for request in sentinel.handle_reply(request, reply, current_time_ms()) {
+                        self.sentinel_links.send(request);
                     }
                 }
             }
 
-            RedisEvent::SlaveConnected { addr } => {
-                println!("New slave connected: {}", addr);
-                let client_id = addr.port() as u64;
+            RedisEvent::SentinelHello { message } => {
+                if let Some(sentinel) = self.sentinel.as_mut() {
+                    sentinel.handle_hello(&message, current_time_ms());
+                }
+            }
+
+            RedisEvent::ClusterMessage { message } => {
+                if let Some(cluster) = self.cluster.as_mut() {
+                    for (addr, reply) in cluster.handle_message(message, current_time_ms()) {
+                        self.cluster_bus.send(&addr, &reply);
+                    }
+                }
+            }
 
-                if let Some(_) = self.client_manager.get_client_mut(&client_id) {
-                    self.replication_config.write().await.register_slave(addr).await;
+            RedisEvent::BackgroundSaveFinished { result } => {
+                match &result {
+                    Ok(()) => log_notice!("Background saving terminated with success"),
+                    Err(e) => log_warning!("Background saving error: {}", e),
                 }
+                self.persistence.finish_bgsave(result.is_ok());
             }
 
-            RedisEvent::SlaveDisconnected { addr } => {
-                println!("Slave disconnected: {}", addr);
+            RedisEvent::ShutdownRequested => self.request_shutdown(None, None),
+            RedisEvent::DebugDumpRequested => self.log_debug_dump().await,
+
+            // 복제 스트림 쓰기에 실패해서 이미 레플리카 목록에서 뺀 연결을 닫음
+            RedisEvent::SlaveDisconnected { client_id, replica_id } => {
+                if let Some(client) = self.release_client(client_id) {
+                    log_notice!("Slave disconnected: {} (replica {})", client.addr, replica_id);
+                }
+                self.publish_server_event(&format!("replica-disconnected id={}", replica_id)).await;
             }
 
-            RedisEvent::PropagateSlave { message } => {
+            RedisEvent::PromotionDrained { client_id } => {
                 let repl_guard = self.replication_config.read().await;
-                let slaves = repl_guard.list_slaves().await;
+                repl_guard.promote_to_master().await;
+                log_notice!("Replica promoted to master after draining the master link");
+                // 하위 레플리카가 다시 붙으면서 PSYNC로 새 복제 ID를 받아 가게 함
+                let mut slaves = repl_guard.get_slaves_mut().await;
+                let client_ids: Vec<u64> = slaves.iter().map(|slave| slave.client_id).collect();
+                self.detach_replicas(&mut slaves, &client_ids).await;
+                drop(slaves);
+                drop(repl_guard);
+                self.publish_server_event("failover-promoted role=master").await;
+                self.write_reply(client_id, REPLICAOF_COMMAND, &RespValue::ok()).await;
+                // 레플리카에서는 블로킹 명령이 READONLY로 거절되지만, 역할이 바뀐 뒤 남은 대기가 있으면 적용을 마친 키스페이스로 다시 확인함
+                self.ready_keys.extend(self.blocking.watched_keys());
+                self.serve_blocked_clients().await;
+            }
 
-                for slave in slaves.iter() {
-                    let client_id = slave.addr.port() as u64;
+            RedisEvent::ExpiredKeysFound { keys, trace } => {
+                self.expire_found_keys(keys, trace).await;
+            }
 
-                    if let Some(client) = self.client_manager.get_client_mut(&client_id) {
-                        if let Err(e) = client.get_writer().write_all(message.as_bytes()).await {
-                            eprintln!("Failed to propagate message to slave {}: {}", slave.addr, e);
+            RedisEvent::KeyspaceNotification { class, event, key } => {
+                self.notify_keyspace_event(class, event, &key).await;
+            }
+
+            RedisEvent::PropagateSlave { message, trace } => {
+                // 레플리카의 하위 레플리카는 마스터에게서 받은 스트림만 받아야 함
+                if self.replication_config.read().await.get_role().await == "slave" {
+                    return;
+                }
+                if let Some(buffered) = self.transaction_propagation.as_mut() {
+                    trace::record(trace, "propagate", "deferred until EXEC");
+                    buffered.extend_from_slice(&message);
+                    return;
+                }
+                self.propagate_to_slaves(Bytes::from(message), trace).await;
+            }
+
+            RedisEvent::PropagateTransaction { exec: false } => {
+                self.transaction_propagation = Some(Vec::new());
+            }
+
+            RedisEvent::PropagateTransaction { exec: true } => {
+                let Some(buffered) = self.transaction_propagation.take().filter(|buffered| !buffered.is_empty()) else {
+                    return;
+                };
+                let mut message = construct_redis_command(&[MULTI_COMMAND]);
+                message.extend_from_slice(&buffered);
+                message.extend(construct_redis_command(&[EXEC_COMMAND]));
+                self.propagate_to_slaves(Bytes::from(message), None).await;
+            }
+
+            // 적용과 전달을 한 이벤트에서 해야 그 사이에 PSYNC한 하위 레플리카가 명령을 빠뜨리거나 두 번 받지 않음
+            // 마스터 링크의 명령은 클라이언트 연결이 없으므로 응답을 만들지 않고 적용만 함, 마스터에게는 GETACK에 대한 ACK만 보냄
+            RedisEvent::MasterStream { command, raw, trace } => {
+                if let Some(command) = command {
+                    trace::record(trace, "execute", &format!("client=master command={}", command.name()));
+                    self.apply_master_command(command).await;
+                }
+                self.forward_to_slaves(Bytes::from(raw), trace).await;
+            }
+
+            RedisEvent::MasterResynced => {
+                let repl_guard = self.replication_config.read().await;
+                let mut slaves = repl_guard.get_slaves_mut().await;
+                if !slaves.is_empty() {
+                    log_notice!("Disconnecting {} sub-replicas after a full resync with the master", slaves.len());
+                    let client_ids: Vec<u64> = slaves.iter().map(|slave| slave.client_id).collect();
+                    self.detach_replicas(&mut slaves, &client_ids).await;
+                }
+            }
+
+            RedisEvent::AdminRequest { path, reply } => {
+                let response = self.handle_admin_request(&path).await;
+                let _ = reply.send(response);
+            }
+        }
+    }
+
+    async fn handle_replicaof(&mut self, client_id: u64, target: Option<(String, u16)>) {
+        // 클러스터 모드에서는 CLUSTER REPLICATE로만 마스터를 정함
+        if self.cluster.is_some() {
+            self.write_reply(client_id, REPLICAOF_COMMAND, &RespValue::error(REPLICAOF_IN_CLUSTER_ERROR)).await;
+            return;
+        }
+        let Some((host, port)) = target else {
+            self.promote_to_master(client_id).await;
+            return;
+        };
+        self.follow_master(host, port).await;
+        self.write_reply(client_id, REPLICAOF_COMMAND, &RespValue::ok()).await;
+    }
+
+    async fn promote_to_master(&mut self, client_id: u64) {
+        let replication_config = self.replication_config.read().await.clone();
+        if replication_config.get_role().await != "slave" {
+            self.write_reply(client_id, REPLICAOF_COMMAND, &RespValue::ok()).await;
+            return;
+        }
+
+        replication_config.set_failover_in_progress(true).await;
+        self.publish_server_event("failover-draining-master-link").await;
+
+        // master link을 먼저 끊어야 이후 큐에 들어오는 마스터 명령이 없음을 보장할 수 있음
+        if let Some(master_link) = replication_config.take_master_link().await {
+            master_link.abort();
+            let _ = master_link.await;
+        }
+
+        let publisher = self.publisher.clone();
+        logging::spawn(async move {
+            if let Err(e) = publisher.publish_promotion_drained(client_id).await {
+                log_warning!("Failed to finish replica promotion: {}", e);
+            }
+        });
+    }
+
+    // 새 마스터로 복제를 시작함, REPLICAOF와 CLUSTER REPLICATE가 함께 씀
+    async fn follow_master(&mut self, host: String, port: u16) {
+        let replication_config = self.replication_config.read().await.clone();
+        if let Some(master_link) = replication_config.take_master_link().await {
+            master_link.abort();
+        }
+        // 블로킹 명령은 모두 쓰기라서 레플리카가 되면 실행할 수 없으므로 Redis처럼 에러로 풀어 줌
+        for blocked_id in self.blocking.client_ids() {
+            if let Some(blocked) = self.blocking.unblock(blocked_id) {
+                self.write_reply(blocked_id, blocked.command.name(), &RespValue::from(RedisError::Unblocked)).await;
+            }
+        }
+        // 새 마스터가 이 서버의 기록을 이어 가고 있으면 PSYNC로 이어받을 수 있음
+        replication_config.cache_master().await;
+        replication_config.set_replica_of(host.clone(), port).await;
+        self.publish_server_event(&format!("replicaof master={}", format_host_port(&host, port))).await;
+
+        let config_handler = ConfigHandler::new(
+            self.db.clone(),
+            self.config.clone(),
+            self.replication_config.clone(),
+            self.functions.clone(),
+            self.publisher.clone(),
+            self.runtime.clone(),
+        );
+        logging::spawn(async move {
+            if let Err(e) = config_handler.handshake_with_master(host, port.to_string()).await {
+                log_warning!("configure failure with : {}", e);
+            }
+        });
+    }
+
+    // MULTI 중이면 명령을 큐에 쌓고, EXEC에서 다른 이벤트가 끼어들지 않게 한 번에 실행함
+    async fn handle_transaction_command(&mut self, client_id: u64, command: Command, trace: Option<TraceContext>) {
+        // 큐에 쌓이는 명령은 EXEC에서 실행될 때 셈, Redis처럼 WATCH는 큐에 쌓지 않고 MULTI 안이면 에러로 답함
+        if matches!(command, Command::MULTI | Command::EXEC | Command::DISCARD | Command::WATCH(_)) {
+            let name = command.name();
+            let call = self.begin_call(client_id);
+            self.execute_command(client_id, command, trace).await;
+            self.end_call(name, call).await;
+            return;
+        }
+        // Redis처럼 RESET은 MULTI 중에도 바로 실행해서 쌓인 트랜잭션을 버림
+        if matches!(command, Command::RESET) {
+            let call = self.begin_call(client_id);
+            self.execute_command(client_id, command, trace).await;
+            self.end_call(RESET_COMMAND, call).await;
+            return;
+        }
+        let Some(client) = self.client_manager.get_client_mut(&client_id) else {
+            return;
+        };
+        if let Some(transaction) = client.transaction.as_mut() {
+            let name = command.name();
+            transaction.commands.push((command, trace));
+            self.write_reply(client_id, name, &RespValue::SimpleString("QUEUED".into())).await;
+        } else {
+            self.dispatch_command(client_id, command, trace).await;
+        }
+    }
+
+    async fn handle_transaction_control(&mut self, client_id: u64, command: Command) {
+        let Some(client) = self.client_manager.get_client_mut(&client_id) else {
+            return;
+        };
+        let response = match (&command, client.transaction.is_some()) {
+            (Command::MULTI, true) => RespValue::error(MULTI_NESTED_ERROR),
+            (Command::MULTI, false) => {
+                client.transaction = Some(Transaction::default());
+                RespValue::ok()
+            }
+            (Command::EXEC, false) => RespValue::error(EXEC_WITHOUT_MULTI_ERROR),
+            (Command::DISCARD, false) => RespValue::error(DISCARD_WITHOUT_MULTI_ERROR),
+            (Command::DISCARD, true) => {
+                client.transaction = None;
+                self.watch_table.unwatch(client_id);
+                RespValue::ok()
+            }
+            (Command::EXEC, true) => {
+                let transaction = client.transaction.take().unwrap_or_default();
+                self.execute_transaction(client_id, transaction).await;
+                return;
+            }
+            (Command::WATCH(_), true) => RespValue::error(WATCH_INSIDE_MULTI_ERROR),
+            (Command::WATCH(keys), false) => {
+                self.watch_table.watch(client_id, keys);
+                RespValue::ok()
+            }
+            _ => unreachable!("not a transaction control command"),
+        };
+        self.write_reply(client_id, command.name(), &response).await;
+    }
+
+    async fn execute_transaction(&mut self, client_id: u64, transaction: Transaction) {
+        let watched_key_changed = self.watch_table.is_dirty(client_id);
+        self.watch_table.unwatch(client_id);
+        if transaction.aborted {
+            self.write_reply(client_id, EXEC_COMMAND, &RespValue::from(RedisError::ExecAbort)).await;
+            return;
+        }
+        if watched_key_changed {
+            self.write_reply(client_id, EXEC_COMMAND, &RespValue::NullArray).await;
+            return;
+        }
+
+        // 레플리카도 한 번에 적용하도록 EXEC 중에 전파되는 명령을 모아서 MULTI/EXEC로 감싸서 전파함
+        // 쓰기 명령의 결과뿐 아니라 읽다가 만료된 키의 DEL도 함께 감싸짐
+        self.propagate_transaction(false).await;
+
+        let header = format!("{}{}{}", ARRAY_PREFIX, transaction.commands.len(), CRLF);
+        self.write_to_client(client_id, EXEC_COMMAND, header.as_bytes()).await;
+        self.executing_transaction = true;
+        for (command, trace) in transaction.commands {
+            self.dispatch_command(client_id, command, trace).await;
+        }
+        self.executing_transaction = false;
+
+        self.propagate_transaction(true).await;
+        self.serve_blocked_clients().await;
+    }
+
+    async fn propagate_transaction(&self, exec: bool) {
+        if let Err(e) = self.publisher.publish_propagate_transaction(exec).await {
+            log_warning!("{}", e);
+        }
+    }
+
+    // 마스터 링크의 MULTI ~ EXEC 사이 명령은 모아 두었다가 한 번에 적용함
+    async fn apply_master_command(&mut self, command: Command) {
+        match command {
+            Command::MULTI => {
+                self.master_transaction = Some(Vec::new());
+            }
+            Command::FUNCTION(function_command) => {
+                self.handle_function(&function_command).await;
+            }
+            Command::EXEC => {
+                let commands = self.master_transaction.take().unwrap_or_default();
+                {
+                    let mut db = self.db.write().await;
+                    for command in commands.iter() {
+                        if let Err(e) = command.execute_without_response(&mut db).await {
+                            log_warning!("Failed to execute command from master: {}", e);
                         }
-                    } else {
-                        println!("No client found for slave addr: {}", slave.addr);
                     }
                 }
+                for command in commands.iter() {
+                    self.invalidate_command_keys(command, None).await;
+                    self.deliver_master_publish(command).await;
+                }
+            }
+            Command::PUBLISH { .. } | Command::SPUBLISH { .. } if self.master_transaction.is_none() => {
+                self.deliver_master_publish(&command).await;
+            }
+            command => {
+                if let Some(commands) = self.master_transaction.as_mut() {
+                    commands.push(command);
+                    return;
+                }
+                if let Err(e) = command.execute_without_response(&mut *self.db.write().await).await {
+                    log_warning!("Failed to execute command from master: {}", e);
+                }
+                self.invalidate_command_keys(&command, None).await;
+            }
+        }
+    }
+
+    // 마스터가 복제한 발행을 이 노드의 구독자에게 전달함, 하위 레플리카에게는 MasterStream에서 원본 바이트로 넘어감
+    async fn deliver_master_publish(&mut self, command: &Command) {
+        match command {
+            Command::PUBLISH { channel, message } => {
+                self.publish_message(channel, message).await;
+            }
+            Command::SPUBLISH { channel, message } => {
+                self.publish_shard_message(channel, message).await;
+            }
+            _ => {}
+        }
+    }
+
+    // 실행 시간과 에러 응답 여부를 commandstats에 남김, EXEC 안의 명령도 하나씩 셈
+    async fn dispatch_command(&mut self, client_id: u64, command: Command, trace: Option<TraceContext>) {
+        if let Err(response) = self.run_pre_execute_hooks(client_id, &command).await {
+            self.reject_command(client_id, command.name(), &response).await;
+            return;
+        }
+        if self.runs_concurrently(client_id, &command) {
+            self.execute_concurrently(client_id, command, trace).await;
+            return;
+        }
+        let name = command.name();
+        let call = self.begin_call(client_id);
+        self.execute_command(client_id, command, trace).await;
+        self.end_call(name, call).await;
+    }
+
+    // ExecutionContext로 실행되는 읽기 전용 명령만 이벤트 루프 밖에서 실행함
+    // MEMORY는 읽기 전용이지만 이벤트 핸들러의 상태가 필요하고, EXEC 안의 명령은 다른 명령과 섞이지 않도록 그 자리에서 실행함
+    // CLIENT CACHING 바로 다음 명령도 그 자리에서 실행해서, 추적할 키를 정할 때 요청 번호가 아직 그 명령의 것이게 함
+    fn runs_concurrently(&self, client_id: u64, command: &Command) -> bool {
+        command.category() == CommandCategory::Read
+            && matches!(command.executor(), Executor::Context(_))
+            && !self.executing_transaction
+            && self.client_manager.get_client(client_id).is_some_and(|client| client.output.is_some() && !client.caching_applies())
+    }
+
+    async fn execute_concurrently(&mut self, client_id: u64, command: Command, trace: Option<TraceContext>) {
+        let Some(client) = self.client_manager.get_client(client_id) else {
+            return;
+        };
+        let (addr, protocol) = (client.addr, client.protocol);
+        self.record_keyspace_lookups(&command).await;
+        self.concurrent_reads.spawn(client_id, command, addr, protocol, trace);
+    }
+
+    // 먼저 시작한 순서대로 응답을 쓰고, 이벤트 루프에서 실행했을 때처럼 통계와 추적 키를 남김
+    async fn complete_concurrent_reads(&mut self) {
+        while let Some((client_id, completed)) = self.concurrent_reads.next().await {
+            let read = match completed {
+                Ok(read) => read,
+                Err(e) => {
+                    // 응답 하나가 빠지면 이후 응답이 어긋나므로 연결을 닫음
+                    log_warning!("Read command for client {} failed, closing the connection: {}", client_id, e);
+                    if let Some(client) = self.client_manager.get_client_mut(&client_id) {
+                        client.kill();
+                    }
+                    continue;
+                }
+            };
+            // write_to_client는 남은 읽기를 먼저 거두므로 쓰지 않음, 뒤의 응답이 앞지르게 됨
+            if let Some(client) = self.client_manager.get_client_mut(&client_id) {
+                if let Err(e) = client.write_all(&read.reply).await {
+                    log_warning!("Failed to write to client {}, closing the connection: {}", client.label(), e);
+                    client.kill();
+                }
+            }
+            let name = read.command.name();
+            let failed = match read.result {
+                Ok((written, failed)) => {
+                    self.stats.write().await.record_reply(name, written);
+                    failed
+                }
+                Err(e) => {
+                    log_warning!("Failed to handle command: {}", e);
+                    true
+                }
+            };
+            self.stats.write().await.record_call(name, read.duration.as_micros() as u64, failed);
+            self.record_latency(LATENCY_EVENT_COMMAND, read.duration).await;
+            let call = CommandCall { client_id, client_addr: read.addr, command: &read.command };
+            self.run_post_execute_hooks(&call, &CallResult { duration: read.duration }).await;
+            trace::record(read.trace, "reply", &format!("client={}", client_id));
+        }
+    }
+
+    // EXEC처럼 명령 안에서 다른 명령을 실행해도 바깥 명령의 상태를 잃지 않도록 돌려받아 end_call에 넘김
+    fn begin_call(&mut self, client_id: u64) -> (Instant, Option<(u64, bool)>) {
+        (Instant::now(), self.current_call.replace((client_id, false)))
+    }
+
+    // 이벤트 핸들러가 직접 처리하는 명령(DEBUG SLEEP 등)도 command 지연 시간에 남도록 훅이 아니라 여기서 기록함
+    async fn end_call(&mut self, name: &str, (started_at, outer): (Instant, Option<(u64, bool)>)) {
+        let failed = self.current_call.take().is_some_and(|(_, failed)| failed);
+        self.current_call = outer;
+        let duration = started_at.elapsed();
+        self.stats.write().await.record_call(name, duration.as_micros() as u64, failed);
+        self.record_latency(LATENCY_EVENT_COMMAND, duration).await;
+    }
+
+    async fn reject_command(&mut self, client_id: u64, command_name: &str, response: &RespValue) {
+        self.stats.write().await.record_rejected_call(command_name);
+        self.write_reply(client_id, command_name, response).await;
+    }
+
+    // 이벤트 핸들러의 상태가 필요한 명령은 레지스트리에 등록된 EventHandler::run_*이 응답까지 쓰고, 나머지는 ExecutionContext로 실행함
+    async fn execute_command(&mut self, client_id: u64, command: Command, trace: Option<TraceContext>) {
+        let Some(client) = self.client_manager.get_client_mut(&client_id) else {
+            return;
+        };
+        // RESP3에서는 구독 중에도 일반 응답과 push가 구분되므로 평소처럼 PONG으로 답함
+        if let Command::PING(message) = &command {
+            if client.protocol == RESP2_PROTOCOL && (client.is_subscribed() || self.shard_channels.count_for(client_id) > 0) {
+                self.write_reply(client_id, command.name(), &pubsub::subscribed_pong_reply(message.as_deref())).await;
+                return;
+            }
+        }
+        if let Executor::EventHandler(execute) = command.executor() {
+            execute(command, EventHandlerContext { handler: self, client_id, trace }).await;
+            return;
+        }
+        let started_at = Instant::now();
+        let client_addr = client.addr;
+        let protocol = client.protocol;
+        if command.category() == CommandCategory::Read {
+            self.record_keyspace_lookups(&command).await;
+        }
+        let Some(client) = self.client_manager.get_client_mut(&client_id) else {
+            return;
+        };
+        // 레플리카로 등록된 연결의 명령(REPLCONF ACK 등)에는 Redis처럼 응답하지 않음
+        let mut discard = tokio::io::sink();
+        let writer: &mut (dyn AsyncWrite + Unpin + Send) = match client.output.as_mut() {
+            Some(output) => output.buffer_mut(),
+            None => &mut discard,
+        };
+        let context = ExecutionContext {
+            db: &self.db,
+            config: &self.config,
+            replication_config: &self.replication_config,
+            functions: &self.functions,
+            client_id,
+            publisher: &self.publisher,
+            runtime: &self.runtime,
+            trace,
+        };
+        match command.handle_command(writer, context, protocol).await {
+            Ok((written, failed)) => {
+                if let Some((_, call_failed)) = self.current_call.as_mut() {
+                    *call_failed |= failed;
+                }
+                self.stats.write().await.record_reply(command.name(), written);
+                if matches!(command, Command::PSYNC(_)) {
+                    self.register_replica(client_id).await;
+                }
+                if matches!(command, Command::CONFIG(_)) {
+                    self.sync_requirepass().await;
+                }
+            }
+            Err(e) => log_warning!("Failed to handle command: {}", e),
+        }
+        let call = CommandCall { client_id, client_addr, command: &command };
+        self.run_post_execute_hooks(&call, &CallResult { duration: started_at.elapsed() }).await;
+        self.collect_ready_keys(&command);
+        trace::record(trace, "reply", &format!("client={}", client_id));
+        if !self.executing_transaction {
+            self.serve_blocked_clients().await;
+        }
+    }
+
+    pub(crate) async fn run_hello(command: Command, context: EventHandlerContext<'_>) {
+        let EventHandlerContext { handler, client_id, .. } = context;
+        let Command::HELLO { protover, auth, setname } = &command else {
+            unreachable!("not a HELLO command");
+        };
+        if let Some(response) = handler.handle_hello(client_id, *protover, auth, setname).await {
+            handler.write_reply(client_id, command.name(), &response).await;
+        }
+    }
+
+    pub(crate) async fn run_auth(command: Command, context: EventHandlerContext<'_>) {
+        let EventHandlerContext { handler, client_id, .. } = context;
+        let Command::AUTH { username, password } = &command else {
+            unreachable!("not an AUTH command");
+        };
+        let response = match handler.authenticate(client_id, username.as_deref(), password).await {
+            Ok(()) => RespValue::ok(),
+            Err(e) => RespValue::from(e),
+        };
+        handler.write_reply(client_id, command.name(), &response).await;
+    }
+
+    pub(crate) async fn run_quit(command: Command, context: EventHandlerContext<'_>) {
+        let EventHandlerContext { handler, client_id, .. } = context;
+        handler.write_reply(client_id, command.name(), &RespValue::ok()).await;
+        if let Some(client) = handler.client_manager.get_client_mut(&client_id) {
+            client.kill();
+        }
+    }
+
+    pub(crate) async fn run_reset(_command: Command, context: EventHandlerContext<'_>) {
+        context.handler.reset_client(context.client_id).await;
+    }
+
+    // ASKING, READONLY, READWRITE: 클러스터 리디렉션을 정하는 연결 상태만 바꿈
+    pub(crate) async fn run_asking_readonly(command: Command, context: EventHandlerContext<'_>) {
+        let EventHandlerContext { handler, client_id, .. } = context;
+        let response = match command {
+            Command::ASKING => handler.handle_asking(client_id),
+            _ => handler.handle_readonly(client_id, matches!(command, Command::READONLY)),
+        };
+        handler.write_reply(client_id, command.name(), &response).await;
+    }
+
+    pub(crate) async fn run_transaction_control(command: Command, context: EventHandlerContext<'_>) {
+        context.handler.handle_transaction_control(context.client_id, command).await;
+    }
+
+    pub(crate) async fn run_unwatch(command: Command, context: EventHandlerContext<'_>) {
+        let EventHandlerContext { handler, client_id, .. } = context;
+        handler.watch_table.unwatch(client_id);
+        handler.write_reply(client_id, command.name(), &RespValue::ok()).await;
+    }
+
+    pub(crate) async fn run_wait(command: Command, context: EventHandlerContext<'_>) {
+        let Command::WAIT { numreplicas, timeout_ms } = command else {
+            unreachable!("not a WAIT command");
+        };
+        context.handler.handle_wait(context.client_id, numreplicas, timeout_ms).await;
+    }
+
+    pub(crate) async fn run_blocking(command: Command, context: EventHandlerContext<'_>) {
+        let EventHandlerContext { handler, client_id, trace } = context;
+        let Some(timeout_ms) = command.blocking_timeout_ms() else {
+            unreachable!("not a blocking command");
+        };
+        handler.handle_blocking_command(client_id, command, timeout_ms, trace).await;
+    }
+
+    pub(crate) async fn run_subscribe(command: Command, context: EventHandlerContext<'_>) {
+        let (Command::SUBSCRIBE(channels) | Command::PSUBSCRIBE(channels)) = &command else {
+            unreachable!("not a SUBSCRIBE command");
+        };
+        let pattern = matches!(command, Command::PSUBSCRIBE(_));
+        context.handler.handle_subscribe(context.client_id, channels, pattern).await;
+    }
+
+    pub(crate) async fn run_unsubscribe(command: Command, context: EventHandlerContext<'_>) {
+        let (Command::UNSUBSCRIBE(channels) | Command::PUNSUBSCRIBE(channels)) = &command else {
+            unreachable!("not an UNSUBSCRIBE command");
+        };
+        let pattern = matches!(command, Command::PUNSUBSCRIBE(_));
+        context.handler.handle_unsubscribe(context.client_id, channels, pattern).await;
+    }
+
+    pub(crate) async fn run_ssubscribe(command: Command, context: EventHandlerContext<'_>) {
+        let EventHandlerContext { handler, client_id, .. } = context;
+        let Command::SSUBSCRIBE(channels) = &command else {
+            unreachable!("not an SSUBSCRIBE command");
+        };
+        for channel in channels {
+            handler.shard_channels.subscribe(client_id, channel);
+            let response = pubsub::subscription_reply("ssubscribe", Some(channel), handler.shard_channels.count_for(client_id));
+            handler.write_reply(client_id, command.name(), &response).await;
+        }
+    }
+
+    pub(crate) async fn run_sunsubscribe(command: Command, context: EventHandlerContext<'_>) {
+        let EventHandlerContext { handler, client_id, .. } = context;
+        let Command::SUNSUBSCRIBE(channels) = &command else {
+            unreachable!("not an SUNSUBSCRIBE command");
+        };
+        let channels = if channels.is_empty() { handler.shard_channels.channels_of(client_id) } else { channels.clone() };
+        if channels.is_empty() {
+            let response = pubsub::subscription_reply("sunsubscribe", None, 0);
+            handler.write_reply(client_id, command.name(), &response).await;
+        }
+        for channel in channels {
+            handler.shard_channels.unsubscribe(client_id, &channel);
+            let response = pubsub::subscription_reply("sunsubscribe", Some(&channel), handler.shard_channels.count_for(client_id));
+            handler.write_reply(client_id, command.name(), &response).await;
+        }
+    }
+
+    pub(crate) async fn run_publish(command: Command, context: EventHandlerContext<'_>) {
+        let EventHandlerContext { handler, client_id, trace } = context;
+        let response = handler.handle_publish(&command, trace).await;
+        handler.write_reply(client_id, command.name(), &response).await;
+    }
+
+    pub(crate) async fn run_pubsub(command: Command, context: EventHandlerContext<'_>) {
+        let EventHandlerContext { handler, client_id, .. } = context;
+        let Command::PUBSUB(pubsub_command) = &command else {
+            unreachable!("not a PUBSUB command");
+        };
+        let response = handler.handle_pubsub(pubsub_command);
+        handler.write_reply(client_id, command.name(), &response).await;
+    }
+
+    pub(crate) async fn run_script(command: Command, context: EventHandlerContext<'_>) {
+        let EventHandlerContext { handler, client_id, .. } = context;
+        let Command::SCRIPT(script_command) = &command else {
+            unreachable!("not a SCRIPT command");
+        };
+        let response = handler.handle_script(script_command);
+        handler.write_reply(client_id, command.name(), &response).await;
+    }
+
+    pub(crate) async fn run_function(command: Command, context: EventHandlerContext<'_>) {
+        let EventHandlerContext { handler, client_id, trace } = context;
+        let Command::FUNCTION(function_command) = &command else {
+            unreachable!("not a FUNCTION command");
+        };
+        let (response, changed) = handler.handle_function(function_command).await;
+        if changed && handler.replication_config.read().await.get_role().await != "slave" {
+            if let Err(e) = handler.publisher.publish_propagate_slave(Self::function_replication_command(function_command), trace).await {
+                log_warning!("Failed to propagate FUNCTION: {}", e);
+            }
+        }
+        handler.write_reply(client_id, command.name(), &response).await;
+    }
+
+    // EVAL, EVALSHA, FCALL, FCALL_RO
+    pub(crate) async fn run_script_call(command: Command, context: EventHandlerContext<'_>) {
+        context.handler.handle_script_run(context.client_id, &command, context.trace).await;
+    }
+
+    pub(crate) async fn run_info(command: Command, context: EventHandlerContext<'_>) {
+        let EventHandlerContext { handler, client_id, .. } = context;
+        let Command::INFO(section) = &command else {
+            unreachable!("not an INFO command");
+        };
+        let response = RespValue::bulk(handler.build_info(section).await);
+        handler.write_reply(client_id, command.name(), &response).await;
+    }
+
+    pub(crate) async fn run_debug(command: Command, context: EventHandlerContext<'_>) {
+        let EventHandlerContext { handler, client_id, .. } = context;
+        let Command::DEBUG(debug_command) = &command else {
+            unreachable!("not a DEBUG command");
+        };
+        let response = handler.handle_debug(debug_command).await;
+        handler.write_reply(client_id, command.name(), &response).await;
+    }
+
+    pub(crate) async fn run_latency(command: Command, context: EventHandlerContext<'_>) {
+        let EventHandlerContext { handler, client_id, .. } = context;
+        let Command::LATENCY(latency_command) = &command else {
+            unreachable!("not a LATENCY command");
+        };
+        let response = handler.handle_latency(latency_command).await;
+        handler.write_reply(client_id, command.name(), &response).await;
+    }
+
+    pub(crate) async fn run_memory(command: Command, context: EventHandlerContext<'_>) {
+        let EventHandlerContext { handler, client_id, .. } = context;
+        let Command::MEMORY(memory_command) = &command else {
+            unreachable!("not a MEMORY command");
+        };
+        let response = handler.handle_memory(memory_command).await;
+        handler.write_reply(client_id, command.name(), &response).await;
+    }
+
+    pub(crate) async fn run_client(command: Command, context: EventHandlerContext<'_>) {
+        let EventHandlerContext { handler, client_id, .. } = context;
+        let Command::CLIENT(client_command) = &command else {
+            unreachable!("not a CLIENT command");
+        };
+        let response = handler.handle_client(client_id, client_command);
+        handler.write_reply(client_id, command.name(), &response).await;
+    }
+
+    pub(crate) async fn run_acl(command: Command, context: EventHandlerContext<'_>) {
+        let EventHandlerContext { handler, client_id, .. } = context;
+        let Command::ACL(acl_command) = &command else {
+            unreachable!("not an ACL command");
+        };
+        let response = handler.handle_acl(client_id, acl_command).await;
+        handler.write_reply(client_id, command.name(), &response).await;
+    }
+
+    pub(crate) async fn run_cluster(command: Command, context: EventHandlerContext<'_>) {
+        let EventHandlerContext { handler, client_id, .. } = context;
+        let Command::CLUSTER(cluster_command) = &command else {
+            unreachable!("not a CLUSTER command");
+        };
+        let response = handler.handle_cluster(cluster_command).await;
+        handler.write_reply(client_id, command.name(), &response).await;
+    }
+
+    pub(crate) async fn run_sentinel(command: Command, context: EventHandlerContext<'_>) {
+        let EventHandlerContext { handler, client_id, .. } = context;
+        let Command::SENTINEL(sentinel_command) = &command else {
+            unreachable!("not a SENTINEL command");
+        };
+        let response = handler.handle_sentinel(sentinel_command);
+        handler.write_reply(client_id, command.name(), &response).await;
+    }
+
+    pub(crate) async fn run_replicaof(command: Command, context: EventHandlerContext<'_>) {
+        let (Command::REPLICAOF(target) | Command::SLAVEOF(target)) = command else {
+            unreachable!("not a REPLICAOF command");
+        };
+        context.handler.handle_replicaof(context.client_id, target).await;
+    }
+
+    pub(crate) async fn run_bgsave(command: Command, context: EventHandlerContext<'_>) {
+        let EventHandlerContext { handler, client_id, .. } = context;
+        let response = match handler.start_background_save().await {
+            Ok(()) => RespValue::SimpleString(BGSAVE_STARTED_REPLY.into()),
+            Err(e) => RespValue::error(&e),
+        };
+        handler.write_reply(client_id, command.name(), &response).await;
+    }
+
+    pub(crate) async fn run_lastsave(command: Command, context: EventHandlerContext<'_>) {
+        let EventHandlerContext { handler, client_id, .. } = context;
+        let response = RespValue::Integer(handler.persistence.last_save_time() as i64);
+        handler.write_reply(client_id, command.name(), &response).await;
+    }
+
+    pub(crate) async fn run_shutdown(command: Command, context: EventHandlerContext<'_>) {
+        let Command::SHUTDOWN(save) = command else {
+            unreachable!("not a SHUTDOWN command");
+        };
+        context.handler.request_shutdown(Some(context.client_id), save);
+    }
+
+    // 대기 중인 클라이언트가 있는 키에 쓴 명령이면 명령(또는 EXEC, 스크립트)이 끝난 뒤 깨우도록 모아 둠
+    fn collect_ready_keys(&mut self, command: &Command) {
+        if command.category() == CommandCategory::Write {
+            let ready: Vec<Vec<u8>> = command.keys().into_iter().filter(|key| self.blocking.is_watched(key)).cloned().collect();
+            self.ready_keys.extend(ready);
+        }
+    }
+
+    // EVAL/EVALSHA/FCALL/FCALL_RO, 스크립트가 부른 명령의 전파는 EXEC처럼 MULTI/EXEC로 감싸서 레플리카도 한 번에 적용하게 함
+    async fn handle_script_run(&mut self, client_id: u64, command: &Command, trace: Option<TraceContext>) {
+        // 스크립트가 오래 돌면 연결 태스크가 다른 클라이언트에게 BUSY를 바로 보내므로, 그보다 앞선 응답을 먼저 내보냄
+        self.flush_clients().await;
+        let (mut run, read_only) = match self.start_script_run(client_id, command).await {
+            Ok(started) => started,
+            Err(response) => {
+                self.write_reply(client_id, command.name(), &response).await;
+                return;
+            }
+        };
+
+        // EXEC 안이면 이미 감싸는 중이므로 그대로 둠
+        let wrap = !self.executing_transaction;
+        if wrap {
+            self.propagate_transaction(false).await;
+        }
+        let response = loop {
+            match run.next().await {
+                ScriptStep::Call { args, reply } => {
+                    let response = self.execute_script_call(client_id, &args, read_only, trace).await;
+                    let _ = reply.send(response);
+                }
+                ScriptStep::Done(response) => break response,
             }
+        };
+        self.script_monitor.finish();
+        if wrap {
+            self.propagate_transaction(true).await;
+        }
+        self.write_reply(client_id, command.name(), &response).await;
+        if wrap {
+            self.serve_blocked_clients().await;
+        }
+    }
+
+    // 실행할 스크립트와 쓰기를 막을지, 함수는 no-writes 플래그가 있으면 쓰기를 막음
+    async fn start_script_run(&mut self, client_id: u64, command: &Command) -> Result<(ScriptRun, bool), RespValue> {
+        let busy_after = Duration::from_millis(
+            self.config
+                .read()
+                .await
+                .get("busy_reply_threshold")
+                .and_then(|threshold| threshold.parse::<u64>().ok())
+                .unwrap_or(DEFAULT_BUSY_REPLY_THRESHOLD_MS),
+        );
+        let function = matches!(command, Command::FCALL { .. } | Command::FCALLRO { .. });
+        match command {
+            Command::EVAL { script, keys, args } => {
+                let sha = self.scripts.load(script);
+                let interrupt = self.script_monitor.start(client_id, function, busy_after);
+                Ok((ScriptRun::eval(script.clone(), sha, keys.clone(), args.clone(), interrupt), false))
+            }
+            Command::EVALSHA { sha, keys, args } => match self.scripts.get(sha) {
+                Some(script) => {
+                    let interrupt = self.script_monitor.start(client_id, function, busy_after);
+                    Ok((ScriptRun::eval(script.to_string(), sha.clone(), keys.clone(), args.clone(), interrupt), false))
+                }
+                None => Err(RespValue::from(RedisError::NoScript)),
+            },
+            Command::FCALL { function, keys, args } | Command::FCALLRO { function, keys, args } => {
+                let functions = self.functions.read().await;
+                let Some((library, info)) = functions.find(function) else {
+                    return Err(RespValue::error(FUNCTION_NOT_FOUND_ERROR));
+                };
+                if matches!(command, Command::FCALLRO { .. }) && !info.no_writes() {
+                    return Err(RespValue::error(FUNCTION_READ_ONLY_ERROR));
+                }
+                let interrupt = self.script_monitor.start(client_id, true, busy_after);
+                let run = ScriptRun::function(library.code.clone(), function.clone(), keys.clone(), args.clone(), interrupt);
+                Ok((run, info.no_writes()))
+            }
+            _ => unreachable!("not a script command"),
+        }
+    }
+
+    // 스크립트의 redis.call/pcall, 클라이언트 명령과 같은 검사를 거치고 응답은 클라이언트 대신 스크립트에 돌려줌
+    // 블로킹 명령은 기다리지 않고 MULTI 안에서처럼 바로 nil을 돌려줌
+    async fn execute_script_call(&mut self, client_id: u64, args: &[Vec<u8>], read_only: bool, trace: Option<TraceContext>) -> RespValue {
+        let Some(handler) = args.first().and_then(|name| command_registry::lookup(&String::from_utf8_lossy(name))) else {
+            return RespValue::error(SCRIPT_UNKNOWN_COMMAND_ERROR);
+        };
+        if !handler.spec().accepts_arity(args.len()) {
+            return RespValue::error(SCRIPT_WRONG_ARITY_ERROR);
+        }
+        let mut command = match CommandParser::parse_args(args) {
+            Ok(command) => command,
+            Err(ArgumentError::General(message)) => return RespValue::error(&message),
+        };
+        if command.spec().has_flag(CMD_NOSCRIPT) {
+            return RespValue::error(SCRIPT_COMMAND_NOT_ALLOWED_ERROR);
+        }
+        if read_only && command.has_flag(CMD_WRITE) {
+            return RespValue::error(SCRIPT_READ_ONLY_WRITE_ERROR);
+        }
+        let Some(client_addr) = self.client_manager.get_client(client_id).map(|client| client.addr) else {
+            return RespValue::error(SCRIPT_ABORTED_ERROR);
+        };
+        if let Err(response) = self.check_script_call(client_id, &mut command).await {
+            return response;
+        }
+
+        if command.category() == CommandCategory::Write {
+            self.script_monitor.record_write();
         }
+        let started_at = Instant::now();
+        let response = match &command {
+            _ if command.blocking_timeout_ms().is_some() => match self.serve_blocking_command(&command, trace).await {
+                Ok(Some(response)) => response,
+                Ok(None) => command.blocking_nil_response(),
+                Err(e) => RespValue::from(e),
+            },
+            Command::PUBLISH { .. } | Command::SPUBLISH { .. } => self.handle_publish(&command, trace).await,
+            _ => {
+                if command.category() == CommandCategory::Read {
+                    self.record_keyspace_lookups(&command).await;
+                }
+                let context = ExecutionContext {
+                    db: &self.db,
+                    config: &self.config,
+                    replication_config: &self.replication_config,
+                    functions: &self.functions,
+                    client_id,
+                    publisher: &self.publisher,
+                    runtime: &self.runtime,
+                    trace,
+                };
+                match command.execute(context).await {
+                    Ok(responses) => responses
+                        .into_iter()
+                        .find_map(|response| match response {
+                            CommandResponse::Value(value) => Some(value),
+                            CommandResponse::Rdb(_) => None,
+                        })
+                        .unwrap_or(RespValue::NullBulk),
+                    Err(e) => RespValue::from(e),
+                }
+            }
+        };
+        let duration = started_at.elapsed();
+        self.stats.write().await.record_call(command.name(), duration.as_micros() as u64, matches!(response, RespValue::Error(_)));
+        let call = CommandCall { client_id, client_addr, command: &command };
+        self.run_post_execute_hooks(&call, &CallResult { duration }).await;
+        self.collect_ready_keys(&command);
+        response
+    }
+
+    // 스크립트 안의 명령도 클라이언트가 직접 보낸 것처럼 ACL, 방화벽, 클러스터 슬롯, 레플리카 쓰기, maxmemory를 확인함
+    async fn check_script_call(&mut self, client_id: u64, command: &mut Command) -> Result<(), RespValue> {
+        let Some(client) = self.client_manager.get_client(client_id) else {
+            return Err(RespValue::error(SCRIPT_ABORTED_ERROR));
+        };
+        let permitted = match self.acl.user(&client.user) {
+            Some(user) => user.check(command).and_then(|()| user.confine(command)),
+            None => Err(RedisError::NoPerm(format!("User {} no longer exists", client.user))),
+        };
+        if let Err(e) = permitted {
+            log_notice!("[acl] denied {} for user {} (client {}) in a script", command.name(), client.user, client.label());
+            return Err(RespValue::from(e));
+        }
+        if !self.firewall.is_allowed(client.addr.ip(), command.category()) {
+            log_notice!("[firewall] denied {} from {} (client {}) in a script", command.name(), client.addr, client.label());
+            return Err(RespValue::error(&format!("command '{}' is not allowed from {}", command.name(), client.addr.ip())));
+        }
+        // 스크립트가 부르는 키는 리다이렉트할 수 없으므로 이 노드가 처리하는 슬롯이어야 함
+        if let Some(cluster) = self.cluster.as_ref() {
+            match cluster.route(&command.keys(), true, 0, false) {
+                Ok(()) => {}
+                Err(RedisError::CrossSlot) => return Err(RespValue::error(SCRIPT_CROSS_SLOT_ERROR)),
+                Err(_) => return Err(RespValue::error(SCRIPT_NON_LOCAL_KEY_ERROR)),
+            }
+        }
+        if command.has_flag(CMD_WRITE) && self.replication_config.read().await.get_role().await == "slave" {
+            return Err(RespValue::from(RedisError::ReadOnly));
+        }
+        if command.has_flag(CMD_DENYOOM) && !self.free_memory_for_write().await {
+            return Err(RespValue::from(RedisError::Oom));
+        }
+        self.check_namespace_quota(client_id, command).await.map_err(RespValue::from)
+    }
+
+    // 네임스페이스 사용자의 maxkeys/maxmemory, maxmemory처럼 DENYOOM 명령만 막으므로 지워서 할당량 아래로 내려올 수 있음
+    // maxkeys는 새 키를 만드는 명령만 막고, 명령의 키는 이미 접두사가 붙은 상태임
+    pub(crate) async fn check_namespace_quota(&self, client_id: u64, command: &Command) -> Result<(), RedisError> {
+        if !command.has_flag(CMD_DENYOOM) {
+            return Ok(());
+        }
+        let Some(namespace) = self
+            .client_manager
+            .get_client(client_id)
+            .and_then(|client| self.acl.user(&client.user))
+            .and_then(AclUser::namespace)
+        else {
+            return Ok(());
+        };
+        let db = self.db.read().await;
+        let usage = db.namespace_usage(namespace.prefix.as_bytes()).unwrap_or_default();
+        if namespace.max_memory.is_some_and(|max_memory| usage.memory >= max_memory) {
+            return Err(RedisError::NamespaceQuota("maxmemory"));
+        }
+        let creates_key = || command.keys().into_iter().any(|key| !db.is_alive(key));
+        if namespace.max_keys.is_some_and(|max_keys| usage.keys >= max_keys) && creates_key() {
+            return Err(RedisError::NamespaceQuota("maxkeys"));
+        }
+        Ok(())
+    }
+
+    async fn record_keyspace_lookups(&self, command: &Command) {
+        let db = self.db.read().await;
+        let mut stats = self.stats.write().await;
+        for key in command.keys() {
+            stats.record_keyspace_lookup(db.get(key).is_some_and(|entry| !entry.is_expired()));
+        }
+    }
+
+    // 클라이언트가 없어졌으면 훅을 부르지 않음
+    async fn run_pre_execute_hooks(&mut self, client_id: u64, command: &Command) -> Result<(), RespValue> {
+        let Some(client_addr) = self.client_manager.get_client(client_id).map(|client| client.addr) else {
+            return Ok(());
+        };
+        let call = CommandCall { client_id, client_addr, command };
+        for hook in self.hooks.hooks() {
+            hook.pre_execute(self, &call).await?;
+        }
+        Ok(())
+    }
+
+    async fn run_post_execute_hooks(&mut self, call: &CommandCall<'_>, result: &CallResult) {
+        for hook in self.hooks.hooks() {
+            hook.post_execute(self, call, result).await;
+        }
+    }
+
+    async fn run_expire_hooks(&mut self, key: &[u8]) {
+        for hook in self.hooks.hooks() {
+            hook.on_expire(self, key).await;
+        }
+    }
+
+    pub(crate) async fn record_command_duration(&mut self, name: &str, client_addr: SocketAddr, duration: Duration) {
+        let duration_us = duration.as_micros() as u64;
+        if duration_us >= self.slowlog_threshold_us().await {
+            self.stats.write().await.record_slow_command(name, client_addr.to_string(), duration_us);
+        }
+    }
+
+    pub(crate) fn track_read_keys(&mut self, client_id: u64, command: &Command) {
+        let Some(client) = self.client_manager.get_client(client_id) else {
+            return;
+        };
+        if client.tracking.as_ref().is_some_and(|options| options.tracks_read(client.caching_applies())) {
+            self.tracking_table.track(client_id, &command.keys());
+        }
+    }
+
+    // 바로 꺼낼 수 있으면 응답하고, 아니면 타임아웃까지 대기시킴. MULTI 안에서는 Redis처럼 기다리지 않고 nil을 돌려줌
+    async fn handle_blocking_command(&mut self, client_id: u64, command: Command, timeout_ms: u64, trace: Option<TraceContext>) {
+        let response = match self.serve_blocking_command(&command, trace).await {
+            Ok(Some(response)) => response,
+            Ok(None) if self.executing_transaction => command.blocking_nil_response(),
+            Ok(None) => {
+                let deadline_ms = (timeout_ms > 0).then(|| current_time_ms().saturating_add(timeout_ms));
+                self.blocking.block(client_id, BlockedClient { command, deadline_ms, trace });
+                return;
+            }
+            Err(e) => RespValue::from(e),
+        };
+        self.write_reply(client_id, command.name(), &response).await;
+        if !self.executing_transaction {
+            self.serve_blocked_clients().await;
+        }
+    }
+
+    async fn serve_blocking_command(&mut self, command: &Command, trace: Option<TraceContext>) -> Result<Option<RespValue>, RedisError> {
+        match command {
+            Command::BLPOP { .. } | Command::BRPOP { .. } => self.serve_blocking_pop(command, trace).await,
+            Command::BLMPOP { .. } | Command::BZMPOP { .. } => self.serve_blocking_multi_pop(command, trace).await,
+            _ => self.serve_blocking_move(command, trace).await,
+        }
+    }
+
+    // 레플리카에는 BLPOP 대신 실제로 일어난 LPOP/RPOP을 전파함
+    async fn serve_blocking_pop(&mut self, command: &Command, trace: Option<TraceContext>) -> Result<Option<RespValue>, RedisError> {
+        let (popped, key_removed) = {
+            let mut db = self.db.write().await;
+            let popped = command.execute_blocking_pop(&mut db)?;
+            let key_removed = popped.as_ref().is_some_and(|(key, _)| !db.contains_key(key));
+            (popped, key_removed)
+        };
+        let Some((key, value)) = popped else {
+            return Ok(None);
+        };
+
+        let (pop_command, event) = match command {
+            Command::BLPOP { .. } => (LPOP_COMMAND, LPOP_EVENT),
+            _ => (RPOP_COMMAND, RPOP_EVENT),
+        };
+        if self.replication_config.read().await.get_role().await != "slave" {
+            if let Err(e) = self.publisher.publish_propagate_slave(construct_redis_command(&[pop_command.as_bytes(), &key]), trace).await {
+                log_warning!("Failed to propagate {}: {}", pop_command, e);
+            }
+        }
+        self.notify_keyspace_event(notify::NOTIFY_LIST, event, &key).await;
+        if key_removed {
+            self.notify_keyspace_event(notify::NOTIFY_GENERIC, DEL_EVENT, &key).await;
+        }
+        self.invalidate_keys(&[&key], None).await;
+        Ok(Some(RespValue::bulk_array(&[key, value])))
+    }
+
+    async fn serve_blocking_multi_pop(&mut self, command: &Command, trace: Option<TraceContext>) -> Result<Option<RespValue>, RedisError> {
+        let outcome = command.execute_multi_pop(&mut *self.db.write().await)?;
+        let Some(outcome) = outcome else {
+            return Ok(None);
+        };
+
+        if self.replication_config.read().await.get_role().await != "slave" {
+            if let Err(e) = self.publisher.publish_propagate_slave(outcome.replication, trace).await {
+                log_warning!("Failed to propagate {}: {}", command.name(), e);
+            }
+        }
+        self.notify_keyspace_event(outcome.class, outcome.event, &outcome.key).await;
+        if outcome.key_removed {
+            self.notify_keyspace_event(notify::NOTIFY_GENERIC, DEL_EVENT, &outcome.key).await;
+        }
+        self.invalidate_keys(&[&outcome.key], None).await;
+        Ok(Some(outcome.response))
+    }
+
+    // 옮긴 원소로 destination을 기다리던 다른 클라이언트도 깨어날 수 있도록 ready_keys에 넣음
+    async fn serve_blocking_move(&mut self, command: &Command, trace: Option<TraceContext>) -> Result<Option<RespValue>, RedisError> {
+        let (source, destination, from, to) = command.move_args();
+        let (moved, source_removed) = {
+            let mut db = self.db.write().await;
+            let moved = command.execute_move(&mut db)?;
+            (moved, !db.contains_key(source))
+        };
+        let Some(value) = moved else {
+            return Ok(None);
+        };
+
+        if self.replication_config.read().await.get_role().await != "slave" {
+            if let Err(e) = self.publisher.publish_propagate_slave(command.move_replication_command(), trace).await {
+                log_warning!("Failed to propagate {}: {}", LMOVE_COMMAND, e);
+            }
+        }
+        let (pop_event, push_event) = Command::move_events(from, to);
+        self.notify_keyspace_event(notify::NOTIFY_LIST, pop_event, source).await;
+        self.notify_keyspace_event(notify::NOTIFY_LIST, push_event, destination).await;
+        if source_removed {
+            self.notify_keyspace_event(notify::NOTIFY_GENERIC, DEL_EVENT, source).await;
+        }
+        self.invalidate_keys(&[source, destination], None).await;
+        if self.blocking.is_watched(destination) {
+            self.ready_keys.push(destination.clone());
+        }
+        Ok(Some(RespValue::bulk(value)))
+    }
+
+    // 준비된 키마다 먼저 대기한 클라이언트부터 리스트가 빌 때까지 차례로 깨움
+    async fn serve_blocked_clients(&mut self) {
+        while !self.ready_keys.is_empty() {
+            for key in std::mem::take(&mut self.ready_keys) {
+                loop {
+                    // 빈 리스트/정렬 집합은 지워지므로 키가 있으면 꺼낼 원소가 있음
+                    let value_type = match self.db.read().await.get(&key) {
+                        Some(entry) if !entry.is_expired() => entry.value.type_name(),
+                        _ => break,
+                    };
+                    let waiter = self.blocking.first_waiter(&key, |blocked| blocked.command.blocking_value_type() == value_type);
+                    let Some((waiter, blocked)) = waiter.and_then(|waiter| self.blocking.unblock(waiter).map(|blocked| (waiter, blocked))) else {
+                        break;
+                    };
+                    let response = match self.serve_blocking_command(&blocked.command, blocked.trace).await {
+                        Ok(Some(response)) => response,
+                        Ok(None) => {
+                            self.blocking.block(waiter, blocked);
+                            break;
+                        }
+                        Err(e) => RespValue::from(e),
+                    };
+                    self.write_reply(waiter, blocked.command.name(), &response).await;
+                }
+            }
+        }
+    }
+
+    // 이미 충분한 레플리카가 따라왔으면 바로 응답하고, 아니면 GETACK을 보내고 ACK나 타임아웃을 기다림
+    async fn handle_wait(&mut self, client_id: u64, numreplicas: usize, timeout_ms: u64) {
+        let repl_guard = self.replication_config.read().await;
+        if repl_guard.get_role().await == "slave" {
+            drop(repl_guard);
+            self.write_reply(client_id, WAIT_COMMAND, &RespValue::error(WAIT_ON_REPLICA_ERROR)).await;
+            return;
+        }
+
+        let slaves = repl_guard.list_slaves().await;
+        let targets: Vec<(u64, i64)> = slaves.iter().map(|slave| (slave.id, slave.sent_offset)).collect();
+        let acked = slaves.iter().filter(|slave| slave.offset >= slave.sent_offset).count();
+        if acked >= numreplicas || self.executing_transaction {
+            drop(slaves);
+            drop(repl_guard);
+            self.write_reply(client_id, WAIT_COMMAND, &RespValue::Integer(acked as i64)).await;
+            return;
+        }
+
+        drop(slaves);
+        drop(repl_guard);
+        self.request_replica_acks().await;
+        let deadline_ms = (timeout_ms > 0).then(|| current_time_ms().saturating_add(timeout_ms));
+        self.replica_waits.push(ReplicaWait { client_id, numreplicas, targets, deadline_ms });
+    }
+
+    // ACK가 올 때마다 확인하고, 주기적인 tick에서는 타임아웃된 WAIT에 그때까지의 수를 돌려줌
+    async fn resolve_replica_waits(&mut self, check_timeouts: bool) {
+        if self.replica_waits.is_empty() {
+            return;
+        }
+        let now_ms = current_time_ms();
+        let mut replies = Vec::new();
+        {
+            let repl_guard = self.replication_config.read().await;
+            let slaves = repl_guard.list_slaves().await;
+            self.replica_waits.retain(|wait| {
+                let acked = wait
+                    .targets
+                    .iter()
+                    .filter(|(id, target)| slaves.iter().any(|slave| slave.id == *id && slave.offset >= *target))
+                    .count();
+                let timed_out = check_timeouts && wait.deadline_ms.is_some_and(|deadline| deadline <= now_ms);
+                if acked >= wait.numreplicas || timed_out {
+                    replies.push((wait.client_id, acked));
+                    return false;
+                }
+                true
+            });
+        }
+        for (client_id, acked) in replies {
+            self.write_reply(client_id, WAIT_COMMAND, &RespValue::Integer(acked as i64)).await;
+        }
+    }
+
+    async fn expire_blocked_clients(&mut self) {
+        for client_id in self.blocking.timed_out(current_time_ms()) {
+            if let Some(blocked) = self.blocking.unblock(client_id) {
+                self.write_reply(client_id, blocked.command.name(), &blocked.command.blocking_nil_response()).await;
+            }
+        }
+    }
+
+    // 시점 복사본은 여기서 만들고 파일 쓰기만 blocking 스레드로 넘겨서 그동안에도 명령을 계속 처리함
+    async fn start_background_save(&mut self) -> Result<(), String> {
+        if !self.persistence.start_bgsave() {
+            return Err(BGSAVE_IN_PROGRESS_ERROR.into());
+        }
+        // 스냅샷을 뜨는 동안 명령 처리가 멈추므로 Redis의 fork처럼 지연으로 기록함
+        let started_at = Instant::now();
+        let entries = persistence::snapshot(&*self.db.read().await);
+        let aux = self.replication_config.read().await.rdb_aux_fields().await;
+        let functions = self.functions.read().await.codes();
+        self.record_latency(LATENCY_EVENT_FORK, started_at.elapsed()).await;
+        let (path, compress) = {
+            let config = self.config.read().await;
+            (persistence::rdb_file_path(&config), persistence::rdb_compression(&config))
+        };
+        log_notice!("Background saving started: {} keys to {}", entries.len(), path.display());
+        let publisher = self.publisher.clone();
+        logging::spawn(async move {
+            let result = logging::spawn_blocking(move || persistence::write_rdb_file(&path, &entries, &aux, &functions, compress))
+                .await
+                .map_err(|e| e.to_string())
+                .and_then(|written| written.map_err(|e| e.to_string()));
+            if let Err(e) = publisher.publish_background_save_finished(result).await {
+                log_warning!("{}", e);
+            }
+        });
+        Ok(())
+    }
+
+    // 먼저 온 요청만 남김, 나중 요청은 어차피 같은 종료를 기다림
+    fn request_shutdown(&mut self, client_id: Option<u64>, save: Option<bool>) {
+        if self.shutdown_request.is_none() {
+            self.shutdown_request = Some((client_id, save));
+        }
+    }
+
+    pub fn shutdown_requested(&self) -> bool {
+        self.shutdown_request.is_some()
+    }
+
+    // 이벤트 루프가 새 요청을 막고 큐에 남은 이벤트를 다 처리한 뒤 부름
+    // 종료해도 되면 true, 저장에 실패하면 요청한 클라이언트에게 에러를 보내고 계속 서비스함
+    pub async fn finish_shutdown(&mut self) -> bool {
+        let Some((client_id, save)) = self.shutdown_request.take() else {
+            return false;
+        };
+        match self.shutdown(save).await {
+            Ok(()) => true,
+            Err(e) => {
+                log_warning!("{}", e);
+                if let Some(client_id) = client_id {
+                    self.write_reply(client_id, SHUTDOWN_COMMAND, &RespValue::error(SHUTDOWN_ERROR)).await;
+                }
+                self.flush_clients().await;
+                false
+            }
+        }
+    }
+
+    // 종료 직전 저장은 명령 처리를 멈춘 상태에서 바로 파일에 씀, 저장에 실패하면 종료하지 않음
+    async fn shutdown(&mut self, save: Option<bool>) -> Result<(), String> {
+        let save = match save {
+            Some(save) => save,
+            None => !persistence::save_points(&*self.config.read().await).is_empty(),
+        };
+        if save {
+            let entries = persistence::snapshot(&*self.db.read().await);
+            let aux = self.replication_config.read().await.rdb_aux_fields().await;
+            let functions = self.functions.read().await.codes();
+            let (path, compress) = {
+                let config = self.config.read().await;
+                (persistence::rdb_file_path(&config), persistence::rdb_compression(&config))
+            };
+            log_notice!("Saving the final RDB snapshot before exiting: {} keys to {}", entries.len(), path.display());
+            persistence::write_rdb_file(&path, &entries, &aux, &functions, compress)
+                .map_err(|e| format!("Error trying to save the DB, can't exit: {}", e))?;
+            log_notice!("DB saved on disk");
+        }
+        // 종료 전에 큐에서 마저 처리한 명령의 응답도 보냄
+        self.flush_clients().await;
+        self.finish_client_outputs().await;
+        self.flush_replicas_before_exit().await;
+        log_notice!("Redis is now ready to exit, bye bye...");
+        Ok(())
+    }
+
+    // 전송 태스크가 남은 응답을 다 쓸 때까지 기다림, 읽지 않는 클라이언트 때문에 종료가 늦어지지 않도록 전체를 shutdown-timeout으로 제한함
+    async fn finish_client_outputs(&mut self) {
+        let timeout = self.shutdown_timeout().await;
+        let deadline = Instant::now() + timeout;
+        let client_ids: Vec<u64> = self.client_manager.list_clients().iter().map(|client| client.id).collect();
+        for client_id in client_ids {
+            if let Some(client) = self.client_manager.get_client_mut(&client_id) {
+                if !client.finish_output(deadline.saturating_duration_since(Instant::now())).await {
+                    log_warning!("Client {} did not receive all replies before shutdown", client.label());
+                }
+            }
+        }
+    }
+
+    async fn shutdown_timeout(&self) -> Duration {
+        let seconds = self
+            .config
+            .read()
+            .await
+            .get("shutdown_timeout")
+            .and_then(|seconds| seconds.parse::<u64>().ok())
+            .unwrap_or(DEFAULT_SHUTDOWN_TIMEOUT_SECS);
+        Duration::from_secs(seconds)
+    }
+
+    // Redis flushSlavesOutputBuffers처럼 레플리카가 종료 직전까지의 복제 스트림을 모두 받게 함
+    // 레플리카마다 shutdown-timeout 초까지만 기다리고, 그래도 남으면 버리고 종료함
+    async fn flush_replicas_before_exit(&mut self) {
+        let timeout = self.shutdown_timeout().await;
+        let replicas: Vec<(u64, String)> = {
+            let repl_guard = self.replication_config.read().await;
+            let slaves = repl_guard.get_slaves_mut().await;
+            slaves.iter().map(|slave| (slave.client_id, slave.addr.to_string())).collect()
+        };
+        if replicas.is_empty() {
+            return;
+        }
+        log_notice!("Flushing output to {} replica(s) before shutting down", replicas.len());
+        for (client_id, addr) in replicas {
+            let Some(client) = self.client_manager.get_client_mut(&client_id) else {
+                continue;
+            };
+            let pending = client.output_buffer_bytes();
+            if !client.drain_replica_output(timeout).await {
+                log_warning!("Replica {} did not receive {} pending bytes before shutdown", addr, pending);
+            }
+        }
+    }
+
+    // Redis serverCron처럼 저장 지점 중 하나라도 만족하면 BGSAVE를 시작함
+    async fn check_save_points(&mut self) {
+        let save_points = persistence::save_points(&*self.config.read().await);
+        let Some((seconds, changes)) = self.persistence.reached_save_point(&save_points) else {
+            return;
+        };
+        log_notice!("{} changes in {} seconds. Saving...", changes, seconds);
+        if let Err(e) = self.start_background_save().await {
+            log_warning!("Failed to start automatic background save: {}", e);
+        }
+    }
+
+    fn cluster_cron(&mut self) {
+        if let Some(cluster) = self.cluster.as_mut() {
+            for (addr, message) in cluster.cron(current_time_ms()) {
+                self.cluster_bus.send(&addr, &message);
+            }
+        }
+    }
+
+    fn sentinel_cron(&mut self) {
+        if let Some(sentinel) = self.sentinel.as_mut() {
+            for request in sentinel.cron(current_time_ms()) {
+                self.sentinel_links.send(request);
+            }
+            self.sentinel_links.sync_hello(sentinel.hello_subscriptions());
+        }
+    }
+
+    fn handle_sentinel(&mut self, sentinel_command: &SentinelCommand) -> RespValue {
+        let Some(sentinel) = self.sentinel.as_mut() else {
+            return RespValue::error(SENTINEL_DISABLED_ERROR);
+        };
+        let now = current_time_ms();
+        let result = match sentinel_command {
+            SentinelCommand::MASTERS => return sentinel.masters_reply(now),
+            SentinelCommand::MYID => return RespValue::bulk(sentinel.myid()),
+            SentinelCommand::GETMASTERADDRBYNAME(name) => return sentinel.master_addr_reply(name),
+            SentinelCommand::ISMASTERDOWNBYADDR { ip, port, epoch, runid } => {
+                return sentinel.is_master_down_by_addr(ip, *port, *epoch, runid, now)
+            }
+            SentinelCommand::MASTER(name) => sentinel.master_reply(name, now),
+            SentinelCommand::REPLICAS(name) => sentinel.replicas_reply(name, now),
+            SentinelCommand::SENTINELS(name) => sentinel.sentinels_reply(name, now),
+            SentinelCommand::CKQUORUM(name) => sentinel.ckquorum(name, now),
+            SentinelCommand::FAILOVER(name) => sentinel.force_failover(name, now).map(|_| RespValue::ok()),
+            SentinelCommand::MONITOR { name, ip, port, quorum } => {
+                sentinel.monitor(name, ip, *port, *quorum, now).map(|_| RespValue::ok())
+            }
+            SentinelCommand::REMOVE(name) => sentinel.remove(name).map(|_| RespValue::ok()),
+        };
+        result.unwrap_or_else(RespValue::from)
+    }
+
+    // 프로토콜을 바꾸고 연결 정보를 RESP3에서는 맵으로, RESP2에서는 키와 값을 번갈아 담은 배열로 돌려줌
+    async fn handle_hello(&mut self, client_id: u64, protover: Option<i64>, auth: &Option<(String, String)>, setname: &Option<String>) -> Option<RespValue> {
+        if protover.is_some_and(|protover| protover != RESP2_PROTOCOL as i64 && protover != RESP3_PROTOCOL as i64) {
+            return Some(RespValue::from(RedisError::NoProto));
+        }
+        if let Some((username, password)) = auth {
+            if let Err(e) = self.authenticate(client_id, Some(username), password).await {
+                return Some(RespValue::from(e));
+            }
+        }
+        if setname.as_ref().is_some_and(|name| !Client::is_valid_name(name)) {
+            return Some(RespValue::error(INVALID_CLIENT_NAME_ERROR));
+        }
+        let client = self.client_manager.get_client_mut(&client_id)?;
+        if !client.authenticated {
+            return Some(RespValue::from(RedisError::NoAuth));
+        }
+        if let Some(protover) = protover {
+            client.protocol = protover as u8;
+        }
+        if let Some(name) = setname {
+            client.name = (!name.is_empty()).then(|| name.clone());
+        }
+        let protocol = client.protocol;
+
+        let mode = if self.sentinel.is_some() {
+            "sentinel"
+        } else if self.cluster.is_some() {
+            "cluster"
+        } else {
+            "standalone"
+        };
+        let role = match self.replication_config.read().await.get_role().await.as_str() {
+            "slave" => "replica",
+            _ => "master",
+        };
+        Some(RespValue::field_map(vec![
+            ("server", RespValue::bulk("redis")),
+            ("version", RespValue::bulk(SERVER_VERSION)),
+            ("proto", RespValue::Integer(protocol as i64)),
+            ("id", RespValue::Integer(client_id as i64)),
+            ("mode", RespValue::bulk(mode)),
+            ("role", RespValue::bulk(role)),
+            ("modules", RespValue::Array(Vec::new())),
+        ]))
+    }
+
+    // 트랜잭션, 구독, 추적, 클라이언트 이름, RESP 버전, 클러스터 READONLY를 처음 상태로 되돌리고 default 사용자로 돌아감
+    // default 사용자에게 비밀번호가 있으면 다시 AUTH해야 함
+    async fn reset_client(&mut self, client_id: u64) {
+        self.shard_channels.remove_client(client_id);
+        self.tracking_table.remove_client(client_id);
+        self.watch_table.unwatch(client_id);
+        self.sync_requirepass().await;
+        let authenticated = self.acl.default_user().is_nopass();
+        let Some(client) = self.client_manager.get_client_mut(&client_id) else {
+            return;
+        };
+        client.transaction = None;
+        client.subscriptions.clear();
+        client.pattern_subscriptions.clear();
+        client.tracking = None;
+        client.caching = None;
+        client.asking = false;
+        client.readonly = false;
+        client.protocol = RESP2_PROTOCOL;
+        client.name = None;
+        client.user = DEFAULT_USER.to_string();
+        client.authenticated = authenticated;
+        self.write_reply(client_id, RESET_COMMAND, &RespValue::SimpleString(RESET_REPLY.into())).await;
+    }
+
+    // Redis 7처럼 bind와 관계없이 default 사용자에게 비밀번호가 없으면 루프백이 아닌 연결을 거절함
+    async fn protected_mode_denies(&self, addr: SocketAddr) -> bool {
+        let protected_mode = self.config.read().await.get("protected_mode").map_or(true, |mode| mode != "no");
+        protected_mode && self.acl.default_user().is_nopass() && !addr.ip().to_canonical().is_loopback()
+    }
+
+    // requirepass가 바뀌었으면 default 사용자의 비밀번호에 반영함
+    async fn sync_requirepass(&mut self) {
+        let requirepass = self.config.read().await.get("requirepass").cloned().unwrap_or_default();
+        self.acl.apply_requirepass(&requirepass);
+    }
+
+    // 사용자 이름이 없으면 default 사용자로 인증함, nopass 사용자는 어떤 비밀번호로든 통과함
+    async fn authenticate(&mut self, client_id: u64, username: Option<&str>, password: &str) -> Result<(), RedisError> {
+        if username.is_none() && self.acl.default_user().is_nopass() {
+            return Err(RedisError::Err(AUTH_WITHOUT_PASSWORD_ERROR.into()));
+        }
+        let username = username.unwrap_or(DEFAULT_USER);
+        if !self.acl.authenticate(username, password) {
+            log_notice!("Authentication failed for user {} (client {})", username, client_id);
+            return Err(RedisError::WrongPass);
+        }
+        if let Some(client) = self.client_manager.get_client_mut(&client_id) {
+            client.authenticated = true;
+            client.user = username.to_string();
+        }
+        Ok(())
+    }
+
+    async fn handle_acl(&mut self, client_id: u64, acl_command: &AclCommand) -> RespValue {
+        match acl_command {
+            AclCommand::SETUSER { username, rules } => match self.acl.set_user(username, rules) {
+                Ok(()) => {
+                    self.db.write().await.track_namespaces(self.acl.namespace_prefixes());
+                    RespValue::ok()
+                }
+                Err(e) => RespValue::error(&e),
+            },
+            AclCommand::GETUSER(username) => self.acl.user(username).map_or(RespValue::NullBulk, |user| user.to_resp()),
+            // 지운 사용자로 인증한 연결은 Redis처럼 끊음
+            AclCommand::DELUSER(usernames) => {
+                if usernames.iter().any(|username| username == DEFAULT_USER) {
+                    return RespValue::error(DELETE_DEFAULT_USER_ERROR);
+                }
+                let mut deleted = 0;
+                for username in usernames {
+                    if !self.acl.delete_user(username) {
+                        continue;
+                    }
+                    deleted += 1;
+                    let ids: Vec<u64> = self
+                        .client_manager
+                        .list_clients()
+                        .into_iter()
+                        .filter(|client| client.user == *username)
+                        .map(|client| client.id)
+                        .collect();
+                    for id in ids {
+                        if let Some(client) = self.client_manager.get_client_mut(&id) {
+                            log_notice!("Killing client {} authenticated as deleted user {}", client.label(), username);
+                            client.kill();
+                        }
+                    }
+                }
+                self.db.write().await.track_namespaces(self.acl.namespace_prefixes());
+                RespValue::Integer(deleted)
+            }
+            AclCommand::LIST => RespValue::Array(self.acl.users().map(|user| RespValue::bulk(user.describe())).collect()),
+            AclCommand::USERS => RespValue::Array(self.acl.users().map(|user| RespValue::bulk(user.name())).collect()),
+            AclCommand::WHOAMI => {
+                let user = self.client_manager.get_client(client_id).map_or(DEFAULT_USER, |client| client.user.as_str());
+                RespValue::bulk(user)
+            }
+        }
+    }
+
+    fn handle_asking(&mut self, client_id: u64) -> RespValue {
+        if self.cluster.is_none() {
+            return RespValue::error(CLUSTER_DISABLED_ERROR);
+        }
+        if let Some(client) = self.client_manager.get_client_mut(&client_id) {
+            client.asking = true;
+        }
+        RespValue::ok()
+    }
+
+    // READONLY/READWRITE는 ASKING과 달리 연결이 끝나거나 다시 바꿀 때까지 유지됨
+    fn handle_readonly(&mut self, client_id: u64, readonly: bool) -> RespValue {
+        if self.cluster.is_none() {
+            return RespValue::error(CLUSTER_DISABLED_ERROR);
+        }
+        if let Some(client) = self.client_manager.get_client_mut(&client_id) {
+            client.readonly = readonly;
+        }
+        RespValue::ok()
+    }
+
+    // 클러스터 모드에서 키의 슬롯을 이 노드가 처리하지 않으면 MOVED/ASK 등을 돌려줌, ASKING은 이 명령에서 소진됨
+    // MIGRATE가 보내는 RESTORE-ASKING은 ASKING을 따로 보내지 않아도 받아 오는 중인 슬롯에 들어감
+    async fn route_in_cluster(&mut self, client_id: u64, command: &Command) -> Result<(), RedisError> {
+        let (asking, readonly) = match self.client_manager.get_client_mut(&client_id) {
+            Some(client) => (std::mem::take(&mut client.asking), client.readonly),
+            None => (false, false),
+        };
+        let asking = asking || matches!(command, Command::RESTORE { asking: true, .. });
+        let Some(cluster) = self.cluster.as_ref() else {
+            return Ok(());
+        };
+        let keys = command.keys();
+        let missing_keys = {
+            let db = self.db.read().await;
+            keys.iter().filter(|key| db.get(key).map_or(true, |entry| entry.is_expired())).count()
+        };
+        cluster.route(&keys, asking, missing_keys, readonly && command.category() == CommandCategory::Read)
+    }
+
+    // 만료되지 않은 키 중 해당 슬롯에 속한 키, 정렬해서 돌려줌
+    async fn keys_in_slot(&self, slot: u16) -> Vec<Vec<u8>> {
+        let db = self.db.read().await;
+        let mut keys: Vec<Vec<u8>> = db
+            .alive()
+            .filter(|(key, _)| key_hash_slot(key) == slot)
+            .map(|(key, _)| key.to_vec())
+            .collect();
+        keys.sort();
+        keys
+    }
+
+    async fn handle_cluster(&mut self, cluster_command: &ClusterCommand) -> RespValue {
+        if self.cluster.is_none() {
+            return RespValue::error(CLUSTER_DISABLED_ERROR);
+        }
+        let replication_offset = self.replication_config.read().await.get_repl_offset().await;
+        let keys_in_slot = match cluster_command {
+            ClusterCommand::SETSLOT { slot, .. } | ClusterCommand::COUNTKEYSINSLOT(slot) | ClusterCommand::GETKEYSINSLOT { slot, .. } => {
+                self.keys_in_slot(*slot).await
+            }
+            _ => Vec::new(),
+        };
+        let Some(cluster) = self.cluster.as_mut() else {
+            return RespValue::error(CLUSTER_DISABLED_ERROR);
+        };
+        // 레플리카가 되는 것은 슬롯 정보와 달리 복제 연결도 바꿔야 하므로 클러스터 상태를 바꾼 뒤 따로 처리함
+        if let ClusterCommand::REPLICATE(node_id) = cluster_command {
+            return match cluster.replicate(node_id) {
+                Ok((host, port)) => {
+                    self.follow_master(host, port).await;
+                    RespValue::ok()
+                }
+                Err(e) => RespValue::error(&e),
+            };
+        }
+        let result = match cluster_command {
+            ClusterCommand::INFO => return RespValue::bulk(cluster.info()),
+            ClusterCommand::MYID => return RespValue::bulk(cluster.myself().id.as_str()),
+            ClusterCommand::NODES => return RespValue::bulk(cluster.nodes_description()),
+            ClusterCommand::SLOTS => return cluster.slots_reply(),
+            ClusterCommand::SHARDS => return cluster.shards_reply(replication_offset),
+            ClusterCommand::KEYSLOT(key) => return RespValue::Integer(key_hash_slot(key) as i64),
+            ClusterCommand::ADDSLOTS(slots) => cluster.add_slots(slots),
+            ClusterCommand::DELSLOTS(slots) => cluster.del_slots(slots),
+            ClusterCommand::SETSLOT { slot, state } => cluster.set_slot(*slot, state, keys_in_slot.len()),
+            ClusterCommand::MEET { ip, port, bus_port } => cluster.meet(ip, *port, *bus_port, current_time_ms()),
+            ClusterCommand::REPLICATE(_) => unreachable!("handled above"),
+            ClusterCommand::REPLICAS(node_id) => return cluster.replicas_reply(node_id).unwrap_or_else(|e| RespValue::error(&e)),
+            ClusterCommand::COUNTKEYSINSLOT(_) => return RespValue::Integer(keys_in_slot.len() as i64),
+            ClusterCommand::GETKEYSINSLOT { count, .. } => {
+                let keys: Vec<Vec<u8>> = keys_in_slot.into_iter().take(*count).collect();
+                return RespValue::bulk_array(&keys);
+            }
+        };
+        match result {
+            Ok(()) => RespValue::ok(),
+            Err(e) => RespValue::error(&e),
+        }
+    }
+
+    // 마스터 연결은 클라이언트 목록에 없으므로 MASTER로 거르면 항상 비어 있음
+    fn client_type(&self, client: &Client) -> ClientType {
+        if client.is_replica {
+            ClientType::REPLICA
+        } else if client.is_subscribed() || self.shard_channels.count_for(client.id) > 0 {
+            ClientType::PUBSUB
+        } else {
+            ClientType::NORMAL
+        }
+    }
+
+    // CLIENT LIST 형식, SIGUSR1 덤프도 같은 줄을 씀
+    fn client_list(&self, filter: Option<ClientType>) -> String {
+        let mut clients = self.client_manager.list_clients();
+        clients.sort_by_key(|client| client.id);
+        clients
+            .into_iter()
+            .filter_map(|client| {
+                let shard_count = self.shard_channels.count_for(client.id);
+                let pubsub = client.is_subscribed() || shard_count > 0;
+                let client_type = self.client_type(client);
+                if filter.is_some_and(|filter| filter != client_type) {
+                    return None;
+                }
+                let mut flags = String::new();
+                if client.is_replica {
+                    flags.push('S');
+                }
+                if pubsub {
+                    flags.push('P');
+                }
+                if client.transaction.is_some() {
+                    flags.push('x');
+                }
+                if client.tracking.is_some() {
+                    flags.push('t');
+                }
+                if flags.is_empty() {
+                    flags.push('N');
+                }
+                let multi = client.transaction.as_ref().map_or(-1, |transaction| transaction.commands.len() as i64);
+                Some(format!(
+                    "id={} addr={} laddr={} name={} age={} flags={} sub={} psub={} ssub={} multi={} omem={} user={} resp={}\n",
+                    client.id,
+                    client.addr,
+                    client.local_addr,
+                    client.name.as_deref().unwrap_or_default(),
+                    client.connected_at.elapsed().as_secs(),
+                    flags,
+                    client.subscriptions.len(),
+                    client.pattern_subscriptions.len(),
+                    shard_count,
+                    multi,
+                    client.output_buffer_bytes(),
+                    client.user,
+                    client.protocol
+                ))
+            })
+            .collect()
+    }
+
+    fn handle_client(&mut self, client_id: u64, client_command: &ClientCommand) -> RespValue {
+        match client_command {
+            ClientCommand::ID => RespValue::Integer(client_id as i64),
+            ClientCommand::GETREDIR => {
+                // 추적이 꺼져 있으면 -1, 리다이렉트 없이 켜져 있으면 0
+                let redirect = match self.client_manager.get_client(client_id).and_then(|client| client.tracking.as_ref()) {
+                    None => -1,
+                    Some(options) => options.redirect.map_or(0, |redirect| redirect as i64),
+                };
+                RespValue::Integer(redirect)
+            }
+            ClientCommand::TRACKING(options) => {
+                let redirect = options.as_ref().and_then(|options| options.redirect);
+                if redirect.is_some_and(|redirect| self.client_manager.get_client(redirect).is_none()) {
+                    return RespValue::error(REDIRECT_CLIENT_MISSING_ERROR);
+                }
+                if options.is_none() {
+                    self.tracking_table.remove_client(client_id);
+                }
+                if let Some(client) = self.client_manager.get_client_mut(&client_id) {
+                    client.tracking = options.clone();
+                    client.caching = None;
+                }
+                RespValue::ok()
+            }
+            ClientCommand::CACHING(yes) => {
+                let Some(client) = self.client_manager.get_client_mut(&client_id) else {
+                    return RespValue::ok();
+                };
+                let error = match client.tracking.as_ref() {
+                    Some(options) if options.optin && *yes => None,
+                    Some(options) if options.optout && !*yes => None,
+                    Some(options) if options.optin || options.optout => {
+                        Some(if *yes { CACHING_YES_WITHOUT_OPTIN_ERROR } else { CACHING_NO_WITHOUT_OPTOUT_ERROR })
+                    }
+                    _ => Some(CACHING_WITHOUT_OPT_MODE_ERROR),
+                };
+                if let Some(error) = error {
+                    return RespValue::error(error);
+                }
+                client.caching = Some(client.request_count + 1);
+                RespValue::ok()
+            }
+            ClientCommand::SETNAME(name) => {
+                if !Client::is_valid_name(name) {
+                    return RespValue::error(INVALID_CLIENT_NAME_ERROR);
+                }
+                if let Some(client) = self.client_manager.get_client_mut(&client_id) {
+                    client.name = (!name.is_empty()).then(|| name.clone());
+                }
+                RespValue::ok()
+            }
+            ClientCommand::GETNAME => match self.client_manager.get_client(client_id).and_then(|client| client.name.as_deref()) {
+                Some(name) => RespValue::bulk(name),
+                None => RespValue::NullBulk,
+            },
+            ClientCommand::KILL { filter, legacy } => {
+                if let Some(user) = filter.user.as_ref().filter(|user| self.acl.user(user).is_none()) {
+                    return RespValue::error(&format!("No such user '{}'", user));
+                }
+                let targets: Vec<u64> = self
+                    .client_manager
+                    .list_clients()
+                    .into_iter()
+                    .filter(|client| !(filter.skip_me && client.id == client_id))
+                    .filter(|client| filter.id.map_or(true, |id| client.id == id))
+                    .filter(|client| filter.addr.as_ref().map_or(true, |addr| client.addr.to_string() == *addr))
+                    .filter(|client| filter.laddr.as_ref().map_or(true, |laddr| client.local_addr.to_string() == *laddr))
+                    .filter(|client| filter.client_type.map_or(true, |client_type| self.client_type(client) == client_type))
+                    .filter(|client| filter.user.as_ref().map_or(true, |user| client.user == *user))
+                    .filter(|client| filter.max_age_secs.map_or(true, |max_age| client.connected_at.elapsed().as_secs() >= max_age))
+                    .map(|client| client.id)
+                    .collect();
+                for target in &targets {
+                    if let Some(client) = self.client_manager.get_client_mut(target) {
+                        log_notice!("Killing client {} by request of client {}", client.label(), client_id);
+                        client.kill();
+                    }
+                }
+                match (legacy, targets.is_empty()) {
+                    (true, true) => RespValue::error(NO_SUCH_CLIENT_ERROR),
+                    (true, false) => RespValue::ok(),
+                    (false, _) => RespValue::Integer(targets.len() as i64),
+                }
+            }
+            ClientCommand::LIST(filter) => RespValue::bulk(self.client_list(*filter)),
+        }
+    }
+
+    pub(crate) async fn invalidate_command_keys(&mut self, command: &Command, origin: Option<u64>) {
+        match command {
+            Command::FLUSHDB(_) | Command::FLUSHALL(_) => self.invalidate_all().await,
+            command => self.invalidate_keys(&command.keys(), origin).await,
+        }
+    }
+
+    // 키를 읽은 클라이언트와 접두사가 맞는 BCAST 클라이언트에게 무효화 메시지를 보냄
+    // 키를 바꾸는 모든 경로가 여기를 거치므로 마지막 저장 이후의 변경 수와 WATCH한 키의 변경도 같이 기록함
+    pub(crate) async fn invalidate_keys(&mut self, keys: &[&Vec<u8>], origin: Option<u64>) {
+        if keys.is_empty() {
+            return;
+        }
+        self.persistence.mark_dirty(keys.len() as u64);
+        self.watch_table.touch(keys);
+        let mut targets = self.tracking_table.take_readers(keys);
+        let tracking_clients = self.client_manager.tracking_clients();
+        for (tracking_id, options) in tracking_clients.iter().filter(|(_, options)| options.bcast) {
+            let matched: Vec<Vec<u8>> = keys
+                .iter()
+                .filter(|key| options.matches_prefix(key))
+                .map(|key| (*key).clone())
+                .collect();
+            if !matched.is_empty() {
+                targets.entry(*tracking_id).or_default().extend(matched);
+            }
+        }
+
+        for (tracking_id, options) in tracking_clients {
+            let Some(keys) = targets.remove(&tracking_id) else {
+                continue;
+            };
+            if options.noloop && origin == Some(tracking_id) {
+                continue;
+            }
+            let target = options.redirect.unwrap_or(tracking_id);
+            self.send_invalidation(target, Some(&keys)).await;
+        }
+    }
+
+    // FLUSHDB/FLUSHALL은 키 목록 대신 nil을 보내 추적 중인 모든 키를 무효화함
+    async fn invalidate_all(&mut self) {
+        self.persistence.mark_dirty(1);
+        self.watch_table.touch_all();
+        self.tracking_table.clear();
+        for (tracking_id, options) in self.client_manager.tracking_clients() {
+            let target = options.redirect.unwrap_or(tracking_id);
+            self.send_invalidation(target, None).await;
+        }
+    }
+
+    // RESP3 연결에는 invalidate push로, RESP2 연결에는 __redis__:invalidate 채널을 구독한 경우에만 보냄
+    async fn send_invalidation(&mut self, target: u64, keys: Option<&[Vec<u8>]>) {
+        let Some(client) = self.client_manager.get_client_mut(&target) else {
+            return;
+        };
+        if client.protocol == RESP2_PROTOCOL && !client.subscriptions.contains(INVALIDATE_CHANNEL) {
+            return;
+        }
+        let payload = pubsub::invalidate_reply(keys, client.protocol).encode(client.protocol);
+        if let Err(e) = client.write_all(&payload).await {
+            log_warning!("Failed to deliver invalidation to client {}: {}", target, e);
+        } else {
+            self.stats.write().await.record_output(payload.len());
+        }
+    }
+
+    fn handle_script(&mut self, script_command: &ScriptCommand) -> RespValue {
+        match script_command {
+            ScriptCommand::LOAD(body) => match scripting::check_syntax(body) {
+                Ok(()) => RespValue::bulk(self.scripts.load(body)),
+                Err(e) => RespValue::error(&e),
+            },
+            ScriptCommand::EXISTS(shas) => {
+                let exists: Vec<i64> = shas.iter().map(|sha| self.scripts.exists(sha) as i64).collect();
+                RespValue::integer_array(&exists)
+            }
+            ScriptCommand::FLUSH(mode) => {
+                self.scripts.flush(*mode == FlushMode::ASYNC);
+                RespValue::ok()
+            }
+            ScriptCommand::KILL => RespValue::from(RedisError::NotBusy),
+        }
+    }
+
+    // 응답과 함께 레지스트리가 바뀌었는지(복제해야 하는지)를 돌려줌
+    async fn handle_function(&mut self, function_command: &FunctionCommand) -> (RespValue, bool) {
+        let mut functions = self.functions.write().await;
+        match function_command {
+            FunctionCommand::LOAD { code, replace } => match functions.load(code, *replace) {
+                Ok(name) => (RespValue::bulk(name), true),
+                Err(e) => (RespValue::error(&e), false),
+            },
+            FunctionCommand::LIST { pattern, with_code } => {
+                let libraries = functions.list(pattern.as_deref());
+                (RespValue::Array(libraries.iter().map(|library| library.render(*with_code)).collect()), false)
+            }
+            FunctionCommand::DELETE(library_name) => {
+                if functions.delete(library_name) {
+                    (RespValue::ok(), true)
+                } else {
+                    (RespValue::error(LIBRARY_NOT_FOUND_ERROR), false)
+                }
+            }
+            FunctionCommand::FLUSH(_) => {
+                functions.flush();
+                (RespValue::ok(), true)
+            }
+            FunctionCommand::KILL => (RespValue::from(RedisError::NotBusy), false),
+        }
+    }
+
+    fn function_replication_command(function_command: &FunctionCommand) -> Vec<u8> {
+        match function_command {
+            FunctionCommand::LOAD { code, replace: true } => {
+                construct_redis_command(&[FUNCTION_COMMAND, FUNCTION_LOAD_OPTION, REPLACE_OPTION, code])
+            }
+            FunctionCommand::LOAD { code, .. } => construct_redis_command(&[FUNCTION_COMMAND, FUNCTION_LOAD_OPTION, code]),
+            FunctionCommand::DELETE(library_name) => {
+                construct_redis_command(&[FUNCTION_COMMAND, FUNCTION_DELETE_OPTION, library_name])
+            }
+            FunctionCommand::FLUSH(mode) => construct_redis_command(&[FUNCTION_COMMAND, FUNCTION_FLUSH_OPTION, mode.as_str()]),
+            FunctionCommand::LIST { .. } => construct_redis_command(&[FUNCTION_COMMAND, FUNCTION_LIST_OPTION]),
+            FunctionCommand::KILL => construct_redis_command(&[FUNCTION_COMMAND, FUNCTION_KILL_OPTION]),
+        }
+    }
+
+    async fn build_info(&self, section: &Option<String>) -> String {
+        let section = section
+            .as_ref()
+            .map(|section| section.to_lowercase())
+            .unwrap_or_else(|| INFO_SECTION_DEFAULT.to_string());
+        let include_all = matches!(
+            section.as_str(),
+            INFO_SECTION_ALL | INFO_SECTION_DEFAULT | INFO_SECTION_EVERYTHING
+        );
+
+        let mut sections = Vec::new();
+        if include_all || section == INFO_SECTION_SERVER {
+            sections.push(self.server_info.read().await.get_server_info());
+        }
+        if include_all || section == INFO_SECTION_CLIENTS {
+            sections.push(self.build_clients_info());
+        }
+        if include_all || section == INFO_SECTION_STATS {
+            let mut stats_info = self.stats.read().await.get_stats_info();
+            stats_info.push_str(&self.publisher.queue_snapshot().render());
+            stats_info.push_str(&format!("tracking_total_keys:{}{}", self.tracking_table.len(), CRLF));
+            stats_info.push_str(&format!("total_concurrent_reads:{}{}", self.concurrent_reads.total(), CRLF));
+            stats_info.push_str(&format!("total_rate_limited_waits:{}{}", self.runtime.rate_limits.throttled_count(), CRLF));
+            sections.push(stats_info);
+        }
+        // Redis처럼 명령별 통계는 default에는 넣지 않음
+        let include_everything = matches!(section.as_str(), INFO_SECTION_ALL | INFO_SECTION_EVERYTHING);
+        if include_everything || section == INFO_SECTION_COMMANDSTATS {
+            sections.push(self.stats.read().await.get_commandstats_info());
+        }
+        if include_everything || section == INFO_SECTION_LATENCYSTATS {
+            sections.push(self.stats.read().await.get_latencystats_info());
+        }
+        if include_all || section == INFO_SECTION_MEMORY {
+            sections.push(self.build_memory_info().await);
+        }
+        if include_all || section == INFO_SECTION_PERSISTENCE {
+            sections.push(self.persistence.get_persistence_info());
+        }
+        if include_all || section == INFO_SECTION_REPLICATION {
+            sections.push(self.replication_config.read().await.get_replication_info().await);
+        }
+        if include_all || section == INFO_SECTION_CLUSTER {
+            sections.push(format!("# Cluster{}cluster_enabled:{}{}", CRLF, self.cluster.is_some() as u8, CRLF));
+        }
+        if let Some(sentinel) = self.sentinel.as_ref().filter(|_| include_all || section == INFO_SECTION_SENTINEL) {
+            sections.push(sentinel.info());
+        }
+        if include_all || section == INFO_SECTION_KEYSPACE {
+            sections.push(self.build_keyspace_info().await);
+        }
+        sections.join(CRLF)
+    }
+
+    // Redis처럼 키가 없는 DB는 줄을 만들지 않음, avg_ttl은 만료가 걸린 키들의 남은 시간 평균(밀리초)
+    async fn build_keyspace_info(&self) -> String {
+        let mut info = format!("# Keyspace{}", CRLF);
+        let db = self.db.read().await;
+        let now = current_time_ms();
+        let (mut keys, mut expires, mut ttl_total) = (0u64, 0u64, 0u64);
+        for entry in db.values().filter(|entry| !entry.is_expired()) {
+            keys += 1;
+            if let Some(expiration_ms) = entry.expiration_ms() {
+                expires += 1;
+                ttl_total += expiration_ms.saturating_sub(now);
+            }
+        }
+        if keys > 0 {
+            let avg_ttl = ttl_total.checked_div(expires).unwrap_or(0);
+            info.push_str(&format!("db0:keys={},expires={},avg_ttl={}{}", keys, expires, avg_ttl, CRLF));
+        }
+        info
+    }
+
+    fn build_clients_info(&self) -> String {
+        let mut info = format!("# Clients{}", CRLF);
+        info.push_str(&format!("connected_clients:{}{}", self.client_manager.normal_client_count(), CRLF));
+        info.push_str(&format!("cluster_connections:{}{}", self.cluster_bus.connection_count(), CRLF));
+        info.push_str(&format!("client_recent_max_output_buffer:{}{}", self.client_manager.max_output_buffer_bytes(), CRLF));
+        info.push_str(&format!("blocked_clients:{}{}", self.blocking.len(), CRLF));
+        info.push_str(&format!("tracking_clients:{}{}", self.client_manager.tracking_clients().len(), CRLF));
+        let pubsub_clients = self
+            .client_manager
+            .list_clients()
+            .iter()
+            .filter(|client| client.is_subscribed() || self.shard_channels.count_for(client.id) > 0)
+            .count();
+        info.push_str(&format!("pubsub_clients:{}{}", pubsub_clients, CRLF));
+        // 연결마다의 응답 경로와 명령 큐 상태: 응답이 밀려 읽기를 멈춘 연결 수, 소켓에 쓰지 못한 응답, 이벤트 큐에 올라간 명령
+        let clients = self.client_manager.list_clients();
+        let paused = clients.iter().filter(|client| client.backpressure.is_paused()).count();
+        let pending_output: usize = clients.iter().map(|client| client.output_buffer_bytes()).sum();
+        let inflight: usize = clients.iter().map(|client| client.backpressure.inflight()).sum();
+        info.push_str(&format!("paused_clients:{}{}", paused, CRLF));
+        info.push_str(&format!("total_client_output_pending:{}{}", pending_output, CRLF));
+        info.push_str(&format!("total_inflight_commands:{}{}", inflight, CRLF));
+        info
+    }
+
+    async fn build_memory_info(&self) -> String {
+        let (_, policy, _) = self.memory_limits().await;
+        let mut info = self.memory_report().await.get_memory_info();
+        info.push_str(&format!("maxmemory_policy:{}{}", policy, CRLF));
+        info.push_str(&format!("lazyfree_pending_objects:{}{}", lazyfree::pending_objects(), CRLF));
+        info.push_str(&format!("lazyfreed_objects:{}{}", lazyfree::freed_objects(), CRLF));
+        info.push_str(&format!("number_of_cached_scripts:{}{}", self.scripts.len(), CRLF));
+        let functions = self.functions.read().await;
+        info.push_str(&format!("number_of_functions:{}{}", functions.function_count(), CRLF));
+        info.push_str(&format!("number_of_libraries:{}{}", functions.library_count(), CRLF));
+        info
+    }
+
+    // 키스페이스가 유지하는 사용량을 읽고 피크도 함께 갱신함
+    async fn memory_report(&self) -> MemoryReport {
+        let (maxmemory, _, _) = self.memory_limits().await;
+        let db = self.db.read().await;
+        let mut report = MemoryReport::collect(&db, maxmemory);
+        report.peak = self.stats.write().await.record_used_memory(report.used_memory);
+        report
+    }
+
+    async fn handle_memory(&self, memory_command: &MemoryCommand) -> RespValue {
+        match memory_command {
+            MemoryCommand::USAGE { key, samples } => match self.db.read().await.get(key).filter(|entry| !entry.is_expired()) {
+                // 기본 SAMPLES는 키스페이스 합계에 쓰인 크기와 같은 기준이므로 다시 재지 않음
+                Some(entry) if *samples == DEFAULT_USAGE_SAMPLES => RespValue::Integer(entry.accounted_size() as i64),
+                Some(entry) => RespValue::Integer(entry.sampled_size(key, *samples) as i64),
+                None => RespValue::NullBulk,
+            },
+            MemoryCommand::STATS => self.memory_report().await.to_resp(),
+            MemoryCommand::DOCTOR => RespValue::bulk(self.memory_report().await.doctor()),
+        }
+    }
+
+    async fn memory_limits(&self) -> (Option<u64>, String, usize) {
+        let config = self.config.read().await;
+        let maxmemory = config
+            .get("maxmemory")
+            .and_then(|value| eviction::parse_memory(value).ok())
+            .filter(|maxmemory| *maxmemory > 0);
+        let policy = config.get("maxmemory_policy").cloned().unwrap_or_else(|| "noeviction".to_string());
+        let samples = config
+            .get("maxmemory_samples")
+            .and_then(|samples| samples.parse::<usize>().ok())
+            .filter(|samples| *samples > 0)
+            .unwrap_or(eviction::DEFAULT_EVICTION_SAMPLES);
+        (maxmemory, policy, samples)
+    }
+
+    // maxmemory를 넘었으면 정책에 따라 키를 지워서 공간을 만듦, 충분히 못 지우면 false
+    // 레플리카는 Redis의 replica-ignore-maxmemory처럼 제한을 적용하지 않음
+    async fn free_memory_for_write(&mut self) -> bool {
+        let (Some(maxmemory), policy, samples) = self.memory_limits().await else {
+            return true;
+        };
+        if self.replication_config.read().await.get_role().await == "slave" {
+            return true;
+        }
+        let policy = EvictionPolicy::parse(&policy).unwrap_or(EvictionPolicy::NoEviction);
+        let lazy = self.config.read().await.get("lazyfree_lazy_eviction").is_some_and(|value| value == "yes");
+
+        let mut evicted = Vec::new();
+        let freed = {
+            let mut db = self.db.write().await;
+            self.stats.write().await.record_used_memory(db.used_memory() as u64);
+            while db.used_memory() as u64 > maxmemory {
+                let Some(key) = self.eviction_pool.select_victim(&db, policy, samples) else {
+                    break;
+                };
+                if let Some(entry) = db.remove(&key) {
+                    if lazy {
+                        lazyfree::free_entry(entry);
+                    }
+                }
+                evicted.push(key);
+            }
+            db.used_memory() as u64 <= maxmemory
+        };
+
+        for key in &evicted {
+            self.stats.write().await.record_evicted_key();
+            if let Err(e) = self.publisher.publish_propagate_slave(construct_redis_command(&[DEL_COMMAND.as_bytes(), key]), None).await {
+                log_warning!("Failed to propagate evicted key {}: {}", String::from_utf8_lossy(key), e);
+            }
+            self.notify_keyspace_event(notify::NOTIFY_EVICTED, EVICTED_EVENT, key).await;
+        }
+        self.invalidate_keys(&evicted.iter().collect::<Vec<_>>(), None).await;
+        freed
+    }
+
+    // pattern이 true면 PSUBSCRIBE: 채널 대신 glob 패턴을 구독함
+    async fn handle_subscribe(&mut self, client_id: u64, channels: &[String], pattern: bool) {
+        let (kind, command_name) = if pattern { ("psubscribe", PSUBSCRIBE_COMMAND) } else { ("subscribe", SUBSCRIBE_COMMAND) };
+        for channel in channels {
+            let Some(client) = self.client_manager.get_client_mut(&client_id) else {
+                return;
+            };
+            if pattern {
+                client.pattern_subscriptions.insert(channel.clone());
+            } else {
+                client.subscriptions.insert(channel.clone());
+            }
+            let response = pubsub::subscription_reply(kind, Some(channel), client.subscription_count());
+            self.write_reply(client_id, command_name, &response).await;
+        }
+    }
+
+    async fn handle_unsubscribe(&mut self, client_id: u64, channels: &[String], pattern: bool) {
+        let (kind, command_name) = if pattern { ("punsubscribe", PUNSUBSCRIBE_COMMAND) } else { ("unsubscribe", UNSUBSCRIBE_COMMAND) };
+        let Some(client) = self.client_manager.get_client_mut(&client_id) else {
+            return;
+        };
+        let channels: Vec<String> = match (channels.is_empty(), pattern) {
+            (true, true) => client.pattern_subscriptions.iter().cloned().collect(),
+            (true, false) => client.subscriptions.iter().cloned().collect(),
+            (false, _) => channels.to_vec(),
+        };
+        if channels.is_empty() {
+            let response = pubsub::subscription_reply(kind, None, client.subscription_count());
+            self.write_reply(client_id, command_name, &response).await;
+            return;
+        }
+
+        for channel in channels {
+            let Some(client) = self.client_manager.get_client_mut(&client_id) else {
+                return;
+            };
+            if pattern {
+                client.pattern_subscriptions.remove(&channel);
+            } else {
+                client.subscriptions.remove(&channel);
+            }
+            let response = pubsub::subscription_reply(kind, Some(&channel), client.subscription_count());
+            self.write_reply(client_id, command_name, &response).await;
+        }
+    }
+
+    fn handle_pubsub(&self, pubsub_command: &PubSubCommand) -> RespValue {
+        match pubsub_command {
+            PubSubCommand::CHANNELS(pattern) => {
+                let mut channels: Vec<&String> = self
+                    .client_manager
+                    .active_channels()
+                    .into_iter()
+                    .filter(|channel| pattern.as_ref().map_or(true, |pattern| glob_match(pattern.as_bytes(), channel.as_bytes())))
+                    .collect();
+                channels.sort();
+                RespValue::Array(channels.into_iter().map(|channel| RespValue::bulk(channel.as_str())).collect())
+            }
+            PubSubCommand::NUMSUB(channels) => RespValue::Map(
+                channels
+                    .iter()
+                    .map(|channel| {
+                        let count = self.client_manager.subscribers(channel).len();
+                        (RespValue::bulk(channel.as_str()), RespValue::Integer(count as i64))
+                    })
+                    .collect(),
+            ),
+            PubSubCommand::NUMPAT => RespValue::Integer(self.client_manager.pattern_count() as i64),
+            PubSubCommand::SHARDCHANNELS(pattern) => {
+                let channels = self.shard_channels.channels(pattern.as_deref());
+                RespValue::Array(channels.into_iter().map(|channel| RespValue::bulk(channel.as_str())).collect())
+            }
+            PubSubCommand::SHARDNUMSUB(channels) => RespValue::Map(
+                channels
+                    .iter()
+                    .map(|channel| {
+                        let count = self.shard_channels.subscribers(channel).len();
+                        (RespValue::bulk(channel.as_str()), RespValue::Integer(count as i64))
+                    })
+                    .collect(),
+            ),
+        }
+    }
+
+    // 채널 구독자에게는 message, 패턴 구독자에게는 매칭된 패턴과 함께 pmessage를 보냄
+    // PUBLISH와 SPUBLISH, 레플리카에도 전파해서 레플리카의 구독자도 받게 함
+    async fn handle_publish(&mut self, command: &Command, trace: Option<TraceContext>) -> RespValue {
+        match command {
+            Command::PUBLISH { channel, .. } if channel == SERVER_EVENTS_CHANNEL => RespValue::error(RESERVED_CHANNEL_ERROR),
+            Command::PUBLISH { channel, message } => {
+                let receivers = self.publish_message(channel, message).await;
+                self.propagate_publish(PUBLISH_COMMAND, channel, message, trace).await;
+                RespValue::Integer(receivers as i64)
+            }
+            Command::SPUBLISH { channel, message } => {
+                let receivers = self.publish_shard_message(channel, message).await;
+                self.propagate_publish(SPUBLISH_COMMAND, channel, message, trace).await;
+                RespValue::Integer(receivers as i64)
+            }
+            _ => unreachable!("not a publish command"),
+        }
+    }
+
+    async fn publish_message(&mut self, channel: &str, message: &[u8]) -> usize {
+        let mut deliveries: Vec<(u64, Vec<u8>)> = self
+            .client_manager
+            .subscribers(channel)
+            .into_iter()
+            .map(|subscriber| (subscriber, pubsub::message_reply(channel, message).encode(self.client_manager.protocol(subscriber))))
+            .collect();
+        deliveries.extend(self.client_manager.pattern_subscribers(channel).into_iter().map(|(subscriber, pattern)| {
+            (subscriber, pubsub::pmessage_reply(&pattern, channel, message).encode(self.client_manager.protocol(subscriber)))
+        }));
+
+        for (subscriber, payload) in deliveries.iter() {
+            if let Some(client) = self.client_manager.get_client_mut(subscriber) {
+                if let Err(e) = client.write_all(payload).await {
+                    log_warning!("Failed to deliver message to client {}: {}", subscriber, e);
+                } else {
+                    self.stats.write().await.record_output(payload.len());
+                }
+            }
+        }
+        deliveries.len()
+    }
+
+    // 샤드 채널은 패턴 구독 없이 해당 채널 구독자에게만 smessage로 전달됨
+    // 레플리카에 붙은 구독자도 받도록 발행을 복제 스트림에 실음, 레플리카에 직접 들어온 발행은 PropagateSlave에서 버려짐
+    async fn propagate_publish(&self, name: &str, channel: &str, message: &[u8], trace: Option<TraceContext>) {
+        let replicated = construct_redis_command(&[name.as_bytes(), channel.as_bytes(), message]);
+        if let Err(e) = self.publisher.publish_propagate_slave(replicated, trace).await {
+            log_warning!("Failed to propagate {}: {}", name, e);
+        }
+    }
+
+    async fn publish_shard_message(&mut self, channel: &str, message: &[u8]) -> usize {
+        let subscribers = self.shard_channels.subscribers(channel);
+        for subscriber in subscribers.iter() {
+            if let Some(client) = self.client_manager.get_client_mut(subscriber) {
+                let payload = pubsub::smessage_reply(channel, message).encode(client.protocol);
+                if let Err(e) = client.write_all(&payload).await {
+                    log_warning!("Failed to deliver shard message to client {}: {}", subscriber, e);
+                } else {
+                    self.stats.write().await.record_output(payload.len());
+                }
+            }
+        }
+        subscribers.len()
+    }
+
+    // __keyspace@0__:<key> 채널에는 이벤트 이름을, __keyevent@0__:<event> 채널에는 키를 발행함
+    pub(crate) async fn notify_keyspace_event(&mut self, class: u32, event: &str, key: &[u8]) {
+        let flags = notify::enabled_flags(&*self.config.read().await);
+        if flags & class == 0 {
+            return;
+        }
+        if flags & notify::NOTIFY_KEYSPACE != 0 {
+            self.publish_message(&notify::keyspace_channel(key), event.as_bytes()).await;
+        }
+        if flags & notify::NOTIFY_KEYEVENT != 0 {
+            self.publish_message(&notify::keyevent_channel(event), key).await;
+        }
+    }
+
+    // 운영자가 로그 대신 일반 구독으로 서버 상태 변화를 볼 수 있도록 예약 채널에 발행
+    async fn publish_server_event(&mut self, event: &str) {
+        self.publish_message(SERVER_EVENTS_CHANNEL, event.as_bytes()).await;
+    }
+
+    // FULLRESYNC 응답과 RDB를 다 보낸 뒤에 등록해야 핸드셰이크 중인 연결에 PING/GETACK이나 전파된 명령이 섞이지 않음
+    async fn register_replica(&mut self, client_id: u64) {
+        let Some(client) = self.client_manager.get_client_mut(&client_id) else {
+            return;
+        };
+        client.is_replica = true;
+        let limits = OutputBufferLimits::for_replicas(self.config.read().await.get("client_output_buffer_limit_replica"));
+        if let Err(e) = client.start_replica_output(limits).await {
+            log_warning!("Failed to flush the full resync payload to replica {}: {}", client.addr, e);
+            client.kill();
+            return;
+        }
+        let (addr, listening_port, announced_ip) = (client.addr, client.replica_listening_port, client.replica_announced_ip.clone());
+
+        let repl_guard = self.replication_config.read().await;
+        let offset = repl_guard.get_repl_offset().await;
+        let replica_id = repl_guard.register_slave(client_id, addr, listening_port, offset).await;
+        if let Some(ip) = announced_ip {
+            repl_guard.set_slave_announced_ip(client_id, ip).await;
+        }
+        drop(repl_guard);
+
+        log_notice!("New slave connected: {} (replica {})", addr, replica_id);
+        let listening_port = listening_port.map_or("unknown".to_string(), |port| port.to_string());
+        self.publish_server_event(&format!("replica-connected id={} addr={} listening_port={}", replica_id, addr, listening_port)).await;
+    }
+
+    async fn propagate_to_slaves(&mut self, message: Bytes, trace: Option<TraceContext>) {
+        self.replication_config.read().await.advance_repl_offset(message.len()).await;
+        self.forward_to_slaves(message, trace).await;
+    }
+
+    // 레플리카는 마스터 링크에서 이미 offset을 셌으므로 받은 바이트를 그대로 보내기만 함
+    // 인코딩한 버퍼는 Bytes로 한 번만 만들고 모든 레플리카가 같은 버퍼를 공유함
+    async fn forward_to_slaves(&mut self, message: Bytes, trace: Option<TraceContext>) {
+        let repl_guard = self.replication_config.read().await;
+        let mut slaves = repl_guard.get_slaves_mut().await;
+        trace::record(trace, "propagate", &format!("replicas={} bytes={}", slaves.len(), message.len()));
+
+        let mut failed = Vec::new();
+        for slave in slaves.iter_mut() {
+            if let Some(client) = self.client_manager.get_client_mut(&slave.client_id) {
+                if let Err(e) = client.write_shared(&message) {
+                    log_warning!("Failed to propagate message to slave {}: {}", slave.addr, e);
+                    failed.push(slave.client_id);
+                } else {
+                    slave.sent_offset += message.len() as i64;
+                    self.stats.write().await.record_repl_output(message.len());
+                }
+            } else {
+                log_notice!("No client found for replica {} ({})", slave.id, slave.addr);
+            }
+        }
+        self.detach_replicas(&mut slaves, &failed).await;
+    }
+
+    // 쓰기에 실패한 레플리카는 이후 전파 대상과 connected_slaves에서 바로 빼고, 연결 정리는 SlaveDisconnected에서 함
+    async fn detach_replicas(&self, slaves: &mut Vec<SlaveInfo>, failed: &[u64]) {
+        if failed.is_empty() {
+            return;
+        }
+        let mut detached = Vec::new();
+        slaves.retain(|slave| {
+            if failed.contains(&slave.client_id) {
+                detached.push((slave.client_id, slave.id));
+                return false;
+            }
+            true
+        });
+        for (client_id, replica_id) in detached {
+            if let Err(e) = self.publisher.publish_slave_disconnected(client_id, replica_id).await {
+                log_warning!("{}", e);
+            }
+        }
+    }
+
+    async fn repl_ping_replica_period_secs(&self) -> u64 {
+        self.config
+            .read()
+            .await
+            .get("repl_ping_replica_period")
+            .and_then(|period| period.parse::<u64>().ok())
+            .filter(|period| *period > 0)
+            .unwrap_or(DEFAULT_REPL_PING_REPLICA_PERIOD_SECS)
+    }
+
+    // 쓰기가 없어도 레플리카가 마스터 연결이 살아 있음을 알 수 있도록 주기마다 복제 스트림으로 PING을 보냄
+    async fn ping_replicas(&mut self) {
+        self.ticks_since_replica_ping += 1;
+        if self.ticks_since_replica_ping < self.repl_ping_replica_period_secs().await {
+            return;
+        }
+        self.ticks_since_replica_ping = 0;
+        if self.replication_config.read().await.list_slaves().await.is_empty() {
+            return;
+        }
+        self.propagate_to_slaves(Bytes::from(construct_redis_command(&[PING_COMMAND])), None).await;
+    }
+
+    // 모든 레플리카가 ACK를 기다리고 있으면 다시 보내지 않음
+    async fn probe_replica_acks(&mut self) {
+        let repl_guard = self.replication_config.read().await;
+        if repl_guard.get_role().await != "master" {
+            return;
+        }
+        let idle = repl_guard.list_slaves().await.iter().any(|slave| slave.getack_sent_at.is_none());
+        drop(repl_guard);
+        if idle {
+            self.request_replica_acks().await;
+        }
+    }
+
+    // GETACK도 복제 스트림으로 보내 offset에 세야 레플리카의 offset이 마스터와 같아서 PSYNC로 이어받을 수 있음
+    // 지연 시간은 ACK가 오지 않은 채 다시 보내도 처음 보낸 GETACK부터 잼
+    async fn request_replica_acks(&mut self) {
+        {
+            let repl_guard = self.replication_config.read().await;
+            for slave in repl_guard.get_slaves_mut().await.iter_mut() {
+                slave.getack_sent_at.get_or_insert_with(Instant::now);
+            }
+        }
+        let message = construct_redis_command(&[REPLCONF_COMMAND, REPLCONF_GETACK, "*"]);
+        self.propagate_to_slaves(Bytes::from(message), None).await;
+    }
+
+    // Redis의 active expire와 같은 방식: TTL이 있는 키를 샘플링해서 만료된 키를 지우고,
+    // 만료 비율이 높으면 같은 주기 안에서 몇 번 더 반복함
+    // 레플리카는 마스터가 보내는 DEL을 기다림
+    async fn active_expire_cycle(&mut self) {
+        if self.replication_config.read().await.get_role().await != "master" {
+            return;
+        }
+
+        let mut expired = Vec::new();
+        {
+            let mut db = self.db.write().await;
+            for _ in 0..ACTIVE_EXPIRE_MAX_ROUNDS {
+                if db.volatile().is_empty() {
+                    break;
+                }
+
+                let sample_size = ACTIVE_EXPIRE_SAMPLE_SIZE.min(db.volatile().len());
+                let sampled: HashSet<Arc<[u8]>> = (0..sample_size).filter_map(|_| db.volatile().random(db.random()).cloned()).collect();
+                let mut expired_in_round = 0;
+                for key in sampled {
+                    if db.get(&key).is_some_and(|entry| entry.is_expired()) {
+                        if let Some(entry) = db.remove(&key) {
+                            expired.push((key.to_vec(), entry));
+                            expired_in_round += 1;
+                        }
+                    }
+                }
+                if expired_in_round * 100 <= sample_size * ACTIVE_EXPIRE_REPEAT_PERCENT {
+                    break;
+                }
+            }
+        }
+        if expired.is_empty() {
+            return;
+        }
+
+        let lazy = self.config.read().await.get("lazyfree_lazy_expire").is_some_and(|value| value == "yes");
+        let (keys, entries): (Vec<Vec<u8>>, Vec<ValueEntry>) = expired.into_iter().unzip();
+        if lazy {
+            lazyfree::free_entries(entries);
+        }
+
+        self.stats.write().await.record_expired_keys(keys.len());
+        let del_command = if lazy { UNLINK_COMMAND } else { DEL_COMMAND };
+        for key in &keys {
+            if let Err(e) = self.publisher.publish_propagate_slave(construct_redis_command(&[del_command.as_bytes(), key]), None).await {
+                log_warning!("Failed to propagate expired key {}: {}", String::from_utf8_lossy(key), e);
+            }
+            self.run_expire_hooks(key).await;
+        }
+    }
+
+    // 읽기 명령이 만난 만료된 키를 지움, 이벤트가 오기 전에 키가 다시 쓰였을 수 있어서 여기서 다시 확인함
+    async fn expire_found_keys(&mut self, keys: Vec<Vec<u8>>, trace: Option<TraceContext>) {
+        if self.replication_config.read().await.get_role().await != "master" {
+            return;
+        }
+
+        let mut expired = Vec::new();
+        {
+            let mut db = self.db.write().await;
+            for key in keys {
+                if db.get(&key).is_some_and(|entry| entry.is_expired()) {
+                    if let Some(entry) = db.remove(&key) {
+                        expired.push((key, entry));
+                    }
+                }
+            }
+        }
+        if expired.is_empty() {
+            return;
+        }
+
+        let lazy = self.config.read().await.get("lazyfree_lazy_expire").is_some_and(|value| value == "yes");
+        let (keys, entries): (Vec<Vec<u8>>, Vec<ValueEntry>) = expired.into_iter().unzip();
+        if lazy {
+            lazyfree::free_entries(entries);
+        }
+
+        self.stats.write().await.record_expired_keys(keys.len());
+        let del_command = if lazy { UNLINK_COMMAND } else { DEL_COMMAND };
+        for key in &keys {
+            if let Err(e) = self.publisher.publish_propagate_slave(construct_redis_command(&[del_command.as_bytes(), key]), trace).await {
+                log_warning!("Failed to propagate expired key {}: {}", String::from_utf8_lossy(key), e);
+            }
+            self.run_expire_hooks(key).await;
+        }
+    }
+
+    // 필드 TTL이 있는 해시를 샘플링해서 만료된 필드를 지우고 레플리카에는 HDEL로 전파함
+    async fn active_expire_hash_fields(&mut self) {
+        if self.replication_config.read().await.get_role().await != "master" {
+            return;
+        }
+
+        let mut expired_fields = Vec::new();
+        {
+            let mut db = self.db.write().await;
+            if db.volatile_hashes().is_empty() {
+                return;
+            }
+
+            let sample_size = ACTIVE_EXPIRE_SAMPLE_SIZE.min(db.volatile_hashes().len());
+            let sampled: HashSet<Arc<[u8]>> = (0..sample_size).filter_map(|_| db.volatile_hashes().random(db.random()).cloned()).collect();
+            for key in sampled {
+                let Some(mut entry) = db.get_mut(&key) else {
+                    continue;
+                };
+                let fields = entry.remove_expired_fields();
+                if fields.is_empty() {
+                    continue;
+                }
+                let emptied = entry.expect_hash().is_ok_and(|hash| hash.is_empty());
+                drop(entry);
+                if emptied {
+                    db.remove(&key);
+                }
+                expired_fields.push((key.to_vec(), fields));
+            }
+        }
+
+        for (key, fields) in &expired_fields {
+            let mut args = vec![HDEL_COMMAND.as_bytes(), key.as_slice()];
+            args.extend(fields.iter().map(|field| field.as_slice()));
+            if let Err(e) = self.publisher.publish_propagate_slave(construct_redis_command(&args), None).await {
+                log_warning!("Failed to propagate expired fields of {}: {}", String::from_utf8_lossy(key), e);
+            }
+            self.notify_keyspace_event(notify::NOTIFY_HASH, HEXPIRED_EVENT, key).await;
+        }
+        let keys: Vec<&Vec<u8>> = expired_fields.iter().map(|(key, _)| key).collect();
+        self.invalidate_keys(&keys, None).await;
+    }
+
+    async fn handle_admin_request(&self, path: &str) -> AdminResponse {
+        match path {
+            ADMIN_PATH_CONFIG => {
+                let config = self.config.read().await;
+                let mut keys: Vec<&String> = config.keys().collect();
+                keys.sort();
+                let entries: Vec<String> = keys
+                    .into_iter()
+                    .map(|key| {
+                        let value_type = config_value_type(key);
+                        let raw = &config[key];
+                        let value = match value_type {
+                            CONFIG_TYPE_INTEGER if raw.parse::<i64>().is_ok() => raw.clone(),
+                            CONFIG_TYPE_BOOL => (raw == "yes").to_string(),
+                            _ => json_string(raw),
+                        };
+                        // 표에 없는 내부 설정(sentinel monitor 목록 등)은 CONFIG SET으로 바꿀 수 없음
+                        let mutable = config_parameter_by_key(key).is_some_and(ConfigParameter::is_mutable);
+                        format!("{}:{{\"type\":{},\"mutable\":{},\"value\":{}}}", json_string(key), json_string(value_type), mutable, value)
+                    })
+                    .collect();
+                AdminResponse::ok(format!("{{{}}}", entries.join(",")))
+            }
+            ADMIN_PATH_INFO => {
+                let info = self.build_info(&Some(INFO_SECTION_EVERYTHING.to_string())).await;
+                let mut sections = Vec::new();
+                let mut fields = Vec::new();
+                let mut section_name = None;
+                for line in info.lines().map(str::trim).filter(|line| !line.is_empty()) {
+                    if let Some(name) = line.strip_prefix("# ") {
+                        if let Some(previous) = section_name.replace(name.to_lowercase()) {
+                            sections.push(format!("{}:{{{}}}", json_string(&previous), fields.join(",")));
+                            fields.clear();
+                        }
+                    } else if let Some((field, value)) = line.split_once(':') {
+                        let is_integer = value.parse::<i64>().is_ok_and(|number| number.to_string() == value);
+                        let value = if is_integer { value.to_string() } else { json_string(value) };
+                        fields.push(format!("{}:{}", json_string(field), value));
+                    }
+                }
+                if let Some(previous) = section_name {
+                    sections.push(format!("{}:{{{}}}", json_string(&previous), fields.join(",")));
+                }
+                AdminResponse::ok(format!("{{{}}}", sections.join(",")))
+            }
+            ADMIN_PATH_CLIENTS => {
+                let mut clients: Vec<_> = self.client_manager.list_clients();
+                clients.sort_by_key(|client| client.id);
+                let entries: Vec<String> = clients
+                    .into_iter()
+                    .map(|client| {
+                        format!(
+                            "{{\"id\":{},\"addr\":{},\"name\":{},\"age\":{},\"requests\":{},\"replica\":{}}}",
+                            client.id,
+                            json_string(&client.addr.to_string()),
+                            client.name.as_deref().map_or("null".to_string(), json_string),
+                            client.connected_at.elapsed().as_secs(),
+                            client.get_request_count(),
+                            client.is_replica
+                        )
+                    })
+                    .collect();
+                AdminResponse::ok(format!("[{}]", entries.join(",")))
+            }
+            ADMIN_PATH_REPLICAS => {
+                let repl_guard = self.replication_config.read().await;
+                let slaves = repl_guard.list_slaves().await;
+                let entries: Vec<String> = slaves
+                    .iter()
+                    .map(|slave| {
+                        format!(
+                            "{{\"id\":{},\"addr\":{},\"ip\":{},\"port\":{},\"state\":{},\"offset\":{},\"lag\":{},\"ack_latency_ms\":{}}}",
+                            slave.id,
+                            json_string(&slave.addr.to_string()),
+                            json_string(&slave.ip()),
+                            slave.port(),
+                            json_string(slave.state()),
+                            slave.offset,
+                            slave.lag_secs(),
+                            slave.ack_latency.map_or("null".to_string(), |latency| latency.as_millis().to_string())
+                        )
+                    })
+                    .collect();
+                AdminResponse::ok(format!("[{}]", entries.join(",")))
+            }
+            ADMIN_PATH_SLOTS => match &self.cluster {
+                Some(cluster) => AdminResponse::ok(format!("{{\"cluster_enabled\":true,\"slots\":{}}}", cluster.slots_json())),
+                None => AdminResponse::ok("{\"cluster_enabled\":false,\"slots\":[]}".to_string()),
+            },
+            ADMIN_PATH_METRICS => AdminResponse::ok(self.stats.read().await.metrics_json()),
+            _ => AdminResponse::error(404, &format!("unknown admin path '{}'", path)),
+        }
+    }
+
+    // latency-monitor-threshold가 0이면 기록하지 않음
+    async fn latency_threshold_ms(&self) -> u64 {
+        self.config
+            .read()
+            .await
+            .get("latency_monitor_threshold")
+            .and_then(|threshold| threshold.parse::<u64>().ok())
+            .unwrap_or(0)
+    }
+
+    async fn record_latency(&mut self, event: &str, elapsed: Duration) {
+        let threshold_ms = self.latency_threshold_ms().await;
+        let latency_ms = elapsed.as_millis() as u64;
+        if threshold_ms > 0 && latency_ms >= threshold_ms {
+            self.latency.record(event, latency_ms, current_time_ms() / 1000);
+        }
+    }
+
+    async fn handle_latency(&mut self, latency_command: &LatencyCommand) -> RespValue {
+        match latency_command {
+            LatencyCommand::LATEST => self.latency.latest(),
+            LatencyCommand::HISTORY(event) => self.latency.history(event),
+            LatencyCommand::RESET(events) => RespValue::Integer(self.latency.reset(events) as i64),
+            LatencyCommand::DOCTOR => RespValue::bulk(self.latency.doctor(self.latency_threshold_ms().await)),
+        }
+    }
+
+    async fn slowlog_threshold_us(&self) -> u64 {
+        self.config
+            .read()
+            .await
+            .get("slowlog_log_slower_than")
+            .and_then(|threshold| threshold.parse::<u64>().ok())
+            .unwrap_or(DEFAULT_SLOWLOG_THRESHOLD_US)
+    }
+
+    async fn handle_debug(&mut self, debug_command: &DebugCommand) -> RespValue {
+        match debug_command {
+            DebugCommand::REPORT => RespValue::bulk(self.build_debug_report().await),
+            DebugCommand::PROTOCOL(kind) => resp::debug_protocol_reply(kind).unwrap_or_else(|| RespValue::error(DEBUG_PROTOCOL_TYPE_ERROR)),
+            // 이벤트 루프 안에서 기다리므로 그동안 다른 명령도 처리되지 않음
+            DebugCommand::SLEEP(ms) => {
+                tokio::time::sleep(Duration::from_millis(*ms)).await;
+                RespValue::ok()
+            }
+            DebugCommand::SETACTIVEEXPIRE(enabled) => {
+                self.active_expire_enabled = *enabled;
+                RespValue::ok()
+            }
+            // Redis와 같은 형식, 값 공유가 없어서 refcount는 항상 1
+            DebugCommand::OBJECT(key) => {
+                let db = self.db.read().await;
+                let Some(entry) = db.get(key).filter(|entry| !entry.is_expired()) else {
+                    return RespValue::error(NO_SUCH_KEY_ERROR);
+                };
+                let idle_ms = entry.idle_ms();
+                RespValue::SimpleString(format!(
+                    "Value at:{:p} refcount:1 encoding:{} serializedlength:{} lru:{} lru_seconds_idle:{}",
+                    entry,
+                    entry.value.encoding(),
+                    rdb_codec::serialized_length(&entry.value),
+                    (current_time_ms().saturating_sub(idle_ms) / 1000) & LRU_CLOCK_MAX,
+                    idle_ms / 1000
+                ))
+            }
+            // 새 replid로 바꾸므로 레플리카는 다음 PSYNC에서 전체 동기화를 받음
+            DebugCommand::CHANGEREPLID => {
+                let replid = self.random.hex(RUN_ID_LEN);
+                log_notice!("Changed replication ID to {}", replid);
+                self.replication_config.read().await.set_replid(replid).await;
+                RespValue::ok()
+            }
+            DebugCommand::RELOAD => match self.reload_rdb().await {
+                Ok(()) => RespValue::ok(),
+                Err(e) => RespValue::error(&e),
+            },
+        }
+    }
+
+    // 저장한 파일을 그대로 다시 읽으므로 RDB에 담기지 않는 상태가 있으면 여기서 드러남
+    async fn reload_rdb(&mut self) -> Result<(), String> {
+        let entries = persistence::snapshot(&*self.db.read().await);
+        let aux = self.replication_config.read().await.rdb_aux_fields().await;
+        let functions = self.functions.read().await.codes();
+        let (path, compress) = {
+            let config = self.config.read().await;
+            (persistence::rdb_file_path(&config), persistence::rdb_compression(&config))
+        };
+        persistence::write_rdb_file(&path, &entries, &aux, &functions, compress).map_err(|e| format!("Error trying to save the DB: {}", e))?;
+
+        let mut db = self.db.write().await;
+        db.clear();
+        let mut parser = RdbParser::new(&mut db, &path.to_string_lossy()).map_err(|e| format!("Error trying to load the RDB dump: {}", e))?;
+        parser.parse().await.map_err(|e| format!("Error trying to load the RDB dump: {}", e))?;
+        self.functions.write().await.restore(parser.functions());
+        log_notice!("DB reloaded by DEBUG RELOAD");
+        Ok(())
+    }
+
+    // SIGUSR1: 서버를 멈추지 않고 지금 상태를 로그로 남김, 디버그 리포트에 클라이언트 목록과 키 일부를 더함
+    async fn log_debug_dump(&self) {
+        let mut dump = self.build_debug_report().await;
+        dump.push_str(&format!("{}# Client list{}", CRLF, CRLF));
+        dump.push_str(&self.client_list(None));
+        {
+            let db = self.db.read().await;
+            dump.push_str(&format!("{}# Keys{}", CRLF, CRLF));
+            dump.push_str(&format!("keys:{} shown:{}{}", db.len(), db.len().min(DEBUG_DUMP_KEY_SAMPLES), CRLF));
+            for (key, entry) in db.iter().take(DEBUG_DUMP_KEY_SAMPLES) {
+                let ttl = entry.remaining_ms().map_or(-1, |ms| ms as i64);
+                dump.push_str(&format!(
+                    "key={} type={} pttl={}{}",
+                    String::from_utf8_lossy(key),
+                    entry.value.type_name(),
+                    ttl,
+                    CRLF
+                ));
+            }
+        }
+        log_warning!("Received SIGUSR1, dumping server state");
+        for line in dump.lines().filter(|line| !line.is_empty()) {
+            log_warning!("{}", line);
+        }
+    }
+
+    // 이슈에 첨부할 수 있도록 서버 상태를 하나의 텍스트로 모음, 비밀 값은 가림
+    async fn build_debug_report(&self) -> String {
+        let mut report = format!("# Debug report{}generated_at:{}{}", CRLF, current_time_ms() / 1000, CRLF);
+
+        report.push_str(&format!("{}# Config{}", CRLF, CRLF));
+        {
+            let config = self.config.read().await;
+            let mut keys: Vec<&String> = config.keys().collect();
+            keys.sort();
+            for key in keys {
+                let is_secret = SECRET_CONFIG_MARKERS.iter().any(|marker| key.contains(marker));
+                let value = if is_secret { "(redacted)" } else { config[key].as_str() };
+                report.push_str(&format!("{}:{}{}", key, value, CRLF));
+            }
+        }
+
+        report.push_str(CRLF);
+        report.push_str(&self.build_info(&Some(INFO_SECTION_EVERYTHING.to_string())).await);
+
+        let clients = self.client_manager.list_clients();
+        let repl_guard = self.replication_config.read().await;
+        let replicas = repl_guard.list_slaves().await.len();
+        report.push_str(&format!("{}# Clients{}", CRLF, CRLF));
+        report.push_str(&format!("connected_clients:{}{}", clients.len(), CRLF));
+        report.push_str(&format!("replica_clients:{}{}", replicas, CRLF));
+        report.push_str(&format!(
+            "pubsub_clients:{}{}",
+            clients.iter().filter(|client| client.is_subscribed()).count(),
+            CRLF
+        ));
+        report.push_str(&format!(
+            "oldest_client_age_seconds:{}{}",
+            clients.iter().map(|client| client.connected_at.elapsed().as_secs()).max().unwrap_or(0),
+            CRLF
+        ));
+
+        let stats = self.stats.read().await;
+        report.push_str(&format!("{}# Slowlog{}", CRLF, CRLF));
+        for entry in stats.recent_slow_commands().take(DEBUG_REPORT_SLOWLOG_ENTRIES) {
+            report.push_str(&format!(
+                "slowlog{}:time={},duration_us={},command={},addr={}{}",
+                entry.id, entry.timestamp, entry.duration_us, entry.command, entry.client_addr, CRLF
+            ));
+        }
+        report
+    }
+
+    // 클라이언트가 협상한 프로토콜로 인코딩해서 씀
+    async fn write_reply(&mut self, client_id: u64, command_name: &str, response: &RespValue) {
+        if let Some((_, failed)) = self.current_call.as_mut().filter(|(id, _)| *id == client_id) {
+            *failed |= matches!(response, RespValue::Error(_));
+        }
+        let response = response.encode(self.client_manager.protocol(client_id));
+        self.write_to_client(client_id, command_name, &response).await;
+    }
+
+    // 이벤트마다 부름, 큐가 비었거나 MAX_CORKED_EVENTS개를 처리했으면 그동안 쌓인 응답을 보냄
+    // 파이프라인으로 들어온 명령들의 응답은 마지막 명령 뒤에 한 번에 나감
+    pub async fn uncork(&mut self, queue_idle: bool) {
+        self.corked_events += 1;
+        if queue_idle || self.corked_events >= MAX_CORKED_EVENTS {
+            self.flush_clients().await;
+        }
+    }
+
+    async fn flush_clients(&mut self) {
+        self.complete_concurrent_reads().await;
+        self.corked_events = 0;
+        for client_id in self.client_manager.clients_with_pending_output() {
+            if let Some(client) = self.client_manager.get_client_mut(&client_id) {
+                if let Err(e) = client.flush() {
+                    log_warning!("Failed to write to client {}, closing the connection: {}", client.label(), e);
+                    client.kill();
+                }
+            }
+        }
+    }
+
+    // 쓰기에 실패한 연결은 더 쓸 수 없으므로 닫고, 읽기 태스크가 보내는 ClientDisconnected가 정리를 마무리함
+    async fn write_to_client(&mut self, client_id: u64, command_name: &str, response: &[u8]) {
+        // 읽기 명령 뒤에 온 명령이 검사에서 거절되는 경우처럼, 아직 쓰지 않은 앞선 응답이 있으면 먼저 씀
+        if self.concurrent_reads.has_pending_for(client_id) {
+            self.complete_concurrent_reads().await;
+        }
+        if let Some(client) = self.client_manager.get_client_mut(&client_id) {
+            if let Err(e) = client.write_all(response).await {
+                log_warning!("Failed to write to client {}, closing the connection: {}", client.label(), e);
+                client.kill();
+            } else {
+                self.stats.write().await.record_reply(command_name, response.len());
+            }
+        }
+    }
+
+    // 연결이 끊긴 클라이언트가 남긴 상태를 모두 지움: 구독(클라이언트에 있는 것과 샤드 채널), 트래킹, WATCH, 블로킹 대기, WAIT
+    // 트랜잭션 큐는 Client와 함께 사라짐
+    fn release_client(&mut self, client_id: u64) -> Option<Client> {
+        let client = self.client_manager.remove_client(client_id)?;
+        self.shard_channels.remove_client(client_id);
+        self.tracking_table.remove_client(client_id);
+        self.watch_table.unwatch(client_id);
+        self.blocking.unblock(client_id);
+        self.replica_waits.retain(|wait| wait.client_id != client_id);
+        Some(client)
     }
-} 
\ No newline at end of file
+}
\ No newline at end of file
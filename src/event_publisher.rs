@@ -1,55 +1,314 @@
+use crate::admin::AdminResponse;
+use crate::client_output::ClientOutput;
+use crate::cluster_bus::BusMessage;
 use crate::command::Command;
 use crate::event::RedisEvent;
+use crate::sentinel::SentinelRequest;
+use crate::resp::RespValue;
+use crate::protocol_constants::CRLF;
+use crate::trace::{self, TraceContext};
 use std::net::SocketAddr;
-use tokio::net::tcp::OwnedWriteHalf;
-use tokio::sync::mpsc::Sender;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::sync::mpsc::error::TrySendError;
+use tokio::sync::mpsc::{Sender, UnboundedSender};
+use tokio::sync::oneshot;
 
+// 일반 큐가 이 비율 이상 차면 shed 정책에서 클라이언트 명령을 거절함
+const SHED_THRESHOLD_PERCENT: usize = 90;
+
+#[derive(Default)]
+struct QueueStats {
+    priority_depth: AtomicUsize,
+    normal_high_water: AtomicUsize,
+    priority_high_water: AtomicUsize,
+    shed_commands: AtomicU64,
+}
+
+pub struct QueueSnapshot {
+    pub capacity: usize,
+    pub normal_depth: usize,
+    pub priority_depth: usize,
+    pub normal_high_water: usize,
+    pub priority_high_water: usize,
+    pub shed_commands: u64,
+    pub shed_enabled: bool,
+}
+
+impl QueueSnapshot {
+    pub fn render(&self) -> String {
+        let mut info = String::new();
+        info.push_str(&format!("event_queue_capacity:{}{}", self.capacity, CRLF));
+        info.push_str(&format!("event_queue_depth:{}{}", self.normal_depth, CRLF));
+        info.push_str(&format!("event_queue_priority_depth:{}{}", self.priority_depth, CRLF));
+        info.push_str(&format!("event_queue_high_water:{}{}", self.normal_high_water, CRLF));
+        info.push_str(&format!("event_queue_priority_high_water:{}{}", self.priority_high_water, CRLF));
+        info.push_str(&format!("event_queue_overload_policy:{}{}", if self.shed_enabled { "shed" } else { "block" }, CRLF));
+        info.push_str(&format!("event_queue_shed_commands:{}{}", self.shed_commands, CRLF));
+        info
+    }
+}
+
+// 이벤트는 두 개의 레인으로 나뉨:
+// priority - 복제 링크, 슬레이브 관리, admin 요청, 새 연결 등록
+//   새 연결은 일반 큐가 찼어도 accept 태스크를 멈추지 않아야 스크립트가 이벤트 루프를 잡고 있을 때도 BUSY를 받고 SCRIPT KILL을 보낼 수 있음
+//   핸들러가 명령 처리 중에 직접 보내는 이벤트(전파 등)가 있으므로 크기 제한이 없어야 함,
+//   제한이 있으면 큐가 찼을 때 핸들러가 자기 자신을 기다리며 멈춤
+// normal - 일반 클라이언트의 연결/명령 (클라이언트별 순서 보장을 위해 같은 레인 사용)
 #[derive(Clone)]
 pub struct EventPublisher {
     tx: Sender<RedisEvent>,
+    priority_tx: UnboundedSender<RedisEvent>,
+    capacity: usize,
+    shed_when_overloaded: bool,
+    stats: Arc<QueueStats>,
 }
 
 impl EventPublisher {
-    pub fn new(tx: Sender<RedisEvent>) -> Self {
-        Self { tx }
+    pub fn new(tx: Sender<RedisEvent>, priority_tx: UnboundedSender<RedisEvent>, capacity: usize, shed_when_overloaded: bool) -> Self {
+        Self {
+            tx,
+            priority_tx,
+            capacity,
+            shed_when_overloaded,
+            stats: Arc::new(QueueStats::default()),
+        }
+    }
+
+    fn depth(tx: &Sender<RedisEvent>) -> usize {
+        tx.max_capacity() - tx.capacity()
+    }
+
+    async fn send(&self, event: RedisEvent) -> Result<(), String> {
+        self.tx.send(event).await.map_err(|e| e.to_string())?;
+        self.stats.normal_high_water.fetch_max(Self::depth(&self.tx), Ordering::Relaxed);
+        Ok(())
+    }
+
+    // 큐가 차 있으면 보내지 않고 넘어감, 채널이 닫혔을 때만 에러
+    fn try_send(&self, event: RedisEvent) -> Result<(), String> {
+        match self.tx.try_send(event) {
+            Ok(()) => {
+                self.stats.normal_high_water.fetch_max(Self::depth(&self.tx), Ordering::Relaxed);
+                Ok(())
+            }
+            Err(TrySendError::Full(_)) => Ok(()),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    async fn send_priority(&self, event: RedisEvent) -> Result<(), String> {
+        // 핸들러가 꺼내기 전에 세어야 값이 0 아래로 내려가지 않음
+        let depth = self.stats.priority_depth.fetch_add(1, Ordering::Relaxed) + 1;
+        if let Err(e) = self.priority_tx.send(event) {
+            self.stats.priority_depth.fetch_sub(1, Ordering::Relaxed);
+            return Err(e.to_string());
+        }
+        self.stats.priority_high_water.fetch_max(depth, Ordering::Relaxed);
+        Ok(())
+    }
+
+    // 제한 없는 채널은 길이를 알 수 없으므로 핸들러 루프가 꺼낼 때마다 알려줌
+    pub fn priority_event_received(&self) {
+        self.stats.priority_depth.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    // 핸들러가 명령을 꺼낸 시점의 일반 큐 적재량(꺼낸 명령 포함)으로 과부하 여부를 판단
+    pub fn should_shed(&self) -> bool {
+        if !self.shed_when_overloaded {
+            return false;
+        }
+        let threshold = (self.capacity * SHED_THRESHOLD_PERCENT).div_ceil(100).max(1);
+        Self::depth(&self.tx) + 1 >= threshold
+    }
+
+    pub fn record_shed(&self) {
+        self.stats.shed_commands.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn queue_snapshot(&self) -> QueueSnapshot {
+        QueueSnapshot {
+            capacity: self.capacity,
+            normal_depth: Self::depth(&self.tx),
+            priority_depth: self.stats.priority_depth.load(Ordering::Relaxed),
+            normal_high_water: self.stats.normal_high_water.load(Ordering::Relaxed),
+            priority_high_water: self.stats.priority_high_water.load(Ordering::Relaxed),
+            shed_commands: self.stats.shed_commands.load(Ordering::Relaxed),
+            shed_enabled: self.shed_when_overloaded,
+        }
     }
 
-    pub async fn publish_command(&self, client_id: u64, command: Command) -> Result<(), String> {
-        self.tx.send(RedisEvent::CommandReceived {
+    pub async fn publish_command(&self, client_id: u64, command: Command, trace: Option<TraceContext>) -> Result<(), String> {
+        self.send(RedisEvent::CommandReceived {
             client_id,
             command,
+            trace,
         })
             .await
-            .map_err(|e| format!("Failed to send command event: {}", e))
+            .map_err(|e| format!("Failed to send command event: {}", e))?;
+        trace::record(trace, "queue", &format!("client={}", client_id));
+        Ok(())
+    }
+
+    pub async fn publish_propagate_transaction(&self, exec: bool) -> Result<(), String> {
+        self.send_priority(RedisEvent::PropagateTransaction { exec })
+            .await
+            .map_err(|e| format!("Failed to send propagate transaction event: {}", e))
+    }
+
+    // 복제 스트림은 우선 레인으로 보냄
+    pub async fn publish_master_stream(&self, command: Option<Command>, raw: Vec<u8>, trace: Option<TraceContext>) -> Result<(), String> {
+        self.send_priority(RedisEvent::MasterStream { command, raw, trace })
+            .await
+            .map_err(|e| format!("Failed to send master stream event: {}", e))?;
+        trace::record(trace, "queue", "client=master");
+        Ok(())
+    }
+
+    pub async fn publish_master_resynced(&self) -> Result<(), String> {
+        self.send_priority(RedisEvent::MasterResynced)
+            .await
+            .map_err(|e| format!("Failed to send master resynced event: {}", e))
+    }
+
+    pub async fn publish_cluster_message(&self, message: BusMessage) -> Result<(), String> {
+        self.send_priority(RedisEvent::ClusterMessage { message })
+            .await
+            .map_err(|e| format!("Failed to send cluster message event: {}", e))
+    }
+
+    pub async fn publish_sentinel_reply(&self, request: SentinelRequest, reply: Result<RespValue, String>) -> Result<(), String> {
+        self.send_priority(RedisEvent::SentinelReply { request, reply })
+            .await
+            .map_err(|e| format!("Failed to send sentinel reply event: {}", e))
+    }
+
+    pub async fn publish_sentinel_hello(&self, message: String) -> Result<(), String> {
+        self.send_priority(RedisEvent::SentinelHello { message })
+            .await
+            .map_err(|e| format!("Failed to send sentinel hello event: {}", e))
     }
 
-    pub async fn publish_client_connected(&self, client_id: u64, writer: OwnedWriteHalf, addr: SocketAddr) -> Result<(), String> {
-        self.tx.send(RedisEvent::ClientConnected {
+    pub async fn publish_client_connected(
+        &self,
+        client_id: u64,
+        output: ClientOutput,
+        addr: SocketAddr,
+        local_addr: SocketAddr,
+        kill_switch: oneshot::Sender<()>,
+    ) -> Result<(), String> {
+        // 우선 레인에서 먼저 처리되므로 같은 클라이언트가 일반 레인에 올린 명령보다 늦게 등록되는 일은 없음
+        self.send_priority(RedisEvent::ClientConnected {
             client_id,
-            writer,
+            output,
             addr,
+            local_addr,
+            kill_switch,
         })
             .await
             .map_err(|e| format!("Failed to send client connected event: {}", e))
     }
 
+    pub async fn publish_command_error(&self, client_id: u64, message: String) -> Result<(), String> {
+        self.send(RedisEvent::CommandError {
+            client_id,
+            message,
+        })
+            .await
+            .map_err(|e| format!("Failed to send command error event: {}", e))
+    }
+
     pub async fn publish_client_disconnected(&self, client_id: u64) -> Result<(), String> {
-        self.tx.send(RedisEvent::ClientDisconnected {
+        self.send(RedisEvent::ClientDisconnected {
             client_id,
         })
             .await
             .map_err(|e| format!("Failed to send client disconnected event: {}", e))
     }
 
-    pub async fn publish_slave_connected(&self, addr: SocketAddr) -> Result<(), String> {
-        self.tx.send(RedisEvent::SlaveConnected { addr })
+    pub async fn publish_slave_listening_port(&self, client_id: u64, listening_port: Option<u16>) -> Result<(), String> {
+        self.send_priority(RedisEvent::SlaveListeningPort { client_id, listening_port })
+            .await
+            .map_err(|e| format!("Failed to send slave listening port event: {}", e))
+    }
+
+    pub async fn publish_slave_announced(&self, client_id: u64, ip: String) -> Result<(), String> {
+        self.send_priority(RedisEvent::SlaveAnnounced { client_id, ip })
+            .await
+            .map_err(|e| format!("Failed to send slave announced event: {}", e))
+    }
+
+    pub async fn publish_slave_acked(&self, client_id: u64, offset: i64) -> Result<(), String> {
+        self.send_priority(RedisEvent::SlaveAcked { client_id, offset })
             .await
-            .map_err(|e| format!("Failed to send slave connected event: {}", e))
+            .map_err(|e| format!("Failed to send slave acked event: {}", e))
     }
 
-    pub async fn publish_propagate_slave(&self, message: String) -> Result<(), String> {
-        self.tx.send(RedisEvent::PropagateSlave { message })
+    pub async fn publish_slave_disconnected(&self, client_id: u64, replica_id: u64) -> Result<(), String> {
+        self.send_priority(RedisEvent::SlaveDisconnected { client_id, replica_id })
+            .await
+            .map_err(|e| format!("Failed to send slave disconnected event: {}", e))
+    }
+
+    pub async fn publish_replica_ack_probe(&self) -> Result<(), String> {
+        self.send_priority(RedisEvent::ReplicaAckProbe)
+            .await
+            .map_err(|e| format!("Failed to send replica ack probe event: {}", e))
+    }
+
+    // 이벤트 루프가 밀려 큐가 차 있으면 이번 주기는 건너뜀, 기다리면 스크립트가 도는 동안 큐를 채워 새 연결과 명령을 막음
+    pub fn publish_active_expire_cycle(&self) -> Result<(), String> {
+        self.try_send(RedisEvent::ActiveExpireCycle)
+            .map_err(|e| format!("Failed to send active expire cycle event: {}", e))
+    }
+
+    pub async fn publish_background_save_finished(&self, result: Result<(), String>) -> Result<(), String> {
+        self.send_priority(RedisEvent::BackgroundSaveFinished { result })
+            .await
+            .map_err(|e| format!("Failed to send background save finished event: {}", e))
+    }
+
+    pub async fn publish_shutdown_requested(&self) -> Result<(), String> {
+        self.send_priority(RedisEvent::ShutdownRequested)
+            .await
+            .map_err(|e| format!("Failed to send shutdown requested event: {}", e))
+    }
+
+    pub async fn publish_debug_dump_requested(&self) -> Result<(), String> {
+        self.send_priority(RedisEvent::DebugDumpRequested)
+            .await
+            .map_err(|e| format!("Failed to send debug dump requested event: {}", e))
+    }
+
+    pub async fn publish_propagate_slave(&self, message: Vec<u8>, trace: Option<TraceContext>) -> Result<(), String> {
+        self.send_priority(RedisEvent::PropagateSlave { message, trace })
             .await
             .map_err(|e| format!("Failed to send propagate slave event: {}", e))
     }
-} 
\ No newline at end of file
+
+    pub async fn publish_keyspace_notification(&self, class: u32, event: &'static str, key: &[u8]) -> Result<(), String> {
+        self.send_priority(RedisEvent::KeyspaceNotification { class, event, key: key.to_vec() })
+            .await
+            .map_err(|e| format!("Failed to send keyspace notification event: {}", e))
+    }
+
+    pub async fn publish_expired_keys_found(&self, keys: Vec<Vec<u8>>, trace: Option<TraceContext>) -> Result<(), String> {
+        self.send_priority(RedisEvent::ExpiredKeysFound { keys, trace })
+            .await
+            .map_err(|e| format!("Failed to send expired keys found event: {}", e))
+    }
+
+    pub async fn publish_promotion_drained(&self, client_id: u64) -> Result<(), String> {
+        self.send_priority(RedisEvent::PromotionDrained { client_id })
+            .await
+            .map_err(|e| format!("Failed to send promotion drained event: {}", e))
+    }
+
+    pub async fn publish_admin_request(&self, path: String) -> Result<AdminResponse, String> {
+        let (reply, response) = oneshot::channel();
+        self.send_priority(RedisEvent::AdminRequest { path, reply })
+            .await
+            .map_err(|e| format!("Failed to send admin request event: {}", e))?;
+        response.await.map_err(|e| format!("Admin request was dropped: {}", e))
+    }
+}
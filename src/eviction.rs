@@ -0,0 +1,120 @@
+use crate::config_handler::Db;
+use crate::keyspace::KeySet;
+use crate::value_entry::ValueEntry;
+
+// Redis 기본 maxmemory-samples
+pub const DEFAULT_EVICTION_SAMPLES: usize = 5;
+// Redis의 EVPOOL_SIZE
+const EVICTION_POOL_SIZE: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EvictionPolicy {
+    NoEviction,
+    AllKeysLru,
+    VolatileLru,
+    AllKeysLfu,
+    VolatileLfu,
+}
+
+impl EvictionPolicy {
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name.to_lowercase().as_str() {
+            "noeviction" => Ok(EvictionPolicy::NoEviction),
+            "allkeys-lru" => Ok(EvictionPolicy::AllKeysLru),
+            "volatile-lru" => Ok(EvictionPolicy::VolatileLru),
+            "allkeys-lfu" => Ok(EvictionPolicy::AllKeysLfu),
+            "volatile-lfu" => Ok(EvictionPolicy::VolatileLfu),
+            _ => Err(format!("Unknown maxmemory policy '{}'", name)),
+        }
+    }
+
+    fn is_candidate(&self, entry: &ValueEntry) -> bool {
+        match self {
+            EvictionPolicy::NoEviction => false,
+            EvictionPolicy::AllKeysLru | EvictionPolicy::AllKeysLfu => true,
+            EvictionPolicy::VolatileLru | EvictionPolicy::VolatileLfu => entry.expiration_ms().is_some(),
+        }
+    }
+
+    // volatile 정책은 TTL이 있는 키 중에서만 고름
+    fn candidates<'a>(&self, db: &'a Db) -> Option<&'a KeySet> {
+        match self {
+            EvictionPolicy::NoEviction => None,
+            EvictionPolicy::AllKeysLru | EvictionPolicy::AllKeysLfu => Some(db.key_set()),
+            EvictionPolicy::VolatileLru | EvictionPolicy::VolatileLfu => Some(db.volatile()),
+        }
+    }
+
+    // 점수가 클수록 먼저 지움: LRU는 유휴 시간, LFU는 (감소가 반영된) 접근 빈도가 낮을수록
+    fn eviction_score(&self, db: &Db, entry: &ValueEntry) -> u64 {
+        match self {
+            EvictionPolicy::AllKeysLfu | EvictionPolicy::VolatileLfu => (u8::MAX - db.lfu_frequency(entry)) as u64,
+            _ => entry.idle_ms(),
+        }
+    }
+}
+
+// 1048576, 100kb, 64mb, 1gb 같은 메모리 크기
+pub fn parse_memory(value: &str) -> Result<u64, String> {
+    let lower = value.trim().to_lowercase();
+    let (digits, unit) = match lower.find(|c: char| !c.is_ascii_digit()) {
+        Some(index) => lower.split_at(index),
+        None => (lower.as_str(), ""),
+    };
+    let multiplier = match unit {
+        "" | "b" => 1,
+        "k" => 1000,
+        "kb" => 1024,
+        "m" => 1000 * 1000,
+        "mb" => 1024 * 1024,
+        "g" => 1000 * 1000 * 1000,
+        "gb" => 1024 * 1024 * 1024,
+        _ => return Err(format!("Invalid memory amount '{}'", value)),
+    };
+    digits
+        .parse::<u64>()
+        .map(|amount| amount.saturating_mul(multiplier))
+        .map_err(|_| format!("Invalid memory amount '{}'", value))
+}
+
+// Redis의 근사 LRU/LFU: 키를 지울 때마다 maxmemory-samples개를 무작위로 뽑아 풀에 넣고, 풀에서 점수가 가장 큰 키를 지움
+// 풀은 점수 오름차순이며 호출 사이에 유지되므로 앞선 표본의 좋은 후보도 다음 선택에 쓰임
+#[derive(Default)]
+pub struct EvictionPool {
+    entries: Vec<(u64, Vec<u8>)>,
+}
+
+impl EvictionPool {
+    pub fn select_victim(&mut self, db: &Db, policy: EvictionPolicy, samples: usize) -> Option<Vec<u8>> {
+        let candidates = policy.candidates(db)?;
+        for _ in 0..samples.min(candidates.len()) {
+            let Some(key) = candidates.random(db.random()) else {
+                break;
+            };
+            if let Some(entry) = db.get(key) {
+                self.offer(policy.eviction_score(db, entry), key);
+            }
+        }
+        // 풀에 남아 있던 키는 그 사이에 지워졌거나 정책이 바뀌어 후보가 아닐 수 있음
+        while let Some((_, key)) = self.entries.pop() {
+            if db.get(&key).is_some_and(|entry| policy.is_candidate(entry)) {
+                return Some(key);
+            }
+        }
+        None
+    }
+
+    fn offer(&mut self, score: u64, key: &[u8]) {
+        if let Some(position) = self.entries.iter().position(|(_, existing)| existing == key) {
+            self.entries.remove(position);
+        }
+        if self.entries.len() == EVICTION_POOL_SIZE {
+            if self.entries[0].0 >= score {
+                return;
+            }
+            self.entries.remove(0);
+        }
+        let position = self.entries.partition_point(|(existing, _)| *existing < score);
+        self.entries.insert(position, (score, key.to_vec()));
+    }
+}
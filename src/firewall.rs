@@ -0,0 +1,114 @@
+use crate::command::CommandCategory;
+use std::net::IpAddr;
+
+#[derive(Debug, Clone)]
+pub struct FirewallRule {
+    network: IpAddr,
+    prefix_len: u8,
+    categories: Vec<CommandCategory>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Firewall {
+    rules: Vec<FirewallRule>,
+}
+
+impl Firewall {
+    // "10.0.2.0/24=read,connection;127.0.0.0/8=all" 형식의 규칙 목록
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let mut rules = Vec::new();
+
+        for rule in spec.split(';').map(str::trim).filter(|rule| !rule.is_empty()) {
+            let (cidr, categories) = rule
+                .split_once('=')
+                .ok_or_else(|| format!("Firewall rule '{}' must look like <cidr>=<categories>", rule))?;
+            let (network, prefix_len) = Self::parse_cidr(cidr.trim())?;
+
+            let mut parsed_categories = Vec::new();
+            for category in categories.split(',').map(str::trim) {
+                if category.eq_ignore_ascii_case("all") {
+                    parsed_categories.extend([
+                        CommandCategory::Connection,
+                        CommandCategory::Read,
+                        CommandCategory::Write,
+                        CommandCategory::Admin,
+                        CommandCategory::PubSub,
+                        CommandCategory::Scripting,
+                    ]);
+                } else {
+                    parsed_categories.push(
+                        CommandCategory::from_name(category)
+                            .ok_or_else(|| format!("Unknown command category '{}' in firewall rule", category))?,
+                    );
+                }
+            }
+
+            rules.push(FirewallRule {
+                network,
+                prefix_len,
+                categories: parsed_categories,
+            });
+        }
+
+        Ok(Self { rules })
+    }
+
+    pub fn is_allowed(&self, ip: IpAddr, category: CommandCategory) -> bool {
+        let ip = Self::canonical(ip);
+        self.rules
+            .iter()
+            .filter(|rule| Self::contains(rule, ip))
+            .max_by_key(|rule| rule.prefix_len)
+            .map(|rule| rule.categories.contains(&category))
+            .unwrap_or(true)
+    }
+
+    fn parse_cidr(cidr: &str) -> Result<(IpAddr, u8), String> {
+        let (addr, prefix) = match cidr.split_once('/') {
+            Some((addr, prefix)) => (addr, Some(prefix)),
+            None => (cidr, None),
+        };
+        let network: IpAddr = addr
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .parse()
+            .map_err(|_| format!("Invalid network address '{}' in firewall rule", addr))?;
+        let max_len = if network.is_ipv4() { 32 } else { 128 };
+        let prefix_len = match prefix {
+            Some(prefix) => prefix
+                .parse::<u8>()
+                .ok()
+                .filter(|len| *len <= max_len)
+                .ok_or_else(|| format!("Invalid prefix length '{}' in firewall rule", prefix))?,
+            None => max_len,
+        };
+        Ok((network, prefix_len))
+    }
+
+    fn canonical(ip: IpAddr) -> IpAddr {
+        match ip {
+            IpAddr::V6(v6) => v6.to_ipv4_mapped().map(IpAddr::V4).unwrap_or(ip),
+            IpAddr::V4(_) => ip,
+        }
+    }
+
+    fn contains(rule: &FirewallRule, ip: IpAddr) -> bool {
+        match (rule.network, ip) {
+            (IpAddr::V4(network), IpAddr::V4(ip)) => {
+                Self::prefix_matches(u32::from(network) as u128, u32::from(ip) as u128, rule.prefix_len, 32)
+            }
+            (IpAddr::V6(network), IpAddr::V6(ip)) => {
+                Self::prefix_matches(u128::from(network), u128::from(ip), rule.prefix_len, 128)
+            }
+            _ => false,
+        }
+    }
+
+    fn prefix_matches(network: u128, ip: u128, prefix_len: u8, bits: u8) -> bool {
+        if prefix_len == 0 {
+            return true;
+        }
+        let shift = (bits - prefix_len) as u32;
+        (network >> shift) == (ip >> shift)
+    }
+}
@@ -0,0 +1,116 @@
+use crate::command::{Command, CommandCategory};
+use crate::event_handler::EventHandler;
+use crate::notify;
+use crate::protocol_constants::EXPIRED_EVENT;
+use crate::resp::RespValue;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use tokio::time::Duration;
+
+pub type HookFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+// 실행하려는(또는 실행한) 명령과 그 명령을 보낸 클라이언트
+pub struct CommandCall<'a> {
+    pub client_id: u64,
+    pub client_addr: SocketAddr,
+    pub command: &'a Command,
+}
+
+pub struct CallResult {
+    pub duration: Duration,
+}
+
+// 명령마다 공통으로 해야 하는 일(슬로우로그, 클라이언트 추적, 키스페이스 알림 등)을 명령 코드 밖에 두는 확장 지점
+// 훅은 상태를 갖지 않고 이벤트 핸들러를 넘겨받아 쓰므로 이벤트 루프 안에서 순서대로 불림
+// 이벤트 핸들러가 직접 처리하는 명령(INFO, CLIENT 등)은 post_execute까지 오지 않음
+pub trait CommandHook: Sync {
+    // 실행 전에 불림, 에러 응답을 돌려주면 명령을 실행하지 않고 그 응답을 보냄
+    fn pre_execute<'a>(&'a self, _handler: &'a mut EventHandler, _call: &'a CommandCall<'a>) -> HookFuture<'a, Result<(), RespValue>> {
+        Box::pin(async { Ok(()) })
+    }
+
+    fn post_execute<'a>(&'a self, _handler: &'a mut EventHandler, _call: &'a CommandCall<'a>, _result: &'a CallResult) -> HookFuture<'a, ()> {
+        Box::pin(async {})
+    }
+
+    // 만료 주기나 읽기 명령이 만난 만료된 키를 이벤트 루프가 지운 뒤 불림
+    fn on_expire<'a>(&'a self, _handler: &'a mut EventHandler, _key: &'a [u8]) -> HookFuture<'a, ()> {
+        Box::pin(async {})
+    }
+}
+
+// 실행 시간이 slowlog-log-slower-than을 넘은 명령을 남김
+struct SlowlogHook;
+
+impl CommandHook for SlowlogHook {
+    fn post_execute<'a>(&'a self, handler: &'a mut EventHandler, call: &'a CommandCall<'a>, result: &'a CallResult) -> HookFuture<'a, ()> {
+        Box::pin(handler.record_command_duration(call.command.name(), call.client_addr, result.duration))
+    }
+}
+
+// 기본 모드의 추적 클라이언트가 읽은 키를 기억해 두고, 쓰기 명령이나 만료로 바뀐 키는 무효화함
+struct TrackingHook;
+
+impl CommandHook for TrackingHook {
+    fn post_execute<'a>(&'a self, handler: &'a mut EventHandler, call: &'a CommandCall<'a>, _result: &'a CallResult) -> HookFuture<'a, ()> {
+        Box::pin(async move {
+            match call.command.category() {
+                CommandCategory::Read => handler.track_read_keys(call.client_id, call.command),
+                CommandCategory::Write => handler.invalidate_command_keys(call.command, Some(call.client_id)).await,
+                _ => {}
+            }
+        })
+    }
+
+    fn on_expire<'a>(&'a self, handler: &'a mut EventHandler, key: &'a [u8]) -> HookFuture<'a, ()> {
+        Box::pin(async move {
+            let key = key.to_vec();
+            handler.invalidate_keys(&[&key], None).await;
+        })
+    }
+}
+
+// 명령이 바꾼 키의 알림은 명령이 직접 보내고, 만료는 명령 없이 일어나므로 여기서 보냄
+struct KeyspaceNotificationHook;
+
+impl CommandHook for KeyspaceNotificationHook {
+    fn on_expire<'a>(&'a self, handler: &'a mut EventHandler, key: &'a [u8]) -> HookFuture<'a, ()> {
+        Box::pin(handler.notify_keyspace_event(notify::NOTIFY_EXPIRED, EXPIRED_EVENT, key))
+    }
+}
+
+// ACL 사용자의 네임스페이스가 maxkeys/maxmemory를 넘었으면 쓰기를 막음
+struct NamespaceQuotaHook;
+
+impl CommandHook for NamespaceQuotaHook {
+    fn pre_execute<'a>(&'a self, handler: &'a mut EventHandler, call: &'a CommandCall<'a>) -> HookFuture<'a, Result<(), RespValue>> {
+        Box::pin(async move { handler.check_namespace_quota(call.client_id, call.command).await.map_err(RespValue::from) })
+    }
+}
+
+// 등록한 순서대로 불림
+static BUILTIN_HOOKS: &[&dyn CommandHook] = &[&SlowlogHook, &TrackingHook, &KeyspaceNotificationHook, &NamespaceQuotaHook];
+
+pub struct HookRegistry {
+    hooks: Vec<&'static dyn CommandHook>,
+}
+
+impl HookRegistry {
+    pub fn new() -> Self {
+        let mut registry = Self { hooks: Vec::new() };
+        for hook in BUILTIN_HOOKS {
+            registry.register(*hook);
+        }
+        registry
+    }
+
+    pub fn register(&mut self, hook: &'static dyn CommandHook) {
+        self.hooks.push(hook);
+    }
+
+    // 훅이 이벤트 핸들러를 빌려 쓰는 동안 목록을 붙잡고 있지 않도록 복사해서 돌려줌
+    pub fn hooks(&self) -> Vec<&'static dyn CommandHook> {
+        self.hooks.clone()
+    }
+}
@@ -0,0 +1,376 @@
+use crate::memory::DEFAULT_USAGE_SAMPLES;
+use crate::random::Random;
+use crate::value_encoding::ListpackLimits;
+use crate::value_entry::{LfuConfig, ValueEntry, ENTRY_OVERHEAD_BYTES, KEY_INDEX_OVERHEAD_BYTES, VOLATILE_INDEX_OVERHEAD_BYTES};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeSet, HashMap};
+use std::hash::{Hash, Hasher};
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+
+// 키스페이스와 그 메모리 사용량 추정치, 바꿀 때마다 바뀐 엔트리만 다시 재서 합계를 고침
+// maxmemory, INFO memory, MEMORY USAGE가 모두 이 합계를 쓰므로 키스페이스를 훑지 않음
+// 읽기는 Deref로 HashMap을 그대로 쓰고, 쓰기는 아래 메서드로만 하게 DerefMut은 두지 않음
+// 접근 기록(LRU/LFU)과 무작위 선택에 쓰는 설정과 난수 생성기도 서버마다 여기에 둠
+// 키 바이트는 Arc로 한 벌만 두고 아래 색인들은 같은 Arc를 나눠 가짐
+pub struct Keyspace {
+    entries: HashMap<Arc<[u8]>, ValueEntry>,
+    used_memory: usize,
+    key_bytes: usize,
+    // 모든 키, 축출이 HashMap에서는 할 수 없는 무작위 접근으로 표본을 뽑음
+    key_set: KeySet,
+    // (해시, 키) 순으로 정렬된 모든 키, SCAN이 커서 위치부터 COUNT개만 꺼내 씀
+    scan_order: BTreeSet<(u64, Arc<[u8]>)>,
+    // TTL이 있는 키, 능동 만료가 키스페이스를 훑지 않고 여기서 표본을 뽑음
+    volatile: KeySet,
+    // 필드에 TTL이 있는 해시, HEXPIRE/HPERSIST/HDEL이 get_mut으로 바꾼 결과가 반영됨
+    volatile_hashes: KeySet,
+    // ACL 사용자의 키 접두사별 사용량, 할당량 검사가 키스페이스를 훑지 않고 여기서 읽음
+    namespaces: NamespaceUsages,
+    lfu: LfuConfig,
+    listpack: ListpackLimits,
+    random: Arc<Random>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct NamespaceUsage {
+    pub keys: usize,
+    pub memory: usize,
+}
+
+// 접두사는 ACL 사용자 수만큼이라 적으므로 키마다 모두 비교함, 겹치는 접두사는 각각 셈
+#[derive(Default)]
+struct NamespaceUsages(Vec<(Vec<u8>, NamespaceUsage)>);
+
+impl NamespaceUsages {
+    fn charge(&mut self, key: &[u8], keys: isize, memory: isize) {
+        for (prefix, usage) in &mut self.0 {
+            if key.starts_with(prefix) {
+                usage.keys = usage.keys.saturating_add_signed(keys);
+                usage.memory = usage.memory.saturating_add_signed(memory);
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        for (_, usage) in &mut self.0 {
+            *usage = NamespaceUsage::default();
+        }
+    }
+}
+
+// 무작위로 하나를 뽑을 수 있는 키 집합, 지울 때는 마지막 키를 빈 자리로 옮김
+#[derive(Default)]
+pub struct KeySet {
+    keys: Vec<Arc<[u8]>>,
+    positions: HashMap<Arc<[u8]>, usize>,
+}
+
+impl KeySet {
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    pub fn random(&self, random: &Random) -> Option<&Arc<[u8]>> {
+        if self.keys.is_empty() {
+            return None;
+        }
+        self.keys.get(random.below(self.keys.len()))
+    }
+
+    // 키스페이스가 가진 Arc를 찾아 줌, 다른 색인에 넣을 때 키 바이트를 복사하지 않고 이것을 나눠 씀
+    fn shared(&self, key: &[u8]) -> Option<&Arc<[u8]>> {
+        self.positions.get(key).map(|&position| &self.keys[position])
+    }
+
+    fn update(&mut self, key: &Arc<[u8]>, member: bool) {
+        match (member, self.positions.contains_key(key)) {
+            (true, false) => {
+                self.positions.insert(key.clone(), self.keys.len());
+                self.keys.push(key.clone());
+            }
+            (false, true) => self.remove(key),
+            _ => {}
+        }
+    }
+
+    fn remove(&mut self, key: &[u8]) {
+        let Some(position) = self.positions.remove(key) else {
+            return;
+        };
+        self.keys.swap_remove(position);
+        if let Some(moved) = self.keys.get(position) {
+            self.positions.insert(moved.clone(), position);
+        }
+    }
+
+    fn clear(&mut self) {
+        self.keys.clear();
+        self.positions.clear();
+    }
+}
+
+// SCAN 커서가 가리키는 키의 위치, HashMap의 버킷 배치와 상관없어서 재해시로 테이블이 커지거나 줄어도 바뀌지 않음
+pub fn scan_hash(key: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    hasher.finish()
+}
+
+// get_mut이 돌려주는 엔트리, 놓을 때 크기를 다시 재서 합계에 반영하고 TTL이 생기거나 없어졌으면 색인을 고침
+pub struct EntryMut<'a> {
+    entry: &'a mut ValueEntry,
+    key: &'a [u8],
+    keys: &'a KeySet,
+    used_memory: &'a mut usize,
+    volatile: &'a mut KeySet,
+    volatile_hashes: &'a mut KeySet,
+    namespaces: &'a mut NamespaceUsages,
+    lfu: LfuConfig,
+    random: &'a Random,
+}
+
+impl EntryMut<'_> {
+    pub fn touch(&self) {
+        self.entry.touch(self.lfu, self.random);
+    }
+}
+
+impl Deref for EntryMut<'_> {
+    type Target = ValueEntry;
+
+    fn deref(&self) -> &ValueEntry {
+        self.entry
+    }
+}
+
+impl DerefMut for EntryMut<'_> {
+    fn deref_mut(&mut self) -> &mut ValueEntry {
+        self.entry
+    }
+}
+
+impl Drop for EntryMut<'_> {
+    fn drop(&mut self) {
+        let previous = account(self.entry, self.key.len());
+        *self.used_memory = *self.used_memory - previous + self.entry.accounted_size();
+        self.namespaces.charge(self.key, 0, self.entry.accounted_size() as isize - previous as isize);
+        let key = self.keys.shared(self.key).expect("every entry is in the key set");
+        self.volatile.update(key, self.entry.expiration_ms().is_some());
+        self.volatile_hashes.update(key, self.entry.has_field_expirations());
+    }
+}
+
+// 큰 컬렉션은 MEMORY USAGE 기본값처럼 앞의 원소 몇 개로 추정하므로 쓰기마다 전체를 훑지 않음
+// 키가 들어 있는 색인도 함께 세므로 TTL이 생기거나 없어지면 크기가 바뀜
+// 엔트리에 새 크기를 적고 이전에 적어 둔 크기를 돌려줌
+fn account(entry: &mut ValueEntry, key_len: usize) -> usize {
+    let size = entry.key_overhead(key_len) + entry.value.sampled_size(DEFAULT_USAGE_SAMPLES);
+    entry.set_accounted_size(size)
+}
+
+impl Deref for Keyspace {
+    type Target = HashMap<Arc<[u8]>, ValueEntry>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.entries
+    }
+}
+
+impl Keyspace {
+    pub fn new(random: Arc<Random>) -> Self {
+        Self {
+            entries: HashMap::new(),
+            used_memory: 0,
+            key_bytes: 0,
+            key_set: KeySet::default(),
+            scan_order: BTreeSet::new(),
+            volatile: KeySet::default(),
+            volatile_hashes: KeySet::default(),
+            namespaces: NamespaceUsages::default(),
+            lfu: LfuConfig::default(),
+            listpack: ListpackLimits::default(),
+            random,
+        }
+    }
+
+    pub fn random(&self) -> &Random {
+        &self.random
+    }
+
+    pub fn set_lfu(&mut self, lfu: LfuConfig) {
+        self.lfu = lfu;
+    }
+
+    pub fn set_listpack_limits(&mut self, listpack: ListpackLimits) {
+        self.listpack = listpack;
+    }
+
+    // 컬렉션에 원소를 넣을 때 넘겨서 listpack으로 둘지 정함
+    pub fn listpack_limits(&self) -> ListpackLimits {
+        self.listpack
+    }
+
+    // 읽기 명령이 찾은 엔트리의 접근을 기록함, 고칠 엔트리는 get_mut이 돌려준 EntryMut::touch를 씀
+    pub fn touch(&self, entry: &ValueEntry) {
+        entry.touch(self.lfu, &self.random);
+    }
+
+    pub fn lfu_frequency(&self, entry: &ValueEntry) -> u8 {
+        entry.lfu_frequency(self.lfu)
+    }
+
+    pub fn used_memory(&self) -> usize {
+        self.used_memory
+    }
+
+    // 접두사마다 지금 있는 키를 한 번 훑어 세고, 이미 세고 있던 접두사는 그대로 둠
+    pub fn track_namespaces(&mut self, prefixes: Vec<Vec<u8>>) {
+        let mut tracked = std::mem::take(&mut self.namespaces.0);
+        for prefix in prefixes {
+            if self.namespaces.0.iter().any(|(tracking, _)| *tracking == prefix) {
+                continue;
+            }
+            let usage = match tracked.iter().position(|(tracking, _)| *tracking == prefix) {
+                Some(index) => tracked.swap_remove(index).1,
+                None => self.entries.iter().filter(|(key, _)| key.starts_with(&prefix)).fold(NamespaceUsage::default(), |usage, (_, entry)| {
+                    NamespaceUsage { keys: usage.keys + 1, memory: usage.memory + entry.accounted_size() }
+                }),
+            };
+            self.namespaces.0.push((prefix, usage));
+        }
+    }
+
+    pub fn namespace_usage(&self, prefix: &[u8]) -> Option<NamespaceUsage> {
+        self.namespaces.0.iter().find(|(tracking, _)| tracking == prefix).map(|(_, usage)| *usage)
+    }
+
+    // 키 이름, 엔트리 헤더와 색인을 뺀 값만의 크기
+    pub fn dataset(&self) -> usize {
+        let volatile_indexes = self.volatile.len() + self.volatile_hashes.len();
+        self.used_memory
+            - self.entries.len() * (ENTRY_OVERHEAD_BYTES + KEY_INDEX_OVERHEAD_BYTES)
+            - self.key_bytes
+            - volatile_indexes * VOLATILE_INDEX_OVERHEAD_BYTES
+    }
+
+    pub fn insert(&mut self, key: Vec<u8>, mut entry: ValueEntry) -> Option<ValueEntry> {
+        account(&mut entry, key.len());
+        self.used_memory += entry.accounted_size();
+        // 이미 있는 키면 그 Arc를 그대로 쓰고, 새 키일 때만 키 바이트를 Arc로 한 번 옮김
+        let (key, previous) = match self.entries.get_key_value(key.as_slice()) {
+            Some((shared, previous)) => (shared.clone(), Some(previous.accounted_size())),
+            None => (Arc::from(key), None),
+        };
+        self.key_set.update(&key, true);
+        self.volatile.update(&key, entry.expiration_ms().is_some());
+        self.volatile_hashes.update(&key, entry.has_field_expirations());
+        if previous.is_none() {
+            self.key_bytes += key.len();
+            self.scan_order.insert((scan_hash(&key), key.clone()));
+        }
+        self.namespaces.charge(&key, previous.is_none() as isize, entry.accounted_size() as isize - previous.unwrap_or(0) as isize);
+        let previous = self.entries.insert(key, entry);
+        if let Some(previous) = &previous {
+            self.used_memory -= previous.accounted_size();
+        }
+        previous
+    }
+
+    pub fn remove(&mut self, key: &[u8]) -> Option<ValueEntry> {
+        let (shared, entry) = self.entries.remove_entry(key)?;
+        self.used_memory -= entry.accounted_size();
+        self.key_bytes -= key.len();
+        self.key_set.remove(key);
+        self.scan_order.remove(&(scan_hash(key), shared));
+        self.volatile.remove(key);
+        self.volatile_hashes.remove(key);
+        self.namespaces.charge(key, -1, -(entry.accounted_size() as isize));
+        Some(entry)
+    }
+
+    pub fn key_set(&self) -> &KeySet {
+        &self.key_set
+    }
+
+    // 해시가 cursor 이상인 키를 해시 순서로 돌려줌, 건너뛴 키를 훑지 않으므로 한 번의 SCAN은 COUNT에 비례하는 일만 함
+    pub fn scan_from(&self, cursor: u64) -> impl Iterator<Item = (u64, &Arc<[u8]>)> {
+        self.scan_order.range((cursor, Arc::from(&[][..]))..).map(|(hash, key)| (*hash, key))
+    }
+
+    pub fn volatile(&self) -> &KeySet {
+        &self.volatile
+    }
+
+    pub fn volatile_hashes(&self) -> &KeySet {
+        &self.volatile_hashes
+    }
+
+    // 만료 시각이 지났지만 아직 지워지지 않은 키를 뺀 순회, 키를 나열하거나 세는 곳은 모두 이것을 씀
+    pub fn alive(&self) -> impl Iterator<Item = (&Arc<[u8]>, &ValueEntry)> {
+        self.entries.iter().filter(|(_, entry)| !entry.is_expired())
+    }
+
+    // 키가 Arc<[u8]>라 HashMap::get에는 &Vec<u8>을 그대로 넘길 수 없어서 바이트 슬라이스로 받는 것을 둠
+    pub fn get(&self, key: &[u8]) -> Option<&ValueEntry> {
+        self.entries.get(key)
+    }
+
+    pub fn contains_key(&self, key: &[u8]) -> bool {
+        self.entries.contains_key(key)
+    }
+
+    pub fn is_alive(&self, key: &[u8]) -> bool {
+        self.entries.get(key).is_some_and(|entry| !entry.is_expired())
+    }
+
+    pub fn get_mut<'a>(&'a mut self, key: &'a [u8]) -> Option<EntryMut<'a>> {
+        let entry = self.entries.get_mut(key)?;
+        Some(EntryMut {
+            entry,
+            key,
+            keys: &self.key_set,
+            used_memory: &mut self.used_memory,
+            volatile: &mut self.volatile,
+            volatile_hashes: &mut self.volatile_hashes,
+            namespaces: &mut self.namespaces,
+            lfu: self.lfu,
+            random: &self.random,
+        })
+    }
+
+    pub fn get_or_insert_with<'a>(&'a mut self, key: &'a [u8], default: impl FnOnce() -> ValueEntry) -> EntryMut<'a> {
+        if !self.entries.contains_key(key) {
+            self.insert(key.to_vec(), default());
+        }
+        self.get_mut(key).expect("entry was just inserted")
+    }
+
+    pub fn reserve(&mut self, additional: usize) {
+        self.entries.reserve(additional);
+    }
+
+    // 설정은 그대로 둔 빈 키스페이스로 바꾸고 이전 내용을 돌려줌, FLUSHALL ASYNC가 백그라운드에서 해제함
+    pub fn take(&mut self) -> Keyspace {
+        let mut empty = Keyspace::new(self.random.clone());
+        empty.lfu = self.lfu;
+        empty.listpack = self.listpack;
+        empty.namespaces.0 = self.namespaces.0.iter().map(|(prefix, _)| (prefix.clone(), NamespaceUsage::default())).collect();
+        std::mem::replace(self, empty)
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.used_memory = 0;
+        self.key_bytes = 0;
+        self.key_set.clear();
+        self.scan_order.clear();
+        self.volatile.clear();
+        self.volatile_hashes.clear();
+        self.namespaces.reset();
+    }
+}
@@ -0,0 +1,129 @@
+use crate::protocol_constants::CRLF;
+use crate::resp::RespValue;
+use std::collections::{BTreeMap, VecDeque};
+
+// Redis처럼 이벤트마다 최근 160개의 스파이크만 남기고, 같은 초에 여러 번이면 가장 큰 값만 남김
+const LATENCY_HISTORY_LEN: usize = 160;
+
+pub const LATENCY_EVENT_COMMAND: &str = "command";
+pub const LATENCY_EVENT_FORK: &str = "fork";
+pub const LATENCY_EVENT_EXPIRE_CYCLE: &str = "expire-cycle";
+
+#[derive(Debug, Clone, Copy)]
+struct LatencySample {
+    timestamp: u64,
+    latency_ms: u64,
+}
+
+#[derive(Debug, Default)]
+struct LatencyEvent {
+    samples: VecDeque<LatencySample>,
+    max_ms: u64,
+}
+
+// latency-monitor-threshold(ms) 이상 걸린 작업을 이벤트 종류별로 모음, LATENCY 명령으로 조회함
+#[derive(Debug, Default)]
+pub struct LatencyMonitor {
+    events: BTreeMap<String, LatencyEvent>,
+}
+
+impl LatencyMonitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&mut self, event: &str, latency_ms: u64, timestamp: u64) {
+        let entry = self.events.entry(event.to_string()).or_default();
+        entry.max_ms = entry.max_ms.max(latency_ms);
+        match entry.samples.back_mut() {
+            Some(last) if last.timestamp == timestamp => last.latency_ms = last.latency_ms.max(latency_ms),
+            _ => {
+                if entry.samples.len() == LATENCY_HISTORY_LEN {
+                    entry.samples.pop_front();
+                }
+                entry.samples.push_back(LatencySample { timestamp, latency_ms });
+            }
+        }
+    }
+
+    // 이벤트를 주지 않으면 모두 지움, 지운 이벤트 수를 돌려줌
+    pub fn reset(&mut self, events: &[String]) -> usize {
+        if events.is_empty() {
+            let count = self.events.len();
+            self.events.clear();
+            return count;
+        }
+        events.iter().filter(|event| self.events.remove(event.as_str()).is_some()).count()
+    }
+
+    // [이벤트, 마지막 스파이크 시각, 마지막 값(ms), 최댓값(ms)]
+    pub fn latest(&self) -> RespValue {
+        RespValue::Array(
+            self.events
+                .iter()
+                .filter_map(|(name, event)| {
+                    let last = event.samples.back()?;
+                    Some(RespValue::Array(vec![
+                        RespValue::bulk(name.as_str()),
+                        RespValue::Integer(last.timestamp as i64),
+                        RespValue::Integer(last.latency_ms as i64),
+                        RespValue::Integer(event.max_ms as i64),
+                    ]))
+                })
+                .collect(),
+        )
+    }
+
+    // [시각, 값(ms)]을 오래된 순서로
+    pub fn history(&self, event: &str) -> RespValue {
+        let samples = self.events.get(event).map(|event| event.samples.iter()).into_iter().flatten();
+        RespValue::Array(
+            samples
+                .map(|sample| RespValue::Array(vec![RespValue::Integer(sample.timestamp as i64), RespValue::Integer(sample.latency_ms as i64)]))
+                .collect(),
+        )
+    }
+
+    // 사람이 읽는 요약, 이벤트마다 스파이크 수와 평균/최댓값, 그리고 원인에 대한 조언을 붙임
+    pub fn doctor(&self, threshold_ms: u64) -> String {
+        if threshold_ms == 0 {
+            return format!(
+                "Latency monitoring is disabled in this instance. Use CONFIG SET latency-monitor-threshold <milliseconds> to enable it.{}",
+                CRLF
+            );
+        }
+        if self.events.values().all(|event| event.samples.is_empty()) {
+            return format!("No latency spike above {} milliseconds was observed so far.{}", threshold_ms, CRLF);
+        }
+        let mut report = format!("Latency spikes above {} milliseconds were observed for the following events:{}{}", threshold_ms, CRLF, CRLF);
+        for (index, (name, event)) in self.events.iter().filter(|(_, event)| !event.samples.is_empty()).enumerate() {
+            let count = event.samples.len() as u64;
+            let average = event.samples.iter().map(|sample| sample.latency_ms).sum::<u64>() / count;
+            let first = event.samples.front().map_or(0, |sample| sample.timestamp);
+            let last = event.samples.back().map_or(0, |sample| sample.timestamp);
+            report.push_str(&format!(
+                "{}. {}: {} latency spikes (average {}ms) over {} seconds. Worst all time event {}ms.{}",
+                index + 1,
+                name,
+                count,
+                average,
+                last - first,
+                event.max_ms,
+                CRLF
+            ));
+        }
+        report.push_str(CRLF);
+        report.push_str("Advice:");
+        report.push_str(CRLF);
+        for name in self.events.keys() {
+            let advice = match name.as_str() {
+                LATENCY_EVENT_COMMAND => "Check SLOWLOG GET for slow commands, and avoid O(N) commands such as KEYS on large datasets.",
+                LATENCY_EVENT_FORK => "Snapshots copy the whole dataset before writing it, consider fewer save points or a smaller dataset.",
+                LATENCY_EVENT_EXPIRE_CYCLE => "Many keys expire at the same time, consider spreading expiration times.",
+                _ => continue,
+            };
+            report.push_str(&format!("- {}: {}{}", name, advice, CRLF));
+        }
+        report
+    }
+}
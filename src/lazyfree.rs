@@ -0,0 +1,85 @@
+use crate::config_handler::Db;
+use crate::value_entry::{RedisValue, ValueEntry};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::sync::OnceLock;
+use std::thread;
+
+// Redis의 LAZYFREE_THRESHOLD: 원소가 이보다 적으면 백그라운드로 넘기는 비용이 더 큼
+const LAZYFREE_THRESHOLD: usize = 64;
+
+type FreeJob = (Box<dyn Send>, u64);
+
+// 큰 값의 해제는 이벤트 핸들러를 막지 않도록 전용 스레드에서 처리함
+static FREE_QUEUE: OnceLock<Sender<FreeJob>> = OnceLock::new();
+static PENDING_OBJECTS: AtomicU64 = AtomicU64::new(0);
+static FREED_OBJECTS: AtomicU64 = AtomicU64::new(0);
+
+fn queue() -> &'static Sender<FreeJob> {
+    FREE_QUEUE.get_or_init(|| {
+        let (tx, rx) = mpsc::channel::<FreeJob>();
+        thread::Builder::new()
+            .name("lazyfree".into())
+            .spawn(move || {
+                for (job, objects) in rx {
+                    drop(job);
+                    PENDING_OBJECTS.fetch_sub(objects, Ordering::Relaxed);
+                    FREED_OBJECTS.fetch_add(objects, Ordering::Relaxed);
+                }
+            })
+            .expect("Failed to start lazyfree thread");
+        tx
+    })
+}
+
+fn submit(job: Box<dyn Send>, objects: u64) {
+    PENDING_OBJECTS.fetch_add(objects, Ordering::Relaxed);
+    if let Err(mpsc::SendError((job, objects))) = queue().send((job, objects)) {
+        // 해제 스레드가 없으면 그냥 여기서 해제
+        PENDING_OBJECTS.fetch_sub(objects, Ordering::Relaxed);
+        drop(job);
+    }
+}
+
+fn free_effort(value: &RedisValue) -> usize {
+    match value {
+        RedisValue::String(_) => 1,
+        RedisValue::List(list) => list.len(),
+        RedisValue::Set(set) => set.len(),
+        RedisValue::Hash(hash) => hash.len(),
+        RedisValue::ZSet(zset) => zset.len(),
+    }
+}
+
+pub fn free_entry(entry: ValueEntry) {
+    if free_effort(&entry.value) > LAZYFREE_THRESHOLD {
+        submit(Box::new(entry), 1);
+    }
+}
+
+pub fn free_entries(entries: Vec<ValueEntry>) {
+    entries.into_iter().for_each(free_entry);
+}
+
+pub fn free_db(db: Db) {
+    if !db.is_empty() {
+        let objects = db.len() as u64;
+        submit(Box::new(db), objects);
+    }
+}
+
+pub fn free_scripts(scripts: HashMap<String, String>) {
+    if !scripts.is_empty() {
+        let objects = scripts.len() as u64;
+        submit(Box::new(scripts), objects);
+    }
+}
+
+pub fn pending_objects() -> u64 {
+    PENDING_OBJECTS.load(Ordering::Relaxed)
+}
+
+pub fn freed_objects() -> u64 {
+    FREED_OBJECTS.load(Ordering::Relaxed)
+}
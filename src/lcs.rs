@@ -0,0 +1,71 @@
+// LCS 명령의 계산, Redis와 같이 (len(a)+1) x (len(b)+1) 표를 채운 뒤 끝에서부터 거슬러 올라가므로
+// 공통 부분 수열과 함께 두 문자열에서 연속으로 일치한 구간을 뒤쪽 구간부터 찾음
+
+// 두 문자열에서 연속으로 일치한 구간, 끝 위치를 포함함
+#[derive(Debug, PartialEq)]
+pub struct LcsMatch {
+    pub a: (usize, usize),
+    pub b: (usize, usize),
+}
+
+impl LcsMatch {
+    pub fn len(&self) -> usize {
+        self.a.1 - self.a.0 + 1
+    }
+}
+
+pub struct Lcs {
+    pub sequence: Vec<u8>,
+    pub matches: Vec<LcsMatch>,
+}
+
+// 표에 필요한 바이트 수, 명령은 이 값이 proto-max-bulk-len을 넘으면 계산하지 않음
+pub fn table_size(a: &[u8], b: &[u8]) -> u64 {
+    (a.len() as u64 + 1) * (b.len() as u64 + 1) * std::mem::size_of::<u32>() as u64
+}
+
+pub fn longest_common_subsequence(a: &[u8], b: &[u8]) -> Lcs {
+    // table[i * width + j]는 a[..i]와 b[..j]의 LCS 길이
+    let width = b.len() + 1;
+    let mut table = vec![0u32; (a.len() + 1) * width];
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            table[i * width + j] = if a[i - 1] == b[j - 1] {
+                table[(i - 1) * width + j - 1] + 1
+            } else {
+                table[(i - 1) * width + j].max(table[i * width + j - 1])
+            };
+        }
+    }
+
+    let mut sequence = vec![0u8; table[a.len() * width + b.len()] as usize];
+    let mut matches = Vec::new();
+    let mut current: Option<LcsMatch> = None;
+    let (mut i, mut j, mut remaining) = (a.len(), b.len(), sequence.len());
+    while i > 0 && j > 0 {
+        if a[i - 1] == b[j - 1] {
+            remaining -= 1;
+            sequence[remaining] = a[i - 1];
+            // 일치하면 두 위치가 함께 줄어드므로 이어서 일치한 글자는 항상 지금 구간의 바로 앞
+            match current.as_mut() {
+                Some(range) => {
+                    range.a.0 = i - 1;
+                    range.b.0 = j - 1;
+                }
+                None => current = Some(LcsMatch { a: (i - 1, i - 1), b: (j - 1, j - 1) }),
+            }
+            i -= 1;
+            j -= 1;
+        } else {
+            // Redis와 같이 LCS가 더 긴 쪽으로, 같으면 b를 줄임
+            if table[(i - 1) * width + j] > table[i * width + j - 1] {
+                i -= 1;
+            } else {
+                j -= 1;
+            }
+            matches.extend(current.take());
+        }
+    }
+    matches.extend(current.take());
+    Lcs { sequence, matches }
+}
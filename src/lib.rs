@@ -0,0 +1,67 @@
+mod acl;
+mod admin;
+pub mod benchmark;
+mod blocking;
+pub mod cli;
+mod client;
+mod cluster;
+mod cluster_bus;
+mod command;
+mod concurrent_reads;
+mod value_entry;
+mod value_encoding;
+mod command_parser;
+mod command_registry;
+mod errors;
+mod protocol_constants;
+mod rdb_codec;
+mod rdb_encoding;
+mod rdb_parser;
+mod state_manager;
+mod config_handler;
+mod replication_config;
+mod util;
+mod client_manager;
+mod client_output;
+mod redis_client;
+mod replica_output;
+mod event;
+mod event_handler;
+mod event_publisher;
+mod eviction;
+mod firewall;
+mod hooks;
+mod latency;
+mod lazyfree;
+mod lcs;
+mod listpack;
+mod logging;
+mod lua;
+mod lua_parser;
+mod lua_stdlib;
+mod lzf;
+mod keyspace;
+mod memory;
+mod namespace;
+mod notify;
+mod persistence;
+mod preflight;
+mod pubsub;
+mod random;
+mod rate_limit;
+mod resp;
+mod script_cache;
+mod scripting;
+mod sentinel;
+mod sentinel_link;
+mod server_info;
+mod server;
+mod stats;
+pub mod test_support;
+mod trace;
+mod tracking;
+mod watch;
+
+pub use client::{Client, PipeSummary};
+pub use resp::RespValue;
+pub use server::{Server, ServerBuilder, ServerHandle};
@@ -0,0 +1,178 @@
+// 작은 해시/리스트/정렬 집합을 담는 연속된 버퍼, 원소마다 길이(LEB128)와 바이트를 이어 붙임
+// 원소마다 따로 할당하지 않으므로 작은 컬렉션의 메모리가 크게 줄어듦
+// Redis listpack처럼 원소가 적을 때만 쓰므로 중간 삽입/삭제는 버퍼를 옮기는 O(n)으로 충분함
+#[derive(Clone, Debug, Default)]
+pub struct Listpack {
+    buf: Vec<u8>,
+    len: usize,
+}
+
+pub struct Entries<'a> {
+    buf: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Iterator for Entries<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.offset >= self.buf.len() {
+            return None;
+        }
+        let (entry, next) = read_entry(self.buf, self.offset);
+        self.offset = next;
+        Some(entry)
+    }
+}
+
+fn encode_entry(out: &mut Vec<u8>, entry: &[u8]) {
+    let mut len = entry.len();
+    loop {
+        let byte = (len & 0x7F) as u8;
+        len >>= 7;
+        if len == 0 {
+            out.push(byte);
+            break;
+        }
+        out.push(byte | 0x80);
+    }
+    out.extend_from_slice(entry);
+}
+
+// offset의 원소와 다음 원소의 위치
+fn read_entry(buf: &[u8], offset: usize) -> (&[u8], usize) {
+    let mut len = 0;
+    let mut shift = 0;
+    let mut position = offset;
+    loop {
+        let byte = buf[position];
+        position += 1;
+        len |= ((byte & 0x7F) as usize) << shift;
+        if byte & 0x80 == 0 {
+            break;
+        }
+        shift += 7;
+    }
+    (&buf[position..position + len], position + len)
+}
+
+impl Listpack {
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // 원소를 담은 버퍼의 크기
+    pub fn bytes(&self) -> usize {
+        self.buf.len()
+    }
+
+    pub fn iter(&self) -> Entries<'_> {
+        Entries { buf: &self.buf, offset: 0 }
+    }
+
+    pub fn push_back(&mut self, entry: &[u8]) {
+        encode_entry(&mut self.buf, entry);
+        self.len += 1;
+    }
+
+    pub fn push_front(&mut self, entry: &[u8]) {
+        let mut encoded = Vec::with_capacity(entry.len() + 1);
+        encode_entry(&mut encoded, entry);
+        self.buf.splice(0..0, encoded);
+        self.len += 1;
+    }
+
+    pub fn pop_front(&mut self) -> Option<Vec<u8>> {
+        if self.is_empty() {
+            return None;
+        }
+        let (entry, next) = read_entry(&self.buf, 0);
+        let entry = entry.to_vec();
+        self.buf.drain(..next);
+        self.len -= 1;
+        Some(entry)
+    }
+
+    pub fn pop_back(&mut self) -> Option<Vec<u8>> {
+        let start = self.offsets().last()?;
+        let (entry, _) = read_entry(&self.buf, start);
+        let entry = entry.to_vec();
+        self.buf.truncate(start);
+        self.len -= 1;
+        Some(entry)
+    }
+
+    fn offsets(&self) -> impl Iterator<Item = usize> + '_ {
+        let mut offset = 0;
+        std::iter::from_fn(move || {
+            if offset >= self.buf.len() {
+                return None;
+            }
+            let start = offset;
+            offset = read_entry(&self.buf, start).1;
+            Some(start)
+        })
+    }
+
+    // 해시와 정렬 집합은 원소 두 개씩 (키, 값) 쌍으로 씀
+    pub fn pairs(&self) -> impl Iterator<Item = (&[u8], &[u8])> + '_ {
+        let mut entries = self.iter();
+        std::iter::from_fn(move || Some((entries.next()?, entries.next()?)))
+    }
+
+    pub fn pair_count(&self) -> usize {
+        self.len / 2
+    }
+
+    // 키 원소의 위치와 값 원소의 (시작, 끝) 위치
+    fn find_pair(&self, key: &[u8]) -> Option<(usize, usize, usize)> {
+        let mut offset = 0;
+        while offset < self.buf.len() {
+            let (entry, value_start) = read_entry(&self.buf, offset);
+            let (_, value_end) = read_entry(&self.buf, value_start);
+            if entry == key {
+                return Some((offset, value_start, value_end));
+            }
+            offset = value_end;
+        }
+        None
+    }
+
+    pub fn get_pair(&self, key: &[u8]) -> Option<&[u8]> {
+        let (_, value_start, _) = self.find_pair(key)?;
+        Some(read_entry(&self.buf, value_start).0)
+    }
+
+    // 새 키면 true, 있던 키면 값만 바꿈
+    pub fn set_pair(&mut self, key: &[u8], value: &[u8]) -> bool {
+        let mut encoded = Vec::with_capacity(value.len() + 1);
+        encode_entry(&mut encoded, value);
+        match self.find_pair(key) {
+            Some((_, value_start, value_end)) => {
+                self.buf.splice(value_start..value_end, encoded);
+                false
+            }
+            None => {
+                encode_entry(&mut self.buf, key);
+                self.buf.extend_from_slice(&encoded);
+                self.len += 2;
+                true
+            }
+        }
+    }
+
+    pub fn remove_pair(&mut self, key: &[u8]) -> bool {
+        match self.find_pair(key) {
+            Some((start, _, end)) => {
+                self.buf.drain(start..end);
+                self.len -= 2;
+                true
+            }
+            None => false,
+        }
+    }
+}
@@ -0,0 +1,224 @@
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::process;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::task::JoinHandle;
+
+const MONTHS: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+
+// Redis loglevel과 같은 단계, 설정한 단계 이상만 남김 (nothing이면 아무것도 남기지 않음)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    Debug,
+    Verbose,
+    Notice,
+    Warning,
+    Nothing,
+}
+
+impl LogLevel {
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name.to_lowercase().as_str() {
+            "debug" => Ok(LogLevel::Debug),
+            "verbose" => Ok(LogLevel::Verbose),
+            "notice" => Ok(LogLevel::Notice),
+            "warning" => Ok(LogLevel::Warning),
+            "nothing" => Ok(LogLevel::Nothing),
+            _ => Err(format!("Unknown log level '{}'", name)),
+        }
+    }
+
+    // Redis 로그 줄에서 단계를 나타내는 문자
+    fn mark(&self) -> char {
+        match self {
+            LogLevel::Debug => '.',
+            LogLevel::Verbose => '-',
+            LogLevel::Notice => '*',
+            LogLevel::Warning | LogLevel::Nothing => '#',
+        }
+    }
+
+    fn from_u8(value: u8) -> Self {
+        match value {
+            0 => LogLevel::Debug,
+            1 => LogLevel::Verbose,
+            2 => LogLevel::Notice,
+            3 => LogLevel::Warning,
+            _ => LogLevel::Nothing,
+        }
+    }
+}
+
+// 서버마다 하나, 파일이 없으면 표준 출력에 씀
+#[derive(Debug)]
+pub struct Logger {
+    level: AtomicU8,
+    role: AtomicU8,
+    file: Mutex<Option<File>>,
+}
+
+impl Default for Logger {
+    fn default() -> Self {
+        Logger::new()
+    }
+}
+
+impl Logger {
+    const fn new() -> Self {
+        Self { level: AtomicU8::new(LogLevel::Notice as u8), role: AtomicU8::new(b'M'), file: Mutex::new(None) }
+    }
+
+    pub fn set_level(&self, level: LogLevel) {
+        self.level.store(level as u8, Ordering::Relaxed);
+    }
+
+    pub fn level(&self) -> LogLevel {
+        LogLevel::from_u8(self.level.load(Ordering::Relaxed))
+    }
+
+    // Redis처럼 로그 줄에 역할을 표시함: M 마스터, S 레플리카, X 센티널
+    pub fn set_role(&self, role: char) {
+        self.role.store(role as u8, Ordering::Relaxed);
+    }
+
+    // logfile이 비어 있으면 표준 출력에 씀
+    pub fn set_file(&self, path: &str) -> Result<(), String> {
+        if path.is_empty() {
+            *self.file.lock().unwrap() = None;
+            return Ok(());
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| format!("Can't open the log file {}: {}", path, e))?;
+        *self.file.lock().unwrap() = Some(file);
+        Ok(())
+    }
+
+    fn log(&self, level: LogLevel, args: fmt::Arguments) {
+        if level < self.level() || level == LogLevel::Nothing {
+            return;
+        }
+        let connection = CONNECTION_ID.try_with(|id| format!("client={} ", id)).unwrap_or_default();
+        let line = format!(
+            "{}:{} {} {} {}{}\n",
+            process::id(),
+            self.role.load(Ordering::Relaxed) as char,
+            timestamp(),
+            level.mark(),
+            connection,
+            args
+        );
+        match self.file.lock().unwrap().as_mut() {
+            Some(file) => {
+                let _ = file.write_all(line.as_bytes());
+            }
+            None => {
+                let _ = std::io::stdout().lock().write_all(line.as_bytes());
+            }
+        }
+    }
+}
+
+// 서버 밖(CLI, 벤치마크)이나 서버가 뜨기 전에 남기는 로그
+static DEFAULT_LOGGER: Logger = Logger::new();
+
+tokio::task_local! {
+    // 서버의 태스크는 모두 이 값으로 감싸서, 한 프로세스에 서버가 여럿이어도 각자의 로그 설정을 따름
+    static LOGGER: Arc<Logger>;
+    // 연결마다 읽기 태스크를 이 값으로 감싸서, 그 태스크의 로그에 클라이언트 id가 붙음
+    pub static CONNECTION_ID: u64;
+}
+
+pub async fn scope<F: Future>(logger: Arc<Logger>, future: F) -> F::Output {
+    LOGGER.scope(logger, future).await
+}
+
+// 서버 안에서 태스크를 띄울 때 tokio::spawn 대신 씀, 띄운 태스크도 같은 서버의 로거를 씀
+pub fn spawn<F>(future: F) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    match LOGGER.try_with(Arc::clone) {
+        Ok(logger) => tokio::spawn(LOGGER.scope(logger, future)),
+        Err(_) => tokio::spawn(future),
+    }
+}
+
+pub fn spawn_blocking<F, R>(f: F) -> JoinHandle<R>
+where
+    F: FnOnce() -> R + Send + 'static,
+    R: Send + 'static,
+{
+    match LOGGER.try_with(Arc::clone) {
+        Ok(logger) => tokio::task::spawn_blocking(move || LOGGER.sync_scope(logger, f)),
+        Err(_) => tokio::task::spawn_blocking(f),
+    }
+}
+
+// 지금 태스크가 속한 서버의 역할을 바꿈
+pub fn set_role(role: char) {
+    let _ = LOGGER.try_with(|logger| logger.set_role(role));
+}
+
+pub fn log(level: LogLevel, args: fmt::Arguments) {
+    if LOGGER.try_with(|logger| logger.log(level, args)).is_err() {
+        DEFAULT_LOGGER.log(level, args);
+    }
+}
+
+// "16 Oct 2026 09:41:07.123" 형식의 UTC 시각
+fn timestamp() -> String {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+    let secs = now.as_secs();
+    let (year, month, day) = civil_from_days((secs / 86400) as i64);
+    let time_of_day = secs % 86400;
+    format!(
+        "{:02} {} {} {:02}:{:02}:{:02}.{:03}",
+        day,
+        MONTHS[(month - 1) as usize],
+        year,
+        time_of_day / 3600,
+        time_of_day % 3600 / 60,
+        time_of_day % 60,
+        now.subsec_millis()
+    )
+}
+
+// 1970-01-01부터의 일 수 -> (년, 월, 일), Howard Hinnant의 civil_from_days
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+macro_rules! log_debug {
+    ($($arg:tt)*) => { $crate::logging::log($crate::logging::LogLevel::Debug, format_args!($($arg)*)) };
+}
+
+macro_rules! log_verbose {
+    ($($arg:tt)*) => { $crate::logging::log($crate::logging::LogLevel::Verbose, format_args!($($arg)*)) };
+}
+
+macro_rules! log_notice {
+    ($($arg:tt)*) => { $crate::logging::log($crate::logging::LogLevel::Notice, format_args!($($arg)*)) };
+}
+
+macro_rules! log_warning {
+    ($($arg:tt)*) => { $crate::logging::log($crate::logging::LogLevel::Warning, format_args!($($arg)*)) };
+}
+
+pub(crate) use {log_debug, log_notice, log_verbose, log_warning};
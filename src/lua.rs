@@ -0,0 +1,1053 @@
+// EVAL/FCALL 스크립트를 돌리는 Lua 5.1 부분 집합의 트리 워킹 인터프리터
+//
+// 지원하는 것:
+// - 문법: local/전역 대입, 다중 대입, if/while/repeat/숫자 for/범용 for, break, 함수와 클로저,
+//   가변 인자(...), 메서드 호출(a:b()), 테이블 생성자, 모든 산술/비교/논리/문자열 연결 연산자와 #
+// - 기본 라이브러리: assert, error, ipairs, next, pairs, pcall, select, tonumber, tostring,
+//   type, unpack, rawget, rawset, rawequal
+// - string(len, sub, upper, lower, rep, reverse, byte, char, format, find, match, gmatch, gsub,
+//   Lua 패턴 포함), table(insert, remove, concat, sort, getn, maxn), math 전체
+// - redis 테이블(call, pcall, error_reply, status_reply, sha1hex, log)은 scripting.rs에서 채움
+//
+// 지원하지 않는 것: 메타테이블(setmetatable/getmetatable), 코루틴, goto, loadstring/load,
+// xpcall, cjson/cmsgpack/bit/struct 라이브러리. 전역 변수 생성과 없는 전역 변수 읽기는
+// Redis처럼 에러로 막음
+
+use crate::lua_parser::{self, BinaryOp, Block, Expr, FunctionBody, StmtKind, TableField, UnaryOp};
+use crate::lua_stdlib;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::ops::Bound;
+use std::rc::{Rc, Weak};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+// 스크립트 함수 호출 깊이 제한, 넘으면 Lua처럼 "stack overflow"
+const MAX_CALL_DEPTH: usize = 200;
+// 약한 참조 목록이 이만큼 늘 때마다 이미 해제된 항목을 걸러 냄
+const HEAP_COMPACT_THRESHOLD: usize = 1024;
+// 호출 한 단계가 트리 워킹 재귀로 스택을 꽤 쓰므로 깊이 제한까지 넉넉히 버티는 크기의 스레드에서 돌림
+const INTERPRETER_STACK_SIZE: usize = 16 * 1024 * 1024;
+// 중단 신호와 시간 제한을 이만큼의 문장마다 확인함
+const INTERRUPT_CHECK_INTERVAL: u32 = 1000;
+// 스크립트가 만드는 문자열의 최대 길이, proto-max-bulk-len 기본값과 같음
+pub const MAX_STRING_LEN: usize = 512 * 1024 * 1024;
+
+pub type TableRef = Rc<RefCell<Table>>;
+pub type NativeFunction = dyn Fn(&mut Interpreter, Vec<Value>) -> Result<Vec<Value>, LuaError>;
+
+#[derive(Clone)]
+pub enum Value {
+    Nil,
+    Boolean(bool),
+    Number(f64),
+    String(Rc<[u8]>),
+    Table(TableRef),
+    Function(Rc<Function>),
+}
+
+pub enum Function {
+    Lua { body: Rc<FunctionBody>, scope: Rc<Scope> },
+    Native { name: &'static str, call: Box<NativeFunction> },
+}
+
+impl Value {
+    pub fn string(bytes: impl AsRef<[u8]>) -> Self {
+        Value::String(Rc::from(bytes.as_ref()))
+    }
+
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Nil => "nil",
+            Value::Boolean(_) => "boolean",
+            Value::Number(_) => "number",
+            Value::String(_) => "string",
+            Value::Table(_) => "table",
+            Value::Function(_) => "function",
+        }
+    }
+
+    pub fn truthy(&self) -> bool {
+        !matches!(self, Value::Nil | Value::Boolean(false))
+    }
+
+    pub fn is_nil(&self) -> bool {
+        matches!(self, Value::Nil)
+    }
+
+    // 산술 연산처럼 숫자 모양의 문자열도 숫자로 봄
+    pub fn to_number(&self) -> Option<f64> {
+        match self {
+            Value::Number(number) => Some(*number),
+            Value::String(bytes) => std::str::from_utf8(bytes).ok().and_then(lua_parser::parse_number),
+            _ => None,
+        }
+    }
+
+    // 연결 연산처럼 숫자도 문자열로 봄
+    pub fn to_bytes(&self) -> Option<Rc<[u8]>> {
+        match self {
+            Value::String(bytes) => Some(bytes.clone()),
+            Value::Number(number) => Some(Rc::from(format_number(*number).as_bytes())),
+            _ => None,
+        }
+    }
+
+    // tostring과 같은 표기
+    pub fn display(&self) -> Vec<u8> {
+        match self {
+            Value::Nil => b"nil".to_vec(),
+            Value::Boolean(value) => value.to_string().into_bytes(),
+            Value::Number(number) => format_number(*number).into_bytes(),
+            Value::String(bytes) => bytes.to_vec(),
+            Value::Table(table) => format!("table: {:p}", Rc::as_ptr(table)).into_bytes(),
+            Value::Function(function) => match function.as_ref() {
+                Function::Native { name, .. } => format!("function: builtin: {}", name).into_bytes(),
+                Function::Lua { .. } => format!("function: {:p}", Rc::as_ptr(function)).into_bytes(),
+            },
+        }
+    }
+
+    pub fn raw_equal(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::Nil, Value::Nil) => true,
+            (Value::Boolean(a), Value::Boolean(b)) => a == b,
+            (Value::Number(a), Value::Number(b)) => a == b,
+            (Value::String(a), Value::String(b)) => a == b,
+            (Value::Table(a), Value::Table(b)) => Rc::ptr_eq(a, b),
+            (Value::Function(a), Value::Function(b)) => Rc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+}
+
+// 해시 부분의 키, 테이블과 함수는 주소로 구분함
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum Key {
+    Boolean(bool),
+    Number(u64),
+    String(Rc<[u8]>),
+    Reference(usize),
+}
+
+impl Key {
+    fn from_value(value: &Value) -> Result<Self, &'static str> {
+        Ok(match value {
+            Value::Nil => return Err("table index is nil"),
+            Value::Boolean(value) => Key::Boolean(*value),
+            Value::Number(number) if number.is_nan() => return Err("table index is NaN"),
+            // 0과 -0은 같은 키
+            Value::Number(number) => Key::Number((number + 0.0).to_bits()),
+            Value::String(bytes) => Key::String(bytes.clone()),
+            Value::Table(table) => Key::Reference(Rc::as_ptr(table) as *const u8 as usize),
+            Value::Function(function) => Key::Reference(Rc::as_ptr(function) as *const u8 as usize),
+        })
+    }
+}
+
+// 1부터 이어지는 정수 키는 배열 부분에, 나머지는 키 순서대로 도는 해시 부분에 둠
+// 배열 부분의 마지막 값은 항상 nil이 아니므로 그 길이가 곧 # 연산자의 값
+#[derive(Default)]
+pub struct Table {
+    array: Vec<Value>,
+    hash: BTreeMap<Key, (Value, Value)>,
+}
+
+fn array_index(key: &Value) -> Option<usize> {
+    match key {
+        Value::Number(number) if *number >= 1.0 && number.fract() == 0.0 && *number <= usize::MAX as f64 => Some(*number as usize),
+        _ => None,
+    }
+}
+
+impl Table {
+    pub fn get(&self, key: &Value) -> Value {
+        if let Some(index) = array_index(key) {
+            if index <= self.array.len() {
+                return self.array[index - 1].clone();
+            }
+        }
+        match Key::from_value(key) {
+            Ok(key) => self.hash.get(&key).map_or(Value::Nil, |(_, value)| value.clone()),
+            Err(_) => Value::Nil,
+        }
+    }
+
+    pub fn get_str(&self, key: &str) -> Value {
+        self.get(&Value::string(key))
+    }
+
+    pub fn set(&mut self, key: Value, value: Value) -> Result<(), &'static str> {
+        if let Some(index) = array_index(&key) {
+            if index <= self.array.len() {
+                self.array[index - 1] = value;
+                while self.array.last().is_some_and(Value::is_nil) {
+                    self.array.pop();
+                }
+                return Ok(());
+            }
+            if index == self.array.len() + 1 && !value.is_nil() {
+                self.hash.remove(&Key::from_value(&key)?);
+                self.array.push(value);
+                // 해시 부분에 있던 다음 정수 키들을 배열 부분으로 옮김
+                loop {
+                    let next = Key::Number(((self.array.len() + 1) as f64).to_bits());
+                    match self.hash.remove(&next) {
+                        Some((_, value)) => self.array.push(value),
+                        None => break,
+                    }
+                }
+                return Ok(());
+            }
+        }
+        let hash_key = Key::from_value(&key)?;
+        if value.is_nil() {
+            self.hash.remove(&hash_key);
+        } else {
+            self.hash.insert(hash_key, (key, value));
+        }
+        Ok(())
+    }
+
+    pub fn set_str(&mut self, key: &str, value: Value) {
+        let _ = self.set(Value::string(key), value);
+    }
+
+    pub fn push(&mut self, value: Value) {
+        let index = self.length() + 1;
+        let _ = self.set(Value::Number(index as f64), value);
+    }
+
+    pub fn length(&self) -> usize {
+        self.array.len()
+    }
+
+    // pairs가 쓰는 순회, 배열 부분을 먼저 돌고 해시 부분을 키 순서대로 돎
+    pub fn next(&self, key: &Value) -> Result<Option<(Value, Value)>, &'static str> {
+        let start = match key {
+            Value::Nil => 0,
+            key => match array_index(key) {
+                Some(index) if index <= self.array.len() => index,
+                _ => {
+                    let key = Key::from_value(key)?;
+                    if !self.hash.contains_key(&key) {
+                        return Err("invalid key to 'next'");
+                    }
+                    return Ok(self
+                        .hash
+                        .range((Bound::Excluded(key), Bound::Unbounded))
+                        .next()
+                        .map(|(_, (key, value))| (key.clone(), value.clone())));
+                }
+            },
+        };
+        for index in start..self.array.len() {
+            if !self.array[index].is_nil() {
+                return Ok(Some((Value::Number((index + 1) as f64), self.array[index].clone())));
+            }
+        }
+        Ok(self.hash.values().next().map(|(key, value)| (key.clone(), value.clone())))
+    }
+
+    fn clear(&mut self) {
+        self.array.clear();
+        self.hash.clear();
+    }
+}
+
+// 블록마다 새로 만드는 지역 변수 범위, 클로저는 만들어질 때의 범위를 잡아 둠
+pub struct Scope {
+    vars: RefCell<Vec<(Rc<str>, Value)>>,
+    parent: Option<Rc<Scope>>,
+    // vararg 함수의 본문 범위에만 있음
+    varargs: Option<Rc<[Value]>>,
+}
+
+impl Scope {
+    fn lookup(&self, name: &str) -> Option<Value> {
+        let mut scope = self;
+        loop {
+            if let Some((_, value)) = scope.vars.borrow().iter().rev().find(|(var, _)| var.as_ref() == name) {
+                return Some(value.clone());
+            }
+            scope = scope.parent.as_deref()?;
+        }
+    }
+
+    fn assign(&self, name: &str, value: Value) -> Result<(), Value> {
+        let mut scope = self;
+        loop {
+            if let Some((_, slot)) = scope.vars.borrow_mut().iter_mut().rev().find(|(var, _)| var.as_ref() == name) {
+                *slot = value;
+                return Ok(());
+            }
+            match scope.parent.as_deref() {
+                Some(parent) => scope = parent,
+                None => return Err(value),
+            }
+        }
+    }
+
+    fn declare(&self, name: Rc<str>, value: Value) {
+        self.vars.borrow_mut().push((name, value));
+    }
+
+    fn varargs(&self) -> Rc<[Value]> {
+        let mut scope = self;
+        loop {
+            if let Some(varargs) = &scope.varargs {
+                return varargs.clone();
+            }
+            match scope.parent.as_deref() {
+                Some(parent) => scope = parent,
+                None => return Rc::from(Vec::new()),
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct LuaError {
+    pub value: Value,
+    // 에러가 난 스크립트 줄, Redis 에러 응답의 "on @user_script:<line>"에 씀
+    pub line: usize,
+}
+
+enum Flow {
+    Normal,
+    Break,
+    Return(Vec<Value>),
+}
+
+// 대입 대상: 지역 변수, 전역 변수, 테이블 필드
+enum Place {
+    Name(Rc<str>),
+    Field(Value, Value),
+}
+
+pub struct Interpreter {
+    globals: TableRef,
+    // 에러 위치 앞에 붙는 청크 이름, EVAL은 user_script, FUNCTION은 user_function
+    chunk: &'static str,
+    line: usize,
+    depth: usize,
+    // 스크립트를 다 넣은 뒤에는 전역 변수를 새로 만들거나 없는 전역 변수를 읽을 수 없음
+    globals_locked: bool,
+    // Rc 순환(자기 자신을 담은 테이블, 재귀 지역 함수)을 끊으려고 만든 테이블과 범위를 기억해 두고 Drop에서 비움
+    tables: Vec<Weak<RefCell<Table>>>,
+    scopes: Vec<Weak<Scope>>,
+    // math.random의 상태, Redis처럼 스크립트마다 같은 수열을 냄
+    pub(crate) random_state: u64,
+    // SCRIPT KILL과 함수 라이브러리 로드 시간 제한, 한 번 걸리면 pcall로도 잡을 수 없이 스크립트 끝까지 에러가 올라감
+    interrupt: Option<Arc<AtomicBool>>,
+    deadline: Option<Instant>,
+    interrupt_message: &'static str,
+    interrupted: bool,
+    ticks: u32,
+}
+
+impl Interpreter {
+    pub fn new(chunk: &'static str) -> Self {
+        let globals = Rc::new(RefCell::new(Table::default()));
+        let mut interpreter = Self {
+            tables: vec![Rc::downgrade(&globals)],
+            globals,
+            chunk,
+            line: 0,
+            depth: 0,
+            globals_locked: false,
+            scopes: Vec::new(),
+            random_state: 0,
+            interrupt: None,
+            deadline: None,
+            interrupt_message: "",
+            interrupted: false,
+            ticks: 0,
+        };
+        lua_stdlib::open(&mut interpreter);
+        interpreter
+    }
+
+    pub fn new_table(&mut self) -> TableRef {
+        let table = Rc::new(RefCell::new(Table::default()));
+        if self.tables.len() >= HEAP_COMPACT_THRESHOLD && self.tables.len().is_power_of_two() {
+            self.tables.retain(|table| table.strong_count() > 0);
+        }
+        self.tables.push(Rc::downgrade(&table));
+        table
+    }
+
+    pub fn table_from(&mut self, values: impl IntoIterator<Item = Value>) -> Value {
+        let table = self.new_table();
+        {
+            let mut table = table.borrow_mut();
+            for value in values {
+                table.push(value);
+            }
+        }
+        Value::Table(table)
+    }
+
+    fn new_scope(&mut self, parent: Option<Rc<Scope>>, varargs: Option<Rc<[Value]>>) -> Rc<Scope> {
+        let scope = Rc::new(Scope { vars: RefCell::new(Vec::new()), parent, varargs });
+        if self.scopes.len() >= HEAP_COMPACT_THRESHOLD && self.scopes.len().is_power_of_two() {
+            self.scopes.retain(|scope| scope.strong_count() > 0);
+        }
+        self.scopes.push(Rc::downgrade(&scope));
+        scope
+    }
+
+    pub fn native(name: &'static str, call: impl Fn(&mut Interpreter, Vec<Value>) -> Result<Vec<Value>, LuaError> + 'static) -> Value {
+        Value::Function(Rc::new(Function::Native { name, call: Box::new(call) }))
+    }
+
+    pub fn global(&self, name: &str) -> Value {
+        self.globals.borrow().get_str(name)
+    }
+
+    pub fn set_global(&mut self, name: &str, value: Value) {
+        self.globals.borrow_mut().set_str(name, value);
+    }
+
+    pub fn lock_globals(&mut self) {
+        self.globals_locked = true;
+    }
+
+    // 다른 스레드가 flag를 켜면 다음 확인 때 message로 스크립트를 멈춤
+    pub fn set_interrupt(&mut self, flag: Arc<AtomicBool>, message: &'static str) {
+        self.interrupt = Some(flag);
+        self.interrupt_message = message;
+    }
+
+    pub fn set_deadline(&mut self, deadline: Instant, message: &'static str) {
+        self.deadline = Some(deadline);
+        self.interrupt_message = message;
+    }
+
+    pub fn is_interrupted(&self) -> bool {
+        self.interrupted
+    }
+
+    fn check_interrupt(&mut self) -> Result<(), LuaError> {
+        self.ticks += 1;
+        if !self.interrupted && self.ticks >= INTERRUPT_CHECK_INTERVAL {
+            self.ticks = 0;
+            self.interrupted = self.interrupt.as_ref().is_some_and(|flag| flag.load(Ordering::Relaxed))
+                || self.deadline.is_some_and(|deadline| Instant::now() >= deadline);
+        }
+        if self.interrupted {
+            return Err(self.raise(Value::string(self.interrupt_message)));
+        }
+        Ok(())
+    }
+
+    // 문자열을 만들기 전에 결과 길이를 확인함, 너무 크면 할당하지 않고 Lua처럼 에러를 냄
+    pub fn check_string_len(&self, len: Option<usize>) -> Result<usize, LuaError> {
+        len.filter(|len| *len <= MAX_STRING_LEN).ok_or_else(|| self.error("resulting string too large"))
+    }
+
+    // 위치가 붙는 런타임 에러, Lua VM이 내는 에러와 error()가 씀
+    pub fn error(&self, message: &str) -> LuaError {
+        self.raise(Value::string(format!("{}:{}: {}", self.chunk, self.line, message)))
+    }
+
+    // 위치 없이 값을 그대로 던짐, 내장 함수의 인자 에러처럼 C 함수가 내는 에러가 여기에 해당함
+    pub fn raise(&self, value: Value) -> LuaError {
+        LuaError { value, line: self.line }
+    }
+
+    // 스크립트를 컴파일해서 인자 없는 vararg 함수로 돌려줌
+    pub fn load(&mut self, source: &[u8]) -> Result<Value, String> {
+        let body = lua_parser::parse(source).map_err(|e| format!("{}:{}: {}", self.chunk, e.line, e.message))?;
+        let scope = self.new_scope(None, None);
+        let body = Rc::new(FunctionBody { params: Vec::new(), vararg: true, body });
+        Ok(Value::Function(Rc::new(Function::Lua { body, scope })))
+    }
+
+    pub fn call(&mut self, function: &Value, args: Vec<Value>) -> Result<Vec<Value>, LuaError> {
+        let Value::Function(function) = function else {
+            return Err(self.error(&format!("attempt to call a {} value", function.type_name())));
+        };
+        self.depth += 1;
+        let line = self.line;
+        let result = if self.depth > MAX_CALL_DEPTH {
+            Err(self.error("stack overflow"))
+        } else {
+            match function.as_ref() {
+                Function::Native { call, .. } => call(self, args),
+                Function::Lua { body, scope } => self.call_lua(body, scope.clone(), args),
+            }
+        };
+        self.depth -= 1;
+        self.line = line;
+        result
+    }
+
+    fn call_lua(&mut self, function: &FunctionBody, parent: Rc<Scope>, args: Vec<Value>) -> Result<Vec<Value>, LuaError> {
+        let mut args = args.into_iter();
+        let fixed: Vec<Value> = function.params.iter().map(|_| args.next().unwrap_or(Value::Nil)).collect();
+        let varargs = function.vararg.then(|| Rc::from(args.collect::<Vec<_>>()));
+        let scope = self.new_scope(Some(parent), varargs);
+        for (name, value) in function.params.iter().zip(fixed) {
+            scope.declare(name.clone(), value);
+        }
+        match self.exec_statements(&function.body, &scope)? {
+            Flow::Return(values) => Ok(values),
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    fn exec_block(&mut self, block: &Block, parent: &Rc<Scope>) -> Result<Flow, LuaError> {
+        let scope = self.new_scope(Some(parent.clone()), None);
+        self.exec_statements(block, &scope)
+    }
+
+    fn exec_statements(&mut self, block: &Block, scope: &Rc<Scope>) -> Result<Flow, LuaError> {
+        // 빈 본문의 반복문도 여기를 지나므로 while true do end도 멈출 수 있음
+        self.check_interrupt()?;
+        for stmt in &block.stmts {
+            self.check_interrupt()?;
+            self.line = stmt.line;
+            match &stmt.kind {
+                StmtKind::Local(names, exprs) => {
+                    let mut values = self.eval_list(exprs, scope)?.into_iter();
+                    for name in names {
+                        scope.declare(name.clone(), values.next().unwrap_or(Value::Nil));
+                    }
+                }
+                StmtKind::LocalFunction(name, body) => {
+                    scope.declare(name.clone(), Value::Nil);
+                    let function = Value::Function(Rc::new(Function::Lua { body: body.clone(), scope: scope.clone() }));
+                    let _ = scope.assign(name, function);
+                }
+                StmtKind::Assign(targets, exprs) => {
+                    let mut places = Vec::with_capacity(targets.len());
+                    for target in targets {
+                        places.push(match target {
+                            Expr::Name(name) => Place::Name(name.clone()),
+                            Expr::Index(object, key) => Place::Field(self.eval(object, scope)?, self.eval(key, scope)?),
+                            _ => return Err(self.error("syntax error")),
+                        });
+                    }
+                    let mut values = self.eval_list(exprs, scope)?.into_iter();
+                    for place in places {
+                        let value = values.next().unwrap_or(Value::Nil);
+                        match place {
+                            Place::Name(name) => self.assign_name(scope, &name, value)?,
+                            Place::Field(object, key) => self.set_index(&object, key, value)?,
+                        }
+                    }
+                }
+                StmtKind::Call(expr) => {
+                    self.eval_multi(expr, scope)?;
+                }
+                StmtKind::Do(body) => match self.exec_block(body, scope)? {
+                    Flow::Normal => {}
+                    flow => return Ok(flow),
+                },
+                StmtKind::While(condition, body) => {
+                    while self.eval(condition, scope)?.truthy() {
+                        match self.exec_block(body, scope)? {
+                            Flow::Normal => {}
+                            Flow::Break => break,
+                            flow => return Ok(flow),
+                        }
+                    }
+                }
+                StmtKind::Repeat(body, condition) => loop {
+                    // until 조건은 본문의 지역 변수를 볼 수 있음
+                    let inner = self.new_scope(Some(scope.clone()), None);
+                    match self.exec_statements(body, &inner)? {
+                        Flow::Normal => {}
+                        Flow::Break => break,
+                        flow => return Ok(flow),
+                    }
+                    if self.eval(condition, &inner)?.truthy() {
+                        break;
+                    }
+                },
+                StmtKind::If(branches, otherwise) => {
+                    let mut chosen = otherwise.as_ref();
+                    for (condition, body) in branches {
+                        if self.eval(condition, scope)?.truthy() {
+                            chosen = Some(body);
+                            break;
+                        }
+                    }
+                    if let Some(body) = chosen {
+                        match self.exec_block(body, scope)? {
+                            Flow::Normal => {}
+                            flow => return Ok(flow),
+                        }
+                    }
+                }
+                StmtKind::NumericFor { var, start, limit, step, body } => {
+                    let start = self.for_number(start, scope, "initial")?;
+                    let limit = self.for_number(limit, scope, "limit")?;
+                    let step = match step {
+                        Some(step) => self.for_number(step, scope, "step")?,
+                        None => 1.0,
+                    };
+                    let mut current = start;
+                    while (step > 0.0 && current <= limit) || (step <= 0.0 && current >= limit) {
+                        let inner = self.new_scope(Some(scope.clone()), None);
+                        inner.declare(var.clone(), Value::Number(current));
+                        match self.exec_statements(body, &inner)? {
+                            Flow::Normal => {}
+                            Flow::Break => break,
+                            flow => return Ok(flow),
+                        }
+                        current += step;
+                    }
+                }
+                StmtKind::GenericFor { names, exprs, body } => {
+                    let mut values = self.eval_list(exprs, scope)?.into_iter();
+                    let iterator = values.next().unwrap_or(Value::Nil);
+                    let state = values.next().unwrap_or(Value::Nil);
+                    let mut control = values.next().unwrap_or(Value::Nil);
+                    loop {
+                        let line = self.line;
+                        let mut results = self.call(&iterator, vec![state.clone(), control.clone()])?.into_iter();
+                        self.line = line;
+                        let first = results.next().unwrap_or(Value::Nil);
+                        if first.is_nil() {
+                            break;
+                        }
+                        control = first.clone();
+                        let inner = self.new_scope(Some(scope.clone()), None);
+                        inner.declare(names[0].clone(), first);
+                        for name in &names[1..] {
+                            inner.declare(name.clone(), results.next().unwrap_or(Value::Nil));
+                        }
+                        match self.exec_statements(body, &inner)? {
+                            Flow::Normal => {}
+                            Flow::Break => break,
+                            flow => return Ok(flow),
+                        }
+                    }
+                }
+                StmtKind::Return(exprs) => return Ok(Flow::Return(self.eval_list(exprs, scope)?)),
+                StmtKind::Break => return Ok(Flow::Break),
+            }
+        }
+        Ok(Flow::Normal)
+    }
+
+    fn for_number(&mut self, expr: &Expr, scope: &Rc<Scope>, what: &str) -> Result<f64, LuaError> {
+        self.eval(expr, scope)?
+            .to_number()
+            .ok_or_else(|| self.error(&format!("'for' {} value must be a number", what)))
+    }
+
+    fn assign_name(&mut self, scope: &Rc<Scope>, name: &str, value: Value) -> Result<(), LuaError> {
+        let Err(value) = scope.assign(name, value) else {
+            return Ok(());
+        };
+        if self.globals_locked {
+            return Err(self.error("Attempt to modify a readonly table"));
+        }
+        self.set_global(name, value);
+        Ok(())
+    }
+
+    fn lookup_name(&self, scope: &Rc<Scope>, name: &str) -> Result<Value, LuaError> {
+        if let Some(value) = scope.lookup(name) {
+            return Ok(value);
+        }
+        let value = self.global(name);
+        if value.is_nil() && self.globals_locked {
+            return Err(self.error(&format!("Script attempted to access nonexistent global variable '{}'", name)));
+        }
+        Ok(value)
+    }
+
+    pub fn index(&self, object: &Value, key: &Value) -> Result<Value, LuaError> {
+        match object {
+            Value::Table(table) => Ok(table.borrow().get(key)),
+            // 문자열은 string 라이브러리를 메서드 테이블로 씀: ("x"):upper()
+            Value::String(_) => match self.global("string") {
+                Value::Table(string) => Ok(string.borrow().get(key)),
+                _ => Ok(Value::Nil),
+            },
+            other => Err(self.error(&format!("attempt to index a {} value", other.type_name()))),
+        }
+    }
+
+    fn set_index(&self, object: &Value, key: Value, value: Value) -> Result<(), LuaError> {
+        match object {
+            Value::Table(table) if Rc::ptr_eq(table, &self.globals) && self.globals_locked => {
+                Err(self.error("Attempt to modify a readonly table"))
+            }
+            Value::Table(table) => table.borrow_mut().set(key, value).map_err(|e| self.error(e)),
+            other => Err(self.error(&format!("attempt to index a {} value", other.type_name()))),
+        }
+    }
+
+    // 식 목록의 값, 마지막 식이 호출이나 ...이면 그 결과를 모두 펼침
+    fn eval_list(&mut self, exprs: &[Expr], scope: &Rc<Scope>) -> Result<Vec<Value>, LuaError> {
+        let mut values = Vec::with_capacity(exprs.len());
+        for (index, expr) in exprs.iter().enumerate() {
+            if index + 1 == exprs.len() && expr.is_multi() {
+                values.extend(self.eval_multi(expr, scope)?);
+            } else {
+                values.push(self.eval(expr, scope)?);
+            }
+        }
+        Ok(values)
+    }
+
+    fn eval_multi(&mut self, expr: &Expr, scope: &Rc<Scope>) -> Result<Vec<Value>, LuaError> {
+        match expr {
+            Expr::Call(callee, args, line) => {
+                let function = self.eval(callee, scope)?;
+                let args = self.eval_list(args, scope)?;
+                self.line = *line;
+                if !matches!(function, Value::Function(_)) {
+                    return Err(self.call_error(callee, &function));
+                }
+                self.call(&function, args)
+            }
+            Expr::Method(object, name, args, line) => {
+                let object = self.eval(object, scope)?;
+                self.line = *line;
+                let function = self.index(&object, &Value::string(name.as_bytes()))?;
+                if !matches!(function, Value::Function(_)) {
+                    return Err(self.error(&format!("attempt to call method '{}' (a {} value)", name, function.type_name())));
+                }
+                let mut call_args = vec![object];
+                call_args.extend(self.eval_list(args, scope)?);
+                self.line = *line;
+                self.call(&function, call_args)
+            }
+            Expr::Vararg => Ok(scope.varargs().to_vec()),
+            expr => Ok(vec![self.eval(expr, scope)?]),
+        }
+    }
+
+    fn call_error(&self, callee: &Expr, function: &Value) -> LuaError {
+        match callee {
+            Expr::Name(name) => self.error(&format!("attempt to call global '{}' (a {} value)", name, function.type_name())),
+            Expr::Index(_, key) => match key.as_ref() {
+                Expr::String(key) => self.error(&format!(
+                    "attempt to call field '{}' (a {} value)",
+                    String::from_utf8_lossy(key),
+                    function.type_name()
+                )),
+                _ => self.error(&format!("attempt to call a {} value", function.type_name())),
+            },
+            _ => self.error(&format!("attempt to call a {} value", function.type_name())),
+        }
+    }
+
+    fn eval(&mut self, expr: &Expr, scope: &Rc<Scope>) -> Result<Value, LuaError> {
+        Ok(match expr {
+            Expr::Nil => Value::Nil,
+            Expr::True => Value::Boolean(true),
+            Expr::False => Value::Boolean(false),
+            Expr::Number(number) => Value::Number(*number),
+            Expr::String(bytes) => Value::String(bytes.clone()),
+            Expr::Vararg => scope.varargs().first().cloned().unwrap_or(Value::Nil),
+            Expr::Function(body) => Value::Function(Rc::new(Function::Lua { body: body.clone(), scope: scope.clone() })),
+            Expr::Table(fields) => self.eval_table(fields, scope)?,
+            Expr::Name(name) => self.lookup_name(scope, name)?,
+            Expr::Index(object, key) => {
+                let object = self.eval(object, scope)?;
+                let key = self.eval(key, scope)?;
+                self.index(&object, &key)?
+            }
+            Expr::Call(..) | Expr::Method(..) => self.eval_multi(expr, scope)?.into_iter().next().unwrap_or(Value::Nil),
+            Expr::Paren(inner) => self.eval(inner, scope)?,
+            Expr::Unary(op, operand) => {
+                let value = self.eval(operand, scope)?;
+                match op {
+                    UnaryOp::Not => Value::Boolean(!value.truthy()),
+                    UnaryOp::Neg => match value.to_number() {
+                        Some(number) => Value::Number(-number),
+                        None => return Err(self.error(&format!("attempt to perform arithmetic on a {} value", value.type_name()))),
+                    },
+                    UnaryOp::Len => match &value {
+                        Value::String(bytes) => Value::Number(bytes.len() as f64),
+                        Value::Table(table) => Value::Number(table.borrow().length() as f64),
+                        other => return Err(self.error(&format!("attempt to get length of a {} value", other.type_name()))),
+                    },
+                }
+            }
+            Expr::Binary(BinaryOp::And, left, right) => {
+                let left = self.eval(left, scope)?;
+                if !left.truthy() {
+                    left
+                } else {
+                    self.eval(right, scope)?
+                }
+            }
+            Expr::Binary(BinaryOp::Or, left, right) => {
+                let left = self.eval(left, scope)?;
+                if left.truthy() {
+                    left
+                } else {
+                    self.eval(right, scope)?
+                }
+            }
+            Expr::Binary(op, left, right) => {
+                let left = self.eval(left, scope)?;
+                let right = self.eval(right, scope)?;
+                self.binary(*op, left, right)?
+            }
+        })
+    }
+
+    fn eval_table(&mut self, fields: &[TableField], scope: &Rc<Scope>) -> Result<Value, LuaError> {
+        let table = self.new_table();
+        let mut index = 1;
+        for (position, field) in fields.iter().enumerate() {
+            match field {
+                TableField::Positional(expr) if position + 1 == fields.len() && expr.is_multi() => {
+                    for value in self.eval_multi(expr, scope)? {
+                        table.borrow_mut().set(Value::Number(index as f64), value).map_err(|e| self.error(e))?;
+                        index += 1;
+                    }
+                }
+                TableField::Positional(expr) => {
+                    let value = self.eval(expr, scope)?;
+                    table.borrow_mut().set(Value::Number(index as f64), value).map_err(|e| self.error(e))?;
+                    index += 1;
+                }
+                TableField::Named(key, value) => {
+                    let key = self.eval(key, scope)?;
+                    let value = self.eval(value, scope)?;
+                    table.borrow_mut().set(key, value).map_err(|e| self.error(e))?;
+                }
+            }
+        }
+        Ok(Value::Table(table))
+    }
+
+    fn binary(&self, op: BinaryOp, left: Value, right: Value) -> Result<Value, LuaError> {
+        match op {
+            BinaryOp::Eq => return Ok(Value::Boolean(left.raw_equal(&right))),
+            BinaryOp::Ne => return Ok(Value::Boolean(!left.raw_equal(&right))),
+            BinaryOp::Lt => return self.less_than(&left, &right).map(Value::Boolean),
+            BinaryOp::Gt => return self.less_than(&right, &left).map(Value::Boolean),
+            BinaryOp::Le => return self.less_equal(&left, &right).map(Value::Boolean),
+            BinaryOp::Ge => return self.less_equal(&right, &left).map(Value::Boolean),
+            BinaryOp::Concat => {
+                return match (left.to_bytes(), right.to_bytes()) {
+                    (Some(left), Some(right)) => {
+                        self.check_string_len(left.len().checked_add(right.len()))?;
+                        Ok(Value::String(Rc::from([&left[..], &right[..]].concat())))
+                    }
+                    (None, _) => Err(self.error(&format!("attempt to concatenate a {} value", left.type_name()))),
+                    (_, None) => Err(self.error(&format!("attempt to concatenate a {} value", right.type_name()))),
+                };
+            }
+            _ => {}
+        }
+        let (a, b) = match (left.to_number(), right.to_number()) {
+            (Some(a), Some(b)) => (a, b),
+            (None, _) => return Err(self.error(&format!("attempt to perform arithmetic on a {} value", left.type_name()))),
+            (_, None) => return Err(self.error(&format!("attempt to perform arithmetic on a {} value", right.type_name()))),
+        };
+        Ok(Value::Number(match op {
+            BinaryOp::Add => a + b,
+            BinaryOp::Sub => a - b,
+            BinaryOp::Mul => a * b,
+            BinaryOp::Div => a / b,
+            BinaryOp::Mod => a - (a / b).floor() * b,
+            _ => a.powf(b),
+        }))
+    }
+
+    pub fn less_than(&self, left: &Value, right: &Value) -> Result<bool, LuaError> {
+        match (left, right) {
+            (Value::Number(a), Value::Number(b)) => Ok(a < b),
+            (Value::String(a), Value::String(b)) => Ok(a < b),
+            _ => Err(self.compare_error(left, right)),
+        }
+    }
+
+    fn less_equal(&self, left: &Value, right: &Value) -> Result<bool, LuaError> {
+        match (left, right) {
+            (Value::Number(a), Value::Number(b)) => Ok(a <= b),
+            (Value::String(a), Value::String(b)) => Ok(a <= b),
+            _ => Err(self.compare_error(left, right)),
+        }
+    }
+
+    fn compare_error(&self, left: &Value, right: &Value) -> LuaError {
+        if left.type_name() == right.type_name() {
+            self.error(&format!("attempt to compare two {} values", left.type_name()))
+        } else {
+            self.error(&format!("attempt to compare {} with {}", left.type_name(), right.type_name()))
+        }
+    }
+}
+
+impl Drop for Interpreter {
+    fn drop(&mut self) {
+        for table in self.tables.drain(..).filter_map(|table| table.upgrade()) {
+            if let Ok(mut table) = table.try_borrow_mut() {
+                table.clear();
+            }
+        }
+        for scope in self.scopes.drain(..).filter_map(|scope| scope.upgrade()) {
+            if let Ok(mut vars) = scope.vars.try_borrow_mut() {
+                vars.clear();
+            }
+        }
+    }
+}
+
+// 인터프리터는 Rc로 값을 나누므로 한 스레드 안에서 만들고 다 쓴 뒤 결과만 돌려줌
+pub fn spawn<T: Send + 'static>(run: impl FnOnce() -> T + Send + 'static) -> std::io::Result<std::thread::JoinHandle<T>> {
+    std::thread::Builder::new().name("lua".to_string()).stack_size(INTERPRETER_STACK_SIZE).spawn(run)
+}
+
+// Lua가 숫자를 문자열로 바꿀 때 쓰는 %.14g
+pub fn format_number(number: f64) -> String {
+    format_g(number, 14, false)
+}
+
+// C printf의 %g, keep_zeros는 # 플래그
+pub fn format_g(number: f64, precision: usize, keep_zeros: bool) -> String {
+    if !number.is_finite() {
+        return format_non_finite(number);
+    }
+    let precision = precision.max(1);
+    let scientific = format!("{:.*e}", precision - 1, number);
+    let (mantissa, exponent) = scientific.split_once('e').unwrap_or((&scientific, "0"));
+    let exponent: i32 = exponent.parse().unwrap_or(0);
+    if exponent < -4 || exponent >= precision as i32 {
+        let mantissa = if keep_zeros { mantissa.to_string() } else { trim_fraction_zeros(mantissa) };
+        format!("{}e{}{:02}", mantissa, if exponent < 0 { '-' } else { '+' }, exponent.abs())
+    } else {
+        let fixed = format!("{:.*}", (precision as i32 - 1 - exponent) as usize, number);
+        if keep_zeros {
+            fixed
+        } else {
+            trim_fraction_zeros(&fixed)
+        }
+    }
+}
+
+// C printf의 %e
+pub fn format_e(number: f64, precision: usize) -> String {
+    if !number.is_finite() {
+        return format_non_finite(number);
+    }
+    let scientific = format!("{:.*e}", precision, number);
+    let (mantissa, exponent) = scientific.split_once('e').unwrap_or((&scientific, "0"));
+    let exponent: i32 = exponent.parse().unwrap_or(0);
+    format!("{}e{}{:02}", mantissa, if exponent < 0 { '-' } else { '+' }, exponent.abs())
+}
+
+pub fn format_non_finite(number: f64) -> String {
+    match (number.is_nan(), number.is_sign_negative()) {
+        (true, true) => "-nan".to_string(),
+        (true, false) => "nan".to_string(),
+        (false, true) => "-inf".to_string(),
+        (false, false) => "inf".to_string(),
+    }
+}
+
+fn trim_fraction_zeros(text: &str) -> String {
+    if !text.contains('.') {
+        return text.to_string();
+    }
+    text.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn display(values: &[Value]) -> String {
+        let parts: Vec<String> = values.iter().map(|value| String::from_utf8_lossy(&value.display()).into_owned()).collect();
+        parts.join(" ")
+    }
+
+    fn run(source: &'static str) -> Result<String, String> {
+        let run = move || {
+            let mut interpreter = Interpreter::new("user_script");
+            let function = interpreter.load(source.as_bytes())?;
+            interpreter
+                .call(&function, Vec::new())
+                .map(|values| display(&values))
+                .map_err(|e| String::from_utf8_lossy(&e.value.display()).into_owned())
+        };
+        match spawn(run) {
+            Ok(handle) => handle.join().unwrap_or_else(|_| Err("panicked".to_string())),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    fn run_display(source: &'static str) -> String {
+        run(source).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    #[test]
+    fn numbers_print_like_lua() {
+        assert_eq!(format_number(3.0), "3");
+        assert_eq!(format_number(0.1), "0.1");
+        assert_eq!(format_number(1e15), "1e+15");
+        assert_eq!(format_number(-2.5), "-2.5");
+        assert_eq!(format_number(1.0 / 3.0), "0.33333333333333");
+        assert_eq!(format_g(1e-5, 6, false), "1e-05");
+        assert_eq!(format_e(12345.678, 2), "1.23e+04");
+    }
+
+    #[test]
+    fn evaluates_operators_with_lua_precedence() {
+        assert_eq!(run_display("return 1 + 2 * 3, 2 ^ 3 ^ 2, -2 ^ 2, 7 % -3, 'a' .. 1 .. 2"), "7 512 -4 -2 a12");
+        assert_eq!(run_display("return 1 < 2 and 'yes' or 'no', nil or false, not nil, #'abc'"), "yes false true 3");
+        assert_eq!(run_display("return '10' + 5, 10 == '10'"), "15 false");
+    }
+
+    #[test]
+    fn closures_capture_each_loop_iteration() {
+        let source = "local fs = {}\nfor i = 1, 3 do fs[i] = function() return i end end\nreturn fs[1](), fs[2](), fs[3]()";
+        assert_eq!(run_display(source), "1 2 3");
+    }
+
+    #[test]
+    fn recursion_and_varargs() {
+        let source = "local function fib(n) if n < 2 then return n end return fib(n - 1) + fib(n - 2) end\n\
+                      local function count(...) return select('#', ...) end\n\
+                      return fib(15), count(1, nil, 3), count()";
+        assert_eq!(run_display(source), "610 3 0");
+    }
+
+    #[test]
+    fn table_length_and_iteration() {
+        let source = "local t = {10, 20, 30, x = 1}\nt[5] = 50\nt[4] = 40\nlocal sum = 0\n\
+                      for k, v in pairs(t) do sum = sum + v end\nlocal n = 0\nfor i, v in ipairs(t) do n = i end\n\
+                      return #t, sum, n";
+        assert_eq!(run_display(source), "5 151 5");
+    }
+
+    #[test]
+    fn globals_are_read_only_once_locked() {
+        let mut interpreter = Interpreter::new("user_script");
+        interpreter.lock_globals();
+        let function = interpreter.load(b"x = 1").unwrap_or_else(|e| panic!("{}", e));
+        let error = interpreter.call(&function, Vec::new()).err().map(|e| e.value.display());
+        assert_eq!(error, Some(b"user_script:1: Attempt to modify a readonly table".to_vec()));
+
+        let function = interpreter.load(b"\nreturn missing").unwrap_or_else(|e| panic!("{}", e));
+        let error = interpreter.call(&function, Vec::new()).err().map(|e| e.value.display());
+        assert_eq!(error, Some(b"user_script:2: Script attempted to access nonexistent global variable 'missing'".to_vec()));
+    }
+
+    #[test]
+    fn runtime_errors_carry_the_line() {
+        assert_eq!(run("local t = nil\nreturn t.x").err().as_deref(), Some("user_script:2: attempt to index a nil value"));
+        assert_eq!(run("return 1 < 'x'").err().as_deref(), Some("user_script:1: attempt to compare number with string"));
+        assert_eq!(run("local function f() return f() end\nreturn f()").err().as_deref(), Some("user_script:1: stack overflow"));
+    }
+
+    #[test]
+    fn syntax_errors_name_the_token() {
+        assert_eq!(run("return (").err().as_deref(), Some("user_script:1: unexpected symbol near '<eof>'"));
+        assert_eq!(run("if true then\nreturn 1").err().as_deref(), Some("user_script:2: 'end' expected (to close 'if' at line 1) near '<eof>'"));
+    }
+}
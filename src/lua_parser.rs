@@ -0,0 +1,980 @@
+use std::rc::Rc;
+
+// Redis 스크립트가 쓰는 Lua 5.1 문법을 구문 트리로 바꿈: 토큰으로 나눈 뒤 재귀 하강으로 읽음
+// goto, 정수 나눗셈처럼 5.2 이후에 생긴 문법은 없음
+
+// 블록과 식의 중첩 제한, 넘으면 Lua처럼 "chunk has too many syntax levels"
+const MAX_SYNTAX_DEPTH: usize = 200;
+// 단항 연산자의 우선순위, ^만 이보다 높음
+const UNARY_PRIORITY: u8 = 8;
+
+const KEYWORDS: &[&str] = &[
+    "and", "break", "do", "else", "elseif", "end", "false", "for", "function", "if", "in", "local", "nil", "not", "or",
+    "repeat", "return", "then", "true", "until", "while",
+];
+// 긴 기호부터 맞춰 봐야 ".."를 "."두 개로 읽지 않음
+const SYMBOLS: &[&str] = &[
+    "...", "..", "==", "~=", "<=", ">=", "+", "-", "*", "/", "%", "^", "#", "<", ">", "=", "(", ")", "{", "}", "[", "]",
+    ";", ":", ",", ".",
+];
+
+#[derive(Debug)]
+pub struct SyntaxError {
+    pub line: usize,
+    pub message: String,
+}
+
+pub struct Block {
+    pub stmts: Vec<Stmt>,
+}
+
+pub struct Stmt {
+    pub kind: StmtKind,
+    pub line: usize,
+}
+
+pub enum StmtKind {
+    Local(Vec<Rc<str>>, Vec<Expr>),
+    // 대상은 Name이나 Index, function a.b() 정의도 대입으로 읽음
+    Assign(Vec<Expr>, Vec<Expr>),
+    Call(Expr),
+    Do(Block),
+    While(Expr, Block),
+    Repeat(Block, Expr),
+    If(Vec<(Expr, Block)>, Option<Block>),
+    NumericFor { var: Rc<str>, start: Expr, limit: Expr, step: Option<Expr>, body: Block },
+    GenericFor { names: Vec<Rc<str>>, exprs: Vec<Expr>, body: Block },
+    LocalFunction(Rc<str>, Rc<FunctionBody>),
+    Return(Vec<Expr>),
+    Break,
+}
+
+pub struct FunctionBody {
+    pub params: Vec<Rc<str>>,
+    pub vararg: bool,
+    pub body: Block,
+}
+
+pub enum TableField {
+    Positional(Expr),
+    Named(Expr, Expr),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryOp {
+    Or,
+    And,
+    Lt,
+    Gt,
+    Le,
+    Ge,
+    Ne,
+    Eq,
+    Concat,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+    Pow,
+}
+
+impl BinaryOp {
+    // Lua 5.1의 (왼쪽, 오른쪽) 우선순위, 오른쪽이 낮으면 오른쪽 결합
+    fn priority(self) -> (u8, u8) {
+        match self {
+            BinaryOp::Or => (1, 1),
+            BinaryOp::And => (2, 2),
+            BinaryOp::Lt | BinaryOp::Gt | BinaryOp::Le | BinaryOp::Ge | BinaryOp::Ne | BinaryOp::Eq => (3, 3),
+            BinaryOp::Concat => (5, 4),
+            BinaryOp::Add | BinaryOp::Sub => (6, 6),
+            BinaryOp::Mul | BinaryOp::Div | BinaryOp::Mod => (7, 7),
+            BinaryOp::Pow => (10, 9),
+        }
+    }
+
+    fn from_token(token: &Token) -> Option<Self> {
+        Some(match token {
+            Token::Keyword("or") => BinaryOp::Or,
+            Token::Keyword("and") => BinaryOp::And,
+            Token::Symbol("<") => BinaryOp::Lt,
+            Token::Symbol(">") => BinaryOp::Gt,
+            Token::Symbol("<=") => BinaryOp::Le,
+            Token::Symbol(">=") => BinaryOp::Ge,
+            Token::Symbol("~=") => BinaryOp::Ne,
+            Token::Symbol("==") => BinaryOp::Eq,
+            Token::Symbol("..") => BinaryOp::Concat,
+            Token::Symbol("+") => BinaryOp::Add,
+            Token::Symbol("-") => BinaryOp::Sub,
+            Token::Symbol("*") => BinaryOp::Mul,
+            Token::Symbol("/") => BinaryOp::Div,
+            Token::Symbol("%") => BinaryOp::Mod,
+            Token::Symbol("^") => BinaryOp::Pow,
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnaryOp {
+    Not,
+    Neg,
+    Len,
+}
+
+pub enum Expr {
+    Nil,
+    True,
+    False,
+    Vararg,
+    Number(f64),
+    String(Rc<[u8]>),
+    Function(Rc<FunctionBody>),
+    Table(Vec<TableField>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    Unary(UnaryOp, Box<Expr>),
+    Name(Rc<str>),
+    Index(Box<Expr>, Box<Expr>),
+    Call(Box<Expr>, Vec<Expr>, usize),
+    Method(Box<Expr>, Rc<str>, Vec<Expr>, usize),
+    // 괄호로 감싼 식은 여러 값을 돌려주는 호출도 값 하나로 줄임
+    Paren(Box<Expr>),
+}
+
+impl Expr {
+    // 식 목록의 마지막에 오면 값을 여러 개 낼 수 있는 식
+    pub fn is_multi(&self) -> bool {
+        matches!(self, Expr::Call(..) | Expr::Method(..) | Expr::Vararg)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Name(String),
+    Keyword(&'static str),
+    Number(f64),
+    String(Vec<u8>),
+    Symbol(&'static str),
+    Eof,
+}
+
+impl Token {
+    // 에러 메시지의 near 뒤에 붙는 표기
+    fn describe(&self) -> String {
+        match self {
+            Token::Name(name) => name.clone(),
+            Token::Keyword(keyword) => keyword.to_string(),
+            Token::Number(number) => number.to_string(),
+            Token::String(bytes) => String::from_utf8_lossy(bytes).into_owned(),
+            Token::Symbol(symbol) => symbol.to_string(),
+            Token::Eof => "<eof>".to_string(),
+        }
+    }
+}
+
+pub fn parse(source: &[u8]) -> Result<Block, SyntaxError> {
+    let tokens = Lexer::new(source).tokenize()?;
+    let mut parser = Parser {
+        tokens,
+        pos: 0,
+        depth: 0,
+        // 메인 청크는 vararg 함수
+        vararg: vec![true],
+    };
+    let block = parser.block()?;
+    if parser.peek() != &Token::Eof {
+        return Err(parser.error_near("'<eof>' expected"));
+    }
+    Ok(block)
+}
+
+struct Lexer<'a> {
+    source: &'a [u8],
+    pos: usize,
+    line: usize,
+}
+
+impl<'a> Lexer<'a> {
+    fn new(source: &'a [u8]) -> Self {
+        // Lua처럼 첫 줄이 #으로 시작하면(#!lua 헤더 등) 건너뜀
+        let pos = if source.first() == Some(&b'#') {
+            source.iter().position(|&b| b == b'\n').unwrap_or(source.len())
+        } else {
+            0
+        };
+        Self { source, pos, line: 1 }
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<u8> {
+        self.source.get(self.pos + offset).copied()
+    }
+
+    fn error(&self, message: &str) -> SyntaxError {
+        SyntaxError { line: self.line, message: message.to_string() }
+    }
+
+    fn tokenize(mut self) -> Result<Vec<(Token, usize)>, SyntaxError> {
+        let mut tokens = Vec::new();
+        loop {
+            self.skip_whitespace_and_comments()?;
+            let line = self.line;
+            let Some(byte) = self.peek_at(0) else {
+                tokens.push((Token::Eof, line));
+                return Ok(tokens);
+            };
+            let token = match byte {
+                b'a'..=b'z' | b'A'..=b'Z' | b'_' => self.name(),
+                b'0'..=b'9' => self.number()?,
+                b'.' if self.peek_at(1).is_some_and(|b| b.is_ascii_digit()) => self.number()?,
+                b'"' | b'\'' => self.quoted_string(byte)?,
+                b'[' if matches!(self.peek_at(1), Some(b'[') | Some(b'=')) && self.long_bracket_level().is_some() => {
+                    Token::String(self.long_bracket()?)
+                }
+                _ => self.symbol()?,
+            };
+            tokens.push((token, line));
+        }
+    }
+
+    fn skip_whitespace_and_comments(&mut self) -> Result<(), SyntaxError> {
+        while let Some(byte) = self.peek_at(0) {
+            match byte {
+                b'\n' => {
+                    self.line += 1;
+                    self.pos += 1;
+                }
+                b' ' | b'\t' | b'\r' | 0x0b | 0x0c => self.pos += 1,
+                b'-' if self.peek_at(1) == Some(b'-') => {
+                    self.pos += 2;
+                    if self.peek_at(0) == Some(b'[') && self.long_bracket_level().is_some() {
+                        self.long_bracket()?;
+                    } else {
+                        while self.peek_at(0).is_some_and(|b| b != b'\n') {
+                            self.pos += 1;
+                        }
+                    }
+                }
+                _ => break,
+            }
+        }
+        Ok(())
+    }
+
+    fn name(&mut self) -> Token {
+        let start = self.pos;
+        while self.peek_at(0).is_some_and(|b| b.is_ascii_alphanumeric() || b == b'_') {
+            self.pos += 1;
+        }
+        let name = std::str::from_utf8(&self.source[start..self.pos]).unwrap_or_default();
+        match KEYWORDS.iter().find(|keyword| **keyword == name) {
+            Some(keyword) => Token::Keyword(keyword),
+            None => Token::Name(name.to_string()),
+        }
+    }
+
+    fn number(&mut self) -> Result<Token, SyntaxError> {
+        let start = self.pos;
+        if self.peek_at(0) == Some(b'0') && matches!(self.peek_at(1), Some(b'x') | Some(b'X')) {
+            self.pos += 2;
+        }
+        // strtod처럼 숫자, 점, 지수 부분을 한 번에 읽고 나서 해석함
+        while let Some(byte) = self.peek_at(0) {
+            let exponent_sign = matches!(byte, b'+' | b'-') && matches!(self.source.get(self.pos - 1), Some(b'e') | Some(b'E'));
+            if !(byte.is_ascii_alphanumeric() || byte == b'.' || byte == b'_' || exponent_sign) {
+                break;
+            }
+            self.pos += 1;
+        }
+        let text = std::str::from_utf8(&self.source[start..self.pos]).unwrap_or_default();
+        parse_number(text)
+            .map(Token::Number)
+            .ok_or_else(|| self.error(&format!("malformed number near '{}'", text)))
+    }
+
+    fn quoted_string(&mut self, quote: u8) -> Result<Token, SyntaxError> {
+        self.pos += 1;
+        let mut bytes = Vec::new();
+        loop {
+            let Some(byte) = self.peek_at(0) else {
+                return Err(self.error("unfinished string near '<eof>'"));
+            };
+            self.pos += 1;
+            match byte {
+                b'\n' => return Err(self.error(&format!("unfinished string near '{}'", String::from_utf8_lossy(&bytes)))),
+                b'\\' => {
+                    let Some(escaped) = self.peek_at(0) else {
+                        return Err(self.error("unfinished string near '<eof>'"));
+                    };
+                    self.pos += 1;
+                    match escaped {
+                        b'n' => bytes.push(b'\n'),
+                        b't' => bytes.push(b'\t'),
+                        b'r' => bytes.push(b'\r'),
+                        b'a' => bytes.push(0x07),
+                        b'b' => bytes.push(0x08),
+                        b'f' => bytes.push(0x0c),
+                        b'v' => bytes.push(0x0b),
+                        b'\n' => {
+                            self.line += 1;
+                            bytes.push(b'\n');
+                        }
+                        b'0'..=b'9' => {
+                            let mut value = (escaped - b'0') as u32;
+                            for _ in 0..2 {
+                                match self.peek_at(0) {
+                                    Some(digit @ b'0'..=b'9') => {
+                                        value = value * 10 + (digit - b'0') as u32;
+                                        self.pos += 1;
+                                    }
+                                    _ => break,
+                                }
+                            }
+                            if value > 255 {
+                                return Err(self.error("escape sequence too large"));
+                            }
+                            bytes.push(value as u8);
+                        }
+                        other => bytes.push(other),
+                    }
+                }
+                byte if byte == quote => return Ok(Token::String(bytes)),
+                byte => bytes.push(byte),
+            }
+        }
+    }
+
+    // [[ 또는 [==[ 처럼 여는 긴 괄호면 = 개수
+    fn long_bracket_level(&self) -> Option<usize> {
+        let mut level = 0;
+        while self.peek_at(1 + level) == Some(b'=') {
+            level += 1;
+        }
+        (self.peek_at(1 + level) == Some(b'[')).then_some(level)
+    }
+
+    fn long_bracket(&mut self) -> Result<Vec<u8>, SyntaxError> {
+        let level = self.long_bracket_level().unwrap_or(0);
+        self.pos += level + 2;
+        // 여는 괄호 바로 뒤의 줄바꿈은 내용에 넣지 않음
+        if self.peek_at(0) == Some(b'\r') {
+            self.pos += 1;
+        }
+        if self.peek_at(0) == Some(b'\n') {
+            self.pos += 1;
+            self.line += 1;
+        }
+        let start = self.pos;
+        loop {
+            match self.peek_at(0) {
+                None => return Err(self.error("unfinished long string near '<eof>'")),
+                Some(b']') if (0..level).all(|i| self.peek_at(1 + i) == Some(b'=')) && self.peek_at(1 + level) == Some(b']') => {
+                    let content = self.source[start..self.pos].to_vec();
+                    self.pos += level + 2;
+                    return Ok(content);
+                }
+                Some(b'\n') => {
+                    self.line += 1;
+                    self.pos += 1;
+                }
+                Some(_) => self.pos += 1,
+            }
+        }
+    }
+
+    fn symbol(&mut self) -> Result<Token, SyntaxError> {
+        let rest = &self.source[self.pos..];
+        match SYMBOLS.iter().find(|symbol| rest.starts_with(symbol.as_bytes())) {
+            Some(symbol) => {
+                self.pos += symbol.len();
+                Ok(Token::Symbol(symbol))
+            }
+            None => Err(self.error(&format!("unexpected symbol near '{}'", rest[0] as char))),
+        }
+    }
+}
+
+// Lua의 숫자 표기(10진 실수, 0x로 시작하는 16진 정수), tonumber와 문자열 산술에도 씀
+pub fn parse_number(text: &str) -> Option<f64> {
+    let text = text.trim_matches(|c: char| c.is_ascii_whitespace());
+    let (negative, unsigned) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, text.strip_prefix('+').unwrap_or(text)),
+    };
+    let value = if let Some(hex) = unsigned.strip_prefix("0x").or_else(|| unsigned.strip_prefix("0X")) {
+        if hex.is_empty() || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+        hex.bytes().fold(0f64, |value, b| value * 16.0 + (b as char).to_digit(16).unwrap_or(0) as f64)
+    } else {
+        // Rust의 parse는 inf, nan 같은 단어도 받으므로 숫자 표기만 넘김
+        if !unsigned.bytes().any(|b| b.is_ascii_digit()) || !unsigned.bytes().all(|b| b.is_ascii_digit() || matches!(b, b'.' | b'e' | b'E' | b'+' | b'-')) {
+            return None;
+        }
+        unsigned.parse::<f64>().ok()?
+    };
+    Some(if negative { -value } else { value })
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+    depth: usize,
+    // 지금 읽는 함수들이 vararg인지, ...는 가장 안쪽 함수가 vararg일 때만 쓸 수 있음
+    vararg: Vec<bool>,
+}
+
+impl Parser {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.pos].0
+    }
+
+    fn peek_next(&self) -> &Token {
+        &self.tokens[(self.pos + 1).min(self.tokens.len() - 1)].0
+    }
+
+    fn line(&self) -> usize {
+        self.tokens[self.pos].1
+    }
+
+    fn advance(&mut self) -> Token {
+        let token = self.tokens[self.pos].0.clone();
+        if self.pos + 1 < self.tokens.len() {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn error_near(&self, message: &str) -> SyntaxError {
+        SyntaxError { line: self.line(), message: format!("{} near '{}'", message, self.peek().describe()) }
+    }
+
+    fn check_symbol(&self, symbol: &str) -> bool {
+        matches!(self.peek(), Token::Symbol(s) if *s == symbol)
+    }
+
+    fn check_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Token::Keyword(k) if *k == keyword)
+    }
+
+    fn accept_symbol(&mut self, symbol: &str) -> bool {
+        let found = self.check_symbol(symbol);
+        if found {
+            self.advance();
+        }
+        found
+    }
+
+    fn accept_keyword(&mut self, keyword: &str) -> bool {
+        let found = self.check_keyword(keyword);
+        if found {
+            self.advance();
+        }
+        found
+    }
+
+    fn expect_symbol(&mut self, symbol: &str) -> Result<(), SyntaxError> {
+        if self.accept_symbol(symbol) {
+            Ok(())
+        } else {
+            Err(self.error_near(&format!("'{}' expected", symbol)))
+        }
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<(), SyntaxError> {
+        if self.accept_keyword(keyword) {
+            Ok(())
+        } else {
+            Err(self.error_near(&format!("'{}' expected", keyword)))
+        }
+    }
+
+    // 여는 줄과 닫는 줄이 다르면 Lua처럼 어디서 열었는지 알려 줌
+    fn expect_closing(&mut self, keyword: &str, opener: &str, line: usize) -> Result<(), SyntaxError> {
+        if self.accept_keyword(keyword) {
+            return Ok(());
+        }
+        if line == self.line() {
+            Err(self.error_near(&format!("'{}' expected", keyword)))
+        } else {
+            Err(self.error_near(&format!("'{}' expected (to close '{}' at line {})", keyword, opener, line)))
+        }
+    }
+
+    fn expect_name(&mut self) -> Result<Rc<str>, SyntaxError> {
+        match self.peek() {
+            Token::Name(name) => {
+                let name = Rc::from(name.as_str());
+                self.advance();
+                Ok(name)
+            }
+            _ => Err(self.error_near("<name> expected")),
+        }
+    }
+
+    fn enter(&mut self) -> Result<(), SyntaxError> {
+        self.depth += 1;
+        if self.depth > MAX_SYNTAX_DEPTH {
+            return Err(self.error_near("chunk has too many syntax levels"));
+        }
+        Ok(())
+    }
+
+    fn leave(&mut self) {
+        self.depth -= 1;
+    }
+
+    fn block_ends(&self) -> bool {
+        matches!(self.peek(), Token::Eof | Token::Keyword("end") | Token::Keyword("else") | Token::Keyword("elseif") | Token::Keyword("until"))
+    }
+
+    fn block(&mut self) -> Result<Block, SyntaxError> {
+        self.enter()?;
+        let mut stmts = Vec::new();
+        while !self.block_ends() {
+            if self.accept_symbol(";") {
+                continue;
+            }
+            let line = self.line();
+            // return과 break는 블록의 마지막 문장이어야 함
+            let last = self.check_keyword("return") || self.check_keyword("break");
+            let kind = self.statement()?;
+            stmts.push(Stmt { kind, line });
+            if last {
+                self.accept_symbol(";");
+                if !self.block_ends() {
+                    return Err(self.error_near("'end' expected"));
+                }
+                break;
+            }
+        }
+        self.leave();
+        Ok(Block { stmts })
+    }
+
+    fn statement(&mut self) -> Result<StmtKind, SyntaxError> {
+        let line = self.line();
+        match self.peek().clone() {
+            Token::Keyword("if") => {
+                self.advance();
+                let mut branches = Vec::new();
+                let condition = self.expression()?;
+                self.expect_keyword("then")?;
+                branches.push((condition, self.block()?));
+                let mut otherwise = None;
+                loop {
+                    if self.accept_keyword("elseif") {
+                        let condition = self.expression()?;
+                        self.expect_keyword("then")?;
+                        branches.push((condition, self.block()?));
+                    } else if self.accept_keyword("else") {
+                        otherwise = Some(self.block()?);
+                        self.expect_closing("end", "if", line)?;
+                        break;
+                    } else {
+                        self.expect_closing("end", "if", line)?;
+                        break;
+                    }
+                }
+                Ok(StmtKind::If(branches, otherwise))
+            }
+            Token::Keyword("while") => {
+                self.advance();
+                let condition = self.expression()?;
+                self.expect_keyword("do")?;
+                let body = self.block()?;
+                self.expect_closing("end", "while", line)?;
+                Ok(StmtKind::While(condition, body))
+            }
+            Token::Keyword("do") => {
+                self.advance();
+                let body = self.block()?;
+                self.expect_closing("end", "do", line)?;
+                Ok(StmtKind::Do(body))
+            }
+            Token::Keyword("for") => {
+                self.advance();
+                let first = self.expect_name()?;
+                if self.accept_symbol("=") {
+                    let start = self.expression()?;
+                    self.expect_symbol(",")?;
+                    let limit = self.expression()?;
+                    let step = if self.accept_symbol(",") { Some(self.expression()?) } else { None };
+                    self.expect_keyword("do")?;
+                    let body = self.block()?;
+                    self.expect_closing("end", "for", line)?;
+                    return Ok(StmtKind::NumericFor { var: first, start, limit, step, body });
+                }
+                let mut names = vec![first];
+                while self.accept_symbol(",") {
+                    names.push(self.expect_name()?);
+                }
+                if !self.accept_keyword("in") {
+                    return Err(self.error_near("'=' or 'in' expected"));
+                }
+                let exprs = self.expression_list()?;
+                self.expect_keyword("do")?;
+                let body = self.block()?;
+                self.expect_closing("end", "for", line)?;
+                Ok(StmtKind::GenericFor { names, exprs, body })
+            }
+            Token::Keyword("repeat") => {
+                self.advance();
+                let body = self.block()?;
+                self.expect_closing("until", "repeat", line)?;
+                let condition = self.expression()?;
+                Ok(StmtKind::Repeat(body, condition))
+            }
+            Token::Keyword("function") => {
+                self.advance();
+                // a.b.c:m 형태의 이름을 대입 대상으로 바꾸고, :가 있으면 self를 첫 인자로 둠
+                let mut target = Expr::Name(self.expect_name()?);
+                let mut is_method = false;
+                while self.check_symbol(".") || self.check_symbol(":") {
+                    is_method = self.check_symbol(":");
+                    self.advance();
+                    let key = self.expect_name()?;
+                    target = Expr::Index(Box::new(target), Box::new(Expr::String(Rc::from(key.as_bytes()))));
+                    if is_method {
+                        break;
+                    }
+                }
+                let body = self.function_body(is_method, line)?;
+                Ok(StmtKind::Assign(vec![target], vec![Expr::Function(body)]))
+            }
+            Token::Keyword("local") => {
+                self.advance();
+                if self.accept_keyword("function") {
+                    let name = self.expect_name()?;
+                    let body = self.function_body(false, line)?;
+                    return Ok(StmtKind::LocalFunction(name, body));
+                }
+                let mut names = vec![self.expect_name()?];
+                while self.accept_symbol(",") {
+                    names.push(self.expect_name()?);
+                }
+                let exprs = if self.accept_symbol("=") { self.expression_list()? } else { Vec::new() };
+                Ok(StmtKind::Local(names, exprs))
+            }
+            Token::Keyword("return") => {
+                self.advance();
+                let exprs = if self.block_ends() || self.check_symbol(";") { Vec::new() } else { self.expression_list()? };
+                Ok(StmtKind::Return(exprs))
+            }
+            Token::Keyword("break") => {
+                self.advance();
+                Ok(StmtKind::Break)
+            }
+            _ => self.expression_statement(),
+        }
+    }
+
+    fn expression_statement(&mut self) -> Result<StmtKind, SyntaxError> {
+        let first = self.suffixed_expression()?;
+        if self.check_symbol("=") || self.check_symbol(",") {
+            let mut targets = vec![first];
+            while self.accept_symbol(",") {
+                targets.push(self.suffixed_expression()?);
+            }
+            if targets.iter().any(|target| !matches!(target, Expr::Name(_) | Expr::Index(..))) {
+                return Err(self.error_near("syntax error"));
+            }
+            self.expect_symbol("=")?;
+            let exprs = self.expression_list()?;
+            return Ok(StmtKind::Assign(targets, exprs));
+        }
+        match first {
+            Expr::Call(..) | Expr::Method(..) => Ok(StmtKind::Call(first)),
+            _ => Err(self.error_near("syntax error")),
+        }
+    }
+
+    fn function_body(&mut self, is_method: bool, line: usize) -> Result<Rc<FunctionBody>, SyntaxError> {
+        let mut params: Vec<Rc<str>> = if is_method { vec![Rc::from("self")] } else { Vec::new() };
+        let mut vararg = false;
+        self.expect_symbol("(")?;
+        if !self.check_symbol(")") {
+            loop {
+                if self.accept_symbol("...") {
+                    vararg = true;
+                    break;
+                }
+                params.push(self.expect_name()?);
+                if !self.accept_symbol(",") {
+                    break;
+                }
+            }
+        }
+        self.expect_symbol(")")?;
+        self.vararg.push(vararg);
+        let body = self.block();
+        self.vararg.pop();
+        let body = body?;
+        self.expect_closing("end", "function", line)?;
+        Ok(Rc::new(FunctionBody { params, vararg, body }))
+    }
+
+    fn expression_list(&mut self) -> Result<Vec<Expr>, SyntaxError> {
+        let mut exprs = vec![self.expression()?];
+        while self.accept_symbol(",") {
+            exprs.push(self.expression()?);
+        }
+        Ok(exprs)
+    }
+
+    fn expression(&mut self) -> Result<Expr, SyntaxError> {
+        self.subexpression(0)
+    }
+
+    // 왼쪽 우선순위가 limit보다 높은 연산자만 이어서 읽는 우선순위 오르기 방식
+    fn subexpression(&mut self, limit: u8) -> Result<Expr, SyntaxError> {
+        self.enter()?;
+        let unary = match self.peek() {
+            Token::Keyword("not") => Some(UnaryOp::Not),
+            Token::Symbol("-") => Some(UnaryOp::Neg),
+            Token::Symbol("#") => Some(UnaryOp::Len),
+            _ => None,
+        };
+        let mut left = match unary {
+            Some(op) => {
+                self.advance();
+                let operand = self.subexpression(UNARY_PRIORITY)?;
+                // 숫자 상수의 부호는 바로 접어 둠
+                match (op, operand) {
+                    (UnaryOp::Neg, Expr::Number(value)) => Expr::Number(-value),
+                    (op, operand) => Expr::Unary(op, Box::new(operand)),
+                }
+            }
+            None => self.simple_expression()?,
+        };
+        while let Some(op) = BinaryOp::from_token(self.peek()) {
+            let (left_priority, right_priority) = op.priority();
+            if left_priority <= limit {
+                break;
+            }
+            self.advance();
+            let right = self.subexpression(right_priority)?;
+            left = Expr::Binary(op, Box::new(left), Box::new(right));
+        }
+        self.leave();
+        Ok(left)
+    }
+
+    fn simple_expression(&mut self) -> Result<Expr, SyntaxError> {
+        let line = self.line();
+        let expr = match self.peek().clone() {
+            Token::Number(value) => Expr::Number(value),
+            Token::String(bytes) => Expr::String(Rc::from(bytes)),
+            Token::Keyword("nil") => Expr::Nil,
+            Token::Keyword("true") => Expr::True,
+            Token::Keyword("false") => Expr::False,
+            Token::Symbol("...") => {
+                if !self.vararg.last().copied().unwrap_or(false) {
+                    return Err(self.error_near("cannot use '...' outside a vararg function"));
+                }
+                Expr::Vararg
+            }
+            Token::Symbol("{") => return self.table_constructor(),
+            Token::Keyword("function") => {
+                self.advance();
+                return Ok(Expr::Function(self.function_body(false, line)?));
+            }
+            _ => return self.suffixed_expression(),
+        };
+        self.advance();
+        Ok(expr)
+    }
+
+    fn primary_expression(&mut self) -> Result<Expr, SyntaxError> {
+        match self.peek().clone() {
+            Token::Name(name) => {
+                self.advance();
+                Ok(Expr::Name(Rc::from(name.as_str())))
+            }
+            Token::Symbol("(") => {
+                let line = self.line();
+                self.advance();
+                let inner = self.expression()?;
+                if !self.accept_symbol(")") {
+                    return Err(if line == self.line() {
+                        self.error_near("')' expected")
+                    } else {
+                        self.error_near(&format!("')' expected (to close '(' at line {})", line))
+                    });
+                }
+                Ok(Expr::Paren(Box::new(inner)))
+            }
+            _ => Err(self.error_near("unexpected symbol")),
+        }
+    }
+
+    fn suffixed_expression(&mut self) -> Result<Expr, SyntaxError> {
+        let mut expr = self.primary_expression()?;
+        loop {
+            let line = self.line();
+            match self.peek() {
+                Token::Symbol(".") => {
+                    self.advance();
+                    let key = self.expect_name()?;
+                    expr = Expr::Index(Box::new(expr), Box::new(Expr::String(Rc::from(key.as_bytes()))));
+                }
+                Token::Symbol("[") => {
+                    self.advance();
+                    let key = self.expression()?;
+                    self.expect_symbol("]")?;
+                    expr = Expr::Index(Box::new(expr), Box::new(key));
+                }
+                Token::Symbol(":") => {
+                    self.advance();
+                    let name = self.expect_name()?;
+                    let args = self.call_arguments()?;
+                    expr = Expr::Method(Box::new(expr), name, args, line);
+                }
+                Token::Symbol("(") | Token::Symbol("{") | Token::String(_) => {
+                    let args = self.call_arguments()?;
+                    expr = Expr::Call(Box::new(expr), args, line);
+                }
+                _ => return Ok(expr),
+            }
+        }
+    }
+
+    fn call_arguments(&mut self) -> Result<Vec<Expr>, SyntaxError> {
+        match self.peek().clone() {
+            Token::String(bytes) => {
+                self.advance();
+                Ok(vec![Expr::String(Rc::from(bytes))])
+            }
+            Token::Symbol("{") => Ok(vec![self.table_constructor()?]),
+            Token::Symbol("(") => {
+                self.advance();
+                if self.accept_symbol(")") {
+                    return Ok(Vec::new());
+                }
+                let args = self.expression_list()?;
+                self.expect_symbol(")")?;
+                Ok(args)
+            }
+            _ => Err(self.error_near("function arguments expected")),
+        }
+    }
+
+    fn table_constructor(&mut self) -> Result<Expr, SyntaxError> {
+        let line = self.line();
+        self.expect_symbol("{")?;
+        let mut fields = Vec::new();
+        while !self.check_symbol("}") {
+            if self.accept_symbol("[") {
+                let key = self.expression()?;
+                self.expect_symbol("]")?;
+                self.expect_symbol("=")?;
+                fields.push(TableField::Named(key, self.expression()?));
+            } else if matches!(self.peek(), Token::Name(_)) && self.peek_next() == &Token::Symbol("=") {
+                let key = self.expect_name()?;
+                self.advance();
+                fields.push(TableField::Named(Expr::String(Rc::from(key.as_bytes())), self.expression()?));
+            } else {
+                fields.push(TableField::Positional(self.expression()?));
+            }
+            if !self.accept_symbol(",") && !self.accept_symbol(";") {
+                break;
+            }
+        }
+        if !self.accept_symbol("}") {
+            return Err(if line == self.line() {
+                self.error_near("'}' expected")
+            } else {
+                self.error_near(&format!("'}}' expected (to close '{{' at line {})", line))
+            });
+        }
+        Ok(Expr::Table(fields))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lua;
+
+    // 깊은 중첩도 읽어 보므로 인터프리터와 같은 스택에서 돌리고, return 문의 식을 괄호 표기로 돌려줌
+    fn parse_returned(source: &str) -> Result<Vec<String>, (usize, String)> {
+        let source = source.to_string();
+        let parsed = lua::spawn(move || match parse(source.as_bytes()) {
+            Ok(block) => match block.stmts.last().map(|stmt| &stmt.kind) {
+                Some(StmtKind::Return(exprs)) => Ok(exprs.iter().map(shape).collect()),
+                _ => Ok(Vec::new()),
+            },
+            Err(e) => Err((e.line, e.message)),
+        });
+        parsed.unwrap().join().unwrap()
+    }
+
+    // 식을 괄호 표기로 바꿔 트리 모양을 비교함
+    fn shape(expr: &Expr) -> String {
+        match expr {
+            Expr::Nil => "nil".to_string(),
+            Expr::True => "true".to_string(),
+            Expr::False => "false".to_string(),
+            Expr::Vararg => "...".to_string(),
+            Expr::Number(number) => number.to_string(),
+            Expr::String(bytes) => format!("{:?}", String::from_utf8_lossy(bytes)),
+            Expr::Function(_) => "function".to_string(),
+            Expr::Table(fields) => format!("{{{}}}", fields.len()),
+            Expr::Binary(op, left, right) => format!("({:?} {} {})", op, shape(left), shape(right)),
+            Expr::Unary(op, operand) => format!("({:?} {})", op, shape(operand)),
+            Expr::Name(name) => name.to_string(),
+            Expr::Index(table, key) => format!("{}[{}]", shape(table), shape(key)),
+            Expr::Call(function, args, _) => format!("{}({})", shape(function), args.iter().map(shape).collect::<Vec<_>>().join(", ")),
+            Expr::Method(object, name, args, _) => format!("{}:{}({})", shape(object), name, args.iter().map(shape).collect::<Vec<_>>().join(", ")),
+            Expr::Paren(inner) => format!("[{}]", shape(inner)),
+        }
+    }
+
+    fn returned(source: &str) -> Vec<String> {
+        parse_returned(source).unwrap_or_else(|(line, message)| panic!("{}: {}", line, message))
+    }
+
+    fn syntax_error(source: &str) -> (usize, String) {
+        parse_returned(source).expect_err("parsed invalid source")
+    }
+
+    #[test]
+    fn binary_operators_follow_lua_precedence_and_associativity() {
+        assert_eq!(returned("return 1 + 2 * 3"), ["(Add 1 (Mul 2 3))"]);
+        assert_eq!(returned("return 2 ^ 3 ^ 2, -2 ^ 2"), ["(Pow 2 (Pow 3 2))", "(Neg (Pow 2 2))"]);
+        assert_eq!(returned("return 'a' .. 'b' .. 'c'"), ["(Concat \"a\" (Concat \"b\" \"c\"))"]);
+        assert_eq!(returned("return a or b and not c == d"), ["(Or a (And b (Eq (Not c) d)))"]);
+        assert_eq!(returned("return (f()), #t"), ["[f()]", "(Len t)"]);
+    }
+
+    #[test]
+    fn suffixes_chain_calls_indexes_and_methods() {
+        assert_eq!(returned("return a.b[1]:c(2)('x')"), ["a[\"b\"][1]:c(2)(\"x\")"]);
+        assert_eq!(returned("return f{1, 2}, g'str'"), ["f({2})", "g(\"str\")"]);
+    }
+
+    #[test]
+    fn strings_decode_escapes_and_long_brackets() {
+        assert_eq!(returned(r"return 'a\n\65\\', [[x]], [==[a]]b]==]"), ["\"a\\nA\\\\\"", "\"x\"", "\"a]]b\""]);
+        assert_eq!(returned("return [[\nfirst]]"), ["\"first\""]);
+        assert_eq!(syntax_error("return 'abc"), (1, "unfinished string near '<eof>'".to_string()));
+        assert_eq!(syntax_error(r"return '\300'"), (1, "escape sequence too large".to_string()));
+    }
+
+    #[test]
+    fn syntax_errors_report_the_line_and_token() {
+        assert_eq!(syntax_error("local x = 1\nx = = 2"), (2, "unexpected symbol near '='".to_string()));
+        assert_eq!(syntax_error("while true do\nlocal y = 1"), (2, "'end' expected (to close 'while' at line 1) near '<eof>'".to_string()));
+        let nested = format!("return {}1{}", "(".repeat(MAX_SYNTAX_DEPTH + 1), ")".repeat(MAX_SYNTAX_DEPTH + 1));
+        assert_eq!(syntax_error(&nested).1, "chunk has too many syntax levels near '('");
+    }
+
+    #[test]
+    fn numbers_parse_decimal_and_hex_forms() {
+        assert_eq!(parse_number(" 0x1F "), Some(31.0));
+        assert_eq!(parse_number("-2.5e2"), Some(-250.0));
+        assert_eq!(parse_number(".5"), Some(0.5));
+        assert_eq!(parse_number("0x"), None);
+        assert_eq!(parse_number("inf"), None);
+        assert_eq!(parse_number("1e"), None);
+    }
+}
@@ -0,0 +1,1209 @@
+use crate::lua::{format_e, format_g, format_non_finite, Interpreter, LuaError, TableRef, Value};
+use std::cell::Cell;
+use std::rc::Rc;
+
+// Redis가 스크립트에 열어 두는 Lua 5.1 기본 라이브러리: base, string, table, math
+// io, os, loadstring처럼 서버 밖에 손대거나 코드를 새로 읽는 함수는 없음
+
+const MAX_CAPTURES: usize = 32;
+const CAP_UNFINISHED: isize = -1;
+const CAP_POSITION: isize = -2;
+// 패턴 매칭의 재귀 깊이 제한
+const MAX_MATCH_DEPTH: usize = 200;
+// 한 번에 돌려줄 수 있는 값의 수, Lua 5.1의 LUAI_MAXCSTACK
+const MAX_RESULTS: i64 = 8000;
+// string.find가 패턴이 아닌 평범한 문자열로 찾아도 되는지 가르는 문자들
+const PATTERN_SPECIALS: &[u8] = b"^$*+?.([%-";
+// Redis의 redisLrand48과 같은 48비트 선형 합동 생성기
+const LRAND48_MULTIPLIER: u64 = 0x5DEECE66D;
+const LRAND48_INCREMENT: u64 = 0xB;
+const LRAND48_MAX: u64 = i32::MAX as u64;
+
+type MathFunction = fn(f64) -> f64;
+
+pub fn open(interpreter: &mut Interpreter) {
+    seed_random(interpreter, 0);
+    open_base(interpreter);
+    let string = open_string(interpreter);
+    interpreter.set_global("string", Value::Table(string));
+    let table = open_table(interpreter);
+    interpreter.set_global("table", Value::Table(table));
+    let math = open_math(interpreter);
+    interpreter.set_global("math", Value::Table(math));
+}
+
+pub(crate) fn register(table: &TableRef, name: &'static str, call: impl Fn(&mut Interpreter, Vec<Value>) -> Result<Vec<Value>, LuaError> + 'static) {
+    table.borrow_mut().set_str(name, Interpreter::native(name, call));
+}
+
+fn arg(args: &[Value], index: usize) -> Value {
+    args.get(index).cloned().unwrap_or(Value::Nil)
+}
+
+fn bad_argument(interpreter: &Interpreter, index: usize, function: &str, message: &str) -> LuaError {
+    interpreter.raise(Value::string(format!("bad argument #{} to '{}' ({})", index + 1, function, message)))
+}
+
+fn type_error(interpreter: &Interpreter, args: &[Value], index: usize, function: &str, expected: &str) -> LuaError {
+    let got = args.get(index).map_or("no value", Value::type_name);
+    bad_argument(interpreter, index, function, &format!("{} expected, got {}", expected, got))
+}
+
+fn check_number(interpreter: &Interpreter, args: &[Value], index: usize, function: &str) -> Result<f64, LuaError> {
+    arg(args, index).to_number().ok_or_else(|| type_error(interpreter, args, index, function, "number"))
+}
+
+pub(crate) fn check_integer(interpreter: &Interpreter, args: &[Value], index: usize, function: &str) -> Result<i64, LuaError> {
+    check_number(interpreter, args, index, function).map(|number| number as i64)
+}
+
+fn opt_integer(interpreter: &Interpreter, args: &[Value], index: usize, function: &str, default: i64) -> Result<i64, LuaError> {
+    match args.get(index) {
+        None | Some(Value::Nil) => Ok(default),
+        Some(_) => check_integer(interpreter, args, index, function),
+    }
+}
+
+pub(crate) fn check_string(interpreter: &Interpreter, args: &[Value], index: usize, function: &str) -> Result<Rc<[u8]>, LuaError> {
+    arg(args, index).to_bytes().ok_or_else(|| type_error(interpreter, args, index, function, "string"))
+}
+
+fn check_table(interpreter: &Interpreter, args: &[Value], index: usize, function: &str) -> Result<TableRef, LuaError> {
+    match args.get(index) {
+        Some(Value::Table(table)) => Ok(table.clone()),
+        _ => Err(type_error(interpreter, args, index, function, "table")),
+    }
+}
+
+fn check_any(interpreter: &Interpreter, args: &[Value], index: usize, function: &str) -> Result<Value, LuaError> {
+    args.get(index).cloned().ok_or_else(|| bad_argument(interpreter, index, function, "value expected"))
+}
+
+fn number(value: impl Into<f64>) -> Value {
+    Value::Number(value.into())
+}
+
+fn open_base(interpreter: &mut Interpreter) {
+    let globals: [(&'static str, Value); 14] = [
+        (
+            "assert",
+            Interpreter::native("assert", |interpreter, args| {
+                if arg(&args, 0).truthy() {
+                    return Ok(args);
+                }
+                match args.get(1) {
+                    Some(message) => Err(interpreter.raise(message.clone())),
+                    None => Err(interpreter.raise(Value::string("assertion failed!"))),
+                }
+            }),
+        ),
+        (
+            "error",
+            Interpreter::native("error", |interpreter, args| {
+                let value = arg(&args, 0);
+                let level = opt_integer(interpreter, &args, 1, "error", 1)?;
+                match &value {
+                    Value::String(message) if level > 0 => Err(interpreter.error(&String::from_utf8_lossy(message))),
+                    _ => Err(interpreter.raise(value)),
+                }
+            }),
+        ),
+        (
+            "ipairs",
+            Interpreter::native("ipairs", |interpreter, args| {
+                let table = check_table(interpreter, &args, 0, "ipairs")?;
+                let iterator = Interpreter::native("ipairs_iterator", |interpreter, args| {
+                    let table = check_table(interpreter, &args, 0, "ipairs")?;
+                    let index = check_number(interpreter, &args, 1, "ipairs")? + 1.0;
+                    let value = table.borrow().get(&Value::Number(index));
+                    Ok(if value.is_nil() { vec![Value::Nil] } else { vec![Value::Number(index), value] })
+                });
+                Ok(vec![iterator, Value::Table(table), number(0)])
+            }),
+        ),
+        (
+            "next",
+            Interpreter::native("next", |interpreter, args| {
+                let table = check_table(interpreter, &args, 0, "next")?;
+                let entry = table.borrow().next(&arg(&args, 1)).map_err(|e| interpreter.raise(Value::string(e)))?;
+                Ok(entry.map_or(vec![Value::Nil], |(key, value)| vec![key, value]))
+            }),
+        ),
+        (
+            "pairs",
+            Interpreter::native("pairs", |interpreter, args| {
+                let table = check_table(interpreter, &args, 0, "pairs")?;
+                Ok(vec![interpreter.global("next"), Value::Table(table), Value::Nil])
+            }),
+        ),
+        (
+            "pcall",
+            Interpreter::native("pcall", |interpreter, mut args| {
+                if args.is_empty() {
+                    return Err(bad_argument(interpreter, 0, "pcall", "value expected"));
+                }
+                let function = args.remove(0);
+                match interpreter.call(&function, args) {
+                    Ok(mut values) => {
+                        values.insert(0, Value::Boolean(true));
+                        Ok(values)
+                    }
+                    // SCRIPT KILL로 멈춘 스크립트는 pcall로 잡지 못함
+                    Err(e) if interpreter.is_interrupted() => Err(e),
+                    Err(e) => Ok(vec![Value::Boolean(false), e.value]),
+                }
+            }),
+        ),
+        (
+            "select",
+            Interpreter::native("select", |interpreter, args| {
+                if let Some(Value::String(selector)) = args.first() {
+                    if selector.as_ref() == b"#" {
+                        return Ok(vec![number((args.len() - 1) as f64)]);
+                    }
+                }
+                let count = args.len() as i64 - 1;
+                let index = check_integer(interpreter, &args, 0, "select")?;
+                let start = if index < 0 { count + index } else { index - 1 };
+                if start < 0 || index == 0 {
+                    return Err(bad_argument(interpreter, 0, "select", "index out of range"));
+                }
+                Ok(args.into_iter().skip(1 + start as usize).collect())
+            }),
+        ),
+        (
+            "tonumber",
+            Interpreter::native("tonumber", |interpreter, args| {
+                let value = check_any(interpreter, &args, 0, "tonumber")?;
+                let base = opt_integer(interpreter, &args, 1, "tonumber", 10)?;
+                if base == 10 {
+                    return Ok(vec![value.to_number().map_or(Value::Nil, Value::Number)]);
+                }
+                if !(2..=36).contains(&base) {
+                    return Err(bad_argument(interpreter, 1, "tonumber", "base out of range"));
+                }
+                let text = check_string(interpreter, &args, 0, "tonumber")?;
+                let text = String::from_utf8_lossy(&text).trim().to_lowercase();
+                let (negative, digits) = match text.strip_prefix('-') {
+                    Some(rest) => (true, rest),
+                    None => (false, text.as_str()),
+                };
+                let parsed = (!digits.is_empty())
+                    .then(|| digits.chars().try_fold(0f64, |total, c| c.to_digit(base as u32).map(|d| total * base as f64 + d as f64)))
+                    .flatten();
+                Ok(vec![parsed.map_or(Value::Nil, |n| Value::Number(if negative { -n } else { n }))])
+            }),
+        ),
+        (
+            "tostring",
+            Interpreter::native("tostring", |interpreter, args| {
+                let value = check_any(interpreter, &args, 0, "tostring")?;
+                Ok(vec![Value::string(value.display())])
+            }),
+        ),
+        (
+            "type",
+            Interpreter::native("type", |interpreter, args| {
+                let value = check_any(interpreter, &args, 0, "type")?;
+                Ok(vec![Value::string(value.type_name())])
+            }),
+        ),
+        (
+            "unpack",
+            Interpreter::native("unpack", |interpreter, args| {
+                let table = check_table(interpreter, &args, 0, "unpack")?;
+                let length = table.borrow().length() as i64;
+                let first = opt_integer(interpreter, &args, 1, "unpack", 1)?;
+                let last = opt_integer(interpreter, &args, 2, "unpack", length)?;
+                if first > last {
+                    return Ok(Vec::new());
+                }
+                if last.saturating_sub(first) >= MAX_RESULTS {
+                    return Err(interpreter.error("too many results to unpack"));
+                }
+                let table = table.borrow();
+                Ok((first..=last).map(|index| table.get(&Value::Number(index as f64))).collect())
+            }),
+        ),
+        (
+            "rawget",
+            Interpreter::native("rawget", |interpreter, args| {
+                let table = check_table(interpreter, &args, 0, "rawget")?;
+                let value = table.borrow().get(&arg(&args, 1));
+                Ok(vec![value])
+            }),
+        ),
+        (
+            "rawset",
+            Interpreter::native("rawset", |interpreter, args| {
+                let table = check_table(interpreter, &args, 0, "rawset")?;
+                table.borrow_mut().set(arg(&args, 1), arg(&args, 2)).map_err(|e| interpreter.raise(Value::string(e)))?;
+                Ok(vec![Value::Table(table)])
+            }),
+        ),
+        (
+            "rawequal",
+            Interpreter::native("rawequal", |interpreter, args| {
+                let left = check_any(interpreter, &args, 0, "rawequal")?;
+                let right = check_any(interpreter, &args, 1, "rawequal")?;
+                Ok(vec![Value::Boolean(left.raw_equal(&right))])
+            }),
+        ),
+    ];
+    for (name, function) in globals {
+        interpreter.set_global(name, function);
+    }
+}
+
+// Lua의 string.sub 등과 같은 1부터 시작하는 위치, 음수는 끝에서부터
+fn relative_position(position: i64, length: usize) -> i64 {
+    if position < 0 {
+        length as i64 + position + 1
+    } else {
+        position
+    }
+}
+
+fn open_string(interpreter: &mut Interpreter) -> TableRef {
+    let string = interpreter.new_table();
+    register(&string, "len", |interpreter, args| {
+        let text = check_string(interpreter, &args, 0, "len")?;
+        Ok(vec![number(text.len() as f64)])
+    });
+    register(&string, "sub", |interpreter, args| {
+        let text = check_string(interpreter, &args, 0, "sub")?;
+        let start = relative_position(check_integer(interpreter, &args, 1, "sub")?, text.len()).max(1);
+        let end = relative_position(opt_integer(interpreter, &args, 2, "sub", -1)?, text.len()).min(text.len() as i64);
+        if start > end {
+            return Ok(vec![Value::string("")]);
+        }
+        Ok(vec![Value::string(&text[start as usize - 1..end as usize])])
+    });
+    register(&string, "upper", |interpreter, args| {
+        let text = check_string(interpreter, &args, 0, "upper")?;
+        Ok(vec![Value::string(text.to_ascii_uppercase())])
+    });
+    register(&string, "lower", |interpreter, args| {
+        let text = check_string(interpreter, &args, 0, "lower")?;
+        Ok(vec![Value::string(text.to_ascii_lowercase())])
+    });
+    register(&string, "rep", |interpreter, args| {
+        let text = check_string(interpreter, &args, 0, "rep")?;
+        let count = check_integer(interpreter, &args, 1, "rep")?;
+        let count = count.max(0) as usize;
+        interpreter.check_string_len(text.len().checked_mul(count))?;
+        Ok(vec![Value::string(text.repeat(count))])
+    });
+    register(&string, "reverse", |interpreter, args| {
+        let text = check_string(interpreter, &args, 0, "reverse")?;
+        Ok(vec![Value::string(text.iter().rev().copied().collect::<Vec<u8>>())])
+    });
+    register(&string, "byte", |interpreter, args| {
+        let text = check_string(interpreter, &args, 0, "byte")?;
+        let start = relative_position(opt_integer(interpreter, &args, 1, "byte", 1)?, text.len()).max(1);
+        let end = relative_position(opt_integer(interpreter, &args, 2, "byte", start)?, text.len()).min(text.len() as i64);
+        Ok((start..=end).map(|position| number(text[position as usize - 1])).collect())
+    });
+    register(&string, "char", |interpreter, args| {
+        let mut bytes = Vec::with_capacity(args.len());
+        for index in 0..args.len() {
+            let code = check_integer(interpreter, &args, index, "char")?;
+            if !(0..=255).contains(&code) {
+                return Err(bad_argument(interpreter, index, "char", "invalid value"));
+            }
+            bytes.push(code as u8);
+        }
+        Ok(vec![Value::string(bytes)])
+    });
+    register(&string, "format", |interpreter, args| string_format(interpreter, &args).map(|text| vec![Value::string(text)]));
+    register(&string, "find", |interpreter, args| string_find(interpreter, &args, true));
+    register(&string, "match", |interpreter, args| string_find(interpreter, &args, false));
+    register(&string, "gmatch", |interpreter, args| {
+        let text = check_string(interpreter, &args, 0, "gmatch")?;
+        let pattern = check_string(interpreter, &args, 1, "gmatch")?;
+        let position = Cell::new(0);
+        let iterator = Interpreter::native("gmatch_iterator", move |interpreter, _| {
+            let mut state = MatchState::new(&text, &pattern);
+            let mut start = position.get();
+            while start <= text.len() {
+                state.reset();
+                if let Some(end) = state.do_match(start, 0).map_err(|e| interpreter.raise(Value::string(e)))? {
+                    position.set(if end == start { end + 1 } else { end });
+                    return state.captures(start, end, true).map_err(|e| interpreter.raise(Value::string(e)));
+                }
+                start += 1;
+            }
+            position.set(start);
+            Ok(vec![Value::Nil])
+        });
+        Ok(vec![iterator])
+    });
+    register(&string, "gsub", string_gsub);
+    string
+}
+
+fn string_find(interpreter: &mut Interpreter, args: &[Value], find: bool) -> Result<Vec<Value>, LuaError> {
+    let function = if find { "find" } else { "match" };
+    let text = check_string(interpreter, args, 0, function)?;
+    let pattern = check_string(interpreter, args, 1, function)?;
+    let init = (relative_position(opt_integer(interpreter, args, 2, function, 1)?, text.len()) - 1).max(0) as usize;
+    if init > text.len() {
+        return Ok(vec![Value::Nil]);
+    }
+    if find && (arg(args, 3).truthy() || !pattern.iter().any(|b| PATTERN_SPECIALS.contains(b))) {
+        let found = if pattern.is_empty() {
+            Some(init)
+        } else {
+            text[init..].windows(pattern.len()).position(|window| window == pattern.as_ref()).map(|offset| init + offset)
+        };
+        return Ok(match found {
+            Some(start) => vec![number(start as f64 + 1.0), number((start + pattern.len()) as f64)],
+            None => vec![Value::Nil],
+        });
+    }
+    let anchored = pattern.first() == Some(&b'^');
+    let pattern_start = anchored as usize;
+    let mut state = MatchState::new(&text, &pattern);
+    let mut start = init;
+    loop {
+        state.reset();
+        if let Some(end) = state.do_match(start, pattern_start).map_err(|e| interpreter.raise(Value::string(e)))? {
+            if !find {
+                return state.captures(start, end, true).map_err(|e| interpreter.raise(Value::string(e)));
+            }
+            let mut values = vec![number(start as f64 + 1.0), number(end as f64)];
+            values.extend(state.captures(start, end, false).map_err(|e| interpreter.raise(Value::string(e)))?);
+            return Ok(values);
+        }
+        start += 1;
+        if anchored || start > text.len() {
+            return Ok(vec![Value::Nil]);
+        }
+    }
+}
+
+fn string_gsub(interpreter: &mut Interpreter, args: Vec<Value>) -> Result<Vec<Value>, LuaError> {
+    let text = check_string(interpreter, &args, 0, "gsub")?;
+    let pattern = check_string(interpreter, &args, 1, "gsub")?;
+    let replacement = arg(&args, 2);
+    if !matches!(replacement, Value::Number(_) | Value::String(_) | Value::Table(_) | Value::Function(_)) {
+        return Err(bad_argument(interpreter, 2, "gsub", "string/function/table expected"));
+    }
+    let max_replacements = opt_integer(interpreter, &args, 3, "gsub", text.len() as i64 + 1)?;
+    let anchored = pattern.first() == Some(&b'^');
+    let pattern_start = anchored as usize;
+    let mut state = MatchState::new(&text, &pattern);
+    let mut result = Vec::with_capacity(text.len());
+    let mut position = 0;
+    let mut count = 0;
+    while count < max_replacements {
+        state.reset();
+        let end = state.do_match(position, pattern_start).map_err(|e| interpreter.raise(Value::string(e)))?;
+        if let Some(end) = end {
+            count += 1;
+            let whole = &text[position..end];
+            let value = match &replacement {
+                Value::Table(table) => {
+                    let key = state.captures(position, end, true).map_err(|e| interpreter.raise(Value::string(e)))?;
+                    let value = table.borrow().get(&key.into_iter().next().unwrap_or(Value::Nil));
+                    value
+                }
+                Value::Function(_) => {
+                    let captures = state.captures(position, end, true).map_err(|e| interpreter.raise(Value::string(e)))?;
+                    interpreter.call(&replacement, captures)?.into_iter().next().unwrap_or(Value::Nil)
+                }
+                _ => {
+                    let template = replacement.to_bytes().unwrap_or_else(|| Rc::from(&b""[..]));
+                    let mut index = 0;
+                    while index < template.len() {
+                        let byte = template[index];
+                        index += 1;
+                        if byte != b'%' || index == template.len() {
+                            result.push(byte);
+                            continue;
+                        }
+                        let next = template[index];
+                        index += 1;
+                        match next {
+                            b'0' => result.extend_from_slice(whole),
+                            b'1'..=b'9' => {
+                                let capture = state
+                                    .capture((next - b'1') as usize, position, end)
+                                    .map_err(|e| interpreter.raise(Value::string(e)))?;
+                                result.extend_from_slice(&capture.to_bytes().unwrap_or_else(|| Rc::from(&b""[..])));
+                            }
+                            other => result.push(other),
+                        }
+                    }
+                    Value::Boolean(true)
+                }
+            };
+            match value {
+                Value::Boolean(true) if !matches!(replacement, Value::Table(_) | Value::Function(_)) => {}
+                Value::Nil | Value::Boolean(false) => result.extend_from_slice(whole),
+                value => match value.to_bytes() {
+                    Some(bytes) => result.extend_from_slice(&bytes),
+                    None => return Err(interpreter.raise(Value::string(format!("invalid replacement value (a {})", value.type_name())))),
+                },
+            }
+        }
+        interpreter.check_string_len(Some(result.len()))?;
+        match end {
+            Some(end) if end > position => position = end,
+            _ if position < text.len() => {
+                result.push(text[position]);
+                position += 1;
+            }
+            _ => break,
+        }
+        if anchored {
+            break;
+        }
+    }
+    result.extend_from_slice(&text[position.min(text.len())..]);
+    Ok(vec![Value::string(result), number(count as f64)])
+}
+
+// lstrlib.c의 패턴 매칭을 옮김, 위치는 0부터 시작하는 바이트 인덱스
+struct MatchState<'a> {
+    source: &'a [u8],
+    pattern: &'a [u8],
+    level: usize,
+    captures: [(usize, isize); MAX_CAPTURES],
+    depth: usize,
+}
+
+impl<'a> MatchState<'a> {
+    fn new(source: &'a [u8], pattern: &'a [u8]) -> Self {
+        Self { source, pattern, level: 0, captures: [(0, 0); MAX_CAPTURES], depth: 0 }
+    }
+
+    fn reset(&mut self) {
+        self.level = 0;
+        self.depth = 0;
+    }
+
+    fn do_match(&mut self, mut s: usize, mut p: usize) -> Result<Option<usize>, String> {
+        self.depth += 1;
+        if self.depth > MAX_MATCH_DEPTH {
+            return Err("pattern too complex".to_string());
+        }
+        let pattern = self.pattern;
+        let result = loop {
+            if p == pattern.len() {
+                break Some(s);
+            }
+            match pattern[p] {
+                b'(' if pattern.get(p + 1) == Some(&b')') => break self.start_capture(s, p + 2, CAP_POSITION)?,
+                b'(' => break self.start_capture(s, p + 1, CAP_UNFINISHED)?,
+                b')' => break self.end_capture(s, p + 1)?,
+                b'$' if p + 1 == pattern.len() => break (s == self.source.len()).then_some(s),
+                b'%' if pattern.get(p + 1) == Some(&b'b') => match self.match_balance(s, p + 2)? {
+                    Some(end) => {
+                        s = end;
+                        p += 4;
+                    }
+                    None => break None,
+                },
+                b'%' if pattern.get(p + 1) == Some(&b'f') => {
+                    p += 2;
+                    if pattern.get(p) != Some(&b'[') {
+                        return Err("missing '[' after '%f' in pattern".to_string());
+                    }
+                    let end = self.class_end(p)?;
+                    let previous = if s == 0 { 0 } else { self.source[s - 1] };
+                    let current = self.source.get(s).copied().unwrap_or(0);
+                    if self.match_bracket_class(previous, p, end - 1) || !self.match_bracket_class(current, p, end - 1) {
+                        break None;
+                    }
+                    p = end;
+                }
+                b'%' if pattern.get(p + 1).is_some_and(u8::is_ascii_digit) => match self.match_capture(s, pattern[p + 1])? {
+                    Some(end) => {
+                        s = end;
+                        p += 2;
+                    }
+                    None => break None,
+                },
+                _ => {
+                    let end = self.class_end(p)?;
+                    let matched = s < self.source.len() && self.single_match(self.source[s], p, end);
+                    match pattern.get(end) {
+                        Some(b'?') => {
+                            if matched {
+                                if let Some(result) = self.do_match(s + 1, end + 1)? {
+                                    break Some(result);
+                                }
+                            }
+                            p = end + 1;
+                        }
+                        Some(b'*') => break self.max_expand(s, p, end)?,
+                        Some(b'+') => break if matched { self.max_expand(s + 1, p, end)? } else { None },
+                        Some(b'-') => break self.min_expand(s, p, end)?,
+                        _ => {
+                            if !matched {
+                                break None;
+                            }
+                            s += 1;
+                            p = end;
+                        }
+                    }
+                }
+            }
+        };
+        self.depth -= 1;
+        Ok(result)
+    }
+
+    fn class_end(&self, mut p: usize) -> Result<usize, String> {
+        let pattern = self.pattern;
+        let first = pattern[p];
+        p += 1;
+        if first == b'%' {
+            if p >= pattern.len() {
+                return Err("malformed pattern (ends with '%')".to_string());
+            }
+            return Ok(p + 1);
+        }
+        if first == b'[' {
+            if pattern.get(p) == Some(&b'^') {
+                p += 1;
+            }
+            // 첫 문자는 ]여도 집합에 들어감
+            loop {
+                let Some(&byte) = pattern.get(p) else {
+                    return Err("malformed pattern (missing ']')".to_string());
+                };
+                p += 1;
+                if byte == b'%' {
+                    p += 1;
+                }
+                if pattern.get(p) == Some(&b']') {
+                    return Ok(p + 1);
+                }
+                if p >= pattern.len() {
+                    return Err("malformed pattern (missing ']')".to_string());
+                }
+            }
+        }
+        Ok(p)
+    }
+
+    fn single_match(&self, byte: u8, p: usize, end: usize) -> bool {
+        match self.pattern[p] {
+            b'.' => true,
+            b'%' => match_class(byte, self.pattern[p + 1]),
+            b'[' => self.match_bracket_class(byte, p, end - 1),
+            other => other == byte,
+        }
+    }
+
+    // p는 [, class_end는 닫는 ]
+    fn match_bracket_class(&self, byte: u8, p: usize, class_end: usize) -> bool {
+        let pattern = self.pattern;
+        let mut p = p + 1;
+        let mut found = true;
+        if pattern[p] == b'^' {
+            found = false;
+            p += 1;
+        }
+        while p < class_end {
+            if pattern[p] == b'%' {
+                p += 1;
+                if match_class(byte, pattern[p]) {
+                    return found;
+                }
+                p += 1;
+            } else if pattern.get(p + 1) == Some(&b'-') && p + 2 < class_end {
+                if pattern[p] <= byte && byte <= pattern[p + 2] {
+                    return found;
+                }
+                p += 3;
+            } else {
+                if pattern[p] == byte {
+                    return found;
+                }
+                p += 1;
+            }
+        }
+        !found
+    }
+
+    fn max_expand(&mut self, s: usize, p: usize, end: usize) -> Result<Option<usize>, String> {
+        let mut count = 0;
+        while s + count < self.source.len() && self.single_match(self.source[s + count], p, end) {
+            count += 1;
+        }
+        loop {
+            if let Some(result) = self.do_match(s + count, end + 1)? {
+                return Ok(Some(result));
+            }
+            if count == 0 {
+                return Ok(None);
+            }
+            count -= 1;
+        }
+    }
+
+    fn min_expand(&mut self, mut s: usize, p: usize, end: usize) -> Result<Option<usize>, String> {
+        loop {
+            if let Some(result) = self.do_match(s, end + 1)? {
+                return Ok(Some(result));
+            }
+            if s < self.source.len() && self.single_match(self.source[s], p, end) {
+                s += 1;
+            } else {
+                return Ok(None);
+            }
+        }
+    }
+
+    fn start_capture(&mut self, s: usize, p: usize, what: isize) -> Result<Option<usize>, String> {
+        if self.level >= MAX_CAPTURES {
+            return Err("too many captures".to_string());
+        }
+        self.captures[self.level] = (s, what);
+        self.level += 1;
+        let result = self.do_match(s, p)?;
+        if result.is_none() {
+            self.level -= 1;
+        }
+        Ok(result)
+    }
+
+    fn end_capture(&mut self, s: usize, p: usize) -> Result<Option<usize>, String> {
+        let index = (0..self.level)
+            .rev()
+            .find(|&index| self.captures[index].1 == CAP_UNFINISHED)
+            .ok_or_else(|| "invalid pattern capture".to_string())?;
+        self.captures[index].1 = (s - self.captures[index].0) as isize;
+        let result = self.do_match(s, p)?;
+        if result.is_none() {
+            self.captures[index].1 = CAP_UNFINISHED;
+        }
+        Ok(result)
+    }
+
+    fn match_balance(&self, s: usize, p: usize) -> Result<Option<usize>, String> {
+        if p + 1 >= self.pattern.len() {
+            return Err("unbalanced pattern".to_string());
+        }
+        let (open, close) = (self.pattern[p], self.pattern[p + 1]);
+        if self.source.get(s) != Some(&open) {
+            return Ok(None);
+        }
+        let mut depth = 1;
+        for index in s + 1..self.source.len() {
+            let byte = self.source[index];
+            if byte == close {
+                depth -= 1;
+                if depth == 0 {
+                    return Ok(Some(index + 1));
+                }
+            } else if byte == open {
+                depth += 1;
+            }
+        }
+        Ok(None)
+    }
+
+    fn match_capture(&self, s: usize, digit: u8) -> Result<Option<usize>, String> {
+        let index = (digit as usize).wrapping_sub(b'1' as usize);
+        if index >= self.level || self.captures[index].1 == CAP_UNFINISHED {
+            return Err("invalid capture index".to_string());
+        }
+        let (start, length) = self.captures[index];
+        let length = length.max(0) as usize;
+        let captured = &self.source[start..start + length];
+        Ok((self.source.len() - s >= length && &self.source[s..s + length] == captured).then_some(s + length))
+    }
+
+    fn capture(&self, index: usize, s: usize, e: usize) -> Result<Value, String> {
+        if index >= self.level {
+            if index == 0 {
+                return Ok(Value::string(&self.source[s..e]));
+            }
+            return Err("invalid capture index".to_string());
+        }
+        let (start, length) = self.captures[index];
+        match length {
+            CAP_UNFINISHED => Err("unfinished capture".to_string()),
+            CAP_POSITION => Ok(number((start + 1) as f64)),
+            length => Ok(Value::string(&self.source[start..start + length as usize])),
+        }
+    }
+
+    // 캡처가 없으면 whole일 때만 매치 전체를 돌려줌
+    fn captures(&self, s: usize, e: usize, whole: bool) -> Result<Vec<Value>, String> {
+        let count = if self.level == 0 && whole { 1 } else { self.level };
+        (0..count).map(|index| self.capture(index, s, e)).collect()
+    }
+}
+
+fn match_class(byte: u8, class: u8) -> bool {
+    let matched = match class.to_ascii_lowercase() {
+        b'a' => byte.is_ascii_alphabetic(),
+        b'c' => byte.is_ascii_control(),
+        b'd' => byte.is_ascii_digit(),
+        b'l' => byte.is_ascii_lowercase(),
+        b'p' => byte.is_ascii_punctuation(),
+        b's' => matches!(byte, b' ' | b'\t' | b'\n' | b'\r' | 0x0b | 0x0c),
+        b'u' => byte.is_ascii_uppercase(),
+        b'w' => byte.is_ascii_alphanumeric(),
+        b'x' => byte.is_ascii_hexdigit(),
+        b'z' => byte == 0,
+        _ => return class == byte,
+    };
+    if class.is_ascii_uppercase() {
+        !matched
+    } else {
+        matched
+    }
+}
+
+// C printf 형식의 string.format, 변환마다 플래그/너비/정밀도를 읽어 직접 채움
+fn string_format(interpreter: &Interpreter, args: &[Value]) -> Result<Vec<u8>, LuaError> {
+    let format = check_string(interpreter, args, 0, "format")?;
+    let mut output = Vec::with_capacity(format.len());
+    let mut index = 0;
+    let mut next_arg = 1;
+    while index < format.len() {
+        let byte = format[index];
+        index += 1;
+        if byte != b'%' {
+            output.push(byte);
+            continue;
+        }
+        if format.get(index) == Some(&b'%') {
+            output.push(b'%');
+            index += 1;
+            continue;
+        }
+        let flags_start = index;
+        while index < format.len() && b"-+ #0".contains(&format[index]) {
+            index += 1;
+        }
+        let flags = &format[flags_start..index];
+        let width = read_digits(&format, &mut index);
+        let precision = if format.get(index) == Some(&b'.') {
+            index += 1;
+            Some(read_digits(&format, &mut index).unwrap_or(0))
+        } else {
+            None
+        };
+        if index - flags_start > 6 {
+            return Err(interpreter.raise(Value::string("invalid format (repeated flags)")));
+        }
+        let Some(&conversion) = format.get(index) else {
+            return Err(interpreter.raise(Value::string("invalid option '%' to 'format'")));
+        };
+        index += 1;
+        let position = next_arg;
+        next_arg += 1;
+        if position >= args.len() {
+            return Err(bad_argument(interpreter, position, "format", "no value"));
+        }
+        let left = flags.contains(&b'-');
+        let zero = flags.contains(&b'0') && !left;
+        let sign = if flags.contains(&b'+') {
+            "+"
+        } else if flags.contains(&b' ') {
+            " "
+        } else {
+            ""
+        };
+        let alternate = flags.contains(&b'#');
+        let (prefix, body, numeric): (String, Vec<u8>, bool) = match conversion {
+            b'c' => (String::new(), vec![check_integer(interpreter, args, position, "format")? as u8], false),
+            b'd' | b'i' => {
+                let value = check_integer(interpreter, args, position, "format")?;
+                let mut digits = value.unsigned_abs().to_string();
+                if let Some(precision) = precision {
+                    digits = format!("{:0>width$}", digits, width = precision);
+                }
+                let prefix = if value < 0 { "-".to_string() } else { sign.to_string() };
+                (prefix, digits.into_bytes(), precision.is_none())
+            }
+            b'o' | b'u' | b'x' | b'X' => {
+                let value = check_number(interpreter, args, position, "format")? as i64 as u64;
+                let mut digits = match conversion {
+                    b'o' => format!("{:o}", value),
+                    b'x' => format!("{:x}", value),
+                    b'X' => format!("{:X}", value),
+                    _ => value.to_string(),
+                };
+                if let Some(precision) = precision {
+                    digits = format!("{:0>width$}", digits, width = precision);
+                }
+                let prefix = match conversion {
+                    b'x' if alternate && value != 0 => "0x",
+                    b'X' if alternate && value != 0 => "0X",
+                    b'o' if alternate && !digits.starts_with('0') => "0",
+                    _ => "",
+                };
+                (prefix.to_string(), digits.into_bytes(), precision.is_none())
+            }
+            b'e' | b'E' | b'f' | b'g' | b'G' => {
+                let value = check_number(interpreter, args, position, "format")?;
+                let precision = precision.unwrap_or(6);
+                let digits = match conversion {
+                    b'e' | b'E' => format_e(value.abs(), precision),
+                    b'f' if value.is_finite() => format!("{:.*}", precision, value.abs()),
+                    b'f' => format_non_finite(value.abs()),
+                    _ => format_g(value.abs(), precision, alternate),
+                };
+                let digits = if conversion.is_ascii_uppercase() { digits.to_uppercase() } else { digits };
+                let prefix = if value.is_sign_negative() && !value.is_nan() { "-".to_string() } else { sign.to_string() };
+                (prefix, digits.into_bytes(), value.is_finite())
+            }
+            b'q' => {
+                let text = check_string(interpreter, args, position, "format")?;
+                let mut quoted = vec![b'"'];
+                for &byte in text.iter() {
+                    match byte {
+                        b'"' | b'\\' | b'\n' => quoted.extend_from_slice(&[b'\\', byte]),
+                        b'\r' => quoted.extend_from_slice(b"\\r"),
+                        0 => quoted.extend_from_slice(b"\\000"),
+                        byte => quoted.push(byte),
+                    }
+                }
+                quoted.push(b'"');
+                interpreter.check_string_len(output.len().checked_add(quoted.len()))?;
+                output.extend_from_slice(&quoted);
+                continue;
+            }
+            b's' => {
+                let text = check_string(interpreter, args, position, "format")?;
+                let text = match precision {
+                    Some(precision) => &text[..precision.min(text.len())],
+                    None => &text[..],
+                };
+                (String::new(), text.to_vec(), false)
+            }
+            other => {
+                return Err(interpreter.raise(Value::string(format!("invalid option '%{}' to 'format'", other as char))));
+            }
+        };
+        let length = prefix.len() + body.len();
+        let padding = width.unwrap_or(0).saturating_sub(length);
+        interpreter.check_string_len(output.len().checked_add(length + padding))?;
+        if left {
+            output.extend_from_slice(prefix.as_bytes());
+            output.extend_from_slice(&body);
+            output.extend(std::iter::repeat(b' ').take(padding));
+        } else if zero && numeric {
+            output.extend_from_slice(prefix.as_bytes());
+            output.extend(std::iter::repeat(b'0').take(padding));
+            output.extend_from_slice(&body);
+        } else {
+            output.extend(std::iter::repeat(b' ').take(padding));
+            output.extend_from_slice(prefix.as_bytes());
+            output.extend_from_slice(&body);
+        }
+    }
+    Ok(output)
+}
+
+fn read_digits(format: &[u8], index: &mut usize) -> Option<usize> {
+    let start = *index;
+    while *index < format.len() && format[*index].is_ascii_digit() && *index - start < 2 {
+        *index += 1;
+    }
+    std::str::from_utf8(&format[start..*index]).ok()?.parse().ok()
+}
+
+fn open_table(interpreter: &mut Interpreter) -> TableRef {
+    let table = interpreter.new_table();
+    register(&table, "insert", |interpreter, args| {
+        let target = check_table(interpreter, &args, 0, "insert")?;
+        let length = target.borrow().length();
+        let (position, value) = match args.len() {
+            2 => (length + 1, arg(&args, 1)),
+            3 => {
+                let position = check_integer(interpreter, &args, 1, "insert")?;
+                (position.max(0) as usize, arg(&args, 2))
+            }
+            _ => return Err(interpreter.raise(Value::string("wrong number of arguments to 'insert'"))),
+        };
+        let mut target = target.borrow_mut();
+        // 뒤에서부터 한 칸씩 밀어 자리를 만듦
+        let mut index = length + 1;
+        while index > position && index > 1 {
+            let previous = target.get(&number((index - 1) as f64));
+            let _ = target.set(number(index as f64), previous);
+            index -= 1;
+        }
+        target.set(number(position as f64), value).map_err(|e| interpreter.raise(Value::string(e)))?;
+        Ok(Vec::new())
+    });
+    register(&table, "remove", |interpreter, args| {
+        let target = check_table(interpreter, &args, 0, "remove")?;
+        let length = target.borrow().length();
+        if length == 0 {
+            return Ok(Vec::new());
+        }
+        let position = opt_integer(interpreter, &args, 1, "remove", length as i64)?.max(1) as usize;
+        let mut target = target.borrow_mut();
+        let removed = target.get(&number(position as f64));
+        for index in position..length {
+            let next = target.get(&number((index + 1) as f64));
+            let _ = target.set(number(index as f64), next);
+        }
+        let _ = target.set(number(length as f64), Value::Nil);
+        Ok(vec![removed])
+    });
+    register(&table, "concat", |interpreter, args| {
+        let target = check_table(interpreter, &args, 0, "concat")?;
+        let separator = match args.get(1) {
+            None | Some(Value::Nil) => Rc::from(&b""[..]),
+            Some(_) => check_string(interpreter, &args, 1, "concat")?,
+        };
+        let length = target.borrow().length() as i64;
+        let first = opt_integer(interpreter, &args, 2, "concat", 1)?;
+        let last = opt_integer(interpreter, &args, 3, "concat", length)?;
+        let target = target.borrow();
+        let mut output = Vec::new();
+        for index in first..=last {
+            let Some(bytes) = target.get(&number(index as f64)).to_bytes() else {
+                return Err(interpreter.raise(Value::string(format!("invalid value (at index {}) in table for 'concat'", index))));
+            };
+            let separator = if index > first { &separator[..] } else { &[] };
+            interpreter.check_string_len(output.len().checked_add(separator.len() + bytes.len()))?;
+            output.extend_from_slice(separator);
+            output.extend_from_slice(&bytes);
+        }
+        Ok(vec![Value::string(output)])
+    });
+    register(&table, "sort", |interpreter, args| {
+        let target = check_table(interpreter, &args, 0, "sort")?;
+        let comparator = arg(&args, 1);
+        if !matches!(comparator, Value::Nil | Value::Function(_)) {
+            return Err(type_error(interpreter, &args, 1, "sort", "function"));
+        }
+        let length = target.borrow().length();
+        let values: Vec<Value> = (1..=length).map(|index| target.borrow().get(&number(index as f64))).collect();
+        let sorted = merge_sort(interpreter, values, &comparator)?;
+        let mut target = target.borrow_mut();
+        for (index, value) in sorted.into_iter().enumerate() {
+            let _ = target.set(number((index + 1) as f64), value);
+        }
+        Ok(Vec::new())
+    });
+    register(&table, "getn", |interpreter, args| {
+        let target = check_table(interpreter, &args, 0, "getn")?;
+        let length = target.borrow().length();
+        Ok(vec![number(length as f64)])
+    });
+    register(&table, "maxn", |interpreter, args| {
+        let target = check_table(interpreter, &args, 0, "maxn")?;
+        let target = target.borrow();
+        let mut max = 0f64;
+        let mut key = Value::Nil;
+        while let Ok(Some((next, _))) = target.next(&key) {
+            if let Value::Number(index) = next {
+                max = max.max(index);
+            }
+            key = next;
+        }
+        Ok(vec![number(max)])
+    });
+    table
+}
+
+// 비교 함수가 에러를 낼 수 있어 표준 정렬 대신 직접 병합 정렬함
+fn merge_sort(interpreter: &mut Interpreter, mut values: Vec<Value>, comparator: &Value) -> Result<Vec<Value>, LuaError> {
+    if values.len() <= 1 {
+        return Ok(values);
+    }
+    let right = values.split_off(values.len() / 2);
+    let left = merge_sort(interpreter, values, comparator)?;
+    let right = merge_sort(interpreter, right, comparator)?;
+    let mut merged = Vec::with_capacity(left.len() + right.len());
+    let mut left = left.into_iter().peekable();
+    let mut right = right.into_iter().peekable();
+    while let (Some(a), Some(b)) = (left.peek(), right.peek()) {
+        let right_first = match comparator {
+            Value::Nil => interpreter.less_than(b, a)?,
+            comparator => {
+                let result = interpreter.call(comparator, vec![b.clone(), a.clone()])?;
+                result.first().is_some_and(Value::truthy)
+            }
+        };
+        merged.extend(if right_first { right.next() } else { left.next() });
+    }
+    merged.extend(left);
+    merged.extend(right);
+    Ok(merged)
+}
+
+fn seed_random(interpreter: &mut Interpreter, seed: i32) {
+    interpreter.random_state = ((seed as u32 as u64) << 16) | 0x330E;
+}
+
+fn next_random(interpreter: &mut Interpreter) -> u64 {
+    interpreter.random_state = (interpreter.random_state.wrapping_mul(LRAND48_MULTIPLIER).wrapping_add(LRAND48_INCREMENT)) & ((1 << 48) - 1);
+    interpreter.random_state >> 17
+}
+
+fn open_math(interpreter: &mut Interpreter) -> TableRef {
+    let math = interpreter.new_table();
+    let unary: [(&'static str, MathFunction); 17] = [
+        ("abs", f64::abs),
+        ("ceil", f64::ceil),
+        ("floor", f64::floor),
+        ("sqrt", f64::sqrt),
+        ("exp", f64::exp),
+        ("log", f64::ln),
+        ("log10", f64::log10),
+        ("sin", f64::sin),
+        ("cos", f64::cos),
+        ("tan", f64::tan),
+        ("asin", f64::asin),
+        ("acos", f64::acos),
+        ("atan", f64::atan),
+        ("sinh", f64::sinh),
+        ("cosh", f64::cosh),
+        ("tanh", f64::tanh),
+        ("deg", f64::to_degrees),
+    ];
+    for (name, function) in unary {
+        register(&math, name, move |interpreter, args| Ok(vec![number(function(check_number(interpreter, &args, 0, name)?))]));
+    }
+    register(&math, "rad", |interpreter, args| Ok(vec![number(check_number(interpreter, &args, 0, "rad")?.to_radians())]));
+    register(&math, "pow", |interpreter, args| {
+        Ok(vec![number(check_number(interpreter, &args, 0, "pow")?.powf(check_number(interpreter, &args, 1, "pow")?))])
+    });
+    register(&math, "atan2", |interpreter, args| {
+        Ok(vec![number(check_number(interpreter, &args, 0, "atan2")?.atan2(check_number(interpreter, &args, 1, "atan2")?))])
+    });
+    register(&math, "fmod", |interpreter, args| {
+        Ok(vec![number(check_number(interpreter, &args, 0, "fmod")? % check_number(interpreter, &args, 1, "fmod")?)])
+    });
+    register(&math, "modf", |interpreter, args| {
+        let value = check_number(interpreter, &args, 0, "modf")?;
+        Ok(vec![number(value.trunc()), number(value.fract())])
+    });
+    register(&math, "ldexp", |interpreter, args| {
+        let value = check_number(interpreter, &args, 0, "ldexp")?;
+        let exponent = check_integer(interpreter, &args, 1, "ldexp")?;
+        Ok(vec![number(value * 2f64.powi(exponent as i32))])
+    });
+    register(&math, "frexp", |interpreter, args| {
+        let value = check_number(interpreter, &args, 0, "frexp")?;
+        if value == 0.0 || !value.is_finite() {
+            return Ok(vec![number(value), number(0)]);
+        }
+        let exponent = value.abs().log2().floor() as i32 + 1;
+        Ok(vec![number(value / 2f64.powi(exponent)), number(exponent)])
+    });
+    register(&math, "max", |interpreter, args| {
+        let mut max = check_number(interpreter, &args, 0, "max")?;
+        for index in 1..args.len() {
+            max = max.max(check_number(interpreter, &args, index, "max")?);
+        }
+        Ok(vec![number(max)])
+    });
+    register(&math, "min", |interpreter, args| {
+        let mut min = check_number(interpreter, &args, 0, "min")?;
+        for index in 1..args.len() {
+            min = min.min(check_number(interpreter, &args, index, "min")?);
+        }
+        Ok(vec![number(min)])
+    });
+    register(&math, "random", |interpreter, args| {
+        let random = (next_random(interpreter) % LRAND48_MAX) as f64 / LRAND48_MAX as f64;
+        match args.len() {
+            0 => Ok(vec![number(random)]),
+            1 => {
+                let upper = check_integer(interpreter, &args, 0, "random")?;
+                if upper < 1 {
+                    return Err(bad_argument(interpreter, 0, "random", "interval is empty"));
+                }
+                Ok(vec![number((random * upper as f64).floor() + 1.0)])
+            }
+            2 => {
+                let lower = check_integer(interpreter, &args, 0, "random")?;
+                let upper = check_integer(interpreter, &args, 1, "random")?;
+                if lower > upper {
+                    return Err(bad_argument(interpreter, 1, "random", "interval is empty"));
+                }
+                Ok(vec![number((random * (upper - lower + 1) as f64).floor() + lower as f64)])
+            }
+            _ => Err(interpreter.raise(Value::string("wrong number of arguments"))),
+        }
+    });
+    register(&math, "randomseed", |interpreter, args| {
+        let seed = check_integer(interpreter, &args, 0, "randomseed")?;
+        seed_random(interpreter, seed as i32);
+        Ok(Vec::new())
+    });
+    math.borrow_mut().set_str("pi", number(std::f64::consts::PI));
+    math.borrow_mut().set_str("huge", number(f64::INFINITY));
+    math
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::lua::Interpreter;
+
+    fn run(source: &str) -> String {
+        let mut interpreter = Interpreter::new("user_script");
+        let function = interpreter.load(source.as_bytes()).unwrap_or_else(|e| panic!("{}", e));
+        let values = match interpreter.call(&function, Vec::new()) {
+            Ok(values) => values,
+            Err(e) => return format!("error: {}", String::from_utf8_lossy(&e.value.display())),
+        };
+        let parts: Vec<String> = values.iter().map(|value| String::from_utf8_lossy(&value.display()).into_owned()).collect();
+        parts.join(" ")
+    }
+
+    #[test]
+    fn string_functions() {
+        assert_eq!(run("return string.sub('hello', 2, -2), ('abc'):upper(), string.rep('ab', 3), #string.char(65, 66)"), "ell ABC ababab 2");
+        assert_eq!(run("return string.byte('A'), string.reverse('abc'), string.len('')"), "65 cba 0");
+    }
+
+    #[test]
+    fn patterns_follow_lua() {
+        assert_eq!(run("return string.find('hello world', 'o w')"), "5 7");
+        assert_eq!(run("return string.find('a.b', '.', 1, true)"), "2 2");
+        assert_eq!(run("return string.match('key:123', '(%a+):(%d+)')"), "key 123");
+        assert_eq!(run("return string.match('  trim  ', '^%s*(.-)%s*$'), string.match('f(a(b)c)', '%b()')"), "trim (a(b)c)");
+        assert_eq!(run("return string.gsub('hello world', 'o', '0'), string.gsub('abc', '%w', '%0%0')"), "hell0 w0rld aabbcc 3");
+        assert_eq!(run("local t = {} for w in string.gmatch('one two three', '%a+') do t[#t + 1] = w end return table.concat(t, ',')"), "one,two,three");
+        assert_eq!(run("return string.gsub('$name is $age', '%$(%w+)', {name = 'bob', age = 3})"), "bob is 3 2");
+        assert_eq!(run("return string.find('THE (quick) fox', '%((%a+)%)')"), "5 11 quick");
+        assert_eq!(run("return string.match('x', '[')"), "error: malformed pattern (missing ']')");
+    }
+
+    #[test]
+    fn format_matches_printf() {
+        assert_eq!(run("return string.format('%5d|%-5s|%05.1f|%x|%q', 42, 'ab', 3.14159, 255, 'a\"b')"), "   42|ab   |003.1|ff|\"a\\\"b\"");
+        assert_eq!(run("return string.format('%g %g %e %s', 0.1, 1e20, 1234.5, 12)"), "0.1 1e+20 1.234500e+03 12");
+        assert_eq!(run("return string.format('%d')"), "error: bad argument #2 to 'format' (no value)");
+    }
+
+    #[test]
+    fn table_functions() {
+        assert_eq!(run("local t = {3, 1, 2} table.sort(t) return table.concat(t, ' ')"), "1 2 3");
+        assert_eq!(run("local t = {3, 1, 2} table.sort(t, function(a, b) return a > b end) return table.concat(t, ' ')"), "3 2 1");
+        assert_eq!(run("local t = {1, 2} table.insert(t, 3) table.insert(t, 1, 0) return table.concat(t, ','), table.remove(t), #t"), "0,1,2,3 3 3");
+        assert_eq!(run("return unpack({1, 2, 3})"), "1 2 3");
+        assert_eq!(run("return table.sort({1, 'x'})"), "error: user_script:1: attempt to compare string with number");
+    }
+
+    #[test]
+    fn base_functions() {
+        assert_eq!(run("return select(2, 'a', 'b', 'c')"), "b c");
+        assert_eq!(run("return pcall(function() error('boom') end)"), "false user_script:1: boom");
+        assert_eq!(run("local ok, e = pcall(error, {code = 1}) return ok, e.code"), "false 1");
+        assert_eq!(run("return tonumber('0x10'), tonumber('z', 36), tonumber('abc'), tostring(nil), type({})"), "16 35 nil nil table");
+        assert_eq!(run("local ok, e = pcall(function() local x = nil return x.y end) return ok, e"), "false user_script:1: attempt to index a nil value");
+    }
+
+    #[test]
+    fn math_random_is_deterministic() {
+        assert_eq!(run("return math.random(100), math.random(100)"), run("return math.random(100), math.random(100)"));
+        assert_eq!(run("return math.floor(3.7), math.max(1, 5, 3), math.huge"), "3 5 inf");
+    }
+}
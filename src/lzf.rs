@@ -0,0 +1,160 @@
+use std::io;
+
+// liblzf 형식: 제어 바이트가 32 미만이면 (값 + 1)바이트 리터럴,
+// 아니면 상위 3비트가 길이(7이면 다음 바이트를 더함), 하위 5비트와 다음 바이트가 13비트 역참조 거리
+const MAX_LITERAL: usize = 32;
+const MAX_OFFSET: usize = 1 << 13;
+const MAX_REF_LEN: usize = 7 + 255 + 2;
+const HASH_LOG: u32 = 14;
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+pub fn decompress(input: &[u8], expected_len: usize) -> io::Result<Vec<u8>> {
+    // 입력 한 바이트가 만들 수 있는 결과는 MAX_REF_LEN바이트를 넘지 않으므로 그보다 긴 길이는 잘못된 데이터임
+    if expected_len > input.len().saturating_mul(MAX_REF_LEN) {
+        return Err(invalid_data("LZF decompressed length exceeds the input"));
+    }
+    let mut out = Vec::with_capacity(expected_len);
+    let mut ip = 0;
+    while ip < input.len() {
+        let ctrl = input[ip] as usize;
+        ip += 1;
+        if ctrl < MAX_LITERAL {
+            let literal = input
+                .get(ip..ip + ctrl + 1)
+                .ok_or_else(|| invalid_data("LZF literal runs past the input"))?;
+            out.extend_from_slice(literal);
+            ip += ctrl + 1;
+            continue;
+        }
+
+        let mut len = ctrl >> 5;
+        if len == 7 {
+            len += *input.get(ip).ok_or_else(|| invalid_data("LZF reference is truncated"))? as usize;
+            ip += 1;
+        }
+        let low = *input.get(ip).ok_or_else(|| invalid_data("LZF reference is truncated"))? as usize;
+        ip += 1;
+        let offset = ((ctrl & 0x1F) << 8) + low + 1;
+        if offset > out.len() {
+            return Err(invalid_data("LZF reference points before the output"));
+        }
+        // 겹치는 역참조가 있으므로 한 바이트씩 복사함
+        let start = out.len() - offset;
+        for i in 0..len + 2 {
+            out.push(out[start + i]);
+        }
+    }
+    if out.len() != expected_len {
+        return Err(invalid_data("LZF decompressed length mismatch"));
+    }
+    Ok(out)
+}
+
+// 압축 결과가 max_len보다 길어지면 None
+pub fn compress(input: &[u8], max_len: usize) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(max_len);
+    // 3바이트 해시 -> 마지막으로 본 위치 + 1
+    let mut table = vec![0usize; 1 << HASH_LOG];
+    let mut ip = 0;
+    let mut literal_start = 0;
+    while ip + 2 < input.len() {
+        let hash = hash3(&input[ip..ip + 3]);
+        let candidate = table[hash];
+        table[hash] = ip + 1;
+        if candidate > 0 {
+            let reference = candidate - 1;
+            let offset = ip - reference - 1;
+            if offset < MAX_OFFSET && input[reference..reference + 3] == input[ip..ip + 3] {
+                let max_match = MAX_REF_LEN.min(input.len() - ip);
+                let mut len = 3;
+                while len < max_match && input[reference + len] == input[ip + len] {
+                    len += 1;
+                }
+                push_literals(&mut out, &input[literal_start..ip]);
+                let encoded_len = len - 2;
+                if encoded_len < 7 {
+                    out.push(((encoded_len << 5) | (offset >> 8)) as u8);
+                } else {
+                    out.push(((7 << 5) | (offset >> 8)) as u8);
+                    out.push((encoded_len - 7) as u8);
+                }
+                out.push(offset as u8);
+                ip += len;
+                literal_start = ip;
+                if out.len() > max_len {
+                    return None;
+                }
+                continue;
+            }
+        }
+        ip += 1;
+    }
+    push_literals(&mut out, &input[literal_start..]);
+    (out.len() <= max_len).then_some(out)
+}
+
+fn push_literals(out: &mut Vec<u8>, literals: &[u8]) {
+    for chunk in literals.chunks(MAX_LITERAL) {
+        out.push((chunk.len() - 1) as u8);
+        out.extend_from_slice(chunk);
+    }
+}
+
+fn hash3(bytes: &[u8]) -> usize {
+    let value = ((bytes[0] as u32) << 16) | ((bytes[1] as u32) << 8) | bytes[2] as u32;
+    (value.wrapping_mul(2654435761) >> (32 - HASH_LOG)) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compresses_a_run_into_one_overlapping_reference() {
+        // 리터럴 'a' 하나, 그 뒤 거리 1에서 9바이트를 복사하는 긴 역참조
+        let compressed = [0x00, b'a', 0xE0, 0x00, 0x00];
+        assert_eq!(compress(b"aaaaaaaaaa", 10).unwrap(), compressed);
+        assert_eq!(decompress(&compressed, 10).unwrap(), b"aaaaaaaaaa");
+    }
+
+    #[test]
+    fn round_trips_literals_and_references() {
+        let mut inputs: Vec<Vec<u8>> = vec![
+            b"".to_vec(),
+            b"ab".to_vec(),
+            b"hello hello hello hello".to_vec(),
+            vec![b'z'; 10_000],
+        ];
+        inputs.push((0..2000u32).flat_map(|i| format!("key:{}|", i % 300).into_bytes()).collect());
+        // 같은 패턴이 최대 거리(8KB)보다 멀리 떨어져 있는 입력
+        let mut far = (0..9000u32).map(|i| (i.wrapping_mul(2654435761) >> 24) as u8).collect::<Vec<u8>>();
+        far.extend_from_within(..100);
+        inputs.push(far);
+
+        for input in inputs {
+            let compressed = compress(&input, input.len() + input.len() / 16 + 64).unwrap_or_else(|| panic!("{} bytes did not fit", input.len()));
+            assert_eq!(decompress(&compressed, input.len()).unwrap(), input);
+        }
+    }
+
+    #[test]
+    fn gives_up_when_the_output_would_not_fit() {
+        let input: Vec<u8> = (0..=255u8).collect();
+        assert!(compress(&input, input.len() - 1).is_none());
+        assert!(compress(&vec![b'a'; 1000], 20).is_some());
+        // 긴 출력에서도 한도는 입력 전체에 대한 것이어야 함
+        let repeated: Vec<u8> = (0..1000u32).flat_map(|i| format!("key:{}|", i % 50).into_bytes()).collect();
+        assert!(compress(&repeated, repeated.len() / 4).is_some());
+    }
+
+    #[test]
+    fn rejects_corrupt_input() {
+        assert!(decompress(&[0x05, b'a'], 6).is_err());
+        assert!(decompress(&[0x00, b'a', 0xE0], 10).is_err());
+        assert!(decompress(&[0x00, b'a', 0x20, 0x01], 4).is_err());
+        assert!(decompress(&[0x00, b'a'], 2).is_err());
+    }
+}
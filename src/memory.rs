@@ -0,0 +1,115 @@
+use crate::protocol_constants::CRLF;
+use crate::resp::RespValue;
+use crate::config_handler::Db;
+
+// MEMORY USAGE의 기본 SAMPLES, Redis와 같음
+pub const DEFAULT_USAGE_SAMPLES: usize = 5;
+// 이보다 적게 쓰는 인스턴스는 MEMORY DOCTOR가 진단하지 않음
+const DOCTOR_MIN_USED_MEMORY: u64 = 5 * 1024 * 1024;
+// 지금 사용량보다 피크가 이 비율 이상 크면 알려줌
+const DOCTOR_PEAK_RATIO: f64 = 1.5;
+// maxmemory의 이 비율 이상을 쓰고 있으면 알려줌
+const DOCTOR_MAXMEMORY_RATIO: f64 = 0.9;
+
+// 키스페이스가 유지하는 메모리 사용량, INFO memory와 MEMORY STATS/DOCTOR가 함께 씀
+pub struct MemoryReport {
+    pub used_memory: u64,
+    pub peak: u64,
+    pub keys: u64,
+    // 값만의 크기, 나머지(키 이름과 엔트리 헤더)는 overhead
+    pub dataset: u64,
+    pub maxmemory: Option<u64>,
+}
+
+impl MemoryReport {
+    // peak는 지금 사용량으로 시작함, 지난 피크는 Stats가 기억하므로 부르는 쪽에서 채움
+    pub fn collect(db: &Db, maxmemory: Option<u64>) -> Self {
+        let used_memory = db.used_memory() as u64;
+        Self { used_memory, peak: used_memory, keys: db.len() as u64, dataset: db.dataset() as u64, maxmemory }
+    }
+
+    pub fn overhead(&self) -> u64 {
+        self.used_memory - self.dataset
+    }
+
+    fn percentage(part: u64, total: u64) -> f64 {
+        if total == 0 {
+            0.0
+        } else {
+            part as f64 * 100.0 / total as f64
+        }
+    }
+
+    pub fn get_memory_info(&self) -> String {
+        let mut info = format!("# Memory{}", CRLF);
+        info.push_str(&format!("used_memory:{}{}", self.used_memory, CRLF));
+        info.push_str(&format!("used_memory_human:{}{}", human_bytes(self.used_memory), CRLF));
+        info.push_str(&format!("used_memory_peak:{}{}", self.peak, CRLF));
+        info.push_str(&format!("used_memory_peak_human:{}{}", human_bytes(self.peak), CRLF));
+        info.push_str(&format!("used_memory_peak_perc:{:.2}%{}", Self::percentage(self.used_memory, self.peak), CRLF));
+        info.push_str(&format!("used_memory_overhead:{}{}", self.overhead(), CRLF));
+        info.push_str(&format!("used_memory_dataset:{}{}", self.dataset, CRLF));
+        info.push_str(&format!("used_memory_dataset_perc:{:.2}%{}", Self::percentage(self.dataset, self.used_memory), CRLF));
+        info.push_str(&format!("maxmemory:{}{}", self.maxmemory.unwrap_or(0), CRLF));
+        info.push_str(&format!("maxmemory_human:{}{}", human_bytes(self.maxmemory.unwrap_or(0)), CRLF));
+        info
+    }
+
+    // MEMORY STATS, Redis와 같은 이름의 필드 중 이 서버가 셀 수 있는 것만
+    pub fn to_resp(&self) -> RespValue {
+        let bytes_per_key = self.used_memory.checked_div(self.keys).unwrap_or(0);
+        RespValue::field_map(vec![
+            ("peak.allocated", RespValue::Integer(self.peak as i64)),
+            ("total.allocated", RespValue::Integer(self.used_memory as i64)),
+            ("overhead.total", RespValue::Integer(self.overhead() as i64)),
+            ("keys.count", RespValue::Integer(self.keys as i64)),
+            ("keys.bytes-per-key", RespValue::Integer(bytes_per_key as i64)),
+            ("dataset.bytes", RespValue::Integer(self.dataset as i64)),
+            ("dataset.percentage", RespValue::Double(Self::percentage(self.dataset, self.used_memory))),
+            ("peak.percentage", RespValue::Double(Self::percentage(self.used_memory, self.peak))),
+        ])
+    }
+
+    // 사람이 읽는 진단, 문제가 없으면 그렇다고만 알려줌
+    pub fn doctor(&self) -> String {
+        if self.used_memory < DOCTOR_MIN_USED_MEMORY {
+            return format!(
+                "This instance is empty or is using very little memory ({}), the memory doctor needs more data to find issues.{}",
+                human_bytes(self.used_memory),
+                CRLF
+            );
+        }
+        let mut issues = Vec::new();
+        if self.peak as f64 > self.used_memory as f64 * DOCTOR_PEAK_RATIO {
+            issues.push(format!(
+                "Peak memory: in the past this instance used {} while it now uses {}. Memory freed after a peak is kept by the allocator and reused as new data arrives.",
+                human_bytes(self.peak),
+                human_bytes(self.used_memory)
+            ));
+        }
+        if let Some(maxmemory) = self.maxmemory.filter(|maxmemory| self.used_memory as f64 >= *maxmemory as f64 * DOCTOR_MAXMEMORY_RATIO) {
+            issues.push(format!(
+                "Near maxmemory: this instance uses {} of the {} maxmemory limit, writes will start evicting keys or failing with OOM depending on maxmemory-policy.",
+                human_bytes(self.used_memory),
+                human_bytes(maxmemory)
+            ));
+        }
+        if issues.is_empty() {
+            return format!("No memory issue was found in this instance.{}", CRLF);
+        }
+        let mut report = format!("The following memory issues were found in this instance:{}{}", CRLF, CRLF);
+        for issue in issues {
+            report.push_str(&format!("- {}{}", issue, CRLF));
+        }
+        report
+    }
+}
+
+// Redis bytesToHuman과 같은 형식 (예: 1.50K, 12.00M)
+pub fn human_bytes(bytes: u64) -> String {
+    const UNITS: [(u64, &str); 4] = [(1 << 40, "T"), (1 << 30, "G"), (1 << 20, "M"), (1 << 10, "K")];
+    match UNITS.iter().find(|(size, _)| bytes >= *size) {
+        Some((size, unit)) => format!("{:.2}{}", bytes as f64 / *size as f64, unit),
+        None => format!("{}B", bytes),
+    }
+}
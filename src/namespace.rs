@@ -0,0 +1,24 @@
+use crate::command::Command;
+use crate::errors::RedisError;
+
+// ACL 사용자의 키 네임스페이스, namespace:<prefix> 규칙이 있는 사용자가 보낸 명령의 키 앞에 접두사를 붙임
+// 사용자는 접두사를 모른 채 쓰고, KEYS/SCAN/DBSIZE는 접두사 아래의 키만 접두사를 떼고 보여 줌
+// 접두사 아래로 가둘 수 없는 RANDOMKEY와 FLUSHDB/FLUSHALL은 거절함
+pub fn confine(command: &mut Command, prefix: &[u8]) -> Result<(), RedisError> {
+    match command {
+        Command::RANDOMKEY | Command::FLUSHDB(_) | Command::FLUSHALL(_) => {
+            return Err(RedisError::NoPerm(format!("The '{}' command can't be used inside a key namespace", command.name().to_lowercase())));
+        }
+        Command::KEYS { namespace, .. } | Command::SCAN { namespace, .. } | Command::DBSIZE(namespace) => {
+            *namespace = Some(prefix.to_vec());
+        }
+        // 스크립트에 넘기는 KEYS는 그대로 두고, 스크립트 안의 redis.call이 이 함수를 다시 거치며 붙임
+        Command::EVAL { .. } | Command::EVALSHA { .. } | Command::FCALL { .. } | Command::FCALLRO { .. } => {}
+        _ => {
+            for key in command.keys_mut() {
+                key.splice(0..0, prefix.iter().copied());
+            }
+        }
+    }
+    Ok(())
+}
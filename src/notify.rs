@@ -0,0 +1,74 @@
+use std::collections::HashMap;
+
+// notify-keyspace-events 플래그, Redis와 같은 문자를 사용함
+pub const NOTIFY_KEYSPACE: u32 = 1 << 0;
+pub const NOTIFY_KEYEVENT: u32 = 1 << 1;
+pub const NOTIFY_GENERIC: u32 = 1 << 2;
+pub const NOTIFY_STRING: u32 = 1 << 3;
+pub const NOTIFY_LIST: u32 = 1 << 4;
+pub const NOTIFY_SET: u32 = 1 << 5;
+pub const NOTIFY_HASH: u32 = 1 << 6;
+pub const NOTIFY_ZSET: u32 = 1 << 7;
+pub const NOTIFY_EXPIRED: u32 = 1 << 8;
+pub const NOTIFY_EVICTED: u32 = 1 << 9;
+pub const NOTIFY_STREAM: u32 = 1 << 10;
+pub const NOTIFY_KEY_MISS: u32 = 1 << 11;
+pub const NOTIFY_NEW: u32 = 1 << 12;
+// 'A'는 m(key miss)과 n(new key)을 제외한 모든 클래스
+const NOTIFY_ALL: u32 = NOTIFY_GENERIC
+    | NOTIFY_STRING
+    | NOTIFY_LIST
+    | NOTIFY_SET
+    | NOTIFY_HASH
+    | NOTIFY_ZSET
+    | NOTIFY_EXPIRED
+    | NOTIFY_EVICTED
+    | NOTIFY_STREAM;
+
+const KEYSPACE_CHANNEL_PREFIX: &str = "__keyspace@0__:";
+const KEYEVENT_CHANNEL_PREFIX: &str = "__keyevent@0__:";
+
+pub fn parse_flags(value: &str) -> Result<u32, String> {
+    value.chars().try_fold(0, |flags, c| {
+        let flag = match c {
+            'K' => NOTIFY_KEYSPACE,
+            'E' => NOTIFY_KEYEVENT,
+            'g' => NOTIFY_GENERIC,
+            '$' => NOTIFY_STRING,
+            'l' => NOTIFY_LIST,
+            's' => NOTIFY_SET,
+            'h' => NOTIFY_HASH,
+            'z' => NOTIFY_ZSET,
+            'x' => NOTIFY_EXPIRED,
+            'e' => NOTIFY_EVICTED,
+            't' => NOTIFY_STREAM,
+            'm' => NOTIFY_KEY_MISS,
+            'n' => NOTIFY_NEW,
+            'A' => NOTIFY_ALL,
+            _ => return Err(format!("Invalid notify-keyspace-events flag '{}'", c)),
+        };
+        Ok(flags | flag)
+    })
+}
+
+// K나 E 중 하나가 없으면 어느 채널에도 발행하지 않으므로 클래스가 켜져 있어도 비활성으로 봄
+pub fn is_enabled(config: &HashMap<String, String>, class: u32) -> bool {
+    let flags = enabled_flags(config);
+    flags & class != 0 && flags & (NOTIFY_KEYSPACE | NOTIFY_KEYEVENT) != 0
+}
+
+pub fn enabled_flags(config: &HashMap<String, String>) -> u32 {
+    config
+        .get("notify_keyspace_events")
+        .and_then(|value| parse_flags(value).ok())
+        .unwrap_or(0)
+}
+
+// 채널 이름은 문자열이라 UTF-8이 아닌 키는 대체 문자로 바뀜
+pub fn keyspace_channel(key: &[u8]) -> String {
+    format!("{}{}", KEYSPACE_CHANNEL_PREFIX, String::from_utf8_lossy(key))
+}
+
+pub fn keyevent_channel(event: &str) -> String {
+    format!("{}{}", KEYEVENT_CHANNEL_PREFIX, event)
+}
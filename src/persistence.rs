@@ -0,0 +1,145 @@
+use crate::config_handler::Db;
+use crate::protocol_constants::{CRLF, INVALID_SAVE_PARAMS_ERROR};
+use crate::rdb_codec;
+use crate::util::current_time_ms;
+use crate::value_entry::{FieldExpirations, RedisValue};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+const DEFAULT_RDB_DIR: &str = ".";
+const DEFAULT_RDB_FILE_NAME: &str = "dump.rdb";
+// Redis 기본 save 설정: 1시간에 1번, 5분에 100번, 1분에 10000번 이상 바뀌면 저장
+const DEFAULT_SAVE_POINTS: &str = "3600 1 300 100 60 10000";
+// 자동 저장이 실패하면 이 시간이 지나기 전에는 다시 시도하지 않음
+const BGSAVE_RETRY_DELAY_SECS: u64 = 5;
+
+// 스냅샷 시점의 (키, 값, 만료 시각 ms, 해시 필드별 만료 시각)
+pub type SnapshotEntry = (Vec<u8>, RedisValue, Option<u64>, FieldExpirations);
+
+// BGSAVE가 쓰는 도중에 종료 저장이 겹쳐도 임시 파일이 섞이지 않도록 저장마다 다른 이름을 씀
+static TEMP_FILE_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+pub struct Persistence {
+    bgsave_in_progress: bool,
+    // 마지막으로 저장에 성공한 시각(unix 초), 아직 저장한 적이 없으면 서버 시작 시각
+    last_save_time: u64,
+    last_bgsave_ok: bool,
+    last_bgsave_try: u64,
+    // 마지막 저장 이후 바뀐 키 수, BGSAVE가 성공하면 시작할 때의 값만큼 뺌
+    dirty: u64,
+    dirty_at_bgsave_start: u64,
+}
+
+impl Persistence {
+    pub fn new() -> Self {
+        Self {
+            bgsave_in_progress: false,
+            last_save_time: current_time_ms() / 1000,
+            last_bgsave_ok: true,
+            last_bgsave_try: 0,
+            dirty: 0,
+            dirty_at_bgsave_start: 0,
+        }
+    }
+
+    // 이미 진행 중이면 false
+    pub fn start_bgsave(&mut self) -> bool {
+        if self.bgsave_in_progress {
+            return false;
+        }
+        self.bgsave_in_progress = true;
+        self.last_bgsave_try = current_time_ms() / 1000;
+        self.dirty_at_bgsave_start = self.dirty;
+        true
+    }
+
+    pub fn finish_bgsave(&mut self, ok: bool) {
+        self.bgsave_in_progress = false;
+        self.last_bgsave_ok = ok;
+        if ok {
+            self.last_save_time = current_time_ms() / 1000;
+            self.dirty = self.dirty.saturating_sub(self.dirty_at_bgsave_start);
+        }
+    }
+
+    pub fn mark_dirty(&mut self, changes: u64) {
+        self.dirty += changes;
+    }
+
+    // 조건을 만족한 (초, 변경 수) 저장 지점, 저장 중이거나 직전 실패 후 재시도 대기 중이면 None
+    pub fn reached_save_point(&self, save_points: &[(u64, u64)]) -> Option<(u64, u64)> {
+        let now = current_time_ms() / 1000;
+        if self.bgsave_in_progress || (!self.last_bgsave_ok && now < self.last_bgsave_try + BGSAVE_RETRY_DELAY_SECS) {
+            return None;
+        }
+        save_points
+            .iter()
+            .copied()
+            .find(|(seconds, changes)| self.dirty >= *changes && now >= self.last_save_time + seconds)
+    }
+
+    pub fn last_save_time(&self) -> u64 {
+        self.last_save_time
+    }
+
+    pub fn get_persistence_info(&self) -> String {
+        let mut info = format!("# Persistence{}", CRLF);
+        info.push_str(&format!("rdb_bgsave_in_progress:{}{}", self.bgsave_in_progress as u8, CRLF));
+        info.push_str(&format!("rdb_changes_since_last_save:{}{}", self.dirty, CRLF));
+        info.push_str(&format!("rdb_last_save_time:{}{}", self.last_save_time, CRLF));
+        info.push_str(&format!("rdb_last_bgsave_status:{}{}", if self.last_bgsave_ok { "ok" } else { "err" }, CRLF));
+        info
+    }
+}
+
+// "<초> <변경 수>" 쌍의 목록, 빈 문자열이면 자동 저장을 끔
+pub fn parse_save_points(value: &str) -> Result<Vec<(u64, u64)>, String> {
+    let parts: Vec<&str> = value.split_whitespace().collect();
+    if parts.len() % 2 != 0 {
+        return Err(INVALID_SAVE_PARAMS_ERROR.into());
+    }
+    parts
+        .chunks(2)
+        .map(|pair| match (pair[0].parse::<u64>(), pair[1].parse::<u64>()) {
+            (Ok(seconds), Ok(changes)) if seconds > 0 && changes > 0 => Ok((seconds, changes)),
+            _ => Err(INVALID_SAVE_PARAMS_ERROR.into()),
+        })
+        .collect()
+}
+
+pub fn save_points(config: &HashMap<String, String>) -> Vec<(u64, u64)> {
+    let value = config.get("save").map_or(DEFAULT_SAVE_POINTS, |value| value.as_str());
+    parse_save_points(value).unwrap_or_default()
+}
+
+// Redis 기본값처럼 rdbcompression no로 끄지 않으면 압축함
+pub fn rdb_compression(config: &HashMap<String, String>) -> bool {
+    !config.get("rdbcompression").is_some_and(|value| value == "no")
+}
+
+pub fn rdb_file_path(config: &HashMap<String, String>) -> PathBuf {
+    let dir = config.get("dir").filter(|dir| !dir.is_empty()).map_or(DEFAULT_RDB_DIR, |dir| dir.as_str());
+    let file_name = config
+        .get("file_name")
+        .filter(|file_name| !file_name.is_empty())
+        .map_or(DEFAULT_RDB_FILE_NAME, |file_name| file_name.as_str());
+    Path::new(dir).join(file_name)
+}
+
+// 이미 만료된 키는 제외함
+pub fn snapshot(db: &Db) -> Vec<SnapshotEntry> {
+    db.alive()
+        .map(|(key, entry)| (key.to_vec(), entry.value.clone(), entry.expiration_ms(), entry.field_expirations().clone()))
+        .collect()
+}
+
+// 임시 파일에 다 쓴 뒤 rename해서 저장 도중 죽어도 기존 파일이 깨지지 않게 함
+pub fn write_rdb_file(path: &Path, entries: &[SnapshotEntry], aux: &[(&str, String)], functions: &[String], compress: bool) -> io::Result<()> {
+    let counter = TEMP_FILE_COUNTER.fetch_add(1, Ordering::Relaxed);
+    let temp_path = path.with_file_name(format!("temp-{}-{}.rdb", std::process::id(), counter));
+    fs::write(&temp_path, rdb_codec::encode_rdb(&[entries], aux, functions, compress))?;
+    fs::rename(&temp_path, path)
+}
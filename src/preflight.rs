@@ -0,0 +1,272 @@
+use crate::command_registry::ClientNames;
+use crate::eviction::{self, EvictionPolicy};
+use crate::firewall::Firewall;
+use crate::notify;
+use crate::protocol_constants::{MAGIC_NUMBER, MIN_PROTO_MAX_BULK_LEN};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::Read;
+use std::net::{TcpListener, ToSocketAddrs};
+use std::path::Path;
+
+// Redis 기본 maxclients와, 리스너/RDB 등을 위해 남겨두는 파일 디스크립터 수
+const DEFAULT_MAX_CLIENTS: u64 = 10000;
+const RESERVED_FDS: u64 = 32;
+
+#[derive(PartialEq)]
+enum Severity {
+    Ok,
+    Warn,
+    Fatal,
+}
+
+impl Severity {
+    fn label(&self) -> &'static str {
+        match self {
+            Severity::Ok => "ok",
+            Severity::Warn => "warn",
+            Severity::Fatal => "fatal",
+        }
+    }
+}
+
+struct Check {
+    name: &'static str,
+    severity: Severity,
+    detail: String,
+}
+
+// 설정을 검증하고 결과를 출력함, 치명적인 문제가 있으면 false
+pub fn run(config: &HashMap<String, String>, config_result: &Result<(), String>) -> bool {
+    let mut checks = vec![check_config(config_result)];
+    checks.extend(check_dir(config));
+    checks.extend(check_rdb(config));
+    checks.extend(check_ports(config));
+    checks.extend(check_firewall(config));
+    checks.extend(check_replicaof(config));
+    checks.extend(check_event_queue(config));
+    checks.extend(check_memory(config));
+    checks.extend(check_notifications(config));
+    checks.push(check_open_files());
+
+    println!("preflight report");
+    for check in &checks {
+        println!("  [{:<5}] {:<10} {}", check.severity.label(), check.name, check.detail);
+    }
+    let fatal = checks.iter().filter(|check| check.severity == Severity::Fatal).count();
+    let warnings = checks.iter().filter(|check| check.severity == Severity::Warn).count();
+    println!("result: {} fatal, {} warning(s)", fatal, warnings);
+    fatal == 0
+}
+
+fn check(name: &'static str, severity: Severity, detail: String) -> Check {
+    Check { name, severity, detail }
+}
+
+fn check_config(config_result: &Result<(), String>) -> Check {
+    match config_result {
+        Ok(()) => check("config", Severity::Ok, "arguments parsed".to_string()),
+        Err(e) => check("config", Severity::Fatal, e.clone()),
+    }
+}
+
+fn check_dir(config: &HashMap<String, String>) -> Option<Check> {
+    let dir = config.get("dir")?;
+    if !Path::new(dir).is_dir() {
+        return Some(check("dir", Severity::Fatal, format!("{} is not a directory", dir)));
+    }
+
+    let probe = Path::new(dir).join(format!(".preflight-{}", std::process::id()));
+    let result = fs::write(&probe, b"").and_then(|_| fs::remove_file(&probe));
+    Some(match result {
+        Ok(()) => check("dir", Severity::Ok, format!("{} is writable", dir)),
+        Err(e) => check("dir", Severity::Fatal, format!("{} is not writable: {}", dir, e)),
+    })
+}
+
+fn check_rdb(config: &HashMap<String, String>) -> Option<Check> {
+    let path = Path::new(config.get("dir")?).join(config.get("file_name")?);
+    if !path.exists() {
+        return Some(check("rdb", Severity::Ok, format!("{} does not exist, starting empty", path.display())));
+    }
+
+    let mut magic = [0u8; 5];
+    let result = File::open(&path).and_then(|mut file| file.read_exact(&mut magic));
+    Some(match result {
+        Ok(()) if magic == *MAGIC_NUMBER => check("rdb", Severity::Ok, format!("{} is readable", path.display())),
+        Ok(()) => check("rdb", Severity::Fatal, format!("{} is not an RDB file", path.display())),
+        Err(e) => check("rdb", Severity::Fatal, format!("{} is not readable: {}", path.display(), e)),
+    })
+}
+
+fn check_ports(config: &HashMap<String, String>) -> Vec<Check> {
+    let mut checks = Vec::new();
+    let port = match config.get("port").map(|port| port.parse::<u16>()) {
+        Some(Ok(port)) => port,
+        Some(Err(e)) => return vec![check("port", Severity::Fatal, format!("invalid port: {}", e))],
+        None => 6379,
+    };
+
+    // 서버는 IPv6 바인딩 실패를 허용하므로 IPv4만 치명적임
+    for (bind_addr, severity) in [
+        (format!("127.0.0.1:{}", port), Severity::Fatal),
+        (format!("[::1]:{}", port), Severity::Warn),
+    ] {
+        checks.push(match TcpListener::bind(&bind_addr) {
+            Ok(_) => check("port", Severity::Ok, format!("{} is available", bind_addr)),
+            Err(e) => check("port", severity, format!("{} is not available: {}", bind_addr, e)),
+        });
+    }
+
+    if let Some(admin_port) = config.get("admin_port") {
+        let bind_addr = format!("127.0.0.1:{}", admin_port);
+        checks.push(match TcpListener::bind(&bind_addr) {
+            Ok(_) => check("admin", Severity::Ok, format!("{} is available", bind_addr)),
+            Err(e) => check("admin", Severity::Warn, format!("{} is not available: {}", bind_addr, e)),
+        });
+    }
+    checks
+}
+
+fn check_firewall(config: &HashMap<String, String>) -> Option<Check> {
+    let spec = config.get("firewall")?;
+    Some(match Firewall::parse(spec) {
+        Ok(_) => check("firewall", Severity::Ok, "rules parsed".to_string()),
+        Err(e) => check("firewall", Severity::Fatal, e),
+    })
+}
+
+// 서버는 연결할 때마다 이름을 다시 찾으며 재시도하지만, 지금 찾을 수 없으면 복제가 시작되지 않으므로 치명적으로 봄
+fn check_replicaof(config: &HashMap<String, String>) -> Option<Check> {
+    let host = config.get("replica_of_host")?;
+    let port = match config.get("replica_of_port")?.parse::<u16>() {
+        Ok(port) => port,
+        Err(e) => return Some(check("replicaof", Severity::Fatal, format!("invalid master port: {}", e))),
+    };
+    Some(match (host.as_str(), port).to_socket_addrs().map(|mut addrs| addrs.next()) {
+        Ok(Some(addr)) => check("replicaof", Severity::Ok, format!("replicating from {} {} ({})", host, port, addr)),
+        Ok(None) => check("replicaof", Severity::Fatal, format!("master host '{}' resolved to no addresses", host)),
+        Err(e) => check("replicaof", Severity::Fatal, format!("could not resolve master host '{}': {}", host, e)),
+    })
+}
+
+fn check_event_queue(config: &HashMap<String, String>) -> Vec<Check> {
+    let mut checks = Vec::new();
+    if let Some(capacity) = config.get("event_queue_capacity") {
+        checks.push(match capacity.parse::<usize>() {
+            Ok(capacity) if capacity > 0 => check("queue", Severity::Ok, format!("event queue capacity {}", capacity)),
+            _ => check("queue", Severity::Fatal, format!("invalid event queue capacity '{}'", capacity)),
+        });
+    }
+    if let Some(policy) = config.get("overload_policy") {
+        checks.push(match policy.as_str() {
+            "block" | "shed" => check("overload", Severity::Ok, format!("overload policy {}", policy)),
+            _ => check("overload", Severity::Fatal, format!("unknown overload policy '{}', expected block or shed", policy)),
+        });
+    }
+    checks
+}
+
+fn check_memory(config: &HashMap<String, String>) -> Vec<Check> {
+    let mut checks = Vec::new();
+    if let Some(maxmemory) = config.get("maxmemory") {
+        checks.push(match eviction::parse_memory(maxmemory) {
+            Ok(0) => check("maxmemory", Severity::Ok, "no memory limit".to_string()),
+            Ok(bytes) => check("maxmemory", Severity::Ok, format!("limit {} bytes", bytes)),
+            Err(e) => check("maxmemory", Severity::Fatal, e),
+        });
+    }
+    if let Some(policy) = config.get("maxmemory_policy") {
+        checks.push(match EvictionPolicy::parse(policy) {
+            Ok(_) => check("policy", Severity::Ok, format!("maxmemory policy {}", policy)),
+            Err(e) => check("policy", Severity::Fatal, e),
+        });
+    }
+    if let Some(max_bulk_len) = config.get("proto_max_bulk_len") {
+        checks.push(match eviction::parse_memory(max_bulk_len) {
+            Ok(bytes) if bytes >= MIN_PROTO_MAX_BULK_LEN => check("bulk", Severity::Ok, format!("proto-max-bulk-len {} bytes", bytes)),
+            Ok(_) => check("bulk", Severity::Fatal, "proto-max-bulk-len must be at least 1mb".to_string()),
+            Err(e) => check("bulk", Severity::Fatal, e),
+        });
+    }
+    for key in ["lfu_log_factor", "lfu_decay_time"] {
+        if let Some(value) = config.get(key) {
+            checks.push(match value.parse::<u64>() {
+                Ok(_) => check("lfu", Severity::Ok, format!("{} {}", key, value)),
+                Err(_) => check("lfu", Severity::Fatal, format!("invalid {} '{}'", key, value)),
+            });
+        }
+    }
+    if config.contains_key("rename_command") || config.contains_key("disabled_commands") {
+        let renames = config.get("rename_command").map_or("", |renames| renames.as_str());
+        let disabled = config.get("disabled_commands").map_or("", |disabled| disabled.as_str());
+        checks.push(match ClientNames::parse(renames, disabled) {
+            Ok(_) => check("commands", Severity::Ok, "renamed and disabled commands are valid".into()),
+            Err(e) => check("commands", Severity::Fatal, e),
+        });
+    }
+    if let Some(value) = config.get("client_max_commands_per_sec") {
+        checks.push(match value.parse::<u64>() {
+            Ok(_) => check("rate-limit", Severity::Ok, format!("client_max_commands_per_sec {}", value)),
+            Err(_) => check("rate-limit", Severity::Fatal, format!("invalid client_max_commands_per_sec '{}'", value)),
+        });
+    }
+    if let Some(value) = config.get("client_max_input_bytes_per_sec") {
+        checks.push(match eviction::parse_memory(value) {
+            Ok(_) => check("rate-limit", Severity::Ok, format!("client_max_input_bytes_per_sec {}", value)),
+            Err(_) => check("rate-limit", Severity::Fatal, format!("invalid client_max_input_bytes_per_sec '{}'", value)),
+        });
+    }
+    for key in ["hash_max_listpack_entries", "hash_max_listpack_value", "zset_max_listpack_entries", "zset_max_listpack_value"] {
+        if let Some(value) = config.get(key) {
+            checks.push(match value.parse::<u64>() {
+                Ok(_) => check("listpack", Severity::Ok, format!("{} {}", key, value)),
+                Err(_) => check("listpack", Severity::Fatal, format!("invalid {} '{}'", key, value)),
+            });
+        }
+    }
+    if let Some(value) = config.get("list_max_listpack_size") {
+        checks.push(match value.parse::<i64>() {
+            Ok(size) if size > 0 || (-5..=-1).contains(&size) => check("listpack", Severity::Ok, format!("list_max_listpack_size {}", value)),
+            _ => check("listpack", Severity::Fatal, format!("invalid list_max_listpack_size '{}'", value)),
+        });
+    }
+    checks
+}
+
+fn check_notifications(config: &HashMap<String, String>) -> Vec<Check> {
+    let Some(flags) = config.get("notify_keyspace_events") else {
+        return Vec::new();
+    };
+    vec![match notify::parse_flags(flags) {
+        Ok(0) => check("notify", Severity::Ok, "keyspace notifications disabled".to_string()),
+        Ok(parsed) if parsed & (notify::NOTIFY_KEYSPACE | notify::NOTIFY_KEYEVENT) == 0 => {
+            check("notify", Severity::Warn, format!("'{}' selects no K or E channel, nothing will be published", flags))
+        }
+        Ok(_) => check("notify", Severity::Ok, format!("keyspace notifications '{}'", flags)),
+        Err(e) => check("notify", Severity::Fatal, e),
+    }]
+}
+
+fn check_open_files() -> Check {
+    let limit = fs::read_to_string("/proc/self/limits").ok().and_then(|limits| {
+        limits
+            .lines()
+            .find(|line| line.starts_with("Max open files"))
+            .and_then(|line| line.split_whitespace().nth(3))
+            .and_then(|soft_limit| soft_limit.parse::<u64>().ok())
+    });
+
+    match limit {
+        Some(limit) => {
+            let max_clients = limit.saturating_sub(RESERVED_FDS);
+            let severity = if max_clients < DEFAULT_MAX_CLIENTS { Severity::Warn } else { Severity::Ok };
+            check(
+                "nofile",
+                severity,
+                format!("open file limit {} allows about {} clients (default maxclients {})", limit, max_clients, DEFAULT_MAX_CLIENTS),
+            )
+        }
+        None => check("nofile", Severity::Warn, "open file limit unknown on this platform".to_string()),
+    }
+}
@@ -1,62 +1,520 @@
 pub const ARRAY_PREFIX: &str = "*";
 pub const BULK_STRING_PREFIX: &str = "$";
 pub const SIMPLE_STRING_PREFIX: &str = "+";
+pub const INTEGER_PREFIX: &str = ":";
+pub const MAP_PREFIX: &str = "%";
+pub const SET_PREFIX: &str = "~";
+pub const PUSH_PREFIX: &str = ">";
+pub const ERROR_PREFIX: &str = "-";
+pub const NULL_PREFIX: &str = "_";
+pub const DOUBLE_PREFIX: &str = ",";
+pub const BOOLEAN_PREFIX: &str = "#";
+pub const BIG_NUMBER_PREFIX: &str = "(";
 pub const CRLF: &str = "\r\n";
+// 벌크 응답을 소켓에 나눠 쓸 때의 크기
+pub const BULK_WRITE_CHUNK_SIZE: usize = 16 * 1024;
 
 pub const PING_COMMAND: &str = "PING";
 pub const ECHO_COMMAND: &str = "ECHO";
 pub const GET_COMMAND: &str = "GET";
 pub const SET_COMMAND: &str = "SET";
+pub const GETSET_COMMAND: &str = "GETSET";
+pub const GETEX_COMMAND: &str = "GETEX";
+pub const INCR_COMMAND: &str = "INCR";
+pub const LCS_COMMAND: &str = "LCS";
+pub const TYPE_COMMAND: &str = "TYPE";
 pub const CONFIG_COMMAND: &str = "CONFIG";
 pub const REPLCONF_COMMAND: &str = "REPLCONF";
 pub const PSYNC_COMMAND: &str = "PSYNC";
+pub const REPLICAOF_COMMAND: &str = "REPLICAOF";
+pub const SLAVEOF_COMMAND: &str = "SLAVEOF";
+pub const SELECT_COMMAND: &str = "SELECT";
+pub const WAIT_COMMAND: &str = "WAIT";
+pub const BGSAVE_COMMAND: &str = "BGSAVE";
+pub const LASTSAVE_COMMAND: &str = "LASTSAVE";
+pub const SHUTDOWN_COMMAND: &str = "SHUTDOWN";
+
+pub const EXPIRE_COMMAND: &str = "EXPIRE";
+pub const PEXPIRE_COMMAND: &str = "PEXPIRE";
+pub const EXPIREAT_COMMAND: &str = "EXPIREAT";
+pub const PEXPIREAT_COMMAND: &str = "PEXPIREAT";
+pub const TTL_COMMAND: &str = "TTL";
+pub const PTTL_COMMAND: &str = "PTTL";
+pub const EXPIRETIME_COMMAND: &str = "EXPIRETIME";
+pub const PEXPIRETIME_COMMAND: &str = "PEXPIRETIME";
+pub const PERSIST_COMMAND: &str = "PERSIST";
+pub const DEL_COMMAND: &str = "DEL";
+pub const UNLINK_COMMAND: &str = "UNLINK";
+pub const EXISTS_COMMAND: &str = "EXISTS";
+pub const TOUCH_COMMAND: &str = "TOUCH";
+pub const SUBSCRIBE_COMMAND: &str = "SUBSCRIBE";
+pub const UNSUBSCRIBE_COMMAND: &str = "UNSUBSCRIBE";
+pub const PUBLISH_COMMAND: &str = "PUBLISH";
+pub const PSUBSCRIBE_COMMAND: &str = "PSUBSCRIBE";
+pub const PUNSUBSCRIBE_COMMAND: &str = "PUNSUBSCRIBE";
+pub const PUBSUB_COMMAND: &str = "PUBSUB";
+pub const SSUBSCRIBE_COMMAND: &str = "SSUBSCRIBE";
+pub const SUNSUBSCRIBE_COMMAND: &str = "SUNSUBSCRIBE";
+pub const SPUBLISH_COMMAND: &str = "SPUBLISH";
+pub const DEBUG_COMMAND: &str = "DEBUG";
+pub const LATENCY_COMMAND: &str = "LATENCY";
+pub const MEMORY_COMMAND: &str = "MEMORY";
+pub const DUMP_COMMAND: &str = "DUMP";
+pub const RESTORE_COMMAND: &str = "RESTORE";
+pub const RESTORE_ASKING_COMMAND: &str = "RESTORE-ASKING";
+pub const MIGRATE_COMMAND: &str = "MIGRATE";
+pub const OBJECT_COMMAND: &str = "OBJECT";
+
+pub const HSET_COMMAND: &str = "HSET";
+pub const HGET_COMMAND: &str = "HGET";
+pub const HGETALL_COMMAND: &str = "HGETALL";
+pub const HRANDFIELD_COMMAND: &str = "HRANDFIELD";
+pub const HDEL_COMMAND: &str = "HDEL";
+pub const HEXPIRE_COMMAND: &str = "HEXPIRE";
+pub const HPEXPIRE_COMMAND: &str = "HPEXPIRE";
+pub const HTTL_COMMAND: &str = "HTTL";
+pub const HPERSIST_COMMAND: &str = "HPERSIST";
+
+pub const LPUSH_COMMAND: &str = "LPUSH";
+pub const RPUSH_COMMAND: &str = "RPUSH";
+pub const LPOP_COMMAND: &str = "LPOP";
+pub const RPOP_COMMAND: &str = "RPOP";
+pub const BLPOP_COMMAND: &str = "BLPOP";
+pub const BRPOP_COMMAND: &str = "BRPOP";
+pub const LMOVE_COMMAND: &str = "LMOVE";
+pub const BLMOVE_COMMAND: &str = "BLMOVE";
+pub const BRPOPLPUSH_COMMAND: &str = "BRPOPLPUSH";
+pub const LMPOP_COMMAND: &str = "LMPOP";
+pub const BLMPOP_COMMAND: &str = "BLMPOP";
+
+pub const ZADD_COMMAND: &str = "ZADD";
+pub const ZMPOP_COMMAND: &str = "ZMPOP";
+pub const BZMPOP_COMMAND: &str = "BZMPOP";
+pub const ZRANDMEMBER_COMMAND: &str = "ZRANDMEMBER";
+pub const ZSCORE_COMMAND: &str = "ZSCORE";
+pub const ZINCRBY_COMMAND: &str = "ZINCRBY";
+
+pub const CLIENT_COMMAND: &str = "CLIENT";
+pub const ACL_COMMAND: &str = "ACL";
+pub const CLUSTER_COMMAND: &str = "CLUSTER";
+pub const ASKING_COMMAND: &str = "ASKING";
+pub const READONLY_COMMAND: &str = "READONLY";
+pub const READWRITE_COMMAND: &str = "READWRITE";
+pub const SENTINEL_COMMAND: &str = "SENTINEL";
+pub const HELLO_COMMAND: &str = "HELLO";
+pub const AUTH_COMMAND: &str = "AUTH";
+pub const QUIT_COMMAND: &str = "QUIT";
+pub const RESET_COMMAND: &str = "RESET";
+pub const MULTI_COMMAND: &str = "MULTI";
+pub const EXEC_COMMAND: &str = "EXEC";
+pub const DISCARD_COMMAND: &str = "DISCARD";
+pub const WATCH_COMMAND: &str = "WATCH";
+pub const UNWATCH_COMMAND: &str = "UNWATCH";
+
+pub const SCRIPT_COMMAND: &str = "SCRIPT";
+pub const FUNCTION_COMMAND: &str = "FUNCTION";
+pub const EVAL_COMMAND: &str = "EVAL";
+pub const EVALSHA_COMMAND: &str = "EVALSHA";
+pub const FCALL_COMMAND: &str = "FCALL";
+pub const FCALL_RO_COMMAND: &str = "FCALL_RO";
 
 pub const KEYS_COMMAND: &str = "KEYS";
+pub const SCAN_COMMAND: &str = "SCAN";
+pub const RANDOMKEY_COMMAND: &str = "RANDOMKEY";
+pub const DBSIZE_COMMAND: &str = "DBSIZE";
+pub const FLUSHDB_COMMAND: &str = "FLUSHDB";
+pub const FLUSHALL_COMMAND: &str = "FLUSHALL";
 pub const INFO_COMMAND: &str = "INFO";
 pub const FULLRESYNC: &str = "FULLRESYNC";
+pub const CONTINUE: &str = "CONTINUE";
 
 pub const PX_OPTION: &str = "PX";
 pub const EX_OPTION: &str = "EX";
+pub const PXAT_OPTION: &str = "PXAT";
+pub const EXAT_OPTION: &str = "EXAT";
+pub const PERSIST_OPTION: &str = "PERSIST";
+pub const INCR_OPTION: &str = "INCR";
+pub const NX_OPTION: &str = "NX";
+pub const XX_OPTION: &str = "XX";
+pub const GT_OPTION: &str = "GT";
+pub const LT_OPTION: &str = "LT";
+pub const LEFT_OPTION: &str = "LEFT";
+pub const RIGHT_OPTION: &str = "RIGHT";
+pub const MIN_OPTION: &str = "MIN";
+pub const MAX_OPTION: &str = "MAX";
+pub const MATCH_OPTION: &str = "MATCH";
+pub const COUNT_OPTION: &str = "COUNT";
+pub const TYPE_OPTION: &str = "TYPE";
+pub const FIELDS_OPTION: &str = "FIELDS";
+pub const WITHSCORES_OPTION: &str = "WITHSCORES";
+pub const WITHVALUES_OPTION: &str = "WITHVALUES";
+pub const LEN_OPTION: &str = "LEN";
+pub const IDX_OPTION: &str = "IDX";
+pub const MINMATCHLEN_OPTION: &str = "MINMATCHLEN";
+pub const WITHMATCHLEN_OPTION: &str = "WITHMATCHLEN";
 
 pub const CONFIG_GET_OPTION: &str = "GET";
+pub const CONFIG_SET_OPTION: &str = "SET";
+
+pub const SAVE_OPTION: &str = "SAVE";
+pub const NOSAVE_OPTION: &str = "NOSAVE";
+
+pub const CLIENT_ID_OPTION: &str = "ID";
+pub const CLIENT_TRACKING_OPTION: &str = "TRACKING";
+pub const CLIENT_GETREDIR_OPTION: &str = "GETREDIR";
+pub const CLIENT_LIST_OPTION: &str = "LIST";
+pub const CLIENT_SETNAME_OPTION: &str = "SETNAME";
+pub const CLIENT_GETNAME_OPTION: &str = "GETNAME";
+pub const CLIENT_KILL_OPTION: &str = "KILL";
+pub const CLIENT_CACHING_OPTION: &str = "CACHING";
+pub const ACL_SETUSER_OPTION: &str = "SETUSER";
+pub const ACL_GETUSER_OPTION: &str = "GETUSER";
+pub const ACL_DELUSER_OPTION: &str = "DELUSER";
+pub const ACL_LIST_OPTION: &str = "LIST";
+pub const ACL_USERS_OPTION: &str = "USERS";
+pub const ACL_WHOAMI_OPTION: &str = "WHOAMI";
+pub const CLIENT_KILL_ID_FILTER: &str = "ID";
+pub const CLIENT_KILL_ADDR_FILTER: &str = "ADDR";
+pub const CLIENT_KILL_LADDR_FILTER: &str = "LADDR";
+pub const CLIENT_KILL_USER_FILTER: &str = "USER";
+pub const CLIENT_KILL_MAXAGE_FILTER: &str = "MAXAGE";
+pub const CLIENT_KILL_SKIPME_FILTER: &str = "SKIPME";
+pub const CLIENT_TYPE_NORMAL: &str = "NORMAL";
+pub const CLIENT_TYPE_MASTER: &str = "MASTER";
+pub const CLIENT_TYPE_REPLICA: &str = "REPLICA";
+pub const CLIENT_TYPE_SLAVE: &str = "SLAVE";
+pub const CLIENT_TYPE_PUBSUB: &str = "PUBSUB";
+pub const HELLO_AUTH_OPTION: &str = "AUTH";
+pub const HELLO_SETNAME_OPTION: &str = "SETNAME";
+pub const DEFAULT_USER: &str = "default";
+pub const RESP2_PROTOCOL: u8 = 2;
+pub const RESP3_PROTOCOL: u8 = 3;
+pub const ON_OPTION: &str = "ON";
+pub const OFF_OPTION: &str = "OFF";
+pub const REDIRECT_OPTION: &str = "REDIRECT";
+pub const BCAST_OPTION: &str = "BCAST";
+pub const PREFIX_OPTION: &str = "PREFIX";
+pub const NOLOOP_OPTION: &str = "NOLOOP";
+pub const OPTIN_OPTION: &str = "OPTIN";
+pub const OPTOUT_OPTION: &str = "OPTOUT";
+pub const YES_OPTION: &str = "YES";
+pub const NO_OPTION: &str = "NO";
+
+pub const CLUSTER_INFO_OPTION: &str = "INFO";
+pub const CLUSTER_MYID_OPTION: &str = "MYID";
+pub const CLUSTER_NODES_OPTION: &str = "NODES";
+pub const CLUSTER_SLOTS_OPTION: &str = "SLOTS";
+pub const CLUSTER_SHARDS_OPTION: &str = "SHARDS";
+pub const CLUSTER_KEYSLOT_OPTION: &str = "KEYSLOT";
+pub const CLUSTER_ADDSLOTS_OPTION: &str = "ADDSLOTS";
+pub const CLUSTER_ADDSLOTSRANGE_OPTION: &str = "ADDSLOTSRANGE";
+pub const CLUSTER_DELSLOTS_OPTION: &str = "DELSLOTS";
+pub const CLUSTER_DELSLOTSRANGE_OPTION: &str = "DELSLOTSRANGE";
+pub const CLUSTER_SETSLOT_OPTION: &str = "SETSLOT";
+pub const CLUSTER_COUNTKEYSINSLOT_OPTION: &str = "COUNTKEYSINSLOT";
+pub const CLUSTER_GETKEYSINSLOT_OPTION: &str = "GETKEYSINSLOT";
+pub const CLUSTER_MEET_OPTION: &str = "MEET";
+pub const CLUSTER_REPLICATE_OPTION: &str = "REPLICATE";
+pub const CLUSTER_REPLICAS_OPTION: &str = "REPLICAS";
+pub const CLUSTER_SLAVES_OPTION: &str = "SLAVES";
+pub const SENTINEL_MASTERS_OPTION: &str = "MASTERS";
+pub const SENTINEL_MASTER_OPTION: &str = "MASTER";
+pub const SENTINEL_REPLICAS_OPTION: &str = "REPLICAS";
+pub const SENTINEL_SLAVES_OPTION: &str = "SLAVES";
+pub const SENTINEL_SENTINELS_OPTION: &str = "SENTINELS";
+pub const SENTINEL_GET_MASTER_ADDR_BY_NAME_OPTION: &str = "GET-MASTER-ADDR-BY-NAME";
+pub const SENTINEL_IS_MASTER_DOWN_BY_ADDR_OPTION: &str = "IS-MASTER-DOWN-BY-ADDR";
+pub const SENTINEL_CKQUORUM_OPTION: &str = "CKQUORUM";
+pub const SENTINEL_FAILOVER_OPTION: &str = "FAILOVER";
+pub const SENTINEL_MYID_OPTION: &str = "MYID";
+pub const SENTINEL_MONITOR_OPTION: &str = "MONITOR";
+pub const SENTINEL_REMOVE_OPTION: &str = "REMOVE";
+pub const SETSLOT_IMPORTING_OPTION: &str = "IMPORTING";
+pub const SETSLOT_MIGRATING_OPTION: &str = "MIGRATING";
+pub const SETSLOT_STABLE_OPTION: &str = "STABLE";
+pub const SETSLOT_NODE_OPTION: &str = "NODE";
+
+pub const PUBSUB_CHANNELS_OPTION: &str = "CHANNELS";
+pub const PUBSUB_NUMSUB_OPTION: &str = "NUMSUB";
+pub const PUBSUB_NUMPAT_OPTION: &str = "NUMPAT";
+pub const PUBSUB_SHARDCHANNELS_OPTION: &str = "SHARDCHANNELS";
+pub const PUBSUB_SHARDNUMSUB_OPTION: &str = "SHARDNUMSUB";
+
+pub const SCRIPT_LOAD_OPTION: &str = "LOAD";
+pub const SCRIPT_EXISTS_OPTION: &str = "EXISTS";
+pub const SCRIPT_FLUSH_OPTION: &str = "FLUSH";
+pub const SCRIPT_KILL_OPTION: &str = "KILL";
+pub const FUNCTION_LOAD_OPTION: &str = "LOAD";
+pub const FUNCTION_LIST_OPTION: &str = "LIST";
+pub const FUNCTION_DELETE_OPTION: &str = "DELETE";
+pub const FUNCTION_FLUSH_OPTION: &str = "FLUSH";
+pub const FUNCTION_KILL_OPTION: &str = "KILL";
+pub const LIBRARYNAME_OPTION: &str = "LIBRARYNAME";
+pub const WITHCODE_OPTION: &str = "WITHCODE";
+pub const FUNCTION_ENGINE_LUA: &str = "lua";
+// redis.register_function의 flags에 줄 수 있는 값, no-writes인 함수만 FCALL_RO로 부를 수 있음
+pub const FUNCTION_NO_WRITES_FLAG: &str = "no-writes";
+pub const FUNCTION_FLAGS: [&str; 5] = [FUNCTION_NO_WRITES_FLAG, "allow-oom", "allow-stale", "no-cluster", "allow-cross-slot-keys"];
+
+pub const SYNC_OPTION: &str = "SYNC";
+pub const ASYNC_OPTION: &str = "ASYNC";
+pub const DEBUG_REPORT_OPTION: &str = "REPORT";
+pub const DEBUG_PROTOCOL_OPTION: &str = "PROTOCOL";
+pub const DEBUG_SLEEP_OPTION: &str = "SLEEP";
+pub const DEBUG_SET_ACTIVE_EXPIRE_OPTION: &str = "SET-ACTIVE-EXPIRE";
+pub const DEBUG_OBJECT_OPTION: &str = "OBJECT";
+pub const DEBUG_CHANGE_REPL_ID_OPTION: &str = "CHANGE-REPL-ID";
+pub const DEBUG_RELOAD_OPTION: &str = "RELOAD";
+pub const LATENCY_LATEST_OPTION: &str = "LATEST";
+pub const LATENCY_HISTORY_OPTION: &str = "HISTORY";
+pub const LATENCY_RESET_OPTION: &str = "RESET";
+pub const LATENCY_DOCTOR_OPTION: &str = "DOCTOR";
+pub const MEMORY_USAGE_OPTION: &str = "USAGE";
+pub const MEMORY_STATS_OPTION: &str = "STATS";
+pub const MEMORY_DOCTOR_OPTION: &str = "DOCTOR";
+pub const SAMPLES_OPTION: &str = "SAMPLES";
+pub const REPLACE_OPTION: &str = "REPLACE";
+pub const ABSTTL_OPTION: &str = "ABSTTL";
+pub const MIGRATE_COPY_OPTION: &str = "COPY";
+pub const MIGRATE_AUTH_OPTION: &str = "AUTH";
+pub const MIGRATE_AUTH2_OPTION: &str = "AUTH2";
+pub const MIGRATE_KEYS_OPTION: &str = "KEYS";
+
+pub const OBJECT_ENCODING_OPTION: &str = "ENCODING";
+pub const OBJECT_IDLETIME_OPTION: &str = "IDLETIME";
+pub const OBJECT_FREQ_OPTION: &str = "FREQ";
+pub const OBJECT_REFCOUNT_OPTION: &str = "REFCOUNT";
+pub const OBJECT_HELP_OPTION: &str = "HELP";
+// OBJECT HELP의 응답, Redis처럼 한 줄씩 status 문자열로 보냄
+pub const OBJECT_HELP_LINES: &[&str] = &[
+    "OBJECT <subcommand> [<arg> [value] [opt] ...]. Subcommands are:",
+    "ENCODING <key>",
+    "    Return the kind of internal representation used in order to store the value",
+    "    associated with a <key>.",
+    "FREQ <key>",
+    "    Return the access frequency index of the <key>. The returned integer is",
+    "    proportional to the logarithm of the recent access frequency of the key.",
+    "IDLETIME <key>",
+    "    Return the idle time of the <key>, that is the approximated number of",
+    "    seconds elapsed since the last access to the key.",
+    "REFCOUNT <key>",
+    "    Return the number of references of the value associated with the specified",
+    "    <key>.",
+    "HELP",
+    "    Prints this help.",
+];
+
+pub const REPLCONF_LISTENING_PORT: &str = "listening-port";
+pub const REPLCONF_IP_ADDRESS: &str = "ip-address";
+pub const REPLCONF_CAPA: &str = "capa";
+pub const REPLCONF_GETACK: &str = "GETACK";
+pub const REPLCONF_ACK: &str = "ACK";
+
+pub const INFO_SECTION_ALL: &str = "all";
+pub const INFO_SECTION_DEFAULT: &str = "default";
+pub const INFO_SECTION_EVERYTHING: &str = "everything";
+pub const INFO_SECTION_SERVER: &str = "server";
+pub const INFO_SECTION_CLIENTS: &str = "clients";
+pub const INFO_SECTION_REPLICATION: &str = "replication";
+pub const INFO_SECTION_STATS: &str = "stats";
+pub const INFO_SECTION_COMMANDSTATS: &str = "commandstats";
+pub const INFO_SECTION_LATENCYSTATS: &str = "latencystats";
+pub const INFO_SECTION_MEMORY: &str = "memory";
+pub const INFO_SECTION_PERSISTENCE: &str = "persistence";
+pub const INFO_SECTION_CLUSTER: &str = "cluster";
+pub const INFO_SECTION_SENTINEL: &str = "sentinel";
+pub const INFO_SECTION_KEYSPACE: &str = "keyspace";
+
+pub const SERVER_EVENTS_CHANNEL: &str = "__server__:events";
+pub const INVALIDATE_CHANNEL: &str = "__redis__:invalidate";
+pub const SENTINEL_HELLO_CHANNEL: &str = "__sentinel__:hello";
+
+pub const SET_EVENT: &str = "set";
+pub const INCRBY_EVENT: &str = "incrby";
+pub const DEL_EVENT: &str = "del";
+pub const EXPIRE_EVENT: &str = "expire";
+pub const PERSIST_EVENT: &str = "persist";
+pub const RESTORE_EVENT: &str = "restore";
+pub const HSET_EVENT: &str = "hset";
+pub const HDEL_EVENT: &str = "hdel";
+pub const HEXPIRE_EVENT: &str = "hexpire";
+pub const HPERSIST_EVENT: &str = "hpersist";
+pub const LPUSH_EVENT: &str = "lpush";
+pub const RPUSH_EVENT: &str = "rpush";
+pub const LPOP_EVENT: &str = "lpop";
+pub const RPOP_EVENT: &str = "rpop";
+pub const ZADD_EVENT: &str = "zadd";
+pub const ZINCR_EVENT: &str = "zincr";
+pub const ZPOPMIN_EVENT: &str = "zpopmin";
+pub const ZPOPMAX_EVENT: &str = "zpopmax";
+pub const EXPIRED_EVENT: &str = "expired";
+pub const HEXPIRED_EVENT: &str = "hexpired";
+pub const EVICTED_EVENT: &str = "evicted";
 
 pub const OPCODE_START_DB: u8 = 0xFE;
-#[allow(dead_code)]
 pub const OPCODE_EXPIRETIME_MS: u8 = 0xFC;
 #[allow(dead_code)]
 pub const OPCODE_EXPIRETIME_S: u8 = 0xFD;
 pub const OPCODE_META: u8 = 0xFA;
+// 재시작 뒤에도 replid와 offset을 이어 쓰도록 RDB 메타데이터에 남기는 항목
+pub const AUX_REPL_STREAM_DB: &str = "repl-stream-db";
+pub const AUX_REPL_ID: &str = "repl-id";
+pub const AUX_REPL_OFFSET: &str = "repl-offset";
 
-#[allow(dead_code)]
+// 라이브러리 하나의 코드, Redis 7의 RDB_OPCODE_FUNCTION2
+pub const OPCODE_FUNCTION: u8 = 0xF5;
 pub const OPCODE_SIZE: u8 = 0xFB;
 pub const OPCODE_EOF: u8 = 0xFF;
-#[allow(dead_code)]
 pub const OPCODE_STRING: u8 = 0x00;
-#[allow(dead_code)]
 pub const OPCODE_LIST: u8 = 0x01;
-#[allow(dead_code)]
+pub const OPCODE_SET: u8 = 0x02;
+pub const OPCODE_ZSET: u8 = 0x03;
 pub const OPCODE_HASH: u8 = 0x04;
+pub const OPCODE_ZSET_2: u8 = 0x05;
+pub const OPCODE_HASH_ZIPMAP: u8 = 0x09;
+pub const OPCODE_LIST_ZIPLIST: u8 = 0x0A;
+pub const OPCODE_SET_INTSET: u8 = 0x0B;
+pub const OPCODE_ZSET_ZIPLIST: u8 = 0x0C;
+pub const OPCODE_HASH_ZIPLIST: u8 = 0x0D;
+pub const OPCODE_LIST_QUICKLIST: u8 = 0x0E;
+pub const OPCODE_HASH_LISTPACK: u8 = 0x10;
+pub const OPCODE_ZSET_LISTPACK: u8 = 0x11;
+pub const OPCODE_LIST_QUICKLIST_2: u8 = 0x12;
+pub const OPCODE_SET_LISTPACK: u8 = 0x14;
+// 필드별 TTL이 있는 해시, Redis 7.4의 RDB_TYPE_HASH_METADATA
+pub const OPCODE_HASH_METADATA: u8 = 0x18;
 pub const MAGIC_NUMBER: &[u8] = b"REDIS";
 
+// Redis 기본값: bulk string 하나는 512MB(proto-max-bulk-len), 개행 없는 인라인 요청은 64KB까지
+pub const DEFAULT_PROTO_MAX_BULK_LEN: usize = 512 * 1024 * 1024;
+pub const MIN_PROTO_MAX_BULK_LEN: u64 = 1024 * 1024;
+pub const DEFAULT_TCP_BACKLOG: u32 = 511;
+pub const PROTO_INLINE_MAX_SIZE: usize = 64 * 1024;
+
 // Error messages
 pub const EMPTY_MESSAGE_ERROR: &str = "Empty message";
-pub const INVALID_ARRAY_SIZE_ERROR: &str = "Invalid array size";
+pub const INVALID_MULTIBULK_LENGTH_ERROR: &str = "Protocol error: invalid multibulk length";
 pub const MISSING_BULK_LENGTH_ERROR: &str = "Missing bulk length";
-pub const INVALID_BULK_STRING_FORMAT_ERROR: &str = "Invalid bulk string format";
-pub const INVALID_BULK_LENGTH_ERROR: &str = "Invalid bulk length";
+pub const EXPECTED_BULK_PREFIX_ERROR: &str = "Protocol error: expected '$'";
+pub const INVALID_BULK_LENGTH_ERROR: &str = "Protocol error: invalid bulk length";
 pub const MISSING_BULK_STRING_ERROR: &str = "Missing bulk string";
-pub const BULK_STRING_LENGTH_MISMATCH_ERROR: &str = "Bulk string length mismatch";
+pub const BULK_STRING_LENGTH_MISMATCH_ERROR: &str = "Protocol error: bulk string length mismatch";
 pub const EMPTY_COMMAND_ERROR: &str = "Empty command";
 pub const UNSUPPORTED_PROTOCOL_ERROR: &str = "Unsupported protocol type";
-pub const UNKNOWN_COMMAND_ERROR: &str = "Unknown command";
+pub const UNBALANCED_QUOTES_ERROR: &str = "Protocol error: unbalanced quotes in request";
+pub const INLINE_REQUEST_TOO_BIG_ERROR: &str = "Protocol error: too big inline request";
+pub const WRONG_ARITY_ERROR: &str = "wrong number of arguments for";
+pub const UNKNOWN_SUBCOMMAND_ERROR: &str = "unknown subcommand";
+pub const UNKNOWN_COMMAND_ERROR: &str = "unknown command";
+// 알 수 없는 명령 에러에 되돌려 주는 명령 이름과 인자 목록의 최대 길이, Redis와 같음
+pub const UNKNOWN_COMMAND_ECHO_LIMIT: usize = 128;
 
-pub const ARGUMENT_ERROR: &str = "Argument Error";
-pub const SET_ARGUMENTS_ERROR: &str = "SET requires at least key and value arguments";
 pub const UNKNOWN_OPTION_ERROR: &str = "Unknown option";
-pub const INVALID_OPTION_VALUE_ERROR: &str = "Invalid option value";
 pub const OPTION_ARGUMENT_MISSING_ERROR: &str = "Option requires an argument";
 
-pub const CONFIG_ARGUMENTS_ERROR: &str = "CONFIG subcommand requires at least 2 arguments";
-pub const UNSUPPORTED_CONFIG_SUBCOMMAND_ERROR: &str = "Unsupported CONFIG subcommand";
+pub const UNKNOWN_CONFIG_SET_OPTION_ERROR: &str = "Unknown option or number of arguments for CONFIG SET";
+pub const CONFIG_SET_FAILED_ERROR: &str = "CONFIG SET failed";
+pub const DUPLICATE_CONFIG_PARAMETER_ERROR: &str = "duplicate parameter";
+pub const INVALID_SAVE_PARAMS_ERROR: &str = "Invalid save parameters";
+
+pub const LFU_NOT_SELECTED_ERROR: &str = "An LFU maxmemory policy is not selected, access frequency not tracked. Please note that when switching between policies at runtime LRU and LFU data will take some time to adjust.";
+pub const LFU_SELECTED_ERROR: &str = "An LFU maxmemory policy is selected, idle time not tracked. Please note that when switching between policies at runtime LRU and LFU data will take some time to adjust.";
+pub const DUMP_PAYLOAD_ERROR: &str = "DUMP payload version or checksum are wrong";
+pub const BAD_DATA_FORMAT_ERROR: &str = "Bad data format";
+pub const INVALID_TTL_ERROR: &str = "Invalid TTL value, must be >= 0";
+pub const NO_SUCH_KEY_ERROR: &str = "no such key";
+pub const DEBUG_PROTOCOL_TYPE_ERROR: &str = "Wrong protocol type name. Please use one of the following: string|integer|double|bignum|null|array|set|map|push|true|false";
+pub const RESERVED_CHANNEL_ERROR: &str = "channel is reserved for server events";
+pub const SYNTAX_ERROR: &str = "syntax error";
+pub const INVALID_CURSOR_ERROR: &str = "invalid cursor";
+pub const NOT_AN_INTEGER_ERROR: &str = "value is not an integer or out of range";
+pub const INCR_OVERFLOW_ERROR: &str = "increment or decrement would overflow";
+pub const UNSUPPORTED_OPTION_ERROR: &str = "Unsupported option";
+pub const NX_INCOMPATIBLE_ERROR: &str = "NX and XX, GT or LT options at the same time are not compatible";
+pub const FIELDS_MISSING_ERROR: &str = "Mandatory argument FIELDS is missing or not at the right position";
+pub const NUMFIELDS_ZERO_ERROR: &str = "Parameter `numFields` should be greater than 0";
+pub const NUMFIELDS_MISMATCH_ERROR: &str = "The `numfields` parameter must match the number of arguments";
+pub const INVALID_FIELD_EXPIRE_ERROR: &str = "invalid expire time, must be >= 0 and <= 2^48";
+pub const BGSAVE_IN_PROGRESS_ERROR: &str = "Background save already in progress";
+pub const SHUTDOWN_ERROR: &str = "Errors trying to SHUTDOWN. Check logs.";
+pub const BGSAVE_STARTED_REPLY: &str = "Background saving started";
+pub const RESET_REPLY: &str = "RESET";
+pub const WAIT_ON_REPLICA_ERROR: &str = "WAIT cannot be used with replica instances";
+pub const TIMEOUT_NOT_FLOAT_ERROR: &str = "timeout is not a float or out of range";
+pub const TIMEOUT_NEGATIVE_ERROR: &str = "timeout is negative";
+pub const TIMEOUT_OUT_OF_RANGE_ERROR: &str = "timeout is out of range";
+pub const NOT_A_FLOAT_ERROR: &str = "value is not a valid float";
+pub const ZADD_INCR_PAIR_ERROR: &str = "INCR option supports a single increment-element pair";
+pub const SCORE_NAN_ERROR: &str = "resulting score is not a number (NaN)";
+pub const NUMKEYS_NOT_POSITIVE_ERROR: &str = "numkeys should be greater than 0";
+pub const COUNT_NOT_POSITIVE_ERROR: &str = "count should be greater than 0";
+pub const VALUE_NOT_POSITIVE_ERROR: &str = "value is out of range, must be positive";
+pub const VALUE_OUT_OF_RANGE_ERROR: &str = "value is out of range";
+pub const LCS_LEN_AND_IDX_ERROR: &str = "If you want both the length and indexes, please just use IDX.";
+pub const LCS_NOT_STRING_ERROR: &str = "The specified keys must contain string values";
+pub const LCS_MEMORY_ERROR: &str = "Insufficient memory, transient memory for LCS exceeds proto-max-bulk-len";
+pub const GT_LT_INCOMPATIBLE_ERROR: &str = "GT and LT options at the same time are not compatible";
+
+pub const DELETE_DEFAULT_USER_ERROR: &str = "The 'default' user cannot be removed";
+pub const UNKNOWN_CLIENT_TYPE_ERROR: &str = "Unknown client type";
+pub const NO_SUCH_CLIENT_ERROR: &str = "No such client";
+pub const CLIENT_ID_ERROR: &str = "client-id should be greater than 0";
+pub const PREFIX_REQUIRES_BCAST_ERROR: &str = "PREFIX option requires BCAST mode to be enabled";
+pub const REDIRECT_CLIENT_MISSING_ERROR: &str = "The client ID you want redirect to does not exist";
+pub const OPTIN_AND_OPTOUT_ERROR: &str = "You can't use both OPTIN and OPTOUT.";
+pub const OPTIN_OPTOUT_WITH_BCAST_ERROR: &str = "OPTIN and OPTOUT are not compatible with BCAST.";
+pub const CACHING_WITHOUT_OPT_MODE_ERROR: &str = "CLIENT CACHING can be called only when the client is in tracking mode with OPTIN or OPTOUT mode enabled";
+pub const CACHING_YES_WITHOUT_OPTIN_ERROR: &str = "CLIENT CACHING YES is only valid when tracking is enabled in OPTIN mode.";
+pub const CACHING_NO_WITHOUT_OPTOUT_ERROR: &str = "CLIENT CACHING NO is only valid when tracking is enabled in OPTOUT mode.";
+pub const MULTI_NESTED_ERROR: &str = "MULTI calls can not be nested";
+pub const EXEC_WITHOUT_MULTI_ERROR: &str = "EXEC without MULTI";
+pub const DISCARD_WITHOUT_MULTI_ERROR: &str = "DISCARD without MULTI";
+pub const WATCH_INSIDE_MULTI_ERROR: &str = "WATCH inside MULTI is not allowed";
+
+pub const CLUSTER_DISABLED_ERROR: &str = "This instance has cluster support disabled";
+pub const REPLICAOF_IN_CLUSTER_ERROR: &str = "REPLICAOF not allowed in cluster mode.";
+pub const MIGRATE_KEYS_WITH_KEY_ERROR: &str = "When using MIGRATE KEYS option, the key argument must be set to the empty string";
+pub const DB_INDEX_OUT_OF_RANGE_ERROR: &str = "DB index is out of range";
+pub const MIGRATE_CONNECT_ERROR: &str = "error or timeout connecting to the client";
+pub const MIGRATE_IO_ERROR: &str = "error or timeout reading to target instance";
+pub const MIGRATE_NOKEY_REPLY: &str = "NOKEY";
+pub const INVALID_SLOT_ERROR: &str = "Invalid or out of range slot";
+pub const INVALID_KEY_COUNT_ERROR: &str = "Invalid number of keys";
+
+pub const SENTINEL_DISABLED_ERROR: &str = "This instance is not running in sentinel mode";
+pub const SENTINEL_MODE_COMMAND_ERROR: &str = "This command is not available in sentinel mode";
+pub const PROTOCOL_VERSION_ERROR: &str = "Protocol version is not an integer or out of range";
+pub const INVALID_CLIENT_NAME_ERROR: &str = "Client names cannot contain spaces, newlines or special characters.";
+pub const AUTH_WITHOUT_PASSWORD_ERROR: &str = "AUTH <password> called without any password configured for the default user. Are you sure your configuration is correct?";
+pub const NO_SUCH_MASTER_ERROR: &str = "No such master with that name";
+
+pub const FUNCTION_MISSING_SHEBANG_ERROR: &str = "Missing library metadata";
+pub const FUNCTION_LIBRARY_NAME_MISSING_ERROR: &str = "Library name was not given";
+pub const LIBRARY_INVALID_NAME_ERROR: &str = "Library names can only contain letters, numbers, or underscores(_) and must be at least one character long";
+pub const FUNCTION_INVALID_NAME_ERROR: &str = "Function names can only contain letters, numbers, or underscores(_) and must be at least one character long";
+pub const FUNCTION_NOTHING_REGISTERED_ERROR: &str = "No functions registered";
+pub const LIBRARY_NOT_FOUND_ERROR: &str = "Library not found";
+pub const FUNCTION_COMPILE_ERROR: &str = "Error compiling function";
+pub const FUNCTION_REGISTER_ERROR: &str = "Error registering functions";
+pub const FUNCTION_UNKNOWN_FLAG_ERROR: &str = "unknown flag given";
+pub const FUNCTION_NOT_FOUND_ERROR: &str = "Function not found";
+pub const FUNCTION_READ_ONLY_ERROR: &str = "Can not execute a script with write flag using *_ro command.";
+pub const SCRIPT_READ_ONLY_WRITE_ERROR: &str = "Write commands are not allowed from read-only scripts.";
+pub const SCRIPT_NEGATIVE_KEYS_ERROR: &str = "Number of keys can't be negative";
+pub const SCRIPT_TOO_MANY_KEYS_ERROR: &str = "Number of keys can't be greater than number of args";
+pub const SCRIPT_COMPILE_ERROR: &str = "Error compiling script (new function)";
+pub const SCRIPT_NO_ARGUMENTS_ERROR: &str = "Please specify at least one argument for this redis lib call";
+pub const SCRIPT_ARGUMENT_TYPE_ERROR: &str = "Lua redis lib command arguments must be strings or integers";
+pub const SCRIPT_UNKNOWN_COMMAND_ERROR: &str = "Unknown Redis command called from script";
+pub const SCRIPT_WRONG_ARITY_ERROR: &str = "Wrong number of args calling Redis command from script";
+pub const SCRIPT_COMMAND_NOT_ALLOWED_ERROR: &str = "This Redis command is not allowed from script";
+pub const SCRIPT_NON_LOCAL_KEY_ERROR: &str = "Script attempted to access a non local key in a cluster node";
+pub const SCRIPT_CROSS_SLOT_ERROR: &str = "Script attempted to access keys that do not hash to the same slot";
+pub const SCRIPT_STACK_LIMIT_ERROR: &str = "reached lua stack limit";
+pub const SCRIPT_ABORTED_ERROR: &str = "The script run was interrupted";
+pub const SCRIPT_KILLED_ERROR: &str = "Script killed by user with SCRIPT KILL...";
+pub const FUNCTION_KILLED_ERROR: &str = "Script killed by user with FUNCTION KILL...";
+pub const FUNCTION_LOAD_TIMEOUT_ERROR: &str = "FUNCTION LOAD timeout";
+
+pub const REPLICAOF_ARGUMENTS_ERROR: &str = "REPLICAOF requires either 'NO ONE' or a host and port";
 
-pub const UNSUPPORTED_PATTERN_ERROR: &str = "Unsupported KEY command args pattern";
\ No newline at end of file
@@ -0,0 +1,124 @@
+use crate::protocol_constants::*;
+use crate::resp::RespValue;
+use crate::util::{glob_match, key_hash_slot};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+// 구독 응답과 메시지는 RESP3에서 일반 응답과 구분되는 push 타입으로 보냄
+fn push(items: &[&[u8]]) -> RespValue {
+    RespValue::Push(items.iter().map(|item| RespValue::bulk(*item)).collect())
+}
+
+// subscribe/unsubscribe 확인 응답, 구독 중인 채널이 없을 때의 unsubscribe는 채널이 nil
+pub fn subscription_reply(kind: &str, channel: Option<&str>, count: usize) -> RespValue {
+    RespValue::Push(vec![
+        RespValue::bulk(kind),
+        channel.map_or(RespValue::NullBulk, RespValue::bulk),
+        RespValue::Integer(count as i64),
+    ])
+}
+
+pub fn message_reply(channel: &str, message: &[u8]) -> RespValue {
+    push(&[b"message", channel.as_bytes(), message])
+}
+
+pub fn pmessage_reply(pattern: &str, channel: &str, message: &[u8]) -> RespValue {
+    push(&[b"pmessage", pattern.as_bytes(), channel.as_bytes(), message])
+}
+
+// PING에 메시지가 없으면 두 번째 요소는 빈 문자열
+pub fn subscribed_pong_reply(message: Option<&[u8]>) -> RespValue {
+    RespValue::Array(vec![RespValue::bulk("pong"), RespValue::bulk(message.unwrap_or_default())])
+}
+
+// RESP2에서는 __redis__:invalidate 채널의 message, RESP3에서는 invalidate push, 키 목록이 nil이면 전체 무효화
+pub fn invalidate_reply(keys: Option<&[Vec<u8>]>, protocol: u8) -> RespValue {
+    let keys = keys.map_or(RespValue::NullArray, RespValue::bulk_array);
+    if protocol == RESP3_PROTOCOL {
+        return RespValue::Push(vec![RespValue::bulk("invalidate"), keys]);
+    }
+    RespValue::Push(vec![RespValue::bulk("message"), RespValue::bulk(INVALIDATE_CHANNEL), keys])
+}
+
+pub fn smessage_reply(channel: &str, message: &[u8]) -> RespValue {
+    push(&[b"smessage", channel.as_bytes(), message])
+}
+
+// 샤드 채널 레지스트리: 클러스터 모드에서 슬롯 단위로 넘길 수 있도록 해시 슬롯별로 나눠 보관함
+pub struct ShardChannels {
+    slots: BTreeMap<u16, HashMap<String, HashSet<u64>>>,
+}
+
+impl ShardChannels {
+    pub fn new() -> Self {
+        Self {
+            slots: BTreeMap::new(),
+        }
+    }
+
+    pub fn subscribe(&mut self, client_id: u64, channel: &str) {
+        self.slots
+            .entry(key_hash_slot(channel.as_bytes()))
+            .or_default()
+            .entry(channel.to_string())
+            .or_default()
+            .insert(client_id);
+    }
+
+    pub fn unsubscribe(&mut self, client_id: u64, channel: &str) {
+        let slot = key_hash_slot(channel.as_bytes());
+        let Some(channels) = self.slots.get_mut(&slot) else {
+            return;
+        };
+        if let Some(subscribers) = channels.get_mut(channel) {
+            subscribers.remove(&client_id);
+            if subscribers.is_empty() {
+                channels.remove(channel);
+            }
+        }
+        if channels.is_empty() {
+            self.slots.remove(&slot);
+        }
+    }
+
+    pub fn remove_client(&mut self, client_id: u64) {
+        for channel in self.channels_of(client_id) {
+            self.unsubscribe(client_id, &channel);
+        }
+    }
+
+    pub fn subscribers(&self, channel: &str) -> Vec<u64> {
+        self.slots
+            .get(&key_hash_slot(channel.as_bytes()))
+            .and_then(|channels| channels.get(channel))
+            .map(|subscribers| subscribers.iter().copied().collect())
+            .unwrap_or_default()
+    }
+
+    pub fn channels_of(&self, client_id: u64) -> Vec<String> {
+        self.slots
+            .values()
+            .flat_map(|channels| channels.iter())
+            .filter(|(_, subscribers)| subscribers.contains(&client_id))
+            .map(|(channel, _)| channel.clone())
+            .collect()
+    }
+
+    pub fn count_for(&self, client_id: u64) -> usize {
+        self.slots
+            .values()
+            .flat_map(|channels| channels.values())
+            .filter(|subscribers| subscribers.contains(&client_id))
+            .count()
+    }
+
+    pub fn channels(&self, pattern: Option<&str>) -> Vec<&String> {
+        let mut channels: Vec<&String> = self
+            .slots
+            .values()
+            .flat_map(|channels| channels.keys())
+            .filter(|channel| pattern.map_or(true, |pattern| glob_match(pattern.as_bytes(), channel.as_bytes())))
+            .collect();
+        channels.sort();
+        channels
+    }
+}
@@ -0,0 +1,95 @@
+use rand::rngs::StdRng;
+use rand::{RngCore, SeedableRng};
+use std::collections::HashMap;
+use std::process;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const ALPHANUMERIC: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+const HEX_DIGITS: &[u8] = b"0123456789abcdef";
+// sample_indices가 미리 잡아 두는 최대 크기
+const SAMPLE_PREALLOCATED: usize = 1024;
+
+// 서버마다 하나씩 두는 난수 생성기, 무작위 값은 모두 여기서 나옴
+// 테스트에서는 debug-random-seed로 시드를 고정해 결과를 재현할 수 있음, 한 프로세스의 다른 서버에는 영향이 없음
+pub struct Random {
+    rng: Mutex<StdRng>,
+    seeded: AtomicBool,
+}
+
+impl Default for Random {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Random {
+    pub fn new() -> Self {
+        Self {
+            rng: Mutex::new(StdRng::seed_from_u64(default_seed())),
+            seeded: AtomicBool::new(false),
+        }
+    }
+
+    pub fn set_seed(&self, seed: u64) {
+        *self.rng.lock().unwrap() = StdRng::seed_from_u64(seed);
+        self.seeded.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_seeded(&self) -> bool {
+        self.seeded.load(Ordering::Relaxed)
+    }
+
+    pub fn next_u64(&self) -> u64 {
+        self.rng.lock().unwrap().next_u64()
+    }
+
+    pub fn next_f64(&self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    pub fn below(&self, upper: usize) -> usize {
+        (self.next_u64() % upper as u64) as usize
+    }
+
+    pub fn alphanumeric(&self, len: usize) -> String {
+        (0..len).map(|_| ALPHANUMERIC[self.below(ALPHANUMERIC.len())] as char).collect()
+    }
+
+    pub fn hex(&self, len: usize) -> String {
+        (0..len).map(|_| HEX_DIGITS[self.below(HEX_DIGITS.len())] as char).collect()
+    }
+
+    // HRANDFIELD, ZRANDMEMBER의 count 규칙으로 0..len에서 위치를 고름
+    // 양수면 겹치지 않게 최대 count개, 음수면 같은 위치가 여러 번 나올 수 있게 정확히 -count개
+    // 음수 count의 크기는 파서가 RANDOM_COUNT_LIMIT로 막아 둠
+    pub fn sample_indices(&self, len: usize, count: i64) -> Vec<usize> {
+        if len == 0 {
+            return Vec::new();
+        }
+        if count < 0 {
+            return (0..count.unsigned_abs()).map(|_| self.below(len)).collect();
+        }
+        // 앞에서부터 count개만 섞는 Fisher-Yates, 자리를 바꾼 위치만 기록해서 len이 커도 count만큼만 일함
+        let count = (count as u64).min(len as u64) as usize;
+        let mut swapped: HashMap<usize, usize> = HashMap::with_capacity(count.min(SAMPLE_PREALLOCATED) * 2);
+        let mut indices = Vec::with_capacity(count.min(SAMPLE_PREALLOCATED));
+        for i in 0..count {
+            let j = i + self.below(len - i);
+            let picked = swapped.get(&j).copied().unwrap_or(j);
+            let displaced = swapped.get(&i).copied().unwrap_or(i);
+            swapped.insert(j, displaced);
+            indices.push(picked);
+        }
+        indices
+    }
+}
+
+fn default_seed() -> u64 {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_nanos() as u64)
+        .unwrap_or(0);
+    nanos ^ ((process::id() as u64) << 32)
+}
@@ -0,0 +1,101 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::time::{Duration, Instant};
+
+// 서버마다 하나, 읽기 태스크가 설정 맵을 보지 않으므로 CONFIG SET 때 여기 값을 바꿈
+// 0이면 제한 없음, 연결마다 따로 셈
+#[derive(Debug, Default)]
+pub struct RateLimits {
+    max_commands_per_sec: AtomicU64,
+    max_input_bytes_per_sec: AtomicU64,
+    // 제한 때문에 읽기나 명령 전달을 미룬 횟수, INFO stats에서 보여 줌
+    throttled: AtomicU64,
+}
+
+impl RateLimits {
+    pub fn set(&self, commands_per_sec: u64, input_bytes_per_sec: u64) {
+        self.max_commands_per_sec.store(commands_per_sec, Ordering::Relaxed);
+        self.max_input_bytes_per_sec.store(input_bytes_per_sec, Ordering::Relaxed);
+    }
+
+    pub fn throttled_count(&self) -> u64 {
+        self.throttled.load(Ordering::Relaxed)
+    }
+}
+
+// 1초 분량까지 모아 둘 수 있는 토큰 버킷, 모자라면 빚을 지고 갚을 때까지 기다림
+// 에러를 돌려주는 대신 기다리게 하므로 클라이언트는 느려질 뿐 요청을 잃지 않음
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+// 처음에는 1초 분량이 가득 찬 상태, refill에서 제한 값으로 줄어듦
+impl Default for Bucket {
+    fn default() -> Self {
+        Self { tokens: f64::INFINITY, refilled_at: Instant::now() }
+    }
+}
+
+impl Bucket {
+    fn refill(&mut self, rate: u64) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.refilled_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * rate as f64).min(rate as f64);
+        self.refilled_at = now;
+    }
+
+    // 빚을 다 갚을 때까지 남은 시간
+    fn delay(&mut self, rate: u64) -> Option<Duration> {
+        if rate == 0 {
+            self.tokens = f64::INFINITY;
+            return None;
+        }
+        self.refill(rate);
+        (self.tokens < 0.0).then(|| Duration::from_secs_f64(-self.tokens / rate as f64))
+    }
+
+    fn consume(&mut self, rate: u64, amount: u64) {
+        if rate > 0 {
+            self.refill(rate);
+            self.tokens -= amount as f64;
+        }
+    }
+}
+
+// 연결의 읽기 태스크가 하나씩 가짐, 이벤트 루프에 넘기기 전에 기다리게 하므로 한 클라이언트가 이벤트 큐를 차지하지 못함
+#[derive(Debug)]
+pub struct RateLimiter {
+    limits: Arc<RateLimits>,
+    commands: Bucket,
+    input: Bucket,
+}
+
+impl RateLimiter {
+    pub fn new(limits: Arc<RateLimits>) -> Self {
+        Self { limits, commands: Bucket::default(), input: Bucket::default() }
+    }
+
+    // 읽기 전에 부름, 앞서 읽은 바이트만큼의 빚을 갚을 때까지 더 읽지 않음
+    pub async fn wait_for_input(&mut self) {
+        if let Some(delay) = self.input.delay(self.limits.max_input_bytes_per_sec.load(Ordering::Relaxed)) {
+            self.limits.throttled.fetch_add(1, Ordering::Relaxed);
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    pub fn input_read(&mut self, len: usize) {
+        self.input.consume(self.limits.max_input_bytes_per_sec.load(Ordering::Relaxed), len as u64);
+    }
+
+    // 명령을 이벤트 큐에 올리기 전에 부름
+    pub async fn acquire_command(&mut self) {
+        let rate = self.limits.max_commands_per_sec.load(Ordering::Relaxed);
+        self.commands.consume(rate, 1);
+        if let Some(delay) = self.commands.delay(rate) {
+            self.limits.throttled.fetch_add(1, Ordering::Relaxed);
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
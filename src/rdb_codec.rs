@@ -0,0 +1,475 @@
+use crate::command::format_score;
+use crate::lzf;
+use crate::persistence::SnapshotEntry;
+use crate::protocol_constants::*;
+use crate::rdb_encoding;
+use crate::server_info::SERVER_VERSION;
+use crate::util::parse_bytes;
+use crate::value_encoding::{HashValue, ListValue, ListpackLimits, StringValue, ZSetValue};
+use crate::value_entry::{FieldExpirations, RedisValue};
+use byteorder::{BigEndian, LittleEndian, ReadBytesExt};
+use crc::{Crc, CRC_64_REDIS};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::io::{self, Cursor, Read};
+
+// DUMP payload 끝에 붙는 RDB 버전, RESTORE는 이보다 새로운 버전을 거부함
+pub const RDB_VERSION: u16 = 11;
+
+const RDB_ENCODING_INT8: u8 = 0;
+const RDB_ENCODING_INT16: u8 = 1;
+const RDB_ENCODING_INT32: u8 = 2;
+const RDB_ENCODING_LZF: u8 = 3;
+const RDB_ENCODING_SPECIAL: u8 = 0xC0;
+// Redis처럼 20바이트 이하의 문자열은 압축하지 않음
+const LZF_MIN_STRING_LEN: usize = 20;
+// quicklist 2 노드: PLAIN은 큰 원소 하나, PACKED는 listpack
+const QUICKLIST_NODE_PLAIN: usize = 1;
+const QUICKLIST_NODE_PACKED: usize = 2;
+const QUICKLIST_NODE_ENTRIES: usize = 128;
+const RDB_LENGTH_32BIT: u8 = 0x80;
+const RDB_LENGTH_64BIT: u8 = 0x81;
+// RESTORE payload와 RDB 파일의 길이 값은 믿을 수 없으므로 미리 잡는 용량은 여기까지만 잡고 나머지는 읽으면서 늘림
+const MAX_PREALLOCATION: usize = 4096;
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+pub fn write_length(out: &mut Vec<u8>, len: usize) {
+    if len < 1 << 6 {
+        out.push(len as u8);
+    } else if len < 1 << 14 {
+        out.push(0x40 | (len >> 8) as u8);
+        out.push(len as u8);
+    } else if len <= u32::MAX as usize {
+        out.push(RDB_LENGTH_32BIT);
+        out.extend_from_slice(&(len as u32).to_be_bytes());
+    } else {
+        out.push(RDB_LENGTH_64BIT);
+        out.extend_from_slice(&(len as u64).to_be_bytes());
+    }
+}
+
+pub fn write_string(out: &mut Vec<u8>, value: &str) {
+    write_length(out, value.len());
+    out.extend_from_slice(value.as_bytes());
+}
+
+// 압축 결과가 원본보다 4바이트 이상 작을 때만 LZF로 씀
+fn write_rdb_bytes(out: &mut Vec<u8>, bytes: &[u8], compress: bool) {
+    if compress && bytes.len() > LZF_MIN_STRING_LEN {
+        if let Some(compressed) = lzf::compress(bytes, bytes.len() - 4) {
+            out.push(RDB_ENCODING_SPECIAL | RDB_ENCODING_LZF);
+            write_length(out, compressed.len());
+            write_length(out, bytes.len());
+            out.extend_from_slice(&compressed);
+            return;
+        }
+    }
+    write_length(out, bytes.len());
+    out.extend_from_slice(bytes);
+}
+
+// 작은 컬렉션은 Redis 7과 같은 listpack/intset 인코딩으로, 큰 것은 원소를 하나씩 씀
+pub fn value_type(value: &RedisValue) -> u8 {
+    match (value, value.encoding()) {
+        (RedisValue::String(_), _) => OPCODE_STRING,
+        (RedisValue::List(_), _) => OPCODE_LIST_QUICKLIST_2,
+        (RedisValue::Set(set), "intset") if set.iter().all(|member| rdb_encoding::canonical_integer(member).is_some()) => {
+            OPCODE_SET_INTSET
+        }
+        (RedisValue::Set(_), "intset" | "listpack") => OPCODE_SET_LISTPACK,
+        (RedisValue::Set(_), _) => OPCODE_SET,
+        (RedisValue::Hash(_), "listpack") => OPCODE_HASH_LISTPACK,
+        (RedisValue::Hash(_), _) => OPCODE_HASH,
+        (RedisValue::ZSet(_), "listpack") => OPCODE_ZSET_LISTPACK,
+        (RedisValue::ZSet(_), _) => OPCODE_ZSET_2,
+    }
+}
+
+// 필드 TTL이 하나라도 있는 해시는 Redis 7.4처럼 필드마다 TTL을 붙여 씀
+fn entry_type(value: &RedisValue, field_expirations: &FieldExpirations) -> u8 {
+    match value {
+        RedisValue::Hash(_) if !field_expirations.is_empty() => OPCODE_HASH_METADATA,
+        _ => value_type(value),
+    }
+}
+
+fn write_entry_body(out: &mut Vec<u8>, value: &RedisValue, field_expirations: &FieldExpirations, compress: bool) {
+    let (RedisValue::Hash(hash), Some(&min_expiration_ms)) = (value, field_expirations.values().min()) else {
+        return write_value_body(out, value, compress);
+    };
+    // 가장 이른 만료 시각 뒤에 필드마다 (TTL, 필드, 값), TTL은 가장 이른 시각과의 차이 + 1이고 0은 TTL 없음
+    out.extend_from_slice(&min_expiration_ms.to_le_bytes());
+    write_length(out, hash.len());
+    for (field, value) in hash.iter() {
+        write_length(out, field_expirations.get(field).map_or(0, |ms| (ms - min_expiration_ms + 1) as usize));
+        write_rdb_bytes(out, field, compress);
+        write_rdb_bytes(out, value, compress);
+    }
+}
+
+fn write_value_body(out: &mut Vec<u8>, value: &RedisValue, compress: bool) {
+    match (value, value_type(value)) {
+        (RedisValue::String(value), _) => write_rdb_bytes(out, &value.as_bytes(), compress),
+        (RedisValue::List(list), _) => {
+            let elements: Vec<&[u8]> = list.iter().collect();
+            let nodes: Vec<&[&[u8]]> = elements.chunks(QUICKLIST_NODE_ENTRIES).collect();
+            write_length(out, nodes.len());
+            for node in nodes {
+                write_length(out, QUICKLIST_NODE_PACKED);
+                write_rdb_bytes(out, &rdb_encoding::encode_listpack(node.iter().copied()), compress);
+            }
+        }
+        (RedisValue::Set(set), OPCODE_SET_INTSET) => {
+            let mut members: Vec<i64> = set.iter().filter_map(|member| rdb_encoding::canonical_integer(member)).collect();
+            write_rdb_bytes(out, &rdb_encoding::encode_intset(&mut members), compress);
+        }
+        (RedisValue::Set(set), OPCODE_SET_LISTPACK) => {
+            write_rdb_bytes(out, &rdb_encoding::encode_listpack(set.iter().map(|member| member.as_slice())), compress);
+        }
+        (RedisValue::Set(set), _) => {
+            write_length(out, set.len());
+            set.iter().for_each(|member| write_rdb_bytes(out, member, compress));
+        }
+        (RedisValue::Hash(hash), OPCODE_HASH_LISTPACK) => {
+            let entries = hash.iter().flat_map(|(field, value)| [field, value]);
+            write_rdb_bytes(out, &rdb_encoding::encode_listpack(entries), compress);
+        }
+        (RedisValue::Hash(hash), _) => {
+            write_length(out, hash.len());
+            for (field, value) in hash.iter() {
+                write_rdb_bytes(out, field, compress);
+                write_rdb_bytes(out, value, compress);
+            }
+        }
+        (RedisValue::ZSet(zset), OPCODE_ZSET_LISTPACK) => {
+            // listpack은 점수 오름차순으로 둠
+            let mut members: Vec<(&[u8], f64)> = zset.iter().collect();
+            members.sort_by(|a, b| a.1.total_cmp(&b.1).then_with(|| a.0.cmp(b.0)));
+            let scores: Vec<String> = members.iter().map(|(_, score)| format_score(*score)).collect();
+            let entries = members.iter().zip(&scores).flat_map(|((member, _), score)| [*member, score.as_bytes()]);
+            write_rdb_bytes(out, &rdb_encoding::encode_listpack(entries), compress);
+        }
+        (RedisValue::ZSet(zset), _) => {
+            write_length(out, zset.len());
+            for (member, score) in zset.iter() {
+                write_rdb_bytes(out, member, compress);
+                out.extend_from_slice(&score.to_le_bytes());
+            }
+        }
+    }
+}
+
+// 길이 또는 정수 인코딩을 읽음, 정수 인코딩이면 (값, true)
+pub fn read_length<R: Read>(reader: &mut R) -> io::Result<(u64, bool)> {
+    let first_byte = reader.read_u8()?;
+    match first_byte >> 6 {
+        0b00 => Ok(((first_byte & 0x3F) as u64, false)),
+        0b01 => {
+            let second_byte = reader.read_u8()?;
+            Ok(((((first_byte & 0x3F) as u64) << 8) | second_byte as u64, false))
+        }
+        0b10 => match first_byte {
+            RDB_LENGTH_32BIT => Ok((reader.read_u32::<BigEndian>()? as u64, false)),
+            RDB_LENGTH_64BIT => Ok((reader.read_u64::<BigEndian>()?, false)),
+            _ => Err(invalid_data("Invalid length encoding")),
+        },
+        _ => Ok(((first_byte & 0x3F) as u64, true)),
+    }
+}
+
+// 정수 인코딩은 10진 문자열로, LZF는 풀어서 원래 바이트로 돌려줌
+pub fn read_bytes<R: Read>(reader: &mut R) -> io::Result<Vec<u8>> {
+    match read_length(reader)? {
+        (len, false) => read_exact_len(reader, len),
+        (encoding, true) => match encoding as u8 {
+            RDB_ENCODING_INT8 => Ok(reader.read_i8()?.to_string().into_bytes()),
+            RDB_ENCODING_INT16 => Ok(reader.read_i16::<LittleEndian>()?.to_string().into_bytes()),
+            RDB_ENCODING_INT32 => Ok(reader.read_i32::<LittleEndian>()?.to_string().into_bytes()),
+            RDB_ENCODING_LZF => {
+                let compressed_len = read_collection_len(reader)?;
+                let len = read_collection_len(reader)?;
+                let compressed = read_exact_len(reader, compressed_len as u64)?;
+                lzf::decompress(&compressed, len)
+            }
+            _ => Err(invalid_data("Unsupported string encoding")),
+        },
+    }
+}
+
+// 실제로 읽은 만큼만 할당하므로 남은 데이터보다 큰 길이는 할당 없이 잘못된 데이터로 끝남
+fn read_exact_len<R: Read>(reader: &mut R, len: u64) -> io::Result<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(preallocation(len as usize));
+    reader.take(len).read_to_end(&mut bytes)?;
+    if bytes.len() as u64 != len {
+        return Err(invalid_data("Length exceeds the remaining data"));
+    }
+    Ok(bytes)
+}
+
+pub fn preallocation(len: usize) -> usize {
+    len.min(MAX_PREALLOCATION)
+}
+
+pub fn read_string<R: Read>(reader: &mut R) -> io::Result<String> {
+    Ok(String::from_utf8_lossy(&read_bytes(reader)?).to_string())
+}
+
+fn read_collection_len<R: Read>(reader: &mut R) -> io::Result<usize> {
+    match read_length(reader)? {
+        (len, false) => Ok(len as usize),
+        _ => Err(invalid_data("Invalid collection length")),
+    }
+}
+
+// read_value에 더해 필드별 TTL이 붙은 해시도 읽음, 컬렉션은 불러오는 서버의 한도에 따라 listpack으로 담음
+pub fn read_entry_value<R: Read>(value_type: u8, reader: &mut R, limits: ListpackLimits) -> io::Result<(RedisValue, FieldExpirations)> {
+    if value_type != OPCODE_HASH_METADATA {
+        return Ok((read_value(value_type, reader, limits)?, FieldExpirations::new()));
+    }
+    let min_expiration_ms = reader.read_u64::<LittleEndian>()?;
+    let len = read_collection_len(reader)?;
+    let mut hash = HashMap::with_capacity(preallocation(len));
+    let mut field_expirations = FieldExpirations::new();
+    for _ in 0..len {
+        let ttl = read_length(reader)?.0;
+        let field = read_bytes(reader)?;
+        if ttl != 0 {
+            let expiration_ms = min_expiration_ms.checked_add(ttl - 1).ok_or_else(|| invalid_data("Invalid hash field TTL"))?;
+            field_expirations.insert(field.clone(), expiration_ms);
+        }
+        hash.insert(field, read_bytes(reader)?);
+    }
+    Ok((RedisValue::Hash(HashValue::from_pairs(hash, limits)), field_expirations))
+}
+
+fn read_value<R: Read>(value_type: u8, reader: &mut R, limits: ListpackLimits) -> io::Result<RedisValue> {
+    match value_type {
+        OPCODE_STRING => Ok(RedisValue::String(StringValue::new(read_bytes(reader)?))),
+        OPCODE_LIST => {
+            let len = read_collection_len(reader)?;
+            let mut list = VecDeque::with_capacity(preallocation(len));
+            for _ in 0..len {
+                list.push_back(read_bytes(reader)?);
+            }
+            Ok(RedisValue::List(ListValue::from_elements(list.into(), limits)))
+        }
+        OPCODE_SET => {
+            let len = read_collection_len(reader)?;
+            let mut set = HashSet::with_capacity(preallocation(len));
+            for _ in 0..len {
+                set.insert(read_bytes(reader)?);
+            }
+            Ok(RedisValue::Set(set))
+        }
+        OPCODE_HASH => {
+            let len = read_collection_len(reader)?;
+            let mut hash = HashMap::with_capacity(preallocation(len));
+            for _ in 0..len {
+                let field = read_bytes(reader)?;
+                hash.insert(field, read_bytes(reader)?);
+            }
+            Ok(RedisValue::Hash(HashValue::from_pairs(hash, limits)))
+        }
+        OPCODE_ZSET_2 => {
+            let len = read_collection_len(reader)?;
+            let mut zset = HashMap::with_capacity(preallocation(len));
+            for _ in 0..len {
+                let member = read_bytes(reader)?;
+                zset.insert(member, reader.read_f64::<LittleEndian>()?);
+            }
+            Ok(RedisValue::ZSet(ZSetValue::from_scores(zset, limits)))
+        }
+        OPCODE_ZSET => {
+            let len = read_collection_len(reader)?;
+            let mut zset = HashMap::with_capacity(preallocation(len));
+            for _ in 0..len {
+                let member = read_bytes(reader)?;
+                zset.insert(member, read_string_score(reader)?);
+            }
+            Ok(RedisValue::ZSet(ZSetValue::from_scores(zset, limits)))
+        }
+        OPCODE_HASH_ZIPMAP => pairs_to_hash(rdb_encoding::decode_zipmap(&read_bytes(reader)?)?, limits),
+        OPCODE_LIST_ZIPLIST => Ok(RedisValue::List(ListValue::from_elements(rdb_encoding::decode_ziplist(&read_bytes(reader)?)?, limits))),
+        OPCODE_LIST_QUICKLIST => {
+            let nodes = read_collection_len(reader)?;
+            let mut list = VecDeque::new();
+            for _ in 0..nodes {
+                list.extend(rdb_encoding::decode_ziplist(&read_bytes(reader)?)?);
+            }
+            Ok(RedisValue::List(ListValue::from_elements(list.into(), limits)))
+        }
+        OPCODE_LIST_QUICKLIST_2 => {
+            let nodes = read_collection_len(reader)?;
+            let mut list = VecDeque::new();
+            for _ in 0..nodes {
+                let container = read_collection_len(reader)?;
+                let node = read_bytes(reader)?;
+                match container {
+                    QUICKLIST_NODE_PLAIN => list.push_back(node),
+                    QUICKLIST_NODE_PACKED => list.extend(rdb_encoding::decode_listpack(&node)?),
+                    _ => return Err(invalid_data("Invalid quicklist node container")),
+                }
+            }
+            Ok(RedisValue::List(ListValue::from_elements(list.into(), limits)))
+        }
+        OPCODE_SET_INTSET => Ok(RedisValue::Set(rdb_encoding::decode_intset(&read_bytes(reader)?)?.into_iter().collect())),
+        OPCODE_SET_LISTPACK => Ok(RedisValue::Set(rdb_encoding::decode_listpack(&read_bytes(reader)?)?.into_iter().collect())),
+        OPCODE_HASH_ZIPLIST => pairs_to_hash(rdb_encoding::decode_ziplist(&read_bytes(reader)?)?, limits),
+        OPCODE_HASH_LISTPACK => pairs_to_hash(rdb_encoding::decode_listpack(&read_bytes(reader)?)?, limits),
+        OPCODE_ZSET_ZIPLIST => pairs_to_zset(rdb_encoding::decode_ziplist(&read_bytes(reader)?)?, limits),
+        OPCODE_ZSET_LISTPACK => pairs_to_zset(rdb_encoding::decode_listpack(&read_bytes(reader)?)?, limits),
+        _ => Err(invalid_data(&format!("Unsupported value type 0x{:02X}", value_type))),
+    }
+}
+
+pub fn is_value_type(marker: u8) -> bool {
+    matches!(
+        marker,
+        OPCODE_STRING
+            | OPCODE_LIST
+            | OPCODE_SET
+            | OPCODE_ZSET
+            | OPCODE_HASH
+            | OPCODE_ZSET_2
+            | OPCODE_HASH_ZIPMAP
+            | OPCODE_LIST_ZIPLIST
+            | OPCODE_SET_INTSET
+            | OPCODE_ZSET_ZIPLIST
+            | OPCODE_HASH_ZIPLIST
+            | OPCODE_LIST_QUICKLIST
+            | OPCODE_HASH_LISTPACK
+            | OPCODE_ZSET_LISTPACK
+            | OPCODE_LIST_QUICKLIST_2
+            | OPCODE_SET_LISTPACK
+            | OPCODE_HASH_METADATA
+    )
+}
+
+// 옛 ZSET 타입의 점수: 길이 바이트 뒤의 10진 문자열, 253/254/255는 각각 NaN/+inf/-inf
+fn read_string_score<R: Read>(reader: &mut R) -> io::Result<f64> {
+    match reader.read_u8()? {
+        253 => Ok(f64::NAN),
+        254 => Ok(f64::INFINITY),
+        255 => Ok(f64::NEG_INFINITY),
+        len => {
+            let mut bytes = vec![0; len as usize];
+            reader.read_exact(&mut bytes)?;
+            parse_score(&bytes)
+        }
+    }
+}
+
+fn parse_score(value: &[u8]) -> io::Result<f64> {
+    parse_bytes::<f64>(value).ok_or_else(|| invalid_data("Invalid sorted set score"))
+}
+
+fn pairs_to_hash(entries: Vec<Vec<u8>>, limits: ListpackLimits) -> io::Result<RedisValue> {
+    if entries.len() % 2 != 0 {
+        return Err(invalid_data("Odd number of hash entries"));
+    }
+    let mut entries = entries.into_iter();
+    let mut hash = HashMap::new();
+    while let (Some(field), Some(value)) = (entries.next(), entries.next()) {
+        hash.insert(field, value);
+    }
+    Ok(RedisValue::Hash(HashValue::from_pairs(hash, limits)))
+}
+
+fn pairs_to_zset(entries: Vec<Vec<u8>>, limits: ListpackLimits) -> io::Result<RedisValue> {
+    if entries.len() % 2 != 0 {
+        return Err(invalid_data("Odd number of sorted set entries"));
+    }
+    let mut entries = entries.into_iter();
+    let mut zset = HashMap::new();
+    while let (Some(member), Some(score)) = (entries.next(), entries.next()) {
+        zset.insert(member, parse_score(&score)?);
+    }
+    Ok(RedisValue::ZSet(ZSetValue::from_scores(zset, limits)))
+}
+
+// 타입 바이트를 뺀 RDB 값 직렬화 길이, DEBUG OBJECT의 serializedlength
+pub fn serialized_length(value: &RedisValue) -> usize {
+    let mut out = Vec::new();
+    write_value_body(&mut out, value, false);
+    out.len()
+}
+
+// DUMP 형식: 값 타입 + RDB 값 직렬화 + RDB 버전(2바이트 LE) + CRC64(8바이트 LE)
+pub fn dump_payload(value: &RedisValue, field_expirations: &FieldExpirations) -> Vec<u8> {
+    let mut payload = vec![entry_type(value, field_expirations)];
+    write_entry_body(&mut payload, value, field_expirations, false);
+    payload.extend_from_slice(&RDB_VERSION.to_le_bytes());
+    let checksum = Crc::<u64>::new(&CRC_64_REDIS).checksum(&payload);
+    payload.extend_from_slice(&checksum.to_le_bytes());
+    payload
+}
+
+// compress가 켜져 있으면 긴 문자열을 LZF로 압축함 (rdbcompression)
+// RDB 파일 형식: "REDIS" + 4자리 버전, 메타데이터, DB마다 (SELECTDB, 키/만료 테이블 크기, 키들), EOF, CRC64(8바이트 LE)
+// databases의 위치가 DB 번호이며 Redis처럼 비어 있는 DB는 섹션을 쓰지 않음
+// aux는 redis-ver 뒤에 더 쓸 메타데이터 (이름, 값)
+pub fn encode_rdb(databases: &[&[SnapshotEntry]], aux: &[(&str, String)], functions: &[String], compress: bool) -> Vec<u8> {
+    let mut out = Vec::new();
+    out.extend_from_slice(MAGIC_NUMBER);
+    out.extend_from_slice(format!("{:04}", RDB_VERSION).as_bytes());
+    out.push(OPCODE_META);
+    write_string(&mut out, "redis-ver");
+    write_string(&mut out, SERVER_VERSION);
+    for (name, value) in aux {
+        out.push(OPCODE_META);
+        write_string(&mut out, name);
+        write_string(&mut out, value);
+    }
+    // Redis처럼 키보다 먼저 써서 불러올 때 함수가 먼저 올라오게 함
+    for code in functions {
+        out.push(OPCODE_FUNCTION);
+        write_string(&mut out, code);
+    }
+    for (db_index, entries) in databases.iter().enumerate().filter(|(_, entries)| !entries.is_empty()) {
+        write_database(&mut out, db_index, entries, compress);
+    }
+    out.push(OPCODE_EOF);
+    let checksum = Crc::<u64>::new(&CRC_64_REDIS).checksum(&out);
+    out.extend_from_slice(&checksum.to_le_bytes());
+    out
+}
+
+// 키마다 (만료 ms) 타입 키 값
+fn write_database(out: &mut Vec<u8>, db_index: usize, entries: &[SnapshotEntry], compress: bool) {
+    out.push(OPCODE_START_DB);
+    write_length(out, db_index);
+    out.push(OPCODE_SIZE);
+    write_length(out, entries.len());
+    write_length(out, entries.iter().filter(|(_, _, expiration_ms, _)| expiration_ms.is_some()).count());
+    for (key, value, expiration_ms, field_expirations) in entries {
+        if let Some(expiration_ms) = expiration_ms {
+            out.push(OPCODE_EXPIRETIME_MS);
+            out.extend_from_slice(&expiration_ms.to_le_bytes());
+        }
+        out.push(entry_type(value, field_expirations));
+        write_rdb_bytes(out, key, compress);
+        write_entry_body(out, value, field_expirations, compress);
+    }
+}
+
+pub fn restore_payload(payload: &[u8], limits: ListpackLimits) -> Result<(RedisValue, FieldExpirations), String> {
+    if payload.len() < 10 {
+        return Err(DUMP_PAYLOAD_ERROR.to_string());
+    }
+    let (body, checksum) = payload.split_at(payload.len() - 8);
+    let version = u16::from_le_bytes([body[body.len() - 2], body[body.len() - 1]]);
+    let checksum = u64::from_le_bytes(checksum.try_into().map_err(|_| DUMP_PAYLOAD_ERROR.to_string())?);
+    if version > RDB_VERSION || Crc::<u64>::new(&CRC_64_REDIS).checksum(body) != checksum {
+        return Err(DUMP_PAYLOAD_ERROR.to_string());
+    }
+
+    let mut reader = Cursor::new(&body[..body.len() - 2]);
+    let value_type = reader.read_u8().map_err(|_| BAD_DATA_FORMAT_ERROR.to_string())?;
+    let value = read_entry_value(value_type, &mut reader, limits).map_err(|_| BAD_DATA_FORMAT_ERROR.to_string())?;
+    if reader.position() as usize != body.len() - 2 {
+        return Err(BAD_DATA_FORMAT_ERROR.to_string());
+    }
+    Ok(value)
+}
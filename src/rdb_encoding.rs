@@ -0,0 +1,367 @@
+use byteorder::{BigEndian, LittleEndian, ReadBytesExt};
+use std::io::{self, Cursor, Read};
+
+// RDB에 문자열 blob으로 들어가는 압축 인코딩들: listpack(7.0+), ziplist(7.0 이전), intset, zipmap(2.6 이전)
+const LISTPACK_HEADER_LEN: usize = 6;
+const LISTPACK_EOF: u8 = 0xFF;
+const ZIPLIST_END: u8 = 0xFF;
+const ZIPLIST_BIG_PREVLEN: u8 = 0xFE;
+const ZIPMAP_BIGLEN: u8 = 0xFE;
+const ZIPMAP_END: u8 = 0xFF;
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+// 남은 바이트보다 긴 길이는 할당하기 전에 거부함
+fn read_bytes(cursor: &mut Cursor<&[u8]>, len: usize) -> io::Result<Vec<u8>> {
+    let remaining = cursor.get_ref().len().saturating_sub(cursor.position() as usize);
+    if len > remaining {
+        return Err(invalid_data("Entry length exceeds the remaining data"));
+    }
+    let mut bytes = vec![0; len];
+    cursor.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+fn sign_extend(value: u64, bits: u32) -> i64 {
+    let shift = 64 - bits;
+    ((value << shift) as i64) >> shift
+}
+
+// Redis의 string2ll처럼 앞의 0, '+', "-0" 없이 그대로 되돌릴 수 있는 정수만 인정함
+pub fn canonical_integer(value: &[u8]) -> Option<i64> {
+    std::str::from_utf8(value)
+        .ok()?
+        .parse::<i64>()
+        .ok()
+        .filter(|parsed| parsed.to_string().as_bytes() == value)
+}
+
+pub fn decode_listpack(blob: &[u8]) -> io::Result<Vec<Vec<u8>>> {
+    let mut cursor = Cursor::new(blob);
+    let total_bytes = cursor.read_u32::<LittleEndian>()? as usize;
+    let _num_elements = cursor.read_u16::<LittleEndian>()?;
+    if total_bytes != blob.len() {
+        return Err(invalid_data("Listpack length mismatch"));
+    }
+
+    let mut entries = Vec::new();
+    loop {
+        let start = cursor.position();
+        let encoding = cursor.read_u8()?;
+        let entry = match encoding {
+            LISTPACK_EOF => break,
+            0x00..=0x7F => (encoding as i64).to_string().into_bytes(),
+            0x80..=0xBF => read_bytes(&mut cursor, (encoding & 0x3F) as usize)?,
+            0xC0..=0xDF => {
+                let value = (((encoding & 0x1F) as u64) << 8) | cursor.read_u8()? as u64;
+                sign_extend(value, 13).to_string().into_bytes()
+            }
+            0xE0..=0xEF => {
+                let len = (((encoding & 0x0F) as usize) << 8) | cursor.read_u8()? as usize;
+                read_bytes(&mut cursor, len)?
+            }
+            0xF0 => {
+                let len = cursor.read_u32::<LittleEndian>()? as usize;
+                read_bytes(&mut cursor, len)?
+            }
+            0xF1 => cursor.read_i16::<LittleEndian>()?.to_string().into_bytes(),
+            0xF2 => sign_extend(cursor.read_u24::<LittleEndian>()? as u64, 24).to_string().into_bytes(),
+            0xF3 => cursor.read_i32::<LittleEndian>()?.to_string().into_bytes(),
+            0xF4 => cursor.read_i64::<LittleEndian>()?.to_string().into_bytes(),
+            _ => return Err(invalid_data("Invalid listpack entry encoding")),
+        };
+        // 뒤로 순회할 때 쓰는 backlen은 건너뜀
+        let entry_len = (cursor.position() - start) as usize;
+        cursor.set_position(cursor.position() + backlen_size(entry_len) as u64);
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+pub fn encode_listpack<'a>(entries: impl Iterator<Item = &'a [u8]>) -> Vec<u8> {
+    let mut out = vec![0; LISTPACK_HEADER_LEN];
+    let mut count = 0usize;
+    for entry in entries {
+        let start = out.len();
+        match canonical_integer(entry) {
+            Some(value @ 0..=127) => out.push(value as u8),
+            Some(value @ -4096..=4095) => {
+                let value = value as u64 & 0x1FFF;
+                out.push(0xC0 | (value >> 8) as u8);
+                out.push(value as u8);
+            }
+            Some(value) if i16::try_from(value).is_ok() => {
+                out.push(0xF1);
+                out.extend_from_slice(&(value as i16).to_le_bytes());
+            }
+            Some(value) if (-(1 << 23)..(1 << 23)).contains(&value) => {
+                out.push(0xF2);
+                out.extend_from_slice(&(value as i32).to_le_bytes()[..3]);
+            }
+            Some(value) if i32::try_from(value).is_ok() => {
+                out.push(0xF3);
+                out.extend_from_slice(&(value as i32).to_le_bytes());
+            }
+            Some(value) => {
+                out.push(0xF4);
+                out.extend_from_slice(&value.to_le_bytes());
+            }
+            None if entry.len() < 1 << 6 => {
+                out.push(0x80 | entry.len() as u8);
+                out.extend_from_slice(entry);
+            }
+            None if entry.len() < 1 << 12 => {
+                out.push(0xE0 | (entry.len() >> 8) as u8);
+                out.push(entry.len() as u8);
+                out.extend_from_slice(entry);
+            }
+            None => {
+                out.push(0xF0);
+                out.extend_from_slice(&(entry.len() as u32).to_le_bytes());
+                out.extend_from_slice(entry);
+            }
+        }
+        let entry_len = out.len() - start;
+        push_backlen(&mut out, entry_len);
+        count += 1;
+    }
+    out.push(LISTPACK_EOF);
+
+    let total_bytes = out.len() as u32;
+    out[..4].copy_from_slice(&total_bytes.to_le_bytes());
+    // 65535 이상이면 개수를 알 수 없다는 뜻으로 u16::MAX를 씀
+    out[4..6].copy_from_slice(&(count.min(u16::MAX as usize) as u16).to_le_bytes());
+    out
+}
+
+fn backlen_size(entry_len: usize) -> usize {
+    match entry_len {
+        0..=127 => 1,
+        128..=16382 => 2,
+        16383..=2097150 => 3,
+        2097151..=268435454 => 4,
+        _ => 5,
+    }
+}
+
+// 엔트리 길이를 7비트씩 나눠서, 마지막 바이트부터 거꾸로 읽을 수 있게 씀
+fn push_backlen(out: &mut Vec<u8>, entry_len: usize) {
+    let size = backlen_size(entry_len);
+    for i in (0..size).rev() {
+        let byte = ((entry_len >> (7 * i)) & 0x7F) as u8;
+        out.push(if i == size - 1 { byte } else { byte | 0x80 });
+    }
+}
+
+pub fn decode_ziplist(blob: &[u8]) -> io::Result<Vec<Vec<u8>>> {
+    let mut cursor = Cursor::new(blob);
+    let total_bytes = cursor.read_u32::<LittleEndian>()? as usize;
+    let _tail_offset = cursor.read_u32::<LittleEndian>()?;
+    let _num_entries = cursor.read_u16::<LittleEndian>()?;
+    if total_bytes != blob.len() {
+        return Err(invalid_data("Ziplist length mismatch"));
+    }
+
+    let mut entries = Vec::new();
+    loop {
+        let prevlen = cursor.read_u8()?;
+        if prevlen == ZIPLIST_END {
+            break;
+        }
+        if prevlen == ZIPLIST_BIG_PREVLEN {
+            cursor.read_u32::<LittleEndian>()?;
+        }
+        let encoding = cursor.read_u8()?;
+        let entry = match encoding >> 6 {
+            0b00 => read_bytes(&mut cursor, (encoding & 0x3F) as usize)?,
+            0b01 => {
+                let len = (((encoding & 0x3F) as usize) << 8) | cursor.read_u8()? as usize;
+                read_bytes(&mut cursor, len)?
+            }
+            0b10 => {
+                let len = cursor.read_u32::<BigEndian>()? as usize;
+                read_bytes(&mut cursor, len)?
+            }
+            _ => match encoding {
+                0xC0 => cursor.read_i16::<LittleEndian>()?.to_string().into_bytes(),
+                0xD0 => cursor.read_i32::<LittleEndian>()?.to_string().into_bytes(),
+                0xE0 => cursor.read_i64::<LittleEndian>()?.to_string().into_bytes(),
+                0xF0 => sign_extend(cursor.read_u24::<LittleEndian>()? as u64, 24).to_string().into_bytes(),
+                0xFE => cursor.read_i8()?.to_string().into_bytes(),
+                // 1111xxxx: xxxx - 1이 0~12 사이의 값
+                0xF1..=0xFD => ((encoding & 0x0F) - 1).to_string().into_bytes(),
+                _ => return Err(invalid_data("Invalid ziplist entry encoding")),
+            },
+        };
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+pub fn decode_intset(blob: &[u8]) -> io::Result<Vec<Vec<u8>>> {
+    let mut cursor = Cursor::new(blob);
+    let width = cursor.read_u32::<LittleEndian>()?;
+    let len = cursor.read_u32::<LittleEndian>()?;
+    (0..len)
+        .map(|_| {
+            let value = match width {
+                2 => cursor.read_i16::<LittleEndian>()? as i64,
+                4 => cursor.read_i32::<LittleEndian>()? as i64,
+                8 => cursor.read_i64::<LittleEndian>()?,
+                _ => return Err(invalid_data("Invalid intset encoding")),
+            };
+            Ok(value.to_string().into_bytes())
+        })
+        .collect()
+}
+
+pub fn encode_intset(values: &mut [i64]) -> Vec<u8> {
+    values.sort_unstable();
+    let width: u32 = if values.iter().all(|value| i16::try_from(*value).is_ok()) {
+        2
+    } else if values.iter().all(|value| i32::try_from(*value).is_ok()) {
+        4
+    } else {
+        8
+    };
+    let mut out = Vec::with_capacity(8 + values.len() * width as usize);
+    out.extend_from_slice(&width.to_le_bytes());
+    out.extend_from_slice(&(values.len() as u32).to_le_bytes());
+    for value in values.iter() {
+        out.extend_from_slice(&value.to_le_bytes()[..width as usize]);
+    }
+    out
+}
+
+// 필드와 값을 번갈아 담은 목록으로 돌려줌
+pub fn decode_zipmap(blob: &[u8]) -> io::Result<Vec<Vec<u8>>> {
+    let mut cursor = Cursor::new(blob);
+    let _len = cursor.read_u8()?;
+    let mut entries = Vec::new();
+    while let Some(field_len) = read_zipmap_len(&mut cursor)? {
+        entries.push(read_bytes(&mut cursor, field_len)?);
+        let value_len = read_zipmap_len(&mut cursor)?.ok_or_else(|| invalid_data("Zipmap field without value"))?;
+        let free = cursor.read_u8()? as u64;
+        entries.push(read_bytes(&mut cursor, value_len)?);
+        cursor.set_position(cursor.position() + free);
+    }
+    Ok(entries)
+}
+
+fn read_zipmap_len(cursor: &mut Cursor<&[u8]>) -> io::Result<Option<usize>> {
+    match cursor.read_u8()? {
+        ZIPMAP_END => Ok(None),
+        ZIPMAP_BIGLEN => Ok(Some(cursor.read_u32::<LittleEndian>()? as usize)),
+        len => Ok(Some(len as usize)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries(values: &[&str]) -> Vec<Vec<u8>> {
+        values.iter().map(|value| value.as_bytes().to_vec()).collect()
+    }
+
+    #[test]
+    fn listpack_matches_the_redis_layout() {
+        // 헤더(전체 길이, 개수), "a"는 6비트 길이 문자열, "1"은 7비트 정수, 엔트리마다 backlen 한 바이트
+        let blob = [0x0C, 0x00, 0x00, 0x00, 0x02, 0x00, 0x81, b'a', 0x02, 0x01, 0x01, 0xFF];
+        assert_eq!(encode_listpack(entries(&["a", "1"]).iter().map(Vec::as_slice)), blob);
+        assert_eq!(decode_listpack(&blob).unwrap(), entries(&["a", "1"]));
+    }
+
+    #[test]
+    fn listpack_round_trips_every_encoding() {
+        let mut values = entries(&[
+            "0", "127", "128", "-1", "4095", "-4096", "4096", "32767", "-32768", "8388607", "-8388608",
+            "2147483647", "-2147483648", "9223372036854775807", "-9223372036854775808", "007", "+1", "-0", "",
+        ]);
+        // 6비트/12비트/32비트 길이 문자열과 두 바이트 backlen이 필요한 길이
+        for len in [63, 64, 200, 4095, 4096, 20000] {
+            values.push(vec![b'x'; len]);
+        }
+        let blob = encode_listpack(values.iter().map(Vec::as_slice));
+        assert_eq!(u32::from_le_bytes(blob[..4].try_into().unwrap()) as usize, blob.len());
+        assert_eq!(u16::from_le_bytes(blob[4..6].try_into().unwrap()) as usize, values.len());
+        assert_eq!(decode_listpack(&blob).unwrap(), values);
+    }
+
+    #[test]
+    fn listpack_rejects_a_wrong_total_length() {
+        let mut blob = encode_listpack(entries(&["a"]).iter().map(Vec::as_slice));
+        blob.push(0);
+        assert!(decode_listpack(&blob).is_err());
+    }
+
+    #[test]
+    fn ziplist_decodes_string_and_integer_entries() {
+        // "a", 1(4비트 즉시값), 1000(int16), -5(int8), 70000(int24)
+        let blob = [
+            0x1C, 0x00, 0x00, 0x00, 0x16, 0x00, 0x00, 0x00, 0x05, 0x00,
+            0x00, 0x01, b'a',
+            0x03, 0xF2,
+            0x02, 0xC0, 0xE8, 0x03,
+            0x04, 0xFE, 0xFB,
+            0x03, 0xF0, 0x70, 0x11, 0x01,
+            0xFF,
+        ];
+        assert_eq!(decode_ziplist(&blob).unwrap(), entries(&["a", "1", "1000", "-5", "70000"]));
+    }
+
+    #[test]
+    fn ziplist_rejects_a_wrong_total_length() {
+        let blob = [0x0C, 0x00, 0x00, 0x00, 0x0A, 0x00, 0x00, 0x00, 0x00, 0x00, 0xFF];
+        assert!(decode_ziplist(&blob).is_err());
+    }
+
+    #[test]
+    fn intset_is_sorted_and_uses_the_narrowest_width() {
+        let blob = encode_intset(&mut [3, 1, 2]);
+        assert_eq!(blob, [0x02, 0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00, 0x01, 0x00, 0x02, 0x00, 0x03, 0x00]);
+        assert_eq!(decode_intset(&blob).unwrap(), entries(&["1", "2", "3"]));
+
+        for (values, width) in [(vec![-32768, 32767], 2), (vec![1, 70000], 4), (vec![i64::MIN, 0, i64::MAX], 8)] {
+            let blob = encode_intset(&mut values.clone());
+            assert_eq!(u32::from_le_bytes(blob[..4].try_into().unwrap()), width);
+            let decoded: Vec<Vec<u8>> = values.iter().map(|value| value.to_string().into_bytes()).collect();
+            assert_eq!(decode_intset(&blob).unwrap(), decoded);
+        }
+    }
+
+    #[test]
+    fn intset_rejects_an_unknown_width() {
+        assert!(decode_intset(&[0x03, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00]).is_err());
+    }
+
+    #[test]
+    fn zipmap_decodes_fields_and_skips_free_bytes() {
+        let blob = [0x01, 0x03, b'f', b'o', b'o', 0x03, 0x00, b'b', b'a', b'r', 0xFF];
+        assert_eq!(decode_zipmap(&blob).unwrap(), entries(&["foo", "bar"]));
+
+        // 값 뒤에 남은 2바이트는 건너뛰고, 254 이상인 길이는 0xFE 뒤 4바이트로 옴
+        let mut blob = vec![0x02, 0x01, b'a', 0x01, 0x02, b'1', 0x00, 0x00];
+        blob.extend_from_slice(&[0x01, b'b', ZIPMAP_BIGLEN, 0x2C, 0x01, 0x00, 0x00, 0x00]);
+        blob.extend_from_slice(&[b'v'; 300]);
+        blob.push(ZIPMAP_END);
+        let mut expected = entries(&["a", "1", "b"]);
+        expected.push(vec![b'v'; 300]);
+        assert_eq!(decode_zipmap(&blob).unwrap(), expected);
+    }
+
+    #[test]
+    fn zipmap_rejects_a_field_without_a_value() {
+        assert!(decode_zipmap(&[0x01, 0x01, b'a', ZIPMAP_END]).is_err());
+    }
+
+    #[test]
+    fn canonical_integer_only_accepts_round_trippable_text() {
+        assert_eq!(canonical_integer(b"-42"), Some(-42));
+        for value in [&b"007"[..], b"+1", b"-0", b" 1", b"1.0", b"", b"9223372036854775808"] {
+            assert_eq!(canonical_integer(value), None);
+        }
+    }
+}
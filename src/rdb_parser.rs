@@ -1,45 +1,68 @@
-use crate::protocol_constants::{MAGIC_NUMBER, OPCODE_EOF, OPCODE_META, OPCODE_START_DB};
-use crate::ValueEntry;
+use crate::config_handler::Db;
+use crate::logging::{log_debug, log_verbose, log_warning};
+use crate::protocol_constants::{AUX_REPL_ID, AUX_REPL_OFFSET, MAGIC_NUMBER, OPCODE_EOF, OPCODE_FUNCTION, OPCODE_META, OPCODE_START_DB};
+use crate::rdb_codec;
+use crate::value_entry::ValueEntry;
 use byteorder::{LittleEndian, ReadBytesExt};
-use crc::{Crc, CRC_64_ECMA_182};
-use std::collections::HashMap;
+use crc::{Crc, CRC_64_REDIS};
 use std::fs::File;
-use std::io::{self, BufReader, Read, Seek, SeekFrom};
-
-pub struct RdbParser<'a> {
-    reader: BufReader<File>,
-    db: &'a mut HashMap<String, ValueEntry>,
+use std::io::{self, BufReader, Cursor, Read, Seek, SeekFrom};
+
+pub struct RdbParser<'a, R> {
+    reader: R,
+    db: &'a mut Db,
+    // SELECTDB로 선택된 DB, 서버에는 DB 0만 있으므로 다른 DB의 키는 읽고 버림
+    db_index: u64,
+    // DB 0 밖에 있어서 버린 키 수, 불러오기가 끝나면 경고로 남김
+    skipped_keys: u64,
+    // repl-id와 repl-offset 메타데이터, 둘 다 있을 때만 replication()으로 돌려줌
+    repl_id: Option<String>,
+    repl_offset: Option<u64>,
+    // FUNCTION LOAD로 올린 라이브러리 코드, 키스페이스 밖이라 불러온 쪽에서 FunctionRegistry로 옮김
+    functions: Vec<String>,
 }
 
-impl<'a> RdbParser<'a> {
-    pub fn new(db: &'a mut HashMap<String, ValueEntry>, rdb_file_path: &str) -> io::Result<Self> {
+impl<'a> RdbParser<'a, BufReader<File>> {
+    pub fn new(db: &'a mut Db, rdb_file_path: &str) -> io::Result<Self> {
         let file = File::open(rdb_file_path)?;
         let reader = BufReader::new(file);
-        Ok(Self { reader, db })
+        Ok(Self { reader, db, db_index: 0, skipped_keys: 0, repl_id: None, repl_offset: None, functions: Vec::new() })
+    }
+}
+
+impl<'a> RdbParser<'a, Cursor<Vec<u8>>> {
+    // 레플리카가 FULLRESYNC로 받은 RDB 페이로드
+    pub fn from_bytes(db: &'a mut Db, data: Vec<u8>) -> Self {
+        Self { reader: Cursor::new(data), db, db_index: 0, skipped_keys: 0, repl_id: None, repl_offset: None, functions: Vec::new() }
     }
+}
 
+impl<'a, R: Read + Seek> RdbParser<'a, R> {
     pub async fn parse(&mut self) -> io::Result<()> {
         self.verify_magic_number()?;
         self.read_version()?;
         self.process_entries().await?;
         self.verify_checksum()?;
+        if self.skipped_keys > 0 {
+            log_warning!("Skipped {} keys stored outside database 0, only database 0 is supported", self.skipped_keys);
+        }
         Ok(())
     }
 
     fn verify_magic_number(&mut self) -> io::Result<()> {
         let mut magic = [0; 5];
         self.reader.read_exact(&mut magic)?;
-        if &magic != MAGIC_NUMBER {
+        if magic != *MAGIC_NUMBER {
             return Err(io::Error::new(io::ErrorKind::InvalidData, "Invalid RDB file format."));
         }
-        println!("Valid Redis RDB file detected.");
+        log_verbose!("Valid Redis RDB file detected.");
         Ok(())
     }
 
     fn read_version(&mut self) -> io::Result<()> {
         let mut version = [0; 4];
         self.reader.read_exact(&mut version)?;
-        println!("RDB Version: {}", String::from_utf8_lossy(&version));
+        log_verbose!("RDB Version: {}", String::from_utf8_lossy(&version));
         Ok(())
     }
 
@@ -47,79 +70,81 @@ impl<'a> RdbParser<'a> {
         loop {
             let mut marker = [0; 1];
             if self.reader.read_exact(&mut marker).is_err() {
-                println!("End of file reached.");
+                log_debug!("End of file reached.");
                 break;
             }
-            println!("Processing marker: 0x{:02X}", marker[0]);
+            log_debug!("Processing marker: 0x{:02X}", marker[0]);
             match marker[0] {
                 OPCODE_META => {
-                    println!("Detected OPCODE_META");
+                    log_debug!("Detected OPCODE_META");
                     self.process_metadata().await?;
                 }
+                OPCODE_FUNCTION => {
+                    log_debug!("Detected OPCODE_FUNCTION");
+                    self.functions.push(rdb_codec::read_string(&mut self.reader)?);
+                }
                 OPCODE_START_DB => {
-                    println!("Detected OPCODE_START_DB");
+                    log_debug!("Detected OPCODE_START_DB");
                     self.process_start_db().await?;
                 }
                 0xFB => {
-                    println!("Detected Resize DB Opcode");
+                    log_debug!("Detected Resize DB Opcode");
                     self.process_resize_db().await?;
                 }
                 0xFD | 0xFC => {
-                    println!("Detected Expiry Opcode: {}", if marker[0] == 0xFD { "seconds" } else { "milliseconds" });
+                    log_debug!("Detected Expiry Opcode: {}", if marker[0] == 0xFD { "seconds" } else { "milliseconds" });
                     self.process_expiry(marker[0]).await?;
                 }
-                0x00 => {
-                    println!("Detected Key without Expiration Opcode");
-                    self.process_key_without_expiration().await?;
+                value_type if rdb_codec::is_value_type(value_type) => {
+                    log_debug!("Detected Key without Expiration Opcode");
+                    self.process_key(marker[0], None).await?;
                 }
                 OPCODE_EOF => {
-                    println!("Detected EOF Opcode");
+                    log_debug!("Detected EOF Opcode");
                     break;
                 }
-                _ => eprintln!("Unknown or unsupported marker: 0x{:02X}", marker[0]),
+                _ => log_warning!("Unknown or unsupported marker: 0x{:02X}", marker[0]),
             }
         }
         Ok(())
     }
 
+    // 값은 문자열이거나 정수 인코딩(redis-bits, ctime 등)이며 read_string이 둘 다 문자열로 돌려줌
     async fn process_metadata(&mut self) -> io::Result<()> {
-        let first_key_byte = self.reader.read_u8()?;
-        let key_length = self.read_length_or_integer(first_key_byte)?;
-
-        let mut key_bytes = vec![0; key_length];
-        self.reader.read_exact(&mut key_bytes)?;
-        let key = String::from_utf8_lossy(&key_bytes).to_string();
-
-        let first_value_byte = self.reader.read_u8()?;
-        if first_value_byte >> 6 == 0b11 {
-            let value = self.read_length_or_integer(first_value_byte)?;
-            println!("Metadata key: {}, value: {}", key, value);
-        } else {
-            let value_length = self.read_length_or_integer(first_value_byte)?;
-            let mut value_bytes = vec![0; value_length];
-            self.reader.read_exact(&mut value_bytes)?;
-
-            match String::from_utf8(value_bytes.clone()) {
-                Ok(value) => println!("Metadata key: {}, value: {}", key, value),
-                Err(_) => {
-                    let hex_value = value_bytes.iter().map(|b| format!("{:02X}", b)).collect::<Vec<_>>().join(" ");
-                    println!("Metadata key: {}, value (raw hex): {}", key, hex_value);
-                }
-            }
+        let key = rdb_codec::read_string(&mut self.reader)?;
+        let value = rdb_codec::read_string(&mut self.reader)?;
+        log_debug!("Metadata key: {}, value: {}", key, value);
+        match key.as_str() {
+            AUX_REPL_ID => self.repl_id = Some(value),
+            AUX_REPL_OFFSET => self.repl_offset = value.parse().ok(),
+            _ => {}
         }
         Ok(())
     }
 
+    // 저장할 때의 (복제 ID, offset)
+    pub fn replication(&self) -> Option<(String, u64)> {
+        Some((self.repl_id.clone()?, self.repl_offset?))
+    }
+
+    pub fn functions(&self) -> &[String] {
+        &self.functions
+    }
+
     async fn process_start_db(&mut self) -> io::Result<()> {
-        let db_index = self.reader.read_u8()?;
-        println!("Starting new database with index: {}", db_index);
+        self.db_index = self.read_plain_length()?;
+        log_debug!("Starting new database with index: {}", self.db_index);
         Ok(())
     }
 
     async fn process_resize_db(&mut self) -> io::Result<()> {
-        let total_size = self.reader.read_u8()?;
-        let expires_size = self.reader.read_u8()?;
-        println!("Resize database: hash table size = {}, expires table size = {}", total_size, expires_size);
+        let total_size = self.read_plain_length()?;
+        let expires_size = self.read_plain_length()?;
+        log_debug!("Resize database: hash table size = {}, expires table size = {}", total_size, expires_size);
+        // 크기는 파일에 적힌 값이라 믿을 수 없으므로 RESTORE payload처럼 상한까지만 미리 잡음
+        if self.db_index == 0 {
+            self.db.reserve(rdb_codec::preallocation(usize::try_from(total_size).unwrap_or(usize::MAX)));
+        }
         Ok(())
     }
 
@@ -132,40 +157,23 @@ impl<'a> RdbParser<'a> {
             Some(self.reader.read_u64::<LittleEndian>()?)
         };
 
-        let _value_type = self.reader.read_u8()?;
-
-        let key_length = self.reader.read_u8()? as usize;
-        let mut key = vec![0; key_length];
-        self.reader.read_exact(&mut key)?;
-        let key_str = String::from_utf8_lossy(&key).to_string();
-
-        let value_length = self.reader.read_u8()? as usize;
-        let mut value = vec![0; value_length];
-        self.reader.read_exact(&mut value)?;
-        let value_str = String::from_utf8_lossy(&value).to_string();
-
-        let entry = ValueEntry::new_absolute(value_str.clone(), expiration_ms);
-        self.db.insert(key_str.clone(), entry);
-        println!("Inserted key: {} with value: {} and expiration: {:?}", key_str, value_str, expiration_ms);
-        Ok(())
+        let value_type = self.reader.read_u8()?;
+        self.process_key(value_type, expiration_ms).await
     }
 
-    async fn process_key_without_expiration(&mut self) -> io::Result<()> {
-        let key_length = self.reader.read_u8()? as usize;
-
-        let mut key = vec![0; key_length];
-        self.reader.read_exact(&mut key)?;
-        let key_str = String::from_utf8_lossy(&key).to_string();
-
-        let value_length = self.reader.read_u8()? as usize;
-
-        let mut value = vec![0; value_length];
-        self.reader.read_exact(&mut value)?;
-        let value_str = String::from_utf8_lossy(&value).to_string();
+    async fn process_key(&mut self, value_type: u8, expiration_ms: Option<u64>) -> io::Result<()> {
+        let key = rdb_codec::read_bytes(&mut self.reader)?;
+        let (value, field_expirations) = rdb_codec::read_entry_value(value_type, &mut self.reader, self.db.listpack_limits())?;
+        if self.db_index != 0 {
+            log_debug!("Skipped key: {} in database {}", String::from_utf8_lossy(&key), self.db_index);
+            self.skipped_keys += 1;
+            return Ok(());
+        }
+        log_debug!("Inserted key: {} of type {} with expiration: {:?}", String::from_utf8_lossy(&key), value.type_name(), expiration_ms);
 
-        let entry = ValueEntry::new_absolute(value_str.clone(), None);
-        self.db.insert(key_str.clone(), entry);
-        println!("Inserted key: {} with value: {} without expiration", key_str, value_str);
+        let mut entry = ValueEntry::new_absolute(value, expiration_ms);
+        entry.set_field_expirations(field_expirations);
+        self.db.insert(key, entry);
         Ok(())
     }
 
@@ -173,49 +181,74 @@ impl<'a> RdbParser<'a> {
         let mut checksum_bytes = [0; 8];
         self.reader.read_exact(&mut checksum_bytes)?;
         let read_checksum = u64::from_le_bytes(checksum_bytes);
+        // rdbchecksum no로 저장된 파일은 체크섬 자리가 0
+        if read_checksum == 0 {
+            return Ok(());
+        }
 
         self.reader.seek(SeekFrom::Start(0))?;
         let mut buffer = Vec::new();
         self.reader.read_to_end(&mut buffer)?;
         let data_to_hash = &buffer[..buffer.len() - 8];
 
-        let crc = Crc::<u64>::new(&CRC_64_ECMA_182);
+        let crc = Crc::<u64>::new(&CRC_64_REDIS);
         let calculated_checksum = crc.checksum(data_to_hash);
 
         if calculated_checksum == read_checksum {
-            println!("Checksum is valid.");
+            log_verbose!("Checksum is valid.");
             Ok(())
         } else {
-            eprintln!("Invalid checksum!");
+            log_warning!("Invalid checksum!");
             Err(io::Error::new(io::ErrorKind::InvalidData, "Checksum mismatch"))
         }
     }
 
-    fn read_length_or_integer(&mut self, first_byte: u8) -> io::Result<usize> {
-        match first_byte >> 6 {
-            0b00 => Ok((first_byte & 0x3F) as usize),
-            0b01 => self.read_14bit_length(first_byte),
-            0b10 => self.read_32bit_length(),
-            0b11 => self.read_encoded_integer(first_byte & 0x3F),
-            _ => Err(io::Error::new(io::ErrorKind::InvalidData, "Invalid length encoding")),
+    // DB 번호와 해시 테이블 크기는 정수 인코딩이 아닌 길이 인코딩만 허용됨
+    fn read_plain_length(&mut self) -> io::Result<u64> {
+        match rdb_codec::read_length(&mut self.reader)? {
+            (len, false) => Ok(len),
+            _ => Err(io::Error::new(io::ErrorKind::InvalidData, "Unexpected integer encoding")),
         }
     }
+}
 
-    fn read_14bit_length(&mut self, first_byte: u8) -> io::Result<usize> {
-        let second_byte = self.reader.read_u8()?;
-        Ok((((first_byte & 0x3F) as usize) << 8) | (second_byte as usize))
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::persistence::SnapshotEntry;
+    use crate::protocol_constants::OPCODE_SIZE;
+    use crate::random::Random;
+    use crate::value_encoding::StringValue;
+    use crate::value_entry::RedisValue;
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    fn string_entry(key: &str) -> SnapshotEntry {
+        (key.as_bytes().to_vec(), RedisValue::String(StringValue::new(b"value".to_vec())), None, HashMap::new())
     }
 
-    fn read_32bit_length(&mut self) -> io::Result<usize> {
-        self.reader.read_u32::<LittleEndian>().map(|len| len as usize)
+    #[tokio::test]
+    async fn a_huge_resizedb_hint_does_not_preallocate_it() {
+        // DB 0, 해시 테이블 크기 1<<60, 키 하나, 체크섬 없음
+        let mut data = b"REDIS0011".to_vec();
+        data.extend_from_slice(&[OPCODE_START_DB, 0, OPCODE_SIZE, 0x81]);
+        data.extend_from_slice(&(1u64 << 60).to_be_bytes());
+        data.extend_from_slice(&[0, 0, 3, b'k', b'e', b'y', 1, b'v', OPCODE_EOF]);
+        data.extend_from_slice(&[0; 8]);
+
+        let mut db = Db::new(Arc::new(Random::new()));
+        RdbParser::from_bytes(&mut db, data).parse().await.unwrap();
+        assert_eq!(db.len(), 1);
     }
 
-    fn read_encoded_integer(&mut self, encoding_type: u8) -> io::Result<usize> {
-        match encoding_type {
-            0 => self.reader.read_u8().map(|val| val as usize),
-            1 => self.reader.read_u16::<LittleEndian>().map(|val| val as usize),
-            2 => self.reader.read_u32::<LittleEndian>().map(|val| val as usize),
-            _ => Err(io::Error::new(io::ErrorKind::InvalidData, "Unsupported encoding type")),
-        }
+    #[tokio::test]
+    async fn keys_outside_database_0_are_skipped() {
+        let data = rdb_codec::encode_rdb(&[&[string_entry("zero")], &[string_entry("one")], &[string_entry("two")]], &[], &[], false);
+        let mut db = Db::new(Arc::new(Random::new()));
+        let mut parser = RdbParser::from_bytes(&mut db, data);
+        parser.parse().await.unwrap();
+        assert_eq!(parser.skipped_keys, 2);
+        assert_eq!(db.len(), 1);
+        assert!(db.contains_key(b"zero"));
     }
-}
\ No newline at end of file
+}
@@ -1,29 +1,195 @@
+use crate::client_output::{Backpressure, ClientOutput};
+use crate::command::Command;
+use crate::protocol_constants::{DEFAULT_USER, RESP2_PROTOCOL};
+use crate::replica_output::{OutputBufferLimits, ReplicaOutput};
+use crate::trace::TraceContext;
+use crate::tracking::TrackingOptions;
+use bytes::Bytes;
+use std::collections::HashSet;
+use std::fmt;
+use std::io;
 use std::net::SocketAddr;
-use tokio::net::tcp::OwnedWriteHalf;
-use tokio::time::Instant;
+use std::sync::Arc;
+use tokio::sync::oneshot;
+use tokio::time::{Duration, Instant};
+
+// MULTI 이후 EXEC까지 쌓아 둔 명령. 큐잉 중 에러가 있었으면 EXEC에서 통째로 버림
+#[derive(Default)]
+pub struct Transaction {
+    pub commands: Vec<(Command, Option<TraceContext>)>,
+    pub aborted: bool,
+}
+
+impl fmt::Debug for Transaction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Transaction")
+            .field("queued", &self.commands.len())
+            .field("aborted", &self.aborted)
+            .finish()
+    }
+}
 
 #[derive(Debug)]
 pub struct Client {
     pub id: u64,
-    pub writer: OwnedWriteHalf,
+    // 응답은 버퍼에 모았다가 이벤트 루프가 flush할 때 연결의 전송 태스크로 넘김
+    // 레플리카로 등록되면 쓰기 절반을 replica_output에 넘기므로 None
+    pub output: Option<ClientOutput>,
+    // 읽기 태스크와 함께 보는 흐름 제어 상태, 레플리카가 되어도 남음
+    pub backpressure: Arc<Backpressure>,
+    pub replica_output: Option<ReplicaOutput>,
     pub connected_at: Instant,
     pub request_count: u64,
     pub addr: SocketAddr,
+    pub local_addr: SocketAddr,
+    // 보내면 연결의 읽기 태스크가 끝나고, 이어서 오는 ClientDisconnected가 정리함
+    kill_switch: Option<oneshot::Sender<()>>,
+    pub subscriptions: HashSet<String>,
+    pub pattern_subscriptions: HashSet<String>,
+    pub transaction: Option<Transaction>,
+    pub tracking: Option<TrackingOptions>,
+    // CLIENT CACHING이 적용될 요청 번호(request_count), CLIENT CACHING 바로 다음 명령 하나에만 적용됨
+    pub caching: Option<u64>,
+    // REPLCONF로 알린 레플리카 주소, PSYNC를 마쳐 레플리카로 등록될 때 함께 기록함
+    pub replica_listening_port: Option<u16>,
+    pub replica_announced_ip: Option<String>,
+    pub is_replica: bool,
+    // ASKING 직후의 명령 하나만 옮겨 오는 중인 슬롯의 키에 접근할 수 있음
+    pub asking: bool,
+    // READONLY를 보낸 연결은 클러스터 레플리카에서 마스터 슬롯의 읽기 명령을 바로 처리함
+    pub readonly: bool,
+    // HELLO로 정한 RESP 버전, 연결 직후에는 RESP2
+    pub protocol: u8,
+    pub name: Option<String>,
+    // requirepass가 없을 때 연결했거나 AUTH/HELLO AUTH를 통과하면 true
+    pub authenticated: bool,
+    // 명령 권한을 검사할 ACL 사용자, 인증 전에는 default
+    pub user: String,
 }
 
 impl Client {
-    pub fn new(id: u64, writer: OwnedWriteHalf, addr: SocketAddr) -> Self {
+    pub fn new(id: u64, output: ClientOutput, addr: SocketAddr, local_addr: SocketAddr, kill_switch: oneshot::Sender<()>) -> Self {
         Self {
             id,
-            writer,
+            backpressure: output.backpressure(),
+            output: Some(output),
+            replica_output: None,
             connected_at: Instant::now(),
             request_count: 0,
             addr,
+            local_addr,
+            kill_switch: Some(kill_switch),
+            subscriptions: HashSet::new(),
+            pattern_subscriptions: HashSet::new(),
+            transaction: None,
+            tracking: None,
+            caching: None,
+            replica_listening_port: None,
+            replica_announced_ip: None,
+            is_replica: false,
+            asking: false,
+            readonly: false,
+            protocol: RESP2_PROTOCOL,
+            name: None,
+            authenticated: false,
+            user: DEFAULT_USER.to_string(),
+        }
+    }
+
+    // 레플리카에게는 전송 큐에 넣기만 하고, 큐가 제한을 넘으면 에러를 돌려줌
+    // 일반 클라이언트는 버퍼에 쌓기만 하고 flush할 때 보냄
+    pub async fn write_all(&mut self, data: &[u8]) -> io::Result<()> {
+        if let Some(output) = self.replica_output.as_mut() {
+            return output.push(Bytes::copy_from_slice(data));
+        }
+        if let Some(output) = self.output.as_mut() {
+            output.buffer_mut().extend_from_slice(data);
+        }
+        Ok(())
+    }
+
+    // 여러 레플리카에게 같은 복제 스트림을 보낼 때 씀, 레플리카의 큐에는 참조만 넣음
+    pub fn write_shared(&mut self, data: &Bytes) -> io::Result<()> {
+        if let Some(output) = self.replica_output.as_mut() {
+            return output.push(data.clone());
+        }
+        if let Some(output) = self.output.as_mut() {
+            output.buffer_mut().extend_from_slice(data);
+        }
+        Ok(())
+    }
+
+    pub fn has_pending_output(&self) -> bool {
+        self.output.as_ref().is_some_and(|output| output.has_pending())
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        match self.output.as_mut() {
+            Some(output) => output.flush(),
+            None => Ok(()),
+        }
+    }
+
+    // FULLRESYNC 응답과 RDB를 전송 태스크가 다 쓸 때까지 기다린 뒤 쓰기 절반을 레플리카 전송 태스크에 넘김
+    pub async fn start_replica_output(&mut self, limits: OutputBufferLimits) -> io::Result<()> {
+        let Some(output) = self.output.take() else {
+            return Ok(());
+        };
+        let writer = output
+            .finish()
+            .await
+            .ok_or_else(|| io::Error::new(io::ErrorKind::BrokenPipe, "failed to send the full resync payload"))?;
+        self.replica_output = Some(ReplicaOutput::spawn(writer, limits));
+        Ok(())
+    }
+
+    // 종료할 때 남은 응답을 보냄, 시간 안에 다 보내면 true
+    pub async fn finish_output(&mut self, timeout: Duration) -> bool {
+        match self.output.take() {
+            Some(output) => tokio::time::timeout(timeout, output.finish()).await.is_ok(),
+            None => true,
+        }
+    }
+
+    // 레플리카가 아니면 보낼 것이 없으므로 true
+    pub async fn drain_replica_output(&mut self, timeout: Duration) -> bool {
+        match self.replica_output.as_mut() {
+            Some(output) => output.drain(timeout).await,
+            None => true,
+        }
+    }
+
+    pub fn output_buffer_bytes(&self) -> usize {
+        match (self.replica_output.as_ref(), self.output.as_ref()) {
+            (Some(output), _) => output.pending_bytes(),
+            (None, Some(output)) => output.pending_bytes(),
+            (None, None) => 0,
+        }
+    }
+
+    // 이미 끊기는 중이면 아무것도 하지 않음
+    pub fn kill(&mut self) {
+        if let Some(kill_switch) = self.kill_switch.take() {
+            let _ = kill_switch.send(());
         }
     }
 
-    pub fn get_writer(&mut self) -> &mut OwnedWriteHalf {
-        &mut self.writer
+    // Redis처럼 공백, 개행, 특수 문자 없이 '!'부터 '~'까지의 글자만 허용함
+    pub fn is_valid_name(name: &str) -> bool {
+        name.chars().all(|c| ('!'..='~').contains(&c))
+    }
+
+    // 로그에 남길 클라이언트 표시, 이름을 붙였으면 함께 보여 줌
+    pub fn label(&self) -> String {
+        match &self.name {
+            Some(name) => format!("{} ({})", self.id, name),
+            None => self.id.to_string(),
+        }
+    }
+
+    // 지금 처리 중인 명령이 CLIENT CACHING 바로 다음 명령인지
+    pub fn caching_applies(&self) -> bool {
+        self.caching == Some(self.request_count)
     }
 
     pub fn increment_request_count(&mut self) {
@@ -34,7 +200,18 @@ impl Client {
         self.request_count
     }
 
-    pub fn get_addr(&self) -> SocketAddr {
-        self.addr
+    // Redis처럼 구독 수는 채널과 패턴을 합친 값
+    pub fn subscription_count(&self) -> usize {
+        self.subscriptions.len() + self.pattern_subscriptions.len()
+    }
+
+    pub fn is_subscribed(&self) -> bool {
+        self.subscription_count() > 0
+    }
+
+    pub fn flag_transaction_error(&mut self) {
+        if let Some(transaction) = self.transaction.as_mut() {
+            transaction.aborted = true;
+        }
     }
 }
\ No newline at end of file
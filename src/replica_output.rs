@@ -0,0 +1,122 @@
+use crate::eviction;
+use crate::logging::{self, log_warning};
+use bytes::Bytes;
+use std::io;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::io::AsyncWriteExt;
+use tokio::net::tcp::OwnedWriteHalf;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio::time::{Duration, Instant};
+
+// Redis 기본 client-output-buffer-limit replica 256mb 64mb 60
+const DEFAULT_REPLICA_OUTPUT_BUFFER_LIMIT: &str = "256mb 64mb 60";
+
+// 0이면 그 제한은 쓰지 않음
+#[derive(Debug, Clone, Copy)]
+pub struct OutputBufferLimits {
+    pub hard_bytes: usize,
+    pub soft_bytes: usize,
+    pub soft_seconds: u64,
+}
+
+impl OutputBufferLimits {
+    // "<hard> <soft> <soft 초>"
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let [hard, soft, soft_seconds] = value.split_whitespace().collect::<Vec<_>>()[..] else {
+            return Err(format!("Invalid output buffer limit '{}'", value));
+        };
+        Ok(Self {
+            hard_bytes: eviction::parse_memory(hard)? as usize,
+            soft_bytes: eviction::parse_memory(soft)? as usize,
+            soft_seconds: soft_seconds
+                .parse()
+                .map_err(|_| format!("Invalid output buffer limit '{}'", value))?,
+        })
+    }
+
+    pub fn for_replicas(value: Option<&String>) -> Self {
+        value
+            .and_then(|value| Self::parse(value).ok())
+            .unwrap_or_else(|| Self::parse(DEFAULT_REPLICA_OUTPUT_BUFFER_LIMIT).expect("default limit is valid"))
+    }
+}
+
+// 레플리카로 보낼 바이트를 쌓아 두는 큐, 소켓 쓰기는 전용 태스크가 하므로 느린 레플리카가 이벤트 루프를 막지 않음
+// 복제 스트림은 한 번 인코딩한 Bytes를 모든 레플리카의 큐가 함께 가리키므로 레플리카 수만큼 복사하지 않음
+#[derive(Debug)]
+pub struct ReplicaOutput {
+    sender: mpsc::UnboundedSender<Bytes>,
+    // 큐에 넣었지만 아직 소켓에 쓰지 못한 바이트 수
+    pending: Arc<AtomicUsize>,
+    limits: OutputBufferLimits,
+    soft_limit_since: Option<Instant>,
+    task: JoinHandle<()>,
+}
+
+impl ReplicaOutput {
+    pub fn spawn(mut writer: OwnedWriteHalf, limits: OutputBufferLimits) -> Self {
+        let (sender, mut receiver) = mpsc::unbounded_channel::<Bytes>();
+        let pending = Arc::new(AtomicUsize::new(0));
+        let task_pending = pending.clone();
+        let task = logging::spawn(async move {
+            while let Some(data) = receiver.recv().await {
+                if let Err(e) = writer.write_all(&data).await {
+                    log_warning!("Failed to flush replica output: {}", e);
+                    break;
+                }
+                task_pending.fetch_sub(data.len(), Ordering::Relaxed);
+            }
+        });
+        Self {
+            sender,
+            pending,
+            limits,
+            soft_limit_since: None,
+            task,
+        }
+    }
+
+    pub fn pending_bytes(&self) -> usize {
+        self.pending.load(Ordering::Relaxed)
+    }
+
+    // 제한을 넘었거나 전송 태스크가 끝났으면 에러, 호출한 쪽에서 레플리카 연결을 끊음
+    pub fn push(&mut self, data: Bytes) -> io::Result<()> {
+        let pending = self.pending.fetch_add(data.len(), Ordering::Relaxed) + data.len();
+        if self.limits.hard_bytes > 0 && pending > self.limits.hard_bytes {
+            return Err(Self::limit_error(pending, "hard"));
+        }
+        if self.limits.soft_bytes > 0 && pending > self.limits.soft_bytes {
+            let since = *self.soft_limit_since.get_or_insert_with(Instant::now);
+            if since.elapsed().as_secs() >= self.limits.soft_seconds {
+                return Err(Self::limit_error(pending, "soft"));
+            }
+        } else {
+            self.soft_limit_since = None;
+        }
+        self.sender
+            .send(data)
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "replica output task stopped"))
+    }
+
+    // 큐를 닫고 전송 태스크가 남은 바이트를 다 쓸 때까지 기다림, 시간 안에 끝나면 true
+    // 전송 태스크가 끝나면서 쓰기 절반이 닫히므로 레플리카는 EOF를 받음
+    pub async fn drain(&mut self, timeout: Duration) -> bool {
+        let (closed, _) = mpsc::unbounded_channel();
+        drop(std::mem::replace(&mut self.sender, closed));
+        tokio::time::timeout(timeout, &mut self.task).await.is_ok()
+    }
+
+    fn limit_error(pending: usize, kind: &str) -> io::Error {
+        io::Error::other(format!("{} output buffer limit reached with {} bytes pending", kind, pending))
+    }
+}
+
+// 연결을 끊을 때 쌓인 데이터를 끝까지 보내지 않고 바로 닫음
+impl Drop for ReplicaOutput {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
@@ -1,66 +1,204 @@
-use crate::protocol_constants::{BULK_STRING_PREFIX, CRLF};
-use rand::distr::Alphanumeric;
-use rand::Rng;
+use crate::logging;
+use crate::protocol_constants::{AUX_REPL_ID, AUX_REPL_OFFSET, AUX_REPL_STREAM_DB, CRLF};
+use crate::random::Random;
+use crate::server_info::RUN_ID_LEN;
 use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+use tokio::time::{Duration, Instant};
+
+// Redis min-replicas-max-lag 기본값, 이보다 오래 ACK가 없으면 INFO에서 lagging으로 표시함
+const REPLICA_MAX_LAG_SECS: u64 = 10;
+// 두 번째 복제 ID가 없을 때 INFO에 보이는 값
+const NO_REPLID: &str = "0000000000000000000000000000000000000000";
 
 #[derive(Clone)]
 pub struct ReplicationConfig {
+    random: Arc<Random>,
     role: Arc<RwLock<String>>,
     master_host: Arc<RwLock<Option<String>>>,
     master_port: Arc<RwLock<Option<u16>>>,
+    // 마스터는 자신의 복제 ID, 레플리카는 FULLRESYNC로 받은 마스터의 복제 ID
     master_replid: Arc<RwLock<String>>,
     master_repl_offset: Arc<RwLock<u64>>,
+    // 승격 전에 쓰던 복제 ID와 그 ID로 이어받을 수 있는 마지막 offset + 1
+    // 예전 마스터의 다른 레플리카가 이 서버로 옮겨 와도 PSYNC로 이어받을 수 있음
+    master_replid2: Arc<RwLock<String>>,
+    second_repl_offset: Arc<RwLock<i64>>,
+    // REPLICAOF NO ONE으로 마스터 링크의 명령을 마저 적용하는 중인지
+    failover_in_progress: Arc<RwLock<bool>>,
+    // RDB에서 복제 ID와 offset을 읽었으면 마스터에 처음 연결할 때 PSYNC로 이어받기를 요청함
+    cached_master: Arc<RwLock<Option<String>>>,
     slaves: Arc<RwLock<Vec<SlaveInfo>>>,
+    master_link: Arc<RwLock<Option<JoinHandle<()>>>>,
+    // 레플리카에서 마스터와 동기화를 마치고 연결되어 있는지, 마지막으로 마스터에게서 데이터를 받은 시각
+    master_link_up: Arc<RwLock<bool>>,
+    master_last_io: Arc<RwLock<Option<Instant>>>,
+    // 같은 주소로 다시 붙어도 구분할 수 있도록 레플리카마다 발급하는 번호
+    next_replica_id: Arc<AtomicU64>,
 }
 
 #[derive(Debug)]
 pub struct SlaveInfo {
+    pub id: u64,
+    pub client_id: u64,
     pub addr: SocketAddr,
     pub offset: i64,
+    // 이 레플리카에게 보낸 복제 스트림 바이트 수, 레플리카가 ACK로 보고하는 offset과 비교함
+    pub sent_offset: i64,
+    pub announced_ip: Option<String>,
+    pub announced_port: Option<u16>,
+    pub getack_sent_at: Option<Instant>,
+    pub ack_latency: Option<Duration>,
+    // 마지막으로 ACK를 받은 시각, 아직 없으면 등록된 시각
+    pub last_ack_at: Instant,
+}
+
+impl SlaveInfo {
+    pub fn ip(&self) -> String {
+        self.announced_ip.clone().unwrap_or_else(|| self.addr.ip().to_string())
+    }
+
+    pub fn port(&self) -> u16 {
+        self.announced_port.unwrap_or(self.addr.port())
+    }
+
+    pub fn ack_latency_ms(&self) -> i64 {
+        self.ack_latency.map_or(-1, |latency| latency.as_millis() as i64)
+    }
+
+    pub fn lag_secs(&self) -> u64 {
+        self.last_ack_at.elapsed().as_secs()
+    }
+
+    pub fn state(&self) -> &'static str {
+        if self.lag_secs() > REPLICA_MAX_LAG_SECS {
+            "lagging"
+        } else {
+            "online"
+        }
+    }
 }
 
 impl ReplicationConfig {
-    pub fn new() -> Self {
-        let replid = Self::generate_replication_id();
+    pub fn new(random: Arc<Random>) -> Self {
         Self {
+            random,
             role: Arc::new(RwLock::new("master".to_string())),
             master_host: Arc::new(RwLock::new(None)),
             master_port: Arc::new(RwLock::new(None)),
-            master_replid: Arc::new(RwLock::new(replid)),
+            // 시작할 때 StateManager::init_random이 run_id로 채움
+            master_replid: Arc::new(RwLock::new(String::new())),
             master_repl_offset: Arc::new(RwLock::new(0)),
+            master_replid2: Arc::new(RwLock::new(NO_REPLID.to_string())),
+            second_repl_offset: Arc::new(RwLock::new(-1)),
+            failover_in_progress: Arc::new(RwLock::new(false)),
+            cached_master: Arc::new(RwLock::new(None)),
             slaves: Arc::new(RwLock::new(Vec::new())),
+            master_link: Arc::new(RwLock::new(None)),
+            master_link_up: Arc::new(RwLock::new(false)),
+            master_last_io: Arc::new(RwLock::new(None)),
+            next_replica_id: Arc::new(AtomicU64::new(1)),
         }
     }
 
-    fn generate_replication_id() -> String {
-        rand::thread_rng()
-            .sample_iter(&Alphanumeric)
-            .take(40)
-            .map(char::from)
-            .collect()
+    // 이전 기록과 이어지지 않는 새 복제 ID, 두 번째 복제 ID도 지움
+    pub async fn set_replid(&self, replid: String) {
+        *self.master_replid.write().await = replid;
+        *self.master_replid2.write().await = NO_REPLID.to_string();
+        *self.second_repl_offset.write().await = -1;
+    }
+
+    // 지금까지의 기록을 이어 가는 새 복제 ID, 지금 ID는 현재 offset까지 두 번째 복제 ID로 받아 줌
+    pub async fn shift_replid(&self, replid: String) {
+        let previous = std::mem::replace(&mut *self.master_replid.write().await, replid);
+        *self.master_replid2.write().await = previous;
+        *self.second_repl_offset.write().await = self.get_repl_offset().await as i64 + 1;
+    }
+
+    // 백로그가 없으므로 레플리카가 이미 모든 바이트를 받았을 때만 이어받기를 받아 줌
+    pub async fn can_continue(&self, replid: &str, psync_offset: u64) -> bool {
+        if psync_offset != self.get_repl_offset().await + 1 {
+            return false;
+        }
+        replid == *self.master_replid.read().await
+            || (replid == *self.master_replid2.read().await && psync_offset as i64 <= *self.second_repl_offset.read().await)
+    }
+
+    // RDB에 남겨 두었던 복제 기록을 이어 씀
+    pub async fn restore_replication(&self, replid: String, offset: u64) {
+        *self.master_replid.write().await = replid.clone();
+        *self.master_repl_offset.write().await = offset;
+        *self.cached_master.write().await = Some(replid);
+    }
+
+    // 다른 마스터로 옮길 때 지금 복제 ID로 이어받기를 요청함
+    pub async fn cache_master(&self) {
+        *self.cached_master.write().await = Some(self.get_repl_id().await);
+    }
+
+    pub async fn take_cached_master(&self) -> Option<String> {
+        self.cached_master.write().await.take()
+    }
+
+    // 저장하는 시점의 복제 ID와 offset, 이 서버는 DB 0만 복제함
+    pub async fn rdb_aux_fields(&self) -> Vec<(&'static str, String)> {
+        vec![
+            (AUX_REPL_STREAM_DB, "0".to_string()),
+            (AUX_REPL_ID, self.get_repl_id().await),
+            (AUX_REPL_OFFSET, self.get_repl_offset().await.to_string()),
+        ]
     }
 
     pub async fn set_replica_of(&self, host: String, port: u16) {
+        logging::set_role('S');
         let mut role_guard = self.role.write().await;
         *role_guard = "slave".to_string();
         let mut master_host = self.master_host.write().await;
         *master_host = Some(host);
         let mut master_port = self.master_port.write().await;
         *master_port = Some(port);
+        *self.master_link_up.write().await = false;
+        *self.master_last_io.write().await = None;
+    }
+
+    pub async fn set_master_link_up(&self, up: bool) {
+        *self.master_link_up.write().await = up;
+    }
+
+    pub async fn record_master_io(&self) {
+        *self.master_last_io.write().await = Some(Instant::now());
+    }
+
+    pub async fn set_master_link(&self, handle: JoinHandle<()>) {
+        if let Some(previous) = self.master_link.write().await.replace(handle) {
+            previous.abort();
+        }
+    }
+
+    pub async fn take_master_link(&self) -> Option<JoinHandle<()>> {
+        self.master_link.write().await.take()
+    }
+
+    pub async fn set_failover_in_progress(&self, in_progress: bool) {
+        *self.failover_in_progress.write().await = in_progress;
     }
 
-    #[allow(dead_code)]
     pub async fn promote_to_master(&self) {
+        logging::set_role('M');
         let mut role_guard = self.role.write().await;
         *role_guard = "master".to_string();
         let mut master_host = self.master_host.write().await;
         *master_host = None;
         let mut master_port = self.master_port.write().await;
         *master_port = None;
-        let mut master_repl_offset = self.master_repl_offset.write().await;
-        *master_repl_offset = 0;
+        // 레플리카는 마스터의 복제 ID를 쓰고 있었으므로 새 ID를 쓰되 offset은 이어 감
+        self.shift_replid(self.random.hex(RUN_ID_LEN)).await;
+        *self.master_link_up.write().await = false;
+        *self.master_last_io.write().await = None;
+        *self.failover_in_progress.write().await = false;
     }
 
     pub async fn get_role(&self) -> String {
@@ -71,24 +209,35 @@ impl ReplicationConfig {
         self.master_replid.read().await.clone()
     }
 
+    pub async fn advance_repl_offset(&self, bytes: usize) {
+        *self.master_repl_offset.write().await += bytes as u64;
+    }
+
+    pub async fn set_repl_offset(&self, offset: u64) {
+        *self.master_repl_offset.write().await = offset;
+    }
+
+    pub async fn get_repl_offset(&self) -> u64 {
+        *self.master_repl_offset.read().await
+    }
+
     pub async fn get_replication_info(&self) -> String {
         let role = self.get_role().await;
-        let mut info = format!("{}role:{}{}", BULK_STRING_PREFIX, role, CRLF);
+        let mut info = format!("# Replication{}role:{}{}", CRLF, role, CRLF);
 
         if role == "master" {
-            let master_replid = self.master_replid.read().await.clone();
-            let master_repl_offset = *self.master_repl_offset.read().await;
-            info.push_str(&format!("master_replid:{}{}", master_replid, CRLF));
-            info.push_str(&format!("master_repl_offset:{}{}", master_repl_offset, CRLF));
             let slaves = self.list_slaves().await;
             info.push_str(&format!("connected_slaves:{}\r\n", slaves.len()));
             for (i, slave) in slaves.iter().enumerate() {
                 info.push_str(&format!(
-                    "slave{}:ip={},port={},state=online,offset={}\r\n",
+                    "slave{}:ip={},port={},state={},offset={},lag={},ack_latency_ms={}\r\n",
                     i,
-                    slave.addr.ip(),
-                    slave.addr.port(),
-                    slave.offset
+                    slave.ip(),
+                    slave.port(),
+                    slave.state(),
+                    slave.offset,
+                    slave.lag_secs(),
+                    slave.ack_latency_ms()
                 ));
             }
         } else if role == "slave" {
@@ -97,34 +246,70 @@ impl ReplicationConfig {
             if let (Some(host), Some(port)) = (master_host.as_ref(), master_port.as_ref()) {
                 info.push_str(&format!("master_host:{}{}", host, CRLF));
                 info.push_str(&format!("master_port:{}{}", port, CRLF));
-                info.push_str(&format!("master_link_status:up{}", CRLF));
+                let link_status = if *self.master_link_up.read().await { "up" } else { "down" };
+                info.push_str(&format!("master_link_status:{}{}", link_status, CRLF));
+                let last_io_seconds_ago = self.master_last_io.read().await.map_or(-1, |last_io| last_io.elapsed().as_secs() as i64);
+                info.push_str(&format!("master_last_io_seconds_ago:{}{}", last_io_seconds_ago, CRLF));
             }
+            info.push_str(&format!("slave_repl_offset:{}{}", self.get_repl_offset().await, CRLF));
         }
 
+        let failover_state = if *self.failover_in_progress.read().await { "failover-in-progress" } else { "no-failover" };
+        info.push_str(&format!("master_failover_state:{}{}", failover_state, CRLF));
+        info.push_str(&format!("master_replid:{}{}", self.get_repl_id().await, CRLF));
+        info.push_str(&format!("master_replid2:{}{}", self.master_replid2.read().await, CRLF));
+        info.push_str(&format!("master_repl_offset:{}{}", self.get_repl_offset().await, CRLF));
+        info.push_str(&format!("second_repl_offset:{}{}", self.second_repl_offset.read().await, CRLF));
+
         info
     }
-    pub async fn register_slave(&self, addr: SocketAddr) {
+    // 이미 등록된 연결이면 기존 번호를 그대로 돌려줌
+    // offset은 레플리카가 동기화를 마친 복제 offset, 이후 보내는 바이트를 여기에 더함
+    pub async fn register_slave(&self, client_id: u64, addr: SocketAddr, listening_port: Option<u16>, offset: u64) -> u64 {
         let mut slaves = self.slaves.write().await;
-        if !slaves.iter().any(|slave| slave.addr == addr) {
+        if let Some(slave) = slaves.iter_mut().find(|slave| slave.client_id == client_id) {
+            slave.announced_port = listening_port;
+            slave.id
+        } else {
+            let id = self.next_replica_id.fetch_add(1, Ordering::Relaxed);
             slaves.push(SlaveInfo {
+                id,
+                client_id,
                 addr,
-                offset: 0,
+                offset: offset as i64,
+                sent_offset: offset as i64,
+                announced_ip: None,
+                announced_port: listening_port,
+                getack_sent_at: None,
+                ack_latency: None,
+                last_ack_at: Instant::now(),
             });
+            id
         }
     }
 
-    pub async fn update_slave_offset(&self, addr: SocketAddr, offset: i64) {
+    // 등록되어 있던 레플리카의 번호
+    pub async fn unregister_slave(&self, client_id: u64) -> Option<u64> {
         let mut slaves = self.slaves.write().await;
-        if let Some(slave) = slaves.iter_mut().find(|slave| slave.addr == addr) {
-            slave.offset = offset;
-        }
+        let index = slaves.iter().position(|slave| slave.client_id == client_id)?;
+        Some(slaves.remove(index).id)
     }
 
+    pub async fn set_slave_announced_ip(&self, client_id: u64, ip: String) {
+        let mut slaves = self.slaves.write().await;
+        if let Some(slave) = slaves.iter_mut().find(|slave| slave.client_id == client_id) {
+            slave.announced_ip = Some(ip);
+        }
+    }
 
-    pub async fn update_slave_state(&self, addr: SocketAddr, offset: i64) {
+    pub async fn record_slave_ack(&self, client_id: u64, offset: i64) {
         let mut slaves = self.slaves.write().await;
-        if let Some(slave) = slaves.iter_mut().find(|slave| slave.addr == addr) {
+        if let Some(slave) = slaves.iter_mut().find(|slave| slave.client_id == client_id) {
             slave.offset = offset;
+            slave.last_ack_at = Instant::now();
+            if let Some(sent_at) = slave.getack_sent_at.take() {
+                slave.ack_latency = Some(sent_at.elapsed());
+            }
         }
     }
 
@@ -0,0 +1,211 @@
+use crate::command::format_score;
+use crate::errors::RedisError;
+use crate::protocol_constants::*;
+
+// 모든 응답은 이 타입으로 만들고, 쓰기 직전에 클라이언트가 협상한 프로토콜로 인코딩함
+// RESP2 연결에는 RESP3 전용 타입을 Redis와 같이 가장 가까운 RESP2 타입으로 바꿔서 보냄
+#[derive(Debug, Clone, PartialEq)]
+pub enum RespValue {
+    SimpleString(String),
+    // 에러 코드를 포함한 전체 메시지 (예: "ERR ...", "WRONGTYPE ...")
+    Error(String),
+    Integer(i64),
+    BulkString(Vec<u8>),
+    Array(Vec<RespValue>),
+    // RESP2의 nil bulk string과 nil 배열, RESP3에서는 둘 다 null
+    NullBulk,
+    NullArray,
+    // RESP2에서는 키와 값을 번갈아 담은 배열
+    Map(Vec<(RespValue, RespValue)>),
+    Set(Vec<RespValue>),
+    // 구독 메시지와 무효화 메시지, RESP2에서는 일반 배열
+    Push(Vec<RespValue>),
+    Double(f64),
+    Boolean(bool),
+    BigNumber(String),
+}
+
+impl From<RedisError> for RespValue {
+    fn from(error: RedisError) -> Self {
+        RespValue::Error(error.to_string())
+    }
+}
+
+impl RespValue {
+    pub fn ok() -> Self {
+        RespValue::SimpleString("OK".into())
+    }
+
+    pub fn bulk(value: impl Into<Vec<u8>>) -> Self {
+        RespValue::BulkString(value.into())
+    }
+
+    pub fn bulk_array<T: AsRef<[u8]>>(values: &[T]) -> Self {
+        RespValue::Array(values.iter().map(|value| RespValue::bulk(value.as_ref())).collect())
+    }
+
+    pub fn integer_array(values: &[i64]) -> Self {
+        RespValue::Array(values.iter().map(|value| RespValue::Integer(*value)).collect())
+    }
+
+    // 이름이 bulk string인 필드 맵
+    pub fn field_map(fields: Vec<(&str, RespValue)>) -> Self {
+        RespValue::Map(fields.into_iter().map(|(name, value)| (RespValue::bulk(name), value)).collect())
+    }
+
+    // 코드가 따로 없는 에러 메시지, 코드가 있는 에러는 RedisError에서 바꿈
+    pub fn error(message: &str) -> Self {
+        RespValue::from(RedisError::from(message))
+    }
+
+    pub fn encode(&self, protocol: u8) -> Vec<u8> {
+        let mut out = Vec::new();
+        self.encode_into(protocol, &mut out);
+        out
+    }
+
+    fn encode_into(&self, protocol: u8, out: &mut Vec<u8>) {
+        let resp3 = protocol == RESP3_PROTOCOL;
+        match self {
+            RespValue::SimpleString(value) => push_status_line(out, SIMPLE_STRING_PREFIX, value),
+            RespValue::Error(message) => push_status_line(out, ERROR_PREFIX, message),
+            RespValue::Integer(value) => push_line(out, INTEGER_PREFIX, &value.to_string()),
+            RespValue::BulkString(value) => {
+                push_line(out, BULK_STRING_PREFIX, &value.len().to_string());
+                out.extend_from_slice(value);
+                out.extend_from_slice(CRLF.as_bytes());
+            }
+            RespValue::NullBulk | RespValue::NullArray if resp3 => push_line(out, NULL_PREFIX, ""),
+            RespValue::NullBulk => push_line(out, BULK_STRING_PREFIX, "-1"),
+            RespValue::NullArray => push_line(out, ARRAY_PREFIX, "-1"),
+            RespValue::Array(items) => Self::encode_items(ARRAY_PREFIX, items, protocol, out),
+            RespValue::Set(items) => Self::encode_items(if resp3 { SET_PREFIX } else { ARRAY_PREFIX }, items, protocol, out),
+            RespValue::Push(items) => Self::encode_items(if resp3 { PUSH_PREFIX } else { ARRAY_PREFIX }, items, protocol, out),
+            RespValue::Map(entries) => {
+                if resp3 {
+                    push_line(out, MAP_PREFIX, &entries.len().to_string());
+                } else {
+                    push_line(out, ARRAY_PREFIX, &(entries.len() * 2).to_string());
+                }
+                for (key, value) in entries {
+                    key.encode_into(protocol, out);
+                    value.encode_into(protocol, out);
+                }
+            }
+            RespValue::Double(value) if resp3 => push_line(out, DOUBLE_PREFIX, &format_score(*value)),
+            RespValue::Double(value) => RespValue::bulk(format_score(*value)).encode_into(protocol, out),
+            RespValue::Boolean(value) if resp3 => push_line(out, BOOLEAN_PREFIX, if *value { "t" } else { "f" }),
+            RespValue::Boolean(value) => RespValue::Integer(*value as i64).encode_into(protocol, out),
+            RespValue::BigNumber(value) if resp3 => push_line(out, BIG_NUMBER_PREFIX, value),
+            RespValue::BigNumber(value) => RespValue::bulk(value.as_str()).encode_into(protocol, out),
+        }
+    }
+
+    fn encode_items(prefix: &str, items: &[RespValue], protocol: u8, out: &mut Vec<u8>) {
+        push_line(out, prefix, &items.len().to_string());
+        for item in items {
+            item.encode_into(protocol, out);
+        }
+    }
+
+    // 버퍼 앞의 값 하나와 그 길이, 아직 다 오지 않았으면 None
+    pub fn parse(buffer: &[u8]) -> Result<Option<(RespValue, usize)>, String> {
+        let Some(line_end) = buffer.windows(2).position(|window| window == CRLF.as_bytes()) else {
+            return Ok(None);
+        };
+        let line = String::from_utf8_lossy(&buffer[1..line_end]).to_string();
+        let header_len = line_end + CRLF.len();
+        let number = || line.parse::<i64>().map_err(|_| format!("Invalid reply header: {}", line));
+        let value = match buffer[0] as char {
+            '+' => RespValue::SimpleString(line),
+            '-' => RespValue::Error(line),
+            ':' => RespValue::Integer(number()?),
+            '_' => RespValue::NullBulk,
+            ',' => RespValue::Double(match line.as_str() {
+                "inf" => f64::INFINITY,
+                "-inf" => f64::NEG_INFINITY,
+                line => line.parse().map_err(|_| format!("Invalid double reply: {}", line))?,
+            }),
+            '#' => RespValue::Boolean(line == "t"),
+            '(' => RespValue::BigNumber(line),
+            '$' => {
+                let len = number()?;
+                if len < 0 {
+                    return Ok(Some((RespValue::NullBulk, header_len)));
+                }
+                let end = header_len + len as usize;
+                if buffer.len() < end + CRLF.len() {
+                    return Ok(None);
+                }
+                return Ok(Some((RespValue::BulkString(buffer[header_len..end].to_vec()), end + CRLF.len())));
+            }
+            prefix @ ('*' | '~' | '>' | '%') => {
+                let count = number()?;
+                if count < 0 {
+                    return Ok(Some((RespValue::NullArray, header_len)));
+                }
+                let count = if prefix == '%' { count * 2 } else { count };
+                let mut items = Vec::new();
+                let mut consumed = header_len;
+                for _ in 0..count {
+                    let Some((item, len)) = Self::parse(&buffer[consumed..])? else {
+                        return Ok(None);
+                    };
+                    items.push(item);
+                    consumed += len;
+                }
+                let value = match prefix {
+                    '~' => RespValue::Set(items),
+                    '>' => RespValue::Push(items),
+                    '%' => {
+                        let mut entries = Vec::new();
+                        let mut items = items.into_iter();
+                        while let (Some(key), Some(value)) = (items.next(), items.next()) {
+                            entries.push((key, value));
+                        }
+                        RespValue::Map(entries)
+                    }
+                    _ => RespValue::Array(items),
+                };
+                return Ok(Some((value, consumed)));
+            }
+            prefix => return Err(format!("Unexpected reply prefix '{}'", prefix)),
+        };
+        Ok(Some((value, header_len)))
+    }
+}
+
+fn push_line(out: &mut Vec<u8>, prefix: &str, line: &str) {
+    out.extend_from_slice(prefix.as_bytes());
+    out.extend_from_slice(line.as_bytes());
+    out.extend_from_slice(CRLF.as_bytes());
+}
+
+// 에러와 simple string에는 클라이언트가 보낸 값이 섞일 수 있음, Redis처럼 줄바꿈을 공백으로 바꿔 응답이 한 줄로 끝나게 함
+fn push_status_line(out: &mut Vec<u8>, prefix: &str, line: &str) {
+    out.extend_from_slice(prefix.as_bytes());
+    out.extend(line.bytes().map(|byte| if byte == b'\r' || byte == b'\n' { b' ' } else { byte }));
+    out.extend_from_slice(CRLF.as_bytes());
+}
+
+// DEBUG PROTOCOL <type>: 각 타입의 예시 값, RESP2 연결에는 인코딩할 때 가장 가까운 RESP2 타입으로 바뀜
+// double 예시 값은 Redis와 같은 3.141
+#[allow(clippy::approx_constant)]
+pub fn debug_protocol_reply(kind: &str) -> Option<RespValue> {
+    let integers = || (0..3).map(RespValue::Integer).collect::<Vec<_>>();
+    let reply = match kind {
+        "string" => RespValue::bulk("Hello World"),
+        "integer" => RespValue::Integer(12345),
+        "double" => RespValue::Double(3.141),
+        "bignum" => RespValue::BigNumber("1234567999999999999999999999999999999".into()),
+        "null" => RespValue::NullBulk,
+        "array" => RespValue::Array(integers()),
+        "set" => RespValue::Set(integers()),
+        "push" => RespValue::Push(integers()),
+        "map" => RespValue::Map((0..3).map(|i| (RespValue::Integer(i), RespValue::Boolean(i == 1))).collect()),
+        "true" => RespValue::Boolean(true),
+        "false" => RespValue::Boolean(false),
+        _ => return None,
+    };
+    Some(reply)
+}
@@ -0,0 +1,122 @@
+use crate::lazyfree;
+use std::collections::HashMap;
+
+// Redis 스크립트 캐시: 본문의 SHA1(소문자 hex)을 키로 사용함
+// SCRIPT LOAD와 EVAL이 넣고 EVALSHA가 꺼내 씀
+pub struct ScriptCache {
+    scripts: HashMap<String, String>,
+}
+
+impl ScriptCache {
+    pub fn new() -> Self {
+        Self {
+            scripts: HashMap::new(),
+        }
+    }
+
+    pub fn load(&mut self, body: &str) -> String {
+        let sha = sha1_hex(body.as_bytes());
+        self.scripts.entry(sha.clone()).or_insert_with(|| body.to_string());
+        sha
+    }
+
+    pub fn exists(&self, sha: &str) -> bool {
+        self.scripts.contains_key(&sha.to_lowercase())
+    }
+
+    pub fn get(&self, sha: &str) -> Option<&str> {
+        self.scripts.get(&sha.to_lowercase()).map(String::as_str)
+    }
+
+    pub fn flush(&mut self, lazy: bool) {
+        let scripts = std::mem::take(&mut self.scripts);
+        if lazy {
+            lazyfree::free_scripts(scripts);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.scripts.len()
+    }
+}
+
+pub fn sha1_hex(data: &[u8]) -> String {
+    let mut state: [u32; 5] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&((data.len() as u64) * 8).to_be_bytes());
+
+    for block in message.chunks(64) {
+        let mut w = [0u32; 80];
+        for (i, word) in block.chunks(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..80 {
+            w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e] = state;
+        for (i, word) in w.iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => ((b & c) | (!b & d), 0x5A827999),
+                20..=39 => (b ^ c ^ d, 0x6ED9EBA1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8F1BBCDC),
+                _ => (b ^ c ^ d, 0xCA62C1D6),
+            };
+            let temp = a.rotate_left(5).wrapping_add(f).wrapping_add(e).wrapping_add(k).wrapping_add(*word);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+        }
+
+        for (value, add) in state.iter_mut().zip([a, b, c, d, e]) {
+            *value = value.wrapping_add(add);
+        }
+    }
+
+    state.iter().map(|value| format!("{:08x}", value)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sha1_matches_the_fips_vectors() {
+        assert_eq!(sha1_hex(b""), "da39a3ee5e6b4b0d3255bfef95601890afd80709");
+        assert_eq!(sha1_hex(b"abc"), "a9993e364706816aba3e25717850c26c9cd0d89d");
+        assert_eq!(
+            sha1_hex(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"),
+            "84983e441c3bd26ebaae4aa1f95129e5e54670f1"
+        );
+        assert_eq!(sha1_hex(&vec![b'a'; 1_000_000]), "34aa973cd4c4daa4f61eeb2bdbad27316534016f");
+    }
+
+    #[test]
+    fn sha1_matches_redis_script_ids() {
+        // SCRIPT LOAD "return 1"이 돌려주는 값
+        assert_eq!(sha1_hex(b"return 1"), "e0e1f9fabfc9d4800c877a703b823ac0578ff8db");
+        assert_eq!(sha1_hex(b"The quick brown fox jumps over the lazy dog"), "2fd4e1c67a2d28fced849ee1bb76e7391b93eb12");
+    }
+
+    #[test]
+    fn cache_is_keyed_by_case_insensitive_sha() {
+        let mut cache = ScriptCache::new();
+        let sha = cache.load("return 1");
+        assert_eq!(cache.load("return 1"), sha);
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.get(&sha.to_uppercase()), Some("return 1"));
+        assert!(cache.exists(&sha));
+        assert!(!cache.exists("0000000000000000000000000000000000000000"));
+
+        cache.flush(false);
+        assert_eq!(cache.len(), 0);
+        assert_eq!(cache.get(&sha), None);
+    }
+}
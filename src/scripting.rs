@@ -0,0 +1,634 @@
+use crate::command::{format_score, Command, FunctionCommand, ScriptCommand};
+use crate::errors::RedisError;
+use crate::logging::{log_debug, log_notice, log_verbose, log_warning};
+use crate::lua::{self, format_g, Interpreter, LuaError, TableRef, Value};
+use crate::lua_parser;
+use crate::lua_stdlib::{check_integer, check_string, register};
+use crate::protocol_constants::*;
+use crate::resp::RespValue;
+use crate::script_cache::sha1_hex;
+use crate::util::glob_match;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc as std_mpsc;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+
+// EVAL 스크립트와 함수 라이브러리의 청크 이름, 에러 위치가 "user_script:<line>"처럼 나옴
+const SCRIPT_CHUNK: &str = "user_script";
+const FUNCTION_CHUNK: &str = "user_function";
+// 스크립트가 돌려준 테이블을 응답으로 바꿀 때의 중첩 제한, 자기 자신을 담은 테이블도 여기서 멈춤
+const MAX_REPLY_DEPTH: usize = 100;
+// redis.call에 넘긴 숫자 인자를 문자열로 바꿀 때 쓰는 %.17g의 정밀도
+const CALL_NUMBER_PRECISION: usize = 17;
+// redis.log의 레벨 상수, Redis와 같은 값
+const LOG_LEVELS: [(&str, i64); 4] = [("LOG_DEBUG", 0), ("LOG_VERBOSE", 1), ("LOG_NOTICE", 2), ("LOG_WARNING", 3)];
+// FUNCTION LOAD가 라이브러리 코드를 실행하는 시간 제한, 이벤트 루프가 기다리므로 Redis처럼 짧게 둠
+const FUNCTION_LOAD_TIMEOUT: Duration = Duration::from_millis(500);
+
+// SCRIPT LOAD처럼 실행하지 않고 문법만 확인함, 파서도 재귀로 내려가므로 인터프리터 스레드의 스택에서 돌림
+pub fn check_syntax(body: &str) -> Result<(), String> {
+    let source = body.as_bytes().to_vec();
+    let checked = lua::spawn(move || {
+        lua_parser::parse(&source)
+            .map(|_| ())
+            .map_err(|e| format!("{}: {}:{}: {}", SCRIPT_COMPILE_ERROR, SCRIPT_CHUNK, e.line, e.message))
+    });
+    match checked.map(|handle| handle.join()) {
+        Ok(Ok(result)) => result,
+        _ => Err(SCRIPT_ABORTED_ERROR.into()),
+    }
+}
+
+// 지금 도는 스크립트, 이벤트 루프가 스크립트를 기다리는 동안 연결 태스크가 보고 BUSY 응답과 SCRIPT KILL을 직접 처리함
+#[derive(Default)]
+pub struct ScriptMonitor {
+    running: Mutex<Option<RunningScript>>,
+}
+
+struct RunningScript {
+    client_id: u64,
+    function: bool,
+    started_at: Instant,
+    // busy-reply-threshold, 이 시간이 지나야 다른 클라이언트에게 BUSY를 보내고 스크립트를 멈출 수 있음
+    busy_after: Duration,
+    // 쓰기 명령을 부른 스크립트는 중간에 멈추면 데이터가 반쯤 바뀌므로 SCRIPT KILL로 멈출 수 없음
+    wrote: bool,
+    interrupt: Arc<AtomicBool>,
+}
+
+impl ScriptMonitor {
+    // 인터프리터에 넘길 중단 신호를 돌려줌
+    pub fn start(&self, client_id: u64, function: bool, busy_after: Duration) -> Arc<AtomicBool> {
+        let interrupt = Arc::new(AtomicBool::new(false));
+        *self.running.lock().unwrap() = Some(RunningScript {
+            client_id,
+            function,
+            started_at: Instant::now(),
+            busy_after,
+            wrote: false,
+            interrupt: interrupt.clone(),
+        });
+        interrupt
+    }
+
+    pub fn finish(&self) {
+        *self.running.lock().unwrap() = None;
+    }
+
+    pub fn record_write(&self) {
+        if let Some(running) = self.running.lock().unwrap().as_mut() {
+            running.wrote = true;
+        }
+    }
+
+    // 이벤트 루프를 거치지 않고 연결 태스크가 바로 보낼 응답, None이면 평소처럼 이벤트 큐에 올림
+    // 스크립트를 부른 클라이언트나 아직 처리되지 않은 명령이 있는 클라이언트는 응답 순서를 지키려고 큐에서 기다리게 함
+    pub fn busy_reply(&self, client_id: u64, inflight: usize, command: &Command) -> Option<RespValue> {
+        let mut running = self.running.lock().unwrap();
+        let script = running.as_mut().filter(|script| script.started_at.elapsed() >= script.busy_after)?;
+        if script.client_id == client_id || inflight > 0 {
+            return None;
+        }
+        let busy = if script.function { RedisError::FunctionBusy } else { RedisError::ScriptBusy };
+        match command {
+            Command::SCRIPT(ScriptCommand::KILL) | Command::FUNCTION(FunctionCommand::KILL) => {
+                let function_kill = matches!(command, Command::FUNCTION(_));
+                if function_kill != script.function {
+                    return Some(RespValue::from(busy));
+                }
+                if script.wrote {
+                    return Some(RespValue::from(RedisError::Unkillable));
+                }
+                script.interrupt.store(true, Ordering::Relaxed);
+                Some(RespValue::ok())
+            }
+            // 쓰기를 했어도 멈추고, 큐에 올린 SHUTDOWN이 스크립트가 끝난 뒤 저장 없이 종료함
+            Command::SHUTDOWN(Some(false)) => {
+                script.interrupt.store(true, Ordering::Relaxed);
+                None
+            }
+            _ => Some(RespValue::from(busy)),
+        }
+    }
+}
+
+// 스크립트 스레드가 이벤트 루프에 보내는 요청
+pub enum ScriptStep {
+    // redis.call/redis.pcall로 부른 명령, 이벤트 루프가 실행한 응답을 reply로 받아 스크립트를 이어서 돌림
+    Call { args: Vec<Vec<u8>>, reply: std_mpsc::Sender<RespValue> },
+    // 스크립트가 끝났고 클라이언트에게 보낼 응답
+    Done(RespValue),
+}
+
+// 인터프리터는 Rc로 값을 나누므로 실행마다 스레드를 띄워서 돌리고, 이벤트 루프는 Done이 올 때까지 Call만 처리함
+// 그동안 다른 이벤트를 처리하지 않으므로 스크립트 전체가 원자적으로 실행됨
+pub struct ScriptRun {
+    steps: mpsc::Receiver<ScriptStep>,
+}
+
+impl ScriptRun {
+    pub fn eval(body: String, sha: String, keys: Vec<Vec<u8>>, args: Vec<Vec<u8>>, interrupt: Arc<AtomicBool>) -> Self {
+        Self::start(move |steps| run_eval(&body, &sha, keys, args, steps, interrupt))
+    }
+
+    // 라이브러리 코드를 다시 읽어서 그 안의 함수 하나를 부름
+    pub fn function(code: String, name: String, keys: Vec<Vec<u8>>, args: Vec<Vec<u8>>, interrupt: Arc<AtomicBool>) -> Self {
+        Self::start(move |steps| run_function(&code, &name, keys, args, steps, interrupt))
+    }
+
+    fn start(run: impl FnOnce(&mpsc::Sender<ScriptStep>) -> RespValue + Send + 'static) -> Self {
+        let (sender, steps) = mpsc::channel(1);
+        let thread_sender = sender.clone();
+        let spawned = lua::spawn(move || {
+            let reply = run(&thread_sender);
+            let _ = thread_sender.blocking_send(ScriptStep::Done(reply));
+        });
+        if let Err(e) = spawned {
+            log_warning!("Failed to start a script thread: {}", e);
+            let _ = sender.try_send(ScriptStep::Done(RespValue::error(SCRIPT_ABORTED_ERROR)));
+        }
+        Self { steps }
+    }
+
+    // 스크립트 스레드가 응답 없이 끝났으면(패닉) 에러로 마침
+    pub async fn next(&mut self) -> ScriptStep {
+        self.steps.recv().await.unwrap_or_else(|| ScriptStep::Done(RespValue::error(SCRIPT_ABORTED_ERROR)))
+    }
+}
+
+fn run_eval(body: &str, sha: &str, keys: Vec<Vec<u8>>, args: Vec<Vec<u8>>, steps: &mpsc::Sender<ScriptStep>, interrupt: Arc<AtomicBool>) -> RespValue {
+    let mut interpreter = Interpreter::new(SCRIPT_CHUNK);
+    interpreter.set_interrupt(interrupt, SCRIPT_KILLED_ERROR);
+    let redis = open_redis(&mut interpreter);
+    register_calls(&redis, steps);
+    let keys = interpreter.table_from(keys.into_iter().map(Value::string));
+    interpreter.set_global("KEYS", keys);
+    let args = interpreter.table_from(args.into_iter().map(Value::string));
+    interpreter.set_global("ARGV", args);
+    let function = match interpreter.load(body.as_bytes()) {
+        Ok(function) => function,
+        Err(e) => return RespValue::error(&format!("{}: {}", SCRIPT_COMPILE_ERROR, e)),
+    };
+    // Redis 7처럼 스크립트는 전역 변수를 만들 수 없음
+    interpreter.lock_globals();
+    match interpreter.call(&function, Vec::new()) {
+        Ok(values) => to_reply(values.first().unwrap_or(&Value::Nil), 0),
+        Err(e) => script_error_reply(&e, sha, SCRIPT_CHUNK),
+    }
+}
+
+fn run_function(code: &str, name: &str, keys: Vec<Vec<u8>>, args: Vec<Vec<u8>>, steps: &mpsc::Sender<ScriptStep>, interrupt: Arc<AtomicBool>) -> RespValue {
+    let mut interpreter = Interpreter::new(FUNCTION_CHUNK);
+    interpreter.set_interrupt(interrupt, FUNCTION_KILLED_ERROR);
+    let redis = open_redis(&mut interpreter);
+    let functions = match load_library(&mut interpreter, &redis, code) {
+        Ok(functions) => functions,
+        Err(e) => return RespValue::error(&e),
+    };
+    let Some((_, callback)) = functions.into_iter().find(|(function, _)| function.name == name) else {
+        return RespValue::error(FUNCTION_NOT_FOUND_ERROR);
+    };
+    register_calls(&redis, steps);
+    let keys = interpreter.table_from(keys.into_iter().map(Value::string));
+    let args = interpreter.table_from(args.into_iter().map(Value::string));
+    match interpreter.call(&callback, vec![keys, args]) {
+        Ok(values) => to_reply(values.first().unwrap_or(&Value::Nil), 0),
+        Err(e) => script_error_reply(&e, name, FUNCTION_CHUNK),
+    }
+}
+
+// 라이브러리 코드를 실행해서 redis.register_function으로 등록한 함수와 콜백을 모음
+// 코드를 읽는 동안에는 redis.call을 부를 수 없고, 다 읽은 뒤에는 register_function을 부를 수 없음
+fn load_library(interpreter: &mut Interpreter, redis: &TableRef, code: &str) -> Result<Vec<(FunctionInfo, Value)>, String> {
+    interpreter.lock_globals();
+    let registered: Rc<RefCell<Vec<(FunctionInfo, Value)>>> = Rc::default();
+    let functions = registered.clone();
+    register(redis, "register_function", move |interpreter, args| {
+        let (function, callback) = register_function(interpreter, &args)?;
+        if functions.borrow().iter().any(|(existing, _)| existing.name == function.name) {
+            return Err(interpreter.raise(Value::string(format!("Function already exists in the library: {}", function.name))));
+        }
+        functions.borrow_mut().push((function, callback));
+        Ok(Vec::new())
+    });
+    let library = interpreter.load(code.as_bytes()).map_err(|e| format!("{}: {}", FUNCTION_COMPILE_ERROR, e))?;
+    let loaded = interpreter.call(&library, Vec::new());
+    redis.borrow_mut().set_str("register_function", Value::Nil);
+    loaded.map_err(|e| format!("{}: {}", FUNCTION_REGISTER_ERROR, error_message(&e.value)))?;
+    let functions = registered.take();
+    if functions.is_empty() {
+        return Err(FUNCTION_NOTHING_REGISTERED_ERROR.into());
+    }
+    Ok(functions)
+}
+
+// redis.register_function(name, callback) 또는 redis.register_function{function_name=..., callback=..., flags={...}, description=...}
+fn register_function(interpreter: &Interpreter, args: &[Value]) -> Result<(FunctionInfo, Value), LuaError> {
+    let fail = |message: &str| interpreter.raise(Value::string(message));
+    let (name, callback, description, flags) = match args {
+        [Value::Table(table)] => {
+            let table = table.borrow();
+            (table.get_str("function_name"), table.get_str("callback"), table.get_str("description"), table.get_str("flags"))
+        }
+        [name, callback] => (name.clone(), callback.clone(), Value::Nil, Value::Nil),
+        _ => return Err(fail("wrong number of arguments to redis.register_function")),
+    };
+    let Value::String(name) = name else {
+        return Err(fail("function_name argument given to redis.register_function must be a string"));
+    };
+    let name = String::from_utf8_lossy(&name).into_owned();
+    if !is_valid_function_name(&name) {
+        return Err(fail(FUNCTION_INVALID_NAME_ERROR));
+    }
+    if !matches!(callback, Value::Function(_)) {
+        return Err(fail("callback argument given to redis.register_function must be a function"));
+    }
+    let description = match description {
+        Value::Nil => None,
+        Value::String(description) => Some(String::from_utf8_lossy(&description).into_owned()),
+        _ => return Err(fail("description argument given to redis.register_function must be a string")),
+    };
+    let flags = match flags {
+        Value::Nil => Vec::new(),
+        Value::Table(table) => {
+            let table = table.borrow();
+            let flags: Vec<Value> = (1..).map(|index| table.get(&Value::Number(index as f64))).take_while(|flag| !flag.is_nil()).collect();
+            let mut names = Vec::with_capacity(flags.len());
+            for flag in flags {
+                match flag {
+                    Value::String(flag) if FUNCTION_FLAGS.iter().any(|known| known.as_bytes() == &flag[..]) => {
+                        names.push(String::from_utf8_lossy(&flag).into_owned());
+                    }
+                    _ => return Err(fail(FUNCTION_UNKNOWN_FLAG_ERROR)),
+                }
+            }
+            names
+        }
+        _ => return Err(fail("flags argument to redis.register_function must be a table representing function flags")),
+    };
+    Ok((FunctionInfo { name, description, flags }, callback))
+}
+
+// 스크립트가 잡지 않은 에러, Redis처럼 스크립트 이름과 줄을 붙임
+fn script_error_reply(error: &LuaError, name: &str, chunk: &str) -> RespValue {
+    let message = match &error.value {
+        Value::Table(table) if matches!(table.borrow().get_str("err"), Value::String(_)) => error_message(&error.value),
+        value => format!("ERR {}", error_message(value)),
+    };
+    RespValue::Error(format!("{} script: {}, on @{}:{}.", single_line(&message), name, chunk, error.line))
+}
+
+// 던져진 값의 메시지, {err = ...} 테이블이면 그 문자열
+fn error_message(value: &Value) -> String {
+    if let Value::Table(table) = value {
+        if let Value::String(message) = table.borrow().get_str("err") {
+            return String::from_utf8_lossy(&message).into_owned();
+        }
+    }
+    String::from_utf8_lossy(&value.display()).into_owned()
+}
+
+// 스크립트에 여는 redis 테이블, 명령을 부르는 call/pcall은 register_calls로 따로 넣음
+fn open_redis(interpreter: &mut Interpreter) -> TableRef {
+    let redis = interpreter.new_table();
+    register(&redis, "error_reply", |interpreter, args| {
+        let message = String::from_utf8_lossy(&check_string(interpreter, &args, 0, "error_reply")?).into_owned();
+        let message = if message.starts_with('-') { message } else { format!("-{}", message) };
+        Ok(vec![error_table(interpreter, &message)])
+    });
+    register(&redis, "status_reply", |interpreter, args| {
+        let status = check_string(interpreter, &args, 0, "status_reply")?;
+        Ok(vec![field_table(interpreter, "ok", Value::String(status))])
+    });
+    register(&redis, "sha1hex", |interpreter, args| {
+        let data = check_string(interpreter, &args, 0, "sha1hex")?;
+        Ok(vec![Value::string(sha1_hex(&data))])
+    });
+    register(&redis, "log", |interpreter, args| {
+        if args.len() < 2 {
+            return Err(raise_error(interpreter, "redis.log() requires two arguments or more."));
+        }
+        let level = check_integer(interpreter, &args, 0, "log")?;
+        let message: Vec<String> = args[1..]
+            .iter()
+            .filter_map(Value::to_bytes)
+            .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+            .collect();
+        let message = message.join(" ");
+        match level {
+            0 => log_debug!("{}", message),
+            1 => log_verbose!("{}", message),
+            2 => log_notice!("{}", message),
+            3 => log_warning!("{}", message),
+            _ => return Err(raise_error(interpreter, "Invalid debug level.")),
+        }
+        Ok(Vec::new())
+    });
+    // Redis 7부터는 항상 명령 효과를 복제하므로 호환용으로만 남아 있음
+    register(&redis, "replicate_commands", |_, _| Ok(vec![Value::Boolean(true)]));
+    for (name, level) in LOG_LEVELS {
+        redis.borrow_mut().set_str(name, Value::Number(level as f64));
+    }
+    interpreter.set_global("redis", Value::Table(redis.clone()));
+    redis
+}
+
+fn register_calls(redis: &TableRef, steps: &mpsc::Sender<ScriptStep>) {
+    for (name, raise) in [("call", true), ("pcall", false)] {
+        let steps = steps.clone();
+        register(redis, name, move |interpreter, args| redis_call(interpreter, &args, &steps, raise));
+    }
+}
+
+// redis.call은 에러 응답을 에러로 던지고, redis.pcall은 {err = ...} 테이블로 돌려줌
+fn redis_call(interpreter: &mut Interpreter, args: &[Value], steps: &mpsc::Sender<ScriptStep>, raise: bool) -> Result<Vec<Value>, LuaError> {
+    let reply = match call_args(args) {
+        Ok(args) => send_call(args, steps),
+        Err(message) => RespValue::error(message),
+    };
+    let failed = matches!(reply, RespValue::Error(_));
+    let value = to_lua(interpreter, reply);
+    if failed && raise {
+        return Err(interpreter.raise(value));
+    }
+    Ok(vec![value])
+}
+
+fn call_args(args: &[Value]) -> Result<Vec<Vec<u8>>, &'static str> {
+    if args.is_empty() {
+        return Err(SCRIPT_NO_ARGUMENTS_ERROR);
+    }
+    args.iter()
+        .map(|arg| match arg {
+            Value::String(bytes) => Ok(bytes.to_vec()),
+            Value::Number(number) => Ok(format_g(*number, CALL_NUMBER_PRECISION, false).into_bytes()),
+            _ => Err(SCRIPT_ARGUMENT_TYPE_ERROR),
+        })
+        .collect()
+}
+
+// 이벤트 루프에 명령을 넘기고 응답이 올 때까지 스크립트 스레드를 멈춤
+fn send_call(args: Vec<Vec<u8>>, steps: &mpsc::Sender<ScriptStep>) -> RespValue {
+    let (reply, response) = std_mpsc::channel();
+    if steps.blocking_send(ScriptStep::Call { args, reply }).is_err() {
+        return RespValue::error(SCRIPT_ABORTED_ERROR);
+    }
+    response.recv().unwrap_or_else(|_| RespValue::error(SCRIPT_ABORTED_ERROR))
+}
+
+fn raise_error(interpreter: &mut Interpreter, message: &str) -> LuaError {
+    let error = error_table(interpreter, message);
+    interpreter.raise(error)
+}
+
+// Redis의 luaPushErrorBuff처럼 "-CODE message"는 '-'만 떼고, 코드가 없으면 ERR를 붙인 {err = ...} 테이블
+fn error_table(interpreter: &mut Interpreter, text: &str) -> Value {
+    let text = text.trim_end_matches(['\r', '\n']);
+    let message = match text.strip_prefix('-') {
+        Some(error) if error.contains(' ') => error.to_string(),
+        Some(error) => format!("ERR {}", error),
+        None => format!("ERR {}", text),
+    };
+    field_table(interpreter, "err", Value::string(message))
+}
+
+fn field_table(interpreter: &mut Interpreter, field: &str, value: Value) -> Value {
+    let table = interpreter.new_table();
+    table.borrow_mut().set_str(field, value);
+    Value::Table(table)
+}
+
+// 명령 응답을 Lua 값으로 바꿈, 스크립트는 RESP2로 보므로 nil은 false, 맵은 키와 값을 번갈아 담은 배열이 됨
+fn to_lua(interpreter: &mut Interpreter, reply: RespValue) -> Value {
+    match reply {
+        RespValue::SimpleString(status) => field_table(interpreter, "ok", Value::string(status)),
+        RespValue::Error(error) => error_table(interpreter, &format!("-{}", error)),
+        RespValue::Integer(value) => Value::Number(value as f64),
+        RespValue::BulkString(bytes) => Value::string(bytes),
+        RespValue::NullBulk | RespValue::NullArray => Value::Boolean(false),
+        RespValue::Array(items) | RespValue::Set(items) | RespValue::Push(items) => {
+            let values: Vec<Value> = items.into_iter().map(|item| to_lua(interpreter, item)).collect();
+            interpreter.table_from(values)
+        }
+        RespValue::Map(entries) => {
+            let mut values = Vec::with_capacity(entries.len() * 2);
+            for (key, value) in entries {
+                values.push(to_lua(interpreter, key));
+                values.push(to_lua(interpreter, value));
+            }
+            interpreter.table_from(values)
+        }
+        RespValue::Double(value) => Value::string(format_score(value)),
+        RespValue::Boolean(value) => Value::Number(value as i64 as f64),
+        RespValue::BigNumber(value) => Value::string(value),
+    }
+}
+
+// 스크립트의 반환값을 응답으로 바꿈, 숫자는 정수로 자르고 배열은 처음 나오는 nil 앞에서 끝남
+fn to_reply(value: &Value, depth: usize) -> RespValue {
+    match value {
+        Value::Nil | Value::Boolean(false) | Value::Function(_) => RespValue::NullBulk,
+        Value::Boolean(true) => RespValue::Integer(1),
+        Value::Number(number) => RespValue::Integer(*number as i64),
+        Value::String(bytes) => RespValue::BulkString(bytes.to_vec()),
+        Value::Table(table) => {
+            let table = table.borrow();
+            if let Value::String(error) = table.get_str("err") {
+                return RespValue::Error(single_line(&String::from_utf8_lossy(&error)));
+            }
+            if let Value::String(status) = table.get_str("ok") {
+                return RespValue::SimpleString(single_line(&String::from_utf8_lossy(&status)));
+            }
+            if depth >= MAX_REPLY_DEPTH {
+                return RespValue::error(SCRIPT_STACK_LIMIT_ERROR);
+            }
+            let items = (1..)
+                .map(|index| table.get(&Value::Number(index as f64)))
+                .take_while(|item| !item.is_nil())
+                .map(|item| to_reply(&item, depth + 1))
+                .collect();
+            RespValue::Array(items)
+        }
+    }
+}
+
+// 에러와 상태 응답은 한 줄이어야 함
+fn single_line(text: &str) -> String {
+    text.replace(['\r', '\n'], " ")
+}
+
+pub struct FunctionInfo {
+    pub name: String,
+    pub description: Option<String>,
+    pub flags: Vec<String>,
+}
+
+impl FunctionInfo {
+    // 쓰기 명령을 부르지 않는다고 표시한 함수, FCALL_RO로 부를 수 있고 FCALL로 불러도 쓰기는 거절됨
+    pub fn no_writes(&self) -> bool {
+        self.flags.iter().any(|flag| flag == FUNCTION_NO_WRITES_FLAG)
+    }
+}
+
+pub struct FunctionLibrary {
+    pub name: String,
+    pub engine: String,
+    pub code: String,
+    pub functions: Vec<FunctionInfo>,
+}
+
+impl FunctionLibrary {
+    // 첫 줄의 "#!lua name=<library>" 헤더를 읽고, 코드를 실행해서 redis.register_function으로 등록한 함수를 모음
+    pub fn parse(code: &str) -> Result<Self, String> {
+        let header = code.lines().next().unwrap_or("");
+        let header = header
+            .strip_prefix("#!")
+            .ok_or_else(|| FUNCTION_MISSING_SHEBANG_ERROR.to_string())?;
+        let mut parts = header.split_whitespace();
+        let engine = parts.next().unwrap_or("").to_string();
+        if !engine.eq_ignore_ascii_case(FUNCTION_ENGINE_LUA) {
+            return Err(format!("Engine '{}' not found", engine));
+        }
+        let mut name = None;
+        for part in parts {
+            match part.split_once('=') {
+                Some(("name", value)) => name = Some(value.to_string()),
+                _ => return Err(format!("Invalid metadata value given: {}", part)),
+            }
+        }
+        let name = name.ok_or_else(|| FUNCTION_LIBRARY_NAME_MISSING_ERROR.to_string())?;
+        if !is_valid_function_name(&name) {
+            return Err(LIBRARY_INVALID_NAME_ERROR.into());
+        }
+
+        let source = code.to_string();
+        let functions = lua::spawn(move || {
+            let mut interpreter = Interpreter::new(FUNCTION_CHUNK);
+            interpreter.set_deadline(Instant::now() + FUNCTION_LOAD_TIMEOUT, FUNCTION_LOAD_TIMEOUT_ERROR);
+            let redis = open_redis(&mut interpreter);
+            load_library(&mut interpreter, &redis, &source).map(|functions| functions.into_iter().map(|(function, _)| function).collect::<Vec<_>>())
+        })
+        .map_err(|e| e.to_string())?
+        .join()
+        .map_err(|_| SCRIPT_ABORTED_ERROR.to_string())??;
+
+        Ok(Self {
+            name,
+            engine: FUNCTION_ENGINE_LUA.to_uppercase(),
+            code: code.to_string(),
+            functions,
+        })
+    }
+
+    pub fn render(&self, with_code: bool) -> RespValue {
+        let functions = self
+            .functions
+            .iter()
+            .map(|function| {
+                RespValue::field_map(vec![
+                    ("name", RespValue::bulk(function.name.as_str())),
+                    ("description", function.description.as_deref().map_or(RespValue::NullBulk, RespValue::bulk)),
+                    ("flags", RespValue::bulk_array(&function.flags)),
+                ])
+            })
+            .collect();
+        let mut fields = vec![
+            ("library_name", RespValue::bulk(self.name.as_str())),
+            ("engine", RespValue::bulk(self.engine.as_str())),
+            ("functions", RespValue::Array(functions)),
+        ];
+        if with_code {
+            fields.push(("library_code", RespValue::bulk(self.code.as_str())));
+        }
+        RespValue::field_map(fields)
+    }
+}
+
+// 라이브러리 이름 -> 라이브러리, 함수 이름은 모든 라이브러리에서 유일해야 함
+pub struct FunctionRegistry {
+    libraries: HashMap<String, FunctionLibrary>,
+}
+
+impl FunctionRegistry {
+    pub fn new() -> Self {
+        Self {
+            libraries: HashMap::new(),
+        }
+    }
+
+    pub fn load(&mut self, code: &str, replace: bool) -> Result<String, String> {
+        let library = FunctionLibrary::parse(code)?;
+        if self.libraries.contains_key(&library.name) && !replace {
+            return Err(format!("Library '{}' already exists", library.name));
+        }
+        for function in &library.functions {
+            let taken = self
+                .libraries
+                .values()
+                .filter(|existing| existing.name != library.name)
+                .any(|existing| existing.functions.iter().any(|f| f.name == function.name));
+            if taken {
+                return Err(format!("Function {} already exists", function.name));
+            }
+        }
+        let name = library.name.clone();
+        self.libraries.insert(name.clone(), library);
+        Ok(name)
+    }
+
+    pub fn delete(&mut self, library_name: &str) -> bool {
+        self.libraries.remove(library_name).is_some()
+    }
+
+    pub fn flush(&mut self) {
+        self.libraries.clear();
+    }
+
+    pub fn list(&self, pattern: Option<&str>) -> Vec<&FunctionLibrary> {
+        let mut libraries: Vec<&FunctionLibrary> = self
+            .libraries
+            .values()
+            .filter(|library| pattern.map_or(true, |pattern| glob_match(pattern.as_bytes(), library.name.as_bytes())))
+            .collect();
+        libraries.sort_by(|a, b| a.name.cmp(&b.name));
+        libraries
+    }
+
+    // RDB에 저장할 라이브러리 코드, 이름순
+    pub fn codes(&self) -> Vec<String> {
+        self.list(None).iter().map(|library| library.code.clone()).collect()
+    }
+
+    // RDB에서 읽은 라이브러리로 통째로 바꿈, 읽지 못한 라이브러리는 로그만 남기고 건너뜀
+    pub fn restore(&mut self, codes: &[String]) {
+        self.flush();
+        for code in codes {
+            if let Err(e) = self.load(code, false) {
+                log_warning!("Skipped a function library stored in the RDB: {}", e);
+            }
+        }
+    }
+
+    // FCALL로 부를 함수와 그 함수가 든 라이브러리
+    pub fn find(&self, function_name: &str) -> Option<(&FunctionLibrary, &FunctionInfo)> {
+        self.libraries
+            .values()
+            .find_map(|library| library.functions.iter().find(|function| function.name == function_name).map(|function| (library, function)))
+    }
+
+    pub fn library_count(&self) -> usize {
+        self.libraries.len()
+    }
+
+    pub fn function_count(&self) -> usize {
+        self.libraries.values().map(|library| library.functions.len()).sum()
+    }
+}
+
+fn is_valid_function_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
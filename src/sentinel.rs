@@ -0,0 +1,896 @@
+use crate::errors::RedisError;
+use crate::logging::{log_notice, log_warning};
+use crate::protocol_constants::*;
+use crate::random::Random;
+use crate::resp::RespValue;
+use crate::util::format_host_port;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::Arc;
+
+// Redis sentinel 기본값
+pub const DEFAULT_SENTINEL_PORT: u16 = 26379;
+pub const DEFAULT_DOWN_AFTER_MS: u64 = 30000;
+pub const DEFAULT_FAILOVER_TIMEOUT_MS: u64 = 180000;
+const PING_PERIOD_MS: u64 = 1000;
+// 평소에는 10초마다 INFO, 마스터가 내려갔거나 페일오버 중이면 1초마다
+const INFO_PERIOD_MS: u64 = 10000;
+const FAST_INFO_PERIOD_MS: u64 = 1000;
+const HELLO_PERIOD_MS: u64 = 2000;
+const ASK_PERIOD_MS: u64 = 1000;
+const ELECTION_TIMEOUT_MS: u64 = 10000;
+// 여러 sentinel이 동시에 페일오버를 시작하지 않도록 시작 시각을 이만큼 안에서 흩뜨림
+const MAX_DESYNC_MS: u64 = 1000;
+// 레플리카가 이 시간 동안 마스터라고 답하면 현재 마스터의 레플리카로 되돌림
+const CONVERT_TO_REPLICA_DELAY_MS: u64 = HELLO_PERIOD_MS * 4;
+const RUN_ID_LEN: usize = 40;
+
+// "<이름> <호스트> <포트> <쿼럼>"
+pub fn parse_monitor(spec: &str) -> Result<(String, String, u16, usize), String> {
+    let [name, host, port, quorum] = spec.split_whitespace().collect::<Vec<_>>()[..] else {
+        return Err(format!("Invalid sentinel monitor '{}'", spec));
+    };
+    let port = port.parse::<u16>().map_err(|_| format!("Invalid port in sentinel monitor '{}'", spec))?;
+    let quorum = quorum.parse::<usize>().map_err(|_| format!("Invalid quorum in sentinel monitor '{}'", spec))?;
+    Ok((name.to_string(), host.to_string(), port, quorum))
+}
+
+// 필드 이름과 값의 맵, RESP2에서는 번갈아 담은 배열
+fn field_map(fields: Vec<(&str, String)>) -> RespValue {
+    RespValue::field_map(fields.into_iter().map(|(name, value)| (name, RespValue::bulk(value))).collect())
+}
+
+// 인스턴스에 보내는 명령 이름을 그대로 씀
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Clone)]
+pub enum RequestKind {
+    PING,
+    INFO,
+    HELLO(String),
+    // runid가 "*"이면 상태만 묻고, 아니면 그 sentinel을 리더로 뽑아 달라는 투표 요청도 함께 보냄
+    ISMASTERDOWN { host: String, port: u16, epoch: u64, runid: String },
+    REPLICAOF(Option<(String, u16)>),
+}
+
+// 감시 중인 마스터 이름, 요청을 보낼 인스턴스 주소, 요청 종류
+#[derive(Debug, Clone)]
+pub struct SentinelRequest {
+    pub master: String,
+    pub addr: String,
+    pub kind: RequestKind,
+}
+
+impl SentinelRequest {
+    pub fn args(&self) -> Vec<String> {
+        let args: Vec<&str> = match &self.kind {
+            RequestKind::PING => vec![PING_COMMAND],
+            RequestKind::INFO => vec![INFO_COMMAND],
+            RequestKind::HELLO(payload) => return vec![PUBLISH_COMMAND.into(), SENTINEL_HELLO_CHANNEL.into(), payload.clone()],
+            RequestKind::ISMASTERDOWN { host, port, epoch, runid } => {
+                return vec![
+                    SENTINEL_COMMAND.into(),
+                    SENTINEL_IS_MASTER_DOWN_BY_ADDR_OPTION.into(),
+                    host.clone(),
+                    port.to_string(),
+                    epoch.to_string(),
+                    runid.clone(),
+                ]
+            }
+            RequestKind::REPLICAOF(None) => vec![REPLICAOF_COMMAND, "NO", "ONE"],
+            RequestKind::REPLICAOF(Some((host, port))) => return vec![REPLICAOF_COMMAND.into(), host.clone(), port.to_string()],
+        };
+        args.into_iter().map(String::from).collect()
+    }
+}
+
+// PING/INFO로 살펴보는 마스터나 레플리카
+struct Instance {
+    host: String,
+    port: u16,
+    last_ping: u64,
+    // 응답을 기다리는 PING을 보낸 시각
+    ping_pending: Option<u64>,
+    // 정상 응답을 받지 못한 첫 PING을 보낸 시각, Redis의 act_ping_time처럼 이 시각부터 down-after를 셈
+    unanswered_since: Option<u64>,
+    last_ok: u64,
+    last_info: u64,
+    info_pending: bool,
+    last_hello: u64,
+    s_down: bool,
+    // INFO로 알게 된 역할과 그 역할을 처음 본 시각
+    role: Option<String>,
+    role_since: u64,
+    repl_offset: u64,
+}
+
+impl Instance {
+    fn new(host: &str, port: u16, now: u64) -> Self {
+        Self {
+            host: host.to_string(),
+            port,
+            last_ping: 0,
+            ping_pending: None,
+            unanswered_since: None,
+            last_ok: now,
+            last_info: 0,
+            info_pending: false,
+            last_hello: 0,
+            s_down: false,
+            role: None,
+            role_since: now,
+            repl_offset: 0,
+        }
+    }
+
+    fn addr(&self) -> String {
+        format_host_port(&self.host, self.port)
+    }
+
+    fn flags(&self, role: &str) -> String {
+        if self.s_down { format!("{},s_down", role) } else { role.to_string() }
+    }
+}
+
+// hello 메시지로 알게 된 같은 마스터를 감시하는 다른 sentinel
+struct PeerSentinel {
+    host: String,
+    port: u16,
+    last_hello: u64,
+    last_ask: u64,
+    // 마지막 IS-MASTER-DOWN-BY-ADDR 응답
+    master_down: bool,
+    leader: Option<String>,
+    leader_epoch: u64,
+}
+
+impl PeerSentinel {
+    fn addr(&self) -> String {
+        format_host_port(&self.host, self.port)
+    }
+}
+
+// INFO의 failover-state 값 이름을 그대로 씀
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FailoverState {
+    NONE,
+    // 리더 선출을 기다림, 강제 페일오버면 바로 레플리카를 고름
+    WAITSTART,
+    // 고른 레플리카에 REPLICAOF NO ONE을 보내고 INFO에서 master 역할이 보이기를 기다림
+    WAITPROMOTION,
+}
+
+struct MonitoredMaster {
+    name: String,
+    instance: Instance,
+    quorum: usize,
+    down_after: u64,
+    failover_timeout: u64,
+    config_epoch: u64,
+    o_down: bool,
+    replicas: BTreeMap<String, Instance>,
+    // 다른 sentinel의 runid를 키로 씀
+    sentinels: BTreeMap<String, PeerSentinel>,
+    // 이 sentinel이 leader_epoch에 투표한 리더
+    leader: Option<String>,
+    leader_epoch: u64,
+    failover: FailoverState,
+    forced_failover: bool,
+    failover_epoch: u64,
+    failover_started: u64,
+    // 객관적 다운을 처음 본 뒤 페일오버를 시작할 수 있는 시각, sentinel마다 달라야 표가 갈리지 않음
+    failover_not_before: u64,
+    failover_state_changed: u64,
+    promoted: Option<String>,
+}
+
+impl MonitoredMaster {
+    fn instance_mut(&mut self, addr: &str) -> Option<&mut Instance> {
+        if self.instance.addr() == addr {
+            Some(&mut self.instance)
+        } else {
+            self.replicas.get_mut(addr)
+        }
+    }
+
+    fn status(&self) -> &'static str {
+        if self.o_down {
+            "odown"
+        } else if self.instance.s_down {
+            "sdown"
+        } else {
+            "ok"
+        }
+    }
+
+    fn flags(&self) -> String {
+        let mut flags = self.instance.flags("master");
+        if self.o_down {
+            flags.push_str(",o_down");
+        }
+        if self.failover != FailoverState::NONE {
+            flags.push_str(",failover_in_progress");
+        }
+        flags
+    }
+
+    // 연결이 살아 있고 레플리카 역할을 하는 것 중 복제 오프셋이 가장 앞선 레플리카
+    fn select_replica(&self, now: u64) -> Option<String> {
+        self.replicas
+            .values()
+            .filter(|replica| {
+                !replica.s_down
+                    && now.saturating_sub(replica.last_ok) <= PING_PERIOD_MS * 5
+                    && replica.role.as_deref() == Some("slave")
+            })
+            .max_by(|a, b| a.repl_offset.cmp(&b.repl_offset).then_with(|| b.addr().cmp(&a.addr())))
+            .map(|replica| replica.addr())
+    }
+
+    // epoch에서 과반(최소 quorum)의 표를 얻은 sentinel
+    fn leader_for(&self, epoch: u64) -> Option<String> {
+        let mut votes: HashMap<&str, usize> = HashMap::new();
+        let peer_votes = self.sentinels.values().filter(|peer| peer.leader_epoch == epoch).filter_map(|peer| peer.leader.as_deref());
+        let my_vote = self.leader.as_deref().filter(|_| self.leader_epoch == epoch);
+        for leader in peer_votes.chain(my_vote) {
+            *votes.entry(leader).or_default() += 1;
+        }
+        let voters = self.sentinels.len() + 1;
+        let needed = self.quorum.max(voters / 2 + 1);
+        votes
+            .into_iter()
+            .filter(|(_, count)| *count >= needed)
+            .max_by_key(|(_, count)| *count)
+            .map(|(leader, _)| leader.to_string())
+    }
+}
+
+pub struct SentinelState {
+    myid: String,
+    host: String,
+    port: u16,
+    current_epoch: u64,
+    masters: BTreeMap<String, MonitoredMaster>,
+    down_after: u64,
+    failover_timeout: u64,
+    random: Arc<Random>,
+}
+
+impl SentinelState {
+    pub fn new(host: &str, port: u16, down_after: u64, failover_timeout: u64, random: Arc<Random>) -> Self {
+        Self {
+            myid: random.alphanumeric(RUN_ID_LEN).to_lowercase(),
+            host: host.to_string(),
+            port,
+            current_epoch: 0,
+            masters: BTreeMap::new(),
+            down_after,
+            failover_timeout,
+            random,
+        }
+    }
+
+    pub fn myid(&self) -> &str {
+        &self.myid
+    }
+
+    pub fn monitor(&mut self, name: &str, host: &str, port: u16, quorum: usize, now: u64) -> Result<(), RedisError> {
+        if self.masters.contains_key(name) {
+            return Err("Duplicated master name".into());
+        }
+        if quorum == 0 {
+            return Err("Quorum must be 1 or greater.".into());
+        }
+        log_notice!("+monitor master {} {} {} quorum {}", name, host, port, quorum);
+        self.masters.insert(
+            name.to_string(),
+            MonitoredMaster {
+                name: name.to_string(),
+                instance: Instance::new(host, port, now),
+                quorum,
+                down_after: self.down_after,
+                failover_timeout: self.failover_timeout,
+                config_epoch: 0,
+                o_down: false,
+                replicas: BTreeMap::new(),
+                sentinels: BTreeMap::new(),
+                leader: None,
+                leader_epoch: 0,
+                failover: FailoverState::NONE,
+                forced_failover: false,
+                failover_epoch: 0,
+                failover_started: 0,
+                failover_not_before: 0,
+                failover_state_changed: 0,
+                promoted: None,
+            },
+        );
+        Ok(())
+    }
+
+    pub fn remove(&mut self, name: &str) -> Result<(), RedisError> {
+        match self.masters.remove(name) {
+            Some(master) => {
+                log_notice!("-monitor master {} {} {}", name, master.instance.host, master.instance.port);
+                Ok(())
+            }
+            None => Err(NO_SUCH_MASTER_ERROR.into()),
+        }
+    }
+
+    fn master(&self, name: &str) -> Result<&MonitoredMaster, RedisError> {
+        self.masters.get(name).ok_or_else(|| NO_SUCH_MASTER_ERROR.into())
+    }
+
+    // hello 채널을 구독할 주소, 마스터가 내려가도 레플리카를 통해 새 설정을 들을 수 있도록 레플리카도 구독함
+    pub fn hello_subscriptions(&self) -> BTreeSet<String> {
+        self.masters
+            .values()
+            .flat_map(|master| std::iter::once(master.instance.addr()).chain(master.replicas.keys().cloned()))
+            .collect()
+    }
+
+    fn hello_payload(&self, master: &MonitoredMaster) -> String {
+        format!(
+            "{},{},{},{},{},{},{},{}",
+            self.host,
+            self.port,
+            self.myid,
+            self.current_epoch,
+            master.name,
+            master.instance.host,
+            master.instance.port,
+            master.config_epoch
+        )
+    }
+
+    // ActiveExpireCycle마다 호출, 보낼 요청을 돌려줌
+    pub fn cron(&mut self, now: u64) -> Vec<SentinelRequest> {
+        let mut requests = Vec::new();
+        let names: Vec<String> = self.masters.keys().cloned().collect();
+        for name in names {
+            self.probe_instances(&name, now, &mut requests);
+            self.check_down(&name, now, &mut requests);
+            self.run_failover(&name, now, &mut requests);
+        }
+        requests
+    }
+
+    fn probe_instances(&mut self, name: &str, now: u64, requests: &mut Vec<SentinelRequest>) {
+        let Some(master) = self.masters.get(name) else {
+            return;
+        };
+        let hello = self.hello_payload(master);
+        let Some(master) = self.masters.get_mut(name) else {
+            return;
+        };
+        let info_period = if master.instance.s_down || master.failover != FailoverState::NONE {
+            FAST_INFO_PERIOD_MS
+        } else {
+            INFO_PERIOD_MS
+        };
+        let down_after = master.down_after;
+        // down-after가 PING 주기보다 짧으면 응답하는 인스턴스도 주기 사이에 내려간 것으로 보이므로 Redis처럼 주기를 줄임
+        let ping_period = PING_PERIOD_MS.min(down_after);
+        let master_addr = master.instance.addr();
+        for instance in std::iter::once(&mut master.instance).chain(master.replicas.values_mut()) {
+            let addr = instance.addr();
+            let request = |kind| SentinelRequest {
+                master: name.to_string(),
+                addr: addr.clone(),
+                kind,
+            };
+            if instance.ping_pending.is_none() && now.saturating_sub(instance.last_ping) >= ping_period {
+                requests.push(request(RequestKind::PING));
+                instance.last_ping = now;
+                instance.ping_pending = Some(now);
+                instance.unanswered_since.get_or_insert(now);
+            }
+            if !instance.info_pending && now.saturating_sub(instance.last_info) >= info_period {
+                requests.push(request(RequestKind::INFO));
+                instance.last_info = now;
+                instance.info_pending = true;
+            }
+            if now.saturating_sub(instance.last_hello) >= HELLO_PERIOD_MS {
+                requests.push(request(RequestKind::HELLO(hello.clone())));
+                instance.last_hello = now;
+            }
+            let s_down = instance.unanswered_since.is_some_and(|sent| now.saturating_sub(sent) > down_after);
+            if s_down != instance.s_down {
+                let event = if s_down { "+sdown" } else { "-sdown" };
+                if instance.addr() == master_addr {
+                    log_notice!("{} master {} {} {}", event, name, instance.host, instance.port);
+                } else {
+                    log_notice!("{} slave {} {} {} @ {} {}", event, instance.addr(), instance.host, instance.port, name, master_addr);
+                }
+                instance.s_down = s_down;
+            }
+        }
+    }
+
+    // 주관적으로 내려간 마스터는 다른 sentinel에게 물어서 쿼럼이 동의하면 객관적으로 내려간 것으로 봄
+    fn check_down(&mut self, name: &str, now: u64, requests: &mut Vec<SentinelRequest>) {
+        let myid = self.myid.clone();
+        let current_epoch = self.current_epoch;
+        let Some(master) = self.masters.get_mut(name) else {
+            return;
+        };
+        if !master.instance.s_down {
+            for peer in master.sentinels.values_mut() {
+                peer.master_down = false;
+            }
+        }
+        let votes = 1 + master.sentinels.values().filter(|peer| peer.master_down).count();
+        let o_down = master.instance.s_down && votes >= master.quorum;
+        if o_down != master.o_down {
+            if o_down {
+                log_notice!("+odown master {} {} {} #quorum {}/{}", name, master.instance.host, master.instance.port, votes, master.quorum);
+                master.failover_not_before = now + self.random.below(MAX_DESYNC_MS as usize) as u64;
+            } else {
+                log_notice!("-odown master {} {} {}", name, master.instance.host, master.instance.port);
+            }
+            master.o_down = o_down;
+        }
+        if !master.instance.s_down {
+            return;
+        }
+        let runid = if master.failover == FailoverState::WAITSTART { myid } else { "*".to_string() };
+        for peer in master.sentinels.values_mut() {
+            if now.saturating_sub(peer.last_ask) < ASK_PERIOD_MS {
+                continue;
+            }
+            peer.last_ask = now;
+            requests.push(SentinelRequest {
+                master: name.to_string(),
+                addr: peer.addr(),
+                kind: RequestKind::ISMASTERDOWN {
+                    host: master.instance.host.clone(),
+                    port: master.instance.port,
+                    epoch: current_epoch,
+                    runid: runid.clone(),
+                },
+            });
+        }
+    }
+
+    fn run_failover(&mut self, name: &str, now: u64, requests: &mut Vec<SentinelRequest>) {
+        let Some(master) = self.masters.get(name) else {
+            return;
+        };
+        match master.failover {
+            FailoverState::NONE => {
+                if master.o_down
+                    && now >= master.failover_not_before
+                    && now.saturating_sub(master.failover_started) > master.failover_timeout * 2
+                {
+                    self.start_failover(name, false, now);
+                    let myid = self.myid.clone();
+                    let epoch = self.current_epoch;
+                    self.vote_leader(name, epoch, &myid, now);
+                }
+            }
+            FailoverState::WAITSTART => {
+                let elected = master.forced_failover || master.leader_for(master.failover_epoch).as_deref() == Some(self.myid.as_str());
+                let election_timeout = ELECTION_TIMEOUT_MS.min(master.failover_timeout);
+                let Some(master) = self.masters.get_mut(name) else {
+                    return;
+                };
+                if !elected {
+                    if now.saturating_sub(master.failover_started) > election_timeout {
+                        log_notice!("-failover-abort-not-elected master {} {} {}", name, master.instance.host, master.instance.port);
+                        master.failover = FailoverState::NONE;
+                    }
+                    return;
+                }
+                if !master.forced_failover {
+                    log_notice!("+elected-leader master {} {} {}", name, master.instance.host, master.instance.port);
+                }
+                let Some(promoted) = master.select_replica(now) else {
+                    log_notice!("-failover-abort-no-good-slave master {} {} {}", name, master.instance.host, master.instance.port);
+                    master.failover = FailoverState::NONE;
+                    return;
+                };
+                log_notice!("+selected-slave slave {} @ {} {}", promoted, name, master.instance.addr());
+                log_notice!("+failover-state-send-slaveof-noone slave {} @ {} {}", promoted, name, master.instance.addr());
+                requests.push(SentinelRequest {
+                    master: name.to_string(),
+                    addr: promoted.clone(),
+                    kind: RequestKind::REPLICAOF(None),
+                });
+                master.promoted = Some(promoted);
+                master.failover = FailoverState::WAITPROMOTION;
+                master.failover_state_changed = now;
+            }
+            FailoverState::WAITPROMOTION => {
+                if now.saturating_sub(master.failover_state_changed) > master.failover_timeout {
+                    let Some(master) = self.masters.get_mut(name) else {
+                        return;
+                    };
+                    log_notice!("-failover-abort-slave-timeout master {} {} {}", name, master.instance.host, master.instance.port);
+                    master.failover = FailoverState::NONE;
+                    master.promoted = None;
+                }
+            }
+        }
+    }
+
+    // SENTINEL FAILOVER은 다른 sentinel의 동의 없이 바로 시작함
+    pub fn force_failover(&mut self, name: &str, now: u64) -> Result<(), RedisError> {
+        let master = self.master(name)?;
+        if master.failover != FailoverState::NONE {
+            return Err(RedisError::InProgress);
+        }
+        if master.select_replica(now).is_none() {
+            return Err(RedisError::NoGoodSlave);
+        }
+        self.start_failover(name, true, now);
+        Ok(())
+    }
+
+    fn start_failover(&mut self, name: &str, forced: bool, now: u64) {
+        self.current_epoch += 1;
+        let epoch = self.current_epoch;
+        let Some(master) = self.masters.get_mut(name) else {
+            return;
+        };
+        log_notice!("+new-epoch {}", epoch);
+        log_notice!("+try-failover master {} {} {}", name, master.instance.host, master.instance.port);
+        master.failover = FailoverState::WAITSTART;
+        master.forced_failover = forced;
+        master.failover_epoch = epoch;
+        master.failover_started = now + self.random.below(MAX_DESYNC_MS as usize) as u64;
+        master.failover_state_changed = now;
+        // 다른 sentinel보다 먼저 표를 요청해야 하므로 다음 cron에서 바로 물어봄
+        for peer in master.sentinels.values_mut() {
+            peer.last_ask = 0;
+        }
+    }
+
+    // 한 epoch에 한 번만, 먼저 요청한 sentinel에게 투표함
+    fn vote_leader(&mut self, name: &str, epoch: u64, runid: &str, now: u64) -> (Option<String>, u64) {
+        if epoch > self.current_epoch {
+            self.current_epoch = epoch;
+            log_notice!("+new-epoch {}", epoch);
+        }
+        let current_epoch = self.current_epoch;
+        let myid = self.myid.clone();
+        let Some(master) = self.masters.get_mut(name) else {
+            return (None, 0);
+        };
+        if master.leader_epoch < epoch && current_epoch <= epoch {
+            master.leader = Some(runid.to_string());
+            master.leader_epoch = current_epoch;
+            log_notice!("+vote-for-leader {} {}", runid, current_epoch);
+            // 다른 sentinel에게 투표했으면 그 페일오버가 끝날 때까지 직접 시작하지 않음
+            if runid != myid {
+                master.failover_started = now + self.random.below(MAX_DESYNC_MS as usize) as u64;
+            }
+        }
+        (master.leader.clone(), master.leader_epoch)
+    }
+
+    // SENTINEL IS-MASTER-DOWN-BY-ADDR 응답: [내려감 여부, 투표한 리더 또는 "*", 리더 epoch]
+    pub fn is_master_down_by_addr(&mut self, host: &str, port: u16, epoch: u64, runid: &str, now: u64) -> RespValue {
+        let name = self
+            .masters
+            .values()
+            .find(|master| master.instance.host == host && master.instance.port == port)
+            .map(|master| master.name.clone());
+        let (down, (leader, leader_epoch)) = match name {
+            Some(name) if runid != "*" => (self.masters[&name].instance.s_down, self.vote_leader(&name, epoch, runid, now)),
+            Some(name) => (self.masters[&name].instance.s_down, (None, 0)),
+            None => (false, (None, 0)),
+        };
+        RespValue::Array(vec![
+            RespValue::Integer(down as i64),
+            RespValue::bulk(leader.as_deref().unwrap_or("*")),
+            RespValue::Integer(leader_epoch as i64),
+        ])
+    }
+
+    pub fn handle_reply(&mut self, request: SentinelRequest, reply: Result<RespValue, String>, now: u64) -> Vec<SentinelRequest> {
+        let Some(master) = self.masters.get_mut(&request.master) else {
+            return Vec::new();
+        };
+        match request.kind {
+            RequestKind::PING => {
+                if let Some(instance) = master.instance_mut(&request.addr) {
+                    instance.ping_pending = None;
+                    let ok = match &reply {
+                        Ok(RespValue::SimpleString(pong)) => pong == "PONG",
+                        Ok(RespValue::Error(error)) => error.starts_with("LOADING") || error.starts_with("MASTERDOWN"),
+                        _ => false,
+                    };
+                    if ok {
+                        instance.last_ok = now;
+                        instance.unanswered_since = None;
+                    }
+                }
+            }
+            RequestKind::INFO => {
+                if let Some(instance) = master.instance_mut(&request.addr) {
+                    instance.info_pending = false;
+                }
+                if let Ok(RespValue::BulkString(info)) = reply {
+                    return self.apply_info(&request.master, &request.addr, &String::from_utf8_lossy(&info), now);
+                }
+            }
+            RequestKind::ISMASTERDOWN { .. } => {
+                let Some(peer) = master.sentinels.values_mut().find(|peer| peer.addr() == request.addr) else {
+                    return Vec::new();
+                };
+                if let Ok(RespValue::Array(items)) = reply {
+                    if let [RespValue::Integer(down), RespValue::BulkString(leader), RespValue::Integer(leader_epoch)] = &items[..] {
+                        peer.master_down = *down == 1;
+                        if leader != b"*" {
+                            peer.leader = Some(String::from_utf8_lossy(leader).into_owned());
+                            peer.leader_epoch = *leader_epoch as u64;
+                        }
+                    }
+                }
+            }
+            RequestKind::REPLICAOF(_) => {
+                if let Ok(RespValue::Error(error)) | Err(error) = reply {
+                    log_warning!("Failed to reconfigure {} for master {}: {}", request.addr, request.master, error);
+                }
+            }
+            RequestKind::HELLO(_) => {}
+        }
+        Vec::new()
+    }
+
+    // INFO replication으로 레플리카를 찾고, 승격이 끝났는지, 옛 마스터가 돌아왔는지 확인함
+    fn apply_info(&mut self, name: &str, addr: &str, info: &str, now: u64) -> Vec<SentinelRequest> {
+        let fields: HashMap<&str, &str> = info.lines().filter_map(|line| line.trim().split_once(':')).collect();
+        let Some(master) = self.masters.get_mut(name) else {
+            return Vec::new();
+        };
+        let master_addr = master.instance.addr();
+        if addr == master_addr {
+            for (key, value) in &fields {
+                if !key.starts_with("slave") || !key[5..].chars().all(|c| c.is_ascii_digit()) || key.len() == 5 {
+                    continue;
+                }
+                let replica: HashMap<&str, &str> = value.split(',').filter_map(|pair| pair.split_once('=')).collect();
+                let (Some(host), Some(Ok(port))) = (replica.get("ip"), replica.get("port").map(|port| port.parse::<u16>())) else {
+                    continue;
+                };
+                let replica_addr = format_host_port(host, port);
+                if replica_addr != master_addr && !master.replicas.contains_key(&replica_addr) {
+                    log_notice!("+slave slave {} {} {} @ {} {}", replica_addr, host, port, name, master_addr);
+                    master.replicas.insert(replica_addr, Instance::new(host, port, now));
+                }
+            }
+        }
+        let Some(instance) = master.instance_mut(addr) else {
+            return Vec::new();
+        };
+        let role = fields.get("role").map(|role| role.to_string());
+        if role != instance.role {
+            instance.role = role.clone();
+            instance.role_since = now;
+        }
+        if let Some(offset) = fields.get("slave_repl_offset").and_then(|offset| offset.parse().ok()) {
+            instance.repl_offset = offset;
+        }
+        let role_since = instance.role_since;
+
+        if master.failover == FailoverState::WAITPROMOTION && master.promoted.as_deref() == Some(addr) && role.as_deref() == Some("master") {
+            return self.finish_failover(name, now);
+        }
+        // 페일오버 뒤 돌아온 옛 마스터처럼 레플리카여야 할 인스턴스가 계속 마스터라고 답하면 되돌림
+        if addr != master_addr
+            && role.as_deref() == Some("master")
+            && master.failover == FailoverState::NONE
+            && !master.instance.s_down
+            && now.saturating_sub(role_since) > CONVERT_TO_REPLICA_DELAY_MS
+        {
+            log_notice!("+convert-to-slave slave {} @ {} {}", addr, name, master_addr);
+            if let Some(instance) = master.instance_mut(addr) {
+                instance.role_since = now;
+            }
+            return vec![SentinelRequest {
+                master: name.to_string(),
+                addr: addr.to_string(),
+                kind: RequestKind::REPLICAOF(Some((master.instance.host.clone(), master.instance.port))),
+            }];
+        }
+        Vec::new()
+    }
+
+    // 승격된 레플리카를 새 마스터로 삼고 나머지 레플리카를 그쪽으로 돌림
+    fn finish_failover(&mut self, name: &str, now: u64) -> Vec<SentinelRequest> {
+        let Some(master) = self.masters.get_mut(name) else {
+            return Vec::new();
+        };
+        let Some(promoted) = master.promoted.take() else {
+            return Vec::new();
+        };
+        let Some(new_master) = master.replicas.get(&promoted) else {
+            return Vec::new();
+        };
+        let (host, port) = (new_master.host.clone(), new_master.port);
+        log_notice!("+promoted-slave slave {} @ {} {}", promoted, name, master.instance.addr());
+        let mut requests = Vec::new();
+        for replica in master.replicas.keys().filter(|addr| **addr != promoted) {
+            log_notice!("+slave-reconf-sent slave {} @ {} {}", replica, name, master.instance.addr());
+            requests.push(SentinelRequest {
+                master: name.to_string(),
+                addr: replica.clone(),
+                kind: RequestKind::REPLICAOF(Some((host.clone(), port))),
+            });
+        }
+        master.config_epoch = master.failover_epoch;
+        log_notice!("+failover-end master {} {} {}", name, master.instance.host, master.instance.port);
+        self.switch_master(name, &host, port, now);
+        requests
+    }
+
+    // 마스터 주소를 바꾸고, 옛 마스터는 새 마스터의 레플리카로 계속 감시함
+    fn switch_master(&mut self, name: &str, host: &str, port: u16, now: u64) {
+        let Some(master) = self.masters.get_mut(name) else {
+            return;
+        };
+        log_notice!("+switch-master {} {} {} {} {}", name, master.instance.host, master.instance.port, host, port);
+        let old = std::mem::replace(&mut master.instance, Instance::new(host, port, now));
+        let new_addr = master.instance.addr();
+        master.replicas.remove(&new_addr);
+        master.replicas.insert(old.addr(), Instance::new(&old.host, old.port, now));
+        master.o_down = false;
+        master.failover = FailoverState::NONE;
+        master.forced_failover = false;
+        master.promoted = None;
+        for peer in master.sentinels.values_mut() {
+            peer.master_down = false;
+        }
+    }
+
+    // "<ip>,<port>,<runid>,<current epoch>,<마스터 이름>,<마스터 ip>,<마스터 port>,<마스터 config epoch>"
+    pub fn handle_hello(&mut self, payload: &str, now: u64) {
+        let [host, port, runid, epoch, name, master_host, master_port, config_epoch] = payload.split(',').collect::<Vec<_>>()[..] else {
+            return;
+        };
+        let (Ok(port), Ok(epoch), Ok(master_port), Ok(config_epoch)) =
+            (port.parse::<u16>(), epoch.parse::<u64>(), master_port.parse::<u16>(), config_epoch.parse::<u64>())
+        else {
+            return;
+        };
+        if runid == self.myid {
+            return;
+        }
+        if epoch > self.current_epoch {
+            self.current_epoch = epoch;
+            log_notice!("+new-epoch {}", epoch);
+        }
+        let Some(master) = self.masters.get_mut(name) else {
+            return;
+        };
+        // 같은 주소로 다시 시작한 sentinel은 runid가 바뀌므로 예전 항목을 지움
+        master.sentinels.retain(|id, peer| id == runid || peer.host != host || peer.port != port);
+        let peer = master.sentinels.entry(runid.to_string()).or_insert_with(|| {
+            log_notice!("+sentinel sentinel {} {} {} @ {} {}", runid, host, port, name, master.instance.addr());
+            PeerSentinel {
+                host: host.to_string(),
+                port,
+                last_hello: now,
+                last_ask: 0,
+                master_down: false,
+                leader: None,
+                leader_epoch: 0,
+            }
+        });
+        peer.last_hello = now;
+        // 다른 sentinel이 더 새로운 epoch로 페일오버를 마쳤으면 그 설정을 따름
+        if config_epoch > master.config_epoch {
+            master.config_epoch = config_epoch;
+            if master.instance.host != master_host || master.instance.port != master_port {
+                log_notice!("+config-update-from sentinel {} {} {} @ {} {}", runid, host, port, name, master.instance.addr());
+                self.switch_master(name, master_host, master_port, now);
+            }
+        }
+    }
+
+    pub fn masters_reply(&self, now: u64) -> RespValue {
+        RespValue::Array(self.masters.values().map(|master| self.master_fields(master, now)).collect())
+    }
+
+    pub fn master_reply(&self, name: &str, now: u64) -> Result<RespValue, RedisError> {
+        Ok(self.master_fields(self.master(name)?, now))
+    }
+
+    fn master_fields(&self, master: &MonitoredMaster, now: u64) -> RespValue {
+        field_map(vec![
+            ("name", master.name.clone()),
+            ("ip", master.instance.host.clone()),
+            ("port", master.instance.port.to_string()),
+            ("flags", master.flags()),
+            ("last-ok-ping-reply", now.saturating_sub(master.instance.last_ok).to_string()),
+            ("down-after-milliseconds", master.down_after.to_string()),
+            ("num-slaves", master.replicas.len().to_string()),
+            ("num-other-sentinels", master.sentinels.len().to_string()),
+            ("quorum", master.quorum.to_string()),
+            ("failover-timeout", master.failover_timeout.to_string()),
+            ("config-epoch", master.config_epoch.to_string()),
+        ])
+    }
+
+    pub fn replicas_reply(&self, name: &str, now: u64) -> Result<RespValue, RedisError> {
+        let master = self.master(name)?;
+        let mut replies = Vec::new();
+        for replica in master.replicas.values() {
+            replies.push(field_map(vec![
+                ("name", replica.addr()),
+                ("ip", replica.host.clone()),
+                ("port", replica.port.to_string()),
+                ("flags", replica.flags("slave")),
+                ("last-ok-ping-reply", now.saturating_sub(replica.last_ok).to_string()),
+                ("role-reported", replica.role.clone().unwrap_or_default()),
+                ("slave-repl-offset", replica.repl_offset.to_string()),
+            ]));
+        }
+        Ok(RespValue::Array(replies))
+    }
+
+    pub fn sentinels_reply(&self, name: &str, now: u64) -> Result<RespValue, RedisError> {
+        let master = self.master(name)?;
+        let mut replies = Vec::new();
+        for (runid, peer) in &master.sentinels {
+            replies.push(field_map(vec![
+                ("name", runid.clone()),
+                ("ip", peer.host.clone()),
+                ("port", peer.port.to_string()),
+                ("runid", runid.clone()),
+                ("flags", "sentinel".to_string()),
+                ("last-hello-message", now.saturating_sub(peer.last_hello).to_string()),
+                ("leader-epoch", peer.leader_epoch.to_string()),
+            ]));
+        }
+        Ok(RespValue::Array(replies))
+    }
+
+    // 알 수 없는 마스터면 null 배열
+    pub fn master_addr_reply(&self, name: &str) -> RespValue {
+        match self.masters.get(name) {
+            Some(master) => RespValue::bulk_array(&[master.instance.host.clone(), master.instance.port.to_string()]),
+            None => RespValue::NullArray,
+        }
+    }
+
+    // 최근에 hello를 보낸 sentinel과 나를 합쳐 쿼럼과 과반을 채울 수 있는지
+    pub fn ckquorum(&self, name: &str, now: u64) -> Result<RespValue, RedisError> {
+        let master = self.master(name)?;
+        let usable = 1 + master
+            .sentinels
+            .values()
+            .filter(|peer| now.saturating_sub(peer.last_hello) <= HELLO_PERIOD_MS * 5)
+            .count();
+        let voters = master.sentinels.len() + 1;
+        if usable < master.quorum {
+            return Err(RedisError::NoQuorum(format!("{} usable Sentinels. Not enough available Sentinels to reach the specified quorum for this master", usable)));
+        }
+        if usable < voters / 2 + 1 {
+            return Err(RedisError::NoQuorum(format!("{} usable Sentinels. Not enough available Sentinels to reach the majority and authorize a failover", usable)));
+        }
+        Ok(RespValue::SimpleString(format!("OK {} usable Sentinels. Quorum and failover authorization can be reached", usable)))
+    }
+
+    pub fn info(&self) -> String {
+        let mut info = format!("# Sentinel{}", CRLF);
+        info.push_str(&format!("sentinel_masters:{}{}", self.masters.len(), CRLF));
+        info.push_str(&format!("sentinel_tilt:0{}", CRLF));
+        info.push_str(&format!("sentinel_running_scripts:0{}", CRLF));
+        info.push_str(&format!("sentinel_scripts_queue_length:0{}", CRLF));
+        for (index, master) in self.masters.values().enumerate() {
+            info.push_str(&format!(
+                "master{}:name={},status={},address={},slaves={},sentinels={}{}",
+                index,
+                master.name,
+                master.status(),
+                master.instance.addr(),
+                master.replicas.len(),
+                master.sentinels.len() + 1,
+                CRLF
+            ));
+        }
+        info
+    }
+}
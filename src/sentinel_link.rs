@@ -0,0 +1,151 @@
+use crate::event_publisher::EventPublisher;
+use crate::logging::{self, log_warning};
+use crate::protocol_constants::*;
+use crate::resp::RespValue;
+use crate::sentinel::SentinelRequest;
+use crate::util::construct_redis_command;
+use std::collections::{BTreeSet, HashMap};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio::time::Duration;
+
+// 연결과 응답 하나를 기다리는 최대 시간, 넘으면 연결을 버리고 다음 요청 때 다시 연결함
+const LINK_TIMEOUT: Duration = Duration::from_secs(1);
+const HELLO_RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+async fn read_reply(stream: &mut TcpStream, buffer: &mut Vec<u8>) -> Result<RespValue, String> {
+    let mut temp_buffer = [0u8; 4096];
+    loop {
+        if let Some((reply, len)) = RespValue::parse(buffer)? {
+            buffer.drain(..len);
+            return Ok(reply);
+        }
+        match stream.read(&mut temp_buffer).await {
+            Ok(n) if n > 0 => buffer.extend_from_slice(&temp_buffer[..n]),
+            Ok(_) => return Err("connection closed".to_string()),
+            Err(e) => return Err(e.to_string()),
+        }
+    }
+}
+
+// 인스턴스 주소마다 연결 하나와 요청 큐 하나, 응답은 이벤트로 이벤트 핸들러에게 돌려줌
+pub struct SentinelLinks {
+    publisher: EventPublisher,
+    links: HashMap<String, mpsc::UnboundedSender<SentinelRequest>>,
+    // hello 채널을 구독 중인 주소마다 태스크 하나
+    hello: HashMap<String, JoinHandle<()>>,
+}
+
+impl SentinelLinks {
+    pub fn new(publisher: EventPublisher) -> Self {
+        Self {
+            publisher,
+            links: HashMap::new(),
+            hello: HashMap::new(),
+        }
+    }
+
+    pub fn send(&mut self, request: SentinelRequest) {
+        let link = self
+            .links
+            .entry(request.addr.clone())
+            .or_insert_with(|| Self::spawn_link(request.addr.clone(), self.publisher.clone()));
+        if let Err(mpsc::error::SendError(request)) = link.send(request) {
+            let link = Self::spawn_link(request.addr.clone(), self.publisher.clone());
+            let addr = request.addr.clone();
+            let _ = link.send(request);
+            self.links.insert(addr, link);
+        }
+    }
+
+    // 감시 중인 인스턴스마다 hello 채널을 구독하도록 맞춤, 페일오버로 빠진 주소의 구독은 끊음
+    pub fn sync_hello(&mut self, subscriptions: BTreeSet<String>) {
+        self.hello.retain(|addr, task| {
+            let keep = subscriptions.contains(addr);
+            if !keep {
+                task.abort();
+            }
+            keep
+        });
+        for addr in subscriptions {
+            if !self.hello.contains_key(&addr) {
+                let task = logging::spawn(Self::subscribe_hello(addr.clone(), self.publisher.clone()));
+                self.hello.insert(addr, task);
+            }
+        }
+    }
+
+    fn spawn_link(addr: String, publisher: EventPublisher) -> mpsc::UnboundedSender<SentinelRequest> {
+        let (sender, mut receiver) = mpsc::unbounded_channel::<SentinelRequest>();
+        logging::spawn(async move {
+            let mut stream: Option<TcpStream> = None;
+            let mut buffer = Vec::new();
+            while let Some(request) = receiver.recv().await {
+                let reply = tokio::time::timeout(LINK_TIMEOUT, Self::call(&addr, &mut stream, &mut buffer, &request.args()))
+                    .await
+                    .unwrap_or_else(|_| Err("timed out".to_string()));
+                if reply.is_err() {
+                    stream = None;
+                    buffer.clear();
+                }
+                if publisher.publish_sentinel_reply(request, reply).await.is_err() {
+                    break;
+                }
+            }
+        });
+        sender
+    }
+
+    async fn call(addr: &str, stream: &mut Option<TcpStream>, buffer: &mut Vec<u8>, args: &[String]) -> Result<RespValue, String> {
+        if stream.is_none() {
+            *stream = Some(TcpStream::connect(addr).await.map_err(|e| e.to_string())?);
+        }
+        let Some(connection) = stream.as_mut() else {
+            return Err("not connected".to_string());
+        };
+        connection
+            .write_all(&construct_redis_command(args))
+            .await
+            .map_err(|e| e.to_string())?;
+        read_reply(connection, buffer).await
+    }
+
+    async fn subscribe_hello(addr: String, publisher: EventPublisher) {
+        // 내려간 인스턴스에는 매초 다시 연결하므로 실패는 연결이 끊길 때 한 번만 알림
+        let mut connected = true;
+        loop {
+            if let Err(e) = Self::read_hello(&addr, &publisher, &mut connected).await {
+                if connected {
+                    log_warning!("Sentinel hello subscription to {} failed: {}", addr, e);
+                }
+            }
+            connected = false;
+            tokio::time::sleep(HELLO_RECONNECT_DELAY).await;
+        }
+    }
+
+    async fn read_hello(addr: &str, publisher: &EventPublisher, connected: &mut bool) -> Result<(), String> {
+        let mut stream = tokio::time::timeout(LINK_TIMEOUT, TcpStream::connect(addr))
+            .await
+            .map_err(|_| "timed out".to_string())?
+            .map_err(|e| e.to_string())?;
+        *connected = true;
+        stream
+            .write_all(&construct_redis_command(&[SUBSCRIBE_COMMAND, SENTINEL_HELLO_CHANNEL]))
+            .await
+            .map_err(|e| e.to_string())?;
+        let mut buffer = Vec::new();
+        loop {
+            let RespValue::Array(items) = read_reply(&mut stream, &mut buffer).await? else {
+                continue;
+            };
+            if let [RespValue::BulkString(kind), RespValue::BulkString(channel), RespValue::BulkString(message)] = &items[..] {
+                if kind == b"message" && channel == SENTINEL_HELLO_CHANNEL.as_bytes() {
+                    publisher.publish_sentinel_hello(String::from_utf8_lossy(message).into_owned()).await?;
+                }
+            }
+        }
+    }
+}
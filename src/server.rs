@@ -0,0 +1,648 @@
+use crate::{admin, cluster_bus, config_handler, eviction, logging, preflight, sentinel, trace};
+use crate::client_output::{Backpressure, ClientOutput};
+use crate::cluster::{ClusterState, DEFAULT_CLUSTER_NODE_TIMEOUT_MS};
+use crate::cluster_bus::ClusterBus;
+use crate::command_parser::{CommandParser, FrameDecoder};
+use crate::command_registry::ClientNames;
+use crate::config_handler::{ConfigHandler, RuntimeSettings};
+use crate::errors::ArgumentError;
+use crate::event::RedisEvent;
+use crate::event_handler::EventHandler;
+use crate::event_publisher::EventPublisher;
+use crate::firewall::Firewall;
+use crate::logging::{log_notice, log_warning};
+use crate::protocol_constants::{DEFAULT_PROTO_MAX_BULK_LEN, DEFAULT_TCP_BACKLOG, MIN_PROTO_MAX_BULK_LEN, RESP2_PROTOCOL};
+use crate::rate_limit::RateLimiter;
+use crate::scripting::ScriptMonitor;
+use crate::sentinel::SentinelState;
+use crate::state_manager::StateManager;
+use crate::stats::Stats;
+use crate::util::{bind_listener, current_time_ms};
+use tokio::io::AsyncReadExt;
+use tokio::net::TcpListener;
+use tokio::signal::unix::{signal, SignalKind};
+use tokio::task::{JoinError, JoinHandle};
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::{mpsc, oneshot, watch, RwLock};
+use tokio::time::Duration;
+
+// 설정 파서는 명령줄처럼 args[0]을 프로그램 이름으로 보고 건너뜀
+const PROGRAM_NAME: &str = "redis-server";
+const DEFAULT_EVENT_QUEUE_CAPACITY: usize = 32;
+// Redis PROTO_IOBUF_LEN과 같은 크기, 한 번에 읽는 양일 뿐 요청 크기는 FrameDecoder 버퍼가 맞춰 늘어남
+const READ_CHUNK_SIZE: usize = 16 * 1024;
+const REPLICA_ACK_PROBE_INTERVAL: Duration = Duration::from_secs(1);
+// Redis 기본 hz 10과 같은 주기
+const ACTIVE_EXPIRE_INTERVAL: Duration = Duration::from_millis(100);
+
+// 다른 프로그램 안에 서버를 띄우기 위한 진입점, main도 이것으로 서버를 띄움
+// 설정은 명령줄과 같은 형식으로 모아 두었다가 spawn할 때 읽음
+pub struct Server;
+
+impl Server {
+    pub fn builder() -> ServerBuilder {
+        ServerBuilder {
+            args: vec![PROGRAM_NAME.to_string()],
+            handle_signals: false,
+        }
+    }
+}
+
+pub struct ServerBuilder {
+    // 첫 원소는 프로그램 이름, 설정 파일 경로는 그 바로 뒤에 옴
+    args: Vec<String>,
+    handle_signals: bool,
+}
+
+impl ServerBuilder {
+    // 명령줄 인자를 그대로 더함, 첫 인자가 옵션이 아니면 설정 파일 경로로 읽음
+    pub fn args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.args.extend(args.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn config_file(mut self, path: impl Into<String>) -> Self {
+        self.args.insert(1, path.into());
+        self
+    }
+
+    // "maxmemory"처럼 명령줄 옵션 이름을 받음, 같은 옵션을 여러 번 주면 뒤의 값이 이김
+    pub fn option(mut self, name: &str, value: impl Into<String>) -> Self {
+        self.args.push(format!("--{}", name));
+        self.args.push(value.into());
+        self
+    }
+
+    // 0이면 OS가 고른 포트로 받음, 실제 포트는 ServerHandle::local_addr로 알 수 있음
+    pub fn port(self, port: u16) -> Self {
+        self.option("port", port.to_string())
+    }
+
+    pub fn dir(self, dir: impl Into<String>) -> Self {
+        self.option("dir", dir)
+    }
+
+    pub fn dbfilename(self, file_name: impl Into<String>) -> Self {
+        self.option("dbfilename", file_name)
+    }
+
+    // 지금까지 준 명령줄 옵션 중 마지막 값, 설정 파일의 값은 보지 않음
+    pub(crate) fn option_value(&self, name: &str) -> Option<&str> {
+        let flag = format!("--{}", name);
+        self.args.windows(2).rev().find(|pair| pair[0] == flag).map(|pair| pair[1].as_str())
+    }
+
+    // SIGTERM/SIGINT로 종료하고 SIGUSR1로 상태를 덤프함, 시그널은 프로세스 전체의 것이므로 기본은 끔
+    pub fn handle_signals(mut self, handle_signals: bool) -> Self {
+        self.handle_signals = handle_signals;
+        self
+    }
+
+    // 서버를 띄우지 않고 설정만 검사함, 문제가 없으면 true
+    pub async fn preflight(self) -> bool {
+        let state = StateManager::new();
+        logging::scope(state.get_runtime().logger.clone(), self.check(state)).await
+    }
+
+    async fn check(self, state: StateManager) -> bool {
+        let config_result = ConfigHandler::load_config(&state.get_config(), &state.get_runtime(), &self.args).await;
+        let config_lock = state.get_config();
+        let config = config_lock.read().await;
+        preflight::run(&config, &config_result)
+    }
+
+    // 설정을 읽고 RDB를 불러온 뒤 리스너를 열고 돌아옴, 요청은 돌려받은 핸들과 상관없이 런타임에서 계속 처리됨
+    pub async fn spawn(self) -> Result<ServerHandle, String> {
+        let state = StateManager::new();
+        logging::scope(state.get_runtime().logger.clone(), self.start(state)).await
+    }
+
+    // 서버의 로거 안에서 실행되고, 서버의 태스크는 모두 logging::spawn으로 띄움
+    async fn start(self, state: StateManager) -> Result<ServerHandle, String> {
+
+        // Redis처럼 설정에 잘못된 줄이 하나라도 있으면 뜨지 않음, 일부만 적용하면 requirepass 같은 설정이 빠진 채 뜰 수 있음
+        ConfigHandler::load_config(&state.get_config(), &state.get_runtime(), &self.args)
+            .await
+            .map_err(|e| format!("FATAL CONFIG FILE ERROR: {}", e))?;
+        state.init_random().await;
+
+        let (queue_capacity, shed_when_overloaded) = {
+            let config_lock = state.get_config();
+            let config = config_lock.read().await;
+            let capacity = config.get("event_queue_capacity")
+                .and_then(|capacity| capacity.parse::<usize>().ok())
+                .filter(|capacity| *capacity > 0)
+                .unwrap_or(DEFAULT_EVENT_QUEUE_CAPACITY);
+            (capacity, config.get("overload_policy").is_some_and(|policy| policy == "shed"))
+        };
+        let (tx, mut rx) = mpsc::channel::<RedisEvent>(queue_capacity);
+        let (priority_tx, mut priority_rx) = mpsc::unbounded_channel::<RedisEvent>();
+        let publisher = EventPublisher::new(tx, priority_tx, queue_capacity, shed_when_overloaded);
+
+        let mut config_handler = ConfigHandler::new(
+            state.get_db(),
+            state.get_config(),
+            state.get_replication_config(),
+            state.get_functions(),
+            publisher.clone(),
+            state.get_runtime(),
+        );
+        config_handler.configure_db().await;
+
+        let sentinel_mode = state.get_config().read().await.get("sentinel").is_some_and(|sentinel| sentinel == "yes");
+        let port = {
+            let config_lock = state.get_config();
+            let config = config_lock.read().await;
+            config.get("port")
+                .and_then(|p| p.parse::<u16>().ok())
+                .unwrap_or(if sentinel_mode { sentinel::DEFAULT_SENTINEL_PORT } else { 6379 })
+        };
+
+        let bind_addresses = {
+            let config_lock = state.get_config();
+            let config = config_lock.read().await;
+            let spec = config.get("bind").map_or(config_handler::DEFAULT_BIND, String::as_str);
+            config_handler::parse_bind_addresses(spec, port).map_err(|e| format!("Invalid bind configuration: {}", e))?
+        };
+        // tcp-keepalive는 초 단위지만 probe 간격은 OS 설정을 따르고, 0이면 끔
+        let (backlog, keepalive, nodelay) = {
+            let config_lock = state.get_config();
+            let config = config_lock.read().await;
+            let backlog = config.get("tcp_backlog").and_then(|backlog| backlog.parse::<u32>().ok()).unwrap_or(DEFAULT_TCP_BACKLOG);
+            let keepalive = config.get("tcp_keepalive").and_then(|seconds| seconds.parse::<u64>().ok()).unwrap_or(300) > 0;
+            let nodelay = config.get("tcp_nodelay").map_or(true, |nodelay| nodelay != "no");
+            (backlog, keepalive, nodelay)
+        };
+        // port 0이면 첫 리스너가 받은 포트를 나머지 주소에도 쓰고, 설정에도 적어 INFO와 복제가 실제 포트를 보게 함
+        let mut listeners = Vec::new();
+        let mut local_addrs = Vec::new();
+        let mut bound_port = port;
+        for (mut bind_addr, optional) in bind_addresses {
+            bind_addr.set_port(bound_port);
+            match bind_listener(bind_addr, backlog, keepalive).and_then(|listener| Ok((listener.local_addr()?, listener))) {
+                Ok((local_addr, listener)) => {
+                    log_notice!("Listening on {}", local_addr);
+                    bound_port = local_addr.port();
+                    local_addrs.push(local_addr);
+                    listeners.push(listener);
+                }
+                Err(e) if optional => log_warning!("Failed to bind {}: {}", bind_addr, e),
+                Err(e) => return Err(format!("Failed to bind {}: {}", bind_addr, e)),
+            }
+        }
+        if listeners.is_empty() {
+            return Err(format!("Could not bind any listening address on port {}", port));
+        }
+        let port = bound_port;
+        state.get_config().write().await.insert("port".into(), port.to_string());
+        {
+            let server_info_lock = state.get_server_info();
+            let mut server_info = server_info_lock.write().await;
+            server_info.set_tcp_port(port);
+            server_info.set_config_file(ConfigHandler::config_file_path(&self.args));
+        }
+
+        let firewall = {
+            let config_lock = state.get_config();
+            let config = config_lock.read().await;
+            match config.get("firewall").map(|spec| Firewall::parse(spec)) {
+                Some(Ok(firewall)) => firewall,
+                Some(Err(e)) => return Err(format!("Invalid firewall configuration: {}", e)),
+                None => Firewall::default(),
+            }
+        };
+
+        // 클러스터 노드 주소는 cluster-announce-ip, 없으면 다른 노드가 닿을 수 있는 첫 바인드 주소로 알림
+        let cluster = {
+            let config_lock = state.get_config();
+            let config = config_lock.read().await;
+            let node_timeout = config
+                .get("cluster_node_timeout")
+                .and_then(|timeout| timeout.parse::<u64>().ok())
+                .unwrap_or(DEFAULT_CLUSTER_NODE_TIMEOUT_MS);
+            config
+                .get("cluster_enabled")
+                .is_some_and(|enabled| enabled == "yes")
+                .then(|| {
+                    let announce_ip = announce_ip(config.get("cluster_announce_ip").map(String::as_str), &local_addrs);
+                    ClusterState::new(&announce_ip, port, node_timeout, state.get_random())
+                })
+        };
+
+        let sentinel = if sentinel_mode {
+            let config_lock = state.get_config();
+            let config = config_lock.read().await;
+            let millis = |key: &str, default: u64| config.get(key).and_then(|value| value.parse::<u64>().ok()).unwrap_or(default);
+            // hello 메시지로 다른 sentinel에게 알리는 주소, 클러스터 노드 주소와 같은 규칙을 따름
+            let announce_ip = announce_ip(config.get("sentinel_announce_ip").map(String::as_str), &local_addrs);
+            let mut sentinel = SentinelState::new(
+                &announce_ip,
+                port,
+                millis("sentinel_down_after_milliseconds", sentinel::DEFAULT_DOWN_AFTER_MS),
+                millis("sentinel_failover_timeout", sentinel::DEFAULT_FAILOVER_TIMEOUT_MS),
+                state.get_random(),
+            );
+            for monitor in config.get("sentinel_monitor").into_iter().flat_map(|monitors| monitors.split(';')) {
+                sentinel::parse_monitor(monitor)
+                    .and_then(|(name, host, master_port, quorum)| sentinel.monitor(&name, &host, master_port, quorum, current_time_ms()).map_err(|e| e.to_string()))
+                    .map_err(|e| format!("Invalid sentinel configuration: {}", e))?;
+            }
+            logging::set_role('X');
+            log_notice!("Sentinel ID is {}", sentinel.myid());
+            Some(sentinel)
+        } else {
+            None
+        };
+
+        // 클러스터 버스는 클라이언트 포트와 따로, 클라이언트 리스너가 붙은 주소마다 받음
+        let cluster_bus = ClusterBus::default();
+        if let Some(cluster) = &cluster {
+            for local_addr in &local_addrs {
+                let mut bus_addr = *local_addr;
+                bus_addr.set_port(cluster.myself().bus_port);
+                let bus_listener = TcpListener::bind(bus_addr)
+                    .await
+                    .map_err(|e| format!("Failed to bind cluster bus {}: {}", bus_addr, e))?;
+                log_notice!("Cluster bus listening on {}", bus_addr);
+                logging::spawn(cluster_bus::serve_bus(bus_listener, publisher.clone(), cluster_bus.inbound_links()));
+            }
+        }
+
+        let mut event_handler = EventHandler::new(&state, publisher.clone(), firewall, cluster, cluster_bus, sentinel);
+
+        // true면 종료 중: 새 연결과 요청을 받지 않음, 종료가 취소되면 false로 돌아가고 이벤트 루프가 끝나면 닫힘
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+        let handler_publisher = publisher.clone();
+        let event_handler_task = logging::spawn(async move {
+            loop {
+                // 복제/관리 이벤트가 일반 명령 뒤에 밀리지 않도록 우선 처리
+                let event = tokio::select! {
+                    biased;
+                    Some(event) = priority_rx.recv() => {
+                        handler_publisher.priority_event_received();
+                        event
+                    }
+                    Some(event) = rx.recv() => event,
+                    else => break,
+                };
+                event_handler.handle_event(event).await;
+                event_handler.uncork(priority_rx.is_empty() && rx.is_empty()).await;
+                if !event_handler.shutdown_requested() {
+                    continue;
+                }
+                // 종료: 새 연결과 요청을 막고, 이미 큐에 들어온 명령은 끝까지 처리한 뒤 최종 저장과 레플리카 flush를 함
+                shutdown_tx.send_replace(true);
+                loop {
+                    let event = match priority_rx.try_recv() {
+                        Ok(event) => {
+                            handler_publisher.priority_event_received();
+                            event
+                        }
+                        Err(_) => match rx.try_recv() {
+                            Ok(event) => event,
+                            Err(_) => break,
+                        },
+                    };
+                    event_handler.handle_event(event).await;
+                }
+                if event_handler.finish_shutdown().await {
+                    break;
+                }
+                shutdown_tx.send_replace(false);
+            }
+        });
+
+        let probe_publisher = publisher.clone();
+        logging::spawn(async move {
+            let mut interval = tokio::time::interval(REPLICA_ACK_PROBE_INTERVAL);
+            loop {
+                interval.tick().await;
+                if probe_publisher.publish_replica_ack_probe().await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        let expire_publisher = publisher.clone();
+        logging::spawn(async move {
+            let mut interval = tokio::time::interval(ACTIVE_EXPIRE_INTERVAL);
+            loop {
+                interval.tick().await;
+                if expire_publisher.publish_active_expire_cycle().is_err() {
+                    break;
+                }
+            }
+        });
+
+        if self.handle_signals {
+            logging::spawn(handle_signals(publisher.clone()));
+        }
+
+        let max_bulk_len = {
+            let config_lock = state.get_config();
+            let config = config_lock.read().await;
+            config.get("proto_max_bulk_len")
+                .and_then(|len| eviction::parse_memory(len).ok())
+                .filter(|len| *len >= MIN_PROTO_MAX_BULK_LEN)
+                .map_or(DEFAULT_PROTO_MAX_BULK_LEN, |len| len as usize)
+        };
+        let connection_options = Arc::new(ConnectionOptions {
+            max_bulk_len,
+            nodelay,
+            client_names: ConfigHandler::client_names(&*state.get_config().read().await),
+            script_monitor: state.get_script_monitor(),
+            runtime: state.get_runtime(),
+        });
+        // 모든 리스너가 같은 카운터에서 id를 받으므로 주소가 달라도 겹치지 않고, 끊긴 연결의 id는 다시 쓰지 않음
+        let next_client_id = Arc::new(AtomicU64::new(1));
+        let accept_tasks: Vec<_> = listeners
+            .into_iter()
+            .map(|listener| {
+                logging::spawn(accept_connections(
+                    listener,
+                    publisher.clone(),
+                    state.get_stats(),
+                    connection_options.clone(),
+                    next_client_id.clone(),
+                    shutdown_rx.clone(),
+                ))
+            })
+            .collect();
+
+        let admin_port = {
+            let config_lock = state.get_config();
+            let config = config_lock.read().await;
+            config.get("admin_port").and_then(|p| p.parse::<u16>().ok())
+        };
+        if let Some(admin_port) = admin_port {
+            let bind_addr = format!("127.0.0.1:{}", admin_port);
+            match TcpListener::bind(&bind_addr).await {
+                Ok(listener) => {
+                    log_notice!("Admin API listening on {}", bind_addr);
+                    logging::spawn(admin::serve_admin(listener, publisher.clone()));
+                }
+                Err(e) => log_warning!("Failed to bind admin API {}: {}", bind_addr, e),
+            }
+        }
+
+        config_handler.configure_replication().await;
+
+        // 이벤트 루프가 끝나면 종료 채널이 닫히면서 리스너와 연결 읽기 태스크도 끝남, 나머지 태스크는 런타임과 함께 정리됨
+        let task = logging::spawn(async move {
+            event_handler_task.await?;
+            for accept_task in accept_tasks {
+                accept_task.await?;
+            }
+            Ok(())
+        });
+        Ok(ServerHandle {
+            local_addrs,
+            publisher,
+            shutdown: shutdown_rx,
+            task: Some(task),
+        })
+    }
+}
+
+pub struct ServerHandle {
+    local_addrs: Vec<SocketAddr>,
+    publisher: EventPublisher,
+    shutdown: watch::Receiver<bool>,
+    task: Option<JoinHandle<Result<(), JoinError>>>,
+}
+
+impl ServerHandle {
+    // 첫 번째로 연 리스너의 주소
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addrs[0]
+    }
+
+    pub fn local_addrs(&self) -> &[SocketAddr] {
+        &self.local_addrs
+    }
+
+    // SHUTDOWN이나 SIGTERM과 같은 종료 경로를 타고, 서버가 끝날 때까지 기다림
+    // 최종 저장에 실패하면 Redis처럼 종료를 취소하고 계속 서비스하므로 에러를 돌려주고 핸들은 그대로 쓸 수 있음
+    pub async fn shutdown(&mut self) -> Result<(), String> {
+        self.shutdown.borrow_and_update();
+        if self.publisher.publish_shutdown_requested().await.is_ok() {
+            // 종료가 시작되면 true, 취소되면 false로 바뀌고, 끝나면 채널이 닫힘
+            while self.shutdown.changed().await.is_ok() {
+                if !*self.shutdown.borrow_and_update() {
+                    return Err("Shutdown was cancelled because the final save failed".into());
+                }
+            }
+        }
+        self.wait().await
+    }
+
+    // 서버가 SHUTDOWN 명령이나 시그널로 끝날 때까지 기다림
+    pub async fn wait(&mut self) -> Result<(), String> {
+        let Some(task) = self.task.take() else {
+            return Ok(());
+        };
+        match task.await {
+            Ok(Ok(())) => Ok(()),
+            Ok(Err(e)) | Err(e) => Err(format!("Server task failed: {}", e)),
+        }
+    }
+}
+
+// SIGTERM/SIGINT는 SHUTDOWN과 같은 종료 경로를 타고, SIGUSR1은 상태를 로그로 덤프함
+async fn handle_signals(publisher: EventPublisher) {
+    let (Ok(mut terminate), Ok(mut interrupt), Ok(mut user1)) =
+        (signal(SignalKind::terminate()), signal(SignalKind::interrupt()), signal(SignalKind::user_defined1()))
+    else {
+        log_warning!("Failed to install signal handlers");
+        return;
+    };
+    loop {
+        let published = tokio::select! {
+            _ = terminate.recv() => {
+                log_notice!("Received SIGTERM scheduling shutdown...");
+                publisher.publish_shutdown_requested().await
+            }
+            _ = interrupt.recv() => {
+                log_notice!("Received SIGINT scheduling shutdown...");
+                publisher.publish_shutdown_requested().await
+            }
+            _ = user1.recv() => publisher.publish_debug_dump_requested().await,
+        };
+        if published.is_err() {
+            break;
+        }
+    }
+}
+
+// 종료가 시작되면(또는 이벤트 루프가 끝났으면) 돌아옴
+async fn shutdown_started(shutdown: &mut watch::Receiver<bool>) {
+    let _ = shutdown.wait_for(|stopping| *stopping).await;
+}
+
+// 종료가 취소되어 다시 받아도 되면 true, 서버가 끝나는 중이면 false
+async fn shutdown_cancelled(shutdown: &mut watch::Receiver<bool>) -> bool {
+    shutdown.wait_for(|stopping| !*stopping).await.is_ok()
+}
+
+// 다른 노드에게 알릴 주소: 설정된 announce 주소, 없으면 루프백이나 0.0.0.0이 아닌 첫 바인드 주소, 그것도 없으면 IPv4 루프백
+fn announce_ip(configured: Option<&str>, local_addrs: &[SocketAddr]) -> String {
+    if let Some(ip) = configured.filter(|ip| !ip.is_empty()) {
+        return ip.to_string();
+    }
+    local_addrs
+        .iter()
+        .map(SocketAddr::ip)
+        .find(|ip| !ip.is_loopback() && !ip.is_unspecified())
+        .map_or_else(|| "127.0.0.1".to_string(), |ip| ip.to_string())
+}
+
+// 모든 리스너의 연결이 함께 쓰는 설정, 시작할 때 이 서버의 설정에서 한 번 정함
+struct ConnectionOptions {
+    max_bulk_len: usize,
+    nodelay: bool,
+    client_names: ClientNames,
+    script_monitor: Arc<ScriptMonitor>,
+    runtime: Arc<RuntimeSettings>,
+}
+
+async fn accept_connections(
+    listener: TcpListener,
+    publisher: EventPublisher,
+    stats: Arc<RwLock<Stats>>,
+    options: Arc<ConnectionOptions>,
+    next_client_id: Arc<AtomicU64>,
+    mut shutdown: watch::Receiver<bool>,
+) {
+    loop {
+        // 종료 중에는 accept하지 않고, 종료가 확정되면 리스너를 닫음
+        let accepted = tokio::select! {
+            accepted = listener.accept() => accepted,
+            _ = shutdown_started(&mut shutdown) => {
+                if shutdown_cancelled(&mut shutdown).await {
+                    continue;
+                }
+                break;
+            }
+        };
+        let Ok((stream, addr)) = accepted else {
+            break;
+        };
+        if let Err(e) = stream.set_nodelay(options.nodelay) {
+            log_warning!("Failed to set TCP_NODELAY for {}: {}", addr, e);
+        }
+        let client_id = next_client_id.fetch_add(1, Ordering::Relaxed);
+        let Ok(local_addr) = stream.local_addr() else {
+            continue;
+        };
+        let (mut read_stream, write_stream) = stream.into_split();
+        // 응답은 연결마다의 전송 태스크가 쓰므로 느린 클라이언트가 이벤트 루프를 막지 않음
+        let backpressure = Arc::new(Backpressure::default());
+        let output = ClientOutput::spawn(client_id, write_stream, backpressure.clone());
+        let direct_replies = output.direct_replies();
+        // CLIENT KILL이 읽기 태스크를 끝낼 수 있도록 신호를 클라이언트에 넘김
+        let (kill_switch, mut killed) = oneshot::channel();
+
+        let publisher = publisher.clone();
+        let stats = stats.clone();
+        let options = options.clone();
+        let mut shutdown = shutdown.clone();
+        if let Err(e) = publisher.publish_client_connected(client_id, output, addr, local_addr, kill_switch).await {
+            log_warning!("Failed to send client connected event: {}", e);
+            continue;
+        }
+
+        logging::spawn(logging::CONNECTION_ID.scope(client_id, async move {
+            let mut decoder = FrameDecoder::with_max_bulk_len(options.max_bulk_len);
+            let mut buffer = vec![0u8; READ_CHUNK_SIZE];
+            let mut rate_limiter = RateLimiter::new(options.runtime.rate_limits.clone());
+            'read: loop {
+                // 보내지 못한 응답이 많이 쌓였거나 입력 속도 제한을 넘었으면 그만큼 요청을 더 읽지 않음
+                let read = tokio::select! {
+                    read = async {
+                        backpressure.wait_for_output().await;
+                        rate_limiter.wait_for_input().await;
+                        read_stream.read(&mut buffer).await
+                    } => read,
+                    _ = &mut killed => break,
+                    // 종료 중에는 새 요청을 읽지 않음, 아직 읽지 않은 요청은 소켓에 남아 종료가 취소되면 이어서 처리됨
+                    _ = shutdown_started(&mut shutdown) => {
+                        if shutdown_cancelled(&mut shutdown).await {
+                            continue;
+                        }
+                        break;
+                    }
+                };
+                match read {
+                    Ok(n) if n > 0 => {
+                        rate_limiter.input_read(n);
+                        decoder.feed(&buffer[..n]);
+                    }
+                    _ => break,
+                }
+                loop {
+                    // 요청 경계를 잃었거나 따옴표가 맞지 않는 프로토콜 에러는 Redis처럼 에러를 보내고 연결을 닫음
+                    let (frame, args) = match decoder.next_frame().and_then(|frame| match frame {
+                        Some(frame) => CommandParser::frame_args(&frame).map(|args| Some((frame, args))),
+                        None => Ok(None),
+                    }) {
+                        Ok(Some(request)) => request,
+                        Ok(None) => break,
+                        Err(ArgumentError::General(message)) => {
+                            log_warning!("Closing client {} after protocol error: {}", addr, message);
+                            if let Err(e) = publisher.publish_command_error(client_id, message).await {
+                                log_warning!("Failed to publish command error: {}", e);
+                            }
+                            break 'read;
+                        }
+                    };
+                    // Redis처럼 빈 줄이나 "*0" 같은 빈 요청은 응답 없이 넘어감
+                    if args.is_empty() {
+                        continue;
+                    }
+                    // 알 수 없는 명령이나 잘못된 인자는 에러만 보내고 연결은 유지함
+                    let parsed_command = match CommandParser::parse_client_args(&args, &options.client_names) {
+                        Ok(parsed_command) => parsed_command,
+                        Err(ArgumentError::General(message)) => {
+                            if let Err(e) = publisher.publish_command_error(client_id, message).await {
+                                log_warning!("Failed to publish command error: {}", e);
+                                break 'read;
+                            }
+                            continue;
+                        }
+                    };
+                    stats.write().await.record_request(parsed_command.name(), frame.len());
+                    // 명령 속도 제한은 기다리는 동안에도 CLIENT KILL로 끊을 수 있어야 함
+                    tokio::select! {
+                        _ = rate_limiter.acquire_command() => {}
+                        _ = &mut killed => break 'read,
+                    }
+                    // 이벤트 루프가 오래 도는 스크립트를 기다리는 중이면 BUSY와 SCRIPT KILL은 여기서 바로 답함
+                    if let Some(reply) = options.script_monitor.busy_reply(client_id, backpressure.inflight(), &parsed_command) {
+                        direct_replies.send(reply.encode(RESP2_PROTOCOL));
+                        continue;
+                    }
+                    backpressure.acquire_command_slot().await;
+                    let trace = options.runtime.tracer.start();
+                    trace::record(trace, "parse", &format!("client={} command={}", client_id, parsed_command.name()));
+                    if let Err(e) = publisher.publish_command(client_id, parsed_command, trace).await {
+                        log_warning!("Failed to publish command: {}", e);
+                        break 'read;
+                    }
+                }
+            }
+            // 종료로 이벤트 루프가 이미 끝났으면 알릴 곳이 없음
+            if shutdown.has_changed().is_err() {
+                return;
+            }
+            if let Err(e) = publisher.publish_client_disconnected(client_id).await {
+                log_warning!("Failed to send client disconnected event: {}", e);
+            }
+        }));
+    }
+}
@@ -0,0 +1,59 @@
+use crate::protocol_constants::CRLF;
+use std::process;
+use tokio::time::Instant;
+
+pub const SERVER_VERSION: &str = env!("CARGO_PKG_VERSION");
+pub const GIT_SHA: &str = env!("GIT_SHA");
+pub const RUSTC_VERSION: &str = env!("RUSTC_VERSION");
+pub const RUN_ID_LEN: usize = 40;
+
+pub struct ServerInfo {
+    started_at: Instant,
+    // 프로세스마다 한 번 만드는 40자리 hex, 복제에서 처음 replid로도 씀
+    run_id: String,
+    tcp_port: u16,
+    config_file: Option<String>,
+}
+
+impl ServerInfo {
+    pub fn new() -> Self {
+        Self {
+            started_at: Instant::now(),
+            run_id: String::new(),
+            tcp_port: 6379,
+            config_file: None,
+        }
+    }
+
+    pub fn set_run_id(&mut self, run_id: String) {
+        self.run_id = run_id;
+    }
+
+    pub fn set_config_file(&mut self, config_file: Option<String>) {
+        self.config_file = config_file;
+    }
+
+    pub fn set_tcp_port(&mut self, tcp_port: u16) {
+        self.tcp_port = tcp_port;
+    }
+
+    pub fn uptime_in_seconds(&self) -> u64 {
+        self.started_at.elapsed().as_secs()
+    }
+
+    pub fn get_server_info(&self) -> String {
+        let uptime = self.uptime_in_seconds();
+        let mut info = format!("# Server{}", CRLF);
+        info.push_str(&format!("redis_version:{}{}", SERVER_VERSION, CRLF));
+        info.push_str(&format!("redis_git_sha1:{}{}", GIT_SHA, CRLF));
+        info.push_str(&format!("rustc_version:{}{}", RUSTC_VERSION, CRLF));
+        info.push_str(&format!("os:{} {}{}", std::env::consts::OS, std::env::consts::ARCH, CRLF));
+        info.push_str(&format!("process_id:{}{}", process::id(), CRLF));
+        info.push_str(&format!("run_id:{}{}", self.run_id, CRLF));
+        info.push_str(&format!("tcp_port:{}{}", self.tcp_port, CRLF));
+        info.push_str(&format!("uptime_in_seconds:{}{}", uptime, CRLF));
+        info.push_str(&format!("uptime_in_days:{}{}", uptime / 86400, CRLF));
+        info.push_str(&format!("config_file:{}{}", self.config_file.as_deref().unwrap_or(""), CRLF));
+        info
+    }
+}
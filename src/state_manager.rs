@@ -1,25 +1,52 @@
+use crate::config_handler::{Db, RuntimeSettings};
 use crate::replication_config::ReplicationConfig;
+use crate::random::Random;
+use crate::scripting::{FunctionRegistry, ScriptMonitor};
+use crate::server_info::{ServerInfo, RUN_ID_LEN};
+use crate::stats::Stats;
 use std::collections::HashMap;
-use crate::value_entry::ValueEntry;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
 pub struct StateManager {
-    db: Arc<RwLock<HashMap<String, ValueEntry>>>,
+    db: Arc<RwLock<Db>>,
     config: Arc<RwLock<HashMap<String, String>>>,
     replication_config: Arc<RwLock<ReplicationConfig>>,
+    stats: Arc<RwLock<Stats>>,
+    server_info: Arc<RwLock<ServerInfo>>,
+    functions: Arc<RwLock<FunctionRegistry>>,
+    script_monitor: Arc<ScriptMonitor>,
+    random: Arc<Random>,
+    runtime: Arc<RuntimeSettings>,
 }
 
 impl StateManager {
     pub fn new() -> Self {
+        let random = Arc::new(Random::new());
         Self {
-            db: Arc::new(RwLock::new(HashMap::new())),
+            db: Arc::new(RwLock::new(Db::new(random.clone()))),
             config: Arc::new(RwLock::new(HashMap::new())),
-            replication_config: Arc::new(RwLock::new(ReplicationConfig::new())),
+            replication_config: Arc::new(RwLock::new(ReplicationConfig::new(random.clone()))),
+            stats: Arc::new(RwLock::new(Stats::new())),
+            server_info: Arc::new(RwLock::new(ServerInfo::new())),
+            functions: Arc::new(RwLock::new(FunctionRegistry::new())),
+            script_monitor: Arc::new(ScriptMonitor::default()),
+            random,
+            runtime: Arc::new(RuntimeSettings::default()),
         }
     }
 
-    pub fn get_db(&self) -> Arc<RwLock<HashMap<String, ValueEntry>>> {
+    // 설정을 읽은 뒤에 불림, debug-random-seed가 있으면 이 서버의 난수 생성기에만 걸어 run_id부터 재현되게 함
+    pub async fn init_random(&self) {
+        if let Some(seed) = self.config.read().await.get("debug_random_seed").and_then(|seed| seed.parse::<u64>().ok()) {
+            self.random.set_seed(seed);
+        }
+        let run_id = self.random.hex(RUN_ID_LEN);
+        self.server_info.write().await.set_run_id(run_id.clone());
+        self.replication_config.read().await.set_replid(run_id).await;
+    }
+
+    pub fn get_db(&self) -> Arc<RwLock<Db>> {
         self.db.clone()
     }
 
@@ -30,4 +57,28 @@ impl StateManager {
     pub fn get_replication_config(&self) -> Arc<RwLock<ReplicationConfig>> {
         self.replication_config.clone()
     }
-}
\ No newline at end of file
+
+    pub fn get_stats(&self) -> Arc<RwLock<Stats>> {
+        self.stats.clone()
+    }
+
+    pub fn get_server_info(&self) -> Arc<RwLock<ServerInfo>> {
+        self.server_info.clone()
+    }
+
+    pub fn get_functions(&self) -> Arc<RwLock<FunctionRegistry>> {
+        self.functions.clone()
+    }
+
+    pub fn get_script_monitor(&self) -> Arc<ScriptMonitor> {
+        self.script_monitor.clone()
+    }
+
+    pub fn get_random(&self) -> Arc<Random> {
+        self.random.clone()
+    }
+
+    pub fn get_runtime(&self) -> Arc<RuntimeSettings> {
+        self.runtime.clone()
+    }
+}
@@ -0,0 +1,354 @@
+use crate::protocol_constants::CRLF;
+use crate::util::{current_time_ms, json_string};
+use std::collections::{HashMap, VecDeque};
+
+const SLOWLOG_MAX_LEN: usize = 128;
+// Redis처럼 최근 16번 샘플한 초당 명령 수의 평균을 instantaneous_ops_per_sec으로 보여줌
+const OPS_SAMPLES: usize = 16;
+const SIZE_BUCKETS: [usize; 8] = [16, 64, 256, 1024, 4096, 16384, 65536, usize::MAX];
+// latencystats에 보여주는 백분위수, Redis latency-tracking-info-percentiles 기본값
+const LATENCY_PERCENTILES: [f64; 3] = [50.0, 99.0, 99.9];
+// 2의 거듭제곱 구간마다 나누는 칸 수, 값의 상대 오차가 1/8 이하가 됨
+const LATENCY_SUB_BUCKETS: u64 = 8;
+
+#[derive(Default)]
+pub struct SizeHistogram {
+    buckets: [u64; SIZE_BUCKETS.len()],
+    count: u64,
+    sum: u64,
+}
+
+impl SizeHistogram {
+    pub fn record(&mut self, size: usize) {
+        let bucket = SIZE_BUCKETS.iter().position(|limit| size <= *limit).unwrap_or(SIZE_BUCKETS.len() - 1);
+        self.buckets[bucket] += 1;
+        self.count += 1;
+        self.sum += size as u64;
+    }
+
+    fn render(&self) -> String {
+        let mut fields = vec![format!("count={}", self.count), format!("sum={}", self.sum)];
+        for (limit, hits) in SIZE_BUCKETS.iter().zip(self.buckets.iter()) {
+            if *limit == usize::MAX {
+                fields.push(format!("le_inf={}", hits));
+            } else {
+                fields.push(format!("le_{}={}", limit, hits));
+            }
+        }
+        fields.join(",")
+    }
+
+    // {"count":..,"sum":..,"buckets":{"16":..,..,"inf":..}}, 칸마다 그 크기 이하인 요청 수
+    fn to_json(&self) -> String {
+        let buckets: Vec<String> = SIZE_BUCKETS
+            .iter()
+            .zip(self.buckets.iter())
+            .map(|(limit, hits)| if *limit == usize::MAX { format!("\"inf\":{}", hits) } else { format!("\"{}\":{}", limit, hits) })
+            .collect();
+        format!("{{\"count\":{},\"sum\":{},\"buckets\":{{{}}}}}", self.count, self.sum, buckets.join(","))
+    }
+}
+
+// 명령 실행 시간(us)의 로그-선형 히스토그램, 호출마다 기록해도 명령당 크기가 500칸 이하로 고정됨
+#[derive(Default)]
+struct LatencyHistogram {
+    buckets: Vec<u64>,
+    count: u64,
+    max: u64,
+}
+
+impl LatencyHistogram {
+    fn bucket(value: u64) -> usize {
+        if value < LATENCY_SUB_BUCKETS {
+            return value as usize;
+        }
+        let exponent = 63 - value.leading_zeros() as u64;
+        let sub = (value >> (exponent - 3)) & (LATENCY_SUB_BUCKETS - 1);
+        (LATENCY_SUB_BUCKETS * (exponent - 2) + sub) as usize
+    }
+
+    // 칸에 들어가는 가장 큰 값
+    fn upper_bound(bucket: usize) -> u64 {
+        let bucket = bucket as u64;
+        if bucket < LATENCY_SUB_BUCKETS {
+            return bucket;
+        }
+        let exponent = bucket / LATENCY_SUB_BUCKETS + 2;
+        let sub = bucket % LATENCY_SUB_BUCKETS;
+        let bound = ((LATENCY_SUB_BUCKETS + sub + 1) as u128) << (exponent - 3);
+        (bound - 1).min(u64::MAX as u128) as u64
+    }
+
+    fn record(&mut self, value: u64) {
+        let bucket = Self::bucket(value);
+        if self.buckets.len() <= bucket {
+            self.buckets.resize(bucket + 1, 0);
+        }
+        self.buckets[bucket] += 1;
+        self.count += 1;
+        self.max = self.max.max(value);
+    }
+
+    fn percentile(&self, percentile: f64) -> u64 {
+        let target = ((percentile / 100.0 * self.count as f64).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (bucket, hits) in self.buckets.iter().enumerate() {
+            seen += hits;
+            if seen >= target {
+                return Self::upper_bound(bucket).min(self.max);
+            }
+        }
+        self.max
+    }
+
+    fn render(&self) -> String {
+        LATENCY_PERCENTILES
+            .iter()
+            .map(|percentile| format!("p{}={:.3}", percentile, self.percentile(*percentile) as f64))
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+// INFO commandstats의 명령별 통계, rejected는 실행 전에 거절된 호출, failed는 실행했지만 에러로 응답한 호출
+#[derive(Default)]
+struct CommandStat {
+    calls: u64,
+    usec: u64,
+    usec_max: u64,
+    rejected_calls: u64,
+    failed_calls: u64,
+    latency: LatencyHistogram,
+}
+
+pub struct SlowlogEntry {
+    pub id: u64,
+    pub timestamp: u64,
+    pub duration_us: u64,
+    pub command: String,
+    pub client_addr: String,
+}
+
+pub struct Stats {
+    deprecated_calls: u64,
+    deprecated_calls_by_command: HashMap<String, u64>,
+    net_input_bytes: u64,
+    net_output_bytes: u64,
+    net_repl_output_bytes: u64,
+    request_sizes: HashMap<String, SizeHistogram>,
+    reply_sizes: HashMap<String, SizeHistogram>,
+    slowlog: VecDeque<SlowlogEntry>,
+    next_slowlog_id: u64,
+    expired_keys: u64,
+    evicted_keys: u64,
+    connections_received: u64,
+    commands_processed: u64,
+    keyspace_hits: u64,
+    keyspace_misses: u64,
+    ops_samples: [u64; OPS_SAMPLES],
+    ops_sample_index: usize,
+    // 마지막 샘플의 (시각 ms, 그때까지 처리한 명령 수)
+    last_ops_sample: Option<(u64, u64)>,
+    used_memory_peak: u64,
+    command_stats: HashMap<String, CommandStat>,
+}
+
+impl Stats {
+    pub fn new() -> Self {
+        Self {
+            deprecated_calls: 0,
+            deprecated_calls_by_command: HashMap::new(),
+            net_input_bytes: 0,
+            net_output_bytes: 0,
+            net_repl_output_bytes: 0,
+            request_sizes: HashMap::new(),
+            reply_sizes: HashMap::new(),
+            slowlog: VecDeque::new(),
+            next_slowlog_id: 0,
+            expired_keys: 0,
+            evicted_keys: 0,
+            connections_received: 0,
+            commands_processed: 0,
+            keyspace_hits: 0,
+            keyspace_misses: 0,
+            ops_samples: [0; OPS_SAMPLES],
+            ops_sample_index: 0,
+            last_ops_sample: None,
+            used_memory_peak: 0,
+            command_stats: HashMap::new(),
+        }
+    }
+
+    pub fn record_request(&mut self, command: &str, size: usize) {
+        self.net_input_bytes += size as u64;
+        self.request_sizes.entry(command.to_lowercase()).or_default().record(size);
+    }
+
+    pub fn record_reply(&mut self, command: &str, size: usize) {
+        self.net_output_bytes += size as u64;
+        self.reply_sizes.entry(command.to_lowercase()).or_default().record(size);
+    }
+
+    pub fn record_output(&mut self, size: usize) {
+        self.net_output_bytes += size as u64;
+    }
+
+    pub fn record_repl_output(&mut self, size: usize) {
+        self.net_repl_output_bytes += size as u64;
+    }
+
+    pub fn record_slow_command(&mut self, command: &str, client_addr: String, duration_us: u64) {
+        if self.slowlog.len() == SLOWLOG_MAX_LEN {
+            self.slowlog.pop_back();
+        }
+        self.slowlog.push_front(SlowlogEntry {
+            id: self.next_slowlog_id,
+            timestamp: current_time_ms() / 1000,
+            duration_us,
+            command: command.to_lowercase(),
+            client_addr,
+        });
+        self.next_slowlog_id += 1;
+    }
+
+    pub fn recent_slow_commands(&self) -> impl Iterator<Item = &SlowlogEntry> {
+        self.slowlog.iter()
+    }
+
+    pub fn record_connection(&mut self) {
+        self.connections_received += 1;
+    }
+
+    pub fn record_call(&mut self, command: &str, duration_us: u64, failed: bool) {
+        self.commands_processed += 1;
+        let stat = self.command_stats.entry(command.to_lowercase()).or_default();
+        stat.calls += 1;
+        stat.usec += duration_us;
+        stat.usec_max = stat.usec_max.max(duration_us);
+        stat.failed_calls += failed as u64;
+        stat.latency.record(duration_us);
+    }
+
+    pub fn record_rejected_call(&mut self, command: &str) {
+        self.command_stats.entry(command.to_lowercase()).or_default().rejected_calls += 1;
+    }
+
+    pub fn record_keyspace_lookup(&mut self, hit: bool) {
+        if hit {
+            self.keyspace_hits += 1;
+        } else {
+            self.keyspace_misses += 1;
+        }
+    }
+
+    // 서버 주기 작업(hz)마다 불러 직전 샘플 이후의 초당 명령 수를 남김
+    pub fn sample_ops(&mut self, now_ms: u64) {
+        if let Some((last_ms, last_commands)) = self.last_ops_sample {
+            let elapsed_ms = now_ms.saturating_sub(last_ms).max(1);
+            let ops = (self.commands_processed - last_commands) * 1000 / elapsed_ms;
+            self.ops_samples[self.ops_sample_index] = ops;
+            self.ops_sample_index = (self.ops_sample_index + 1) % OPS_SAMPLES;
+        }
+        self.last_ops_sample = Some((now_ms, self.commands_processed));
+    }
+
+    pub fn instantaneous_ops_per_sec(&self) -> u64 {
+        self.ops_samples.iter().sum::<u64>() / OPS_SAMPLES as u64
+    }
+
+    // 사용량을 잴 때마다 불러 피크를 갱신함, 갱신된 피크를 돌려줌
+    pub fn record_used_memory(&mut self, used_memory: u64) -> u64 {
+        self.used_memory_peak = self.used_memory_peak.max(used_memory);
+        self.used_memory_peak
+    }
+
+    pub fn record_expired_keys(&mut self, count: usize) {
+        self.expired_keys += count as u64;
+    }
+
+    pub fn record_evicted_key(&mut self) {
+        self.evicted_keys += 1;
+    }
+
+    pub fn record_deprecated_call(&mut self, command: &str) {
+        self.deprecated_calls += 1;
+        *self.deprecated_calls_by_command.entry(command.to_lowercase()).or_insert(0) += 1;
+    }
+
+    pub fn get_commandstats_info(&self) -> String {
+        let mut info = format!("# Commandstats{}", CRLF);
+        let mut commands: Vec<_> = self.command_stats.iter().collect();
+        commands.sort_by(|a, b| a.0.cmp(b.0));
+        for (command, stat) in commands {
+            let usec_per_call = if stat.calls == 0 { 0.0 } else { stat.usec as f64 / stat.calls as f64 };
+            info.push_str(&format!(
+                "cmdstat_{}:calls={},usec={},usec_per_call={:.2},usec_max={},rejected_calls={},failed_calls={}{}",
+                command, stat.calls, stat.usec, usec_per_call, stat.usec_max, stat.rejected_calls, stat.failed_calls, CRLF
+            ));
+        }
+
+        // 명령별 요청/응답 크기 분포도 명령마다 한 줄씩이라 Stats가 아니라 여기에 둠
+        let mut request_sizes: Vec<_> = self.request_sizes.iter().collect();
+        request_sizes.sort_by(|a, b| a.0.cmp(b.0));
+        for (command, histogram) in request_sizes {
+            info.push_str(&format!("cmdrequestsize_{}:{}{}", command, histogram.render(), CRLF));
+        }
+
+        let mut reply_sizes: Vec<_> = self.reply_sizes.iter().collect();
+        reply_sizes.sort_by(|a, b| a.0.cmp(b.0));
+        for (command, histogram) in reply_sizes {
+            info.push_str(&format!("cmdreplysize_{}:{}{}", command, histogram.render(), CRLF));
+        }
+        info
+    }
+
+    pub fn get_latencystats_info(&self) -> String {
+        let mut info = format!("# Latencystats{}", CRLF);
+        let mut commands: Vec<_> = self.command_stats.iter().filter(|(_, stat)| stat.calls > 0).collect();
+        commands.sort_by(|a, b| a.0.cmp(b.0));
+        for (command, stat) in commands {
+            info.push_str(&format!("latency_percentiles_usec_{}:{}{}", command, stat.latency.render(), CRLF));
+        }
+        info
+    }
+
+    // 관리 리스너의 /metrics 뷰: 네트워크 바이트 누계와 명령별 요청/응답 크기 분포
+    pub fn metrics_json(&self) -> String {
+        let histograms = |sizes: &HashMap<String, SizeHistogram>| {
+            let mut commands: Vec<_> = sizes.iter().collect();
+            commands.sort_by(|a, b| a.0.cmp(b.0));
+            let entries: Vec<String> = commands.into_iter().map(|(command, histogram)| format!("{}:{}", json_string(command), histogram.to_json())).collect();
+            format!("{{{}}}", entries.join(","))
+        };
+        format!(
+            "{{\"total_net_input_bytes\":{},\"total_net_output_bytes\":{},\"total_net_repl_output_bytes\":{},\"request_sizes\":{},\"reply_sizes\":{}}}",
+            self.net_input_bytes,
+            self.net_output_bytes,
+            self.net_repl_output_bytes,
+            histograms(&self.request_sizes),
+            histograms(&self.reply_sizes)
+        )
+    }
+
+    pub fn get_stats_info(&self) -> String {
+        let mut info = format!("# Stats{}", CRLF);
+        info.push_str(&format!("total_connections_received:{}{}", self.connections_received, CRLF));
+        info.push_str(&format!("total_commands_processed:{}{}", self.commands_processed, CRLF));
+        info.push_str(&format!("instantaneous_ops_per_sec:{}{}", self.instantaneous_ops_per_sec(), CRLF));
+        info.push_str(&format!("total_net_input_bytes:{}{}", self.net_input_bytes, CRLF));
+        info.push_str(&format!("total_net_output_bytes:{}{}", self.net_output_bytes, CRLF));
+        info.push_str(&format!("total_net_repl_output_bytes:{}{}", self.net_repl_output_bytes, CRLF));
+        info.push_str(&format!("expired_keys:{}{}", self.expired_keys, CRLF));
+        info.push_str(&format!("evicted_keys:{}{}", self.evicted_keys, CRLF));
+        info.push_str(&format!("keyspace_hits:{}{}", self.keyspace_hits, CRLF));
+        info.push_str(&format!("keyspace_misses:{}{}", self.keyspace_misses, CRLF));
+        info.push_str(&format!("total_deprecated_calls:{}{}", self.deprecated_calls, CRLF));
+
+        let mut commands: Vec<_> = self.deprecated_calls_by_command.iter().collect();
+        commands.sort();
+        for (command, calls) in commands {
+            info.push_str(&format!("deprecated_calls_{}:{}{}", command, calls, CRLF));
+        }
+        info
+    }
+}
@@ -0,0 +1,102 @@
+use crate::client::Client;
+use crate::resp::RespValue;
+use crate::server::{Server, ServerBuilder, ServerHandle};
+use std::io;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+// 한 프로세스에서 동시에 도는 테스트 서버끼리 RDB 디렉터리가 겹치지 않게 함
+static NEXT_SERVER_ID: AtomicU64 = AtomicU64::new(1);
+
+// 테스트마다 임시 디렉터리와 OS가 고른 포트로 띄우는 서버, 같은 프로세스 안에서 돌므로 따로 빌드하거나 기다릴 필요가 없음
+// 종료 시 저장하지 않도록 save ""로 띄우고, drop하면 디렉터리를 지움
+pub struct TestServer {
+    handle: ServerHandle,
+    dir: PathBuf,
+}
+
+impl TestServer {
+    pub async fn start() -> Result<Self, String> {
+        Self::start_with(|builder| builder).await
+    }
+
+    // 포트와 디렉터리 외의 옵션은 configure에서 더함
+    pub async fn start_with(configure: impl FnOnce(ServerBuilder) -> ServerBuilder) -> Result<Self, String> {
+        let dir = std::env::temp_dir().join(format!(
+            "redis-test-{}-{}",
+            std::process::id(),
+            NEXT_SERVER_ID.fetch_add(1, Ordering::Relaxed)
+        ));
+        std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        let builder = Server::builder()
+            .port(0)
+            .option("bind", "127.0.0.1")
+            .dir(dir.display().to_string())
+            .option("save", "");
+        let builder = configure(builder);
+        // 다른 테스트 서버의 디렉터리를 이어받는 것은 되지만, 저장소 루트처럼 임시 디렉터리 밖에는 쓰지 못하게 함
+        if let Some(configured) = builder.option_value("dir") {
+            if !Path::new(configured).starts_with(std::env::temp_dir()) {
+                let _ = std::fs::remove_dir_all(&dir);
+                return Err(format!("Test servers must keep their files under {}, got dir {}", std::env::temp_dir().display(), configured));
+            }
+        }
+        match builder.spawn().await {
+            Ok(handle) => Ok(Self { handle, dir }),
+            Err(e) => {
+                let _ = std::fs::remove_dir_all(&dir);
+                Err(e)
+            }
+        }
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.handle.local_addr()
+    }
+
+    pub fn port(&self) -> u16 {
+        self.addr().port()
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub async fn client(&self) -> io::Result<Client> {
+        Client::connect(self.addr()).await
+    }
+
+    pub async fn shutdown(mut self) -> Result<(), String> {
+        self.handle.shutdown().await
+    }
+}
+
+impl Drop for TestServer {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+// 테스트에서 기대 응답을 짧게 쓰기 위한 생성자
+pub fn ok() -> RespValue {
+    RespValue::ok()
+}
+
+pub fn bulk(value: &str) -> RespValue {
+    RespValue::bulk(value)
+}
+
+// 에러 코드까지 포함한 응답 그대로, RespValue::error와 달리 ERR을 붙이지 않음
+pub fn error(message: &str) -> RespValue {
+    RespValue::Error(message.into())
+}
+
+// INFO <section>에서 한 필드의 값, 필드가 없으면 None
+pub async fn info_field(client: &mut Client, section: &str, field: &str) -> Option<String> {
+    let RespValue::BulkString(info) = client.command(&["INFO", section]).await.unwrap() else {
+        panic!("INFO {} did not return a bulk string", section);
+    };
+    let prefix = format!("{}:", field);
+    String::from_utf8_lossy(&info).lines().find_map(|line| line.strip_prefix(&prefix).map(str::to_string))
+}
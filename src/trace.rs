@@ -0,0 +1,56 @@
+use crate::logging::log_notice;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use tokio::time::Instant;
+
+// 서버마다 하나, trace 설정이 켜져 있을 때만 요청마다 id를 매김
+#[derive(Debug)]
+pub struct Tracer {
+    enabled: AtomicBool,
+    next_id: AtomicU64,
+}
+
+impl Default for Tracer {
+    fn default() -> Self {
+        Self { enabled: AtomicBool::new(false), next_id: AtomicU64::new(1) }
+    }
+}
+
+impl Tracer {
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    pub fn start(&self) -> Option<TraceContext> {
+        if !self.enabled.load(Ordering::Relaxed) {
+            return None;
+        }
+        Some(TraceContext {
+            id: self.next_id.fetch_add(1, Ordering::Relaxed),
+            started_at: Instant::now(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct TraceContext {
+    pub id: u64,
+    started_at: Instant,
+}
+
+impl TraceContext {
+    pub fn record(&self, stage: &str, detail: &str) {
+        log_notice!(
+            "[trace {:08x}] +{}us {} {}",
+            self.id,
+            self.started_at.elapsed().as_micros(),
+            stage,
+            detail
+        );
+    }
+}
+
+pub fn record(trace: Option<TraceContext>, stage: &str, detail: &str) {
+    if let Some(trace) = trace {
+        trace.record(stage, detail);
+    }
+}
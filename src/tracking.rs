@@ -0,0 +1,77 @@
+use std::collections::{HashMap, HashSet};
+
+// CLIENT TRACKING ON 옵션, 클라이언트별로 보관함
+#[derive(Debug, Clone, Default)]
+pub struct TrackingOptions {
+    pub redirect: Option<u64>,
+    pub bcast: bool,
+    pub prefixes: Vec<String>,
+    pub noloop: bool,
+    // OPTIN은 CLIENT CACHING yes 바로 다음 명령이 읽은 키만, OPTOUT은 CLIENT CACHING no 바로 다음 명령을 뺀 모든 읽기를 추적함
+    pub optin: bool,
+    pub optout: bool,
+}
+
+impl TrackingOptions {
+    // caching은 이 명령 바로 앞에 CLIENT CACHING을 보냈는지
+    pub fn tracks_read(&self, caching: bool) -> bool {
+        if self.optin {
+            caching
+        } else if self.optout {
+            !caching
+        } else {
+            !self.bcast
+        }
+    }
+
+    pub fn matches_prefix(&self, key: &[u8]) -> bool {
+        self.prefixes.is_empty() || self.prefixes.iter().any(|prefix| key.starts_with(prefix.as_bytes()))
+    }
+}
+
+// 기본 모드의 키 -> 그 키를 읽은 클라이언트 목록, 무효화 메시지를 한 번 보내면 다시 읽을 때까지 추적하지 않음
+pub struct TrackingTable {
+    readers: HashMap<Vec<u8>, HashSet<u64>>,
+}
+
+impl TrackingTable {
+    pub fn new() -> Self {
+        Self {
+            readers: HashMap::new(),
+        }
+    }
+
+    pub fn track(&mut self, client_id: u64, keys: &[&Vec<u8>]) {
+        for key in keys {
+            self.readers.entry((*key).clone()).or_default().insert(client_id);
+        }
+    }
+
+    // 키별 reader를 꺼내서 클라이언트 -> 무효화할 키 목록으로 모음
+    pub fn take_readers(&mut self, keys: &[&Vec<u8>]) -> HashMap<u64, Vec<Vec<u8>>> {
+        let mut targets: HashMap<u64, Vec<Vec<u8>>> = HashMap::new();
+        for key in keys {
+            if let Some(readers) = self.readers.remove(*key) {
+                for reader in readers {
+                    targets.entry(reader).or_default().push((*key).clone());
+                }
+            }
+        }
+        targets
+    }
+
+    pub fn remove_client(&mut self, client_id: u64) {
+        self.readers.retain(|_, readers| {
+            readers.remove(&client_id);
+            !readers.is_empty()
+        });
+    }
+
+    pub fn clear(&mut self) {
+        self.readers.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.readers.len()
+    }
+}
@@ -1,10 +1,211 @@
 use crate::protocol_constants::*;
+use crc::{Crc, CRC_16_XMODEM};
+use std::io;
+use std::net::SocketAddr;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::net::{TcpListener, TcpSocket, TcpStream};
 
-pub fn construct_redis_command(args: &[&str]) -> String {
-    let mut command = format!("{}{}{}", ARRAY_PREFIX, args.len(), CRLF);
+pub fn construct_redis_command<T: AsRef<[u8]>>(args: &[T]) -> Vec<u8> {
+    let mut command = format!("{}{}{}", ARRAY_PREFIX, args.len(), CRLF).into_bytes();
     for arg in args {
-        command.push_str(&format!("{}{}{}{}", BULK_STRING_PREFIX, arg.len(), CRLF, arg));
-        command.push_str(CRLF);
+        let arg = arg.as_ref();
+        command.extend_from_slice(format!("{}{}{}", BULK_STRING_PREFIX, arg.len(), CRLF).as_bytes());
+        command.extend_from_slice(arg);
+        command.extend_from_slice(CRLF.as_bytes());
     }
     command
-}
\ No newline at end of file
+}
+
+pub fn format_host_port(host: &str, port: u16) -> String {
+    if host.contains(':') && !host.starts_with('[') {
+        format!("[{}]:{}", host, port)
+    } else {
+        format!("{}:{}", host, port)
+    }
+}
+
+pub fn current_time_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+// 바이트로 된 인자/값에서 숫자를 읽음, UTF-8이 아니면 숫자가 아님
+pub fn parse_bytes<T: std::str::FromStr>(bytes: &[u8]) -> Option<T> {
+    std::str::from_utf8(bytes).ok()?.parse().ok()
+}
+
+pub const CLUSTER_SLOTS: u16 = 16384;
+
+// Redis Cluster 해시 슬롯: {hashtag}가 있으면 그 안의 내용만 해싱함
+pub fn key_hash_slot(key: &[u8]) -> u16 {
+    let hashed = match key.iter().position(|&b| b == b'{') {
+        Some(open) => match key[open + 1..].iter().position(|&b| b == b'}') {
+            Some(len) if len > 0 => &key[open + 1..open + 1 + len],
+            _ => key,
+        },
+        None => key,
+    };
+    Crc::<u16>::new(&CRC_16_XMODEM).checksum(hashed) % CLUSTER_SLOTS
+}
+
+// 키 패턴은 바이트 단위로 비교함, 채널/설정 이름처럼 문자열인 쪽은 as_bytes()로 넘김
+pub fn glob_match(pattern: &[u8], text: &[u8]) -> bool {
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() {
+            match pattern[p] {
+                b'*' => {
+                    backtrack = Some((p, t));
+                    p += 1;
+                    continue;
+                }
+                b'?' => {
+                    p += 1;
+                    t += 1;
+                    continue;
+                }
+                b'[' => {
+                    if let Some((matched, next)) = match_class(pattern, p, text[t]) {
+                        if matched {
+                            p = next;
+                            t += 1;
+                            continue;
+                        }
+                    }
+                }
+                b'\\' if p + 1 < pattern.len() && pattern[p + 1] == text[t] => {
+                    p += 2;
+                    t += 1;
+                    continue;
+                }
+                // 이스케이프된 문자가 다르면 '\\' 자체와 비교하지 않음
+                b'\\' if p + 1 < pattern.len() => {}
+                c if c == text[t] => {
+                    p += 1;
+                    t += 1;
+                    continue;
+                }
+                _ => {}
+            }
+        }
+
+        match backtrack {
+            Some((star_p, star_t)) => {
+                backtrack = Some((star_p, star_t + 1));
+                p = star_p + 1;
+                t = star_t + 1;
+            }
+            None => return false,
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == b'*')
+}
+
+fn match_class(pattern: &[u8], start: usize, c: u8) -> Option<(bool, usize)> {
+    let mut i = start + 1;
+    let negate = i < pattern.len() && pattern[i] == b'^';
+    if negate {
+        i += 1;
+    }
+
+    let mut matched = false;
+    while i < pattern.len() && pattern[i] != b']' {
+        if pattern[i] == b'\\' && i + 1 < pattern.len() {
+            matched |= pattern[i + 1] == c;
+            i += 2;
+        } else if i + 2 < pattern.len() && pattern[i + 1] == b'-' && pattern[i + 2] != b']' {
+            let (low, high) = if pattern[i] <= pattern[i + 2] {
+                (pattern[i], pattern[i + 2])
+            } else {
+                (pattern[i + 2], pattern[i])
+            };
+            matched |= low <= c && c <= high;
+            i += 3;
+        } else {
+            matched |= pattern[i] == c;
+            i += 1;
+        }
+    }
+
+    if i >= pattern.len() {
+        return None;
+    }
+    Some((matched != negate, i + 1))
+}
+
+pub fn json_string(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len() + 2);
+    escaped.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+// 비밀번호 비교, 첫 번째로 다른 바이트의 위치가 응답 시간으로 드러나지 않도록 항상 끝까지 비교함
+pub fn time_independent_eq(a: &[u8], b: &[u8]) -> bool {
+    let mut diff = a.len() ^ b.len();
+    for i in 0..a.len().max(b.len()) {
+        let x = a.get(i).copied().unwrap_or(0);
+        let y = b.get(i).copied().unwrap_or(0);
+        diff |= (x ^ y) as usize;
+    }
+    diff == 0
+}
+
+// 리눅스에서는 받은 연결이 리슨 소켓의 SO_KEEPALIVE를 물려받으므로 리슨 소켓에 켜 둠
+pub fn bind_listener(addr: SocketAddr, backlog: u32, keepalive: bool) -> io::Result<TcpListener> {
+    let socket = if addr.is_ipv4() { TcpSocket::new_v4()? } else { TcpSocket::new_v6()? };
+    socket.set_reuseaddr(true)?;
+    socket.set_keepalive(keepalive)?;
+    socket.bind(addr)?;
+    socket.listen(backlog)
+}
+
+// 호스트 이름이면 주소를 찾아 차례로 시도함, keepalive는 연결하기 전에 켬
+pub async fn connect_tcp(address: &str, keepalive: bool) -> io::Result<TcpStream> {
+    let mut last_error = io::Error::new(io::ErrorKind::NotFound, format!("could not resolve {}", address));
+    let addrs = tokio::net::lookup_host(address)
+        .await
+        .map_err(|e| io::Error::new(e.kind(), format!("could not resolve {}: {}", address, e)))?;
+    for addr in addrs {
+        let socket = if addr.is_ipv4() { TcpSocket::new_v4()? } else { TcpSocket::new_v6()? };
+        socket.set_keepalive(keepalive)?;
+        match socket.connect(addr).await {
+            Ok(stream) => return Ok(stream),
+            Err(e) => last_error = e,
+        }
+    }
+    Err(last_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_host_port_brackets_ipv6_hosts() {
+        assert_eq!(format_host_port("127.0.0.1", 6379), "127.0.0.1:6379");
+        assert_eq!(format_host_port("localhost", 6379), "localhost:6379");
+        assert_eq!(format_host_port("::1", 6379), "[::1]:6379");
+        assert_eq!(format_host_port("fe80::1%eth0", 7000), "[fe80::1%eth0]:7000");
+        // 이미 괄호로 감싼 주소는 그대로 씀
+        assert_eq!(format_host_port("[::1]", 6379), "[::1]:6379");
+        // 괄호로 감싼 주소는 SocketAddr로 다시 읽을 수 있어야 함
+        assert_eq!(format_host_port("::1", 6379).parse::<SocketAddr>().unwrap(), SocketAddr::from(([0, 0, 0, 0, 0, 0, 0, 1], 6379)));
+    }
+}
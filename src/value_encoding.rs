@@ -0,0 +1,365 @@
+use crate::listpack::Listpack;
+use crate::rdb_encoding::canonical_integer;
+use std::borrow::Cow;
+use std::collections::{HashMap, VecDeque};
+
+// Redis처럼 44바이트 이하의 문자열은 객체 안에 바로 담음(embstr)
+const EMBSTR_MAX_LEN: usize = 44;
+// i64의 10진 표현이 가장 길 때의 길이
+const INT_MAX_DIGITS: usize = 20;
+// list-max-listpack-size가 음수일 때의 바이트 한도: -1은 4KB, -2는 8KB ... -5는 64KB
+const LIST_LISTPACK_BYTE_LIMITS: [usize; 5] = [4096, 8192, 16384, 32768, 65536];
+
+// Redis 기본값과 같은 listpack 변환 기준
+pub const DEFAULT_HASH_MAX_LISTPACK_ENTRIES: usize = 128;
+pub const DEFAULT_HASH_MAX_LISTPACK_VALUE: usize = 64;
+pub const DEFAULT_LIST_MAX_LISTPACK_SIZE: i64 = -2;
+pub const DEFAULT_ZSET_MAX_LISTPACK_ENTRIES: usize = 128;
+pub const DEFAULT_ZSET_MAX_LISTPACK_VALUE: usize = 64;
+
+// Redis의 *-max-listpack-* 설정, 쓰기 잠금 아래에서 원소를 넣을 때마다 보므로 LFU 설정처럼 키스페이스가 들고 있음
+// 한도를 낮춰도 이미 있는 값은 그대로 두고, 다음에 원소를 넣을 때 넘으면 변환함 (Redis와 같음)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ListpackLimits {
+    pub hash_entries: usize,
+    pub hash_value: usize,
+    pub list_size: i64,
+    pub zset_entries: usize,
+    pub zset_value: usize,
+}
+
+impl Default for ListpackLimits {
+    fn default() -> Self {
+        Self {
+            hash_entries: DEFAULT_HASH_MAX_LISTPACK_ENTRIES,
+            hash_value: DEFAULT_HASH_MAX_LISTPACK_VALUE,
+            list_size: DEFAULT_LIST_MAX_LISTPACK_SIZE,
+            zset_entries: DEFAULT_ZSET_MAX_LISTPACK_ENTRIES,
+            zset_value: DEFAULT_ZSET_MAX_LISTPACK_VALUE,
+        }
+    }
+}
+
+impl ListpackLimits {
+    fn hash_fits(&self, entries: usize, longest: usize) -> bool {
+        entries <= self.hash_entries && longest <= self.hash_value
+    }
+
+    fn zset_fits(&self, entries: usize, longest: usize) -> bool {
+        entries <= self.zset_entries && longest <= self.zset_value
+    }
+
+    // 양수면 원소 수, 음수면 버퍼 크기로 제한함
+    fn list_fits(&self, entries: usize, bytes: usize) -> bool {
+        match self.list_size {
+            size if size > 0 => entries <= size as usize,
+            size => {
+                let index = (size.unsigned_abs().max(1) as usize).min(LIST_LISTPACK_BYTE_LIMITS.len()) - 1;
+                bytes <= LIST_LISTPACK_BYTE_LIMITS[index]
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum StringValue {
+    Int(i64),
+    Embedded { len: u8, bytes: [u8; EMBSTR_MAX_LEN] },
+    Raw(Vec<u8>),
+}
+
+impl StringValue {
+    // 10진 표현이 원래 바이트와 똑같은 정수만 int로 담음, "007"이나 "+1"은 그대로 돌려줘야 하므로 문자열로 둠
+    pub fn new(value: Vec<u8>) -> Self {
+        if value.len() <= INT_MAX_DIGITS {
+            if let Some(number) = canonical_integer(&value) {
+                return StringValue::Int(number);
+            }
+        }
+        if value.len() <= EMBSTR_MAX_LEN {
+            let mut bytes = [0; EMBSTR_MAX_LEN];
+            bytes[..value.len()].copy_from_slice(&value);
+            return StringValue::Embedded { len: value.len() as u8, bytes };
+        }
+        StringValue::Raw(value)
+    }
+
+    pub fn as_bytes(&self) -> Cow<'_, [u8]> {
+        match self {
+            StringValue::Int(number) => Cow::Owned(number.to_string().into_bytes()),
+            StringValue::Embedded { len, bytes } => Cow::Borrowed(&bytes[..*len as usize]),
+            StringValue::Raw(value) => Cow::Borrowed(value),
+        }
+    }
+
+    pub fn encoding(&self) -> &'static str {
+        match self {
+            StringValue::Int(_) => "int",
+            StringValue::Embedded { .. } => "embstr",
+            StringValue::Raw(_) => "raw",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum ListValue {
+    Listpack(Listpack),
+    Quicklist(VecDeque<Vec<u8>>),
+}
+
+impl Default for ListValue {
+    fn default() -> Self {
+        ListValue::Listpack(Listpack::default())
+    }
+}
+
+impl ListValue {
+    // RDB에서 읽은 리스트, 한도 안이면 listpack으로 담음
+    pub fn from_elements(elements: Vec<Vec<u8>>, limits: ListpackLimits) -> Self {
+        let mut list = ListValue::Quicklist(elements.into());
+        list.shrink_to_listpack(limits);
+        list
+    }
+
+    fn shrink_to_listpack(&mut self, limits: ListpackLimits) {
+        if let ListValue::Quicklist(elements) = self {
+            let bytes: usize = elements.iter().map(|element| element.len() + 1).sum();
+            if limits.list_fits(elements.len(), bytes) {
+                let mut listpack = Listpack::default();
+                elements.iter().for_each(|element| listpack.push_back(element));
+                *self = ListValue::Listpack(listpack);
+            }
+        }
+    }
+
+    fn convert_if_needed(&mut self, limits: ListpackLimits) {
+        if let ListValue::Listpack(listpack) = self {
+            if !limits.list_fits(listpack.len(), listpack.bytes()) {
+                *self = ListValue::Quicklist(listpack.iter().map(|element| element.to_vec()).collect());
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            ListValue::Listpack(listpack) => listpack.len(),
+            ListValue::Quicklist(elements) => elements.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn iter(&self) -> Box<dyn Iterator<Item = &[u8]> + '_> {
+        match self {
+            ListValue::Listpack(listpack) => Box::new(listpack.iter()),
+            ListValue::Quicklist(elements) => Box::new(elements.iter().map(|element| element.as_slice())),
+        }
+    }
+
+    pub fn push_front(&mut self, element: Vec<u8>, limits: ListpackLimits) {
+        match self {
+            ListValue::Listpack(listpack) => listpack.push_front(&element),
+            ListValue::Quicklist(elements) => elements.push_front(element),
+        }
+        self.convert_if_needed(limits);
+    }
+
+    pub fn push_back(&mut self, element: Vec<u8>, limits: ListpackLimits) {
+        match self {
+            ListValue::Listpack(listpack) => listpack.push_back(&element),
+            ListValue::Quicklist(elements) => elements.push_back(element),
+        }
+        self.convert_if_needed(limits);
+    }
+
+    pub fn pop_front(&mut self) -> Option<Vec<u8>> {
+        match self {
+            ListValue::Listpack(listpack) => listpack.pop_front(),
+            ListValue::Quicklist(elements) => elements.pop_front(),
+        }
+    }
+
+    pub fn pop_back(&mut self) -> Option<Vec<u8>> {
+        match self {
+            ListValue::Listpack(listpack) => listpack.pop_back(),
+            ListValue::Quicklist(elements) => elements.pop_back(),
+        }
+    }
+
+    pub fn encoding(&self) -> &'static str {
+        match self {
+            ListValue::Listpack(_) => "listpack",
+            ListValue::Quicklist(_) => "quicklist",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum HashValue {
+    Listpack(Listpack),
+    Hashtable(HashMap<Vec<u8>, Vec<u8>>),
+}
+
+impl Default for HashValue {
+    fn default() -> Self {
+        HashValue::Listpack(Listpack::default())
+    }
+}
+
+impl HashValue {
+    // RDB에서 읽은 해시, 한도 안이면 listpack으로 담음
+    pub fn from_pairs(pairs: HashMap<Vec<u8>, Vec<u8>>, limits: ListpackLimits) -> Self {
+        let longest = pairs.iter().map(|(field, value)| field.len().max(value.len())).max().unwrap_or(0);
+        if !limits.hash_fits(pairs.len(), longest) {
+            return HashValue::Hashtable(pairs);
+        }
+        let mut listpack = Listpack::default();
+        for (field, value) in &pairs {
+            listpack.set_pair(field, value);
+        }
+        HashValue::Listpack(listpack)
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            HashValue::Listpack(listpack) => listpack.pair_count(),
+            HashValue::Hashtable(hash) => hash.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn get(&self, field: &[u8]) -> Option<&[u8]> {
+        match self {
+            HashValue::Listpack(listpack) => listpack.get_pair(field),
+            HashValue::Hashtable(hash) => hash.get(field).map(|value| value.as_slice()),
+        }
+    }
+
+    pub fn iter(&self) -> Box<dyn Iterator<Item = (&[u8], &[u8])> + '_> {
+        match self {
+            HashValue::Listpack(listpack) => Box::new(listpack.pairs()),
+            HashValue::Hashtable(hash) => Box::new(hash.iter().map(|(field, value)| (field.as_slice(), value.as_slice()))),
+        }
+    }
+
+    // 새 필드면 true
+    pub fn insert(&mut self, field: Vec<u8>, value: Vec<u8>, limits: ListpackLimits) -> bool {
+        if let HashValue::Listpack(listpack) = self {
+            let entries = listpack.pair_count() + usize::from(listpack.get_pair(&field).is_none());
+            if !limits.hash_fits(entries, field.len().max(value.len())) {
+                let hash = listpack.pairs().map(|(field, value)| (field.to_vec(), value.to_vec())).collect();
+                *self = HashValue::Hashtable(hash);
+            }
+        }
+        match self {
+            HashValue::Listpack(listpack) => listpack.set_pair(&field, &value),
+            HashValue::Hashtable(hash) => hash.insert(field, value).is_none(),
+        }
+    }
+
+    pub fn remove(&mut self, field: &[u8]) -> bool {
+        match self {
+            HashValue::Listpack(listpack) => listpack.remove_pair(field),
+            HashValue::Hashtable(hash) => hash.remove(field).is_some(),
+        }
+    }
+
+    pub fn encoding(&self) -> &'static str {
+        match self {
+            HashValue::Listpack(_) => "listpack",
+            HashValue::Hashtable(_) => "hashtable",
+        }
+    }
+}
+
+// listpack에는 멤버 다음 원소에 점수를 f64 리틀 엔디언 8바이트로 둠
+// 큰 정렬 집합은 Redis에서 skiplist지만 여기서는 점수 맵으로 두고 필요할 때 정렬함
+#[derive(Clone, Debug)]
+pub enum ZSetValue {
+    Listpack(Listpack),
+    Skiplist(HashMap<Vec<u8>, f64>),
+}
+
+impl Default for ZSetValue {
+    fn default() -> Self {
+        ZSetValue::Listpack(Listpack::default())
+    }
+}
+
+fn decode_score(bytes: &[u8]) -> f64 {
+    f64::from_le_bytes(bytes.try_into().unwrap_or([0; 8]))
+}
+
+impl ZSetValue {
+    // RDB에서 읽은 정렬 집합, 한도 안이면 listpack으로 담음
+    pub fn from_scores(scores: HashMap<Vec<u8>, f64>, limits: ListpackLimits) -> Self {
+        let longest = scores.keys().map(|member| member.len()).max().unwrap_or(0);
+        if !limits.zset_fits(scores.len(), longest) {
+            return ZSetValue::Skiplist(scores);
+        }
+        let mut listpack = Listpack::default();
+        for (member, score) in &scores {
+            listpack.set_pair(member, &score.to_le_bytes());
+        }
+        ZSetValue::Listpack(listpack)
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            ZSetValue::Listpack(listpack) => listpack.pair_count(),
+            ZSetValue::Skiplist(scores) => scores.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn iter(&self) -> Box<dyn Iterator<Item = (&[u8], f64)> + '_> {
+        match self {
+            ZSetValue::Listpack(listpack) => Box::new(listpack.pairs().map(|(member, score)| (member, decode_score(score)))),
+            ZSetValue::Skiplist(scores) => Box::new(scores.iter().map(|(member, score)| (member.as_slice(), *score))),
+        }
+    }
+
+    // 새 멤버면 true
+    pub fn insert(&mut self, member: Vec<u8>, score: f64, limits: ListpackLimits) -> bool {
+        if let ZSetValue::Listpack(listpack) = self {
+            let entries = listpack.pair_count() + usize::from(listpack.get_pair(&member).is_none());
+            if !limits.zset_fits(entries, member.len()) {
+                let scores = listpack.pairs().map(|(member, score)| (member.to_vec(), decode_score(score))).collect();
+                *self = ZSetValue::Skiplist(scores);
+            }
+        }
+        match self {
+            ZSetValue::Listpack(listpack) => listpack.set_pair(&member, &score.to_le_bytes()),
+            ZSetValue::Skiplist(scores) => scores.insert(member, score).is_none(),
+        }
+    }
+
+    pub fn score(&self, member: &[u8]) -> Option<f64> {
+        match self {
+            ZSetValue::Listpack(listpack) => listpack.get_pair(member).map(decode_score),
+            ZSetValue::Skiplist(scores) => scores.get(member).copied(),
+        }
+    }
+
+    pub fn remove(&mut self, member: &[u8]) -> bool {
+        match self {
+            ZSetValue::Listpack(listpack) => listpack.remove_pair(member),
+            ZSetValue::Skiplist(scores) => scores.remove(member).is_some(),
+        }
+    }
+
+    pub fn encoding(&self) -> &'static str {
+        match self {
+            ZSetValue::Listpack(_) => "listpack",
+            ZSetValue::Skiplist(_) => "skiplist",
+        }
+    }
+}
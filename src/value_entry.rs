@@ -1,21 +1,348 @@
+use crate::errors::RedisError;
+use crate::random::Random;
+use crate::util::{current_time_ms, parse_bytes};
+use crate::value_encoding::{HashValue, ListValue, StringValue, ZSetValue};
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
+// 문자열, 해시, 리스트, 정렬 집합은 크기에 따라 작은 인코딩으로 담고 커지면 바꿈 (value_encoding)
+#[derive(Clone, Debug)]
+pub enum RedisValue {
+    String(StringValue),
+    List(ListValue),
+    Set(HashSet<Vec<u8>>),
+    Hash(HashValue),
+    ZSet(ZSetValue),
+}
+
+// 집합은 RDB에서만 만들어지므로 내용을 보고 인코딩을 보고함, Redis 기본값과 같은 기준
+const SET_LISTPACK_MAX_ENTRIES: usize = 128;
+const SET_LISTPACK_MAX_VALUE_LEN: usize = 64;
+const INTSET_MAX_ENTRIES: usize = 512;
+
+// 메모리 사용량 추정용: 키 하나당 dict 엔트리/객체 헤더, 컬렉션 원소 하나당 노드 크기
+pub const ENTRY_OVERHEAD_BYTES: usize = 64;
+// 키 하나가 키스페이스 색인에 더 쓰는 크기: 공유 Arc 헤더, KeySet의 Vec 칸과 위치 HashMap 칸, SCAN 순서 BTreeSet 칸
+pub const KEY_INDEX_OVERHEAD_BYTES: usize = 104;
+// TTL이 있는 키나 필드 TTL이 있는 해시가 만료 색인(KeySet)에 하나씩 더 쓰는 크기
+pub const VOLATILE_INDEX_OVERHEAD_BYTES: usize = 48;
+const ELEMENT_OVERHEAD_BYTES: usize = 16;
+
+const LFU_INIT_VAL: u8 = 5;
+pub const DEFAULT_LFU_LOG_FACTOR: u64 = 10;
+pub const DEFAULT_LFU_DECAY_MINUTES: u64 = 1;
+
+// Redis의 lfu-log-factor / lfu-decay-time, decay_minutes가 0이면 감소하지 않음
+// 읽기 잠금 아래에서 touch가 불리므로 키스페이스가 설정 맵 대신 이 값을 들고 있음
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LfuConfig {
+    pub log_factor: u64,
+    pub decay_minutes: u64,
+}
+
+impl Default for LfuConfig {
+    fn default() -> Self {
+        Self {
+            log_factor: DEFAULT_LFU_LOG_FACTOR,
+            decay_minutes: DEFAULT_LFU_DECAY_MINUTES,
+        }
+    }
+}
+
+impl RedisValue {
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            RedisValue::String(_) => "string",
+            RedisValue::List(_) => "list",
+            RedisValue::Set(_) => "set",
+            RedisValue::Hash(_) => "hash",
+            RedisValue::ZSet(_) => "zset",
+        }
+    }
+
+    // MEMORY USAGE처럼 앞의 원소 samples개의 평균 크기로 컬렉션 전체를 추정함, samples가 0이면 전부 셈
+    pub fn sampled_size(&self, samples: usize) -> usize {
+        let estimate = |len: usize, sizes: Box<dyn Iterator<Item = usize> + '_>| {
+            let taken = if samples == 0 { len } else { samples.min(len) };
+            if taken == 0 {
+                return 0;
+            }
+            sizes.take(taken).sum::<usize>() * len / taken
+        };
+        // int는 객체 안의 정수, embstr은 객체와 한 번에 할당되므로 따로 셀 할당이 없음
+        // listpack은 원소를 하나의 버퍼에 담으므로 버퍼 크기가 곧 사용량
+        match self {
+            RedisValue::String(StringValue::Int(_)) => 0,
+            RedisValue::String(StringValue::Embedded { len, .. }) => *len as usize,
+            RedisValue::String(StringValue::Raw(value)) => value.len() + ELEMENT_OVERHEAD_BYTES,
+            RedisValue::List(ListValue::Listpack(listpack))
+            | RedisValue::Hash(HashValue::Listpack(listpack))
+            | RedisValue::ZSet(ZSetValue::Listpack(listpack)) => listpack.bytes(),
+            RedisValue::List(list) => estimate(list.len(), Box::new(list.iter().map(|value| value.len() + ELEMENT_OVERHEAD_BYTES))),
+            RedisValue::Set(set) => estimate(set.len(), Box::new(set.iter().map(|member| member.len() + ELEMENT_OVERHEAD_BYTES))),
+            RedisValue::Hash(hash) => estimate(
+                hash.len(),
+                Box::new(hash.iter().map(|(field, value)| field.len() + value.len() + ELEMENT_OVERHEAD_BYTES)),
+            ),
+            RedisValue::ZSet(zset) => estimate(
+                zset.len(),
+                Box::new(zset.iter().map(|(member, _)| member.len() + std::mem::size_of::<f64>() + ELEMENT_OVERHEAD_BYTES)),
+            ),
+        }
+    }
+
+    pub fn encoding(&self) -> &'static str {
+        match self {
+            RedisValue::String(value) => value.encoding(),
+            RedisValue::List(list) => list.encoding(),
+            RedisValue::Set(set) if set.len() <= INTSET_MAX_ENTRIES && set.iter().all(|member| parse_bytes::<i64>(member).is_some()) => "intset",
+            RedisValue::Set(set)
+                if set.len() <= SET_LISTPACK_MAX_ENTRIES && set.iter().all(|member| member.len() <= SET_LISTPACK_MAX_VALUE_LEN) =>
+            {
+                "listpack"
+            }
+            RedisValue::Set(_) => "hashtable",
+            RedisValue::Hash(hash) => hash.encoding(),
+            RedisValue::ZSet(zset) => zset.encoding(),
+        }
+    }
+}
+
+// 해시 필드 이름별 만료 시각(ms), Redis 7.4의 HEXPIRE 계열
+pub type FieldExpirations = HashMap<Vec<u8>, u64>;
 
-#[derive(Clone)]
 pub struct ValueEntry {
-    pub(crate) value: String,
+    pub(crate) value: RedisValue,
     expiration: Option<SystemTime>,
+    // 읽기 잠금만 잡은 상태에서도 갱신할 수 있도록 atomic으로 둠
+    last_access_ms: AtomicU64,
+    lfu_counter: AtomicU8,
+    field_expirations: FieldExpirations,
+    // 키스페이스 메모리 합계에 반영된 이 엔트리의 크기, Keyspace만 갱신함
+    accounted_size: usize,
+}
+
+impl Clone for ValueEntry {
+    fn clone(&self) -> Self {
+        Self {
+            value: self.value.clone(),
+            expiration: self.expiration,
+            last_access_ms: AtomicU64::new(self.last_access_ms.load(Ordering::Relaxed)),
+            lfu_counter: AtomicU8::new(self.lfu_counter.load(Ordering::Relaxed)),
+            field_expirations: self.field_expirations.clone(),
+            accounted_size: self.accounted_size,
+        }
+    }
 }
 
 impl ValueEntry {
-    pub fn new_absolute(value: String, expiration_ms: Option<u64>) -> ValueEntry {
+    pub fn new_absolute(value: RedisValue, expiration_ms: Option<u64>) -> ValueEntry {
         let expiration = expiration_ms.map(|ms| UNIX_EPOCH + Duration::from_millis(ms));
-        ValueEntry { value, expiration }
+        Self::with_expiration(value, expiration)
     }
 
-    pub fn new_relative(value: String, duration_ms: Option<u64>) -> ValueEntry {
+    pub fn new_relative(value: RedisValue, duration_ms: Option<u64>) -> ValueEntry {
         let expiration = duration_ms.map(|ms| SystemTime::now() + Duration::from_millis(ms));
-        ValueEntry { value, expiration }
+        Self::with_expiration(value, expiration)
+    }
+
+    fn with_expiration(value: RedisValue, expiration: Option<SystemTime>) -> ValueEntry {
+        ValueEntry {
+            value,
+            expiration,
+            last_access_ms: AtomicU64::new(current_time_ms()),
+            lfu_counter: AtomicU8::new(LFU_INIT_VAL),
+            field_expirations: HashMap::new(),
+            accounted_size: 0,
+        }
+    }
+
+    // 키스페이스의 LFU 설정과 난수 생성기로 접근을 기록함, 보통은 Keyspace::touch나 EntryMut::touch로 부름
+    pub fn touch(&self, lfu: LfuConfig, random: &Random) {
+        let counter = self.lfu_frequency(lfu);
+        let base = counter.saturating_sub(LFU_INIT_VAL) as f64;
+        let log_factor = lfu.log_factor as f64;
+        let increment = counter < u8::MAX && random.next_f64() < 1.0 / (base * log_factor + 1.0);
+        self.lfu_counter.store(if increment { counter + 1 } else { counter }, Ordering::Relaxed);
+        self.last_access_ms.store(current_time_ms(), Ordering::Relaxed);
+    }
+
+    pub fn set_idle_ms(&self, idle_ms: u64) {
+        self.last_access_ms.store(current_time_ms().saturating_sub(idle_ms), Ordering::Relaxed);
+    }
+
+    pub fn set_lfu_frequency(&self, frequency: u8) {
+        self.lfu_counter.store(frequency, Ordering::Relaxed);
+        self.last_access_ms.store(current_time_ms(), Ordering::Relaxed);
+    }
+
+    // Redis의 LFU처럼 카운터는 로그 스케일로 증가하고, 접근이 없던 시간만큼 감소함
+    pub fn lfu_frequency(&self, lfu: LfuConfig) -> u8 {
+        let decay = match lfu.decay_minutes {
+            0 => 0,
+            decay_minutes => self.idle_ms() / 60_000 / decay_minutes,
+        };
+        let counter = self.lfu_counter.load(Ordering::Relaxed);
+        counter.saturating_sub(decay.min(u8::MAX as u64) as u8)
+    }
+
+    pub fn last_access_ms(&self) -> u64 {
+        self.last_access_ms.load(Ordering::Relaxed)
+    }
+
+    pub fn idle_ms(&self) -> u64 {
+        current_time_ms().saturating_sub(self.last_access_ms())
+    }
+
+    pub fn sampled_size(&self, key: &[u8], samples: usize) -> usize {
+        self.key_overhead(key.len()) + self.value.sampled_size(samples)
+    }
+
+    // 값을 뺀 나머지: 엔트리 헤더, 키 이름, 이 키가 들어 있는 색인들
+    pub fn key_overhead(&self, key_len: usize) -> usize {
+        let volatile_indexes = self.expiration_ms().is_some() as usize + self.has_field_expirations() as usize;
+        ENTRY_OVERHEAD_BYTES + KEY_INDEX_OVERHEAD_BYTES + key_len + volatile_indexes * VOLATILE_INDEX_OVERHEAD_BYTES
+    }
+
+    pub fn accounted_size(&self) -> usize {
+        self.accounted_size
+    }
+
+    // 이전 값을 돌려줌
+    pub(crate) fn set_accounted_size(&mut self, size: usize) -> usize {
+        std::mem::replace(&mut self.accounted_size, size)
+    }
+
+    pub fn expect_string(&self) -> Result<Cow<'_, [u8]>, RedisError> {
+        match &self.value {
+            RedisValue::String(value) => Ok(value.as_bytes()),
+            _ => Err(RedisError::WrongType),
+        }
+    }
+
+    pub fn expect_hash(&self) -> Result<&HashValue, RedisError> {
+        match &self.value {
+            RedisValue::Hash(hash) => Ok(hash),
+            _ => Err(RedisError::WrongType),
+        }
+    }
+
+    pub fn expect_hash_mut(&mut self) -> Result<&mut HashValue, RedisError> {
+        match &mut self.value {
+            RedisValue::Hash(hash) => Ok(hash),
+            _ => Err(RedisError::WrongType),
+        }
+    }
+
+    pub fn expect_list(&self) -> Result<&ListValue, RedisError> {
+        match &self.value {
+            RedisValue::List(list) => Ok(list),
+            _ => Err(RedisError::WrongType),
+        }
+    }
+
+    pub fn expect_list_mut(&mut self) -> Result<&mut ListValue, RedisError> {
+        match &mut self.value {
+            RedisValue::List(list) => Ok(list),
+            _ => Err(RedisError::WrongType),
+        }
+    }
+
+    pub fn expect_zset(&self) -> Result<&ZSetValue, RedisError> {
+        match &self.value {
+            RedisValue::ZSet(zset) => Ok(zset),
+            _ => Err(RedisError::WrongType),
+        }
+    }
+
+    pub fn expect_zset_mut(&mut self) -> Result<&mut ZSetValue, RedisError> {
+        match &mut self.value {
+            RedisValue::ZSet(zset) => Ok(zset),
+            _ => Err(RedisError::WrongType),
+        }
+    }
+
+    // 만료된 필드는 지워지기 전까지 없는 필드처럼 보여야 함
+    pub fn hash_field(&self, field: &[u8]) -> Option<&[u8]> {
+        let RedisValue::Hash(hash) = &self.value else {
+            return None;
+        };
+        hash.get(field).filter(|_| !self.is_field_expired(field))
+    }
+
+    pub fn field_expiration_ms(&self, field: &[u8]) -> Option<u64> {
+        self.field_expirations.get(field).copied()
+    }
+
+    pub fn set_field_expiration_ms(&mut self, field: &[u8], expiration_ms: Option<u64>) {
+        match expiration_ms {
+            Some(ms) => self.field_expirations.insert(field.to_vec(), ms),
+            None => self.field_expirations.remove(field),
+        };
+    }
+
+    pub fn is_field_expired(&self, field: &[u8]) -> bool {
+        self.field_expiration_ms(field).is_some_and(|ms| ms <= current_time_ms())
+    }
+
+    pub fn field_expirations(&self) -> &FieldExpirations {
+        &self.field_expirations
+    }
+
+    // RDB나 DUMP payload에서 읽은 필드 TTL을 한 번에 붙임
+    pub fn set_field_expirations(&mut self, field_expirations: FieldExpirations) {
+        self.field_expirations = field_expirations;
+    }
+
+    pub fn has_field_expirations(&self) -> bool {
+        !self.field_expirations.is_empty()
+    }
+
+    // 필드를 지우면서 필드 TTL도 함께 정리함
+    pub fn remove_hash_field(&mut self, field: &[u8]) -> bool {
+        self.field_expirations.remove(field);
+        match &mut self.value {
+            RedisValue::Hash(hash) => hash.remove(field),
+            _ => false,
+        }
+    }
+
+    pub fn remove_expired_fields(&mut self) -> Vec<Vec<u8>> {
+        let now = current_time_ms();
+        let expired: Vec<Vec<u8>> = self
+            .field_expirations
+            .iter()
+            .filter(|(_, ms)| **ms <= now)
+            .map(|(field, _)| field.clone())
+            .collect();
+        for field in &expired {
+            self.remove_hash_field(field);
+        }
+        expired
+    }
+
+    pub fn expiration_ms(&self) -> Option<u64> {
+        self.expiration.map(|expiration| {
+            expiration
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_millis() as u64)
+                .unwrap_or(0)
+        })
+    }
+
+    pub fn set_expiration_ms(&mut self, expiration_ms: Option<u64>) {
+        self.expiration = expiration_ms.map(|ms| UNIX_EPOCH + Duration::from_millis(ms));
+    }
+
+    pub fn remaining_ms(&self) -> Option<u64> {
+        self.expiration.map(|expiration| {
+            expiration
+                .duration_since(SystemTime::now())
+                .map(|duration| duration.as_millis() as u64)
+                .unwrap_or(0)
+        })
     }
 
     pub fn is_expired(&self) -> bool {
@@ -0,0 +1,50 @@
+use std::collections::{HashMap, HashSet};
+
+// WATCH한 키 -> 그 키를 지켜보는 클라이언트 목록, 지켜보는 키가 바뀐 클라이언트는 EXEC에서 트랜잭션을 버림
+pub struct WatchTable {
+    watchers: HashMap<Vec<u8>, HashSet<u64>>,
+    dirty: HashSet<u64>,
+}
+
+impl WatchTable {
+    pub fn new() -> Self {
+        Self {
+            watchers: HashMap::new(),
+            dirty: HashSet::new(),
+        }
+    }
+
+    pub fn watch(&mut self, client_id: u64, keys: &[Vec<u8>]) {
+        for key in keys {
+            self.watchers.entry(key.clone()).or_default().insert(client_id);
+        }
+    }
+
+    // 한 번 바뀐 것으로 표시한 클라이언트는 UNWATCH/EXEC/DISCARD까지 그대로 둠
+    pub fn touch(&mut self, keys: &[&Vec<u8>]) {
+        for key in keys {
+            if let Some(watchers) = self.watchers.get(*key) {
+                self.dirty.extend(watchers);
+            }
+        }
+    }
+
+    // FLUSHDB/FLUSHALL은 지켜보는 키를 모두 바꾼 것으로 봄
+    pub fn touch_all(&mut self) {
+        for watchers in self.watchers.values() {
+            self.dirty.extend(watchers);
+        }
+    }
+
+    pub fn is_dirty(&self, client_id: u64) -> bool {
+        self.dirty.contains(&client_id)
+    }
+
+    pub fn unwatch(&mut self, client_id: u64) {
+        self.watchers.retain(|_, watchers| {
+            watchers.remove(&client_id);
+            !watchers.is_empty()
+        });
+        self.dirty.remove(&client_id);
+    }
+}
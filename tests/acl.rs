@@ -0,0 +1,173 @@
+use redis_starter_rust::test_support::{bulk, error, ok, TestServer};
+use redis_starter_rust::{Client, RespValue};
+
+const WRONGPASS_ERROR: &str = "WRONGPASS invalid username-password pair or user is disabled.";
+
+// cache:로 시작하는 키에 GET/SET만 할 수 있는 사용자
+async fn create_cache_user(client: &mut Client) {
+    assert_eq!(client.command(&["ACL", "SETUSER", "alice", "on", ">secret", "~cache:*", "+get", "+set"]).await.unwrap(), ok());
+}
+
+#[tokio::test]
+async fn acl_rejects_commands_the_user_may_not_run() {
+    let server = TestServer::start().await.unwrap();
+    let mut admin = server.client().await.unwrap();
+    create_cache_user(&mut admin).await;
+    admin.command(&["SET", "cache:1", "cached"]).await.unwrap();
+
+    let mut client = server.client().await.unwrap();
+    assert_eq!(client.command(&["AUTH", "alice", "secret"]).await.unwrap(), ok());
+    assert_eq!(client.command(&["GET", "cache:1"]).await.unwrap(), RespValue::BulkString(b"cached".to_vec()));
+    assert_eq!(
+        client.command(&["DEL", "cache:1"]).await.unwrap(),
+        error("NOPERM User alice has no permissions to run the 'del' command")
+    );
+    assert_eq!(
+        client.command(&["CONFIG", "SET", "maxmemory", "1"]).await.unwrap(),
+        error("NOPERM User alice has no permissions to run the 'config' command")
+    );
+    // 거절된 명령은 실행되지 않음
+    assert_eq!(admin.command(&["EXISTS", "cache:1"]).await.unwrap(), RespValue::Integer(1));
+    assert_eq!(
+        admin.command(&["CONFIG", "GET", "maxmemory"]).await.unwrap(),
+        RespValue::Array(vec![RespValue::BulkString(b"maxmemory".to_vec()), RespValue::BulkString(b"0".to_vec())])
+    );
+
+    // 규칙을 바꾸면 이미 인증한 연결에도 바로 적용됨
+    assert_eq!(admin.command(&["ACL", "SETUSER", "alice", "+del"]).await.unwrap(), ok());
+    assert_eq!(client.command(&["DEL", "cache:1"]).await.unwrap(), RespValue::Integer(1));
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn acl_rejects_keys_outside_the_users_patterns() {
+    let server = TestServer::start().await.unwrap();
+    let mut admin = server.client().await.unwrap();
+    create_cache_user(&mut admin).await;
+
+    let mut client = server.client().await.unwrap();
+    assert_eq!(client.command(&["AUTH", "alice", "secret"]).await.unwrap(), ok());
+    assert_eq!(client.command(&["SET", "cache:user", "1"]).await.unwrap(), ok());
+    assert_eq!(client.command(&["SET", "session:user", "1"]).await.unwrap(), error("NOPERM No permissions to access a key"));
+    assert_eq!(client.command(&["GET", "session:user"]).await.unwrap(), error("NOPERM No permissions to access a key"));
+    assert_eq!(admin.command(&["EXISTS", "session:user"]).await.unwrap(), RespValue::Integer(0));
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn auth_rejects_wrong_passwords_and_disabled_users() {
+    let server = TestServer::start_with(|builder| builder.option("requirepass", "adminpass")).await.unwrap();
+    let mut client = server.client().await.unwrap();
+    assert_eq!(client.command(&["GET", "key"]).await.unwrap(), error("NOAUTH Authentication required."));
+    assert_eq!(client.command(&["AUTH", "wrongpass"]).await.unwrap(), error(WRONGPASS_ERROR));
+    // 실패한 AUTH는 연결을 인증하지 않음
+    assert_eq!(client.command(&["GET", "key"]).await.unwrap(), error("NOAUTH Authentication required."));
+    assert_eq!(client.command(&["AUTH", "adminpass"]).await.unwrap(), ok());
+    assert_eq!(client.command(&["GET", "key"]).await.unwrap(), RespValue::NullBulk);
+
+    create_cache_user(&mut client).await;
+    let mut other = server.client().await.unwrap();
+    assert_eq!(other.command(&["AUTH", "alice", "wrong"]).await.unwrap(), error(WRONGPASS_ERROR));
+    assert_eq!(other.command(&["AUTH", "nobody", "secret"]).await.unwrap(), error(WRONGPASS_ERROR));
+    assert_eq!(client.command(&["ACL", "SETUSER", "alice", "off"]).await.unwrap(), ok());
+    assert_eq!(other.command(&["AUTH", "alice", "secret"]).await.unwrap(), error(WRONGPASS_ERROR));
+    assert_eq!(other.command(&["GET", "cache:1"]).await.unwrap(), error("NOAUTH Authentication required."));
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn reset_returns_to_the_default_user() {
+    let server = TestServer::start_with(|builder| builder.option("requirepass", "adminpass")).await.unwrap();
+    let mut client = server.client().await.unwrap();
+    assert_eq!(client.command(&["AUTH", "adminpass"]).await.unwrap(), ok());
+    assert_eq!(client.command(&["RESET"]).await.unwrap(), RespValue::SimpleString("RESET".into()));
+    // default 사용자에게 비밀번호가 있으므로 다시 인증해야 함
+    assert_eq!(client.command(&["GET", "key"]).await.unwrap(), error("NOAUTH Authentication required."));
+
+    server.shutdown().await.unwrap();
+}
+
+// team-a: 아래로 갇힌 사용자, 키 패턴은 접두사를 떼고 본 이름으로 검사함
+async fn create_tenant_user(client: &mut Client, quotas: &[&str]) {
+    let mut args = vec!["ACL", "SETUSER", "tenant", "on", ">secret", "~*", "allcommands", "namespace:team-a:"];
+    args.extend_from_slice(quotas);
+    assert_eq!(client.command(&args).await.unwrap(), ok());
+}
+
+#[tokio::test]
+async fn namespaced_users_see_only_their_own_keys() {
+    let server = TestServer::start().await.unwrap();
+    let mut admin = server.client().await.unwrap();
+    create_tenant_user(&mut admin, &[]).await;
+    admin.command(&["SET", "shared", "admin"]).await.unwrap();
+
+    let mut tenant = server.client().await.unwrap();
+    assert_eq!(tenant.command(&["AUTH", "tenant", "secret"]).await.unwrap(), ok());
+    assert_eq!(tenant.command(&["SET", "shared", "tenant"]).await.unwrap(), ok());
+    assert_eq!(tenant.command(&["GET", "shared"]).await.unwrap(), bulk("tenant"));
+    assert_eq!(admin.command(&["GET", "shared"]).await.unwrap(), bulk("admin"));
+    assert_eq!(admin.command(&["GET", "team-a:shared"]).await.unwrap(), bulk("tenant"));
+
+    assert_eq!(tenant.command(&["KEYS", "*"]).await.unwrap(), RespValue::Array(vec![bulk("shared")]));
+    assert_eq!(
+        tenant.command(&["SCAN", "0", "COUNT", "100"]).await.unwrap(),
+        RespValue::Array(vec![bulk("0"), RespValue::Array(vec![bulk("shared")])])
+    );
+    assert_eq!(tenant.command(&["DBSIZE"]).await.unwrap(), RespValue::Integer(1));
+    assert_eq!(
+        tenant.command(&["FLUSHALL"]).await.unwrap(),
+        error("NOPERM The 'flushall' command can't be used inside a key namespace")
+    );
+    // 스크립트 안의 redis.call도 같은 접두사 아래로 감
+    assert_eq!(tenant.command(&["EVAL", "return redis.call('incr', KEYS[1])", "1", "counter"]).await.unwrap(), RespValue::Integer(1));
+    assert_eq!(admin.command(&["GET", "team-a:counter"]).await.unwrap(), bulk("1"));
+
+    let RespValue::Array(user) = admin.command(&["ACL", "GETUSER", "tenant"]).await.unwrap() else {
+        panic!("ACL GETUSER did not return an array");
+    };
+    assert!(user.windows(2).any(|pair| pair[0] == bulk("namespace") && pair[1] == bulk("namespace:team-a:")));
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn namespace_quotas_reject_writes_that_would_grow_the_namespace() {
+    let server = TestServer::start().await.unwrap();
+    let mut admin = server.client().await.unwrap();
+    // 설정 전부터 있던 키도 할당량에 셈
+    admin.command(&["SET", "team-a:existing", "1"]).await.unwrap();
+    create_tenant_user(&mut admin, &["maxkeys:2"]).await;
+
+    let mut tenant = server.client().await.unwrap();
+    assert_eq!(tenant.command(&["AUTH", "tenant", "secret"]).await.unwrap(), ok());
+    assert_eq!(tenant.command(&["SET", "a", "1"]).await.unwrap(), ok());
+    assert_eq!(
+        tenant.command(&["SET", "b", "1"]).await.unwrap(),
+        error("OOM command not allowed when the key namespace is over its 'maxkeys' quota.")
+    );
+    // 이미 있는 키를 고치거나 지우는 것은 됨
+    assert_eq!(tenant.command(&["SET", "a", "2"]).await.unwrap(), ok());
+    assert_eq!(tenant.command(&["DEL", "existing"]).await.unwrap(), RespValue::Integer(1));
+    assert_eq!(tenant.command(&["SET", "b", "1"]).await.unwrap(), ok());
+    // 다른 사용자의 키는 세지 않음
+    assert_eq!(admin.command(&["SET", "other", "1"]).await.unwrap(), ok());
+
+    assert_eq!(admin.command(&["ACL", "SETUSER", "tenant", "maxkeys:100", "maxmemory:1"]).await.unwrap(), ok());
+    assert_eq!(
+        tenant.command(&["SET", "c", "1"]).await.unwrap(),
+        error("OOM command not allowed when the key namespace is over its 'maxmemory' quota.")
+    );
+    assert_eq!(tenant.command(&["DEL", "a", "b"]).await.unwrap(), RespValue::Integer(2));
+    assert_eq!(tenant.command(&["SET", "c", "1"]).await.unwrap(), ok());
+
+    // 네임스페이스 없이 할당량만 줄 수는 없음
+    assert!(matches!(
+        admin.command(&["ACL", "SETUSER", "plain", "maxkeys:1"]).await.unwrap(),
+        RespValue::Error(message) if message.contains("Set a namespace before its maxkeys quota")
+    ));
+
+    server.shutdown().await.unwrap();
+}
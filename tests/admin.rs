@@ -0,0 +1,66 @@
+use redis_starter_rust::test_support::TestServer;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+// 관리 리스너는 OS가 고른 포트를 알려 주지 않으므로 비어 있는 포트를 골라 넘김
+fn free_port() -> u16 {
+    std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port()
+}
+
+async fn start_with_admin() -> (TestServer, u16) {
+    let admin_port = free_port();
+    let server = TestServer::start_with(|builder| builder.option("admin-port", admin_port.to_string())).await.unwrap();
+    (server, admin_port)
+}
+
+// GET 요청을 보내고 (상태 코드, 본문)을 돌려줌, 응답은 Connection: close라 끝까지 읽음
+async fn get(admin_port: u16, path: &str) -> (u16, String) {
+    let mut stream = TcpStream::connect(("127.0.0.1", admin_port)).await.unwrap();
+    stream.write_all(format!("GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path).as_bytes()).await.unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+    let (head, body) = response.split_once("\r\n\r\n").expect("no header terminator");
+    let status = head.split(' ').nth(1).unwrap().parse().unwrap();
+    (status, body.to_string())
+}
+
+#[tokio::test]
+async fn metrics_report_network_totals_and_size_histograms() {
+    let (server, admin_port) = start_with_admin().await;
+    let mut client = server.client().await.unwrap();
+    client.command(&["SET", "key", "value"]).await.unwrap();
+    client.command(&["GET", "key"]).await.unwrap();
+
+    let (status, body) = get(admin_port, "/metrics").await;
+    assert_eq!(status, 200);
+    for field in ["\"total_net_input_bytes\":", "\"total_net_output_bytes\":", "\"total_net_repl_output_bytes\":0"] {
+        assert!(body.contains(field), "{} missing from {}", field, body);
+    }
+    assert!(!body.contains("\"total_net_input_bytes\":0,"), "{}", body);
+    // SET key value는 33바이트 요청, GET의 응답 $5\r\nvalue\r\n은 11바이트
+    assert!(body.contains("\"set\":{\"count\":1,\"sum\":33,\"buckets\":{\"16\":0,\"64\":1,"), "{}", body);
+    assert!(body.contains("\"get\":{\"count\":1,\"sum\":11,\"buckets\":{\"16\":1,"), "{}", body);
+    assert!(body.contains("\"inf\":0}"), "{}", body);
+
+    assert_eq!(get(admin_port, "/nothing").await.0, 404);
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn config_view_reports_each_parameter_type_and_mutability() {
+    let admin_port = free_port();
+    let server = TestServer::start_with(|builder| builder.option("admin-port", admin_port.to_string()).option("maxmemory", "1000000").option("trace", "no"))
+        .await
+        .unwrap();
+
+    let (status, body) = get(admin_port, "/config").await;
+    assert_eq!(status, 200);
+    // port는 시작할 때만 정할 수 있고, maxmemory와 trace는 CONFIG SET으로 바꿀 수 있음
+    let port = format!("\"port\":{{\"type\":\"integer\",\"mutable\":false,\"value\":{}}}", server.port());
+    assert!(body.contains(&port), "{} missing from {}", port, body);
+    assert!(body.contains("\"maxmemory\":{\"type\":\"string\",\"mutable\":true,\"value\":\"1000000\"}"), "{}", body);
+    assert!(body.contains("\"trace\":{\"type\":\"bool\",\"mutable\":true,\"value\":false}"), "{}", body);
+
+    server.shutdown().await.unwrap();
+}
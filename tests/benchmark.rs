@@ -0,0 +1,40 @@
+use redis_starter_rust::benchmark::{self, BenchmarkOptions};
+use redis_starter_rust::test_support::TestServer;
+
+#[tokio::test]
+async fn benchmark_runs_every_request() {
+    let server = TestServer::start().await.unwrap();
+    let options = BenchmarkOptions::parse(&["-p", &server.port().to_string(), "-c", "4", "-n", "203", "-P", "5", "-r", "10", "-t", "set,get"].map(String::from)).unwrap();
+
+    let reports = benchmark::run(&options).await.unwrap();
+    assert_eq!(reports.iter().map(|report| report.test.as_str()).collect::<Vec<_>>(), ["set", "get"]);
+    for report in &reports {
+        assert_eq!(report.requests, 203);
+        assert_eq!(report.errors, 0);
+        assert!(report.percentile_ms(50.0) <= report.percentile_ms(99.0));
+    }
+
+    server.shutdown().await.unwrap();
+}
+
+// -t 없이 돌리면 기본 테스트를 모두 돌리고, 서버가 모든 명령을 받아들여야 함
+#[tokio::test]
+async fn default_tests_run_without_errors() {
+    let server = TestServer::start().await.unwrap();
+    let options = BenchmarkOptions::parse(&["-p", &server.port().to_string(), "-c", "2", "-n", "100", "-r", "10"].map(String::from)).unwrap();
+
+    let reports = benchmark::run(&options).await.unwrap();
+    assert_eq!(reports.iter().map(|report| report.test.as_str()).collect::<Vec<_>>(), ["set", "get", "incr"]);
+    for report in &reports {
+        assert_eq!(report.requests, 100);
+        assert_eq!(report.errors, 0, "{} had errors", report.test);
+    }
+
+    server.shutdown().await.unwrap();
+}
+
+#[test]
+fn rejects_unknown_tests() {
+    assert!(BenchmarkOptions::parse(&["-t", "set,lpush"].map(String::from)).is_err());
+    assert!(BenchmarkOptions::parse(&["-P", "0"].map(String::from)).is_err());
+}
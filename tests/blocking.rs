@@ -0,0 +1,102 @@
+use redis_starter_rust::test_support::{bulk, TestServer};
+use redis_starter_rust::{Client, RespValue};
+use std::time::Duration;
+
+const REPLY_TIMEOUT: Duration = Duration::from_secs(5);
+
+fn bulks(values: &[&str]) -> RespValue {
+    RespValue::Array(values.iter().map(|value| bulk(value)).collect())
+}
+
+// 명령이 이벤트 루프에 들어가 대기 상태가 될 때까지 기다림
+async fn block(client: &mut Client, args: &[&str]) {
+    client.send_command(args).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+}
+
+async fn reply(client: &mut Client) -> RespValue {
+    tokio::time::timeout(REPLY_TIMEOUT, client.read_reply()).await.unwrap().unwrap()
+}
+
+// 먼저 기다리기 시작한 클라이언트가 먼저 받음
+#[tokio::test]
+async fn blpop_serves_blocked_clients_in_arrival_order() {
+    let server = TestServer::start().await.unwrap();
+    let mut first = server.client().await.unwrap();
+    let mut second = server.client().await.unwrap();
+    let mut client = server.client().await.unwrap();
+
+    block(&mut first, &["BLPOP", "queue", "0"]).await;
+    block(&mut second, &["BRPOP", "other", "queue", "0"]).await;
+    assert_eq!(client.command(&["RPUSH", "queue", "a"]).await.unwrap(), RespValue::Integer(1));
+    assert_eq!(reply(&mut first).await, bulks(&["queue", "a"]));
+    assert_eq!(client.command(&["RPUSH", "queue", "b", "c"]).await.unwrap(), RespValue::Integer(2));
+    assert_eq!(reply(&mut second).await, bulks(&["queue", "c"]));
+    assert_eq!(client.command(&["LPOP", "queue", "10"]).await.unwrap(), bulks(&["b"]));
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn blpop_times_out_and_rejects_bad_timeouts() {
+    let server = TestServer::start().await.unwrap();
+    let mut client = server.client().await.unwrap();
+
+    assert_eq!(client.command(&["BLPOP", "missing", "0.05"]).await.unwrap(), RespValue::NullArray);
+    assert_eq!(client.command(&["BLPOP", "missing", "-1"]).await.unwrap(), RespValue::Error("ERR timeout is negative".into()));
+    assert_eq!(client.command(&["BLPOP", "missing", "abc"]).await.unwrap(), RespValue::Error("ERR timeout is not a float or out of range".into()));
+    // 밀리초로 바꾸면 long long을 넘는 값은 마감 시각을 계산하지 않고 거절함
+    for command in [
+        &["BLPOP", "missing", "1e300"][..],
+        &["BLMOVE", "missing", "dst", "LEFT", "RIGHT", "1e300"],
+        &["BLMPOP", "1e300", "1", "missing", "LEFT"],
+        &["BZMPOP", "1e300", "1", "missing", "MIN"],
+    ] {
+        assert_eq!(client.command(command).await.unwrap(), RespValue::Error("ERR timeout is out of range".into()), "{:?}", command);
+    }
+    assert_eq!(client.command(&["PING"]).await.unwrap(), RespValue::SimpleString("PONG".into()));
+
+    server.shutdown().await.unwrap();
+}
+
+// 원본과 대상이 같으면 리스트를 돌림
+#[tokio::test]
+async fn blmove_rotates_a_list_onto_itself() {
+    let server = TestServer::start().await.unwrap();
+    let mut blocked = server.client().await.unwrap();
+    let mut client = server.client().await.unwrap();
+
+    client.command(&["RPUSH", "ring", "a", "b", "c"]).await.unwrap();
+    assert_eq!(client.command(&["BLMOVE", "ring", "ring", "LEFT", "RIGHT", "0"]).await.unwrap(), bulk("a"));
+    assert_eq!(client.command(&["LPOP", "ring", "10"]).await.unwrap(), bulks(&["b", "c", "a"]));
+
+    // 기다리던 BLMOVE도 값이 들어오면 같은 키에 다시 넣음
+    block(&mut blocked, &["BLMOVE", "empty", "empty", "RIGHT", "LEFT", "0"]).await;
+    client.command(&["RPUSH", "empty", "x", "y"]).await.unwrap();
+    assert_eq!(reply(&mut blocked).await, bulk("y"));
+    assert_eq!(client.command(&["LPOP", "empty", "10"]).await.unwrap(), bulks(&["y", "x"]));
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn blmpop_and_bzmpop_wake_on_push() {
+    let server = TestServer::start().await.unwrap();
+    let mut lists = server.client().await.unwrap();
+    let mut zsets = server.client().await.unwrap();
+    let mut client = server.client().await.unwrap();
+
+    block(&mut lists, &["BLMPOP", "0", "2", "first", "second", "LEFT", "COUNT", "2"]).await;
+    client.command(&["RPUSH", "second", "a", "b", "c"]).await.unwrap();
+    assert_eq!(reply(&mut lists).await, RespValue::Array(vec![bulk("second"), bulks(&["a", "b"])]));
+
+    block(&mut zsets, &["BZMPOP", "0", "1", "scores", "MAX"]).await;
+    client.command(&["ZADD", "scores", "1", "low", "2", "high"]).await.unwrap();
+    assert_eq!(
+        reply(&mut zsets).await,
+        RespValue::Array(vec![bulk("scores"), RespValue::Array(vec![RespValue::Array(vec![bulk("high"), bulk("2")])])])
+    );
+    assert_eq!(client.command(&["BLMPOP", "0.05", "1", "missing", "RIGHT"]).await.unwrap(), RespValue::NullArray);
+
+    server.shutdown().await.unwrap();
+}
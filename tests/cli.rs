@@ -0,0 +1,52 @@
+use redis_starter_rust::cli::{format_reply, CliOptions};
+use redis_starter_rust::test_support::{bulk, TestServer};
+use redis_starter_rust::{PipeSummary, RespValue};
+
+#[test]
+fn formats_like_redis_cli() {
+    assert_eq!(format_reply(&bulk("a \"b\"\n\x01")), "\"a \\\"b\\\"\\n\\x01\"\n");
+    assert_eq!(format_reply(&RespValue::NullBulk), "(nil)\n");
+    assert_eq!(format_reply(&RespValue::Array(vec![])), "(empty array)\n");
+
+    let nested = RespValue::Array(vec![
+        bulk("a"),
+        RespValue::Array(vec![bulk("x"), RespValue::Integer(2)]),
+        RespValue::Set(vec![RespValue::Boolean(true)]),
+    ]);
+    assert_eq!(format_reply(&nested), "1) \"a\"\n2) 1) \"x\"\n   2) (integer) 2\n3) 1~ (true)\n");
+
+    let items: Vec<RespValue> = (0..10).map(RespValue::Integer).collect();
+    assert!(format_reply(&RespValue::Array(items)).ends_with(" 9) (integer) 8\n10) (integer) 9\n"));
+
+    let map = RespValue::Map(vec![
+        (bulk("name"), bulk("v")),
+        (bulk("list"), RespValue::Array(vec![bulk("p"), bulk("q")])),
+    ]);
+    assert_eq!(format_reply(&map), "1# \"name\" => \"v\"\n2# \"list\" => 1) \"p\"\n             2) \"q\"\n");
+}
+
+#[test]
+fn parses_options_and_command() {
+    let args = ["-p", "7000", "-3", "SET", "-p", "v"].map(String::from);
+    let options = CliOptions::parse(&args).unwrap();
+    assert_eq!(options.port, 7000);
+    assert!(options.resp3);
+    assert_eq!(options.command, ["SET", "-p", "v"]);
+    assert!(CliOptions::parse(&["--bogus".to_string()]).is_err());
+}
+
+#[tokio::test]
+async fn pipe_counts_replies_and_errors() {
+    let server = TestServer::start().await.unwrap();
+    let mut client = server.client().await.unwrap();
+
+    let mut data = Vec::new();
+    for i in 0..5000 {
+        data.extend(format!("*3\r\n$3\r\nSET\r\n${}\r\nkey{}\r\n$1\r\nv\r\n", 3 + i.to_string().len(), i).into_bytes());
+    }
+    data.extend(b"*1\r\n$6\r\nBADCMD\r\n");
+    assert_eq!(client.pipe(&data).await.unwrap(), PipeSummary { replies: 5001, errors: 1 });
+    assert_eq!(client.command(&["GET", "key4999"]).await.unwrap(), bulk("v"));
+
+    server.shutdown().await.unwrap();
+}
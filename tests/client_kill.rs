@@ -0,0 +1,170 @@
+use redis_starter_rust::test_support::{bulk, error, info_field, ok, TestServer};
+use redis_starter_rust::{Client, RespValue};
+use std::time::Duration;
+
+async fn client_id(client: &mut Client) -> String {
+    let RespValue::Integer(id) = client.command(&["CLIENT", "ID"]).await.unwrap() else {
+        panic!("CLIENT ID did not return an integer");
+    };
+    id.to_string()
+}
+
+// CLIENT LIST에서 그 연결의 줄을 찾아 addr= 값을 꺼냄
+async fn client_addr(client: &mut Client) -> String {
+    let id = format!("id={}", client_id(client).await);
+    let RespValue::BulkString(list) = client.command(&["CLIENT", "LIST"]).await.unwrap() else {
+        panic!("CLIENT LIST did not return a bulk string");
+    };
+    let list = String::from_utf8(list).unwrap();
+    let line = list.lines().find(|line| line.split(' ').any(|field| field == id)).unwrap();
+    line.split(' ').find_map(|field| field.strip_prefix("addr=")).unwrap().to_string()
+}
+
+// 연결이 끊긴 뒤의 정리는 이벤트 루프가 비동기로 하므로 CLIENT LIST에서 사라질 때까지 기다림
+async fn wait_until_gone(client: &mut Client, id: &str) {
+    let id = format!("id={}", id);
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(5);
+    loop {
+        let RespValue::BulkString(list) = client.command(&["CLIENT", "LIST"]).await.unwrap() else {
+            panic!("CLIENT LIST did not return a bulk string");
+        };
+        let list = String::from_utf8(list).unwrap();
+        if !list.lines().any(|line| line.split(' ').any(|field| field == id)) {
+            return;
+        }
+        assert!(tokio::time::Instant::now() < deadline, "client {} is still listed:\n{}", id, list);
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+}
+
+// 닫힌 연결은 다음 명령에 응답하지 못함
+async fn is_killed(client: &mut Client) -> bool {
+    client.command(&["PING"]).await.is_err()
+}
+
+#[tokio::test]
+async fn kill_by_id_and_addr() {
+    let server = TestServer::start().await.unwrap();
+    let mut admin = server.client().await.unwrap();
+    let mut by_id = server.client().await.unwrap();
+    let mut by_addr = server.client().await.unwrap();
+    let mut bystander = server.client().await.unwrap();
+
+    let id = client_id(&mut by_id).await;
+    assert_eq!(admin.command(&["CLIENT", "KILL", "ID", &id]).await.unwrap(), RespValue::Integer(1));
+    assert!(is_killed(&mut by_id).await);
+    assert_eq!(admin.command(&["CLIENT", "KILL", "ID", &id]).await.unwrap(), RespValue::Integer(0));
+    assert_eq!(admin.command(&["CLIENT", "KILL", "ID", "0"]).await.unwrap(), error("ERR client-id should be greater than 0"));
+
+    // 예전 형식은 주소 하나만 받고 없으면 에러로 답함
+    let addr = client_addr(&mut by_addr).await;
+    assert_eq!(admin.command(&["CLIENT", "KILL", &addr]).await.unwrap(), ok());
+    assert!(is_killed(&mut by_addr).await);
+    assert_eq!(admin.command(&["CLIENT", "KILL", &addr]).await.unwrap(), error("ERR No such client"));
+    assert_eq!(admin.command(&["CLIENT", "KILL", "ADDR", &addr]).await.unwrap(), RespValue::Integer(0));
+
+    assert!(!is_killed(&mut bystander).await);
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn kill_by_user() {
+    let server = TestServer::start().await.unwrap();
+    let mut admin = server.client().await.unwrap();
+    assert_eq!(admin.command(&["ACL", "SETUSER", "alice", "on", ">secret", "+@all", "~*"]).await.unwrap(), ok());
+    let mut alice = server.client().await.unwrap();
+    assert_eq!(alice.command(&["AUTH", "alice", "secret"]).await.unwrap(), ok());
+    let mut default = server.client().await.unwrap();
+
+    assert_eq!(admin.command(&["CLIENT", "KILL", "USER", "nobody"]).await.unwrap(), error("ERR No such user 'nobody'"));
+    assert_eq!(admin.command(&["CLIENT", "KILL", "USER", "alice"]).await.unwrap(), RespValue::Integer(1));
+    assert!(is_killed(&mut alice).await);
+    assert!(!is_killed(&mut default).await);
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn skipme_decides_whether_the_caller_is_killed() {
+    let server = TestServer::start().await.unwrap();
+    let mut caller = server.client().await.unwrap();
+    let mut other = server.client().await.unwrap();
+
+    // 기본값은 SKIPME yes라서 자기 자신은 빼고 닫음
+    assert_eq!(caller.command(&["CLIENT", "KILL", "USER", "default"]).await.unwrap(), RespValue::Integer(1));
+    assert!(is_killed(&mut other).await);
+    assert!(!is_killed(&mut caller).await);
+
+    let id = client_id(&mut caller).await;
+    assert_eq!(caller.command(&["CLIENT", "KILL", "ID", &id]).await.unwrap(), RespValue::Integer(0));
+    assert_eq!(caller.command(&["CLIENT", "KILL", "ID", &id, "SKIPME", "maybe"]).await.unwrap(), error("ERR syntax error"));
+    caller.send_command(&["CLIENT", "KILL", "ID", &id, "SKIPME", "no"]).await.unwrap();
+    assert_eq!(caller.read_reply().await.unwrap(), RespValue::Integer(1));
+    assert!(is_killed(&mut caller).await);
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn maxage_kills_only_older_connections_and_zero_means_no_filter() {
+    let server = TestServer::start().await.unwrap();
+    let mut admin = server.client().await.unwrap();
+    let mut old = server.client().await.unwrap();
+    assert_eq!(old.command(&["PING"]).await.unwrap(), RespValue::SimpleString("PONG".into()));
+
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+    let mut young = server.client().await.unwrap();
+    assert_eq!(admin.command(&["CLIENT", "KILL", "MAXAGE", "1"]).await.unwrap(), RespValue::Integer(1));
+    assert!(is_killed(&mut old).await);
+    assert!(!is_killed(&mut young).await);
+
+    assert_eq!(admin.command(&["CLIENT", "KILL", "MAXAGE", "-1"]).await.unwrap(), error("ERR value is not an integer or out of range"));
+    assert_eq!(admin.command(&["CLIENT", "KILL", "MAXAGE", "0"]).await.unwrap(), RespValue::Integer(1));
+    assert!(is_killed(&mut young).await);
+
+    server.shutdown().await.unwrap();
+}
+
+// 클라이언트가 스스로 연결을 끊으면 다른 클라이언트가 보는 목록, 구독 수, 대기, INFO 수치에서 모두 빠져야 함
+#[tokio::test]
+async fn dropped_connections_are_cleaned_up() {
+    let server = TestServer::start().await.unwrap();
+    let mut observer = server.client().await.unwrap();
+    let mut subscriber = server.client().await.unwrap();
+    let mut waiter = server.client().await.unwrap();
+    let subscriber_id = client_id(&mut subscriber).await;
+    let waiter_id = client_id(&mut waiter).await;
+
+    subscriber.send_command(&["SSUBSCRIBE", "news"]).await.unwrap();
+    subscriber.read_reply().await.unwrap();
+    waiter.send_command(&["BLPOP", "jobs", "0"]).await.unwrap();
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(5);
+    while info_field(&mut observer, "clients", "blocked_clients").await.as_deref() != Some("1") {
+        assert!(tokio::time::Instant::now() < deadline, "BLPOP never blocked");
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert_eq!(info_field(&mut observer, "clients", "connected_clients").await.as_deref(), Some("3"));
+    assert_eq!(info_field(&mut observer, "clients", "pubsub_clients").await.as_deref(), Some("1"));
+    assert_eq!(
+        observer.command(&["PUBSUB", "SHARDNUMSUB", "news"]).await.unwrap(),
+        RespValue::Array(vec![bulk("news"), RespValue::Integer(1)])
+    );
+
+    drop(subscriber);
+    drop(waiter);
+    wait_until_gone(&mut observer, &subscriber_id).await;
+    wait_until_gone(&mut observer, &waiter_id).await;
+
+    assert_eq!(
+        observer.command(&["PUBSUB", "SHARDNUMSUB", "news"]).await.unwrap(),
+        RespValue::Array(vec![bulk("news"), RespValue::Integer(0)])
+    );
+    assert_eq!(info_field(&mut observer, "clients", "connected_clients").await.as_deref(), Some("1"));
+    assert_eq!(info_field(&mut observer, "clients", "pubsub_clients").await.as_deref(), Some("0"));
+    assert_eq!(info_field(&mut observer, "clients", "blocked_clients").await.as_deref(), Some("0"));
+    // 끊긴 BLPOP이 남아 있으면 이 값을 가져가 버림
+    assert_eq!(observer.command(&["LPUSH", "jobs", "job-1"]).await.unwrap(), RespValue::Integer(1));
+    assert_eq!(observer.command(&["LPOP", "jobs"]).await.unwrap(), bulk("job-1"));
+
+    server.shutdown().await.unwrap();
+}
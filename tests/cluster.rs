@@ -0,0 +1,462 @@
+use redis_starter_rust::test_support::{error, ok, TestServer};
+use redis_starter_rust::{Client, RespValue};
+use std::time::Duration;
+
+// 클러스터 버스는 클라이언트 포트 + 10000에서 받음
+const BUS_PORT_OFFSET: u16 = 10000;
+// 응답 없는 노드를 빨리 장애로 보도록 기본값 15초 대신 씀
+const NODE_TIMEOUT_MS: &str = "1000";
+// 노드들이 서로를 알거나 장애를 알아채기까지 기다리는 시간
+const GOSSIP_TIMEOUT: Duration = Duration::from_secs(15);
+
+// 버스 포트가 클라이언트 포트에서 정해지므로 OS가 고른 포트를 쓸 수 없음, 두 포트가 모두 비어 있는 포트를 고름
+fn free_cluster_port() -> u16 {
+    loop {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        if port <= u16::MAX - BUS_PORT_OFFSET && std::net::TcpListener::bind(("127.0.0.1", port + BUS_PORT_OFFSET)).is_ok() {
+            return port;
+        }
+    }
+}
+
+async fn start_node() -> TestServer {
+    let port = free_cluster_port();
+    TestServer::start_with(|builder| builder.port(port).option("cluster-enabled", "yes").option("cluster-node-timeout", NODE_TIMEOUT_MS))
+        .await
+        .unwrap()
+}
+
+async fn cluster_info(client: &mut Client, field: &str) -> String {
+    let RespValue::BulkString(info) = client.command(&["CLUSTER", "INFO"]).await.unwrap() else {
+        panic!("CLUSTER INFO did not return a bulk string");
+    };
+    let prefix = format!("{}:", field);
+    String::from_utf8_lossy(&info)
+        .lines()
+        .find_map(|line| line.strip_prefix(&prefix).map(str::to_string))
+        .unwrap_or_else(|| panic!("no {} in CLUSTER INFO", field))
+}
+
+async fn keyslot(client: &mut Client, key: &str) -> i64 {
+    let RespValue::Integer(slot) = client.command(&["CLUSTER", "KEYSLOT", key]).await.unwrap() else {
+        panic!("CLUSTER KEYSLOT did not return an integer");
+    };
+    slot
+}
+
+#[tokio::test]
+async fn cluster_keyslot_hashes_keys_and_hash_tags() {
+    let node = start_node().await;
+    let mut client = node.client().await.unwrap();
+
+    // redis-cli CLUSTER KEYSLOT이 돌려주는 값
+    assert_eq!(keyslot(&mut client, "foo").await, 12182);
+    assert_eq!(keyslot(&mut client, "somekey").await, 11058);
+    assert_eq!(keyslot(&mut client, "").await, 0);
+    // 중괄호 안의 태그만 해시하므로 같은 태그의 키는 같은 슬롯에 들어감
+    let tagged = keyslot(&mut client, "user1000").await;
+    assert_eq!(keyslot(&mut client, "{user1000}.following").await, tagged);
+    assert_eq!(keyslot(&mut client, "{user1000}.followers").await, tagged);
+    assert_eq!(keyslot(&mut client, "x{foo}y{bar}").await, 12182);
+    // 빈 태그는 태그가 없는 것처럼 키 전체를 해시함
+    assert_ne!(keyslot(&mut client, "{}foo").await, 12182);
+
+    node.shutdown().await.unwrap();
+}
+
+// CLUSTER NODES에서 myself 줄의 <ip:port@cport>
+async fn own_address(client: &mut Client) -> String {
+    let nodes = bulk_text(client.command(&["CLUSTER", "NODES"]).await.unwrap());
+    let line = nodes.lines().find(|line| line.contains("myself")).expect("no myself line in CLUSTER NODES");
+    line.split(' ').nth(1).unwrap().to_string()
+}
+
+async fn start_node_on(bind: &str, announce_ip: Option<&str>) -> TestServer {
+    let port = free_cluster_port();
+    TestServer::start_with(|builder| {
+        let builder = builder.port(port).option("bind", bind).option("cluster-enabled", "yes");
+        match announce_ip {
+            Some(ip) => builder.option("cluster-announce-ip", ip),
+            None => builder,
+        }
+    })
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn node_announces_its_bind_address_and_serves_the_bus_on_every_bind_address() {
+    // 루프백 주소만 있으면 127.0.0.1을 알리고, 버스는 바인드한 주소마다 받음
+    let node = start_node_on("127.0.0.1 127.0.0.2", None).await;
+    let mut client = node.client().await.unwrap();
+    let bus_port = node.port() + BUS_PORT_OFFSET;
+    assert_eq!(own_address(&mut client).await, format!("127.0.0.1:{}@{}", node.port(), bus_port));
+    for ip in ["127.0.0.1", "127.0.0.2"] {
+        tokio::net::TcpStream::connect((ip, bus_port)).await.unwrap_or_else(|e| panic!("no cluster bus on {}: {}", ip, e));
+    }
+    node.shutdown().await.unwrap();
+
+    // cluster-announce-ip가 있으면 바인드 주소 대신 알림
+    let node = start_node_on("127.0.0.1", Some("127.0.0.2")).await;
+    let mut client = node.client().await.unwrap();
+    assert_eq!(own_address(&mut client).await, format!("127.0.0.2:{}@{}", node.port(), node.port() + BUS_PORT_OFFSET));
+    node.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn cluster_slot_ranges_are_added_and_deleted() {
+    let node = start_node().await;
+    let mut client = node.client().await.unwrap();
+    assert_eq!(cluster_info(&mut client, "cluster_state").await, "fail");
+    // 슬롯을 맡은 노드가 없으면 키 명령을 받지 않음
+    assert_eq!(client.command(&["SET", "foo", "bar"]).await.unwrap(), error("CLUSTERDOWN The cluster is down"));
+
+    assert_eq!(client.command(&["CLUSTER", "ADDSLOTSRANGE", "0", "8191", "8192", "16383"]).await.unwrap(), ok());
+    assert_eq!(cluster_info(&mut client, "cluster_state").await, "ok");
+    assert_eq!(cluster_info(&mut client, "cluster_slots_assigned").await, "16384");
+    assert_eq!(client.command(&["SET", "foo", "bar"]).await.unwrap(), ok());
+    let RespValue::Array(slots) = client.command(&["CLUSTER", "SLOTS"]).await.unwrap() else {
+        panic!("CLUSTER SLOTS did not return an array");
+    };
+    assert_eq!(slots.len(), 1);
+    let RespValue::Array(range) = &slots[0] else {
+        panic!("unexpected slot range {:?}", slots[0]);
+    };
+    assert_eq!(range[..2], [RespValue::Integer(0), RespValue::Integer(16383)]);
+
+    assert_eq!(client.command(&["CLUSTER", "ADDSLOTSRANGE", "10", "20"]).await.unwrap(), error("ERR Slot 10 is already busy"));
+    assert_eq!(client.command(&["CLUSTER", "DELSLOTSRANGE", "100", "199", "12000", "12999"]).await.unwrap(), ok());
+    assert_eq!(cluster_info(&mut client, "cluster_slots_assigned").await, "15284");
+    assert_eq!(cluster_info(&mut client, "cluster_state").await, "fail");
+    // 구간 중 하나라도 맡지 않은 슬롯이면 아무것도 바꾸지 않음
+    assert_eq!(
+        client.command(&["CLUSTER", "DELSLOTSRANGE", "0", "99", "150", "250"]).await.unwrap(),
+        error("ERR Slot 150 is already unassigned")
+    );
+    assert_eq!(cluster_info(&mut client, "cluster_slots_assigned").await, "15284");
+    assert_eq!(
+        client.command(&["CLUSTER", "ADDSLOTSRANGE", "100", "150", "120", "199"]).await.unwrap(),
+        error("ERR Slot 120 specified multiple times")
+    );
+    assert_eq!(
+        client.command(&["CLUSTER", "ADDSLOTSRANGE", "199", "100"]).await.unwrap(),
+        error("ERR start slot number 199 is greater than end slot number 100")
+    );
+    assert_eq!(client.command(&["CLUSTER", "ADDSLOTSRANGE", "100", "199", "12000", "12999"]).await.unwrap(), ok());
+    assert_eq!(cluster_info(&mut client, "cluster_state").await, "ok");
+    assert_eq!(client.command(&["GET", "foo"]).await.unwrap(), RespValue::BulkString(b"bar".to_vec()));
+
+    node.shutdown().await.unwrap();
+}
+
+fn bulk_text(reply: RespValue) -> String {
+    let RespValue::BulkString(value) = reply else {
+        panic!("expected a bulk string, got {:?}", reply);
+    };
+    String::from_utf8_lossy(&value).into_owned()
+}
+
+// 슬롯을 반씩 맡은 두 노드를 MEET으로 잇고 서로의 슬롯을 알 때까지 기다림
+async fn start_two_nodes() -> (TestServer, TestServer) {
+    let first = start_node().await;
+    let second = start_node().await;
+    let mut first_client = first.client().await.unwrap();
+    let mut second_client = second.client().await.unwrap();
+    assert_eq!(first_client.command(&["CLUSTER", "ADDSLOTSRANGE", "0", "8191"]).await.unwrap(), ok());
+    assert_eq!(second_client.command(&["CLUSTER", "ADDSLOTSRANGE", "8192", "16383"]).await.unwrap(), ok());
+    let second_port = second.port().to_string();
+    assert_eq!(first_client.command(&["CLUSTER", "MEET", "127.0.0.1", &second_port]).await.unwrap(), ok());
+
+    let started = tokio::time::Instant::now();
+    while cluster_info(&mut first_client, "cluster_state").await != "ok" || cluster_info(&mut second_client, "cluster_state").await != "ok" {
+        assert!(started.elapsed() < GOSSIP_TIMEOUT, "the nodes did not learn each other's slots");
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    assert_eq!(cluster_info(&mut first_client, "cluster_known_nodes").await, "2");
+    (first, second)
+}
+
+#[tokio::test]
+async fn keys_owned_by_another_node_are_redirected() {
+    let (first, second) = start_two_nodes().await;
+    let mut first_client = first.client().await.unwrap();
+    let mut second_client = second.client().await.unwrap();
+    let first_addr = format!("127.0.0.1:{}", first.port());
+    let second_addr = format!("127.0.0.1:{}", second.port());
+
+    // foo는 12182, bar는 5061번 슬롯
+    assert_eq!(first_client.command(&["SET", "bar", "1"]).await.unwrap(), ok());
+    assert_eq!(first_client.command(&["SET", "foo", "1"]).await.unwrap(), error(&format!("MOVED 12182 {}", second_addr)));
+    assert_eq!(second_client.command(&["GET", "bar"]).await.unwrap(), error(&format!("MOVED 5061 {}", first_addr)));
+    assert_eq!(second_client.command(&["SET", "foo", "1"]).await.unwrap(), ok());
+    assert_eq!(first_client.command(&["EXISTS", "bar", "foo"]).await.unwrap(), error("CROSSSLOT Keys in request don't hash to the same slot"));
+
+    // 12182번 슬롯을 두 번째 노드에서 첫 번째 노드로 옮기는 중
+    let first_id = bulk_text(first_client.command(&["CLUSTER", "MYID"]).await.unwrap());
+    let second_id = bulk_text(second_client.command(&["CLUSTER", "MYID"]).await.unwrap());
+    assert_eq!(second_client.command(&["CLUSTER", "SETSLOT", "12182", "MIGRATING", &first_id]).await.unwrap(), ok());
+    assert_eq!(first_client.command(&["CLUSTER", "SETSLOT", "12182", "IMPORTING", &second_id]).await.unwrap(), ok());
+
+    // 아직 남아 있는 키는 옮기는 노드가 처리하고, 없는 키는 받는 노드로 ASK함
+    assert_eq!(second_client.command(&["GET", "foo"]).await.unwrap(), RespValue::BulkString(b"1".to_vec()));
+    assert_eq!(second_client.command(&["GET", "{foo}new"]).await.unwrap(), error(&format!("ASK 12182 {}", first_addr)));
+    // 받는 노드는 ASKING 바로 다음 명령만 받고, 그 뒤에는 다시 MOVED로 돌려보냄
+    assert_eq!(first_client.command(&["SET", "{foo}new", "2"]).await.unwrap(), error(&format!("MOVED 12182 {}", second_addr)));
+    assert_eq!(first_client.command(&["ASKING"]).await.unwrap(), ok());
+    assert_eq!(first_client.command(&["SET", "{foo}new", "2"]).await.unwrap(), ok());
+    assert_eq!(first_client.command(&["GET", "{foo}new"]).await.unwrap(), error(&format!("MOVED 12182 {}", second_addr)));
+    // 여러 키 중 일부만 옮겨졌으면 어느 쪽도 한 번에 처리할 수 없음
+    assert_eq!(
+        second_client.command(&["EXISTS", "foo", "{foo}new"]).await.unwrap(),
+        error("TRYAGAIN Multiple keys request during rehashing of slot")
+    );
+
+    // 슬롯에 키가 남아 있으면 담당 노드를 넘길 수 없음
+    let message = match second_client.command(&["CLUSTER", "SETSLOT", "12182", "NODE", &first_id]).await.unwrap() {
+        RespValue::Error(message) => message,
+        reply => panic!("SETSLOT NODE was accepted: {:?}", reply),
+    };
+    assert!(message.contains("I still hold keys for this hash slot"), "{}", message);
+
+    second.shutdown().await.unwrap();
+    first.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn redirects_and_topology_use_the_address_a_node_announces() {
+    let first = start_node_on("127.0.0.1", None).await;
+    let second = start_node_on("127.0.0.1 127.0.0.2", Some("127.0.0.2")).await;
+    let mut first_client = first.client().await.unwrap();
+    let mut second_client = second.client().await.unwrap();
+    assert_eq!(first_client.command(&["CLUSTER", "ADDSLOTSRANGE", "0", "8191"]).await.unwrap(), ok());
+    assert_eq!(second_client.command(&["CLUSTER", "ADDSLOTSRANGE", "8192", "16383"]).await.unwrap(), ok());
+    // 루프백으로 MEET해도 핸드셰이크가 끝나면 두 번째 노드가 알린 주소로 바뀜
+    let second_port = second.port().to_string();
+    assert_eq!(first_client.command(&["CLUSTER", "MEET", "127.0.0.1", &second_port]).await.unwrap(), ok());
+    let started = tokio::time::Instant::now();
+    while cluster_info(&mut first_client, "cluster_state").await != "ok" {
+        assert!(started.elapsed() < GOSSIP_TIMEOUT, "the first node did not learn the second node's slots");
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+
+    let second_addr = format!("127.0.0.2:{}", second.port());
+    assert_eq!(first_client.command(&["SET", "foo", "1"]).await.unwrap(), error(&format!("MOVED 12182 {}", second_addr)));
+    let nodes = bulk_text(first_client.command(&["CLUSTER", "NODES"]).await.unwrap());
+    assert!(nodes.contains(&format!("{}@{}", second_addr, second.port() + BUS_PORT_OFFSET)), "{}", nodes);
+    let RespValue::Array(ranges) = first_client.command(&["CLUSTER", "SLOTS"]).await.unwrap() else {
+        panic!("CLUSTER SLOTS did not return an array");
+    };
+    let second_range = ranges
+        .iter()
+        .find(|range| matches!(range, RespValue::Array(items) if items[0] == RespValue::Integer(8192)))
+        .expect("no range starting at slot 8192");
+    let RespValue::Array(items) = second_range else { unreachable!() };
+    let RespValue::Array(endpoint) = &items[2] else {
+        panic!("no endpoint in {:?}", items);
+    };
+    assert_eq!(endpoint[0], RespValue::BulkString(b"127.0.0.2".to_vec()));
+
+    second.shutdown().await.unwrap();
+    first.shutdown().await.unwrap();
+}
+
+async fn node_flags(client: &mut Client, node_id: &str) -> String {
+    let nodes = bulk_text(client.command(&["CLUSTER", "NODES"]).await.unwrap());
+    let line = nodes.lines().find(|line| line.starts_with(node_id)).unwrap_or_else(|| panic!("{} is not in CLUSTER NODES:\n{}", node_id, nodes));
+    line.split(' ').nth(2).unwrap().to_string()
+}
+
+async fn distinct_epochs(clients: &mut [Client]) -> usize {
+    let mut epochs = Vec::new();
+    for client in clients.iter_mut() {
+        epochs.push(cluster_info(client, "cluster_my_epoch").await);
+    }
+    epochs.sort();
+    epochs.dedup();
+    epochs.len()
+}
+
+#[tokio::test]
+async fn nodes_meet_through_gossip_and_agree_on_a_failure() {
+    let nodes = [start_node().await, start_node().await, start_node().await];
+    let mut clients = Vec::new();
+    for (node, range) in nodes.iter().zip([["0", "5460"], ["5461", "10922"], ["10923", "16383"]]) {
+        let mut client = node.client().await.unwrap();
+        assert_eq!(client.command(&["CLUSTER", "ADDSLOTSRANGE", range[0], range[1]]).await.unwrap(), ok());
+        clients.push(client);
+    }
+    // 첫 번째 노드만 나머지를 만나고, 두 번째와 세 번째 노드는 가십으로 서로를 알게 됨
+    for node in &nodes[1..] {
+        let port = node.port().to_string();
+        assert_eq!(clients[0].command(&["CLUSTER", "MEET", "127.0.0.1", &port]).await.unwrap(), ok());
+    }
+    let started = tokio::time::Instant::now();
+    for client in clients.iter_mut() {
+        while cluster_info(client, "cluster_known_nodes").await != "3" || cluster_info(client, "cluster_state").await != "ok" {
+            assert!(started.elapsed() < GOSSIP_TIMEOUT, "the nodes did not form a cluster");
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+    }
+    // 처음에는 모두 config epoch 0이므로 충돌을 풀면서 서로 다른 epoch를 갖게 됨
+    let started = tokio::time::Instant::now();
+    while distinct_epochs(&mut clients).await != 3 {
+        assert!(started.elapsed() < GOSSIP_TIMEOUT, "config epochs did not become unique");
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+
+    // 세 번째 노드가 내려가면 남은 두 마스터가 과반이 되어 FAIL로 표시하고, 그 노드의 슬롯 때문에 클러스터가 내려감
+    let [first, second, third] = nodes;
+    let third_id = bulk_text(clients[2].command(&["CLUSTER", "MYID"]).await.unwrap());
+    third.shutdown().await.unwrap();
+    let started = tokio::time::Instant::now();
+    for client in clients[..2].iter_mut() {
+        while node_flags(client, &third_id).await != "master,fail" {
+            assert!(started.elapsed() < GOSSIP_TIMEOUT, "the stopped node was not marked as failing");
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        assert_eq!(cluster_info(client, "cluster_state").await, "fail");
+        assert_eq!(cluster_info(client, "cluster_slots_fail").await, "5461");
+    }
+    assert_eq!(clients[0].command(&["SET", "bar", "1"]).await.unwrap(), error("CLUSTERDOWN The cluster is down"));
+
+    second.shutdown().await.unwrap();
+    first.shutdown().await.unwrap();
+}
+
+async fn replicas(client: &mut Client, node_id: &str) -> Vec<String> {
+    let RespValue::Array(lines) = client.command(&["CLUSTER", "REPLICAS", node_id]).await.unwrap() else {
+        panic!("CLUSTER REPLICAS did not return an array");
+    };
+    lines.into_iter().map(bulk_text).collect()
+}
+
+// 레플리카는 버스 메시지로 자기 마스터를 알리고, READONLY 연결은 레플리카에서 마스터 슬롯을 읽을 수 있음
+#[tokio::test]
+async fn replicas_announce_themselves_and_serve_readonly_reads() {
+    let master = start_node().await;
+    let replica = start_node().await;
+    let mut master_client = master.client().await.unwrap();
+    let mut replica_client = replica.client().await.unwrap();
+    assert_eq!(master_client.command(&["CLUSTER", "ADDSLOTSRANGE", "0", "16383"]).await.unwrap(), ok());
+    let replica_port = replica.port().to_string();
+    assert_eq!(master_client.command(&["CLUSTER", "MEET", "127.0.0.1", &replica_port]).await.unwrap(), ok());
+    let master_id = bulk_text(master_client.command(&["CLUSTER", "MYID"]).await.unwrap());
+    let replica_id = bulk_text(replica_client.command(&["CLUSTER", "MYID"]).await.unwrap());
+
+    let started = tokio::time::Instant::now();
+    while cluster_info(&mut replica_client, "cluster_state").await != "ok" || cluster_info(&mut master_client, "cluster_known_nodes").await != "2" {
+        assert!(started.elapsed() < GOSSIP_TIMEOUT, "the nodes did not form a cluster");
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    assert_eq!(replica_client.command(&["REPLICAOF", "127.0.0.1", &master.port().to_string()]).await.unwrap(), error("ERR REPLICAOF not allowed in cluster mode."));
+    assert_eq!(replica_client.command(&["CLUSTER", "REPLICATE", &replica_id]).await.unwrap(), error("ERR Can't replicate myself"));
+    assert_eq!(replica_client.command(&["CLUSTER", "REPLICATE", "nosuchnode"]).await.unwrap(), error("ERR Unknown node nosuchnode"));
+    assert_eq!(replica_client.command(&["CLUSTER", "REPLICATE", &master_id]).await.unwrap(), ok());
+    assert_eq!(node_flags(&mut replica_client, &replica_id).await, "myself,slave");
+
+    // 마스터는 다음 버스 메시지로 레플리카를 알게 됨
+    let started = tokio::time::Instant::now();
+    while replicas(&mut master_client, &master_id).await.is_empty() {
+        assert!(started.elapsed() < GOSSIP_TIMEOUT, "the master did not learn about its replica");
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    let lines = replicas(&mut master_client, &master_id).await;
+    assert_eq!(lines.len(), 1);
+    let fields: Vec<&str> = lines[0].split(' ').collect();
+    assert_eq!((fields[0], fields[2], fields[3]), (replica_id.as_str(), "slave", master_id.as_str()));
+    assert_eq!(
+        master_client.command(&["CLUSTER", "REPLICAS", &replica_id]).await.unwrap(),
+        error("ERR The specified node is not a master")
+    );
+    assert_eq!(
+        master_client.command(&["CLUSTER", "REPLICATE", &replica_id]).await.unwrap(),
+        error("ERR I can only replicate a master, not a replica.")
+    );
+    let RespValue::Array(slots) = master_client.command(&["CLUSTER", "SLOTS"]).await.unwrap() else {
+        panic!("CLUSTER SLOTS did not return an array");
+    };
+    let RespValue::Array(range) = &slots[0] else {
+        panic!("unexpected slot range {:?}", slots[0]);
+    };
+    assert_eq!(range.len(), 4);
+    assert_eq!(range[3], RespValue::Array(vec![RespValue::BulkString(b"127.0.0.1".to_vec()), RespValue::Integer(replica.port() as i64), RespValue::BulkString(replica_id.clone().into_bytes())]));
+
+    // 레플리카는 기본으로 MOVED를 돌려주고, READONLY 뒤에는 읽기만 직접 처리함
+    let master_addr = format!("127.0.0.1:{}", master.port());
+    assert_eq!(master_client.command(&["SET", "foo", "bar"]).await.unwrap(), ok());
+    assert_eq!(replica_client.command(&["GET", "foo"]).await.unwrap(), error(&format!("MOVED 12182 {}", master_addr)));
+    assert_eq!(replica_client.command(&["READONLY"]).await.unwrap(), ok());
+    let started = tokio::time::Instant::now();
+    while replica_client.command(&["GET", "foo"]).await.unwrap() != RespValue::BulkString(b"bar".to_vec()) {
+        assert!(started.elapsed() < GOSSIP_TIMEOUT, "the replica did not serve the replicated key");
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    assert_eq!(replica_client.command(&["SET", "foo", "baz"]).await.unwrap(), error(&format!("MOVED 12182 {}", master_addr)));
+    assert_eq!(replica_client.command(&["READWRITE"]).await.unwrap(), ok());
+    assert_eq!(replica_client.command(&["GET", "foo"]).await.unwrap(), error(&format!("MOVED 12182 {}", master_addr)));
+
+    replica.shutdown().await.unwrap();
+    master.shutdown().await.unwrap();
+}
+
+// 12182번 슬롯을 MIGRATING/IMPORTING으로 두고 키를 MIGRATE로 옮긴 뒤 담당 노드를 넘김
+// 받는 노드는 RESTORE-ASKING을 받아들이고, 같은 키를 일반 RESTORE로 보내면 MOVED로 돌려보냄
+#[tokio::test]
+async fn migrate_moves_a_slot_between_two_nodes() {
+    let (first, second) = start_two_nodes().await;
+    let mut first_client = first.client().await.unwrap();
+    let mut second_client = second.client().await.unwrap();
+    let first_id = bulk_text(first_client.command(&["CLUSTER", "MYID"]).await.unwrap());
+    let second_id = bulk_text(second_client.command(&["CLUSTER", "MYID"]).await.unwrap());
+    let first_addr = format!("127.0.0.1:{}", first.port());
+    let second_addr = format!("127.0.0.1:{}", second.port());
+
+    assert_eq!(second_client.command(&["SET", "foo", "1"]).await.unwrap(), ok());
+    assert_eq!(second_client.command(&["RPUSH", "{foo}list", "a", "b"]).await.unwrap(), RespValue::Integer(2));
+    assert_eq!(second_client.command(&["SET", "{foo}ttl", "x", "PX", "100000"]).await.unwrap(), ok());
+    let RespValue::BulkString(payload) = second_client.command(&["DUMP", "foo"]).await.unwrap() else {
+        panic!("DUMP did not return a payload");
+    };
+    let payload = String::from_utf8_lossy(&payload).into_owned();
+    assert_eq!(
+        first_client.command(&["RESTORE-ASKING", "foo", "0", &payload]).await.unwrap(),
+        error(&format!("MOVED 12182 {}", second_addr))
+    );
+
+    assert_eq!(first_client.command(&["CLUSTER", "SETSLOT", "12182", "IMPORTING", &second_id]).await.unwrap(), ok());
+    assert_eq!(second_client.command(&["CLUSTER", "SETSLOT", "12182", "MIGRATING", &first_id]).await.unwrap(), ok());
+    // 받는 노드에서도 ASKING 없는 RESTORE는 아직 담당 노드로 보냄
+    assert_eq!(
+        first_client.command(&["RESTORE", "foo", "0", &payload]).await.unwrap(),
+        error(&format!("MOVED 12182 {}", second_addr))
+    );
+
+    let port = first.port().to_string();
+    assert_eq!(second_client.command(&["MIGRATE", "127.0.0.1", &port, "foo", "0", "5000"]).await.unwrap(), ok());
+    assert_eq!(
+        second_client.command(&["MIGRATE", "127.0.0.1", &port, "", "0", "5000", "KEYS", "{foo}list", "{foo}ttl"]).await.unwrap(),
+        ok()
+    );
+    assert_eq!(second_client.command(&["GET", "foo"]).await.unwrap(), error(&format!("ASK 12182 {}", first_addr)));
+    assert_eq!(first_client.command(&["ASKING"]).await.unwrap(), ok());
+    assert_eq!(first_client.command(&["GET", "foo"]).await.unwrap(), RespValue::BulkString(b"1".to_vec()));
+    assert_eq!(second_client.command(&["CLUSTER", "COUNTKEYSINSLOT", "12182"]).await.unwrap(), RespValue::Integer(0));
+    assert_eq!(first_client.command(&["CLUSTER", "COUNTKEYSINSLOT", "12182"]).await.unwrap(), RespValue::Integer(3));
+
+    // 슬롯이 비었으니 담당 노드를 넘기면 받는 노드가 직접 처리함
+    assert_eq!(second_client.command(&["CLUSTER", "SETSLOT", "12182", "NODE", &first_id]).await.unwrap(), ok());
+    assert_eq!(first_client.command(&["CLUSTER", "SETSLOT", "12182", "NODE", &first_id]).await.unwrap(), ok());
+    assert_eq!(
+        first_client.command(&["LPOP", "{foo}list", "2"]).await.unwrap(),
+        RespValue::Array(vec![RespValue::BulkString(b"a".to_vec()), RespValue::BulkString(b"b".to_vec())])
+    );
+    let RespValue::Integer(ttl) = first_client.command(&["PTTL", "{foo}ttl"]).await.unwrap() else {
+        panic!("PTTL did not return an integer");
+    };
+    assert!(ttl > 0 && ttl <= 100000, "{}", ttl);
+    assert_eq!(second_client.command(&["GET", "foo"]).await.unwrap(), error(&format!("MOVED 12182 {}", first_addr)));
+
+    second.shutdown().await.unwrap();
+    first.shutdown().await.unwrap();
+}
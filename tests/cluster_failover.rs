@@ -0,0 +1,146 @@
+use redis_starter_rust::test_support::{bulk, info_field, TestServer};
+use redis_starter_rust::RespValue;
+use std::time::Duration;
+
+const MASTER_NAME: &str = "mymaster";
+const WRITES: usize = 100;
+// sentinel이 마스터가 내려간 것을 알아채고 레플리카를 승격시키기까지 기다리는 시간
+const FAILOVER_TIMEOUT: Duration = Duration::from_secs(20);
+
+// 조건을 다시 확인하기 전에 부름, 기다린 시간이 FAILOVER_TIMEOUT을 넘으면 실패함
+async fn retry(started: tokio::time::Instant, what: &str) {
+    assert!(started.elapsed() < FAILOVER_TIMEOUT, "{} did not happen in time", what);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+}
+
+// 한 프로세스 안에 마스터 하나, 레플리카 둘, 이들을 감시하는 sentinel 하나를 띄움
+struct FailoverCluster {
+    master: Option<TestServer>,
+    replicas: Vec<TestServer>,
+    sentinel: TestServer,
+}
+
+impl FailoverCluster {
+    async fn start() -> Self {
+        let master = TestServer::start().await.unwrap();
+        let replica_of = format!("127.0.0.1 {}", master.port());
+        let mut replicas = Vec::new();
+        for _ in 0..2 {
+            let replica_of = replica_of.clone();
+            replicas.push(TestServer::start_with(|builder| builder.option("replicaof", replica_of)).await.unwrap());
+        }
+        let mut master_client = master.client().await.unwrap();
+        let started = tokio::time::Instant::now();
+        while info_field(&mut master_client, "replication", "connected_slaves").await.as_deref() != Some("2") {
+            retry(started, "replicas connecting").await;
+        }
+
+        // sentinel은 첫 INFO에서 레플리카를 알아내므로 레플리카가 붙은 뒤에 띄움
+        let monitor = format!("{} 127.0.0.1 {} 1", MASTER_NAME, master.port());
+        let sentinel = TestServer::start_with(|builder| {
+            builder
+                .args(["--sentinel"])
+                .option("sentinel-monitor", monitor)
+                .option("sentinel-down-after-milliseconds", "500")
+                .option("sentinel-failover-timeout", "5000")
+        })
+        .await
+        .unwrap();
+        let mut sentinel_client = sentinel.client().await.unwrap();
+        let started = tokio::time::Instant::now();
+        while !matches!(
+            sentinel_client.command(&["SENTINEL", "REPLICAS", MASTER_NAME]).await.unwrap(),
+            RespValue::Array(replicas) if replicas.len() == 2
+        ) {
+            retry(started, "sentinel discovering the replicas").await;
+        }
+
+        Self { master: Some(master), replicas, sentinel }
+    }
+
+    async fn master_port(&self) -> u16 {
+        let reply = self.sentinel.client().await.unwrap().command(&["SENTINEL", "GET-MASTER-ADDR-BY-NAME", MASTER_NAME]).await.unwrap();
+        let RespValue::Array(addr) = reply else {
+            panic!("sentinel did not return the master address: {:?}", reply);
+        };
+        let RespValue::BulkString(port) = &addr[1] else {
+            panic!("unexpected master port {:?}", addr[1]);
+        };
+        String::from_utf8_lossy(port).parse().unwrap()
+    }
+
+    async fn kill_master(&mut self) -> u16 {
+        let master = self.master.take().unwrap();
+        let port = master.port();
+        master.shutdown().await.unwrap();
+        port
+    }
+
+    // sentinel이 새 마스터로 알려 준 레플리카와 나머지 레플리카
+    async fn wait_for_promotion(&self, old_port: u16) -> (&TestServer, &TestServer) {
+        let started = tokio::time::Instant::now();
+        while self.master_port().await == old_port {
+            retry(started, "sentinel switching the master").await;
+        }
+        let new_port = self.master_port().await;
+        let promoted = self.replicas.iter().position(|replica| replica.port() == new_port).expect("sentinel promoted an unknown node");
+        (&self.replicas[promoted], &self.replicas[1 - promoted])
+    }
+
+    async fn shutdown(self) {
+        self.sentinel.shutdown().await.unwrap();
+        for replica in self.replicas {
+            replica.shutdown().await.unwrap();
+        }
+        if let Some(master) = self.master {
+            master.shutdown().await.unwrap();
+        }
+    }
+}
+
+#[tokio::test]
+async fn sentinel_promotes_a_replica_that_kept_every_acknowledged_write() {
+    let mut cluster = FailoverCluster::start().await;
+    let mut master_client = cluster.master.as_ref().unwrap().client().await.unwrap();
+    for i in 0..WRITES {
+        master_client.command(&["SET", &format!("key:{}", i), &i.to_string()]).await.unwrap();
+    }
+    // WAIT이 돌려준 수만큼의 레플리카가 받은 쓰기는 페일오버 뒤에도 남아야 함
+    assert_eq!(master_client.command(&["WAIT", "2", "5000"]).await.unwrap(), RespValue::Integer(2));
+    let old_replid = info_field(&mut master_client, "replication", "master_replid").await.unwrap();
+    let old_offset: u64 = info_field(&mut master_client, "replication", "master_repl_offset").await.unwrap().parse().unwrap();
+
+    let old_port = cluster.kill_master().await;
+    let (promoted, follower) = cluster.wait_for_promotion(old_port).await;
+
+    // 승격된 레플리카는 옛 기록을 두 번째 복제 ID로 이어 감
+    let mut promoted_client = promoted.client().await.unwrap();
+    assert_eq!(info_field(&mut promoted_client, "replication", "role").await.unwrap(), "master");
+    assert_eq!(info_field(&mut promoted_client, "replication", "master_replid2").await.unwrap(), old_replid);
+    let promoted_offset: u64 = info_field(&mut promoted_client, "replication", "master_repl_offset").await.unwrap().parse().unwrap();
+    assert!(promoted_offset >= old_offset, "offset went back from {} to {}", old_offset, promoted_offset);
+    let second_offset: u64 = info_field(&mut promoted_client, "replication", "second_repl_offset").await.unwrap().parse().unwrap();
+    assert!(second_offset > old_offset, "second_repl_offset {} does not follow {}", second_offset, old_offset);
+    assert_eq!(promoted_client.command(&["DBSIZE"]).await.unwrap(), RespValue::Integer(WRITES as i64));
+    for i in 0..WRITES {
+        assert_eq!(promoted_client.command(&["GET", &format!("key:{}", i)]).await.unwrap(), bulk(&i.to_string()));
+    }
+
+    // 남은 레플리카는 sentinel이 새 마스터로 돌리고, 새 마스터의 쓰기를 받음
+    let new_replid = info_field(&mut promoted_client, "replication", "master_replid").await.unwrap();
+    let mut follower_client = follower.client().await.unwrap();
+    let promoted_port = promoted.port().to_string();
+    let started = tokio::time::Instant::now();
+    while info_field(&mut follower_client, "replication", "master_port").await != Some(promoted_port.clone())
+        || info_field(&mut follower_client, "replication", "master_link_status").await.as_deref() != Some("up")
+        || info_field(&mut follower_client, "replication", "master_replid").await != Some(new_replid.clone())
+    {
+        retry(started, "the other replica following the new master").await;
+    }
+    promoted_client.command(&["SET", "after", "failover"]).await.unwrap();
+    assert_eq!(promoted_client.command(&["WAIT", "1", "5000"]).await.unwrap(), RespValue::Integer(1));
+    assert_eq!(follower_client.command(&["GET", "after"]).await.unwrap(), bulk("failover"));
+    assert_eq!(follower_client.command(&["DBSIZE"]).await.unwrap(), RespValue::Integer(WRITES as i64 + 1));
+
+    cluster.shutdown().await;
+}
@@ -0,0 +1,93 @@
+use redis_starter_rust::test_support::{bulk, info_field, ok, TestServer};
+use redis_starter_rust::{Client, RespValue};
+
+#[tokio::test]
+async fn maxmemory_samples_is_configurable() {
+    let server = TestServer::start_with(|builder| builder.option("maxmemory-samples", "10")).await.unwrap();
+    let mut client = server.client().await.unwrap();
+
+    assert_eq!(
+        client.command(&["CONFIG", "GET", "maxmemory-samples"]).await.unwrap(),
+        RespValue::Array(vec![bulk("maxmemory-samples"), bulk("10")])
+    );
+    assert_eq!(client.command(&["CONFIG", "SET", "maxmemory-samples", "3"]).await.unwrap(), ok());
+    assert!(matches!(client.command(&["CONFIG", "SET", "maxmemory-samples", "0"]).await.unwrap(), RespValue::Error(_)));
+
+    server.shutdown().await.unwrap();
+}
+
+// volatile 정책은 TTL이 있는 키의 색인에서만 표본을 뽑으므로 TTL이 없는 키는 지우지 않음
+#[tokio::test]
+async fn volatile_policy_only_evicts_keys_with_a_ttl() {
+    let server = TestServer::start().await.unwrap();
+    let mut client = server.client().await.unwrap();
+    let value = "x".repeat(100);
+    for i in 0..50 {
+        client.command(&["SET", &format!("persistent:{}", i), &value]).await.unwrap();
+        client.command(&["SET", &format!("volatile:{}", i), &value, "EX", "1000"]).await.unwrap();
+    }
+    let used_memory = info_field(&mut client, "memory", "used_memory").await.unwrap().parse::<u64>().unwrap();
+    assert_eq!(client.command(&["CONFIG", "SET", "maxmemory-policy", "volatile-lru"]).await.unwrap(), ok());
+    assert_eq!(client.command(&["CONFIG", "SET", "maxmemory", &(used_memory - 2000).to_string()]).await.unwrap(), ok());
+
+    assert_eq!(client.command(&["SET", "trigger", "1", "EX", "1000"]).await.unwrap(), ok());
+    assert!(info_field(&mut client, "stats", "evicted_keys").await.unwrap().parse::<u64>().unwrap() > 0);
+    for i in 0..50 {
+        assert_eq!(client.command(&["EXISTS", &format!("persistent:{}", i)]).await.unwrap(), RespValue::Integer(1));
+    }
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn allkeys_policy_frees_enough_memory_for_the_write() {
+    let server = TestServer::start().await.unwrap();
+    let mut client = server.client().await.unwrap();
+    let value = "x".repeat(100);
+    for i in 0..100 {
+        client.command(&["SET", &format!("key:{}", i), &value]).await.unwrap();
+    }
+    let used_memory = info_field(&mut client, "memory", "used_memory").await.unwrap().parse::<u64>().unwrap();
+    let maxmemory = used_memory / 2;
+    assert_eq!(client.command(&["CONFIG", "SET", "maxmemory-policy", "allkeys-lru"]).await.unwrap(), ok());
+    assert_eq!(client.command(&["CONFIG", "SET", "maxmemory", &maxmemory.to_string()]).await.unwrap(), ok());
+
+    assert_eq!(client.command(&["SET", "trigger", "1"]).await.unwrap(), ok());
+    assert!(info_field(&mut client, "memory", "used_memory").await.unwrap().parse::<u64>().unwrap() <= maxmemory + 1000);
+    let RespValue::Integer(remaining) = client.command(&["DBSIZE"]).await.unwrap() else {
+        panic!("DBSIZE did not return an integer");
+    };
+    assert!(remaining > 0 && remaining < 100, "{}", remaining);
+
+    server.shutdown().await.unwrap();
+}
+
+async fn frequency_after_reads(client: &mut Client, reads: usize) -> i64 {
+    assert_eq!(client.command(&["SET", "key", "value"]).await.unwrap(), ok());
+    for _ in 0..reads {
+        client.command(&["GET", "key"]).await.unwrap();
+    }
+    let RespValue::Integer(frequency) = client.command(&["OBJECT", "FREQ", "key"]).await.unwrap() else {
+        panic!("OBJECT FREQ did not return an integer");
+    };
+    frequency
+}
+
+// LFU 설정은 서버마다 따로 가지므로 한 프로세스에서 함께 떠 있는 두 서버가 서로 다른 값을 씀
+#[tokio::test]
+async fn servers_in_one_process_keep_their_own_lfu_settings() {
+    let start = |log_factor: &'static str| {
+        TestServer::start_with(move |builder| builder.option("maxmemory-policy", "allkeys-lfu").option("lfu-log-factor", log_factor))
+    };
+    let eager = start("0").await.unwrap();
+    let damped = start("100").await.unwrap();
+    let mut eager_client = eager.client().await.unwrap();
+    let mut damped_client = damped.client().await.unwrap();
+
+    // log factor가 0이면 읽을 때마다 카운터가 오르고, 100이면 몇 번만 오름
+    assert!(frequency_after_reads(&mut eager_client, 100).await >= 100);
+    assert!(frequency_after_reads(&mut damped_client, 100).await < 50);
+
+    eager.shutdown().await.unwrap();
+    damped.shutdown().await.unwrap();
+}
@@ -0,0 +1,42 @@
+use redis_starter_rust::test_support::{ok, TestServer};
+use redis_starter_rust::RespValue;
+
+fn denied(command: &str) -> RespValue {
+    RespValue::Error(format!("ERR command '{}' is not allowed from 127.0.0.1", command))
+}
+
+#[tokio::test]
+async fn firewall_rejects_denied_commands_before_they_run() {
+    // 더 긴 접두사가 이기므로 루프백 중 127.0.0.1만 읽기와 연결 명령으로 제한됨
+    let server = TestServer::start_with(|builder| builder.option("firewall", "127.0.0.0/8=all;127.0.0.1/32=read,connection"))
+        .await
+        .unwrap();
+    let mut client = server.client().await.unwrap();
+
+    assert_eq!(client.command(&["PING"]).await.unwrap(), RespValue::SimpleString("PONG".into()));
+    assert_eq!(client.command(&["SET", "key", "value"]).await.unwrap(), denied("SET"));
+    assert_eq!(client.command(&["GET", "key"]).await.unwrap(), RespValue::NullBulk);
+    assert_eq!(client.command(&["FLUSHALL"]).await.unwrap(), denied("FLUSHALL"));
+    assert_eq!(client.command(&["DBSIZE"]).await.unwrap(), RespValue::Integer(0));
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn firewall_allows_addresses_without_a_matching_rule() {
+    let server = TestServer::start_with(|builder| builder.option("firewall", "10.0.0.0/8=read")).await.unwrap();
+    let mut client = server.client().await.unwrap();
+
+    assert_eq!(client.command(&["SET", "key", "value"]).await.unwrap(), ok());
+    assert_eq!(client.command(&["GET", "key"]).await.unwrap(), RespValue::BulkString(b"value".to_vec()));
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn server_refuses_an_invalid_firewall_rule() {
+    let Err(message) = TestServer::start_with(|builder| builder.option("firewall", "127.0.0.1/33=read")).await else {
+        panic!("the server started with an invalid firewall rule");
+    };
+    assert!(message.starts_with("Invalid firewall configuration"), "{}", message);
+}
@@ -0,0 +1,182 @@
+use redis_starter_rust::test_support::{bulk, ok, TestServer};
+use redis_starter_rust::{Client, RespValue};
+use std::collections::HashSet;
+
+const FIELDS: [(&str, &str); 3] = [("a", "1"), ("b", "2"), ("c", "3")];
+
+async fn fields(client: &mut Client, args: &[&str]) -> Vec<String> {
+    let RespValue::Array(items) = client.command(args).await.unwrap() else {
+        panic!("{:?} did not return an array", args);
+    };
+    items
+        .into_iter()
+        .map(|item| match item {
+            RespValue::BulkString(field) => String::from_utf8(field).unwrap(),
+            other => panic!("unexpected item {:?}", other),
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn hrandfield_samples_with_and_without_repetition() {
+    let server = TestServer::start().await.unwrap();
+    let mut client = server.client().await.unwrap();
+    client.command(&["HSET", "hash", "a", "1", "b", "2", "c", "3"]).await.unwrap();
+    let names: HashSet<String> = FIELDS.iter().map(|(field, _)| field.to_string()).collect();
+
+    let RespValue::BulkString(field) = client.command(&["HRANDFIELD", "hash"]).await.unwrap() else {
+        panic!("HRANDFIELD without count did not return a field");
+    };
+    assert!(names.contains(&String::from_utf8(field).unwrap()));
+
+    // 양수 count는 겹치지 않고 해시 크기를 넘지 않음
+    let sampled = fields(&mut client, &["HRANDFIELD", "hash", "2"]).await;
+    assert_eq!(sampled.len(), 2);
+    assert_ne!(sampled[0], sampled[1]);
+    let all: HashSet<String> = fields(&mut client, &["HRANDFIELD", "hash", "10"]).await.into_iter().collect();
+    assert_eq!(all, names);
+
+    // 음수 count는 정확히 그 수만큼, 같은 필드가 여러 번 나올 수 있음
+    let repeated = fields(&mut client, &["HRANDFIELD", "hash", "-20"]).await;
+    assert_eq!(repeated.len(), 20);
+    assert!(repeated.iter().all(|field| names.contains(field)));
+
+    // WITHVALUES는 필드와 값을 번갈아 보냄
+    let with_values = fields(&mut client, &["HRANDFIELD", "hash", "-5", "withvalues"]).await;
+    assert_eq!(with_values.len(), 10);
+    for pair in with_values.chunks(2) {
+        assert!(FIELDS.contains(&(pair[0].as_str(), pair[1].as_str())), "{:?}", pair);
+    }
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn hrandfield_skips_expired_fields() {
+    let server = TestServer::start().await.unwrap();
+    let mut client = server.client().await.unwrap();
+    client.command(&["HSET", "hash", "live", "1", "dead", "2"]).await.unwrap();
+    client.command(&["DEBUG", "SET-ACTIVE-EXPIRE", "0"]).await.unwrap();
+    client.command(&["HPEXPIRE", "hash", "50", "FIELDS", "1", "dead"]).await.unwrap();
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+
+    assert_eq!(fields(&mut client, &["HRANDFIELD", "hash", "-10"]).await, vec!["live"; 10]);
+    assert_eq!(fields(&mut client, &["HRANDFIELD", "hash", "5", "WITHVALUES"]).await, ["live", "1"]);
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn hrandfield_on_missing_keys_and_bad_arguments() {
+    let server = TestServer::start().await.unwrap();
+    let mut client = server.client().await.unwrap();
+
+    assert_eq!(client.command(&["HRANDFIELD", "missing"]).await.unwrap(), RespValue::NullBulk);
+    assert_eq!(client.command(&["HRANDFIELD", "missing", "-3"]).await.unwrap(), RespValue::Array(vec![]));
+    client.command(&["HSET", "hash", "a", "1"]).await.unwrap();
+    assert_eq!(client.command(&["HRANDFIELD", "hash", "0"]).await.unwrap(), RespValue::Array(vec![]));
+    // 양수 count는 키 크기를 넘지 않으므로 아무리 커도 그대로 받음
+    assert_eq!(client.command(&["HRANDFIELD", "hash", "100000000000"]).await.unwrap(), RespValue::Array(vec![RespValue::BulkString(b"a".to_vec())]));
+
+    client.command(&["SET", "string", "value"]).await.unwrap();
+    let RespValue::Error(message) = client.command(&["HRANDFIELD", "string"]).await.unwrap() else {
+        panic!("HRANDFIELD accepted a string key");
+    };
+    assert!(message.starts_with("WRONGTYPE"));
+    for (args, expected) in [
+        (&["HRANDFIELD", "hash", "x"][..], "ERR value is not an integer or out of range"),
+        (&["HRANDFIELD", "hash", "1", "WITHSCORES"], "ERR syntax error"),
+        (&["HRANDFIELD", "hash", "1", "WITHVALUES", "x"], "ERR syntax error"),
+        (&["HRANDFIELD", "hash", "-9223372036854775808"], "ERR value is out of range"),
+        (&["HRANDFIELD", "hash", "-4611686018427387904", "WITHVALUES"], "ERR value is out of range"),
+        (&["HRANDFIELD", "hash", "-100000000000"], "ERR value is out of range"),
+        (&["HRANDFIELD", "hash", "-16777217"], "ERR value is out of range"),
+        (&["HRANDFIELD", "hash", "-8388609", "WITHVALUES"], "ERR value is out of range"),
+    ] {
+        assert_eq!(client.command(args).await.unwrap(), RespValue::Error(expected.into()), "{:?}", args);
+    }
+
+    server.shutdown().await.unwrap();
+}
+
+// 능동 만료는 필드 TTL이 있는 해시의 집합에서 표본을 뽑음, HPERSIST/HDEL로 TTL이 없어진 해시는 빠져야 함
+#[tokio::test]
+async fn active_expire_only_visits_hashes_with_field_ttls() {
+    let server = TestServer::start().await.unwrap();
+    let mut client = server.client().await.unwrap();
+    client.command(&["CONFIG", "SET", "notify-keyspace-events", "Eh"]).await.unwrap();
+    let mut subscriber = server.client().await.unwrap();
+    subscriber.command(&["SUBSCRIBE", "__keyevent@0__:hexpired"]).await.unwrap();
+
+    for key in ["expiring", "persisted", "deleted"] {
+        client.command(&["HSET", key, "a", "1", "b", "2"]).await.unwrap();
+        client.command(&["HPEXPIRE", key, "100", "FIELDS", "1", "a"]).await.unwrap();
+    }
+    client.command(&["HPERSIST", "persisted", "FIELDS", "1", "a"]).await.unwrap();
+    client.command(&["HDEL", "deleted", "a"]).await.unwrap();
+
+    let timeout = std::time::Duration::from_secs(5);
+    let message = tokio::time::timeout(timeout, subscriber.read_reply()).await.unwrap().unwrap();
+    let RespValue::Array(parts) = message else {
+        panic!("unexpected notification {:?}", message);
+    };
+    assert_eq!(parts.last(), Some(&RespValue::BulkString(b"expiring".to_vec())));
+    assert!(tokio::time::timeout(std::time::Duration::from_millis(300), subscriber.read_reply()).await.is_err());
+    assert_eq!(fields(&mut client, &["HGETALL", "expiring"]).await, ["b", "2"]);
+    assert_eq!(client.command(&["HGET", "persisted", "a"]).await.unwrap(), RespValue::BulkString(b"1".to_vec()));
+
+    server.shutdown().await.unwrap();
+}
+
+async fn field_ttls(client: &mut Client, key: &str) -> Vec<i64> {
+    let RespValue::Array(items) = client.command(&["HTTL", key, "FIELDS", "2", "a", "b"]).await.unwrap() else {
+        panic!("HTTL did not return an array");
+    };
+    items
+        .into_iter()
+        .map(|item| match item {
+            RespValue::Integer(ttl) => ttl,
+            other => panic!("unexpected item {:?}", other),
+        })
+        .collect()
+}
+
+// 필드 TTL은 RDB와 DUMP payload에 함께 담겨 DEBUG RELOAD와 RESTORE 뒤에도 남아야 함
+#[tokio::test]
+async fn field_ttls_survive_reload_and_dump_restore() {
+    let server = TestServer::start().await.unwrap();
+    let mut client = server.client().await.unwrap();
+    client.command(&["HSET", "hash", "a", "1", "b", "2"]).await.unwrap();
+    client.command(&["HEXPIRE", "hash", "100", "FIELDS", "1", "a"]).await.unwrap();
+
+    assert_eq!(client.command(&["DEBUG", "RELOAD"]).await.unwrap(), ok());
+    let ttls = field_ttls(&mut client, "hash").await;
+    assert!(matches!(ttls[..], [99 | 100, -1]), "{:?}", ttls);
+
+    let payload = client.command(&["DUMP", "hash"]).await.unwrap();
+    let RespValue::BulkString(payload) = payload else {
+        panic!("unexpected DUMP reply {:?}", payload);
+    };
+    let restore: [&[u8]; 4] = [b"RESTORE", b"copy", b"0", &payload];
+    assert_eq!(client.command(&restore).await.unwrap(), ok());
+    let ttls = field_ttls(&mut client, "copy").await;
+    assert!(matches!(ttls[..], [99 | 100, -1]), "{:?}", ttls);
+    assert_eq!(client.command(&["HGET", "copy", "b"]).await.unwrap(), RespValue::BulkString(b"2".to_vec()));
+
+    server.shutdown().await.unwrap();
+}
+
+// listpack 한도는 서버마다 따로 가지므로 한 프로세스에서 함께 떠 있는 두 서버가 서로 다른 인코딩을 고름
+#[tokio::test]
+async fn servers_in_one_process_keep_their_own_listpack_limits() {
+    let small = TestServer::start_with(|builder| builder.option("hash-max-listpack-entries", "1")).await.unwrap();
+    let default = TestServer::start().await.unwrap();
+    for (server, expected) in [(&small, "hashtable"), (&default, "listpack")] {
+        let mut client = server.client().await.unwrap();
+        client.command(&["HSET", "hash", "a", "1", "b", "2"]).await.unwrap();
+        assert_eq!(client.command(&["OBJECT", "ENCODING", "hash"]).await.unwrap(), bulk(expected));
+    }
+
+    small.shutdown().await.unwrap();
+    default.shutdown().await.unwrap();
+}
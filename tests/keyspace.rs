@@ -0,0 +1,327 @@
+use redis_starter_rust::test_support::{bulk, error, ok, TestServer};
+use redis_starter_rust::{Client, RespValue};
+use std::time::Duration;
+
+const NOTIFICATION_TIMEOUT: Duration = Duration::from_secs(5);
+
+// 백그라운드 만료를 끄고 "live"와 이미 만료된 "dead:*" 키를 남김, 이후에는 나열하는 명령만 만료된 키를 지울 수 있음
+async fn keyspace_with_expired_keys(client: &mut Client) {
+    assert_eq!(client.command(&["DEBUG", "SET-ACTIVE-EXPIRE", "0"]).await.unwrap(), ok());
+    assert_eq!(client.command(&["SET", "live", "1"]).await.unwrap(), ok());
+    for key in ["dead:1", "dead:2", "dead:3"] {
+        assert_eq!(client.command(&["SET", key, "1", "PX", "50"]).await.unwrap(), ok());
+    }
+    tokio::time::sleep(Duration::from_millis(100)).await;
+}
+
+async fn expired_event(subscriber: &mut Client) -> RespValue {
+    let message = tokio::time::timeout(NOTIFICATION_TIMEOUT, subscriber.read_reply()).await.unwrap().unwrap();
+    let RespValue::Array(mut parts) = message else {
+        panic!("unexpected notification {:?}", message);
+    };
+    parts.pop().unwrap()
+}
+
+#[tokio::test]
+async fn enumerating_commands_hide_expired_keys() {
+    let server = TestServer::start().await.unwrap();
+    let mut client = server.client().await.unwrap();
+    keyspace_with_expired_keys(&mut client).await;
+
+    assert_eq!(client.command(&["KEYS", "*"]).await.unwrap(), RespValue::Array(vec![bulk("live")]));
+    assert_eq!(client.command(&["DBSIZE"]).await.unwrap(), RespValue::Integer(1));
+    for _ in 0..10 {
+        assert_eq!(client.command(&["RANDOMKEY"]).await.unwrap(), bulk("live"));
+    }
+    assert_eq!(
+        client.command(&["SCAN", "0", "COUNT", "100"]).await.unwrap(),
+        RespValue::Array(vec![bulk("0"), RespValue::Array(vec![bulk("live")])])
+    );
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn enumerating_commands_delete_the_expired_keys_they_meet() {
+    let server = TestServer::start().await.unwrap();
+    let mut client = server.client().await.unwrap();
+    keyspace_with_expired_keys(&mut client).await;
+    assert_eq!(client.command(&["CONFIG", "SET", "notify-keyspace-events", "Ex"]).await.unwrap(), ok());
+    let mut subscriber = server.client().await.unwrap();
+    subscriber.command(&["SUBSCRIBE", "__keyevent@0__:expired"]).await.unwrap();
+
+    // KEYS는 패턴에 맞은 키만, SCAN은 패턴과 상관없이 페이지에서 만난 키를 지움
+    assert_eq!(client.command(&["KEYS", "dead:1"]).await.unwrap(), RespValue::Array(vec![]));
+    assert_eq!(expired_event(&mut subscriber).await, bulk("dead:1"));
+    assert_eq!(
+        client.command(&["SCAN", "0", "MATCH", "live", "COUNT", "100"]).await.unwrap(),
+        RespValue::Array(vec![bulk("0"), RespValue::Array(vec![bulk("live")])])
+    );
+    let mut deleted = vec![expired_event(&mut subscriber).await, expired_event(&mut subscriber).await];
+    deleted.sort_by_key(|key| format!("{:?}", key));
+    assert_eq!(deleted, vec![bulk("dead:2"), bulk("dead:3")]);
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn keys_filters_with_glob_patterns() {
+    let server = TestServer::start().await.unwrap();
+    let mut client = server.client().await.unwrap();
+    for key in ["hello", "hallo", "hxllo", "heeello", "user:1", "user:22"] {
+        assert_eq!(client.command(&["SET", key, "1"]).await.unwrap(), ok());
+    }
+
+    let keys = |reply: RespValue| {
+        let RespValue::Array(keys) = reply else {
+            panic!("KEYS did not return an array: {:?}", reply);
+        };
+        let mut keys: Vec<Vec<u8>> = keys
+            .into_iter()
+            .map(|key| match key {
+                RespValue::BulkString(key) => key,
+                other => panic!("unexpected key {:?}", other),
+            })
+            .collect();
+        keys.sort();
+        keys.into_iter().map(RespValue::BulkString).collect::<Vec<_>>()
+    };
+    assert_eq!(keys(client.command(&["KEYS", "h?llo"]).await.unwrap()), vec![bulk("hallo"), bulk("hello"), bulk("hxllo")]);
+    assert_eq!(keys(client.command(&["KEYS", "h[ae]llo"]).await.unwrap()), vec![bulk("hallo"), bulk("hello")]);
+    assert_eq!(keys(client.command(&["KEYS", "h*llo"]).await.unwrap()).len(), 4);
+    assert_eq!(keys(client.command(&["KEYS", "user:?"]).await.unwrap()), vec![bulk("user:1")]);
+    assert_eq!(keys(client.command(&["KEYS", "nothing*"]).await.unwrap()), vec![]);
+    assert_eq!(keys(client.command(&["KEYS", "*"]).await.unwrap()).len(), 6);
+
+    server.shutdown().await.unwrap();
+}
+
+// DUMP payload의 버전/CRC는 맞게 붙이고 본문만 바꿔서, 길이 값만 잘못된 payload를 만듦
+fn payload_with_body(dump: &[u8], body: &[u8]) -> Vec<u8> {
+    let version = &dump[dump.len() - 10..dump.len() - 8];
+    let mut payload = [body, version].concat();
+    let checksum = crc::Crc::<u64>::new(&crc::CRC_64_REDIS).checksum(&payload);
+    payload.extend_from_slice(&checksum.to_le_bytes());
+    payload
+}
+
+#[tokio::test]
+async fn restore_rejects_lengths_past_the_payload() {
+    let server = TestServer::start().await.unwrap();
+    let mut client = server.client().await.unwrap();
+    assert_eq!(client.command(&["SET", "key", "value"]).await.unwrap(), ok());
+    let RespValue::BulkString(dump) = client.command(&["DUMP", "key"]).await.unwrap() else {
+        panic!("DUMP did not return a payload");
+    };
+    assert_eq!(client.command(&[&b"RESTORE"[..], b"copy", b"0", &dump]).await.unwrap(), ok());
+    assert_eq!(client.command(&["GET", "copy"]).await.unwrap(), bulk("value"));
+
+    // 원소가 2^64 - 1개인 set, 64비트 길이로 된 문자열, 32비트 길이로 된 문자열
+    let bodies: [&[u8]; 3] = [
+        &[0x02, 0x81, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x01, b'a'],
+        &[0x00, 0x81, 0x7F, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, b'a'],
+        &[0x00, 0x80, 0xFF, 0xFF, 0xFF, 0xFF, b'a'],
+    ];
+    for body in bodies {
+        let payload = payload_with_body(&dump, body);
+        assert_eq!(
+            client.command(&[&b"RESTORE"[..], b"bad", b"0", &payload]).await.unwrap(),
+            RespValue::Error("ERR Bad data format".into())
+        );
+    }
+    assert_eq!(client.command(&["EXISTS", "bad"]).await.unwrap(), RespValue::Integer(0));
+    assert_eq!(client.command(&["PING"]).await.unwrap(), RespValue::SimpleString("PONG".into()));
+
+    server.shutdown().await.unwrap();
+}
+
+// 능동 만료는 TTL이 있는 키의 색인에서 표본을 뽑으므로 TTL을 붙이고 떼는 모든 경로가 색인에 반영되어야 함
+#[tokio::test]
+async fn active_expire_finds_keys_through_every_ttl_change() {
+    let server = TestServer::start().await.unwrap();
+    let mut client = server.client().await.unwrap();
+    for i in 0..50 {
+        assert_eq!(client.command(&["SET", &format!("tmp:{}", i), "1", "PX", "100"]).await.unwrap(), ok());
+    }
+    assert_eq!(client.command(&["SET", "persisted", "1", "PX", "100"]).await.unwrap(), ok());
+    assert_eq!(client.command(&["PERSIST", "persisted"]).await.unwrap(), RespValue::Integer(1));
+    assert_eq!(client.command(&["SET", "overwritten", "1", "PX", "100"]).await.unwrap(), ok());
+    assert_eq!(client.command(&["SET", "overwritten", "2"]).await.unwrap(), ok());
+    assert_eq!(client.command(&["SET", "expired-later", "1"]).await.unwrap(), ok());
+    assert_eq!(client.command(&["PEXPIRE", "expired-later", "100"]).await.unwrap(), RespValue::Integer(1));
+
+    let deadline = tokio::time::Instant::now() + NOTIFICATION_TIMEOUT;
+    let mut stats = String::new();
+    while tokio::time::Instant::now() < deadline {
+        let RespValue::BulkString(info) = client.command(&["INFO", "stats"]).await.unwrap() else {
+            panic!("INFO did not return a bulk string");
+        };
+        stats = String::from_utf8_lossy(&info).into_owned();
+        if stats.contains("expired_keys:51\r\n") {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    assert!(stats.contains("expired_keys:51\r\n"), "{}", stats);
+    assert_eq!(client.command(&["DBSIZE"]).await.unwrap(), RespValue::Integer(2));
+    assert_eq!(client.command(&["TTL", "persisted"]).await.unwrap(), RespValue::Integer(-1));
+    assert_eq!(client.command(&["TTL", "overwritten"]).await.unwrap(), RespValue::Integer(-1));
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn expire_rejects_incompatible_conditions() {
+    let server = TestServer::start().await.unwrap();
+    let mut client = server.client().await.unwrap();
+    assert_eq!(client.command(&["SET", "key", "1"]).await.unwrap(), ok());
+
+    let nx_error = RespValue::Error("ERR NX and XX, GT or LT options at the same time are not compatible".into());
+    for command in ["EXPIRE", "PEXPIRE", "EXPIREAT", "PEXPIREAT"] {
+        for other in ["XX", "gt", "LT"] {
+            assert_eq!(client.command(&[command, "key", "100000", "NX", other]).await.unwrap(), nx_error, "{} NX {}", command, other);
+        }
+    }
+    // 옵션은 시간보다 먼저 확인함
+    assert_eq!(client.command(&["EXPIRE", "key", "soon", "XX", "NX"]).await.unwrap(), nx_error);
+    assert_eq!(
+        client.command(&["EXPIRE", "key", "100", "GT", "LT"]).await.unwrap(),
+        RespValue::Error("ERR GT and LT options at the same time are not compatible".into())
+    );
+    assert_eq!(client.command(&["EXPIRE", "key", "100", "FOO"]).await.unwrap(), RespValue::Error("ERR Unsupported option FOO".into()));
+    assert_eq!(client.command(&["TTL", "key"]).await.unwrap(), RespValue::Integer(-1));
+
+    // 함께 쓸 수 있는 조건은 모두 만족해야 적용됨
+    assert_eq!(client.command(&["EXPIRE", "key", "100", "XX"]).await.unwrap(), RespValue::Integer(0));
+    assert_eq!(client.command(&["EXPIRE", "key", "100", "NX"]).await.unwrap(), RespValue::Integer(1));
+    assert_eq!(client.command(&["EXPIRE", "key", "200", "NX"]).await.unwrap(), RespValue::Integer(0));
+    assert_eq!(client.command(&["EXPIRE", "key", "50", "XX", "GT"]).await.unwrap(), RespValue::Integer(0));
+    assert_eq!(client.command(&["EXPIRE", "key", "200", "XX", "GT"]).await.unwrap(), RespValue::Integer(1));
+    assert_eq!(client.command(&["EXPIRE", "key", "300", "LT"]).await.unwrap(), RespValue::Integer(0));
+    let ttl = client.command(&["TTL", "key"]).await.unwrap();
+    assert!(matches!(ttl, RespValue::Integer(199..=200)), "{:?}", ttl);
+
+    server.shutdown().await.unwrap();
+}
+
+// 클러스터가 아닌 두 인스턴스 사이의 MIGRATE는 RESTORE로 옮기고, 옮긴 키는 원본에서 지움
+#[tokio::test]
+async fn migrate_moves_keys_to_another_instance() {
+    let source = TestServer::start().await.unwrap();
+    let target = TestServer::start().await.unwrap();
+    let mut source_client = source.client().await.unwrap();
+    let mut target_client = target.client().await.unwrap();
+    let port = target.port().to_string();
+
+    source_client.command(&["SET", "plain", "1"]).await.unwrap();
+    source_client.command(&["SET", "expiring", "2", "PX", "100000"]).await.unwrap();
+    source_client.command(&["HSET", "hash", "field", "value"]).await.unwrap();
+    assert_eq!(source_client.command(&["MIGRATE", "127.0.0.1", &port, "plain", "0", "5000"]).await.unwrap(), ok());
+    assert_eq!(source_client.command(&["EXISTS", "plain"]).await.unwrap(), RespValue::Integer(0));
+    assert_eq!(target_client.command(&["GET", "plain"]).await.unwrap(), bulk("1"));
+
+    // COPY는 원본을 남기고, 대상에 이미 있는 키는 REPLACE 없이는 덮어쓰지 않음
+    let keys = ["MIGRATE", "127.0.0.1", &port, "", "0", "5000", "COPY", "KEYS", "expiring", "hash", "missing"];
+    assert_eq!(source_client.command(&keys).await.unwrap(), ok());
+    assert_eq!(source_client.command(&["EXISTS", "expiring", "hash"]).await.unwrap(), RespValue::Integer(2));
+    let RespValue::Integer(ttl) = target_client.command(&["PTTL", "expiring"]).await.unwrap() else {
+        panic!("PTTL did not return an integer");
+    };
+    assert!(ttl > 0 && ttl <= 100000, "{}", ttl);
+    assert_eq!(target_client.command(&["HGET", "hash", "field"]).await.unwrap(), bulk("value"));
+    let RespValue::Error(message) = source_client.command(&["MIGRATE", "127.0.0.1", &port, "hash", "0", "5000"]).await.unwrap() else {
+        panic!("MIGRATE overwrote an existing key");
+    };
+    assert_eq!(message, "ERR Target instance replied with error: BUSYKEY Target key name already exists.");
+    assert_eq!(source_client.command(&["EXISTS", "hash"]).await.unwrap(), RespValue::Integer(1));
+    assert_eq!(source_client.command(&["MIGRATE", "127.0.0.1", &port, "hash", "0", "5000", "REPLACE"]).await.unwrap(), ok());
+    assert_eq!(source_client.command(&["EXISTS", "hash"]).await.unwrap(), RespValue::Integer(0));
+
+    for (args, expected) in [
+        (&["MIGRATE", "127.0.0.1", &port, "missing", "0", "5000"][..], RespValue::SimpleString("NOKEY".into())),
+        (&["MIGRATE", "127.0.0.1", &port, "key", "0", "5000", "KEYS", "a"], RespValue::Error("ERR When using MIGRATE KEYS option, the key argument must be set to the empty string".into())),
+        (&["MIGRATE", "127.0.0.1", &port, "key", "1", "5000"], RespValue::Error("ERR DB index is out of range".into())),
+        (&["MIGRATE", "127.0.0.1", &port, "key", "0", "5000", "AUTH"], RespValue::Error("ERR syntax error".into())),
+    ] {
+        assert_eq!(source_client.command(args).await.unwrap(), expected, "{:?}", args);
+    }
+    // 대상 서버가 없으면 IOERR
+    source_client.command(&["SET", "plain", "1"]).await.unwrap();
+    target.shutdown().await.unwrap();
+    let RespValue::Error(message) = source_client.command(&["MIGRATE", "127.0.0.1", &port, "plain", "0", "500"]).await.unwrap() else {
+        panic!("MIGRATE to a stopped server succeeded");
+    };
+    assert!(message.starts_with("IOERR"), "{}", message);
+    assert_eq!(source_client.command(&["GET", "plain"]).await.unwrap(), bulk("1"));
+
+    source.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn touch_counts_existing_keys_and_refreshes_idle_time() {
+    let server = TestServer::start().await.unwrap();
+    let mut client = server.client().await.unwrap();
+
+    assert_eq!(client.command(&["SET", "a", "1"]).await.unwrap(), ok());
+    assert_eq!(client.command(&["SET", "b", "2"]).await.unwrap(), ok());
+    assert_eq!(client.command(&["SET", "gone", "3", "PX", "10"]).await.unwrap(), ok());
+    tokio::time::sleep(Duration::from_millis(2100)).await;
+
+    // 만료된 키와 없는 키는 세지 않고, 같은 키를 여러 번 주면 그만큼 셈
+    assert_eq!(client.command(&["TOUCH", "a", "missing", "gone", "a"]).await.unwrap(), RespValue::Integer(2));
+
+    // OBJECT IDLETIME은 접근 시각을 바꾸지 않으므로 b만 그대로 늙어 있음
+    assert_eq!(client.command(&["OBJECT", "IDLETIME", "a"]).await.unwrap(), RespValue::Integer(0));
+    let RespValue::Integer(idle) = client.command(&["OBJECT", "IDLETIME", "b"]).await.unwrap() else {
+        panic!("OBJECT IDLETIME did not return an integer");
+    };
+    assert!(idle >= 2, "idle time was {}", idle);
+    assert_eq!(client.command(&["TOUCH", "b"]).await.unwrap(), RespValue::Integer(1));
+    assert_eq!(client.command(&["OBJECT", "IDLETIME", "b"]).await.unwrap(), RespValue::Integer(0));
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn touch_bumps_the_lfu_counter() {
+    let server = TestServer::start_with(|builder| builder.option("maxmemory-policy", "allkeys-lfu")).await.unwrap();
+    let mut client = server.client().await.unwrap();
+
+    assert_eq!(client.command(&["SET", "key", "value"]).await.unwrap(), ok());
+    // 새 키는 LFU_INIT_VAL(5)에서 시작하고, 그 값에서는 접근할 때마다 반드시 한 번 오름
+    assert_eq!(client.command(&["OBJECT", "FREQ", "key"]).await.unwrap(), RespValue::Integer(5));
+    assert_eq!(client.command(&["TOUCH", "key"]).await.unwrap(), RespValue::Integer(1));
+    assert_eq!(client.command(&["OBJECT", "FREQ", "key"]).await.unwrap(), RespValue::Integer(6));
+    assert!(matches!(client.command(&["OBJECT", "IDLETIME", "key"]).await.unwrap(), RespValue::Error(_)));
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn object_help_and_missing_keys() {
+    let server = TestServer::start().await.unwrap();
+    let mut client = server.client().await.unwrap();
+
+    let RespValue::Array(lines) = client.command(&["OBJECT", "HELP"]).await.unwrap() else {
+        panic!("OBJECT HELP did not return an array");
+    };
+    for subcommand in ["ENCODING <key>", "FREQ <key>", "IDLETIME <key>", "REFCOUNT <key>", "HELP"] {
+        assert!(lines.contains(&RespValue::SimpleString(subcommand.into())), "{} is missing from {:?}", subcommand, lines);
+    }
+    assert_eq!(
+        client.command(&["OBJECT", "HELP", "key"]).await.unwrap(),
+        error("ERR wrong number of arguments for 'object|help' command")
+    );
+
+    // 없는 키는 에러가 아니라 nil
+    assert_eq!(client.command(&["OBJECT", "ENCODING", "missing"]).await.unwrap(), RespValue::NullBulk);
+
+    // 기본 정책(noeviction)에서는 LFU 카운터를 쓰지 않으므로 FREQ는 에러
+    assert_eq!(client.command(&["SET", "key", "value"]).await.unwrap(), ok());
+    assert_eq!(
+        client.command(&["OBJECT", "FREQ", "key"]).await.unwrap(),
+        error("ERR An LFU maxmemory policy is not selected, access frequency not tracked. Please note that when switching between policies at runtime LRU and LFU data will take some time to adjust.")
+    );
+
+    server.shutdown().await.unwrap();
+}
@@ -0,0 +1,83 @@
+use redis_starter_rust::test_support::{ok, TestServer};
+use redis_starter_rust::{Client, RespValue};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// 이벤트 루프를 30ms 붙잡음
+async fn run_slow_command(client: &mut Client) {
+    assert_eq!(client.command(&["DEBUG", "SLEEP", "0.03"]).await.unwrap(), ok());
+}
+
+fn integer(value: &RespValue) -> i64 {
+    match value {
+        RespValue::Integer(value) => *value,
+        other => panic!("expected an integer, got {:?}", other),
+    }
+}
+
+fn array(value: RespValue) -> Vec<RespValue> {
+    match value {
+        RespValue::Array(items) => items,
+        other => panic!("expected an array, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn nothing_is_recorded_below_the_threshold_or_when_disabled() {
+    let server = TestServer::start().await.unwrap();
+    let mut client = server.client().await.unwrap();
+
+    // 기본값 0은 모니터링을 끔
+    run_slow_command(&mut client).await;
+    assert_eq!(client.command(&["LATENCY", "LATEST"]).await.unwrap(), RespValue::Array(vec![]));
+
+    assert_eq!(client.command(&["CONFIG", "SET", "latency-monitor-threshold", "10000"]).await.unwrap(), ok());
+    run_slow_command(&mut client).await;
+    assert_eq!(client.command(&["LATENCY", "LATEST"]).await.unwrap(), RespValue::Array(vec![]));
+    assert_eq!(client.command(&["LATENCY", "HISTORY", "command"]).await.unwrap(), RespValue::Array(vec![]));
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn spikes_over_the_threshold_show_in_latest_and_history() {
+    let server = TestServer::start_with(|builder| builder.option("latency-monitor-threshold", "10")).await.unwrap();
+    let mut client = server.client().await.unwrap();
+
+    run_slow_command(&mut client).await;
+    let latest = array(client.command(&["LATENCY", "LATEST"]).await.unwrap());
+    let command_event = latest
+        .into_iter()
+        .map(array)
+        .find(|event| event[0] == RespValue::BulkString(b"command".to_vec()))
+        .expect("command event was not recorded");
+    // [이벤트, 마지막 시각, 마지막 값(ms), 최댓값(ms)]
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64;
+    assert!((now - integer(&command_event[1])).abs() <= 2, "{:?}", command_event);
+    assert!(integer(&command_event[2]) >= 30, "{:?}", command_event);
+    assert_eq!(command_event[3], command_event[2]);
+
+    let history = array(client.command(&["LATENCY", "HISTORY", "command"]).await.unwrap());
+    assert!(!history.is_empty());
+    let last = array(history.last().unwrap().clone());
+    assert_eq!(last, vec![command_event[1].clone(), command_event[2].clone()]);
+    assert_eq!(client.command(&["LATENCY", "HISTORY", "no-such-event"]).await.unwrap(), RespValue::Array(vec![]));
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn reset_drops_the_named_or_all_events() {
+    let server = TestServer::start_with(|builder| builder.option("latency-monitor-threshold", "10")).await.unwrap();
+    let mut client = server.client().await.unwrap();
+
+    run_slow_command(&mut client).await;
+    assert_eq!(client.command(&["LATENCY", "RESET", "no-such-event"]).await.unwrap(), RespValue::Integer(0));
+    assert_eq!(client.command(&["LATENCY", "RESET", "command"]).await.unwrap(), RespValue::Integer(1));
+    assert_eq!(client.command(&["LATENCY", "HISTORY", "command"]).await.unwrap(), RespValue::Array(vec![]));
+
+    run_slow_command(&mut client).await;
+    assert!(integer(&client.command(&["LATENCY", "RESET"]).await.unwrap()) >= 1);
+    assert_eq!(client.command(&["LATENCY", "LATEST"]).await.unwrap(), RespValue::Array(vec![]));
+
+    server.shutdown().await.unwrap();
+}
@@ -0,0 +1,181 @@
+use redis_starter_rust::test_support::{bulk, TestServer};
+use redis_starter_rust::{Client, RespValue};
+
+// Redis의 tests/unit/scripting.tcl에 있는 EVAL 케이스를 옮겨 온 것, 기대값은 Redis 7이 돌려주는 값 그대로임
+
+async fn eval(client: &mut Client, script: &str, keys: &[&str]) -> RespValue {
+    let numkeys = keys.len().to_string();
+    let mut args = vec!["EVAL", script, numkeys.as_str()];
+    args.extend_from_slice(keys);
+    client.command(&args).await.unwrap()
+}
+
+async fn eval_error(client: &mut Client, script: &str, keys: &[&str]) -> String {
+    match eval(client, script, keys).await {
+        RespValue::Error(message) => message,
+        reply => panic!("{} did not fail: {:?}", script, reply),
+    }
+}
+
+#[tokio::test]
+async fn lua_values_convert_to_redis_replies_like_redis() {
+    let server = TestServer::start().await.unwrap();
+    let mut client = server.client().await.unwrap();
+
+    assert_eq!(eval(&mut client, "return 100.5", &[]).await, RespValue::Integer(100));
+    assert_eq!(eval(&mut client, "return -3.9", &[]).await, RespValue::Integer(-3));
+    assert_eq!(eval(&mut client, "return 'hello world'", &[]).await, bulk("hello world"));
+    assert_eq!(eval(&mut client, "return true", &[]).await, RespValue::Integer(1));
+    assert_eq!(eval(&mut client, "return false", &[]).await, RespValue::NullBulk);
+    assert_eq!(eval(&mut client, "return nil", &[]).await, RespValue::NullBulk);
+    assert_eq!(eval(&mut client, "return {ok='fine'}", &[]).await, RespValue::SimpleString("fine".into()));
+    assert_eq!(eval(&mut client, "return {err='ERR this is an error'}", &[]).await, RespValue::Error("ERR this is an error".into()));
+    assert_eq!(
+        eval(&mut client, "return {1,2,3,'ciao',{1,2}}", &[]).await,
+        RespValue::Array(vec![
+            RespValue::Integer(1),
+            RespValue::Integer(2),
+            RespValue::Integer(3),
+            bulk("ciao"),
+            RespValue::Array(vec![RespValue::Integer(1), RespValue::Integer(2)]),
+        ])
+    );
+    assert_eq!(
+        eval(&mut client, "return {KEYS[1],KEYS[2],ARGV[1],ARGV[2]}", &["a{t}", "b{t}"]).await,
+        RespValue::Array(vec![bulk("a{t}"), bulk("b{t}")])
+    );
+    assert_eq!(
+        client.command(&["EVAL", "return {KEYS[1],KEYS[2],ARGV[1],ARGV[2]}", "2", "a{t}", "b{t}", "c{t}", "d{t}"]).await.unwrap(),
+        RespValue::Array(vec![bulk("a{t}"), bulk("b{t}"), bulk("c{t}"), bulk("d{t}")])
+    );
+    assert_eq!(eval(&mut client, "return 'hello' --trailing comment", &[]).await, bulk("hello"));
+    // 키가 없는 테이블은 빈 배열
+    assert_eq!(eval(&mut client, "return {foo='bar'}", &[]).await, RespValue::Array(Vec::new()));
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn redis_replies_convert_to_lua_values_like_redis() {
+    let server = TestServer::start().await.unwrap();
+    let mut client = server.client().await.unwrap();
+
+    assert_eq!(
+        eval(&mut client, "local foo = redis.pcall('incr',KEYS[1]); return {type(foo),foo}", &["mykey"]).await,
+        RespValue::Array(vec![bulk("number"), RespValue::Integer(1)])
+    );
+    client.command(&["SET", "mykey", "myval"]).await.unwrap();
+    assert_eq!(
+        eval(&mut client, "local foo = redis.pcall('get',KEYS[1]); return {type(foo),foo}", &["mykey"]).await,
+        RespValue::Array(vec![bulk("string"), bulk("myval")])
+    );
+    client.command(&["RPUSH", "mylist", "a", "b", "c"]).await.unwrap();
+    assert_eq!(
+        eval(&mut client, "local foo = redis.pcall('lpop',KEYS[1],3); return {type(foo),foo[1],foo[2],foo[3],# foo}", &["mylist"]).await,
+        RespValue::Array(vec![bulk("table"), bulk("a"), bulk("b"), bulk("c"), RespValue::Integer(3)])
+    );
+    assert_eq!(
+        eval(&mut client, "local foo = redis.pcall('set',KEYS[1],'myval'); return {type(foo),foo['ok']}", &["mykey"]).await,
+        RespValue::Array(vec![bulk("table"), bulk("OK")])
+    );
+    assert_eq!(
+        eval(&mut client, "local foo = redis.pcall('incr',KEYS[1]); return {type(foo),foo['err']}", &["mykey"]).await,
+        RespValue::Array(vec![bulk("table"), bulk("ERR value is not an integer or out of range")])
+    );
+    client.command(&["DEL", "mykey"]).await.unwrap();
+    assert_eq!(
+        eval(&mut client, "local foo = redis.pcall('get',KEYS[1]); return {type(foo),foo == false}", &["mykey"]).await,
+        RespValue::Array(vec![bulk("boolean"), RespValue::Integer(1)])
+    );
+
+    // 정밀도 회귀 케이스 (Redis issue #1118)
+    assert_eq!(
+        eval(&mut client, "local value = 9007199254740991; redis.call('set','foo',value); return redis.call('get','foo')", &[]).await,
+        bulk("9007199254740991")
+    );
+    assert_eq!(
+        eval(&mut client, "redis.call('set', 'key', '12039611435714932082'); return redis.call('get', 'key')", &[]).await,
+        bulk("12039611435714932082")
+    );
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn redis_call_errors_match_redis() {
+    let server = TestServer::start().await.unwrap();
+    let mut client = server.client().await.unwrap();
+
+    let message = eval_error(&mut client, "return redis.call('nosuchcommand')", &[]).await;
+    assert!(message.starts_with("ERR Unknown Redis command called from script"), "{}", message);
+    let message = eval_error(&mut client, "return redis.call('get','a','b','c')", &[]).await;
+    assert!(message.starts_with("ERR Wrong number of args calling Redis command from script"), "{}", message);
+    let message = eval_error(&mut client, "redis.call('set','invalid')", &[]).await;
+    assert!(message.starts_with("ERR Wrong number of args calling Redis command from script"), "{}", message);
+    client.command(&["SET", "foo", "bar"]).await.unwrap();
+    let message = eval_error(&mut client, "return redis.call('lpush',KEYS[1],'val')", &["foo"]).await;
+    assert!(message.starts_with("WRONGTYPE Operation against a key holding the wrong kind of value script:"), "{}", message);
+    let message = eval_error(&mut client, "return redis.pcall()", &[]).await;
+    assert!(message.starts_with("ERR Please specify at least one argument for this redis lib call"), "{}", message);
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn script_sandbox_matches_redis() {
+    let server = TestServer::start().await.unwrap();
+    let mut client = server.client().await.unwrap();
+
+    assert_eq!(eval(&mut client, "return redis.sha1hex('')", &[]).await, bulk("da39a3ee5e6b4b0d3255bfef95601890afd80709"));
+    assert_eq!(eval(&mut client, "return redis.sha1hex('Pizza & Mandolino')", &[]).await, bulk("74822d82031af7493c20eefa13bd07ec4fada82f"));
+
+    // 전역 변수는 만들 수 없고, 없는 전역 변수를 읽는 것도 에러
+    let message = eval_error(&mut client, "a = 10", &[]).await;
+    assert!(message.contains("Attempt to modify a readonly table"), "{}", message);
+    let message = eval_error(&mut client, "return a", &[]).await;
+    assert!(message.contains("Script attempted to access nonexistent global variable 'a'"), "{}", message);
+    // 파일과 코드 로딩 함수는 열려 있지 않음
+    for function in ["print", "dofile", "loadfile"] {
+        let message = eval_error(&mut client, &format!("{}('x')", function), &[]).await;
+        assert!(message.contains(&format!("Script attempted to access nonexistent global variable '{}'", function)), "{}", message);
+    }
+
+    let message = eval_error(&mut client, "return unpack({1,2,3}, 0, 2147483647)", &[]).await;
+    assert!(message.contains("too many results to unpack"), "{}", message);
+    // 자기 자신을 담은 테이블은 중첩 제한까지 배열로 내려가다 맨 안쪽에 에러를 둠
+    let mut reply = eval(&mut client, "local a = {}; local b = {a}; a[1] = b; return a", &[]).await;
+    while let RespValue::Array(mut items) = reply {
+        assert_eq!(items.len(), 1);
+        reply = items.remove(0);
+    }
+    assert_eq!(reply, RespValue::Error("ERR reached lua stack limit".into()));
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn standard_library_subset_behaves_like_lua_5_1() {
+    let server = TestServer::start().await.unwrap();
+    let mut client = server.client().await.unwrap();
+
+    assert_eq!(eval(&mut client, "return string.format('%d %s %5.2f', 7, 'x', 3.14159)", &[]).await, bulk("7 x  3.14"));
+    assert_eq!(eval(&mut client, "return string.rep('ab', 3)", &[]).await, bulk("ababab"));
+    assert_eq!(eval(&mut client, "return (string.gsub('hello world', 'o', '0'))", &[]).await, bulk("hell0 w0rld"));
+    assert_eq!(eval(&mut client, "return string.match('key:123', '(%d+)')", &[]).await, bulk("123"));
+    assert_eq!(eval(&mut client, "return table.concat({'a','b','c'}, ',')", &[]).await, bulk("a,b,c"));
+    assert_eq!(
+        eval(&mut client, "local t = {3,1,2}; table.sort(t); return t", &[]).await,
+        RespValue::Array(vec![RespValue::Integer(1), RespValue::Integer(2), RespValue::Integer(3)])
+    );
+    assert_eq!(eval(&mut client, "return tostring(10/4)", &[]).await, bulk("2.5"));
+    assert_eq!(eval(&mut client, "return tonumber('0x10')", &[]).await, RespValue::Integer(16));
+    assert_eq!(eval(&mut client, "return math.floor(-1.5)", &[]).await, RespValue::Integer(-2));
+    assert_eq!(eval(&mut client, "local n = 0; for k, v in pairs({a=1, b=2}) do n = n + v end; return n", &[]).await, RespValue::Integer(3));
+    assert_eq!(eval(&mut client, "return select('#', 1, nil, 3)", &[]).await, RespValue::Integer(3));
+    assert_eq!(
+        eval(&mut client, "local ok, err = pcall(error, {code=1}); return {tostring(ok), err.code}", &[]).await,
+        RespValue::Array(vec![bulk("false"), RespValue::Integer(1)])
+    );
+
+    server.shutdown().await.unwrap();
+}
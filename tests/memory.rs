@@ -0,0 +1,120 @@
+use redis_starter_rust::test_support::{bulk, info_field, ok, TestServer};
+use redis_starter_rust::{Client, RespValue};
+
+// 엔트리 헤더 64 + 키스페이스 색인 104, TTL이 있으면 만료 색인 48을 더 씀
+const KEY_OVERHEAD: i64 = 64 + 104;
+const VOLATILE_OVERHEAD: i64 = 48;
+
+const MB: usize = 1024 * 1024;
+
+async fn usage(client: &mut Client, args: &[&str]) -> RespValue {
+    client.command(&[&["MEMORY", "USAGE"], args].concat()).await.unwrap()
+}
+
+// RESP2에서 MEMORY STATS는 이름과 값을 번갈아 담은 배열
+async fn stats_field(client: &mut Client, name: &str) -> i64 {
+    let RespValue::Array(fields) = client.command(&["MEMORY", "STATS"]).await.unwrap() else {
+        panic!("MEMORY STATS did not return an array");
+    };
+    let value = fields.chunks(2).find(|pair| pair[0] == bulk(name)).map(|pair| pair[1].clone());
+    match value {
+        Some(RespValue::Integer(value)) => value,
+        other => panic!("{} was {:?}", name, other),
+    }
+}
+
+async fn doctor(client: &mut Client) -> String {
+    let RespValue::BulkString(report) = client.command(&["MEMORY", "DOCTOR"]).await.unwrap() else {
+        panic!("MEMORY DOCTOR did not return a bulk string");
+    };
+    String::from_utf8(report).unwrap()
+}
+
+#[tokio::test]
+async fn usage_counts_the_key_its_indexes_and_the_value() {
+    let server = TestServer::start().await.unwrap();
+    let mut client = server.client().await.unwrap();
+
+    assert_eq!(usage(&mut client, &["missing"]).await, RespValue::NullBulk);
+
+    // embstr "value"는 5바이트
+    assert_eq!(client.command(&["SET", "key", "value"]).await.unwrap(), ok());
+    assert_eq!(usage(&mut client, &["key"]).await, RespValue::Integer(KEY_OVERHEAD + 3 + 5));
+    assert_eq!(usage(&mut client, &["key", "SAMPLES", "0"]).await, RespValue::Integer(KEY_OVERHEAD + 3 + 5));
+
+    // 키 이름은 한 번만 셈
+    assert_eq!(client.command(&["SET", "longer-key", "value"]).await.unwrap(), ok());
+    assert_eq!(usage(&mut client, &["longer-key"]).await, RespValue::Integer(KEY_OVERHEAD + 10 + 5));
+
+    // TTL을 붙이면 만료 색인 몫이 늘고 PERSIST로 다시 줄어듦
+    assert_eq!(client.command(&["EXPIRE", "key", "100"]).await.unwrap(), RespValue::Integer(1));
+    assert_eq!(usage(&mut client, &["key"]).await, RespValue::Integer(KEY_OVERHEAD + VOLATILE_OVERHEAD + 3 + 5));
+    assert_eq!(client.command(&["PERSIST", "key"]).await.unwrap(), RespValue::Integer(1));
+    assert_eq!(usage(&mut client, &["key"]).await, RespValue::Integer(KEY_OVERHEAD + 3 + 5));
+
+    // 정수로 담긴 값은 따로 할당하지 않음
+    assert_eq!(client.command(&["SET", "n", "12345"]).await.unwrap(), ok());
+    assert_eq!(usage(&mut client, &["n"]).await, RespValue::Integer(KEY_OVERHEAD + 1));
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn stats_split_used_memory_into_overhead_and_dataset() {
+    let server = TestServer::start().await.unwrap();
+    let mut client = server.client().await.unwrap();
+
+    assert_eq!(stats_field(&mut client, "keys.count").await, 0);
+    assert_eq!(stats_field(&mut client, "total.allocated").await, 0);
+
+    for index in 0..10 {
+        let key = format!("key:{}", index);
+        assert_eq!(client.command(&["SET", &key, "value"]).await.unwrap(), ok());
+    }
+    assert_eq!(client.command(&["SET", "expiring", "value", "EX", "100"]).await.unwrap(), ok());
+
+    let total = stats_field(&mut client, "total.allocated").await;
+    let overhead = stats_field(&mut client, "overhead.total").await;
+    let dataset = stats_field(&mut client, "dataset.bytes").await;
+    assert_eq!(stats_field(&mut client, "keys.count").await, 11);
+    assert_eq!(info_field(&mut client, "memory", "used_memory").await, Some(total.to_string()));
+    assert_eq!(overhead + dataset, total);
+
+    // 값은 "value" 11개뿐이고 나머지는 키 이름, 엔트리 헤더, 색인
+    assert_eq!(dataset, 11 * 5);
+    assert_eq!(overhead, 11 * KEY_OVERHEAD + 10 * 5 + 8 + VOLATILE_OVERHEAD);
+    assert_eq!(stats_field(&mut client, "keys.bytes-per-key").await, total / 11);
+    assert!(stats_field(&mut client, "peak.allocated").await >= total);
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn doctor_reports_small_instances_peaks_and_maxmemory() {
+    let server = TestServer::start().await.unwrap();
+    let mut client = server.client().await.unwrap();
+    let value = "x".repeat(MB);
+
+    assert!(doctor(&mut client).await.starts_with("This instance is empty or is using very little memory"));
+
+    for index in 0..12 {
+        assert_eq!(client.command(&["SET", &format!("big:{}", index), &value]).await.unwrap(), ok());
+    }
+    assert_eq!(doctor(&mut client).await, "No memory issue was found in this instance.\r\n");
+
+    // 절반을 지우면 지금 사용량보다 피크가 1.5배 넘게 큼
+    for index in 0..6 {
+        assert_eq!(client.command(&["DEL", &format!("big:{}", index)]).await.unwrap(), RespValue::Integer(1));
+    }
+    let report = doctor(&mut client).await;
+    assert!(report.starts_with("The following memory issues were found in this instance:"), "{}", report);
+    assert!(report.contains("- Peak memory: in the past this instance used 12.00M"), "{}", report);
+    assert!(!report.contains("Near maxmemory"), "{}", report);
+
+    let used: u64 = info_field(&mut client, "memory", "used_memory").await.unwrap().parse().unwrap();
+    assert_eq!(client.command(&["CONFIG", "SET", "maxmemory", &(used + used / 20).to_string()]).await.unwrap(), ok());
+    let report = doctor(&mut client).await;
+    assert!(report.contains("- Near maxmemory: this instance uses 6.00M"), "{}", report);
+
+    server.shutdown().await.unwrap();
+}
@@ -0,0 +1,86 @@
+use std::fs;
+use std::net::TcpListener;
+use std::os::unix::fs::PermissionsExt;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+fn preflight(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_redis-starter-rust")).arg("--preflight").args(args).output().unwrap()
+}
+
+fn report(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+// 테스트마다 새로 만든 빈 디렉터리, 임시 디렉터리에 남은 dump.rdb가 rdb 검사에 걸리지 않게 함
+fn empty_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("redis-test-{}-{}", std::process::id(), name));
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+// 쓰기 권한을 뺀 디렉터리, root는 권한 비트를 무시하므로 그때는 파일을 만들 수 없는 /proc을 씀
+fn unwritable_dir() -> PathBuf {
+    let dir = empty_dir("readonly");
+    fs::set_permissions(&dir, fs::Permissions::from_mode(0o555)).unwrap();
+    let probe = dir.join("probe");
+    if fs::write(&probe, b"").is_ok() {
+        fs::remove_file(&probe).unwrap();
+        fs::remove_dir(&dir).unwrap();
+        return PathBuf::from("/proc");
+    }
+    dir
+}
+
+// 빈 포트를 하나 받아 두고 리스너를 닫음
+fn free_port() -> u16 {
+    TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port()
+}
+
+#[test]
+fn preflight_fails_on_an_unwritable_dir() {
+    let dir = unwritable_dir();
+    let port = free_port().to_string();
+    let output = preflight(&["--dir", dir.to_str().unwrap(), "--port", &port]);
+    let report = report(&output);
+
+    assert_eq!(output.status.code(), Some(1), "{}", report);
+    assert!(report.contains("preflight report"), "{}", report);
+    let line = report.lines().find(|line| line.contains("] dir ")).unwrap_or_else(|| panic!("no dir line in\n{}", report));
+    assert!(line.starts_with("  [fatal] dir"), "{}", line);
+    assert!(line.contains(&format!("{} is not writable", dir.display())), "{}", line);
+    assert!(report.contains(&format!("  [ok   ] port       127.0.0.1:{} is available", port)), "{}", report);
+    assert!(report.contains("result: 1 fatal"), "{}", report);
+
+    if dir.starts_with(std::env::temp_dir()) {
+        fs::remove_dir(&dir).unwrap();
+    }
+}
+
+#[test]
+fn preflight_fails_on_an_occupied_port() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let dir = empty_dir("occupied-port");
+    let output = preflight(&["--dir", dir.to_str().unwrap(), "--port", &port.to_string()]);
+    let report = report(&output);
+
+    assert_eq!(output.status.code(), Some(1), "{}", report);
+    assert!(report.contains(&format!("  [fatal] port       127.0.0.1:{} is not available", port)), "{}", report);
+    assert!(report.contains(&format!("  [ok   ] dir        {} is writable", dir.display())), "{}", report);
+    assert!(report.contains("result: 1 fatal"), "{}", report);
+    drop(listener);
+    fs::remove_dir(&dir).unwrap();
+}
+
+#[test]
+fn preflight_passes_when_everything_is_available() {
+    let dir = empty_dir("available");
+    let port = free_port().to_string();
+    let output = preflight(&["--dir", dir.to_str().unwrap(), "--port", &port]);
+    let report = report(&output);
+
+    assert_eq!(output.status.code(), Some(0), "{}", report);
+    assert!(report.contains("result: 0 fatal"), "{}", report);
+    fs::remove_dir(&dir).unwrap();
+}
@@ -0,0 +1,78 @@
+use redis_starter_rust::test_support::{error, ok, TestServer};
+use redis_starter_rust::{Client, RespValue};
+use std::net::{IpAddr, SocketAddr, UdpSocket};
+
+// 이 호스트의 루프백이 아닌 주소, UDP 소켓을 연결만 해서 라우팅이 고른 출발 주소를 얻음(패킷은 보내지 않음)
+// 루프백밖에 없는 호스트에서는 None이라 외부 연결을 흉내 낼 수 없음
+fn external_ip() -> Option<IpAddr> {
+    let socket = UdpSocket::bind("0.0.0.0:0").ok()?;
+    socket.connect("192.0.2.1:9").ok()?;
+    Some(socket.local_addr().ok()?.ip()).filter(|ip| !ip.is_loopback() && !ip.is_unspecified())
+}
+
+async fn start_on_all_interfaces() -> TestServer {
+    TestServer::start_with(|builder| builder.option("bind", "0.0.0.0")).await.unwrap()
+}
+
+async fn external_client(server: &TestServer, ip: IpAddr) -> Client {
+    Client::connect(SocketAddr::new(ip, server.port())).await.unwrap()
+}
+
+fn is_denied(reply: &RespValue) -> bool {
+    matches!(reply, RespValue::Error(message) if message.starts_with("DENIED Redis is running in protected mode"))
+}
+
+#[tokio::test]
+async fn protected_mode_denies_external_clients_without_a_password() {
+    let Some(ip) = external_ip() else {
+        eprintln!("skipping: no non-loopback address to connect from");
+        return;
+    };
+    let server = start_on_all_interfaces().await;
+
+    // 서버가 연결하자마자 DENIED를 보내고 닫으므로 명령을 보내지 않고 읽음
+    let mut external = external_client(&server, ip).await;
+    let reply = external.read_reply().await.unwrap();
+    assert!(is_denied(&reply), "{:?}", reply);
+
+    // bind가 모든 인터페이스여도 루프백 연결은 그대로 받음
+    let mut local = server.client().await.unwrap();
+    assert_eq!(local.command(&["PING"]).await.unwrap(), RespValue::SimpleString("PONG".into()));
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn disabling_protected_mode_admits_external_clients() {
+    let Some(ip) = external_ip() else {
+        eprintln!("skipping: no non-loopback address to connect from");
+        return;
+    };
+    let server = start_on_all_interfaces().await;
+    let mut local = server.client().await.unwrap();
+    assert_eq!(local.command(&["CONFIG", "SET", "protected-mode", "no"]).await.unwrap(), ok());
+
+    let mut external = external_client(&server, ip).await;
+    assert_eq!(external.command(&["PING"]).await.unwrap(), RespValue::SimpleString("PONG".into()));
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn a_default_user_password_admits_external_clients() {
+    let Some(ip) = external_ip() else {
+        eprintln!("skipping: no non-loopback address to connect from");
+        return;
+    };
+    let server = start_on_all_interfaces().await;
+    let mut local = server.client().await.unwrap();
+    assert_eq!(local.command(&["CONFIG", "SET", "requirepass", "secret"]).await.unwrap(), ok());
+
+    // 거절되지 않고 연결되지만 인증은 해야 함
+    let mut external = external_client(&server, ip).await;
+    assert_eq!(external.command(&["PING"]).await.unwrap(), error("NOAUTH Authentication required."));
+    assert_eq!(external.command(&["AUTH", "secret"]).await.unwrap(), ok());
+    assert_eq!(external.command(&["PING"]).await.unwrap(), RespValue::SimpleString("PONG".into()));
+
+    server.shutdown().await.unwrap();
+}
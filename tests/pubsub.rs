@@ -0,0 +1,231 @@
+use redis_starter_rust::test_support::{bulk, ok, TestServer};
+use redis_starter_rust::{Client, RespValue};
+use std::time::Duration;
+
+const REPLICATION_TIMEOUT: Duration = Duration::from_secs(5);
+
+async fn wait_for_key(client: &mut Client, key: &str) {
+    let deadline = tokio::time::Instant::now() + REPLICATION_TIMEOUT;
+    while client.command(&["GET", key]).await.unwrap() == RespValue::NullBulk {
+        assert!(tokio::time::Instant::now() < deadline, "{} was not replicated in time", key);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+}
+
+#[tokio::test]
+async fn resp2_subscriber_only_accepts_subscription_commands() {
+    let server = TestServer::start().await.unwrap();
+    let mut client = server.client().await.unwrap();
+
+    client.command(&["SUBSCRIBE", "news"]).await.unwrap();
+    let RespValue::Error(message) = client.command(&["GET", "key"]).await.unwrap() else {
+        panic!("GET was accepted in subscribed mode");
+    };
+    assert!(message.starts_with("ERR Can't execute 'get': only (P|S)SUBSCRIBE / (P|S)UNSUBSCRIBE / PING / QUIT / RESET"));
+
+    // RESP2 구독 중 PING은 메시지와 같은 배열로 답함
+    assert_eq!(client.command(&["PING"]).await.unwrap(), RespValue::Array(vec![bulk("pong"), bulk("")]));
+    assert_eq!(client.command(&["PING", "hello"]).await.unwrap(), RespValue::Array(vec![bulk("pong"), bulk("hello")]));
+    client.command(&["PSUBSCRIBE", "n*"]).await.unwrap();
+
+    // 구독을 모두 풀면 다시 모든 명령을 받음
+    client.command(&["UNSUBSCRIBE"]).await.unwrap();
+    assert!(matches!(client.command(&["GET", "key"]).await.unwrap(), RespValue::Error(_)));
+    client.command(&["PUNSUBSCRIBE"]).await.unwrap();
+    assert_eq!(client.command(&["GET", "key"]).await.unwrap(), RespValue::NullBulk);
+
+    client.command(&["SUBSCRIBE", "news"]).await.unwrap();
+    assert_eq!(client.command(&["QUIT"]).await.unwrap(), ok());
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn resp3_subscriber_can_run_any_command() {
+    let server = TestServer::start().await.unwrap();
+    let mut client = server.client().await.unwrap();
+
+    assert!(matches!(client.command(&["HELLO", "3"]).await.unwrap(), RespValue::Map(_)));
+    assert!(matches!(client.command(&["SUBSCRIBE", "news"]).await.unwrap(), RespValue::Push(_)));
+    assert_eq!(client.command(&["SET", "key", "value"]).await.unwrap(), ok());
+    assert_eq!(client.command(&["GET", "key"]).await.unwrap(), bulk("value"));
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn reset_leaves_subscribed_mode_and_drops_the_transaction() {
+    let server = TestServer::start().await.unwrap();
+    let mut client = server.client().await.unwrap();
+    let mut publisher = server.client().await.unwrap();
+    let reset = RespValue::SimpleString("RESET".into());
+
+    client.command(&["CLIENT", "SETNAME", "worker"]).await.unwrap();
+    client.command(&["SUBSCRIBE", "news"]).await.unwrap();
+    client.command(&["PSUBSCRIBE", "n*"]).await.unwrap();
+    assert_eq!(client.command(&["RESET"]).await.unwrap(), reset);
+    assert_eq!(publisher.command(&["PUBLISH", "news", "hello"]).await.unwrap(), RespValue::Integer(0));
+    assert_eq!(client.command(&["GET", "key"]).await.unwrap(), RespValue::NullBulk);
+    assert_eq!(client.command(&["CLIENT", "GETNAME"]).await.unwrap(), RespValue::NullBulk);
+
+    // MULTI 중에도 큐에 쌓지 않고 바로 실행해서 트랜잭션을 버림
+    client.command(&["MULTI"]).await.unwrap();
+    client.command(&["SET", "key", "value"]).await.unwrap();
+    assert_eq!(client.command(&["RESET"]).await.unwrap(), reset);
+    assert_eq!(client.command(&["EXEC"]).await.unwrap(), RespValue::Error("ERR EXEC without MULTI".into()));
+    assert_eq!(client.command(&["GET", "key"]).await.unwrap(), RespValue::NullBulk);
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn publish_reaches_subscribers_on_chained_replicas() {
+    let master = TestServer::start().await.unwrap();
+    let replica_of = format!("127.0.0.1 {}", master.port());
+    let replica = TestServer::start_with(|builder| builder.option("replicaof", replica_of)).await.unwrap();
+    let replica_of = format!("127.0.0.1 {}", replica.port());
+    let sub_replica = TestServer::start_with(|builder| builder.option("replicaof", replica_of)).await.unwrap();
+
+    let mut master_client = master.client().await.unwrap();
+    master_client.command(&["SET", "synced", "1"]).await.unwrap();
+    wait_for_key(&mut replica.client().await.unwrap(), "synced").await;
+    wait_for_key(&mut sub_replica.client().await.unwrap(), "synced").await;
+
+    let mut replica_subscriber = replica.client().await.unwrap();
+    replica_subscriber.command(&["SUBSCRIBE", "news"]).await.unwrap();
+    let mut sub_replica_subscriber = sub_replica.client().await.unwrap();
+    sub_replica_subscriber.command(&["PSUBSCRIBE", "n*"]).await.unwrap();
+
+    // 응답의 수신자 수는 마스터에 붙은 구독자만 셈
+    assert_eq!(master_client.command(&["PUBLISH", "news", "hello"]).await.unwrap(), RespValue::Integer(0));
+    let message = tokio::time::timeout(REPLICATION_TIMEOUT, replica_subscriber.read_reply()).await.unwrap().unwrap();
+    assert_eq!(message, RespValue::Array(vec![bulk("message"), bulk("news"), bulk("hello")]));
+    let message = tokio::time::timeout(REPLICATION_TIMEOUT, sub_replica_subscriber.read_reply()).await.unwrap().unwrap();
+    assert_eq!(message, RespValue::Array(vec![bulk("pmessage"), bulk("n*"), bulk("news"), bulk("hello")]));
+
+    sub_replica.shutdown().await.unwrap();
+    replica.shutdown().await.unwrap();
+    master.shutdown().await.unwrap();
+}
+
+fn push(items: &[&str]) -> RespValue {
+    RespValue::Array(items.iter().map(|item| bulk(item)).collect())
+}
+
+fn subscription(kind: &str, channel: &str, count: i64) -> RespValue {
+    RespValue::Array(vec![bulk(kind), bulk(channel), RespValue::Integer(count)])
+}
+
+#[tokio::test]
+async fn spublish_reaches_only_shard_subscribers() {
+    let server = TestServer::start().await.unwrap();
+    let mut subscriber = server.client().await.unwrap();
+    let mut publisher = server.client().await.unwrap();
+
+    // 채널마다 확인 응답을 하나씩 보냄
+    assert_eq!(subscriber.command(&["SSUBSCRIBE", "orders", "payments"]).await.unwrap(), subscription("ssubscribe", "orders", 1));
+    assert_eq!(subscriber.read_reply().await.unwrap(), subscription("ssubscribe", "payments", 2));
+
+    assert_eq!(publisher.command(&["SPUBLISH", "orders", "created"]).await.unwrap(), RespValue::Integer(1));
+    assert_eq!(subscriber.read_reply().await.unwrap(), push(&["smessage", "orders", "created"]));
+
+    // 샤드 채널과 일반 채널은 이름이 같아도 따로 움직임
+    assert_eq!(publisher.command(&["PUBLISH", "orders", "plain"]).await.unwrap(), RespValue::Integer(0));
+    assert_eq!(publisher.command(&["SPUBLISH", "nobody", "hello"]).await.unwrap(), RespValue::Integer(0));
+
+    assert_eq!(subscriber.command(&["SUNSUBSCRIBE", "orders"]).await.unwrap(), subscription("sunsubscribe", "orders", 1));
+    assert_eq!(publisher.command(&["SPUBLISH", "orders", "again"]).await.unwrap(), RespValue::Integer(0));
+    assert_eq!(publisher.command(&["SPUBLISH", "payments", "paid"]).await.unwrap(), RespValue::Integer(1));
+    assert_eq!(subscriber.read_reply().await.unwrap(), push(&["smessage", "payments", "paid"]));
+
+    // 인자가 없으면 남은 샤드 채널을 모두 풀고, 풀 채널이 없으면 채널 자리에 nil을 보냄
+    assert_eq!(subscriber.command(&["SUNSUBSCRIBE"]).await.unwrap(), subscription("sunsubscribe", "payments", 0));
+    assert_eq!(
+        subscriber.command(&["SUNSUBSCRIBE"]).await.unwrap(),
+        RespValue::Array(vec![bulk("sunsubscribe"), RespValue::NullBulk, RespValue::Integer(0)])
+    );
+    assert_eq!(subscriber.command(&["GET", "key"]).await.unwrap(), RespValue::NullBulk);
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn resp3_smessage_is_a_push() {
+    let server = TestServer::start().await.unwrap();
+    let mut subscriber = server.client().await.unwrap();
+    let mut publisher = server.client().await.unwrap();
+
+    assert!(matches!(subscriber.command(&["HELLO", "3"]).await.unwrap(), RespValue::Map(_)));
+    assert_eq!(
+        subscriber.command(&["SSUBSCRIBE", "orders"]).await.unwrap(),
+        RespValue::Push(vec![bulk("ssubscribe"), bulk("orders"), RespValue::Integer(1)])
+    );
+    assert_eq!(publisher.command(&["SPUBLISH", "orders", "created"]).await.unwrap(), RespValue::Integer(1));
+    assert_eq!(subscriber.read_reply().await.unwrap(), RespValue::Push(vec![bulk("smessage"), bulk("orders"), bulk("created")]));
+
+    server.shutdown().await.unwrap();
+}
+
+// SHARDCHANNELS는 슬롯 순서로 나오므로 이름순으로 맞춰서 비교함
+fn sorted_bulks(reply: RespValue) -> Vec<RespValue> {
+    let RespValue::Array(items) = reply else {
+        panic!("expected an array, got {:?}", reply);
+    };
+    let mut names: Vec<Vec<u8>> = items
+        .into_iter()
+        .map(|item| match item {
+            RespValue::BulkString(name) => name,
+            other => panic!("expected a bulk string, got {:?}", other),
+        })
+        .collect();
+    names.sort();
+    names.into_iter().map(RespValue::BulkString).collect()
+}
+
+#[tokio::test]
+async fn pubsub_introspection_counts_channels_and_patterns() {
+    let server = TestServer::start().await.unwrap();
+    let mut first = server.client().await.unwrap();
+    let mut second = server.client().await.unwrap();
+    let mut shard = server.client().await.unwrap();
+    let mut observer = server.client().await.unwrap();
+
+    first.command(&["SUBSCRIBE", "news.tech"]).await.unwrap();
+    second.command(&["SUBSCRIBE", "news.tech", "sports"]).await.unwrap();
+    second.read_reply().await.unwrap();
+    second.command(&["PSUBSCRIBE", "news.*"]).await.unwrap();
+    first.command(&["PSUBSCRIBE", "news.*", "alerts.*"]).await.unwrap();
+    first.read_reply().await.unwrap();
+    shard.command(&["SSUBSCRIBE", "orders", "orders.eu"]).await.unwrap();
+    shard.read_reply().await.unwrap();
+
+    // 일반 채널만 세고 패턴 구독과 샤드 채널은 넣지 않음, 결과는 이름순
+    assert_eq!(observer.command(&["PUBSUB", "CHANNELS"]).await.unwrap(), RespValue::Array(vec![bulk("news.tech"), bulk("sports")]));
+    assert_eq!(observer.command(&["PUBSUB", "CHANNELS", "news.*"]).await.unwrap(), RespValue::Array(vec![bulk("news.tech")]));
+    assert_eq!(
+        observer.command(&["PUBSUB", "NUMSUB", "news.tech", "sports", "nobody"]).await.unwrap(),
+        RespValue::Array(vec![bulk("news.tech"), RespValue::Integer(2), bulk("sports"), RespValue::Integer(1), bulk("nobody"), RespValue::Integer(0)])
+    );
+    assert_eq!(observer.command(&["PUBSUB", "NUMSUB"]).await.unwrap(), RespValue::Array(vec![]));
+
+    // 같은 패턴을 여러 클라이언트가 구독해도 한 번만 셈
+    assert_eq!(observer.command(&["PUBSUB", "NUMPAT"]).await.unwrap(), RespValue::Integer(2));
+
+    assert_eq!(sorted_bulks(observer.command(&["PUBSUB", "SHARDCHANNELS"]).await.unwrap()), vec![bulk("orders"), bulk("orders.eu")]);
+    assert_eq!(observer.command(&["PUBSUB", "SHARDCHANNELS", "*.eu"]).await.unwrap(), RespValue::Array(vec![bulk("orders.eu")]));
+    assert_eq!(
+        observer.command(&["PUBSUB", "SHARDNUMSUB", "orders", "news.tech"]).await.unwrap(),
+        RespValue::Array(vec![bulk("orders"), RespValue::Integer(1), bulk("news.tech"), RespValue::Integer(0)])
+    );
+
+    // 구독을 풀면 목록에서 빠짐
+    second.command(&["UNSUBSCRIBE", "sports"]).await.unwrap();
+    second.command(&["PUNSUBSCRIBE"]).await.unwrap();
+    first.command(&["PUNSUBSCRIBE", "alerts.*"]).await.unwrap();
+    shard.command(&["SUNSUBSCRIBE", "orders"]).await.unwrap();
+    assert_eq!(observer.command(&["PUBSUB", "CHANNELS"]).await.unwrap(), RespValue::Array(vec![bulk("news.tech")]));
+    assert_eq!(observer.command(&["PUBSUB", "NUMPAT"]).await.unwrap(), RespValue::Integer(1));
+    assert_eq!(observer.command(&["PUBSUB", "SHARDCHANNELS"]).await.unwrap(), RespValue::Array(vec![bulk("orders.eu")]));
+
+    server.shutdown().await.unwrap();
+}
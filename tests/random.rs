@@ -0,0 +1,48 @@
+use redis_starter_rust::test_support::TestServer;
+use redis_starter_rust::RespValue;
+
+// 무작위 값을 쓰는 응답을 차례로 모음, 시드가 같으면 서버를 다시 띄워도 같아야 함
+async fn random_replies(seed: &str) -> Vec<RespValue> {
+    let server = TestServer::start_with(|builder| builder.option("debug-random-seed", seed)).await.unwrap();
+    let mut client = server.client().await.unwrap();
+    let mut zadd = vec!["ZADD".to_string(), "zset".to_string()];
+    let mut hset = vec!["HSET".to_string(), "hash".to_string()];
+    for i in 0..10 {
+        client.command(&["SET", &format!("key:{}", i), "value"]).await.unwrap();
+        zadd.extend([i.to_string(), format!("member:{}", i)]);
+    }
+    // listpack이 아니라 해시 테이블로 저장될 만큼 필드를 넣음
+    for i in 0..200 {
+        hset.extend([format!("field:{}", i), i.to_string()]);
+    }
+    client.command(&zadd).await.unwrap();
+    client.command(&hset).await.unwrap();
+
+    let mut replies = vec![client.command(&["INFO", "replication"]).await.unwrap()];
+    for _ in 0..5 {
+        replies.push(client.command(&["RANDOMKEY"]).await.unwrap());
+    }
+    replies.push(client.command(&["ZRANDMEMBER", "zset", "5"]).await.unwrap());
+    replies.push(client.command(&["ZRANDMEMBER", "zset", "-10", "WITHSCORES"]).await.unwrap());
+    replies.push(client.command(&["HRANDFIELD", "hash", "5"]).await.unwrap());
+    replies.push(client.command(&["HRANDFIELD", "hash", "-10", "WITHVALUES"]).await.unwrap());
+    server.shutdown().await.unwrap();
+    replies
+}
+
+#[tokio::test]
+async fn fixed_seed_reproduces_random_replies() {
+    let first = random_replies("42").await;
+    assert_eq!(random_replies("42").await, first);
+    assert_ne!(random_replies("7").await, first);
+}
+
+// 시드는 서버마다 걸리므로 다른 시드의 서버가 함께 돌아도 결과가 바뀌지 않음
+#[tokio::test]
+async fn servers_in_one_process_keep_their_own_seed() {
+    let alone = random_replies("42").await;
+    let (first, other, second) = tokio::join!(random_replies("42"), random_replies("7"), random_replies("42"));
+    assert_eq!(first, alone);
+    assert_eq!(second, alone);
+    assert_ne!(other, alone);
+}
@@ -0,0 +1,46 @@
+use redis_starter_rust::test_support::TestServer;
+use redis_starter_rust::RespValue;
+
+#[tokio::test]
+async fn renamed_and_disabled_commands_ignore_case() {
+    let server = TestServer::start_with(|builder| builder.option("rename-command", "GET fetch").option("disabled-commands", "flushall"))
+        .await
+        .unwrap();
+    let mut client = server.client().await.unwrap();
+    client.command(&["SET", "key", "value"]).await.unwrap();
+
+    for name in ["FETCH", "fetch", "Fetch"] {
+        assert_eq!(client.command(&[name, "key"]).await.unwrap(), RespValue::BulkString(b"value".to_vec()));
+    }
+    for args in [&["GET", "key"][..], &["get", "key"], &["FLUSHALL"], &["flushall"]] {
+        let RespValue::Error(message) = client.command(args).await.unwrap() else {
+            panic!("{:?} was accepted", args);
+        };
+        let expected = match args {
+            [name, key] => format!("ERR unknown command '{}', with args beginning with: '{}' ", name, key),
+            _ => format!("ERR unknown command '{}', with args beginning with: ", args[0]),
+        };
+        assert_eq!(message, expected);
+    }
+
+    server.shutdown().await.unwrap();
+}
+
+// 바꾼 이름은 서버마다 따로 가지므로 함께 떠 있는 다른 서버에는 영향이 없음
+#[tokio::test]
+async fn renames_apply_only_to_their_own_server() {
+    let renamed = TestServer::start_with(|builder| builder.option("rename-command", "GET fetch")).await.unwrap();
+    let plain = TestServer::start().await.unwrap();
+    let mut renamed_client = renamed.client().await.unwrap();
+    let mut plain_client = plain.client().await.unwrap();
+    renamed_client.command(&["SET", "key", "value"]).await.unwrap();
+    plain_client.command(&["SET", "key", "value"]).await.unwrap();
+
+    assert_eq!(renamed_client.command(&["FETCH", "key"]).await.unwrap(), RespValue::BulkString(b"value".to_vec()));
+    assert!(matches!(renamed_client.command(&["GET", "key"]).await.unwrap(), RespValue::Error(_)));
+    assert_eq!(plain_client.command(&["GET", "key"]).await.unwrap(), RespValue::BulkString(b"value".to_vec()));
+    assert!(matches!(plain_client.command(&["FETCH", "key"]).await.unwrap(), RespValue::Error(_)));
+
+    renamed.shutdown().await.unwrap();
+    plain.shutdown().await.unwrap();
+}
@@ -0,0 +1,538 @@
+use redis_starter_rust::test_support::{info_field, ok, TestServer};
+use redis_starter_rust::{Client, RespValue};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+const REPLICATION_TIMEOUT: Duration = Duration::from_secs(5);
+// 키가 없고 체크섬을 끈 RDB
+const EMPTY_RDB: &[u8] = b"REDIS0011\xff\0\0\0\0\0\0\0\0";
+
+#[tokio::test]
+async fn restarted_master_keeps_its_replication_id_and_offset() {
+    // 시작할 때는 dbfilename을 직접 준 경우에만 RDB를 읽음
+    let master = TestServer::start_with(|builder| builder.dbfilename("dump.rdb")).await.unwrap();
+    let mut client = master.client().await.unwrap();
+    for i in 0..10 {
+        client.command(&["SET", &format!("key:{}", i), "value"]).await.unwrap();
+    }
+    let replid = info_field(&mut client, "replication", "master_replid").await.unwrap();
+    let offset = info_field(&mut client, "replication", "master_repl_offset").await.unwrap();
+    // 종료하면서 저장한 RDB를 같은 디렉터리로 띄운 서버가 읽음, 연결은 응답 없이 끊김
+    assert!(client.command(&["SHUTDOWN", "SAVE"]).await.is_err());
+
+    let dir = master.dir().display().to_string();
+    let restarted = TestServer::start_with(|builder| builder.dir(dir).dbfilename("dump.rdb")).await.unwrap();
+    let mut client = restarted.client().await.unwrap();
+    assert_eq!(info_field(&mut client, "replication", "master_replid").await.unwrap(), replid);
+    assert_eq!(info_field(&mut client, "replication", "master_repl_offset").await.unwrap(), offset);
+    assert_eq!(client.command(&["DBSIZE"]).await.unwrap(), RespValue::Integer(10));
+
+    restarted.shutdown().await.unwrap();
+}
+
+// 테스트가 저장소에 커밋된 dump.rdb를 덮어쓰지 않도록 작업 디렉터리에는 띄우지 않음
+#[tokio::test]
+async fn test_servers_never_write_to_the_working_directory() {
+    assert!(TestServer::start_with(|builder| builder.dir(".")).await.is_err());
+    let cwd = std::env::current_dir().unwrap().display().to_string();
+    assert!(TestServer::start_with(|builder| builder.dir(cwd)).await.is_err());
+}
+
+#[tokio::test]
+async fn replica_takes_the_masters_replication_id() {
+    let master = TestServer::start().await.unwrap();
+    let mut master_client = master.client().await.unwrap();
+    let replid = info_field(&mut master_client, "replication", "master_replid").await.unwrap();
+
+    let replica_of = format!("127.0.0.1 {}", master.port());
+    let replica = TestServer::start_with(|builder| builder.option("replicaof", replica_of)).await.unwrap();
+    let mut replica_client = replica.client().await.unwrap();
+    let deadline = tokio::time::Instant::now() + REPLICATION_TIMEOUT;
+    while info_field(&mut replica_client, "replication", "master_link_status").await.as_deref() != Some("up") {
+        assert!(tokio::time::Instant::now() < deadline, "replica did not sync in time");
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    assert_eq!(info_field(&mut replica_client, "replication", "master_replid").await.unwrap(), replid);
+
+    replica.shutdown().await.unwrap();
+    master.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn wait_counts_replicas_connected_over_ipv4_and_ipv6() {
+    let master = TestServer::start_with(|builder| builder.option("bind", "127.0.0.1 ::1")).await.unwrap();
+    let mut master_client = master.client().await.unwrap();
+    let over_ipv4 = format!("127.0.0.1 {}", master.port());
+    let replica_v4 = TestServer::start_with(|builder| builder.option("replicaof", over_ipv4)).await.unwrap();
+    let over_ipv6 = format!("::1 {}", master.port());
+    let replica_v6 = TestServer::start_with(|builder| builder.option("replicaof", over_ipv6)).await.unwrap();
+
+    let deadline = tokio::time::Instant::now() + REPLICATION_TIMEOUT;
+    while info_field(&mut master_client, "replication", "connected_slaves").await.as_deref() != Some("2") {
+        assert!(tokio::time::Instant::now() < deadline, "replicas did not connect in time");
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    // 두 레플리카의 ACK가 각자의 연결로 기록되어야 WAIT가 둘 다 셈
+    master_client.command(&["SET", "key", "value"]).await.unwrap();
+    assert_eq!(master_client.command(&["WAIT", "2", "5000"]).await.unwrap(), RespValue::Integer(2));
+
+    replica_v6.shutdown().await.unwrap();
+    replica_v4.shutdown().await.unwrap();
+    master.shutdown().await.unwrap();
+}
+
+async fn wait_for_offset(client: &mut Client, offset: &str) {
+    let deadline = tokio::time::Instant::now() + REPLICATION_TIMEOUT;
+    while info_field(client, "replication", "master_link_status").await.as_deref() != Some("up")
+        || info_field(client, "replication", "slave_repl_offset").await.as_deref() != Some(offset)
+    {
+        assert!(tokio::time::Instant::now() < deadline, "replica did not reach offset {} in time", offset);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+}
+
+#[tokio::test]
+async fn replica_of_the_old_master_partially_resyncs_with_the_promoted_replica() {
+    // 마스터의 PING이 두 레플리카의 offset을 엇갈리게 하지 않도록 주기를 늘림
+    let master = TestServer::start_with(|builder| builder.option("repl-ping-replica-period", "3600")).await.unwrap();
+    let mut master_client = master.client().await.unwrap();
+    let old_replid = info_field(&mut master_client, "replication", "master_replid").await.unwrap();
+    let replica_of = format!("127.0.0.1 {}", master.port());
+    let promoted = TestServer::start_with(|builder| builder.option("replicaof", replica_of.clone())).await.unwrap();
+    let moved = TestServer::start_with(|builder| builder.option("replicaof", replica_of)).await.unwrap();
+
+    for i in 0..10 {
+        master_client.command(&["SET", &format!("key:{}", i), "value"]).await.unwrap();
+    }
+    let offset = info_field(&mut master_client, "replication", "master_repl_offset").await.unwrap();
+    let mut promoted_client = promoted.client().await.unwrap();
+    let mut moved_client = moved.client().await.unwrap();
+    wait_for_offset(&mut promoted_client, &offset).await;
+    wait_for_offset(&mut moved_client, &offset).await;
+
+    assert_eq!(promoted_client.command(&["REPLICAOF", "NO", "ONE"]).await.unwrap(), ok());
+    let new_replid = info_field(&mut promoted_client, "replication", "master_replid").await.unwrap();
+    assert_ne!(new_replid, old_replid);
+    assert_eq!(info_field(&mut promoted_client, "replication", "master_replid2").await.unwrap(), old_replid);
+    assert_eq!(info_field(&mut promoted_client, "replication", "master_repl_offset").await.unwrap(), offset);
+    let second_offset = (offset.parse::<u64>().unwrap() + 1).to_string();
+    assert_eq!(info_field(&mut promoted_client, "replication", "second_repl_offset").await.unwrap(), second_offset);
+    assert_eq!(info_field(&mut promoted_client, "replication", "master_failover_state").await.unwrap(), "no-failover");
+
+    // 이어받으면 전체 동기화와 달리 예전 ID가 두 번째 복제 ID로 남음
+    let promoted_port = promoted.port().to_string();
+    moved_client.command(&["REPLICAOF", "127.0.0.1", &promoted_port]).await.unwrap();
+    wait_for_offset(&mut moved_client, &offset).await;
+    assert_eq!(info_field(&mut moved_client, "replication", "master_replid").await.unwrap(), new_replid);
+    assert_eq!(info_field(&mut moved_client, "replication", "master_replid2").await.unwrap(), old_replid);
+    assert_eq!(moved_client.command(&["DBSIZE"]).await.unwrap(), RespValue::Integer(10));
+
+    promoted_client.command(&["SET", "after", "failover"]).await.unwrap();
+    let deadline = tokio::time::Instant::now() + REPLICATION_TIMEOUT;
+    while moved_client.command(&["GET", "after"]).await.unwrap() == RespValue::NullBulk {
+        assert!(tokio::time::Instant::now() < deadline, "write on the promoted replica was not replicated");
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+
+    moved.shutdown().await.unwrap();
+    promoted.shutdown().await.unwrap();
+    master.shutdown().await.unwrap();
+}
+
+async fn wait_until_replicated(client: &mut Client, args: &[&str], expected: RespValue) {
+    let deadline = tokio::time::Instant::now() + REPLICATION_TIMEOUT;
+    while client.command(args).await.unwrap() != expected {
+        assert!(tokio::time::Instant::now() < deadline, "{:?} did not reach {:?} in time", args, expected);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+}
+
+#[tokio::test]
+async fn promotion_reattaches_sub_replicas_to_the_new_replication_id() {
+    let master = TestServer::start().await.unwrap();
+    let mut master_client = master.client().await.unwrap();
+    let replica_of = format!("127.0.0.1 {}", master.port());
+    let promoted = TestServer::start_with(|builder| builder.option("replicaof", replica_of)).await.unwrap();
+    let replica_of = format!("127.0.0.1 {}", promoted.port());
+    let sub_replica = TestServer::start_with(|builder| builder.option("replicaof", replica_of)).await.unwrap();
+    let mut promoted_client = promoted.client().await.unwrap();
+    let mut sub_replica_client = sub_replica.client().await.unwrap();
+
+    master_client.command(&["SET", "before", "promotion"]).await.unwrap();
+    wait_until_replicated(&mut sub_replica_client, &["EXISTS", "before"], RespValue::Integer(1)).await;
+
+    assert_eq!(promoted_client.command(&["REPLICAOF", "NO", "ONE"]).await.unwrap(), ok());
+    assert_eq!(info_field(&mut promoted_client, "replication", "role").await.unwrap(), "master");
+    assert_eq!(promoted_client.command(&["SET", "after", "promotion"]).await.unwrap(), ok());
+
+    // 하위 레플리카는 끊겼다가 새 복제 ID로 다시 붙고 승격 뒤의 쓰기를 받음
+    wait_until_replicated(&mut sub_replica_client, &["EXISTS", "after"], RespValue::Integer(1)).await;
+    let new_replid = info_field(&mut promoted_client, "replication", "master_replid").await.unwrap();
+    assert_eq!(info_field(&mut sub_replica_client, "replication", "master_replid").await.unwrap(), new_replid);
+    // 예전 마스터의 쓰기는 더 이상 받지 않음
+    master_client.command(&["SET", "from", "old-master"]).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert_eq!(promoted_client.command(&["EXISTS", "from"]).await.unwrap(), RespValue::Integer(0));
+
+    sub_replica.shutdown().await.unwrap();
+    promoted.shutdown().await.unwrap();
+    master.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn demotion_releases_blocked_clients() {
+    let master = TestServer::start().await.unwrap();
+    let demoted = TestServer::start().await.unwrap();
+    let mut blocked = demoted.client().await.unwrap();
+    blocked.send_command(&["BLPOP", "queue", "0"]).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let mut client = demoted.client().await.unwrap();
+    let master_port = master.port().to_string();
+    assert_eq!(client.command(&["REPLICAOF", "127.0.0.1", &master_port]).await.unwrap(), ok());
+    let reply = tokio::time::timeout(REPLICATION_TIMEOUT, blocked.read_reply()).await.unwrap().unwrap();
+    let RespValue::Error(message) = reply else {
+        panic!("blocked client got {:?}", reply);
+    };
+    assert!(message.starts_with("UNBLOCKED"), "{}", message);
+
+    demoted.shutdown().await.unwrap();
+    master.shutdown().await.unwrap();
+}
+
+fn encode(args: &[&str]) -> Vec<u8> {
+    let mut out = format!("*{}\r\n", args.len()).into_bytes();
+    for arg in args {
+        out.extend_from_slice(format!("${}\r\n{}\r\n", arg.len(), arg).as_bytes());
+    }
+    out
+}
+
+// 가짜 마스터 쪽에서 레플리카가 보낸 프레임 하나를 읽음
+async fn read_frame(link: &mut TcpStream, buffer: &mut Vec<u8>) -> RespValue {
+    let mut chunk = [0u8; 1024];
+    loop {
+        if let Some((frame, len)) = RespValue::parse(buffer).unwrap() {
+            buffer.drain(..len);
+            return frame;
+        }
+        let n = tokio::time::timeout(REPLICATION_TIMEOUT, link.read(&mut chunk)).await.unwrap().unwrap();
+        assert!(n > 0, "replica closed the master link");
+        buffer.extend_from_slice(&chunk[..n]);
+    }
+}
+
+fn ack(offset: usize) -> RespValue {
+    let offset = offset.to_string();
+    RespValue::Array(["REPLCONF", "ACK", offset.as_str()].iter().map(|arg| RespValue::BulkString(arg.as_bytes().to_vec())).collect())
+}
+
+// 레플리카를 가짜 마스터에 붙이고 PSYNC까지 받음, 이후 FULLRESYNC 응답과 RDB는 테스트가 보냄
+async fn replica_of_fake_master() -> (TestServer, TcpStream, Vec<u8>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let replica_of = format!("127.0.0.1 {}", listener.local_addr().unwrap().port());
+    let replica = TestServer::start_with(|builder| builder.option("replicaof", replica_of)).await.unwrap();
+    let (mut link, _) = tokio::time::timeout(REPLICATION_TIMEOUT, listener.accept()).await.unwrap().unwrap();
+
+    // PING, REPLCONF listening-port, REPLCONF capa 다음에 PSYNC
+    let mut buffer = Vec::new();
+    for reply in ["+PONG\r\n", "+OK\r\n", "+OK\r\n"] {
+        read_frame(&mut link, &mut buffer).await;
+        link.write_all(reply.as_bytes()).await.unwrap();
+    }
+    let RespValue::Array(psync) = read_frame(&mut link, &mut buffer).await else {
+        panic!("replica did not send PSYNC");
+    };
+    assert_eq!(psync[0], RespValue::BulkString(b"PSYNC".to_vec()));
+    (replica, link, buffer)
+}
+
+// Redis 마스터처럼 RDB를 보내기 전에 연결 유지용 빈 줄을 보냄
+fn full_resync_payload() -> Vec<u8> {
+    let mut payload = format!("+FULLRESYNC {} 0\r\n\n\n${}\r\n", "a".repeat(40), EMPTY_RDB.len()).into_bytes();
+    payload.extend_from_slice(EMPTY_RDB);
+    payload
+}
+
+#[tokio::test]
+async fn replica_only_answers_getack_on_the_master_link() {
+    let (replica, mut link, mut buffer) = replica_of_fake_master().await;
+    link.write_all(&full_resync_payload()).await.unwrap();
+
+    // 일반 클라이언트라면 응답이나 에러를 받을 명령들, 레플리카는 GETACK에만 답해야 함
+    let mut stream = Vec::new();
+    for command in [&["SET", "key", "value"][..], &["GET", "key"], &["PING"], &["NOSUCHCOMMAND"], &["ECHO", "hello"]] {
+        stream.extend(encode(command));
+    }
+    let offset = stream.len();
+    stream.extend(encode(&["REPLCONF", "GETACK", "*"]));
+    link.write_all(&stream).await.unwrap();
+
+    assert_eq!(read_frame(&mut link, &mut buffer).await, ack(offset));
+    assert!(buffer.is_empty(), "replica wrote more than the ACK on the master link");
+    let mut client = replica.client().await.unwrap();
+    assert_eq!(client.command(&["GET", "key"]).await.unwrap(), RespValue::BulkString(b"value".to_vec()));
+
+    replica.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn promotion_closes_the_master_link_after_applying_what_it_read() {
+    let (replica, mut link, mut buffer) = replica_of_fake_master().await;
+    let mut payload = full_resync_payload();
+    for i in 0..3 {
+        payload.extend(encode(&["SET", &format!("key:{}", i), "value"]));
+    }
+    link.write_all(&payload).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let mut client = replica.client().await.unwrap();
+    assert_eq!(client.command(&["REPLICAOF", "NO", "ONE"]).await.unwrap(), ok());
+    // 응답을 받았을 때는 이미 읽은 명령이 모두 적용되어 있음
+    assert_eq!(client.command(&["DBSIZE"]).await.unwrap(), RespValue::Integer(3));
+    assert_eq!(info_field(&mut client, "replication", "role").await.unwrap(), "master");
+
+    // 주기적인 ACK 뒤에 연결이 닫힘
+    let mut chunk = [0u8; 1024];
+    loop {
+        let n = tokio::time::timeout(REPLICATION_TIMEOUT, link.read(&mut chunk)).await.unwrap().unwrap_or(0);
+        if n == 0 {
+            break;
+        }
+        buffer.extend_from_slice(&chunk[..n]);
+    }
+    let _ = link.write_all(&encode(&["SET", "late", "write"])).await;
+    assert_eq!(client.command(&["SET", "own", "write"]).await.unwrap(), ok());
+    assert_eq!(client.command(&["GET", "late"]).await.unwrap(), RespValue::NullBulk);
+
+    replica.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn replica_applies_commands_sent_right_behind_the_rdb() {
+    let (replica, mut link, mut buffer) = replica_of_fake_master().await;
+
+    // RDB와 같은 패킷에 쓰기 명령을 붙이고, 마지막 명령은 둘로 나눠 나중에 마저 보냄
+    let mut commands = Vec::new();
+    for i in 0..3 {
+        commands.extend(encode(&["SET", &format!("key:{}", i), "value"]));
+    }
+    let (first, rest) = commands.split_at(commands.len() - 5);
+    let mut payload = full_resync_payload();
+    payload.extend_from_slice(first);
+    link.write_all(&payload).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    link.write_all(rest).await.unwrap();
+    link.write_all(&encode(&["REPLCONF", "GETACK", "*"])).await.unwrap();
+
+    assert_eq!(read_frame(&mut link, &mut buffer).await, ack(commands.len()));
+    let mut client = replica.client().await.unwrap();
+    for i in 0..3 {
+        assert_eq!(client.command(&["GET", &format!("key:{}", i)]).await.unwrap(), RespValue::BulkString(b"value".to_vec()));
+    }
+
+    replica.shutdown().await.unwrap();
+}
+
+// 마스터에 레플리카처럼 붙어 FULLRESYNC와 RDB까지 받음, 이후 마스터가 전파하는 명령을 읽을 수 있음
+async fn fake_replica_of(master: &TestServer) -> (TcpStream, Vec<u8>) {
+    let mut link = TcpStream::connect(master.addr()).await.unwrap();
+    let mut buffer = Vec::new();
+    for command in [&["PING"][..], &["REPLCONF", "listening-port", "0"], &["REPLCONF", "capa", "psync2"]] {
+        link.write_all(&encode(command)).await.unwrap();
+        read_frame(&mut link, &mut buffer).await;
+    }
+    link.write_all(&encode(&["PSYNC", "?", "-1"])).await.unwrap();
+    let RespValue::SimpleString(reply) = read_frame(&mut link, &mut buffer).await else {
+        panic!("master did not answer PSYNC");
+    };
+    assert!(reply.starts_with("FULLRESYNC"), "{}", reply);
+
+    // RDB는 bulk string과 달리 끝에 CRLF가 없으므로 길이만큼 직접 버림
+    let mut chunk = [0u8; 1024];
+    loop {
+        if let Some(header_end) = buffer.windows(2).position(|window| window == b"\r\n") {
+            let rdb_len: usize = String::from_utf8_lossy(&buffer[1..header_end]).parse().unwrap();
+            if buffer.len() >= header_end + 2 + rdb_len {
+                buffer.drain(..header_end + 2 + rdb_len);
+                return (link, buffer);
+            }
+        }
+        let n = tokio::time::timeout(REPLICATION_TIMEOUT, link.read(&mut chunk)).await.unwrap().unwrap();
+        assert!(n > 0, "master closed the replication link");
+        buffer.extend_from_slice(&chunk[..n]);
+    }
+}
+
+// 마스터가 보내는 PING, SELECT, REPLCONF GETACK은 건너뛰고 다음 쓰기 명령을 읽음
+async fn next_replicated_write(link: &mut TcpStream, buffer: &mut Vec<u8>) -> Vec<String> {
+    loop {
+        let RespValue::Array(args) = read_frame(link, buffer).await else {
+            panic!("master sent a non-array frame");
+        };
+        let args: Vec<String> = args
+            .iter()
+            .map(|arg| match arg {
+                RespValue::BulkString(arg) => String::from_utf8_lossy(arg).into_owned(),
+                other => panic!("unexpected argument {:?}", other),
+            })
+            .collect();
+        if !matches!(args[0].to_uppercase().as_str(), "PING" | "SELECT" | "REPLCONF") {
+            return args;
+        }
+    }
+}
+
+#[tokio::test]
+async fn getset_replicates_through_the_set_path() {
+    let master = TestServer::start().await.unwrap();
+    let (mut link, mut buffer) = fake_replica_of(&master).await;
+    let mut client = master.client().await.unwrap();
+
+    client.command(&["SET", "key", "old", "PX", "100000"]).await.unwrap();
+    let RespValue::Integer(expire_at) = client.command(&["PEXPIRETIME", "key"]).await.unwrap() else {
+        panic!("PEXPIRETIME did not return an integer");
+    };
+    assert_eq!(client.command(&["GETSET", "key", "new"]).await.unwrap(), RespValue::BulkString(b"old".to_vec()));
+    assert_eq!(client.command(&["PTTL", "key"]).await.unwrap(), RespValue::Integer(-1));
+
+    // SET은 TTL을 PXAT 절대 시각으로 붙여 한 명령으로 보내고, GETSET은 TTL을 지운 SET으로 바뀌어 감
+    assert_eq!(next_replicated_write(&mut link, &mut buffer).await, ["SET", "key", "old", "PXAT", &expire_at.to_string()]);
+    assert_eq!(next_replicated_write(&mut link, &mut buffer).await, ["SET", "key", "new"]);
+
+    master.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn getex_replicates_the_resulting_ttl_change() {
+    let master = TestServer::start().await.unwrap();
+    let (mut link, mut buffer) = fake_replica_of(&master).await;
+    let mut client = master.client().await.unwrap();
+
+    client.command(&["SET", "key", "value"]).await.unwrap();
+    client.command(&["GETEX", "key"]).await.unwrap();
+    client.command(&["GETEX", "key", "PX", "100000"]).await.unwrap();
+    let RespValue::Integer(expire_at) = client.command(&["PEXPIRETIME", "key"]).await.unwrap() else {
+        panic!("PEXPIRETIME did not return an integer");
+    };
+    client.command(&["GETEX", "key", "PERSIST"]).await.unwrap();
+    // TTL이 없는 키의 PERSIST는 아무것도 바꾸지 않으므로 전파하지 않음
+    client.command(&["GETEX", "key", "PERSIST"]).await.unwrap();
+    client.command(&["GETEX", "key", "PXAT", "1"]).await.unwrap();
+
+    // 옵션 없는 GETEX는 읽기만 하므로 SET 다음은 바로 PEXPIREAT임
+    assert_eq!(next_replicated_write(&mut link, &mut buffer).await, ["SET", "key", "value"]);
+    assert_eq!(next_replicated_write(&mut link, &mut buffer).await, ["PEXPIREAT", "key", &expire_at.to_string()]);
+    assert_eq!(next_replicated_write(&mut link, &mut buffer).await, ["PERSIST", "key"]);
+    assert_eq!(next_replicated_write(&mut link, &mut buffer).await, ["DEL", "key"]);
+
+    master.shutdown().await.unwrap();
+}
+
+// 기다리다 깨어난 BLMOVE와 BRPOPLPUSH는 실제로 일어난 LMOVE로 전파되고, 아무것도 안 옮긴 채 끝나면 전파하지 않음
+#[tokio::test]
+async fn served_blocking_moves_replicate_as_lmove() {
+    let master = TestServer::start().await.unwrap();
+    let (mut link, mut buffer) = fake_replica_of(&master).await;
+    let mut blocked = master.client().await.unwrap();
+    let mut client = master.client().await.unwrap();
+
+    assert_eq!(client.command(&["BLMOVE", "missing", "dst", "LEFT", "RIGHT", "0.05"]).await.unwrap(), RespValue::NullBulk);
+    blocked.send_command(&["BLMOVE", "src", "dst", "RIGHT", "LEFT", "0"]).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    client.command(&["RPUSH", "src", "a", "b"]).await.unwrap();
+    let moved = tokio::time::timeout(REPLICATION_TIMEOUT, blocked.read_reply()).await.unwrap().unwrap();
+    assert_eq!(moved, RespValue::BulkString(b"b".to_vec()));
+    assert_eq!(client.command(&["BRPOPLPUSH", "src", "dst", "0"]).await.unwrap(), RespValue::BulkString(b"a".to_vec()));
+
+    assert_eq!(next_replicated_write(&mut link, &mut buffer).await, ["RPUSH", "src", "a", "b"]);
+    assert_eq!(next_replicated_write(&mut link, &mut buffer).await, ["LMOVE", "src", "dst", "RIGHT", "LEFT"]);
+    assert_eq!(next_replicated_write(&mut link, &mut buffer).await, ["LMOVE", "src", "dst", "RIGHT", "LEFT"]);
+
+    master.shutdown().await.unwrap();
+}
+
+// 읽기 명령은 만료된 키를 없는 키처럼 답하고, 지우기와 DEL 전파는 이벤트 루프가 맡음
+#[tokio::test]
+async fn reads_hand_expired_keys_to_the_event_loop_for_deletion() {
+    let master = TestServer::start().await.unwrap();
+    let (mut link, mut buffer) = fake_replica_of(&master).await;
+    let mut client = master.client().await.unwrap();
+    assert_eq!(client.command(&["DEBUG", "SET-ACTIVE-EXPIRE", "0"]).await.unwrap(), ok());
+    for key in ["string", "hash", "other"] {
+        client.command(&["SET", key, "value", "PX", "50"]).await.unwrap();
+    }
+    for key in ["string", "hash", "other"] {
+        assert_eq!(next_replicated_write(&mut link, &mut buffer).await[..4], ["SET", key, "value", "PXAT"]);
+    }
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    assert_eq!(client.command(&["GET", "string"]).await.unwrap(), RespValue::NullBulk);
+    assert_eq!(next_replicated_write(&mut link, &mut buffer).await, ["DEL", "string"]);
+    assert_eq!(client.command(&["HGET", "hash", "field"]).await.unwrap(), RespValue::NullBulk);
+    assert_eq!(next_replicated_write(&mut link, &mut buffer).await, ["DEL", "hash"]);
+
+    assert_eq!(client.command(&["CONFIG", "SET", "lazyfree-lazy-expire", "yes"]).await.unwrap(), ok());
+    assert_eq!(client.command(&["EXISTS", "other"]).await.unwrap(), RespValue::Integer(0));
+    assert_eq!(next_replicated_write(&mut link, &mut buffer).await, ["UNLINK", "other"]);
+    assert_eq!(info_field(&mut client, "stats", "expired_keys").await.as_deref(), Some("3"));
+
+    master.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn incr_is_replicated_and_applied_by_replicas() {
+    let master = TestServer::start().await.unwrap();
+    let (mut link, mut buffer) = fake_replica_of(&master).await;
+    let mut client = master.client().await.unwrap();
+
+    assert_eq!(client.command(&["INCR", "counter"]).await.unwrap(), RespValue::Integer(1));
+    assert_eq!(next_replicated_write(&mut link, &mut buffer).await, ["INCR", "counter"]);
+    // 실패한 INCR는 전파하지 않음
+    client.command(&["SET", "text", "abc"]).await.unwrap();
+    assert!(matches!(client.command(&["INCR", "text"]).await.unwrap(), RespValue::Error(_)));
+    assert_eq!(client.command(&["INCR", "counter"]).await.unwrap(), RespValue::Integer(2));
+    assert_eq!(next_replicated_write(&mut link, &mut buffer).await, ["SET", "text", "abc"]);
+    assert_eq!(next_replicated_write(&mut link, &mut buffer).await, ["INCR", "counter"]);
+
+    let replica_of = format!("127.0.0.1 {}", master.port());
+    let replica = TestServer::start_with(|builder| builder.option("replicaof", replica_of)).await.unwrap();
+    let mut replica_client = replica.client().await.unwrap();
+    let deadline = tokio::time::Instant::now() + REPLICATION_TIMEOUT;
+    while info_field(&mut replica_client, "replication", "master_link_status").await.as_deref() != Some("up") {
+        assert!(tokio::time::Instant::now() < deadline, "replica did not sync in time");
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    // 전체 동기화 뒤에 온 INCR를 레플리카가 스트림에서 적용함
+    assert_eq!(client.command(&["INCR", "counter"]).await.unwrap(), RespValue::Integer(3));
+    while replica_client.command(&["GET", "counter"]).await.unwrap() != RespValue::BulkString(b"3".to_vec()) {
+        assert!(tokio::time::Instant::now() < deadline, "replica did not apply INCR in time");
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+
+    replica.shutdown().await.unwrap();
+    master.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn replica_rejects_function_changes_from_clients() {
+    let (replica, mut link, mut buffer) = replica_of_fake_master().await;
+    link.write_all(&full_resync_payload()).await.unwrap();
+    let code = "#!lua name=mylib\nredis.register_function{function_name='answer', callback=function() return 42 end, flags={'no-writes'}}";
+    let mut stream = encode(&["FUNCTION", "LOAD", code]);
+    let offset = stream.len();
+    stream.extend(encode(&["REPLCONF", "GETACK", "*"]));
+    link.write_all(&stream).await.unwrap();
+    assert_eq!(read_frame(&mut link, &mut buffer).await, ack(offset));
+
+    // 마스터 링크로 온 FUNCTION LOAD는 적용하고, 클라이언트가 함수 목록을 바꾸려 하면 READONLY
+    let mut client = replica.client().await.unwrap();
+    let readonly = RespValue::Error("READONLY You can't write against a read only replica.".into());
+    for command in [&["FUNCTION", "LOAD", "REPLACE", code][..], &["FUNCTION", "DELETE", "mylib"], &["FUNCTION", "FLUSH"]] {
+        assert_eq!(client.command(command).await.unwrap(), readonly, "{:?}", command);
+    }
+    assert_eq!(client.command(&["FCALL_RO", "answer", "0"]).await.unwrap(), RespValue::Integer(42));
+
+    replica.shutdown().await.unwrap();
+}
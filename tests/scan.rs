@@ -0,0 +1,126 @@
+use redis_starter_rust::test_support::TestServer;
+use redis_starter_rust::{Client, RespValue};
+use std::collections::{HashMap, HashSet};
+
+async fn pipeline(client: &mut Client, commands: &[Vec<String>]) {
+    for command in commands {
+        client.send_command(command).await.unwrap();
+    }
+    for _ in commands {
+        assert!(!matches!(client.read_reply().await.unwrap(), RespValue::Error(_)));
+    }
+}
+
+fn set(key: &str) -> Vec<String> {
+    vec!["SET".into(), key.into(), "v".into()]
+}
+
+fn del(key: &str) -> Vec<String> {
+    vec!["DEL".into(), key.into()]
+}
+
+async fn scan(client: &mut Client, cursor: &str, count: usize) -> (String, Vec<String>) {
+    let reply = client.command(&["SCAN", cursor, "COUNT", &count.to_string()]).await.unwrap();
+    let RespValue::Array(mut parts) = reply else {
+        panic!("SCAN returned {:?}", reply);
+    };
+    let (Some(RespValue::Array(keys)), Some(RespValue::BulkString(cursor))) = (parts.pop(), parts.pop()) else {
+        panic!("unexpected SCAN reply shape");
+    };
+    let keys = keys
+        .into_iter()
+        .map(|key| match key {
+            RespValue::BulkString(key) => String::from_utf8(key).unwrap(),
+            other => panic!("SCAN returned non-bulk key {:?}", other),
+        })
+        .collect();
+    (String::from_utf8(cursor).unwrap(), keys)
+}
+
+// 순회 중에 키스페이스가 몇 배로 커지고(재해시) 일부 키가 지워져도, 처음부터 끝까지 있던 키는 빠짐없이 한 번만 나와야 함
+// 호출마다 늘어나는 키가 COUNT보다 적어야 순회가 끝남
+#[tokio::test]
+async fn scan_returns_every_stable_key_while_the_keyspace_grows() {
+    let server = TestServer::start().await.unwrap();
+    let mut client = server.client().await.unwrap();
+
+    let stable: Vec<String> = (0..100).map(|i| format!("stable:{}", i)).collect();
+    let doomed: Vec<String> = (0..20).map(|i| format!("doomed:{}", i)).collect();
+    pipeline(&mut client, &stable.iter().chain(&doomed).map(|key| set(key)).collect::<Vec<_>>()).await;
+
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    let mut cursor = "0".to_string();
+    let mut calls = 0;
+    loop {
+        let (next, keys) = scan(&mut client, &cursor, 10).await;
+        for key in keys {
+            *seen.entry(key).or_default() += 1;
+        }
+        calls += 1;
+        if next == "0" {
+            break;
+        }
+        cursor = next;
+        // 호출마다 새 키 12개를 넣고, 지울 키 하나와 방금 넣은 키 4개를 지움
+        let mut churn: Vec<Vec<String>> = (0..12).map(|i| set(&format!("churn:{}:{}", calls, i))).collect();
+        churn.extend((0..4).map(|i| del(&format!("churn:{}:{}", calls, i))));
+        if let Some(key) = doomed.get(calls) {
+            churn.push(del(key));
+        }
+        pipeline(&mut client, &churn).await;
+        assert!(calls < 1_000, "SCAN did not terminate");
+    }
+
+    for key in &stable {
+        assert_eq!(seen.get(key), Some(&1), "{} was returned {:?} times", key, seen.get(key));
+    }
+    // 키스페이스가 처음의 두 배를 넘어 재해시가 일어났는지 확인
+    assert!(calls * 8 > stable.len() + doomed.len(), "only {} SCAN calls, the keyspace did not grow enough", calls);
+    assert!(seen.values().all(|&times| times == 1), "a key was returned more than once");
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn scan_survives_the_keyspace_shrinking() {
+    let server = TestServer::start().await.unwrap();
+    let mut client = server.client().await.unwrap();
+
+    let keys: Vec<String> = (0..2000).map(|i| format!("key:{}", i)).collect();
+    pipeline(&mut client, &keys.iter().map(|key| set(key)).collect::<Vec<_>>()).await;
+
+    // 첫 페이지를 받은 뒤 절반을 지우고 끝까지 순회함
+    let (mut cursor, first) = scan(&mut client, "0", 50).await;
+    let removed: HashSet<&String> = keys.iter().skip(1).step_by(2).collect();
+    pipeline(&mut client, &removed.iter().map(|key| del(key)).collect::<Vec<_>>()).await;
+    let mut seen: HashSet<String> = first.into_iter().collect();
+    while cursor != "0" {
+        let (next, page) = scan(&mut client, &cursor, 50).await;
+        for key in page {
+            assert!(seen.insert(key.clone()), "{} was returned twice", key);
+        }
+        cursor = next;
+    }
+
+    for key in keys.iter().filter(|key| !removed.contains(key)) {
+        assert!(seen.contains(key), "{} was never returned", key);
+    }
+
+    server.shutdown().await.unwrap();
+}
+
+// COUNT는 클라이언트가 정하는 값이라, 아주 큰 COUNT도 그만큼 메모리를 잡지 않고 남은 키를 모두 돌려줘야 함
+#[tokio::test]
+async fn scan_accepts_a_huge_count() {
+    let server = TestServer::start().await.unwrap();
+    let mut client = server.client().await.unwrap();
+
+    let keys: Vec<String> = (0..30).map(|i| format!("key:{}", i)).collect();
+    pipeline(&mut client, &keys.iter().map(|key| set(key)).collect::<Vec<_>>()).await;
+
+    let (cursor, page) = scan(&mut client, "0", 100_000_000_000).await;
+    assert_eq!(cursor, "0");
+    assert_eq!(page.into_iter().collect::<HashSet<_>>(), keys.into_iter().collect::<HashSet<_>>());
+
+    server.shutdown().await.unwrap();
+}
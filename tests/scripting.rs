@@ -0,0 +1,372 @@
+use redis_starter_rust::test_support::{bulk, ok, TestServer};
+use redis_starter_rust::{Client, RespValue};
+use std::time::Duration;
+
+// redis-cli SCRIPT LOAD "return 1"이 돌려주는 값
+const RETURN_ONE_SHA: &str = "e0e1f9fabfc9d4800c877a703b823ac0578ff8db";
+const LIBRARY_CODE: &str = "#!lua name=mylib\nredis.register_function{function_name='myfunc', callback=function(keys, args) return 1 end, flags={'no-writes'}}";
+
+#[tokio::test]
+async fn script_cache_loads_checks_and_flushes_by_sha() {
+    let server = TestServer::start().await.unwrap();
+    let mut client = server.client().await.unwrap();
+
+    assert_eq!(client.command(&["SCRIPT", "LOAD", "return 1"]).await.unwrap(), bulk(RETURN_ONE_SHA));
+    let upper = RETURN_ONE_SHA.to_uppercase();
+    assert_eq!(
+        client.command(&["SCRIPT", "EXISTS", &upper, "0000000000000000000000000000000000000000"]).await.unwrap(),
+        RespValue::Array(vec![RespValue::Integer(1), RespValue::Integer(0)])
+    );
+    assert_eq!(client.command(&["SCRIPT", "FLUSH", "ASYNC"]).await.unwrap(), ok());
+    assert_eq!(
+        client.command(&["SCRIPT", "EXISTS", RETURN_ONE_SHA]).await.unwrap(),
+        RespValue::Array(vec![RespValue::Integer(0)])
+    );
+
+    assert_eq!(
+        client.command(&["EVALSHA", RETURN_ONE_SHA, "0"]).await.unwrap(),
+        RespValue::Error("NOSCRIPT No matching script. Please use EVAL.".into())
+    );
+    // EVAL로 실행한 스크립트도 캐시에 남아 EVALSHA로 부를 수 있음
+    assert_eq!(client.command(&["EVAL", "return 1", "0"]).await.unwrap(), RespValue::Integer(1));
+    assert_eq!(client.command(&["EVALSHA", &upper, "0"]).await.unwrap(), RespValue::Integer(1));
+
+    let RespValue::Error(message) = client.command(&["SCRIPT", "LOAD", "return +"]).await.unwrap() else {
+        panic!("SCRIPT LOAD accepted a syntax error");
+    };
+    assert!(message.starts_with("ERR Error compiling script (new function): user_script:1:"), "{}", message);
+
+    server.shutdown().await.unwrap();
+}
+
+fn error_message(reply: RespValue) -> String {
+    match reply {
+        RespValue::Error(message) => message,
+        reply => panic!("expected an error, got {:?}", reply),
+    }
+}
+
+#[tokio::test]
+async fn eval_converts_between_lua_values_and_replies() {
+    let server = TestServer::start().await.unwrap();
+    let mut client = server.client().await.unwrap();
+
+    // 숫자는 정수로 잘리고, false는 nil이 되고, 배열은 첫 nil 앞에서 끝남
+    assert_eq!(
+        client.command(&["EVAL", "return {1, 'two', 3.99, true, false, nil, 'unreachable'}", "0"]).await.unwrap(),
+        RespValue::Array(vec![RespValue::Integer(1), bulk("two"), RespValue::Integer(3), RespValue::Integer(1), RespValue::NullBulk])
+    );
+    assert_eq!(
+        client.command(&["EVAL", "return KEYS[1] .. ':' .. ARGV[2] .. ':' .. #ARGV", "1", "key", "first", "second"]).await.unwrap(),
+        bulk("key:second:2")
+    );
+    assert_eq!(client.command(&["EVAL", "return redis.status_reply('PONG')", "0"]).await.unwrap(), RespValue::SimpleString("PONG".into()));
+    assert_eq!(
+        client.command(&["EVAL", "return redis.error_reply('MYCODE something failed')", "0"]).await.unwrap(),
+        RespValue::Error("MYCODE something failed".into())
+    );
+    assert_eq!(client.command(&["EVAL", "return redis.error_reply('failed')", "0"]).await.unwrap(), RespValue::Error("ERR failed".into()));
+    assert_eq!(client.command(&["EVAL", "return redis.sha1hex('return 1')", "0"]).await.unwrap(), bulk(RETURN_ONE_SHA));
+
+    assert_eq!(
+        client.command(&["EVAL", "return 1", "2", "key"]).await.unwrap(),
+        RespValue::Error("ERR Number of keys can't be greater than number of args".into())
+    );
+    assert_eq!(
+        client.command(&["EVAL", "return 1", "-1"]).await.unwrap(),
+        RespValue::Error("ERR Number of keys can't be negative".into())
+    );
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn eval_runs_commands_through_redis_call() {
+    let server = TestServer::start().await.unwrap();
+    let mut client = server.client().await.unwrap();
+
+    let script = "redis.call('SET', KEYS[1], ARGV[1]); redis.call('INCR', KEYS[2]); return {redis.call('GET', KEYS[1]), redis.call('GET', 'missing')}";
+    assert_eq!(
+        client.command(&["EVAL", script, "2", "key", "counter", "value"]).await.unwrap(),
+        RespValue::Array(vec![bulk("value"), RespValue::NullBulk])
+    );
+    assert_eq!(client.command(&["GET", "key"]).await.unwrap(), bulk("value"));
+
+    // 숫자 인자는 문자열로 바뀌어 넘어감
+    assert_eq!(client.command(&["EVAL", "redis.call('SET', KEYS[1], 42); return redis.call('INCR', KEYS[1])", "1", "number"]).await.unwrap(), RespValue::Integer(43));
+
+    // redis.pcall은 에러를 {err = ...} 테이블로 돌려줌
+    assert_eq!(
+        client.command(&["EVAL", "return redis.pcall('INCR', KEYS[1]).err", "1", "key"]).await.unwrap(),
+        bulk("ERR value is not an integer or out of range")
+    );
+    // redis.call의 에러는 스크립트를 멈추고 위치를 붙여서 돌아옴
+    let message = error_message(client.command(&["EVAL", "\nreturn redis.call('INCR', KEYS[1])", "1", "key"]).await.unwrap());
+    assert!(message.starts_with("ERR value is not an integer or out of range script: "), "{}", message);
+    assert!(message.ends_with(", on @user_script:2."), "{}", message);
+
+    let message = error_message(client.command(&["EVAL", "return redis.call('NOSUCH')", "0"]).await.unwrap());
+    assert!(message.starts_with("ERR Unknown Redis command called from script"), "{}", message);
+    let message = error_message(client.command(&["EVAL", "return redis.call('MULTI')", "0"]).await.unwrap());
+    assert!(message.starts_with("ERR This Redis command is not allowed from script"), "{}", message);
+    let message = error_message(client.command(&["EVAL", "return redis.call()", "0"]).await.unwrap());
+    assert!(message.starts_with("ERR Please specify at least one argument for this redis lib call"), "{}", message);
+
+    let message = error_message(client.command(&["EVAL", "error('boom')", "0"]).await.unwrap());
+    assert!(message.starts_with("ERR user_script:1: boom script: "), "{}", message);
+    let message = error_message(client.command(&["EVAL", "leaked = 1", "0"]).await.unwrap());
+    assert!(message.contains("Attempt to modify a readonly table"), "{}", message);
+    let message = error_message(client.command(&["EVAL", "return +", "0"]).await.unwrap());
+    assert!(message.starts_with("ERR Error compiling script (new function): user_script:1:"), "{}", message);
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn eval_writes_reach_the_replica() {
+    let master = TestServer::start().await.unwrap();
+    let mut master_client = master.client().await.unwrap();
+    let replica_of = format!("127.0.0.1 {}", master.port());
+    let replica = TestServer::start_with(|builder| builder.option("replicaof", replica_of)).await.unwrap();
+    let mut replica_client = replica.client().await.unwrap();
+
+    let script = "redis.call('SET', KEYS[1], ARGV[1]); redis.call('RPUSH', KEYS[2], ARGV[1], ARGV[1]); return 1";
+    assert_eq!(master_client.command(&["EVAL", script, "2", "key", "list", "value"]).await.unwrap(), RespValue::Integer(1));
+    let started = tokio::time::Instant::now();
+    while replica_client.command(&["EXISTS", "key", "list"]).await.unwrap() != RespValue::Integer(2) {
+        assert!(started.elapsed() < Duration::from_secs(5), "replica did not apply the script's writes");
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert_eq!(replica_client.command(&["GET", "key"]).await.unwrap(), bulk("value"));
+
+    // 레플리카에서 쓰기를 부르는 스크립트는 READONLY로 거절됨
+    let message = error_message(replica_client.command(&["EVAL", "return redis.call('SET', 'key', 'other')", "0"]).await.unwrap());
+    assert!(message.starts_with("READONLY "), "{}", message);
+
+    replica.shutdown().await.unwrap();
+    master.shutdown().await.unwrap();
+}
+
+async fn load_library(client: &mut Client) -> RespValue {
+    assert_eq!(client.command(&["FUNCTION", "LOAD", LIBRARY_CODE]).await.unwrap(), bulk("mylib"));
+    let listed = client.command(&["FUNCTION", "LIST", "WITHCODE"]).await.unwrap();
+    assert!(matches!(&listed, RespValue::Array(libraries) if libraries.len() == 1), "{:?}", listed);
+    listed
+}
+
+#[tokio::test]
+async fn functions_survive_debug_reload() {
+    let server = TestServer::start().await.unwrap();
+    let mut client = server.client().await.unwrap();
+    let listed = load_library(&mut client).await;
+    client.command(&["SET", "key", "value"]).await.unwrap();
+
+    assert_eq!(client.command(&["DEBUG", "RELOAD"]).await.unwrap(), ok());
+    assert_eq!(client.command(&["FUNCTION", "LIST", "WITHCODE"]).await.unwrap(), listed);
+    assert_eq!(client.command(&["GET", "key"]).await.unwrap(), bulk("value"));
+
+    // 다시 읽은 라이브러리의 함수도 그대로 부를 수 있음
+    assert_eq!(client.command(&["FCALL", "myfunc", "0"]).await.unwrap(), RespValue::Integer(1));
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn fcall_runs_registered_functions() {
+    let server = TestServer::start().await.unwrap();
+    let mut client = server.client().await.unwrap();
+    let code = "#!lua name=counters\n\
+        redis.register_function('bump', function(keys, args) return redis.call('INCR', keys[1]) end)\n\
+        redis.register_function{function_name='peek', callback=function(keys, args) return redis.call('GET', keys[1]) end, flags={'no-writes'}}\n\
+        redis.register_function{function_name='sneaky', callback=function(keys, args) return redis.pcall('SET', keys[1], args[1]) end, flags={'no-writes'}}";
+    assert_eq!(client.command(&["FUNCTION", "LOAD", code]).await.unwrap(), bulk("counters"));
+
+    assert_eq!(client.command(&["FCALL", "bump", "1", "counter"]).await.unwrap(), RespValue::Integer(1));
+    assert_eq!(client.command(&["FCALL", "bump", "1", "counter"]).await.unwrap(), RespValue::Integer(2));
+    assert_eq!(client.command(&["FCALL_RO", "peek", "1", "counter"]).await.unwrap(), bulk("2"));
+    assert_eq!(client.command(&["FCALL", "peek", "1", "counter"]).await.unwrap(), bulk("2"));
+
+    // 쓰기 플래그가 있는 함수는 FCALL_RO로 부를 수 없고, no-writes 함수 안의 쓰기는 거절됨
+    assert_eq!(
+        client.command(&["FCALL_RO", "bump", "1", "counter"]).await.unwrap(),
+        RespValue::Error("ERR Can not execute a script with write flag using *_ro command.".into())
+    );
+    assert_eq!(
+        client.command(&["FCALL", "sneaky", "1", "counter", "0"]).await.unwrap(),
+        RespValue::Error("ERR Write commands are not allowed from read-only scripts.".into())
+    );
+    assert_eq!(client.command(&["GET", "counter"]).await.unwrap(), bulk("2"));
+
+    assert_eq!(client.command(&["FCALL", "missing", "0"]).await.unwrap(), RespValue::Error("ERR Function not found".into()));
+    assert_eq!(
+        client.command(&["FCALL", "bump", "2", "counter"]).await.unwrap(),
+        RespValue::Error("ERR Number of keys can't be greater than number of args".into())
+    );
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn function_load_rejects_broken_libraries() {
+    let server = TestServer::start().await.unwrap();
+    let mut client = server.client().await.unwrap();
+
+    let message = error_message(client.command(&["FUNCTION", "LOAD", "#!lua name=broken\nreturn +"]).await.unwrap());
+    assert!(message.starts_with("ERR Error compiling function"), "{}", message);
+    let message = error_message(
+        client
+            .command(&["FUNCTION", "LOAD", "#!lua name=flags\nredis.register_function{function_name='f', callback=function() return 1 end, flags={'bogus'}}"])
+            .await
+            .unwrap(),
+    );
+    assert!(message.contains("unknown flag given"), "{}", message);
+    let message = error_message(client.command(&["FUNCTION", "LOAD", "#!lua name=empty\nlocal x = 1"]).await.unwrap());
+    assert!(message.starts_with("ERR No functions registered"), "{}", message);
+    assert_eq!(client.command(&["FUNCTION", "LIST"]).await.unwrap(), RespValue::Array(vec![]));
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn functions_are_loaded_from_the_rdb_on_startup() {
+    let server = TestServer::start_with(|builder| builder.dbfilename("functions.rdb")).await.unwrap();
+    let mut client = server.client().await.unwrap();
+    let listed = load_library(&mut client).await;
+    client.command(&["BGSAVE"]).await.unwrap();
+    let rdb_path = server.dir().join("functions.rdb");
+    let started = tokio::time::Instant::now();
+    while !rdb_path.exists() {
+        assert!(started.elapsed() < Duration::from_secs(5), "BGSAVE did not write {}", rdb_path.display());
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+
+    let dir = server.dir().display().to_string();
+    let restarted = TestServer::start_with(|builder| builder.dir(dir).dbfilename("functions.rdb")).await.unwrap();
+    let mut restarted_client = restarted.client().await.unwrap();
+    assert_eq!(restarted_client.command(&["FUNCTION", "LIST", "WITHCODE"]).await.unwrap(), listed);
+
+    restarted.shutdown().await.unwrap();
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn replica_gets_the_masters_functions_in_the_full_resync() {
+    let master = TestServer::start().await.unwrap();
+    let mut master_client = master.client().await.unwrap();
+    let listed = load_library(&mut master_client).await;
+
+    let replica_of = format!("127.0.0.1 {}", master.port());
+    let replica = TestServer::start_with(|builder| builder.option("replicaof", replica_of)).await.unwrap();
+    let mut replica_client = replica.client().await.unwrap();
+    let started = tokio::time::Instant::now();
+    while replica_client.command(&["FUNCTION", "LIST", "WITHCODE"]).await.unwrap() != listed {
+        assert!(started.elapsed() < Duration::from_secs(5), "replica did not load the master's functions");
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+
+    replica.shutdown().await.unwrap();
+    master.shutdown().await.unwrap();
+}
+
+// busy-reply-threshold가 지나면 다른 클라이언트는 BUSY를 받고, SCRIPT KILL로 끝없는 스크립트를 멈출 수 있음
+#[tokio::test]
+async fn script_kill_stops_a_runaway_script() {
+    let server = TestServer::start_with(|builder| builder.option("busy-reply-threshold", "100")).await.unwrap();
+    let mut runner = server.client().await.unwrap();
+    let mut other = server.client().await.unwrap();
+
+    assert_eq!(other.command(&["SCRIPT", "KILL"]).await.unwrap(), RespValue::Error("NOTBUSY No scripts in execution right now.".into()));
+
+    runner.send_command(&["EVAL", "while true do end", "0"]).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    assert!(error_message(other.command(&["PING"]).await.unwrap()).starts_with("BUSY "));
+    assert!(error_message(other.command(&["FUNCTION", "KILL"]).await.unwrap()).starts_with("BUSY "));
+    assert_eq!(other.command(&["SCRIPT", "KILL"]).await.unwrap(), ok());
+
+    let message = error_message(runner.read_reply().await.unwrap());
+    assert!(message.starts_with("ERR Script killed by user with SCRIPT KILL..."), "{}", message);
+    assert_eq!(other.command(&["PING"]).await.unwrap(), RespValue::SimpleString("PONG".into()));
+    assert_eq!(runner.command(&["PING"]).await.unwrap(), RespValue::SimpleString("PONG".into()));
+
+    // pcall로 감싸도 멈춤
+    runner.send_command(&["EVAL", "return pcall(function() while true do end end)", "0"]).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    assert_eq!(other.command(&["SCRIPT", "KILL"]).await.unwrap(), ok());
+    assert!(error_message(runner.read_reply().await.unwrap()).starts_with("ERR Script killed"));
+
+    server.shutdown().await.unwrap();
+}
+
+// 스크립트가 도는 동안 주기 이벤트로 일반 큐가 차도 새 연결은 받아서 BUSY로 답하고 SCRIPT KILL을 처리함
+#[tokio::test]
+async fn fresh_connections_can_kill_a_script_after_the_queue_fills() {
+    let server = TestServer::start_with(|builder| builder.option("busy-reply-threshold", "100").option("event-queue-capacity", "2"))
+        .await
+        .unwrap();
+    let mut runner = server.client().await.unwrap();
+
+    runner.send_command(&["EVAL", "while true do end", "0"]).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(600)).await;
+    let mut fresh = tokio::time::timeout(Duration::from_secs(2), server.client()).await.unwrap().unwrap();
+    let ping = tokio::time::timeout(Duration::from_secs(2), fresh.command(&["PING"])).await.expect("PING on a new connection timed out");
+    assert!(error_message(ping.unwrap()).starts_with("BUSY "));
+    assert_eq!(fresh.command(&["SCRIPT", "KILL"]).await.unwrap(), ok());
+    assert!(error_message(runner.read_reply().await.unwrap()).starts_with("ERR Script killed"));
+    assert_eq!(fresh.command(&["PING"]).await.unwrap(), RespValue::SimpleString("PONG".into()));
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn scripts_that_wrote_cannot_be_killed() {
+    let server = TestServer::start_with(|builder| builder.option("busy-reply-threshold", "100")).await.unwrap();
+    let mut runner = server.client().await.unwrap();
+    let mut other = server.client().await.unwrap();
+
+    let script = "redis.call('SET', KEYS[1], 'v') for i = 1, 5000000 do end return 1";
+    runner.send_command(&["EVAL", script, "1", "k"]).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    assert!(error_message(other.command(&["SCRIPT", "KILL"]).await.unwrap()).starts_with("UNKILLABLE "));
+    assert_eq!(runner.read_reply().await.unwrap(), RespValue::Integer(1));
+    assert_eq!(other.command(&["GET", "k"]).await.unwrap(), bulk("v"));
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn function_kill_stops_a_runaway_function() {
+    let server = TestServer::start_with(|builder| builder.option("busy-reply-threshold", "100")).await.unwrap();
+    let mut runner = server.client().await.unwrap();
+    let mut other = server.client().await.unwrap();
+
+    let library = "#!lua name=spin\nredis.register_function('spin', function() while true do end end)";
+    assert_eq!(runner.command(&["FUNCTION", "LOAD", library]).await.unwrap(), bulk("spin"));
+    runner.send_command(&["FCALL", "spin", "0"]).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    assert!(error_message(other.command(&["SCRIPT", "KILL"]).await.unwrap()).contains("FUNCTION KILL"));
+    assert_eq!(other.command(&["FUNCTION", "KILL"]).await.unwrap(), ok());
+    assert!(error_message(runner.read_reply().await.unwrap()).starts_with("ERR Script killed by user with FUNCTION KILL..."));
+
+    // 라이브러리를 읽는 코드는 시간 제한에 걸림
+    let library = "#!lua name=stuck\nwhile true do end";
+    assert!(error_message(runner.command(&["FUNCTION", "LOAD", library]).await.unwrap()).contains("FUNCTION LOAD timeout"));
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn scripts_cannot_build_huge_strings() {
+    let server = TestServer::start().await.unwrap();
+    let mut client = server.client().await.unwrap();
+
+    for script in [
+        "return string.rep('x', 100000000000)",
+        "return string.rep('ab', 300000000)",
+    ] {
+        let message = error_message(client.command(&["EVAL", script, "0"]).await.unwrap());
+        assert!(message.contains("resulting string too large"), "{}: {}", script, message);
+    }
+    assert_eq!(client.command(&["PING"]).await.unwrap(), RespValue::SimpleString("PONG".into()));
+
+    server.shutdown().await.unwrap();
+}
@@ -0,0 +1,199 @@
+use redis_starter_rust::test_support::{bulk, info_field, ok, TestServer};
+use redis_starter_rust::{Client, RespValue};
+use std::time::Duration;
+
+const MASTER_NAME: &str = "mymaster";
+// 다운 판정과 페일오버를 기다리는 시간
+const STATE_TIMEOUT: Duration = Duration::from_secs(20);
+
+// 조건을 다시 확인하기 전에 부름, 기다린 시간이 STATE_TIMEOUT을 넘으면 실패함
+async fn retry(started: tokio::time::Instant, what: &str) {
+    assert!(started.elapsed() < STATE_TIMEOUT, "{} did not happen in time", what);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+}
+
+async fn start_sentinel() -> TestServer {
+    TestServer::start_with(|builder| {
+        builder
+            .args(["--sentinel"])
+            .option("sentinel-down-after-milliseconds", "500")
+            .option("sentinel-failover-timeout", "5000")
+    })
+    .await
+    .unwrap()
+}
+
+// SENTINEL MASTER/REPLICAS의 한 항목, RESP2에서는 필드 이름과 값을 번갈아 담은 배열임
+fn field(entry: &RespValue, name: &str) -> String {
+    let RespValue::Array(items) = entry else {
+        panic!("expected a field list, got {:?}", entry);
+    };
+    let value = items
+        .chunks(2)
+        .find(|pair| pair[0] == bulk(name))
+        .unwrap_or_else(|| panic!("no {} field in {:?}", name, entry));
+    let RespValue::BulkString(value) = &value[1] else {
+        panic!("unexpected {} value {:?}", name, value[1]);
+    };
+    String::from_utf8_lossy(value).into_owned()
+}
+
+async fn master_field(sentinel: &mut Client, name: &str) -> String {
+    field(&sentinel.command(&["SENTINEL", "MASTER", MASTER_NAME]).await.unwrap(), name)
+}
+
+async fn master_port(sentinel: &mut Client) -> u16 {
+    let reply = sentinel.command(&["SENTINEL", "GET-MASTER-ADDR-BY-NAME", MASTER_NAME]).await.unwrap();
+    let RespValue::Array(addr) = &reply else {
+        panic!("sentinel did not return the master address: {:?}", reply);
+    };
+    assert_eq!(addr[0], bulk("127.0.0.1"));
+    let RespValue::BulkString(port) = &addr[1] else {
+        panic!("unexpected master port {:?}", addr[1]);
+    };
+    String::from_utf8_lossy(port).parse().unwrap()
+}
+
+#[tokio::test]
+async fn sentinel_monitor_registers_and_removes_masters() {
+    let master = TestServer::start().await.unwrap();
+    let sentinel = start_sentinel().await;
+    let mut client = sentinel.client().await.unwrap();
+    let port = master.port().to_string();
+
+    assert_eq!(client.command(&["SENTINEL", "MASTERS"]).await.unwrap(), RespValue::Array(vec![]));
+    assert_eq!(client.command(&["SENTINEL", "GET-MASTER-ADDR-BY-NAME", MASTER_NAME]).await.unwrap(), RespValue::NullArray);
+    assert_eq!(
+        client.command(&["SENTINEL", "MONITOR", MASTER_NAME, "127.0.0.1", &port, "2"]).await.unwrap(),
+        ok()
+    );
+    assert_eq!(
+        client.command(&["SENTINEL", "MONITOR", MASTER_NAME, "127.0.0.1", &port, "2"]).await.unwrap(),
+        RespValue::Error("ERR Duplicated master name".into())
+    );
+    assert_eq!(
+        client.command(&["SENTINEL", "MONITOR", "other", "127.0.0.1", &port, "0"]).await.unwrap(),
+        RespValue::Error("ERR Quorum must be 1 or greater.".into())
+    );
+
+    assert_eq!(master_port(&mut client).await, master.port());
+    assert_eq!(master_field(&mut client, "name").await, MASTER_NAME);
+    assert_eq!(master_field(&mut client, "flags").await, "master");
+    assert_eq!(master_field(&mut client, "quorum").await, "2");
+    assert_eq!(master_field(&mut client, "config-epoch").await, "0");
+    // 레플리카가 없는 마스터는 페일오버할 수 없음
+    assert_eq!(
+        client.command(&["SENTINEL", "FAILOVER", MASTER_NAME]).await.unwrap(),
+        RespValue::Error("NOGOODSLAVE No suitable replica to promote".into())
+    );
+    // sentinel 모드에서는 데이터 명령을 받지 않음
+    assert!(matches!(client.command(&["SET", "key", "value"]).await.unwrap(), RespValue::Error(_)));
+
+    assert_eq!(client.command(&["SENTINEL", "REMOVE", MASTER_NAME]).await.unwrap(), ok());
+    assert_eq!(client.command(&["SENTINEL", "GET-MASTER-ADDR-BY-NAME", MASTER_NAME]).await.unwrap(), RespValue::NullArray);
+    assert_eq!(
+        client.command(&["SENTINEL", "MASTER", MASTER_NAME]).await.unwrap(),
+        RespValue::Error("ERR No such master with that name".into())
+    );
+
+    sentinel.shutdown().await.unwrap();
+    master.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn sentinel_announces_the_configured_address_in_hello_messages() {
+    let master = TestServer::start().await.unwrap();
+    let sentinel = TestServer::start_with(|builder| builder.args(["--sentinel"]).option("sentinel-announce-ip", "10.0.0.9")).await.unwrap();
+    let mut subscriber = master.client().await.unwrap();
+    subscriber.command(&["SUBSCRIBE", "__sentinel__:hello"]).await.unwrap();
+    let mut client = sentinel.client().await.unwrap();
+    let port = master.port().to_string();
+    client.command(&["SENTINEL", "MONITOR", MASTER_NAME, "127.0.0.1", &port, "1"]).await.unwrap();
+
+    // hello: <ip>,<port>,<runid>,<current-epoch>,<master-name>,<master-ip>,<master-port>,<master-config-epoch>
+    let message = tokio::time::timeout(STATE_TIMEOUT, subscriber.read_reply()).await.expect("no hello message").unwrap();
+    let RespValue::Array(items) = &message else {
+        panic!("unexpected message {:?}", message);
+    };
+    let RespValue::BulkString(payload) = &items[2] else {
+        panic!("unexpected hello payload {:?}", items[2]);
+    };
+    let payload = String::from_utf8_lossy(payload);
+    assert!(payload.starts_with(&format!("10.0.0.9,{},", sentinel.port())), "{}", payload);
+    assert!(payload.ends_with(&format!(",{},127.0.0.1,{},0", MASTER_NAME, port)), "{}", payload);
+
+    sentinel.shutdown().await.unwrap();
+    master.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn sentinel_marks_an_unreachable_master_down() {
+    let master = TestServer::start().await.unwrap();
+    let sentinel = start_sentinel().await;
+    let mut client = sentinel.client().await.unwrap();
+    let port = master.port().to_string();
+    client.command(&["SENTINEL", "MONITOR", MASTER_NAME, "127.0.0.1", &port, "1"]).await.unwrap();
+
+    master.shutdown().await.unwrap();
+    // 쿼럼이 1이므로 주관적 다운이 곧 객관적 다운이 됨, 승격할 레플리카가 없으므로 주소는 그대로임
+    let started = tokio::time::Instant::now();
+    while !master_field(&mut client, "flags").await.contains("o_down") {
+        retry(started, "the master being marked o_down").await;
+    }
+    assert!(master_field(&mut client, "flags").await.starts_with("master,s_down"));
+    let RespValue::BulkString(info) = client.command(&["INFO", "sentinel"]).await.unwrap() else {
+        panic!("INFO did not return a bulk string");
+    };
+    assert!(String::from_utf8_lossy(&info).contains(&format!("master0:name={},status=odown", MASTER_NAME)));
+    assert_eq!(master_port(&mut client).await, port.parse::<u16>().unwrap());
+
+    sentinel.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn sentinel_failover_promotes_the_replica_and_ends() {
+    let master = TestServer::start().await.unwrap();
+    let replica_of = format!("127.0.0.1 {}", master.port());
+    let replica = TestServer::start_with(|builder| builder.option("replicaof", replica_of)).await.unwrap();
+    let sentinel = start_sentinel().await;
+    let mut client = sentinel.client().await.unwrap();
+    let master_port_text = master.port().to_string();
+    client.command(&["SENTINEL", "MONITOR", MASTER_NAME, "127.0.0.1", &master_port_text, "1"]).await.unwrap();
+
+    // 마스터의 INFO로 레플리카를 찾고, 레플리카의 INFO로 역할을 확인해야 승격 후보가 됨
+    let started = tokio::time::Instant::now();
+    loop {
+        let replicas = client.command(&["SENTINEL", "REPLICAS", MASTER_NAME]).await.unwrap();
+        if matches!(&replicas, RespValue::Array(entries) if entries.len() == 1 && field(&entries[0], "role-reported") == "slave") {
+            break;
+        }
+        retry(started, "sentinel discovering the replica").await;
+    }
+
+    // 강제 페일오버는 시작되면 끝날 때까지 다시 시작할 수 없음
+    assert_eq!(client.command(&["SENTINEL", "FAILOVER", MASTER_NAME]).await.unwrap(), ok());
+    assert_eq!(
+        client.command(&["SENTINEL", "FAILOVER", MASTER_NAME]).await.unwrap(),
+        RespValue::Error("INPROG Failover already in progress".into())
+    );
+    assert!(master_field(&mut client, "flags").await.contains("failover_in_progress"));
+
+    let started = tokio::time::Instant::now();
+    while master_port(&mut client).await != replica.port() {
+        retry(started, "sentinel switching to the promoted replica").await;
+    }
+    assert_eq!(master_field(&mut client, "flags").await, "master");
+    assert_eq!(master_field(&mut client, "config-epoch").await, "1");
+    let mut replica_client = replica.client().await.unwrap();
+    assert_eq!(info_field(&mut replica_client, "replication", "role").await.as_deref(), Some("master"));
+    // 옛 마스터는 새 마스터의 레플리카로 계속 감시함
+    let RespValue::Array(replicas) = client.command(&["SENTINEL", "REPLICAS", MASTER_NAME]).await.unwrap() else {
+        panic!("SENTINEL REPLICAS did not return an array");
+    };
+    assert_eq!(replicas.len(), 1);
+    assert_eq!(field(&replicas[0], "port"), master_port_text);
+
+    sentinel.shutdown().await.unwrap();
+    replica.shutdown().await.unwrap();
+    master.shutdown().await.unwrap();
+}
@@ -0,0 +1,468 @@
+use redis_starter_rust::test_support::{bulk, error, info_field, ok, TestServer};
+use redis_starter_rust::{Client, RespValue};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+const REPLICATION_TIMEOUT: Duration = Duration::from_secs(5);
+
+// 복제는 비동기라 기다릴 수밖에 없음, 시간 안에 기대한 응답이 오지 않으면 마지막 응답으로 실패함
+async fn wait_for_reply(client: &mut Client, args: &[&str], expected: &RespValue) {
+    let deadline = tokio::time::Instant::now() + REPLICATION_TIMEOUT;
+    loop {
+        let reply = client.command(args).await.unwrap();
+        if &reply == expected {
+            return;
+        }
+        assert!(tokio::time::Instant::now() < deadline, "{:?} returned {:?}, expected {:?}", args, reply, expected);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+}
+
+#[tokio::test]
+async fn set_and_get() {
+    let server = TestServer::start().await.unwrap();
+    let mut client = server.client().await.unwrap();
+
+    assert_eq!(client.command(&["SET", "key", "value"]).await.unwrap(), ok());
+    assert_eq!(client.command(&["GET", "key"]).await.unwrap(), bulk("value"));
+    assert_eq!(client.command(&["GET", "missing"]).await.unwrap(), RespValue::NullBulk);
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn pipelined_replies_arrive_in_order() {
+    let server = TestServer::start().await.unwrap();
+    let mut client = server.client().await.unwrap();
+
+    client.send_command(&["SET", "key", "1"]).await.unwrap();
+    client.send_command(&["GET", "key"]).await.unwrap();
+    client.send_command(&["DEL", "key"]).await.unwrap();
+    client.send_command(&["GET", "key"]).await.unwrap();
+    assert_eq!(client.read_reply().await.unwrap(), ok());
+    assert_eq!(client.read_reply().await.unwrap(), bulk("1"));
+    assert_eq!(client.read_reply().await.unwrap(), RespValue::Integer(1));
+    assert_eq!(client.read_reply().await.unwrap(), RespValue::NullBulk);
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn errors_keep_the_connection_open() {
+    let server = TestServer::start().await.unwrap();
+    let mut client = server.client().await.unwrap();
+
+    assert!(matches!(client.command(&["NOSUCHCOMMAND"]).await.unwrap(), RespValue::Error(_)));
+    assert!(matches!(client.command(&["GET"]).await.unwrap(), RespValue::Error(_)));
+    assert_eq!(client.command(&["PING"]).await.unwrap(), RespValue::SimpleString("PONG".into()));
+
+    server.shutdown().await.unwrap();
+}
+
+// 에러에 되돌려 준 인자의 줄바꿈이 응답을 끊지 않아야 함, 끊기면 뒤에 가짜 응답이 하나 더 읽힘
+#[tokio::test]
+async fn echoed_arguments_cannot_inject_replies() {
+    let server = TestServer::start().await.unwrap();
+    let mut client = server.client().await.unwrap();
+
+    let RespValue::Error(message) = client.command(&["NOSUCH\r\n+X"]).await.unwrap() else {
+        panic!("unknown command did not fail");
+    };
+    assert!(message.contains("NOSUCH  +X"), "{}", message);
+    assert!(!message.contains('\r') && !message.contains('\n'));
+    let RespValue::Error(message) = client.command(&["CONFIG", "SET", "maxmemory", "1\r\n+INJECTED"]).await.unwrap() else {
+        panic!("CONFIG SET accepted a bad maxmemory");
+    };
+    assert!(!message.contains('\r') && !message.contains('\n'), "{}", message);
+    assert_eq!(client.command(&["PING"]).await.unwrap(), RespValue::SimpleString("PONG".into()));
+
+    server.shutdown().await.unwrap();
+}
+
+// Redis처럼 인자를 '인자' 뒤에 공백을 붙여 늘어놓고, 합쳐서 128자가 넘으면 거기서 자름
+#[tokio::test]
+async fn unknown_commands_echo_their_leading_arguments() {
+    let server = TestServer::start().await.unwrap();
+    let mut client = server.client().await.unwrap();
+
+    assert_eq!(
+        client.command(&["NOSUCH", "key", "some value"]).await.unwrap(),
+        error("ERR unknown command 'NOSUCH', with args beginning with: 'key' 'some value' ")
+    );
+    let long = "x".repeat(200);
+    let expected = format!("ERR unknown command 'NOSUCH', with args beginning with: 'a' '{}' ", "x".repeat(124));
+    assert_eq!(client.command(&["NOSUCH", "a", &long, "dropped"]).await.unwrap(), error(&expected));
+
+    server.shutdown().await.unwrap();
+}
+
+// 하위 명령의 인자 수가 틀리면 "명령|하위 명령" 이름으로, 없는 하위 명령이면 HELP를 안내함
+#[tokio::test]
+async fn subcommand_errors_use_the_redis_messages() {
+    let server = TestServer::start().await.unwrap();
+    let mut client = server.client().await.unwrap();
+
+    for (args, expected) in [
+        (&["GET"][..], "ERR wrong number of arguments for 'get' command"),
+        (&["OBJECT", "ENCODING"], "ERR wrong number of arguments for 'object|encoding' command"),
+        (&["LATENCY", "HISTORY"], "ERR wrong number of arguments for 'latency|history' command"),
+        (&["MEMORY", "USAGE"], "ERR wrong number of arguments for 'memory|usage' command"),
+        (&["CONFIG", "GET"], "ERR wrong number of arguments for 'config|get' command"),
+        (&["client", "setname"], "ERR wrong number of arguments for 'client|setname' command"),
+        (&["PUBSUB", "NUMPAT", "extra"], "ERR wrong number of arguments for 'pubsub|numpat' command"),
+        (&["SCRIPT", "EXISTS"], "ERR wrong number of arguments for 'script|exists' command"),
+        (&["CLIENT", "nosuch"], "ERR unknown subcommand 'nosuch'. Try CLIENT HELP."),
+        (&["latency", "nosuch"], "ERR unknown subcommand 'nosuch'. Try LATENCY HELP."),
+        (&["CONFIG", "nosuch", "x"], "ERR unknown subcommand 'nosuch'. Try CONFIG HELP."),
+        (&["OBJECT", "nosuch", "key"], "ERR unknown subcommand 'nosuch'. Try OBJECT HELP."),
+    ] {
+        assert_eq!(client.command(args).await.unwrap(), error(expected), "{:?}", args);
+    }
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn ping_echoes_an_optional_message() {
+    let server = TestServer::start().await.unwrap();
+    let mut client = server.client().await.unwrap();
+
+    assert_eq!(client.command(&["PING", "hello world"]).await.unwrap(), bulk("hello world"));
+    assert_eq!(
+        client.command(&["PING", "a", "b"]).await.unwrap(),
+        RespValue::Error("ERR wrong number of arguments for 'ping' command".into())
+    );
+
+    server.shutdown().await.unwrap();
+}
+
+// Redis처럼 명령 이름은 대소문자를 가리지 않음, redis-cli는 입력한 그대로 보냄
+#[tokio::test]
+async fn command_names_are_case_insensitive() {
+    let server = TestServer::start().await.unwrap();
+    let mut client = server.client().await.unwrap();
+
+    assert_eq!(client.command(&["ping"]).await.unwrap(), RespValue::SimpleString("PONG".into()));
+    assert_eq!(client.command(&["set", "key", "value", "px", "100000"]).await.unwrap(), ok());
+    assert_eq!(client.command(&["Get", "key"]).await.unwrap(), bulk("value"));
+    client.command(&["zAdd", "zset", "1", "a"]).await.unwrap();
+    assert_eq!(
+        client.command(&["zmpop", "1", "zset", "min"]).await.unwrap(),
+        RespValue::Array(vec![bulk("zset"), RespValue::Array(vec![RespValue::Array(vec![bulk("a"), bulk("1")])])])
+    );
+    assert_eq!(
+        client.command(&["nosuchcommand"]).await.unwrap(),
+        error("ERR unknown command 'nosuchcommand', with args beginning with: ")
+    );
+
+    // telnet처럼 보낸 인라인 명령
+    let mut stream = TcpStream::connect(server.addr()).await.unwrap();
+    stream.write_all(b"ping\r\nset inline value\r\nget inline\r\n").await.unwrap();
+    let expected = b"+PONG\r\n+OK\r\n$5\r\nvalue\r\n";
+    let mut replies = vec![0u8; expected.len()];
+    tokio::time::timeout(REPLICATION_TIMEOUT, stream.read_exact(&mut replies)).await.unwrap().unwrap();
+    assert_eq!(replies, expected);
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn info_replconf_and_psync_are_dispatched() {
+    let server = TestServer::start().await.unwrap();
+    let mut client = server.client().await.unwrap();
+
+    assert_eq!(info_field(&mut client, "replication", "role").await.as_deref(), Some("master"));
+    assert!(matches!(client.command(&["INFO"]).await.unwrap(), RespValue::BulkString(_)));
+    assert_eq!(client.command(&["REPLCONF", "listening-port", "6380"]).await.unwrap(), ok());
+
+    // 인자가 모자라면 CONFIG의 메시지가 아니라 각 명령의 이름으로 알려 줌
+    for (args, name) in [(&["INFO", "server", "clients"][..], "INFO"), (&["REPLCONF", "ACK"], "REPLCONF"), (&["PSYNC", "?"], "PSYNC")] {
+        let RespValue::Error(message) = client.command(args).await.unwrap() else {
+            panic!("{:?} was accepted", args);
+        };
+        let message = message.to_uppercase();
+        assert!(message.contains(name) && !message.contains("CONFIG"), "{}", message);
+    }
+
+    let mut replica = TcpStream::connect(server.addr()).await.unwrap();
+    replica.write_all(b"*3\r\n$5\r\nPSYNC\r\n$1\r\n?\r\n$2\r\n-1\r\n").await.unwrap();
+    let mut reply = vec![0u8; b"+FULLRESYNC".len()];
+    tokio::time::timeout(REPLICATION_TIMEOUT, replica.read_exact(&mut reply)).await.unwrap().unwrap();
+    assert_eq!(reply, b"+FULLRESYNC");
+
+    server.shutdown().await.unwrap();
+}
+
+async fn info(client: &mut Client, section: &str) -> String {
+    let RespValue::BulkString(info) = client.command(&["INFO", section]).await.unwrap() else {
+        panic!("INFO {} did not return a bulk string", section);
+    };
+    String::from_utf8(info).unwrap()
+}
+
+// 빌드 스크립트가 커밋마다 다시 돌아서 INFO의 SHA가 지금 HEAD와 같아야 함
+#[tokio::test]
+async fn info_reports_the_current_git_sha() {
+    let Ok(output) = std::process::Command::new("git").args(["rev-parse", "--short=8", "HEAD"]).output() else {
+        return;
+    };
+    if !output.status.success() {
+        return;
+    }
+    let head = String::from_utf8(output.stdout).unwrap().trim().to_string();
+    let server = TestServer::start().await.unwrap();
+    let mut client = server.client().await.unwrap();
+
+    assert_eq!(info_field(&mut client, "server", "redis_git_sha1").await, Some(head));
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn info_keyspace_counts_keys_and_expires() {
+    let server = TestServer::start().await.unwrap();
+    let mut client = server.client().await.unwrap();
+    assert!(!info(&mut client, "keyspace").await.contains("db0:"));
+
+    for i in 0..10 {
+        client.command(&["SET", &format!("key:{}", i), "value"]).await.unwrap();
+    }
+    for i in 0..3 {
+        client.command(&["EXPIRE", &format!("key:{}", i), "1000"]).await.unwrap();
+    }
+    let keyspace = info(&mut client, "keyspace").await;
+    let line = keyspace.lines().find_map(|line| line.strip_prefix("db0:")).expect("no db0 line");
+    let fields: Vec<&str> = line.split(',').collect();
+    assert_eq!(&fields[..2], ["keys=10", "expires=3"]);
+    let avg_ttl: u64 = fields[2].strip_prefix("avg_ttl=").unwrap().parse().unwrap();
+    assert!(avg_ttl > 990_000 && avg_ttl <= 1_000_000, "{}", avg_ttl);
+    assert!(info(&mut client, "default").await.contains("db0:keys=10,expires=3,"));
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn per_command_histograms_stay_out_of_info_stats() {
+    let server = TestServer::start().await.unwrap();
+    let mut client = server.client().await.unwrap();
+    client.command(&["SET", "key", "value"]).await.unwrap();
+    client.command(&["GET", "key"]).await.unwrap();
+
+    let stats = info(&mut client, "stats").await;
+    assert!(stats.contains("total_commands_processed:"));
+    assert!(!stats.contains("cmdrequestsize_") && !stats.contains("cmdreplysize_"), "{}", stats);
+    assert!(!info(&mut client, "default").await.contains("cmdrequestsize_"));
+    let commandstats = info(&mut client, "commandstats").await;
+    assert!(commandstats.contains("cmdstat_get:") && commandstats.contains("cmdrequestsize_get:") && commandstats.contains("cmdreplysize_get:"));
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn keys_expire() {
+    let server = TestServer::start().await.unwrap();
+    let mut client = server.client().await.unwrap();
+
+    assert_eq!(client.command(&["SET", "key", "value", "PX", "100"]).await.unwrap(), ok());
+    assert!(matches!(client.command(&["PTTL", "key"]).await.unwrap(), RespValue::Integer(ttl) if ttl > 0 && ttl <= 100));
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert_eq!(client.command(&["GET", "key"]).await.unwrap(), RespValue::NullBulk);
+    assert_eq!(client.command(&["EXISTS", "key"]).await.unwrap(), RespValue::Integer(0));
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn replica_syncs_and_follows_the_master() {
+    let master = TestServer::start().await.unwrap();
+    let mut master_client = master.client().await.unwrap();
+    assert_eq!(master_client.command(&["SET", "before", "1"]).await.unwrap(), ok());
+
+    let replica_of = format!("127.0.0.1 {}", master.port());
+    let replica = TestServer::start_with(|builder| builder.option("replicaof", replica_of)).await.unwrap();
+    let mut replica_client = replica.client().await.unwrap();
+
+    // 핸드셰이크 뒤 전체 동기화로 받은 키와, 그 뒤 명령 스트림으로 받은 키
+    wait_for_reply(&mut replica_client, &["GET", "before"], &bulk("1")).await;
+    assert_eq!(master_client.command(&["SET", "after", "2"]).await.unwrap(), ok());
+    wait_for_reply(&mut replica_client, &["GET", "after"], &bulk("2")).await;
+    assert_eq!(master_client.command(&["DEL", "before"]).await.unwrap(), RespValue::Integer(1));
+    wait_for_reply(&mut replica_client, &["GET", "before"], &RespValue::NullBulk).await;
+
+    assert_eq!(info_field(&mut master_client, "replication", "connected_slaves").await.as_deref(), Some("1"));
+
+    replica.shutdown().await.unwrap();
+    master.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn replica_resolves_a_master_hostname_given_as_two_arguments() {
+    let master = TestServer::start().await.unwrap();
+    let mut master_client = master.client().await.unwrap();
+    assert_eq!(master_client.command(&["SET", "key", "1"]).await.unwrap(), ok());
+
+    let port = master.port().to_string();
+    let replica = TestServer::start_with(|builder| builder.args(["--replicaof", "localhost", port.as_str()])).await.unwrap();
+    let mut replica_client = replica.client().await.unwrap();
+    wait_for_reply(&mut replica_client, &["GET", "key"], &bulk("1")).await;
+
+    assert_eq!(info_field(&mut replica_client, "replication", "master_host").await.as_deref(), Some("localhost"));
+
+    replica.shutdown().await.unwrap();
+    master.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn a_config_file_with_an_error_refuses_to_start() {
+    let path = std::env::temp_dir().join(format!("redis-test-{}-broken.conf", std::process::id()));
+    std::fs::write(&path, "requirepass secret\nrequirepas typo\n").unwrap();
+    let result = TestServer::start_with(|builder| builder.config_file(path.display().to_string())).await;
+    std::fs::remove_file(&path).unwrap();
+
+    // 잘못된 줄을 건너뛰고 뜨면 requirepass가 빠진 서버가 되므로 아예 뜨지 않아야 함
+    let error = result.err().expect("server started with a broken config file");
+    assert!(error.starts_with("FATAL CONFIG FILE ERROR: "), "{}", error);
+    assert!(error.contains(":2: "), "{}", error);
+}
+
+#[tokio::test]
+async fn servers_in_one_process_keep_their_own_rate_limits() {
+    let limited = TestServer::start_with(|builder| builder.option("client-max-commands-per-sec", "20")).await.unwrap();
+    let unlimited = TestServer::start().await.unwrap();
+    for server in [&limited, &unlimited] {
+        let mut client = server.client().await.unwrap();
+        for _ in 0..30 {
+            client.command(&["PING"]).await.unwrap();
+        }
+    }
+
+    let mut client = limited.client().await.unwrap();
+    assert_ne!(info_field(&mut client, "stats", "total_rate_limited_waits").await.as_deref(), Some("0"));
+    let mut client = unlimited.client().await.unwrap();
+    assert_eq!(info_field(&mut client, "stats", "total_rate_limited_waits").await.as_deref(), Some("0"));
+
+    limited.shutdown().await.unwrap();
+    unlimited.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn servers_in_one_process_write_their_own_log_files() {
+    let path = |name: &str| std::env::temp_dir().join(format!("redis-test-{}-{}.log", std::process::id(), name));
+    let (first_log, second_log) = (path("first"), path("second"));
+    let first = TestServer::start_with(|builder| builder.option("logfile", first_log.display().to_string())).await.unwrap();
+    let second = TestServer::start_with(|builder| builder.option("logfile", second_log.display().to_string()).option("loglevel", "warning"))
+        .await
+        .unwrap();
+    let (first_port, second_port) = (first.port(), second.port());
+    first.shutdown().await.unwrap();
+    second.shutdown().await.unwrap();
+
+    // 나중에 뜬 서버의 logfile과 loglevel이 먼저 뜬 서버의 로그를 가져가지 않아야 함
+    let first_lines = std::fs::read_to_string(&first_log).unwrap();
+    let second_lines = std::fs::read_to_string(&second_log).unwrap();
+    std::fs::remove_file(&first_log).unwrap();
+    std::fs::remove_file(&second_log).unwrap();
+    assert!(first_lines.contains(&format!("Listening on 127.0.0.1:{}", first_port)), "{}", first_lines);
+    assert!(first_lines.contains("Redis is now ready to exit"), "{}", first_lines);
+    assert!(!first_lines.contains(&format!(":{}", second_port)), "{}", first_lines);
+    assert!(!second_lines.contains(&format!("Listening on 127.0.0.1:{}", second_port)), "{}", second_lines);
+}
+
+// shed 정책으로 거절한 명령은 스크립트의 BUSY와 다른 코드로 답함
+#[tokio::test]
+async fn shed_commands_reply_overloaded_instead_of_busy() {
+    let server = TestServer::start_with(|builder| builder.option("event-queue-capacity", "4").option("overload-policy", "shed")).await.unwrap();
+    let mut sleeper = server.client().await.unwrap();
+    let mut client = server.client().await.unwrap();
+
+    // 이벤트 루프가 자는 동안 파이프라인한 명령이 큐를 채움
+    sleeper.send_command(&["DEBUG", "SLEEP", "0.3"]).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    for _ in 0..10 {
+        client.send_command(&["PING"]).await.unwrap();
+    }
+    let mut shed = 0;
+    for _ in 0..10 {
+        match client.read_reply().await.unwrap() {
+            RespValue::SimpleString(pong) => assert_eq!(pong, "PONG"),
+            reply => {
+                assert_eq!(reply, error("OVERLOADED Server is overloaded, command shed. Retry later."));
+                shed += 1;
+            }
+        }
+    }
+    assert!(shed > 0);
+    assert_eq!(sleeper.read_reply().await.unwrap(), ok());
+    assert_eq!(info_field(&mut client, "stats", "event_queue_shed_commands").await, Some(shed.to_string()));
+
+    server.shutdown().await.unwrap();
+}
+
+// 연결을 닫기 전까지 받은 바이트 전부, 프로토콜 에러 뒤에는 에러 한 줄을 보내고 바로 닫아야 함
+async fn read_until_closed(stream: &mut TcpStream) -> Vec<u8> {
+    let mut received = Vec::new();
+    tokio::time::timeout(REPLICATION_TIMEOUT, stream.read_to_end(&mut received)).await.unwrap().unwrap();
+    received
+}
+
+// 여러 MB짜리 값이 TCP 쓰기 여러 번에 나뉘어 와도 FrameDecoder가 모아서 한 요청으로 읽음
+#[tokio::test]
+async fn multi_megabyte_values_survive_split_writes() {
+    let server = TestServer::start().await.unwrap();
+    let value = "v".repeat(4 * 1024 * 1024 + 7);
+
+    let mut stream = TcpStream::connect(server.addr()).await.unwrap();
+    let request = format!("*3\r\n$3\r\nSET\r\n$3\r\nbig\r\n${}\r\n{}\r\n", value.len(), value);
+    for chunk in request.as_bytes().chunks(300 * 1024) {
+        stream.write_all(chunk).await.unwrap();
+        stream.flush().await.unwrap();
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+    let mut reply = vec![0u8; b"+OK\r\n".len()];
+    tokio::time::timeout(REPLICATION_TIMEOUT, stream.read_exact(&mut reply)).await.unwrap().unwrap();
+    assert_eq!(reply, b"+OK\r\n");
+
+    let mut client = server.client().await.unwrap();
+    assert_eq!(client.command(&["GET", "big"]).await.unwrap(), bulk(&value));
+
+    server.shutdown().await.unwrap();
+}
+
+// 길이가 proto-max-bulk-len을 넘는 bulk 헤더는 값을 받기 전에 거절하고 연결을 닫음
+#[tokio::test]
+async fn bulk_headers_over_proto_max_bulk_len_are_rejected() {
+    let server = TestServer::start_with(|builder| builder.option("proto-max-bulk-len", "1mb")).await.unwrap();
+
+    let mut stream = TcpStream::connect(server.addr()).await.unwrap();
+    stream.write_all(format!("*3\r\n$3\r\nSET\r\n$1\r\nk\r\n${}\r\n", 1024 * 1024 + 1).as_bytes()).await.unwrap();
+    assert_eq!(read_until_closed(&mut stream).await, b"-ERR Protocol error: invalid bulk length\r\n");
+
+    // 한도 안의 값은 그대로 받고, 다른 연결은 영향을 받지 않음
+    let mut client = server.client().await.unwrap();
+    let value = "v".repeat(1024 * 1024);
+    assert_eq!(client.command(&["SET", "k", &value]).await.unwrap(), ok());
+    assert_eq!(client.command(&["GET", "k"]).await.unwrap(), bulk(&value));
+
+    server.shutdown().await.unwrap();
+}
+
+// 개행 없이 PROTO_INLINE_MAX_SIZE(64KB)를 넘게 쌓이는 인라인 요청은 끝까지 기다리지 않고 거절함
+#[tokio::test]
+async fn oversized_inline_requests_are_rejected() {
+    let server = TestServer::start().await.unwrap();
+
+    let mut stream = TcpStream::connect(server.addr()).await.unwrap();
+    let request = format!("SET k {}", "v".repeat(64 * 1024));
+    stream.write_all(request.as_bytes()).await.unwrap();
+    assert_eq!(read_until_closed(&mut stream).await, b"-ERR Protocol error: too big inline request\r\n");
+
+    // 한도 안의 인라인 요청은 평소처럼 처리함
+    let mut stream = TcpStream::connect(server.addr()).await.unwrap();
+    stream.write_all(format!("SET k {}\r\n", "v".repeat(60 * 1024)).as_bytes()).await.unwrap();
+    let mut reply = vec![0u8; b"+OK\r\n".len()];
+    tokio::time::timeout(REPLICATION_TIMEOUT, stream.read_exact(&mut reply)).await.unwrap().unwrap();
+    assert_eq!(reply, b"+OK\r\n");
+
+    server.shutdown().await.unwrap();
+}
@@ -0,0 +1,139 @@
+use redis_starter_rust::test_support::{bulk, error, info_field, TestServer};
+use redis_starter_rust::{Client, RespValue};
+use std::collections::HashSet;
+
+const MEMBERS: [&str; 3] = ["a", "b", "c"];
+
+async fn members(client: &mut Client, args: &[&str]) -> Vec<String> {
+    let RespValue::Array(items) = client.command(args).await.unwrap() else {
+        panic!("{:?} did not return an array", args);
+    };
+    items
+        .into_iter()
+        .map(|item| match item {
+            RespValue::BulkString(member) => String::from_utf8(member).unwrap(),
+            other => panic!("unexpected item {:?}", other),
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn zrandmember_samples_with_and_without_repetition() {
+    let server = TestServer::start().await.unwrap();
+    let mut client = server.client().await.unwrap();
+    client.command(&["ZADD", "zset", "1", "a", "2", "b", "3", "c"]).await.unwrap();
+
+    let RespValue::BulkString(member) = client.command(&["ZRANDMEMBER", "zset"]).await.unwrap() else {
+        panic!("ZRANDMEMBER without count did not return a member");
+    };
+    assert!(MEMBERS.contains(&String::from_utf8(member).unwrap().as_str()));
+
+    // 양수 count는 겹치지 않고 집합 크기를 넘지 않음
+    let sampled = members(&mut client, &["ZRANDMEMBER", "zset", "2"]).await;
+    assert_eq!(sampled.len(), 2);
+    assert_ne!(sampled[0], sampled[1]);
+    let all: HashSet<String> = members(&mut client, &["ZRANDMEMBER", "zset", "10"]).await.into_iter().collect();
+    assert_eq!(all, MEMBERS.iter().map(|member| member.to_string()).collect());
+
+    // 음수 count는 정확히 그 수만큼, 같은 멤버가 여러 번 나올 수 있음
+    let repeated = members(&mut client, &["ZRANDMEMBER", "zset", "-20"]).await;
+    assert_eq!(repeated.len(), 20);
+    assert!(repeated.iter().all(|member| MEMBERS.contains(&member.as_str())));
+
+    // WITHSCORES는 멤버와 점수를 번갈아 보냄
+    let with_scores = members(&mut client, &["ZRANDMEMBER", "zset", "-5", "WITHSCORES"]).await;
+    assert_eq!(with_scores.len(), 10);
+    for pair in with_scores.chunks(2) {
+        let expected = (MEMBERS.iter().position(|member| *member == pair[0]).unwrap() + 1).to_string();
+        assert_eq!(pair[1], expected);
+    }
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn zrandmember_on_missing_keys_and_bad_arguments() {
+    let server = TestServer::start().await.unwrap();
+    let mut client = server.client().await.unwrap();
+
+    assert_eq!(client.command(&["ZRANDMEMBER", "missing"]).await.unwrap(), RespValue::NullBulk);
+    assert_eq!(client.command(&["ZRANDMEMBER", "missing", "-3"]).await.unwrap(), RespValue::Array(vec![]));
+    client.command(&["ZADD", "zset", "1", "a"]).await.unwrap();
+    assert_eq!(client.command(&["ZRANDMEMBER", "zset", "0"]).await.unwrap(), RespValue::Array(vec![]));
+    // 양수 count는 키 크기를 넘지 않으므로 아무리 커도 그대로 받음
+    assert_eq!(client.command(&["ZRANDMEMBER", "zset", "100000000000"]).await.unwrap(), RespValue::Array(vec![RespValue::BulkString(b"a".to_vec())]));
+
+    client.command(&["SET", "string", "value"]).await.unwrap();
+    let RespValue::Error(message) = client.command(&["ZRANDMEMBER", "string"]).await.unwrap() else {
+        panic!("ZRANDMEMBER accepted a string key");
+    };
+    assert!(message.starts_with("WRONGTYPE"));
+    for (args, expected) in [
+        (&["ZRANDMEMBER", "zset", "x"][..], "ERR value is not an integer or out of range"),
+        (&["ZRANDMEMBER", "zset", "1", "SCORES"], "ERR syntax error"),
+        (&["ZRANDMEMBER", "zset", "1", "WITHSCORES", "x"], "ERR syntax error"),
+        (&["ZRANDMEMBER", "zset", "-9223372036854775808"], "ERR value is out of range"),
+        (&["ZRANDMEMBER", "zset", "-4611686018427387904", "WITHSCORES"], "ERR value is out of range"),
+        (&["ZRANDMEMBER", "zset", "-100000000000"], "ERR value is out of range"),
+        (&["ZRANDMEMBER", "zset", "-16777217"], "ERR value is out of range"),
+        (&["ZRANDMEMBER", "zset", "-8388609", "WITHSCORES"], "ERR value is out of range"),
+    ] {
+        assert_eq!(client.command(args).await.unwrap(), RespValue::Error(expected.into()), "{:?}", args);
+    }
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn zscore_is_a_bulk_string_for_resp2_and_a_double_for_resp3() {
+    let server = TestServer::start().await.unwrap();
+    let mut client = server.client().await.unwrap();
+    client.command(&["ZADD", "zset", "1.5", "a", "-2", "b"]).await.unwrap();
+    client.command(&["SET", "string", "value"]).await.unwrap();
+
+    assert_eq!(client.command(&["ZSCORE", "zset", "a"]).await.unwrap(), RespValue::BulkString(b"1.5".to_vec()));
+    assert_eq!(client.command(&["ZSCORE", "zset", "missing"]).await.unwrap(), RespValue::NullBulk);
+    assert_eq!(client.command(&["ZSCORE", "nokey", "a"]).await.unwrap(), RespValue::NullBulk);
+    let RespValue::Error(message) = client.command(&["ZSCORE", "string", "a"]).await.unwrap() else {
+        panic!("ZSCORE on a string was accepted");
+    };
+    assert!(message.starts_with("WRONGTYPE"), "{}", message);
+
+    assert!(matches!(client.command(&["HELLO", "3"]).await.unwrap(), RespValue::Map(_)));
+    assert_eq!(client.command(&["ZSCORE", "zset", "b"]).await.unwrap(), RespValue::Double(-2.0));
+    assert_eq!(client.command(&["ZSCORE", "zset", "missing"]).await.unwrap(), RespValue::NullBulk);
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn zincrby_adds_to_the_score_and_zadd_incr_is_counted_as_deprecated() {
+    let server = TestServer::start().await.unwrap();
+    let mut client = server.client().await.unwrap();
+
+    // 없는 멤버는 0점에서 시작함
+    assert_eq!(client.command(&["ZINCRBY", "zset", "1.5", "a"]).await.unwrap(), bulk("1.5"));
+    assert_eq!(client.command(&["ZINCRBY", "zset", "-4", "a"]).await.unwrap(), bulk("-2.5"));
+    assert_eq!(client.command(&["ZINCRBY", "zset", "x", "a"]).await.unwrap(), error("ERR value is not a valid float"));
+    assert_eq!(info_field(&mut client, "stats", "total_deprecated_calls").await, Some("0".into()));
+
+    // 옛 형식인 ZADD INCR도 같은 결과를 내지만 폐기 예정 호출로 셈
+    assert_eq!(client.command(&["ZADD", "zset", "INCR", "2", "a"]).await.unwrap(), bulk("-0.5"));
+    assert_eq!(
+        client.command(&["ZADD", "zset", "INCR", "1", "a", "1", "b"]).await.unwrap(),
+        error("ERR INCR option supports a single increment-element pair")
+    );
+    assert_eq!(client.command(&["ZSCORE", "zset", "a"]).await.unwrap(), bulk("-0.5"));
+    assert_eq!(info_field(&mut client, "stats", "total_deprecated_calls").await, Some("1".into()));
+    assert_eq!(info_field(&mut client, "stats", "deprecated_calls_zadd").await, Some("1".into()));
+
+    // 무한대끼리 더해 NaN이 되면 점수를 바꾸지 않음
+    assert_eq!(client.command(&["ZINCRBY", "zset", "inf", "b"]).await.unwrap(), bulk("inf"));
+    assert_eq!(
+        client.command(&["ZINCRBY", "zset", "-inf", "b"]).await.unwrap(),
+        error("ERR resulting score is not a number (NaN)")
+    );
+    assert_eq!(client.command(&["ZSCORE", "zset", "b"]).await.unwrap(), bulk("inf"));
+
+    server.shutdown().await.unwrap();
+}
@@ -0,0 +1,198 @@
+use redis_starter_rust::test_support::{bulk, ok, TestServer};
+use redis_starter_rust::{Client, RespValue};
+
+fn range(start: i64, end: i64) -> RespValue {
+    RespValue::Array(vec![RespValue::Integer(start), RespValue::Integer(end)])
+}
+
+// RESP2에서는 맵이 키와 값을 번갈아 담은 배열로 옴
+fn idx_reply(matches: Vec<RespValue>, len: i64) -> RespValue {
+    RespValue::Array(vec![bulk("matches"), RespValue::Array(matches), bulk("len"), RespValue::Integer(len)])
+}
+
+async fn lcs(client: &mut Client, options: &[&str]) -> RespValue {
+    let args: Vec<&str> = ["LCS", "key1", "key2"].iter().chain(options).copied().collect();
+    client.command(&args).await.unwrap()
+}
+
+#[tokio::test]
+async fn lcs_reports_the_sequence_its_length_and_match_ranges() {
+    let server = TestServer::start().await.unwrap();
+    let mut client = server.client().await.unwrap();
+    client.command(&["SET", "key1", "ohmytext"]).await.unwrap();
+    client.command(&["SET", "key2", "mynewtext"]).await.unwrap();
+
+    assert_eq!(lcs(&mut client, &[]).await, bulk("mytext"));
+    assert_eq!(lcs(&mut client, &["LEN"]).await, RespValue::Integer(6));
+    // 구간은 문자열 뒤쪽부터 나옴
+    let text = RespValue::Array(vec![range(4, 7), range(5, 8)]);
+    let my = RespValue::Array(vec![range(2, 3), range(0, 1)]);
+    assert_eq!(lcs(&mut client, &["IDX"]).await, idx_reply(vec![text, my], 6));
+    let text_with_len = RespValue::Array(vec![range(4, 7), range(5, 8), RespValue::Integer(4)]);
+    assert_eq!(lcs(&mut client, &["IDX", "MINMATCHLEN", "4", "WITHMATCHLEN"]).await, idx_reply(vec![text_with_len], 6));
+
+    // 없는 키는 빈 문자열
+    assert_eq!(client.command(&["LCS", "key1", "missing"]).await.unwrap(), bulk(""));
+    assert_eq!(client.command(&["LCS", "key1", "missing", "IDX"]).await.unwrap(), idx_reply(vec![], 0));
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn lcs_rejects_bad_options_and_non_string_keys() {
+    let server = TestServer::start().await.unwrap();
+    let mut client = server.client().await.unwrap();
+    client.command(&["SET", "key1", "a"]).await.unwrap();
+    client.command(&["ZADD", "key2", "1", "a"]).await.unwrap();
+
+    assert_eq!(
+        lcs(&mut client, &[]).await,
+        RespValue::Error("ERR The specified keys must contain string values".into())
+    );
+    for (options, expected) in [
+        (&["LEN", "IDX"][..], "ERR If you want both the length and indexes, please just use IDX."),
+        (&["IDX", "MINMATCHLEN"], "ERR syntax error"),
+        (&["IDX", "MINMATCHLEN", "x"], "ERR value is not an integer or out of range"),
+        (&["NOSUCHOPTION"], "ERR syntax error"),
+    ] {
+        assert_eq!(lcs(&mut client, options).await, RespValue::Error(expected.into()), "{:?}", options);
+    }
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn incr_counts_from_zero_and_keeps_the_ttl() {
+    let server = TestServer::start().await.unwrap();
+    let mut client = server.client().await.unwrap();
+
+    assert_eq!(client.command(&["INCR", "counter"]).await.unwrap(), RespValue::Integer(1));
+    assert_eq!(client.command(&["INCR", "counter"]).await.unwrap(), RespValue::Integer(2));
+    assert_eq!(client.command(&["GET", "counter"]).await.unwrap(), bulk("2"));
+    assert_eq!(client.command(&["OBJECT", "ENCODING", "counter"]).await.unwrap(), bulk("int"));
+
+    client.command(&["SET", "volatile", "-1", "EX", "100"]).await.unwrap();
+    assert_eq!(client.command(&["INCR", "volatile"]).await.unwrap(), RespValue::Integer(0));
+    assert!(matches!(client.command(&["TTL", "volatile"]).await.unwrap(), RespValue::Integer(ttl) if ttl > 90));
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn incr_rejects_non_integers_and_overflow() {
+    let server = TestServer::start().await.unwrap();
+    let mut client = server.client().await.unwrap();
+    client.command(&["SET", "text", "abc"]).await.unwrap();
+    client.command(&["SET", "padded", "007"]).await.unwrap();
+    client.command(&["SET", "max", &i64::MAX.to_string()]).await.unwrap();
+    client.command(&["HSET", "hash", "field", "1"]).await.unwrap();
+
+    for key in ["text", "padded"] {
+        assert_eq!(
+            client.command(&["INCR", key]).await.unwrap(),
+            RespValue::Error("ERR value is not an integer or out of range".into())
+        );
+    }
+    assert_eq!(
+        client.command(&["INCR", "max"]).await.unwrap(),
+        RespValue::Error("ERR increment or decrement would overflow".into())
+    );
+    assert_eq!(client.command(&["GET", "max"]).await.unwrap(), bulk(&i64::MAX.to_string()));
+    let RespValue::Error(message) = client.command(&["INCR", "hash"]).await.unwrap() else {
+        panic!("INCR on a hash was accepted");
+    };
+    assert!(message.starts_with("WRONGTYPE"), "{}", message);
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn set_rejects_non_positive_and_overflowing_expire_times() {
+    let server = TestServer::start().await.unwrap();
+    let mut client = server.client().await.unwrap();
+
+    let max = i64::MAX.to_string();
+    for (option, value) in [("EX", "0"), ("PX", "0"), ("PX", "-5"), ("EX", "-1"), ("EXAT", "-1"), ("EX", max.as_str()), ("PX", max.as_str())] {
+        assert_eq!(
+            client.command(&["SET", "key", "value", option, value]).await.unwrap(),
+            RespValue::Error("ERR invalid expire time in 'set' command".into()),
+            "SET {} {}",
+            option,
+            value
+        );
+    }
+    assert_eq!(client.command(&["EXISTS", "key"]).await.unwrap(), RespValue::Integer(0));
+    assert_eq!(
+        client.command(&["SET", "key", "value", "PX", "soon"]).await.unwrap(),
+        RespValue::Error("ERR value is not an integer or out of range".into())
+    );
+    assert_eq!(client.command(&["SET", "key", "value", "PX", "100000"]).await.unwrap(), ok());
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn set_takes_absolute_expire_times_and_only_one_expire_option() {
+    let server = TestServer::start().await.unwrap();
+    let mut client = server.client().await.unwrap();
+    let at_ms = (std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_millis() + 100_000) as i64;
+
+    assert_eq!(client.command(&["SET", "key", "value", "PXAT", &at_ms.to_string()]).await.unwrap(), ok());
+    assert_eq!(client.command(&["PEXPIRETIME", "key"]).await.unwrap(), RespValue::Integer(at_ms));
+    let at_seconds = at_ms / 1000;
+    assert_eq!(client.command(&["SET", "key", "value", "EXAT", &at_seconds.to_string()]).await.unwrap(), ok());
+    assert_eq!(client.command(&["EXPIRETIME", "key"]).await.unwrap(), RespValue::Integer(at_seconds));
+    assert_eq!(
+        client.command(&["SET", "key", "value", "EX", "10", "PX", "100"]).await.unwrap(),
+        RespValue::Error("ERR syntax error".into())
+    );
+    assert_eq!(
+        client.command(&["SET", "key", "value", "EXAT", "0"]).await.unwrap(),
+        RespValue::Error("ERR invalid expire time in 'set' command".into())
+    );
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn getex_reads_the_value_and_changes_or_clears_its_ttl() {
+    let server = TestServer::start().await.unwrap();
+    let mut client = server.client().await.unwrap();
+    client.command(&["SET", "key", "value"]).await.unwrap();
+
+    // 옵션이 없으면 GET과 같고, 없는 키는 nil
+    assert_eq!(client.command(&["GETEX", "key"]).await.unwrap(), bulk("value"));
+    assert_eq!(client.command(&["TTL", "key"]).await.unwrap(), RespValue::Integer(-1));
+    assert_eq!(client.command(&["GETEX", "missing", "EX", "10"]).await.unwrap(), RespValue::NullBulk);
+    assert_eq!(client.command(&["EXISTS", "missing"]).await.unwrap(), RespValue::Integer(0));
+
+    assert_eq!(client.command(&["GETEX", "key", "EX", "100"]).await.unwrap(), bulk("value"));
+    assert!(matches!(client.command(&["TTL", "key"]).await.unwrap(), RespValue::Integer(ttl) if (99..=100).contains(&ttl)));
+    assert_eq!(client.command(&["GETEX", "key", "PERSIST"]).await.unwrap(), bulk("value"));
+    assert_eq!(client.command(&["TTL", "key"]).await.unwrap(), RespValue::Integer(-1));
+    assert_eq!(client.command(&["GETEX", "key", "PXAT", "4102444800000"]).await.unwrap(), bulk("value"));
+    assert_eq!(client.command(&["PEXPIRETIME", "key"]).await.unwrap(), RespValue::Integer(4102444800000));
+    // 이미 지난 시각이면 값을 돌려주고 키를 지움
+    assert_eq!(client.command(&["GETEX", "key", "EXAT", "1"]).await.unwrap(), bulk("value"));
+    assert_eq!(client.command(&["EXISTS", "key"]).await.unwrap(), RespValue::Integer(0));
+
+    client.command(&["SET", "key", "value"]).await.unwrap();
+    for options in [&["EX", "10", "PERSIST"][..], &["PERSIST", "EX"], &["KEEPTTL"], &["EX"]] {
+        let mut args = vec!["GETEX", "key"];
+        args.extend_from_slice(options);
+        assert!(matches!(client.command(&args).await.unwrap(), RespValue::Error(_)), "{:?}", args);
+    }
+    for amount in ["0", "-5"] {
+        assert_eq!(
+            client.command(&["GETEX", "key", "PX", amount]).await.unwrap(),
+            RespValue::Error("ERR invalid expire time in 'getex' command".into())
+        );
+    }
+    client.command(&["RPUSH", "list", "a"]).await.unwrap();
+    assert_eq!(
+        client.command(&["GETEX", "list", "PERSIST"]).await.unwrap(),
+        RespValue::Error("WRONGTYPE Operation against a key holding the wrong kind of value".into())
+    );
+
+    server.shutdown().await.unwrap();
+}
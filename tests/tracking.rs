@@ -0,0 +1,142 @@
+use redis_starter_rust::test_support::{bulk, error, ok, TestServer};
+use redis_starter_rust::{Client, RespValue};
+
+fn invalidate(keys: &[&str]) -> RespValue {
+    RespValue::Push(vec![bulk("invalidate"), RespValue::Array(keys.iter().map(|key| bulk(key)).collect())])
+}
+
+async fn resp3_client(server: &TestServer) -> Client {
+    let mut client = server.client().await.unwrap();
+    assert!(matches!(client.command(&["HELLO", "3"]).await.unwrap(), RespValue::Map(_)));
+    client
+}
+
+// 무효화 메시지가 먼저 와 있었다면 PING 응답보다 앞에 읽힘
+async fn assert_no_invalidation(client: &mut Client) {
+    assert_eq!(client.command(&["PING"]).await.unwrap(), RespValue::SimpleString("PONG".into()));
+}
+
+#[tokio::test]
+async fn write_from_another_client_invalidates_a_tracked_read() {
+    let server = TestServer::start().await.unwrap();
+    let mut tracker = resp3_client(&server).await;
+    let mut writer = server.client().await.unwrap();
+
+    assert_eq!(writer.command(&["SET", "key", "1"]).await.unwrap(), ok());
+    assert_eq!(tracker.command(&["CLIENT", "TRACKING", "ON"]).await.unwrap(), ok());
+    assert_eq!(tracker.command(&["GET", "key"]).await.unwrap(), bulk("1"));
+
+    assert_eq!(writer.command(&["SET", "key", "2"]).await.unwrap(), ok());
+    assert_eq!(tracker.read_reply().await.unwrap(), invalidate(&["key"]));
+
+    // 무효화한 키는 다시 읽기 전까지 추적하지 않음
+    assert_eq!(writer.command(&["SET", "key", "3"]).await.unwrap(), ok());
+    assert_no_invalidation(&mut tracker).await;
+
+    // 읽지 않은 키를 바꿔도 알리지 않음
+    assert_eq!(writer.command(&["SET", "other", "1"]).await.unwrap(), ok());
+    assert_no_invalidation(&mut tracker).await;
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn resp2_tracking_redirects_to_the_invalidate_channel() {
+    let server = TestServer::start().await.unwrap();
+    let mut subscriber = server.client().await.unwrap();
+    let mut tracker = server.client().await.unwrap();
+    let mut writer = server.client().await.unwrap();
+
+    let RespValue::Integer(subscriber_id) = subscriber.command(&["CLIENT", "ID"]).await.unwrap() else {
+        panic!("CLIENT ID did not return an integer");
+    };
+    subscriber.command(&["SUBSCRIBE", "__redis__:invalidate"]).await.unwrap();
+    let subscriber_id = subscriber_id.to_string();
+    assert_eq!(tracker.command(&["CLIENT", "TRACKING", "ON", "REDIRECT", &subscriber_id]).await.unwrap(), ok());
+    assert_eq!(tracker.command(&["GET", "key"]).await.unwrap(), RespValue::NullBulk);
+
+    assert_eq!(writer.command(&["SET", "key", "1"]).await.unwrap(), ok());
+    assert_eq!(
+        subscriber.read_reply().await.unwrap(),
+        RespValue::Array(vec![bulk("message"), bulk("__redis__:invalidate"), RespValue::Array(vec![bulk("key")])])
+    );
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn bcast_invalidates_only_keys_under_the_prefixes() {
+    let server = TestServer::start().await.unwrap();
+    let mut tracker = resp3_client(&server).await;
+    let mut writer = server.client().await.unwrap();
+
+    assert_eq!(tracker.command(&["CLIENT", "TRACKING", "ON", "PREFIX", "user:"]).await.unwrap(), error("ERR PREFIX option requires BCAST mode to be enabled"));
+    assert_eq!(tracker.command(&["CLIENT", "TRACKING", "ON", "BCAST", "PREFIX", "user:", "PREFIX", "session:"]).await.unwrap(), ok());
+
+    // BCAST는 읽지 않은 키도 접두사만 맞으면 알림
+    assert_eq!(writer.command(&["SET", "user:1", "a"]).await.unwrap(), ok());
+    assert_eq!(tracker.read_reply().await.unwrap(), invalidate(&["user:1"]));
+    assert_eq!(writer.command(&["SET", "session:1", "a"]).await.unwrap(), ok());
+    assert_eq!(tracker.read_reply().await.unwrap(), invalidate(&["session:1"]));
+
+    assert_eq!(writer.command(&["SET", "order:1", "a"]).await.unwrap(), ok());
+    assert_no_invalidation(&mut tracker).await;
+
+    // 읽기는 추적하지 않으므로 접두사 밖의 키는 읽은 뒤 바뀌어도 알리지 않음
+    assert_eq!(tracker.command(&["GET", "order:1"]).await.unwrap(), bulk("a"));
+    assert_eq!(writer.command(&["SET", "order:1", "b"]).await.unwrap(), ok());
+    assert_no_invalidation(&mut tracker).await;
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn optin_tracks_only_reads_after_caching_yes() {
+    let server = TestServer::start().await.unwrap();
+    let mut tracker = resp3_client(&server).await;
+    let mut writer = server.client().await.unwrap();
+
+    assert_eq!(tracker.command(&["CLIENT", "CACHING", "yes"]).await.unwrap(), error("ERR CLIENT CACHING can be called only when the client is in tracking mode with OPTIN or OPTOUT mode enabled"));
+    assert_eq!(tracker.command(&["CLIENT", "TRACKING", "ON", "OPTIN", "OPTOUT"]).await.unwrap(), error("ERR You can't use both OPTIN and OPTOUT."));
+    assert_eq!(tracker.command(&["CLIENT", "TRACKING", "ON", "BCAST", "OPTIN"]).await.unwrap(), error("ERR OPTIN and OPTOUT are not compatible with BCAST."));
+    assert_eq!(tracker.command(&["CLIENT", "TRACKING", "ON", "OPTIN"]).await.unwrap(), ok());
+    assert_eq!(tracker.command(&["CLIENT", "CACHING", "no"]).await.unwrap(), error("ERR CLIENT CACHING NO is only valid when tracking is enabled in OPTOUT mode."));
+
+    // CLIENT CACHING yes 없이 읽은 키는 추적하지 않음
+    assert_eq!(tracker.command(&["GET", "plain"]).await.unwrap(), RespValue::NullBulk);
+    assert_eq!(writer.command(&["SET", "plain", "1"]).await.unwrap(), ok());
+    assert_no_invalidation(&mut tracker).await;
+
+    // CLIENT CACHING yes는 바로 다음 명령 하나에만 적용됨
+    assert_eq!(tracker.command(&["CLIENT", "CACHING", "yes"]).await.unwrap(), ok());
+    assert_eq!(tracker.command(&["GET", "cached"]).await.unwrap(), RespValue::NullBulk);
+    assert_eq!(tracker.command(&["GET", "after"]).await.unwrap(), RespValue::NullBulk);
+    assert_eq!(writer.command(&["SET", "after", "1"]).await.unwrap(), ok());
+    assert_no_invalidation(&mut tracker).await;
+    assert_eq!(writer.command(&["SET", "cached", "1"]).await.unwrap(), ok());
+    assert_eq!(tracker.read_reply().await.unwrap(), invalidate(&["cached"]));
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn optout_skips_reads_after_caching_no() {
+    let server = TestServer::start().await.unwrap();
+    let mut tracker = resp3_client(&server).await;
+    let mut writer = server.client().await.unwrap();
+
+    assert_eq!(tracker.command(&["CLIENT", "TRACKING", "ON", "OPTOUT"]).await.unwrap(), ok());
+    assert_eq!(tracker.command(&["CLIENT", "CACHING", "yes"]).await.unwrap(), error("ERR CLIENT CACHING YES is only valid when tracking is enabled in OPTIN mode."));
+
+    // CLIENT CACHING no 바로 다음에 읽은 키만 추적하지 않음
+    assert_eq!(tracker.command(&["CLIENT", "CACHING", "no"]).await.unwrap(), ok());
+    assert_eq!(tracker.command(&["GET", "skipped"]).await.unwrap(), RespValue::NullBulk);
+    assert_eq!(tracker.command(&["GET", "tracked"]).await.unwrap(), RespValue::NullBulk);
+
+    assert_eq!(writer.command(&["SET", "skipped", "1"]).await.unwrap(), ok());
+    assert_no_invalidation(&mut tracker).await;
+    assert_eq!(writer.command(&["SET", "tracked", "1"]).await.unwrap(), ok());
+    assert_eq!(tracker.read_reply().await.unwrap(), invalidate(&["tracked"]));
+
+    server.shutdown().await.unwrap();
+}
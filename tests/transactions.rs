@@ -0,0 +1,141 @@
+use redis_starter_rust::test_support::{bulk, error, ok, TestServer};
+use redis_starter_rust::RespValue;
+
+fn queued() -> RespValue {
+    RespValue::SimpleString("QUEUED".into())
+}
+
+#[tokio::test]
+async fn exec_runs_queued_commands_in_order() {
+    let server = TestServer::start().await.unwrap();
+    let mut client = server.client().await.unwrap();
+
+    assert_eq!(client.command(&["EXEC"]).await.unwrap(), error("ERR EXEC without MULTI"));
+    assert_eq!(client.command(&["MULTI"]).await.unwrap(), ok());
+    assert_eq!(client.command(&["MULTI"]).await.unwrap(), error("ERR MULTI calls can not be nested"));
+    assert_eq!(client.command(&["SET", "counter", "1"]).await.unwrap(), queued());
+    assert_eq!(client.command(&["INCR", "counter"]).await.unwrap(), queued());
+    assert_eq!(client.command(&["GET", "counter"]).await.unwrap(), queued());
+
+    // 실행 중 에러는 그 명령의 응답에만 남고 나머지는 그대로 실행함
+    assert_eq!(client.command(&["INCR", "counter"]).await.unwrap(), queued());
+    assert_eq!(client.command(&["LPUSH", "counter", "x"]).await.unwrap(), queued());
+    assert_eq!(
+        client.command(&["EXEC"]).await.unwrap(),
+        RespValue::Array(vec![
+            ok(),
+            RespValue::Integer(2),
+            bulk("2"),
+            RespValue::Integer(3),
+            error("WRONGTYPE Operation against a key holding the wrong kind of value"),
+        ])
+    );
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn discard_drops_the_queue() {
+    let server = TestServer::start().await.unwrap();
+    let mut client = server.client().await.unwrap();
+
+    assert_eq!(client.command(&["DISCARD"]).await.unwrap(), error("ERR DISCARD without MULTI"));
+    assert_eq!(client.command(&["MULTI"]).await.unwrap(), ok());
+    assert_eq!(client.command(&["SET", "key", "value"]).await.unwrap(), queued());
+    assert_eq!(client.command(&["DISCARD"]).await.unwrap(), ok());
+    assert_eq!(client.command(&["GET", "key"]).await.unwrap(), RespValue::NullBulk);
+    assert_eq!(client.command(&["EXEC"]).await.unwrap(), error("ERR EXEC without MULTI"));
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn queue_time_error_aborts_exec() {
+    let server = TestServer::start().await.unwrap();
+    let mut client = server.client().await.unwrap();
+
+    assert_eq!(client.command(&["MULTI"]).await.unwrap(), ok());
+    assert_eq!(client.command(&["SET", "key", "value"]).await.unwrap(), queued());
+    assert!(matches!(client.command(&["SET"]).await.unwrap(), RespValue::Error(_)));
+    assert!(matches!(client.command(&["NOSUCHCOMMAND"]).await.unwrap(), RespValue::Error(_)));
+    assert_eq!(client.command(&["EXEC"]).await.unwrap(), error("EXECABORT Transaction discarded because of previous errors."));
+
+    // 버린 트랜잭션의 명령은 실행되지 않고 연결은 MULTI 밖으로 돌아옴
+    assert_eq!(client.command(&["GET", "key"]).await.unwrap(), RespValue::NullBulk);
+    assert_eq!(client.command(&["EXEC"]).await.unwrap(), error("ERR EXEC without MULTI"));
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn watch_aborts_exec_after_another_client_changes_the_key() {
+    let server = TestServer::start().await.unwrap();
+    let mut client = server.client().await.unwrap();
+    let mut other = server.client().await.unwrap();
+
+    assert_eq!(client.command(&["SET", "balance", "10"]).await.unwrap(), ok());
+    assert_eq!(client.command(&["WATCH", "balance"]).await.unwrap(), ok());
+    assert_eq!(other.command(&["INCR", "balance"]).await.unwrap(), RespValue::Integer(11));
+    assert_eq!(client.command(&["MULTI"]).await.unwrap(), ok());
+    assert_eq!(client.command(&["SET", "balance", "0"]).await.unwrap(), queued());
+    assert_eq!(client.command(&["EXEC"]).await.unwrap(), RespValue::NullArray);
+    assert_eq!(client.command(&["GET", "balance"]).await.unwrap(), bulk("11"));
+
+    // EXEC가 WATCH를 풀었으므로 다음 트랜잭션은 실행됨
+    assert_eq!(other.command(&["INCR", "balance"]).await.unwrap(), RespValue::Integer(12));
+    assert_eq!(client.command(&["MULTI"]).await.unwrap(), ok());
+    assert_eq!(client.command(&["SET", "balance", "0"]).await.unwrap(), queued());
+    assert_eq!(client.command(&["EXEC"]).await.unwrap(), RespValue::Array(vec![ok()]));
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn watch_keeps_exec_when_nothing_changed() {
+    let server = TestServer::start().await.unwrap();
+    let mut client = server.client().await.unwrap();
+    let mut other = server.client().await.unwrap();
+
+    // 지켜보지 않는 키의 변경이나 읽기는 트랜잭션을 막지 않음
+    assert_eq!(client.command(&["WATCH", "watched"]).await.unwrap(), ok());
+    assert_eq!(other.command(&["SET", "unrelated", "1"]).await.unwrap(), ok());
+    assert_eq!(other.command(&["GET", "watched"]).await.unwrap(), RespValue::NullBulk);
+    assert_eq!(client.command(&["MULTI"]).await.unwrap(), ok());
+    assert_eq!(client.command(&["WATCH", "other"]).await.unwrap(), error("ERR WATCH inside MULTI is not allowed"));
+    assert_eq!(client.command(&["SET", "watched", "1"]).await.unwrap(), queued());
+    assert_eq!(client.command(&["EXEC"]).await.unwrap(), RespValue::Array(vec![ok()]));
+
+    // UNWATCH 뒤의 변경은 보지 않음
+    assert_eq!(client.command(&["WATCH", "watched"]).await.unwrap(), ok());
+    assert_eq!(client.command(&["UNWATCH"]).await.unwrap(), ok());
+    assert_eq!(other.command(&["SET", "watched", "2"]).await.unwrap(), ok());
+    assert_eq!(client.command(&["MULTI"]).await.unwrap(), ok());
+    assert_eq!(client.command(&["GET", "watched"]).await.unwrap(), queued());
+    assert_eq!(client.command(&["EXEC"]).await.unwrap(), RespValue::Array(vec![bulk("2")]));
+
+    server.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn discard_and_flushall_release_or_touch_watched_keys() {
+    let server = TestServer::start().await.unwrap();
+    let mut client = server.client().await.unwrap();
+    let mut other = server.client().await.unwrap();
+
+    // DISCARD도 WATCH를 풂
+    assert_eq!(client.command(&["WATCH", "key"]).await.unwrap(), ok());
+    assert_eq!(client.command(&["MULTI"]).await.unwrap(), ok());
+    assert_eq!(client.command(&["DISCARD"]).await.unwrap(), ok());
+    assert_eq!(other.command(&["SET", "key", "1"]).await.unwrap(), ok());
+    assert_eq!(client.command(&["MULTI"]).await.unwrap(), ok());
+    assert_eq!(client.command(&["GET", "key"]).await.unwrap(), queued());
+    assert_eq!(client.command(&["EXEC"]).await.unwrap(), RespValue::Array(vec![bulk("1")]));
+
+    assert_eq!(client.command(&["WATCH", "key"]).await.unwrap(), ok());
+    assert_eq!(other.command(&["FLUSHALL"]).await.unwrap(), ok());
+    assert_eq!(client.command(&["MULTI"]).await.unwrap(), ok());
+    assert_eq!(client.command(&["SET", "key", "2"]).await.unwrap(), queued());
+    assert_eq!(client.command(&["EXEC"]).await.unwrap(), RespValue::NullArray);
+
+    server.shutdown().await.unwrap();
+}